@@ -0,0 +1,60 @@
+use std::{ops::Deref, str::FromStr};
+
+use anyhow::{anyhow, Result};
+
+use super::Block;
+
+/// PKCS7 pads with the amount of padding bytes, so a block can't hold more bytes than fit in a single byte value.
+#[derive(Clone, Copy, Debug)]
+pub struct BlockSize(u8);
+
+pub trait BlockSizeTrait {
+    fn block_size(&self) -> BlockSize;
+}
+
+impl From<u8> for BlockSize {
+    fn from(data: u8) -> Self {
+        match data {
+            0 => unreachable!("{}", format!("Invalid block size: {}", data)),
+            _ => Self(data),
+        }
+    }
+}
+
+impl From<usize> for BlockSize {
+    fn from(data: usize) -> Self {
+        match u8::try_from(data) {
+            Ok(data) => data.into(),
+            Err(_) => unreachable!("{}", format!("Invalid block size: {}", data)),
+        }
+    }
+}
+
+impl FromStr for BlockSize {
+    type Err = anyhow::Error;
+
+    fn from_str(data: &str) -> Result<Self> {
+        match data.parse::<u8>() {
+            Ok(block_size) if block_size > 0 => Ok(Self(block_size)),
+            _ => Err(anyhow!(
+                "`{}` is an invalid block size. Expected a size between 1 and {}",
+                data,
+                u8::MAX
+            )),
+        }
+    }
+}
+
+impl From<&Block> for BlockSize {
+    fn from(block: &Block) -> Self {
+        block.len().into()
+    }
+}
+
+impl Deref for BlockSize {
+    type Target = u8;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
@@ -1,4 +1,5 @@
-pub(super) mod block_size;
+pub mod block_size;
+pub mod padding_scheme;
 
 use std::{
     fmt::Display,
@@ -7,53 +8,47 @@ use std::{
 
 use serde::{Deserialize, Serialize};
 
-use self::block_size::{BlockSize, BlockSizeTrait};
+use self::{
+    block_size::{BlockSize, BlockSizeTrait},
+    padding_scheme::PaddingScheme,
+};
 
 #[derive(Debug, Clone, Serialize, Deserialize, Hash, PartialEq, Eq)]
-pub(super) enum Block {
+#[serde(from = "SerializableBlock")]
+pub struct Block(Vec<u8>);
+
+/// Blocks used to be stored in the cache as an enum of fixed size arrays.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum SerializableBlock {
+    Bytes(Vec<u8>),
+    Legacy(LegacyBlock),
+}
+
+#[derive(Deserialize)]
+enum LegacyBlock {
     Eight([u8; 8]),
     Sixteen([u8; 16]),
 }
 
 impl Block {
-    pub(super) fn new(block_size: &BlockSize) -> Self {
-        match block_size {
-            BlockSize::Eight => Block::Eight([0; 8]),
-            BlockSize::Sixteen => Block::Sixteen([0; 16]),
-        }
+    pub fn new(block_size: &BlockSize) -> Self {
+        Block(vec![0; **block_size as usize])
     }
 
     fn new_incremental_padding(block_size: &BlockSize) -> Self {
-        match block_size {
-            BlockSize::Eight => Block::Eight([8, 7, 6, 5, 4, 3, 2, 1]),
-            BlockSize::Sixteen => {
-                Block::Sixteen([16, 15, 14, 13, 12, 11, 10, 9, 8, 7, 6, 5, 4, 3, 2, 1])
-            }
-        }
+        Block((1..=**block_size).rev().collect())
     }
 
-    pub(super) fn set_byte(&mut self, index: usize, value: u8) -> &mut Self {
-        match self {
-            Block::Eight(data) => {
-                if index < 8 {
-                    data[index] += value;
-                } else {
-                    panic!(
-                        "Tried to increment byte at index {} of 8-byte block",
-                        index + 1
-                    );
-                }
-            }
-            Block::Sixteen(data) => {
-                if index < 16 {
-                    data[index] = value;
-                } else {
-                    panic!(
-                        "Tried to increment byte at index {} of 16-byte block",
-                        index + 1
-                    );
-                }
-            }
+    pub fn set_byte(&mut self, index: usize, value: u8) -> &mut Self {
+        if index < self.len() {
+            self[index] = value;
+        } else {
+            panic!(
+                "Tried to increment byte at index {} of {}-byte block",
+                index + 1,
+                self.len()
+            );
         }
 
         self
@@ -61,22 +56,24 @@ impl Block {
 
     /// Clone this block and adjusts bytes to produce the correct padding
     /// Due to xor's working, this cannot be done as a simple +1 in byte value. We must use xor's commutative property.
-    pub(super) fn to_adjusted_for_padding(&self, pad_size: u8) -> Self {
+    /// The bytes of this block must be solved for PKCS7, and are adjusted to the padding of `padding_scheme`.
+    pub fn to_adjusted_for_padding(&self, pad_size: u8, padding_scheme: &PaddingScheme) -> Self {
         let mut adjusted_block = self.clone();
 
         for i in self.len() - (pad_size as usize)..self.len() {
             adjusted_block[i] ^= (self.len() - i) as u8; // get actual padding out
-            adjusted_block[i] ^= pad_size; // put WIP padding in
+            adjusted_block[i] ^= padding_scheme.padding_byte(self.len(), i, pad_size);
+            // put WIP padding in
         }
 
         adjusted_block
     }
 
-    pub(super) fn to_hex(&self) -> String {
+    pub fn to_hex(&self) -> String {
         hex::encode(&**self)
     }
 
-    pub(super) fn to_ascii(&self) -> String {
+    pub fn to_ascii(&self) -> String {
         self.iter()
             .map(|byte_value| *byte_value as char)
             .map(|c| {
@@ -89,7 +86,7 @@ impl Block {
             .collect::<String>()
     }
 
-    pub(super) fn to_intermediate(&self) -> Block {
+    pub fn to_intermediate(&self) -> Block {
         self ^ &Block::new_incremental_padding(&self.block_size())
     }
 }
@@ -116,7 +113,6 @@ impl BitXor for &Block {
             .deref()
             .iter()
             .zip(rhs.deref().iter())
-            .into_iter()
             .map(|(l, r)| l ^ r)
             .collect();
 
@@ -126,18 +122,18 @@ impl BitXor for &Block {
 
 impl From<&[u8]> for Block {
     fn from(chunk_data: &[u8]) -> Self {
-        let block_size = chunk_data.len().into();
-        match block_size {
-            BlockSize::Eight => Block::Eight(
-                chunk_data
-                    .try_into()
-                    .unwrap_or_else(|_| panic!("Not enough data to fill block of {}", *block_size)),
-            ),
-            BlockSize::Sixteen => Block::Sixteen(
-                chunk_data
-                    .try_into()
-                    .unwrap_or_else(|_| panic!("Not enough data to fill block of {}", *block_size)),
-            ),
+        // validates the size
+        let _ = BlockSize::from(chunk_data.len());
+        Block(chunk_data.to_vec())
+    }
+}
+
+impl From<SerializableBlock> for Block {
+    fn from(block: SerializableBlock) -> Self {
+        match block {
+            SerializableBlock::Bytes(data) => Block(data),
+            SerializableBlock::Legacy(LegacyBlock::Eight(data)) => Block(data.to_vec()),
+            SerializableBlock::Legacy(LegacyBlock::Sixteen(data)) => Block(data.to_vec()),
         }
     }
 }
@@ -146,19 +142,13 @@ impl Deref for Block {
     type Target = [u8];
 
     fn deref(&self) -> &Self::Target {
-        match self {
-            Block::Eight(data) => data,
-            Block::Sixteen(data) => data,
-        }
+        &self.0
     }
 }
 
 impl DerefMut for Block {
     fn deref_mut(&mut self) -> &mut Self::Target {
-        match self {
-            Block::Eight(data) => data,
-            Block::Sixteen(data) => data,
-        }
+        &mut self.0
     }
 }
 
@@ -0,0 +1,113 @@
+use std::{fmt::Display, str::FromStr};
+
+use anyhow::{anyhow, Result};
+use itertools::Itertools;
+
+/// Padding which the oracle validates.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PaddingScheme {
+    // every padding byte holds the amount of padding bytes
+    #[default]
+    Pkcs7,
+    // zeroes, followed by the amount of padding bytes
+    AnsiX923,
+    // a 0x80 marker, followed by zeroes
+    Iso7816,
+}
+
+impl PaddingScheme {
+    fn variants() -> &'static [Self] {
+        &[Self::Pkcs7, Self::AnsiX923, Self::Iso7816]
+    }
+
+    /// Value of the byte at `idx` of a block which ends in `pad_size` bytes of padding
+    pub fn padding_byte(&self, block_size: usize, idx: usize, pad_size: u8) -> u8 {
+        match self {
+            PaddingScheme::Pkcs7 => pad_size,
+            PaddingScheme::AnsiX923 => {
+                if idx == block_size - 1 {
+                    pad_size
+                } else {
+                    0
+                }
+            }
+            PaddingScheme::Iso7816 => {
+                if idx == block_size - pad_size as usize {
+                    0x80
+                } else {
+                    0
+                }
+            }
+        }
+    }
+
+    /// Append padding to `data`. Data which fills its last block gets a whole block of padding
+    pub fn pad(&self, data: &[u8], block_size: usize) -> Vec<u8> {
+        let padding_size = block_size - data.len() % block_size;
+
+        data.iter()
+            .cloned()
+            .chain(
+                (block_size - padding_size..block_size)
+                    .map(|idx| self.padding_byte(block_size, idx, padding_size as u8)),
+            )
+            .collect()
+    }
+
+    /// `data` without its padding, if the padding is valid
+    pub fn unpad<'a>(&self, data: &'a [u8], block_size: usize) -> Option<&'a [u8]> {
+        let padding_size = match self {
+            PaddingScheme::Pkcs7 | PaddingScheme::AnsiX923 => *data.last()? as usize,
+            PaddingScheme::Iso7816 => {
+                data.len() - data.iter().rposition(|byte_value| *byte_value != 0)?
+            }
+        };
+        if padding_size == 0 || padding_size > block_size || padding_size > data.len() {
+            return None;
+        }
+
+        let unpadded_size = data.len() - padding_size;
+        (unpadded_size..data.len())
+            .all(|idx| {
+                data[idx] == self.padding_byte(block_size, idx % block_size, padding_size as u8)
+            })
+            .then(|| &data[..unpadded_size])
+    }
+}
+
+impl Display for PaddingScheme {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PaddingScheme::Pkcs7 => write!(f, "pkcs7"),
+            PaddingScheme::AnsiX923 => write!(f, "x923"),
+            PaddingScheme::Iso7816 => write!(f, "iso7816"),
+        }
+    }
+}
+
+impl FromStr for PaddingScheme {
+    type Err = anyhow::Error;
+
+    fn from_str(input: &str) -> Result<Self> {
+        match input.to_lowercase().replace(['-', '_', ' '], "").as_str() {
+            "pkcs7" | "pkcs5" => Ok(Self::Pkcs7),
+            "x923" | "ansix923" => Ok(Self::AnsiX923),
+            "iso7816" | "iso78164" => Ok(Self::Iso7816),
+            // the padding bytes are random, so only the last byte can be validated. That leaks the last byte of each block, but nothing more
+            "iso10126" => Err(anyhow!(
+                "ISO 10126 padding can't be attacked. Only its last byte is validated, which reveals nothing about the other bytes of a block"
+            )),
+            "zero" | "zeroes" | "zeros" => Err(anyhow!(
+                "Zero padding can't be attacked. It's indistinguishable from plain text ending in zeroes, so it can't be invalid"
+            )),
+            _ => Err(anyhow!(
+                "`{}` is not a padding scheme. Expected one of: [{}]",
+                input,
+                Self::variants()
+                    .iter()
+                    .map(|variant| variant.to_string())
+                    .join(", ")
+            )),
+        }
+    }
+}
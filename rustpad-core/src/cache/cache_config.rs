@@ -0,0 +1,49 @@
+use getset::Getters;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    calibrator::calibration_response::{
+        CalibrationResponse, SerializableCalibrationResponse, SerializableCalibrationResponseV2,
+    },
+    oracle::oracle_location::{OracleLocation, SerializableOracleLocation},
+};
+
+/// State which defines the validity of a cache entry.
+/// In other words, all of the properties between the current and the cache's must match to allow loading of the associated values.
+/// Part of the cache file's format. Changing it requires bumping the cache's version.
+#[derive(Serialize, Deserialize, Hash, PartialEq, Eq, Clone, Getters)]
+pub struct CacheConfig {
+    #[getset(get = "pub")]
+    oracle_location: SerializableOracleLocation,
+    calibration_response: Option<SerializableCalibrationResponse>,
+}
+
+/// Layout of cache version 2
+#[derive(Deserialize, Hash, PartialEq, Eq)]
+pub struct CacheConfigV2 {
+    oracle_location: SerializableOracleLocation,
+    calibration_response: Option<SerializableCalibrationResponseV2>,
+}
+
+impl From<CacheConfigV2> for CacheConfig {
+    fn from(config: CacheConfigV2) -> Self {
+        Self {
+            oracle_location: config.oracle_location,
+            calibration_response: config
+                .calibration_response
+                .map(SerializableCalibrationResponse::from),
+        }
+    }
+}
+
+impl CacheConfig {
+    pub fn new(
+        oracle_location: OracleLocation,
+        calibration_response: Option<CalibrationResponse>,
+    ) -> Self {
+        Self {
+            oracle_location: oracle_location.into(),
+            calibration_response: calibration_response.map(SerializableCalibrationResponse::from),
+        }
+    }
+}
@@ -0,0 +1,316 @@
+pub mod cache_config;
+
+use std::{
+    collections::HashMap,
+    fs::{self, create_dir_all, File},
+    io::{ErrorKind, Write},
+    path::{Path, PathBuf},
+    process,
+};
+
+use anyhow::{anyhow, Context, Result};
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+
+use crate::{block::Block, logging::LOG_TARGET};
+
+use self::cache_config::{CacheConfig, CacheConfigV2};
+
+const CACHE_FILE_NAME: &str = "cache.bin";
+// a corrupted cache is moved aside, instead of being overwritten, so it can still be inspected
+const CORRUPTED_CACHE_SUFFIX: &str = "corrupt";
+// precedes the version and the MessagePack data. Files of rustpad versions before the header start with the data right away
+const CACHE_MAGIC: &[u8] = b"RUSTPAD-CACHE";
+/// Format of the cached data. Bump it when `CacheData` changes, including anything serialized in `CacheConfig`, and migrate older caches in `migrate`
+const CACHE_VERSION: u16 = 3;
+
+type CacheData = HashMap<CacheConfig, HashMap<(Block, Block), CachedBlock>>;
+// of versions 0 and 1
+type CacheDataV1 = HashMap<CacheConfig, HashMap<(Block, Block), Block>>;
+type CacheDataV2 = HashMap<CacheConfigV2, HashMap<(Block, Block), CachedBlock>>;
+
+#[derive(Serialize, Deserialize)]
+struct CachedBlock {
+    forged_block_solution: Block,
+    // of the block's plain text in that of its cypher text. `None` if it has no place in a plain text, e.g. it was forged while encrypting
+    plain_text_offset: Option<usize>,
+}
+
+pub struct Cache {
+    cache_file_path: PathBuf,
+    config: CacheConfig,
+    data: CacheData,
+    // whether the oracle still agrees with the cached blocks, e.g. hasn't rotated its key since they were cached
+    verified: bool,
+}
+
+enum StoredCache {
+    Readable(CacheData),
+    // written by a newer rustpad, which changed the format
+    Newer(u16),
+}
+
+impl Cache {
+    pub fn load_from_file(config: CacheConfig) -> Result<Self> {
+        let cache_file_path = cache_file_path()?;
+
+        let file_data = match fs::read(&cache_file_path) {
+            Ok(file_data) => file_data,
+            Err(e) if e.kind() == ErrorKind::NotFound => vec![],
+            Err(e) => {
+                return Err(e).context(format!(
+                    "Cache file `{}` read failure",
+                    cache_file_path.display()
+                ))
+            }
+        };
+
+        let mut data = if file_data.is_empty() {
+            HashMap::new()
+        } else {
+            match decode(&file_data) {
+                Ok(StoredCache::Readable(data)) => data,
+                // the newer rustpad can still use it, so it's kept
+                Ok(StoredCache::Newer(version)) => {
+                    let backup_path = back_up(&cache_file_path, &format!("v{}", version))?;
+                    warn!(
+                        target: LOG_TARGET,
+                        "The cache file is of version {}, written by a newer rustpad. This one understands up to version {}. Moved it to `{}` and starting with an empty cache",
+                        version,
+                        CACHE_VERSION,
+                        backup_path.display()
+                    );
+                    HashMap::new()
+                }
+                // losing the cache only costs oracle requests, so don't fail the whole run over it
+                Err(e) => {
+                    let backup_path = back_up(&cache_file_path, CORRUPTED_CACHE_SUFFIX)?;
+                    warn!(
+                        target: LOG_TARGET,
+                        "{:#}. Moved it to `{}` and starting with an empty cache",
+                        e,
+                        backup_path.display()
+                    );
+                    HashMap::new()
+                }
+            }
+        };
+
+        // create an entry for the current config if needed
+        let _ = data.entry(config.clone()).or_insert_with(HashMap::new);
+
+        Ok(Self {
+            cache_file_path,
+            config,
+            data,
+            verified: false,
+        })
+    }
+
+    pub fn insert(
+        &mut self,
+        key: (Block, Block),
+        value: Block,
+        plain_text_offset: Option<usize>,
+    ) -> Result<()> {
+        let _ = self.data.entry(self.config.clone()).or_default().insert(
+            key,
+            CachedBlock {
+                forged_block_solution: value,
+                plain_text_offset,
+            },
+        );
+
+        self.save()
+    }
+
+    pub fn get(&self, key: &(Block, Block)) -> Option<&Block> {
+        self.data
+            .get(&self.config)
+            .and_then(|blocks_mapping| blocks_mapping.get(key))
+            .map(|cached_block| &cached_block.forged_block_solution)
+    }
+
+    /// Plain text of the blocks cached for this oracle, with their offsets. Includes those of other cypher texts, and those cached under another calibration of the oracle
+    pub fn plain_texts(&self) -> Vec<(usize, Block)> {
+        self.data
+            .iter()
+            .filter(|(config, _)| config.oracle_location() == self.config.oracle_location())
+            .flat_map(|(_, blocks_mapping)| blocks_mapping.iter())
+            .filter_map(|((preceding_block, _), cached_block)| {
+                cached_block.plain_text_offset.map(|plain_text_offset| {
+                    (
+                        plain_text_offset,
+                        &cached_block.forged_block_solution.to_intermediate() ^ preceding_block,
+                    )
+                })
+            })
+            .collect()
+    }
+
+    /// Like `get`, but the 1st hit is checked with `verify`, which tells whether the oracle still agrees with the cached block.
+    /// If it doesn't, the oracle's key was likely rotated. All blocks cached for the oracle are then dropped, instead of handing out stale decryptions
+    pub fn get_verified(
+        &mut self,
+        key: &(Block, Block),
+        verify: impl FnOnce(&Block) -> Result<bool>,
+    ) -> Result<Option<Block>> {
+        let cached_block = match self.get(key) {
+            Some(cached_block) => cached_block.clone(),
+            None => return Ok(None),
+        };
+        if self.verified {
+            return Ok(Some(cached_block));
+        }
+
+        // blocks cached from now on are checked by solving them
+        self.verified = true;
+        if verify(&cached_block)? {
+            return Ok(Some(cached_block));
+        }
+
+        let mut amount_dropped = 0;
+        self.data.retain(|config, blocks_mapping| {
+            let same_oracle = config.oracle_location() == self.config.oracle_location();
+            if same_oracle {
+                amount_dropped += blocks_mapping.len();
+            }
+            !same_oracle
+        });
+        let _ = self.data.entry(self.config.clone()).or_default();
+        self.save()?;
+        warn!(
+            target: LOG_TARGET,
+            "The cached blocks of this oracle no longer decrypt correctly, so its key was likely rotated. Dropped its {} cached block(s)",
+            amount_dropped
+        );
+
+        Ok(None)
+    }
+
+    /// Write the cache back to its file
+    fn save(&self) -> Result<()> {
+        // write to a temporary file 1st and then rename it over the cache. The rename is atomic, so a crash mid-write leaves the previous cache intact instead of corrupted data
+        let file_data = [
+            CACHE_MAGIC,
+            &CACHE_VERSION.to_be_bytes(),
+            &rmp_serde::to_vec(&self.data).context("Cache data serialization failed")?,
+        ]
+        .concat();
+        // per process, so concurrent runs don't write into each other's temporary file
+        let temp_file_path = self
+            .cache_file_path
+            .with_extension(format!("{}.tmp", process::id()));
+
+        let written = File::create(&temp_file_path)
+            .context(format!(
+                "Temporary cache file `{}` failed to open",
+                temp_file_path.display()
+            ))
+            .and_then(|mut temp_file| {
+                temp_file
+                    .write_all(&file_data)
+                    .context("Cache could not be saved")?;
+                // the data must be on disk before the rename makes it the cache
+                temp_file
+                    .sync_all()
+                    .context("Cache could not be flushed to disk")
+            })
+            .and_then(|_| {
+                fs::rename(&temp_file_path, &self.cache_file_path).context(format!(
+                    "Cache file `{}` could not be replaced",
+                    self.cache_file_path.display()
+                ))
+            });
+        if written.is_err() {
+            let _ = fs::remove_file(&temp_file_path);
+        }
+
+        written
+    }
+}
+
+fn cache_file_path() -> Result<PathBuf> {
+    let cache_file_dir = dirs::cache_dir()
+        .map(|dir| dir.join(env!("CARGO_PKG_NAME")))
+        .unwrap_or_else(|| PathBuf::from("./cache"));
+    create_dir_all(&cache_file_dir).context("Cache directory creation failed")?;
+
+    Ok(cache_file_dir.join(CACHE_FILE_NAME))
+}
+
+fn decode(file_data: &[u8]) -> Result<StoredCache> {
+    let (version, payload) = match file_data.strip_prefix(CACHE_MAGIC) {
+        Some(versioned) => {
+            let (version, payload) = versioned
+                .split_at_checked(2)
+                .context("Cache file de-serialization failed: the version is missing")?;
+            (u16::from_be_bytes([version[0], version[1]]), payload)
+        }
+        None => (0, file_data),
+    };
+
+    match version {
+        CACHE_VERSION => rmp_serde::from_read_ref(payload)
+            .map(StoredCache::Readable)
+            .context("Cache file de-serialization failed: corrupted MessagePack data"),
+        version if version > CACHE_VERSION => Ok(StoredCache::Newer(version)),
+        version => migrate(version, payload).map(StoredCache::Readable),
+    }
+}
+
+/// Upgrade the data of an older cache format to the current one
+fn migrate(version: u16, payload: &[u8]) -> Result<CacheData> {
+    let data = match version {
+        // version 0 is without header, but has the same data as version 1. Neither cached the offsets of blocks
+        0 | 1 => rmp_serde::from_read_ref::<_, CacheDataV1>(payload)
+            .context("Cache file de-serialization failed: corrupted MessagePack data")?
+            .into_iter()
+            .map(|(config, blocks_mapping)| {
+                let blocks_mapping = blocks_mapping
+                    .into_iter()
+                    .map(|(key, forged_block_solution)| {
+                        (
+                            key,
+                            CachedBlock {
+                                forged_block_solution,
+                                plain_text_offset: None,
+                            },
+                        )
+                    })
+                    .collect();
+                (config, blocks_mapping)
+            })
+            .collect(),
+        // the calibration response's header fields were in the middle, rather than at the end
+        2 => rmp_serde::from_read_ref::<_, CacheDataV2>(payload)
+            .context("Cache file de-serialization failed: corrupted MessagePack data")?
+            .into_iter()
+            .map(|(config, blocks_mapping)| (CacheConfig::from(config), blocks_mapping))
+            .collect(),
+        version => {
+            return Err(anyhow!(
+                "Cache file de-serialization failed: migrating version {} is unsupported",
+                version
+            ))
+        }
+    };
+    info!(
+        target: LOG_TARGET,
+        "Migrated the cache file from version {} to {}", version, CACHE_VERSION
+    );
+
+    Ok(data)
+}
+
+/// Move the cache aside, with the suffix added to its name. An older backup is replaced
+fn back_up(cache_file_path: &Path, suffix: &str) -> Result<PathBuf> {
+    let backup_path = cache_file_path.with_extension(format!("bin.{}", suffix));
+    fs::rename(cache_file_path, &backup_path).context(format!(
+        "Cache file `{}` could not be moved to `{}`",
+        cache_file_path.display(),
+        backup_path.display()
+    ))?;
+
+    Ok(backup_path)
+}
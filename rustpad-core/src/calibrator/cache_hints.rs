@@ -0,0 +1,94 @@
+use reqwest::header::{self, HeaderMap, HeaderValue};
+
+/// Headers set by caches, usually CDNs, to indicate whether the response was served from cache.
+const CACHE_STATUS_HEADERS: [&str; 5] = [
+    "x-cache",
+    "x-cache-status",
+    "cf-cache-status",
+    "x-proxy-cache",
+    "x-drupal-cache",
+];
+/// Headers uniquely identifying a response. A fresh response gets a new ID.
+const RESPONSE_ID_HEADERS: [&str; 6] = [
+    "x-request-id",
+    "x-amz-cf-id",
+    "x-amzn-requestid",
+    "x-amz-request-id",
+    "x-correlation-id",
+    "cf-ray",
+];
+
+/// Parts of a web response which reveal whether it was served from a cache instead of by the oracle itself.
+/// If our forged cypher texts are answered from cache, the oracle never gets to judge the padding and every verdict is worthless.
+#[derive(Debug, Clone)]
+pub struct CacheHints {
+    cache_hit: bool,
+    aged: bool,
+    response_id: Option<HeaderValue>,
+    etag: Option<HeaderValue>,
+}
+
+impl CacheHints {
+    /// Reason why the responses to distinct cypher texts look like they were served from cache, if they do.
+    /// Identical ETags are only suspicious if the responses are identical as well. Otherwise, the ETag merely identifies e.g. the static error page.
+    pub fn caching_reason(hints: &[Self], all_responses_same: bool) -> Option<&'static str> {
+        if hints.iter().any(|hint| hint.cache_hit) {
+            return Some("the target reports a cache hit");
+        }
+        if hints.iter().any(|hint| hint.aged) {
+            return Some("responses have a non-zero `Age` header");
+        }
+        if all_the_same(hints.iter().map(|hint| hint.response_id.as_ref())) {
+            return Some("distinct requests got the same response ID");
+        }
+        if all_responses_same && all_the_same(hints.iter().map(|hint| hint.etag.as_ref())) {
+            return Some("distinct requests got the same ETag");
+        }
+
+        None
+    }
+}
+
+impl From<&HeaderMap> for CacheHints {
+    fn from(headers: &HeaderMap) -> Self {
+        let cache_hit = CACHE_STATUS_HEADERS.iter().any(|name| {
+            headers
+                .get_all(*name)
+                .iter()
+                .filter_map(|value| value.to_str().ok())
+                .any(|value| value.to_lowercase().contains("hit"))
+        });
+        let aged = headers
+            .get(header::AGE)
+            .and_then(|age| age.to_str().ok())
+            .and_then(|age| age.trim().parse::<u64>().ok())
+            .map(|age| age > 0)
+            .unwrap_or(false);
+        let response_id = RESPONSE_ID_HEADERS
+            .iter()
+            .find_map(|name| headers.get(*name))
+            .cloned();
+        let etag = headers.get(header::ETAG).cloned();
+
+        Self {
+            cache_hit,
+            aged,
+            response_id,
+            etag,
+        }
+    }
+}
+
+// at least 2 values are needed to tell anything. A missing value breaks the streak
+fn all_the_same<'a>(mut values: impl Iterator<Item = Option<&'a HeaderValue>>) -> bool {
+    match values.next() {
+        Some(Some(first)) => {
+            let mut amount_values = 1;
+            values.all(|value| {
+                amount_values += 1;
+                value == Some(first)
+            }) && amount_values > 1
+        }
+        _ => false,
+    }
+}
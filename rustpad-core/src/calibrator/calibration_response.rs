@@ -0,0 +1,365 @@
+use anyhow::{Context, Result};
+use getset::Getters;
+use reqwest::{
+    header::{self, HeaderName, HeaderValue},
+    Response, StatusCode,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::config::{
+    padding_error_overrides::PaddingErrorOverrides, padding_error_pattern::PaddingErrorPattern,
+    WebConfig,
+};
+
+/// Contains the parts of web response which are relevant to deciding whether the web oracle decided the padding was correct or not.
+#[derive(Hash, Eq, PartialEq, Debug, Clone, Getters)]
+pub struct CalibrationResponse {
+    #[getset(get = "pub")]
+    status: StatusCode,
+    #[getset(get = "pub")]
+    location: Option<HeaderValue>,
+    // headers of `compare-header` normalization rules, `None` if missing
+    #[getset(get = "pub")]
+    headers: Vec<(HeaderName, Option<HeaderValue>)>,
+    #[getset(get = "pub")]
+    content: Option<String>,
+    #[getset(get = "pub")]
+    content_length: Option<u64>,
+    #[getset(get = "pub")]
+    body_pattern: Option<PaddingErrorPattern>,
+    #[getset(get)] // private
+    comparison: Comparison,
+    // padding error responses of other backends, see `CalibrationMode::MultiBackend`
+    #[getset(get = "pub")]
+    other_signatures: Vec<CalibrationResponse>,
+}
+
+/// Which parts of a response are compared to decide whether it's a padding error.
+#[derive(Serialize, Deserialize, Hash, Eq, PartialEq, Debug, Clone, Copy, Default)]
+enum Comparison {
+    // calibrated: responses must be identical
+    #[default]
+    Exact,
+    // given by the user, or derived from the body: only the given parts are compared
+    Partial {
+        status: bool,
+        location: bool,
+        size: bool,
+        body: bool,
+    },
+}
+
+/// Fields are serialized by position, so new ones go at the end, with a default for caches which predate them
+#[derive(Serialize, Deserialize, Hash, PartialEq, Eq, Clone)]
+pub struct SerializableCalibrationResponse {
+    status: u16,
+    location: Option<Vec<u8>>,
+    content: Option<String>,
+    content_length: Option<u64>,
+    // caches from before overrides existed only hold calibrated responses
+    #[serde(default)]
+    comparison: Comparison,
+    #[serde(default)]
+    body_pattern: Option<String>,
+    #[serde(default)]
+    other_signatures: Vec<SerializableCalibrationResponse>,
+    #[serde(default)]
+    headers: Vec<(String, Option<Vec<u8>>)>,
+}
+
+/// Layout of cache version 2, which had `headers` in the middle
+#[derive(Deserialize, Hash, PartialEq, Eq)]
+pub struct SerializableCalibrationResponseV2 {
+    status: u16,
+    location: Option<Vec<u8>>,
+    headers: Vec<(String, Option<Vec<u8>>)>,
+    content: Option<String>,
+    content_length: Option<u64>,
+    comparison: Comparison,
+    body_pattern: Option<String>,
+    other_signatures: Vec<SerializableCalibrationResponseV2>,
+}
+
+impl CalibrationResponse {
+    pub async fn from_response(response: Response, config: &WebConfig) -> Result<Self> {
+        let status = response.status();
+        let normalization_rules = config.normalization_rules();
+        let response_headers = normalization_rules.apply_to_headers(response.headers());
+        let location = response_headers.get(header::LOCATION).cloned();
+        let headers = normalization_rules
+            .compared_headers()
+            .map(|name| (name.clone(), response_headers.get(name).cloned()))
+            .collect();
+        let mut content_length = if *config.consider_body() {
+            response.content_length()
+        } else {
+            None
+        };
+        let content = if *config.consider_body() {
+            let content = response.text().await?;
+            if config.body_normalizations().is_empty() && !normalization_rules.normalizes_body() {
+                Some(content)
+            } else {
+                let content = config
+                    .body_normalizations()
+                    .iter()
+                    .fold(content, |content, normalization| {
+                        normalization.apply(&content)
+                    });
+                let content = normalization_rules.apply_to_body(content);
+                // the header describes the original body, which is exactly what's being ignored
+                content_length = Some(content.len() as u64);
+                Some(content)
+            }
+        } else {
+            None
+        };
+
+        Ok(CalibrationResponse {
+            status,
+            location,
+            headers,
+            content,
+            content_length,
+            body_pattern: None,
+            comparison: Comparison::Exact,
+            other_signatures: vec![],
+        })
+    }
+
+    /// Padding error responses are recognised solely by their body matching `body_pattern`
+    pub fn from_body_pattern(body_pattern: PaddingErrorPattern) -> Self {
+        CalibrationResponse {
+            status: StatusCode::default(),
+            location: None,
+            headers: vec![],
+            content: None,
+            content_length: None,
+            body_pattern: Some(body_pattern),
+            comparison: Comparison::Partial {
+                status: false,
+                location: false,
+                size: false,
+                body: true,
+            },
+            other_signatures: vec![],
+        }
+    }
+
+    /// Construct the padding error response from the user's overrides, skipping calibration. `None` if nothing was overridden.
+    pub fn from_overrides(overrides: &PaddingErrorOverrides) -> Option<Self> {
+        if overrides.is_empty() {
+            return None;
+        }
+
+        Some(CalibrationResponse {
+            // not compared if not given
+            status: overrides.status().unwrap_or_default(),
+            location: overrides.location().clone(),
+            headers: vec![],
+            content: None,
+            content_length: *overrides.size(),
+            body_pattern: overrides.body_pattern().clone(),
+            comparison: Comparison::Partial {
+                status: overrides.status().is_some(),
+                location: overrides.location().is_some(),
+                size: overrides.size().is_some(),
+                body: overrides.body_pattern().is_some(),
+            },
+            other_signatures: vec![],
+        })
+    }
+
+    /// The same padding error response, which also matches the padding error responses of other backends
+    pub fn with_other_signatures(self, other_signatures: Vec<Self>) -> Self {
+        Self {
+            other_signatures,
+            ..self
+        }
+    }
+
+    /// The parts of the response by which padding errors are recognised, one per line. E.g. for reports
+    pub fn describe(&self) -> Vec<String> {
+        let (status, location, size, body) = match self.comparison {
+            Comparison::Exact => (true, true, true, true),
+            Comparison::Partial {
+                status,
+                location,
+                size,
+                body,
+            } => (status, location, size, body),
+        };
+
+        let mut lines = Vec::new();
+        if status {
+            lines.push(format!("Status: {}", self.status));
+        }
+        if let (true, Some(location_value)) = (location, &self.location) {
+            lines.push(format!(
+                "Location: {}",
+                String::from_utf8_lossy(location_value.as_bytes())
+            ));
+        }
+        // only compared as part of the whole response
+        if let Comparison::Exact = self.comparison {
+            for (name, value) in &self.headers {
+                lines.push(format!(
+                    "{}: {}",
+                    name,
+                    value
+                        .as_ref()
+                        .map(|value| String::from_utf8_lossy(value.as_bytes()).into_owned())
+                        .unwrap_or_else(|| "(missing)".to_string())
+                ));
+            }
+        }
+        if let (true, Some(content_length)) = (size, self.content_length) {
+            lines.push(format!("Content length: {}", content_length));
+        }
+        if let (true, Some(body_pattern)) = (body, &self.body_pattern) {
+            lines.push(format!("Body pattern: {}", body_pattern.as_str()));
+        }
+        if !self.other_signatures.is_empty() {
+            lines.push(format!(
+                "Backends: {} padding error responses, each matched as a whole",
+                self.other_signatures.len() + 1
+            ));
+        }
+
+        lines
+    }
+
+    /// Whether `response` looks like this (padding error) response
+    pub fn matches(&self, response: &Self) -> bool {
+        self.matches_signature(response)
+            || self
+                .other_signatures
+                .iter()
+                .any(|signature| signature.matches_signature(response))
+    }
+
+    fn matches_signature(&self, response: &Self) -> bool {
+        match self.comparison {
+            // the other signatures are irrelevant to whether this one is matched
+            Comparison::Exact => {
+                self.status == response.status
+                    && self.location == response.location
+                    && self.headers == response.headers
+                    && self.content == response.content
+                    && self.content_length == response.content_length
+                    && self.body_pattern == response.body_pattern
+            }
+            Comparison::Partial {
+                status,
+                location,
+                size,
+                body,
+            } => {
+                (!status || self.status == response.status)
+                    && (!location || self.location == response.location)
+                    && (!size || self.content_length == response.body_size())
+                    && (!body || self.body_matches(response))
+            }
+        }
+    }
+
+    fn body_matches(&self, response: &Self) -> bool {
+        match (&self.body_pattern, &response.content) {
+            (Some(body_pattern), Some(content)) => body_pattern.is_match(content.as_bytes()),
+            _ => false,
+        }
+    }
+
+    // the `Content-Length` header is missing for e.g. chunked responses. Measure the body instead if we have it
+    fn body_size(&self) -> Option<u64> {
+        self.content
+            .as_ref()
+            .map(|content| content.len() as u64)
+            .or(self.content_length)
+    }
+}
+
+impl From<CalibrationResponse> for SerializableCalibrationResponse {
+    fn from(response: CalibrationResponse) -> Self {
+        Self {
+            status: response.status().as_u16(),
+            location: response
+                .location()
+                .as_ref()
+                .map(|v| Vec::from(v.as_bytes())),
+            headers: response
+                .headers()
+                .iter()
+                .map(|(name, value)| {
+                    (
+                        name.to_string(),
+                        value.as_ref().map(|v| Vec::from(v.as_bytes())),
+                    )
+                })
+                .collect(),
+            content: response.content().clone(),
+            content_length: *response.content_length(),
+            comparison: *response.comparison(),
+            body_pattern: response
+                .body_pattern()
+                .as_ref()
+                .map(|body_pattern| body_pattern.as_str().to_string()),
+            other_signatures: response
+                .other_signatures
+                .into_iter()
+                .map(SerializableCalibrationResponse::from)
+                .collect(),
+        }
+    }
+}
+
+impl From<SerializableCalibrationResponseV2> for SerializableCalibrationResponse {
+    fn from(response: SerializableCalibrationResponseV2) -> Self {
+        Self {
+            status: response.status,
+            location: response.location,
+            content: response.content,
+            content_length: response.content_length,
+            comparison: response.comparison,
+            body_pattern: response.body_pattern,
+            other_signatures: response
+                .other_signatures
+                .into_iter()
+                .map(SerializableCalibrationResponse::from)
+                .collect(),
+            headers: response.headers,
+        }
+    }
+}
+
+impl From<SerializableCalibrationResponse> for CalibrationResponse {
+    fn from(response: SerializableCalibrationResponse) -> Self {
+        Self {
+            status: StatusCode::from_u16(response.status).context("Status code stored in cache is invalid").expect("Data stored in the cache was verified when it was created. As such, the only possible reason for this must be a corrupted cache file."),
+            location: response
+                .location
+                .map(|v| HeaderValue::from_bytes(&v[..]).context("Header value stored in cache is invalid").expect("Data stored in the cache was verified when it was created. As such, the only possible reason for this must be a corrupted cache file.")),
+            headers: response
+                .headers
+                .into_iter()
+                .map(|(name, value)| {
+                    (
+                        HeaderName::from_bytes(name.as_bytes()).context("Header name stored in cache is invalid").expect("Data stored in the cache was verified when it was created. As such, the only possible reason for this must be a corrupted cache file."),
+                        value.map(|v| HeaderValue::from_bytes(&v[..]).context("Header value stored in cache is invalid").expect("Data stored in the cache was verified when it was created. As such, the only possible reason for this must be a corrupted cache file.")),
+                    )
+                })
+                .collect(),
+            content: response.content,
+            content_length: response.content_length,
+            body_pattern: response
+                .body_pattern
+                .map(|body_pattern| body_pattern.parse().context("Body pattern stored in cache is invalid").expect("Data stored in the cache was verified when it was created. As such, the only possible reason for this must be a corrupted cache file.")),
+            comparison: response.comparison,
+            other_signatures: response
+                .other_signatures
+                .into_iter()
+                .map(CalibrationResponse::from)
+                .collect(),
+        }
+    }
+}
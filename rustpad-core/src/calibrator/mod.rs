@@ -0,0 +1,431 @@
+pub mod cache_hints;
+pub mod calibration_response;
+
+use cache_hints::CacheHints;
+use calibration_response::CalibrationResponse;
+
+use std::collections::{HashMap, HashSet};
+
+use anyhow::{anyhow, Context, Result};
+use log::{debug, info, warn, Level};
+use rayon::iter::{IntoParallelIterator, ParallelIterator};
+use retry::{delay::Fibonacci, retry_with_index, OperationResult};
+
+use crate::{
+    config::{calibration_mode::CalibrationMode, padding_error_pattern::PaddingErrorPattern},
+    cypher_text::forged_cypher_text::ForgedCypherText,
+    error::RustpadError,
+    logging::{log_coalesced, LOG_TARGET},
+    oracle::web::calibrate_web::CalibrationWebOracle,
+    other::{RETRY_DELAY_MS, RETRY_MAX_ATTEMPTS},
+};
+
+// below this confidence, the user is warned that calibration might have picked the wrong response
+const LOW_CONFIDENCE: f64 = 0.9;
+// amount of probes which can have correct padding. Only the last byte being `0x01` is guaranteed, but `0x02 0x02` etc. can happen by chance
+const EXPECTED_VALID_PADDINGS: usize = 2;
+
+pub struct Calibrator<'a> {
+    forged_cypher_text: ForgedCypherText<'a>,
+}
+
+impl<'a> Calibrator<'a> {
+    pub fn new(forged_cypher_text: ForgedCypherText<'a>) -> Self {
+        Self { forged_cypher_text }
+    }
+
+    /// Find how the web oracle responds in case of a padding error.
+    /// If the responses look like they were served from cache, cache busting is enabled on the oracle and calibration is redone.
+    /// With multiple passes, the response to each probe is decided by majority vote.
+    pub fn determine_padding_error_response(
+        &self,
+        oracle: &mut CalibrationWebOracle,
+    ) -> Result<CalibrationResponse> {
+        let (mut responses, mut cache_hints) = self.question_oracle(oracle)?;
+
+        if !oracle.cache_busting() {
+            if let Some(reason) = caching_reason(&responses, &cache_hints) {
+                warn!(
+                    target: LOG_TARGET,
+                    "Responses seem to be served from cache, as {}. Enabling cache busting and recalibrating",
+                    reason
+                );
+                oracle.enable_cache_busting();
+                (responses, cache_hints) = self.question_oracle(oracle)?;
+
+                if let Some(reason) = caching_reason(&responses, &cache_hints) {
+                    warn!(
+                        target: LOG_TARGET,
+                        "Responses still seem to be served from cache, as {}. Verdicts of the oracle may be unreliable",
+                        reason
+                    );
+                }
+            }
+        }
+
+        let amount_passes = oracle.config().calibration_passes().get();
+        let mut passes = vec![responses];
+        for pass in 2..=amount_passes {
+            info!(
+                target: LOG_TARGET,
+                "Calibration pass {}/{}...", pass, amount_passes
+            );
+            passes.push(self.question_oracle(oracle)?.0);
+        }
+        let (responses, agreement) = vote_per_probe(passes);
+
+        if *oracle.config().calibration_mode() == CalibrationMode::BodyPattern {
+            let body_pattern = derive_body_pattern(&responses)?;
+
+            info!(
+                target: LOG_TARGET,
+                "Calibrated the web oracle! Using parameters:"
+            );
+            info!(target: LOG_TARGET, "- Body pattern: {}", body_pattern.as_str());
+
+            return Ok(CalibrationResponse::from_body_pattern(body_pattern));
+        }
+
+        let responses_len = responses.len();
+        // false positive, the hashmap's key (`response`) is obviously not mutable
+        #[allow(clippy::mutable_key_type)]
+        let counted_responses = responses.into_iter().fold(
+            HashMap::new(),
+            |mut acc: HashMap<CalibrationResponse, usize>, response| {
+                *acc.entry(response).or_default() += 1;
+                acc
+            },
+        );
+        debug!(
+            target: LOG_TARGET,
+            "Calibration results: {:#?}", counted_responses
+        );
+
+        if counted_responses.len() < 2 {
+            return Err(RustpadError::CalibrationFailed {
+                reason: "We don't know how a response to (in)correct padding looks, as all responses looked the same. Try adding the `--consider-body` flag".to_string(),
+            }
+            .into());
+        }
+
+        let (padding_error_response, padding_errors_seen) = if *oracle.config().calibration_mode()
+            == CalibrationMode::MultiBackend
+        {
+            backend_signatures(&counted_responses)?
+        } else {
+            counted_responses
+                    .iter()
+                    .max_by_key(|(_, seen)| **seen)
+                    .map(|(response, seen)| (response.clone(), *seen))
+                    .expect("The hashmap can only be empty if no responses were received, which can only happen if errors occurred. But errors were already resolved by unpacking the potential responses.")
+        };
+        let features = discriminating_features(
+            &padding_error_response,
+            counted_responses
+                .keys()
+                .filter(|response| !padding_error_response.matches(response)),
+        );
+        let confidence = confidence(agreement, padding_errors_seen, responses_len);
+
+        info!(
+            target: LOG_TARGET,
+            "Calibrated the web oracle! Using parameters:"
+        );
+        info!(
+            target: LOG_TARGET,
+            "- Status: {}",
+            padding_error_response.status()
+        );
+        if let Some(location) = padding_error_response.location() {
+            info!(target: LOG_TARGET, "- Location: {}", location.to_str()?);
+        }
+        for (name, value) in padding_error_response.headers() {
+            info!(
+                target: LOG_TARGET,
+                "- {}: {}",
+                name,
+                value
+                    .as_ref()
+                    .map(|value| String::from_utf8_lossy(value.as_bytes()).into_owned())
+                    .unwrap_or_else(|| "(missing)".to_string())
+            );
+        }
+        if *oracle.config().consider_body() {
+            info!(
+                target: LOG_TARGET,
+                "- Content length: {}",
+                padding_error_response
+                    .content_length()
+                    .map(|length| length.to_string())
+                    .unwrap_or_else(|| "?".to_string())
+            );
+        }
+        if !padding_error_response.other_signatures().is_empty() {
+            info!(
+                target: LOG_TARGET,
+                "- Backends: {} padding error responses, each matched as a whole",
+                padding_error_response.other_signatures().len() + 1
+            );
+        }
+        info!(
+            target: LOG_TARGET,
+            "- Distinguished by: {}",
+            features.join(", ")
+        );
+        info!(
+            target: LOG_TARGET,
+            "- Confidence: {:.0}% ({} of {} probes look like padding errors, passes agree on {:.0}% of responses)",
+            confidence * 100.0,
+            padding_errors_seen,
+            responses_len,
+            agreement * 100.0
+        );
+        if confidence < LOW_CONFIDENCE {
+            warn!(
+                target: LOG_TARGET,
+                "Confidence in the calibration is low. Consider more `--calibration-passes`, or pass the padding error response with the `--padding-error-*` options"
+            );
+        }
+
+        Ok(padding_error_response)
+    }
+
+    /// Question the oracle with every possible value of the calibration byte
+    fn question_oracle(
+        &self,
+        oracle: &CalibrationWebOracle,
+    ) -> Result<(Vec<CalibrationResponse>, Vec<CacheHints>)> {
+        let responses = (u8::MIN..=u8::MAX)
+            .into_par_iter()
+            .map(|byte_value| {
+                let mut forged_cypher_text = self.forged_cypher_text.clone();
+
+                forged_cypher_text.set_current_byte(byte_value);
+                debug!(
+                    target: LOG_TARGET,
+                    "Calibration block attempt: {}",
+                    forged_cypher_text.forged_block_wip().to_hex()
+                );
+
+                retry_with_index(Fibonacci::from_millis(RETRY_DELAY_MS), |attempt| {
+                    calibrate_while_handling_retries(
+                        attempt,
+                        byte_value,
+                        oracle,
+                        &forged_cypher_text,
+                    )
+                })
+                .map_err(|e| anyhow!(e.to_string()))
+            })
+            .collect::<Result<Vec<_>>>()
+            .map_err(|e| RustpadError::OracleUnreachable {
+                reason: format!("{:#}", e),
+            })
+            .context("Failed to contact web oracle for calibration")?;
+
+        Ok(responses.into_iter().unzip())
+    }
+}
+
+/// Decide each probe's response by majority vote over the passes. Also returns the fraction of all responses which agree with the vote.
+fn vote_per_probe(passes: Vec<Vec<CalibrationResponse>>) -> (Vec<CalibrationResponse>, f64) {
+    let amount_passes = passes.len();
+    let amount_probes = passes[0].len();
+
+    let mut votes: Vec<Vec<CalibrationResponse>> = (0..amount_probes).map(|_| Vec::new()).collect();
+    for pass in passes {
+        for (probe_idx, response) in pass.into_iter().enumerate() {
+            votes[probe_idx].push(response);
+        }
+    }
+
+    let mut agreeing_votes = 0;
+    let responses = votes
+        .into_iter()
+        .map(|probe_votes| {
+            // false positive, the hashmap's key (`response`) is obviously not mutable
+            #[allow(clippy::mutable_key_type)]
+            let counted_votes = probe_votes.into_iter().fold(
+                HashMap::new(),
+                |mut acc: HashMap<CalibrationResponse, usize>, response| {
+                    *acc.entry(response).or_default() += 1;
+                    acc
+                },
+            );
+            let (response, seen) = counted_votes
+                .into_iter()
+                .max_by_key(|(_, seen)| *seen)
+                .expect("Every probe was questioned at least once");
+
+            agreeing_votes += seen;
+            response
+        })
+        .collect();
+
+    (
+        responses,
+        agreeing_votes as f64 / (amount_passes * amount_probes) as f64,
+    )
+}
+
+/// Every response which is too common to be valid padding is the padding error response of some backend. The most common one is returned, holding the others. Also returns how many probes got any of these responses.
+#[allow(clippy::mutable_key_type)]
+fn backend_signatures(
+    counted_responses: &HashMap<CalibrationResponse, usize>,
+) -> Result<(CalibrationResponse, usize)> {
+    let mut signatures: Vec<(&CalibrationResponse, usize)> = counted_responses
+        .iter()
+        .filter(|(_, seen)| **seen > EXPECTED_VALID_PADDINGS)
+        .map(|(response, seen)| (response, *seen))
+        .collect();
+    if signatures.len() == counted_responses.len() {
+        return Err(RustpadError::CalibrationFailed {
+            reason: format!(
+                "All {} distinct responses were common, so none of them looks like a response to correct padding. Try the default `--calibration-mode`",
+                counted_responses.len()
+            ),
+        }
+        .into());
+    }
+    if signatures.is_empty() {
+        return Err(RustpadError::CalibrationFailed {
+            reason: "No response was common enough to be a padding error. Try adding the `--consider-body` flag only if the bodies are stable".to_string(),
+        }
+        .into());
+    }
+
+    signatures.sort_by_key(|(_, seen)| std::cmp::Reverse(*seen));
+    let padding_errors_seen = signatures.iter().map(|(_, seen)| seen).sum();
+    let other_signatures = signatures[1..]
+        .iter()
+        .map(|(response, _)| (*response).clone())
+        .collect();
+
+    Ok((
+        signatures[0]
+            .0
+            .clone()
+            .with_other_signatures(other_signatures),
+        padding_errors_seen,
+    ))
+}
+
+/// Parts of the padding error response which differ from other responses
+fn discriminating_features<'a>(
+    padding_error_response: &CalibrationResponse,
+    other_responses: impl Iterator<Item = &'a CalibrationResponse>,
+) -> Vec<&'static str> {
+    let mut status = false;
+    let mut location = false;
+    let mut headers = false;
+    let mut content_length = false;
+    let mut content = false;
+
+    for response in other_responses {
+        status |= response.status() != padding_error_response.status();
+        location |= response.location() != padding_error_response.location();
+        headers |= response.headers() != padding_error_response.headers();
+        content_length |= response.content_length() != padding_error_response.content_length();
+        content |= response.content() != padding_error_response.content();
+    }
+
+    [
+        (status, "status"),
+        (location, "location"),
+        (headers, "compared headers"),
+        (content_length, "content length"),
+        (content, "content"),
+    ]
+    .into_iter()
+    .filter(|(differs, _)| *differs)
+    .map(|(_, feature)| feature)
+    .collect()
+}
+
+/// How sure we are that calibration found the padding error response. Lowered if passes disagree, or if more probes than expected had correct padding
+fn confidence(agreement: f64, padding_errors_seen: usize, amount_probes: usize) -> f64 {
+    let valid_paddings_seen = amount_probes - padding_errors_seen;
+    let plausibility = if valid_paddings_seen <= EXPECTED_VALID_PADDINGS {
+        1.0
+    } else {
+        EXPECTED_VALID_PADDINGS as f64 / valid_paddings_seen as f64
+    };
+
+    agreement * plausibility
+}
+
+fn caching_reason(
+    responses: &[CalibrationResponse],
+    cache_hints: &[CacheHints],
+) -> Option<&'static str> {
+    let all_responses_same = responses.iter().all(|response| *response == responses[0]);
+    CacheHints::caching_reason(cache_hints, all_responses_same)
+}
+
+/// Find a piece of text which the majority of bodies, i.e. the padding errors, contain, but the others don't.
+/// Bodies are split into fragments on markup and line boundaries. The most common of such fragments wins, with ties broken by length.
+fn derive_body_pattern(responses: &[CalibrationResponse]) -> Result<PaddingErrorPattern> {
+    let fragment_counts = responses
+        .iter()
+        .filter_map(|response| response.content().as_ref())
+        .map(|content| {
+            content
+                .split(['<', '>', '\n'])
+                .map(str::trim)
+                .filter(|fragment| !fragment.is_empty())
+                .collect::<HashSet<_>>()
+        })
+        .fold(
+            HashMap::new(),
+            |mut acc: HashMap<&str, usize>, fragments| {
+                for fragment in fragments {
+                    *acc.entry(fragment).or_default() += 1;
+                }
+                acc
+            },
+        );
+
+    let distinguishing_fragment = fragment_counts
+        .into_iter()
+        .filter(|(_, seen)| *seen < responses.len() && *seen * 2 > responses.len())
+        .max_by_key(|(fragment, seen)| (*seen, fragment.len()))
+        .map(|(fragment, _)| fragment)
+        .ok_or_else(|| RustpadError::CalibrationFailed {
+            reason: "No piece of text in the body distinguishes a response to (in)correct padding. Try passing a pattern with `--padding-error-regex`".to_string(),
+        })?;
+
+    regex::escape(distinguishing_fragment).parse()
+}
+
+fn calibrate_while_handling_retries(
+    attempt: u64,
+    byte_value: u8,
+    oracle: &CalibrationWebOracle,
+    forged_cypher_text: &ForgedCypherText,
+) -> OperationResult<(CalibrationResponse, CacheHints), String> {
+    if attempt > RETRY_MAX_ATTEMPTS {
+        return OperationResult::Err(format!(
+            "Calibration block, value {}: validation failed",
+            byte_value
+        ));
+    }
+
+    match oracle.ask_validation(forged_cypher_text) {
+        Ok(response) => OperationResult::Ok(response),
+        Err(e) => {
+            log_coalesced(
+                Level::Warn,
+                "retry calibration",
+                format!(
+                    "Calibration block, value {}: retrying validation ({}/{})",
+                    byte_value, attempt, RETRY_MAX_ATTEMPTS
+                ),
+            );
+            debug!(target: LOG_TARGET, "{:?}", e);
+            OperationResult::Retry(format!(
+                "Calibration block, value {}: retrying validation ({}/{})",
+                byte_value, attempt, RETRY_MAX_ATTEMPTS
+            ))
+        }
+    }
+}
@@ -0,0 +1,50 @@
+use std::{fmt::Display, str::FromStr};
+
+use anyhow::{anyhow, Result};
+use itertools::Itertools;
+
+/// Whether forged base64 cypher texts are padded with `=`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Base64Padding {
+    // same as the original cypher text
+    #[default]
+    Keep,
+    Always,
+    Never,
+}
+
+impl Base64Padding {
+    fn variants() -> &'static [Self] {
+        &[Self::Keep, Self::Always, Self::Never]
+    }
+}
+
+impl Display for Base64Padding {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Base64Padding::Keep => write!(f, "keep"),
+            Base64Padding::Always => write!(f, "always"),
+            Base64Padding::Never => write!(f, "never"),
+        }
+    }
+}
+
+impl FromStr for Base64Padding {
+    type Err = anyhow::Error;
+
+    fn from_str(input: &str) -> Result<Self> {
+        match input.to_lowercase().as_str() {
+            "keep" | "original" => Ok(Self::Keep),
+            "always" | "pad" => Ok(Self::Always),
+            "never" | "no-pad" | "no_pad" => Ok(Self::Never),
+            _ => Err(anyhow!(
+                "`{}` is not a base64 padding style. Expected one of: [{}]",
+                input,
+                Self::variants()
+                    .iter()
+                    .map(|variant| variant.to_string())
+                    .join(", ")
+            )),
+        }
+    }
+}
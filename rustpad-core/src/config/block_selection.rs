@@ -0,0 +1,78 @@
+use std::{fmt::Display, ops::RangeInclusive, str::FromStr};
+
+use anyhow::{anyhow, Context, Result};
+use itertools::Itertools;
+
+/// Blocks to decrypt, as a comma separated list of indices and inclusive ranges, e.g. `3,5-7`. Blocks are numbered by their index in the cypher text, the IV being 0.
+#[derive(Debug, Clone)]
+pub struct BlockSelection(Vec<RangeInclusive<usize>>);
+
+impl BlockSelection {
+    pub fn contains(&self, block_idx: usize) -> bool {
+        self.0.iter().any(|range| range.contains(&block_idx))
+    }
+
+    /// Highest selected index
+    pub fn last(&self) -> usize {
+        self.0
+            .iter()
+            .map(|range| *range.end())
+            .max()
+            .expect("Parsing ensures at least 1 range")
+    }
+}
+
+impl FromStr for BlockSelection {
+    type Err = anyhow::Error;
+
+    fn from_str(input: &str) -> Result<Self> {
+        let parse_idx = |idx: &str| -> Result<usize> {
+            let idx = idx
+                .trim()
+                .parse()
+                .context(format!("`{}` is not a valid block index", idx))?;
+            if idx == 0 {
+                return Err(anyhow!(
+                    "Block 0 is the IV, which can't be decrypted. The 1st block to decrypt is 1"
+                ));
+            }
+            Ok(idx)
+        };
+
+        let ranges = input
+            .split(',')
+            .map(|range| match range.split_once('-') {
+                Some((start, end)) => {
+                    let (start, end) = (parse_idx(start)?, parse_idx(end)?);
+                    if start > end {
+                        return Err(anyhow!(
+                            "`{}` is not a valid block range. Its start comes after its end",
+                            range
+                        ));
+                    }
+                    Ok(start..=end)
+                }
+                None => parse_idx(range).map(|idx| idx..=idx),
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(Self(ranges))
+    }
+}
+
+impl Display for BlockSelection {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            self.0
+                .iter()
+                .map(|range| if range.start() == range.end() {
+                    range.start().to_string()
+                } else {
+                    format!("{}-{}", range.start(), range.end())
+                })
+                .join(",")
+        )
+    }
+}
@@ -0,0 +1,73 @@
+use std::str::FromStr;
+
+use anyhow::{anyhow, Context, Result};
+use itertools::Itertools;
+use regex::Regex;
+use scraper::{Html, Selector};
+use serde_json::Value;
+
+/// Step to remove dynamic content, such as timestamps or CSRF tokens, from a response body before it's compared.
+#[derive(Debug, Clone)]
+pub enum BodyNormalization {
+    // remove matches of the regex
+    Strip(Regex),
+    // remove all whitespace
+    Whitespace,
+    // keep only the HTML of elements matching the selector
+    Css(Selector),
+    // keep only the value at the JSON pointer
+    Json(String),
+}
+
+impl BodyNormalization {
+    pub fn apply(&self, body: &str) -> String {
+        match self {
+            BodyNormalization::Strip(regex) => regex.replace_all(body, "").into_owned(),
+            BodyNormalization::Whitespace => body.chars().filter(|c| !c.is_whitespace()).collect(),
+            BodyNormalization::Css(selector) => Html::parse_document(body)
+                .select(selector)
+                .map(|element| element.html())
+                .join("\n"),
+            // a body which isn't JSON at all is likely an error page, which is distinctive as is
+            BodyNormalization::Json(pointer) => match serde_json::from_str::<Value>(body) {
+                Ok(json) => json
+                    .pointer(pointer)
+                    .map(|value| value.to_string())
+                    .unwrap_or_default(),
+                Err(_) => body.to_string(),
+            },
+        }
+    }
+}
+
+impl FromStr for BodyNormalization {
+    type Err = anyhow::Error;
+
+    fn from_str(input: &str) -> Result<Self> {
+        let (kind, argument) = input.split_once(':').unwrap_or((input, ""));
+
+        match kind.trim().to_lowercase().as_str() {
+            "strip" => Ok(Self::Strip(
+                Regex::new(argument)
+                    .context(format!("`{}` is not a valid regular expression", argument))?,
+            )),
+            "whitespace" | "ws" => Ok(Self::Whitespace),
+            "css" => Ok(Self::Css(Selector::parse(argument).map_err(|e| {
+                anyhow!("`{}` is not a valid CSS selector: {:?}", argument, e)
+            })?)),
+            "json" => {
+                if !argument.is_empty() && !argument.starts_with('/') {
+                    return Err(anyhow!(
+                        "`{}` is not a valid JSON pointer. It must start with `/`, e.g. `/data/status`",
+                        argument
+                    ));
+                }
+                Ok(Self::Json(argument.to_string()))
+            }
+            _ => Err(anyhow!(
+                "`{}` is not a body normalization. Expected one of: [strip:<regex>, whitespace, css:<selector>, json:<pointer>]",
+                input
+            )),
+        }
+    }
+}
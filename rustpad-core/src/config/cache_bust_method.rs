@@ -0,0 +1,58 @@
+use std::{fmt::Display, str::FromStr};
+
+use anyhow::{anyhow, Result};
+use itertools::Itertools;
+
+/// How to make every web request unique, to prevent caches from answering in the oracle's stead.
+#[derive(Debug, Clone, Copy)]
+pub enum CacheBustMethod {
+    // random query parameter
+    Parameter,
+    // `Cache-Control: no-cache`
+    Header,
+    All,
+}
+
+impl CacheBustMethod {
+    fn variants() -> &'static [Self] {
+        &[Self::Parameter, Self::Header, Self::All]
+    }
+
+    pub fn uses_parameter(&self) -> bool {
+        matches!(self, Self::Parameter | Self::All)
+    }
+
+    pub fn uses_header(&self) -> bool {
+        matches!(self, Self::Header | Self::All)
+    }
+}
+
+impl Display for CacheBustMethod {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CacheBustMethod::Parameter => write!(f, "param"),
+            CacheBustMethod::Header => write!(f, "header"),
+            CacheBustMethod::All => write!(f, "all"),
+        }
+    }
+}
+
+impl FromStr for CacheBustMethod {
+    type Err = anyhow::Error;
+
+    fn from_str(input: &str) -> Result<Self> {
+        match input.to_lowercase().as_str() {
+            "param" | "parameter" | "query" => Ok(Self::Parameter),
+            "header" | "headers" => Ok(Self::Header),
+            "all" | "both" => Ok(Self::All),
+            _ => Err(anyhow!(
+                "`{}` is not a cache busting method. Expected one of: [{}]",
+                input,
+                Self::variants()
+                    .iter()
+                    .map(|variant| variant.to_string())
+                    .join(", ")
+            )),
+        }
+    }
+}
@@ -0,0 +1,52 @@
+use std::{fmt::Display, str::FromStr};
+
+use anyhow::{anyhow, Result};
+use itertools::Itertools;
+
+/// How calibration decides which responses indicate a padding error.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CalibrationMode {
+    // the most common response, compared as a whole
+    #[default]
+    Exact,
+    // a substring of the body which only padding errors contain
+    BodyPattern,
+    // every common response, compared as a whole. Backends of a load balancer may each answer padding errors differently
+    MultiBackend,
+}
+
+impl CalibrationMode {
+    fn variants() -> &'static [Self] {
+        &[Self::Exact, Self::BodyPattern, Self::MultiBackend]
+    }
+}
+
+impl Display for CalibrationMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CalibrationMode::Exact => write!(f, "exact"),
+            CalibrationMode::BodyPattern => write!(f, "body-pattern"),
+            CalibrationMode::MultiBackend => write!(f, "multi-backend"),
+        }
+    }
+}
+
+impl FromStr for CalibrationMode {
+    type Err = anyhow::Error;
+
+    fn from_str(input: &str) -> Result<Self> {
+        match input.to_lowercase().as_str() {
+            "exact" => Ok(Self::Exact),
+            "body-pattern" | "body_pattern" | "pattern" | "body" => Ok(Self::BodyPattern),
+            "multi-backend" | "multi_backend" | "backends" => Ok(Self::MultiBackend),
+            _ => Err(anyhow!(
+                "`{}` is not a calibration mode. Expected one of: [{}]",
+                input,
+                Self::variants()
+                    .iter()
+                    .map(|variant| variant.to_string())
+                    .join(", ")
+            )),
+        }
+    }
+}
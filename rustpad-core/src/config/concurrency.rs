@@ -0,0 +1,48 @@
+use std::{fmt::Display, ops::Deref, str::FromStr};
+
+use anyhow::{anyhow, Context, Result};
+
+use super::thread_count::ThreadCount;
+
+/// Maximum amount of requests which are in flight at the same time.
+#[derive(Debug, Clone)]
+pub struct Concurrency(usize);
+
+impl From<&ThreadCount> for Concurrency {
+    fn from(thread_count: &ThreadCount) -> Self {
+        Concurrency(**thread_count)
+    }
+}
+
+impl FromStr for Concurrency {
+    type Err = anyhow::Error;
+
+    fn from_str(concurrency: &str) -> Result<Self> {
+        let concurrency = concurrency.parse::<usize>().context(format!(
+            "`{}`. Expected a positive, non-zero integer",
+            concurrency
+        ))?;
+        if concurrency > 0 {
+            Ok(Self(concurrency))
+        } else {
+            Err(anyhow!(
+                "`{}`. Expected a positive, non-zero integer",
+                concurrency
+            ))
+        }
+    }
+}
+
+impl Deref for Concurrency {
+    type Target = usize;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl Display for Concurrency {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
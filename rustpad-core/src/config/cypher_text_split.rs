@@ -0,0 +1,75 @@
+use std::{borrow::Cow, num::NonZeroUsize, ops::Deref};
+
+use getset::Getters;
+
+/// Some targets split the cypher text over multiple cookies or parameters, each holding a chunk of fixed length.
+/// Chunk `n` is placed at `<keyword><n>` in the request.
+#[derive(Debug, Clone, Getters)]
+pub struct CypherTextSplit {
+    #[getset(get = "pub")]
+    chunk_length: usize,
+    #[getset(get = "pub")]
+    amount_chunks: usize,
+}
+
+impl CypherTextSplit {
+    /// The chunk length is taken from the user if given. Otherwise, if the cypher text was passed in multiple chunks, the length of the 1st chunk is used.
+    pub fn new(
+        chunk_length: Option<NonZeroUsize>,
+        cypher_text_chunks: &[impl Deref<Target = str>],
+        no_url_encode: bool,
+    ) -> Option<Self> {
+        // lengths are counted on the cypher text as it is before URL encoding. Otherwise, an escape sequence could be split over 2 chunks
+        let url_decode = |chunk: &'_ str| -> usize {
+            if no_url_encode {
+                chunk.len()
+            } else {
+                urlencoding::decode(chunk)
+                    .unwrap_or(Cow::Borrowed(chunk))
+                    .len()
+            }
+        };
+
+        let chunk_length = match chunk_length {
+            Some(chunk_length) => chunk_length.get(),
+            None if cypher_text_chunks.len() > 1 => url_decode(&cypher_text_chunks[0]),
+            None => return None,
+        };
+        let total_length: usize = cypher_text_chunks
+            .iter()
+            .map(|chunk| url_decode(chunk))
+            .sum();
+
+        Some(Self {
+            chunk_length,
+            amount_chunks: total_length.div_ceil(chunk_length).max(1),
+        })
+    }
+
+    /// Split the cypher text the same way the original was. Chunks which don't hold any data are empty, and excess data is put in the last chunk.
+    pub fn split(&self, encoded_cypher_text: &str, url_encoded: bool) -> Vec<String> {
+        let raw_cypher_text = if url_encoded {
+            urlencoding::decode(encoded_cypher_text).unwrap_or(Cow::Borrowed(encoded_cypher_text))
+        } else {
+            Cow::Borrowed(encoded_cypher_text)
+        };
+
+        (0..self.amount_chunks)
+            .map(|idx| {
+                let start = (idx * self.chunk_length).min(raw_cypher_text.len());
+                let end = if idx == self.amount_chunks - 1 {
+                    raw_cypher_text.len()
+                } else {
+                    ((idx + 1) * self.chunk_length).min(raw_cypher_text.len())
+                };
+                let chunk = &raw_cypher_text[start..end];
+
+                if url_encoded {
+                    urlencoding::encode(chunk).to_string()
+                } else {
+                    chunk.to_string()
+                }
+            })
+            .collect()
+    }
+}
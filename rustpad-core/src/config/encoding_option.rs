@@ -4,7 +4,7 @@ use anyhow::{anyhow, Result};
 use itertools::Itertools;
 
 #[derive(Debug, Clone)]
-pub(crate) enum EncodingOption {
+pub enum EncodingOption {
     Auto,
     Hex,
     Base64,
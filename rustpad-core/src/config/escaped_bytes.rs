@@ -0,0 +1,69 @@
+use std::{fmt::Display, ops::Deref, str::FromStr};
+
+use anyhow::{anyhow, Context, Result};
+
+/// Raw bytes, passed by the user as a string with escape sequences. Supports `\n`, `\r`, `\t`, `\0`, `\\`, and `\xHH`.
+#[derive(Debug, Clone)]
+pub struct EscapedBytes(Vec<u8>);
+
+impl FromStr for EscapedBytes {
+    type Err = anyhow::Error;
+
+    fn from_str(input: &str) -> Result<Self> {
+        let mut bytes = Vec::with_capacity(input.len());
+        let mut chars = input.chars();
+
+        while let Some(c) = chars.next() {
+            if c != '\\' {
+                let mut buffer = [0; 4];
+                bytes.extend_from_slice(c.encode_utf8(&mut buffer).as_bytes());
+                continue;
+            }
+
+            match chars.next() {
+                Some('n') => bytes.push(b'\n'),
+                Some('r') => bytes.push(b'\r'),
+                Some('t') => bytes.push(b'\t'),
+                Some('0') => bytes.push(b'\0'),
+                Some('\\') => bytes.push(b'\\'),
+                Some('x') => {
+                    let hex_digits: String = chars.by_ref().take(2).collect();
+                    bytes.push(u8::from_str_radix(&hex_digits, 16).context(format!(
+                        "`\\x{}` in `{}`. Expected 2 hexadecimal digits",
+                        hex_digits, input
+                    ))?);
+                }
+                Some(other) => {
+                    return Err(anyhow!(
+                        "`\\{}` in `{}` is not a supported escape sequence. Expected one of: [\\n, \\r, \\t, \\0, \\\\, \\xHH]",
+                        other,
+                        input
+                    ))
+                }
+                None => return Err(anyhow!("`{}` ends with an unfinished escape sequence", input)),
+            }
+        }
+
+        Ok(Self(bytes))
+    }
+}
+
+impl From<Vec<u8>> for EscapedBytes {
+    fn from(bytes: Vec<u8>) -> Self {
+        Self(bytes)
+    }
+}
+
+impl Deref for EscapedBytes {
+    type Target = [u8];
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl Display for EscapedBytes {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0.escape_ascii())
+    }
+}
@@ -0,0 +1,90 @@
+use std::{fmt::Display, fs, str::FromStr};
+
+use anyhow::{anyhow, Context, Result};
+use itertools::Itertools;
+
+// English letter frequency, then digits, punctuation and whitespace. PKCS7 padding ends the last block
+const ASCII_PRIORITY: &[u8] = b" etaoinsrhldcumfpgwybvkxjqzETAOINSRHLDCUMFPGWYBVKXJQZ0123456789.,'\"-_:;!?/()=&@#%+*\n\r\t\x01\x02\x03\x04\x05\x06\x07\x08\x09\x0a\x0b\x0c\x0d\x0e\x0f\x10";
+// JSON's structure, then the characters of keys and values
+const JSON_PRIORITY: &[u8] = b"\"{}:,[] etaoinsrhldcumfpgwybvkxjqz0123456789ETAOINSRHLDCUMFPGWYBVKXJQZ.-_+/\\\x01\x02\x03\x04\x05\x06\x07\x08\x09\x0a\x0b\x0c\x0d\x0e\x0f\x10";
+
+/// Order in which the values of a byte are tried. A value decrypts to a known plain text byte, so the plain text bytes which are expected the most are tried first.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum GuessOrder {
+    #[default]
+    Ascii,
+    Json,
+    // every value in numerical order, ignoring the plain text
+    Uniform,
+    // plain text bytes from a file, most expected first
+    Custom {
+        path: String,
+        priority: Vec<u8>,
+    },
+}
+
+impl GuessOrder {
+    fn variants() -> &'static [&'static str] {
+        &["ascii", "json", "uniform", "@<file>"]
+    }
+
+    /// All plain text bytes, most expected first. `None` if the plain text doesn't matter
+    pub fn plain_text_bytes(&self) -> Option<Vec<u8>> {
+        let priority = match self {
+            GuessOrder::Ascii => ASCII_PRIORITY,
+            GuessOrder::Json => JSON_PRIORITY,
+            GuessOrder::Uniform => return None,
+            GuessOrder::Custom { priority, .. } => priority,
+        };
+
+        Some(
+            priority
+                .iter()
+                .cloned()
+                .chain(u8::MIN..=u8::MAX)
+                .unique()
+                .collect(),
+        )
+    }
+}
+
+impl Display for GuessOrder {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GuessOrder::Ascii => write!(f, "ascii"),
+            GuessOrder::Json => write!(f, "json"),
+            GuessOrder::Uniform => write!(f, "uniform"),
+            GuessOrder::Custom { path, .. } => write!(f, "@{}", path),
+        }
+    }
+}
+
+impl FromStr for GuessOrder {
+    type Err = anyhow::Error;
+
+    fn from_str(input: &str) -> Result<Self> {
+        if let Some(path) = input.strip_prefix('@') {
+            let priority = fs::read(path)
+                .context(format!("Reading the guess order from `{}` failed", path))?;
+            if priority.is_empty() {
+                return Err(anyhow!("The guess order read from `{}` is empty", path));
+            }
+
+            return Ok(Self::Custom {
+                path: path.to_string(),
+                priority,
+            });
+        }
+
+        match input.to_lowercase().as_str() {
+            "ascii" | "text" => Ok(Self::Ascii),
+            "json" => Ok(Self::Json),
+            "uniform" | "none" => Ok(Self::Uniform),
+            _ => Err(anyhow!(
+                "`{}` is not a guess order. Expected one of: [{}]",
+                input,
+                Self::variants().iter().join(", ")
+            )),
+        }
+    }
+}
@@ -0,0 +1,82 @@
+use std::{fmt::Display, str::FromStr};
+
+use anyhow::{anyhow, Result};
+use itertools::Itertools;
+
+use crate::divination::guess_strategy::{
+    FrequencyStrategy, GuessStrategy, MarkovStrategy, RandomStrategy, SequentialStrategy,
+};
+
+use super::guess_order::GuessOrder;
+
+/// Which `GuessStrategy` decides the order in which the values of a byte are tried.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum GuessStrategyOption {
+    // every value in numerical order
+    Sequential,
+    // a fresh shuffle for every byte
+    Random,
+    // the plain text bytes of `--guess-order`
+    #[default]
+    Frequency,
+    // learned from the plain text solved so far, falling back to `--guess-order`
+    Markov,
+}
+
+impl GuessStrategyOption {
+    fn variants() -> &'static [Self] {
+        &[
+            Self::Sequential,
+            Self::Random,
+            Self::Frequency,
+            Self::Markov,
+        ]
+    }
+
+    /// Whether the strategy may be seeded with the plain text of cached cypher texts, with a `WarmStartStrategy`. The orders of `sequential` and `random` are kept as they are
+    pub fn warm_starts(&self) -> bool {
+        matches!(self, Self::Frequency | Self::Markov)
+    }
+
+    /// A new strategy, with nothing learned yet. `guess_order` orders the plain text bytes of which the strategy knows nothing better
+    pub fn to_strategy(&self, guess_order: &GuessOrder) -> Box<dyn GuessStrategy> {
+        match self {
+            Self::Sequential => Box::new(SequentialStrategy),
+            Self::Random => Box::new(RandomStrategy),
+            Self::Frequency => Box::new(FrequencyStrategy::new(guess_order.clone())),
+            Self::Markov => Box::new(MarkovStrategy::new(guess_order.clone())),
+        }
+    }
+}
+
+impl Display for GuessStrategyOption {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Sequential => write!(f, "sequential"),
+            Self::Random => write!(f, "random"),
+            Self::Frequency => write!(f, "frequency"),
+            Self::Markov => write!(f, "markov"),
+        }
+    }
+}
+
+impl FromStr for GuessStrategyOption {
+    type Err = anyhow::Error;
+
+    fn from_str(input: &str) -> Result<Self> {
+        match input.to_lowercase().as_str() {
+            "sequential" | "seq" => Ok(Self::Sequential),
+            "random" | "rand" | "shuffle" => Ok(Self::Random),
+            "frequency" | "freq" => Ok(Self::Frequency),
+            "markov" => Ok(Self::Markov),
+            _ => Err(anyhow!(
+                "`{}` is not a guess strategy. Expected one of: [{}]",
+                input,
+                Self::variants()
+                    .iter()
+                    .map(|variant| variant.to_string())
+                    .join(", ")
+            )),
+        }
+    }
+}
@@ -4,10 +4,10 @@ use anyhow::{Context, Result};
 use getset::Getters;
 
 #[derive(Debug, Clone, Getters)]
-pub(crate) struct Header {
-    #[get = "pub(crate)"]
+pub struct Header {
+    #[get = "pub"]
     name: String,
-    #[get = "pub(crate)"]
+    #[get = "pub"]
     value: String,
 }
 
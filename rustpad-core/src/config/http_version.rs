@@ -0,0 +1,71 @@
+use std::{fmt::Display, str::FromStr};
+
+use anyhow::{anyhow, Result};
+use itertools::Itertools;
+
+/// HTTP version which the web oracle speaks.
+#[derive(Debug, Clone, Copy, Default)]
+pub enum HttpVersion {
+    #[default]
+    Http1,
+    // negotiated with ALPN, falling back to HTTP/1.1 if the server doesn't offer it. Plain HTTP always uses HTTP/1.1
+    Http2,
+    // without negotiation, so also over plain HTTP (h2c)
+    Http2PriorKnowledge,
+    // over QUIC, falling back to HTTP/2 or HTTP/1.1 if the oracle can't be reached over it. Only with the `http3` feature
+    Http3,
+}
+
+impl HttpVersion {
+    fn variants() -> &'static [Self] {
+        &[
+            Self::Http1,
+            Self::Http2,
+            Self::Http2PriorKnowledge,
+            Self::Http3,
+        ]
+    }
+
+    /// Whether requests may be multiplexed over HTTP/2 or HTTP/3 connections
+    pub fn multiplexes(&self) -> bool {
+        matches!(self, Self::Http2 | Self::Http2PriorKnowledge | Self::Http3)
+    }
+}
+
+impl Display for HttpVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            HttpVersion::Http1 => write!(f, "1.1"),
+            HttpVersion::Http2 => write!(f, "2"),
+            HttpVersion::Http2PriorKnowledge => write!(f, "2-prior-knowledge"),
+            HttpVersion::Http3 => write!(f, "3"),
+        }
+    }
+}
+
+impl FromStr for HttpVersion {
+    type Err = anyhow::Error;
+
+    fn from_str(input: &str) -> Result<Self> {
+        match input
+            .to_lowercase()
+            .trim_start_matches("http/")
+            .trim_start_matches("http")
+            .replace('_', "-")
+            .as_str()
+        {
+            "1" | "1.1" => Ok(Self::Http1),
+            "2" | "h2" => Ok(Self::Http2),
+            "2-prior-knowledge" | "h2c" => Ok(Self::Http2PriorKnowledge),
+            "3" | "h3" | "quic" => Ok(Self::Http3),
+            _ => Err(anyhow!(
+                "`{}` is not an HTTP version. Expected one of: [{}]",
+                input,
+                Self::variants()
+                    .iter()
+                    .map(|variant| variant.to_string())
+                    .join(", ")
+            )),
+        }
+    }
+}
@@ -0,0 +1,170 @@
+use std::{fmt::Display, num::NonZeroUsize, str::FromStr};
+
+use anyhow::{anyhow, Context, Result};
+use getset::Getters;
+use itertools::Itertools;
+
+/// Which occurrences of the keyword are to be replaced by the cypher text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeywordOccurrence {
+    All,
+    Last,
+    // 1-indexed
+    Nth(NonZeroUsize),
+}
+
+/// Part of the web request in which the keyword is replaced.
+#[derive(Debug, Clone, Copy)]
+pub enum RequestPart {
+    Url,
+    PostData,
+    Headers,
+}
+
+/// `--keyword-occurrence` option, as passed by the user. Applies to all parts of the request if no part is specified.
+#[derive(Debug, Clone)]
+pub struct KeywordOccurrenceOption {
+    request_part: Option<RequestPart>,
+    occurrence: KeywordOccurrence,
+}
+
+/// The occurrences of the keyword to replace, per part of the web request. Headers are considered individually.
+#[derive(Debug, Clone, Getters)]
+pub struct KeywordOccurrences {
+    #[getset(get = "pub")]
+    url: KeywordOccurrence,
+    #[getset(get = "pub")]
+    post_data: KeywordOccurrence,
+    #[getset(get = "pub")]
+    headers: KeywordOccurrence,
+}
+
+impl KeywordOccurrence {
+    /// Replace the selected occurrences of `placeholder` in `text`.
+    pub fn replace(&self, text: &str, placeholder: &str, value: &str) -> String {
+        let occurrence_idx = match self {
+            KeywordOccurrence::All => return text.replace(placeholder, value),
+            KeywordOccurrence::Last => text.rmatch_indices(placeholder).next(),
+            KeywordOccurrence::Nth(n) => text.match_indices(placeholder).nth(n.get() - 1),
+        }
+        .map(|(idx, _)| idx);
+
+        match occurrence_idx {
+            Some(idx) => format!(
+                "{}{}{}",
+                &text[..idx],
+                value,
+                &text[idx + placeholder.len()..]
+            ),
+            None => text.to_string(),
+        }
+    }
+
+    /// Amount of occurrences of the keyword which need to exist for this selection to make sense.
+    pub fn required_occurrences(&self) -> usize {
+        match self {
+            KeywordOccurrence::All | KeywordOccurrence::Last => 1,
+            KeywordOccurrence::Nth(n) => n.get(),
+        }
+    }
+}
+
+impl Default for KeywordOccurrences {
+    fn default() -> Self {
+        Self {
+            url: KeywordOccurrence::All,
+            post_data: KeywordOccurrence::All,
+            headers: KeywordOccurrence::All,
+        }
+    }
+}
+
+impl From<&[KeywordOccurrenceOption]> for KeywordOccurrences {
+    /// Later options override earlier ones
+    fn from(options: &[KeywordOccurrenceOption]) -> Self {
+        options
+            .iter()
+            .fold(Self::default(), |mut occurrences, option| {
+                match option.request_part {
+                    Some(RequestPart::Url) => occurrences.url = option.occurrence,
+                    Some(RequestPart::PostData) => occurrences.post_data = option.occurrence,
+                    Some(RequestPart::Headers) => occurrences.headers = option.occurrence,
+                    None => {
+                        occurrences.url = option.occurrence;
+                        occurrences.post_data = option.occurrence;
+                        occurrences.headers = option.occurrence;
+                    }
+                }
+                occurrences
+            })
+    }
+}
+
+impl RequestPart {
+    fn variants() -> &'static [Self] {
+        &[Self::Url, Self::PostData, Self::Headers]
+    }
+}
+
+impl Display for RequestPart {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RequestPart::Url => write!(f, "url"),
+            RequestPart::PostData => write!(f, "data"),
+            RequestPart::Headers => write!(f, "header"),
+        }
+    }
+}
+
+impl FromStr for RequestPart {
+    type Err = anyhow::Error;
+
+    fn from_str(input: &str) -> Result<Self> {
+        match input.to_lowercase().as_str() {
+            "url" => Ok(Self::Url),
+            "data" | "post-data" | "post_data" => Ok(Self::PostData),
+            "header" | "headers" => Ok(Self::Headers),
+            _ => Err(anyhow!(
+                "`{}` is not a part of the request. Expected one of: [{}]",
+                input,
+                Self::variants()
+                    .iter()
+                    .map(|variant| variant.to_string())
+                    .join(", ")
+            )),
+        }
+    }
+}
+
+impl FromStr for KeywordOccurrence {
+    type Err = anyhow::Error;
+
+    fn from_str(input: &str) -> Result<Self> {
+        match input.to_lowercase().as_str() {
+            "all" => Ok(Self::All),
+            "first" => Ok(Self::Nth(NonZeroUsize::new(1).expect("1 is not 0"))),
+            "last" => Ok(Self::Last),
+            nth => nth.parse::<NonZeroUsize>().map(Self::Nth).context(format!(
+                "`{}`. Expected one of: [all, first, last], or a positive, non-zero integer",
+                input
+            )),
+        }
+    }
+}
+
+impl FromStr for KeywordOccurrenceOption {
+    type Err = anyhow::Error;
+
+    fn from_str(input: &str) -> Result<Self> {
+        match input.split_once('=') {
+            Some((request_part, occurrence)) => Ok(Self {
+                request_part: Some(request_part.trim().parse()?),
+                occurrence: occurrence.trim().parse()?,
+            }),
+            None => Ok(Self {
+                request_part: None,
+                occurrence: input.trim().parse()?,
+            }),
+        }
+    }
+}
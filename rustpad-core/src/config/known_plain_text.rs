@@ -0,0 +1,58 @@
+use std::{collections::HashMap, str::FromStr};
+
+use anyhow::{anyhow, Context, Result};
+
+use super::escaped_bytes::EscapedBytes;
+
+/// Plain text which the user expects at an offset, as `<offset>:<text>`.
+#[derive(Debug, Clone)]
+pub struct KnownPlainTextHint {
+    offset: usize,
+    text: EscapedBytes,
+}
+
+/// Plain text bytes which are known, or at least expected, per offset in the plain text. The IV is not part of the plain text.
+#[derive(Debug, Clone, Default)]
+pub struct KnownPlainText(HashMap<usize, u8>);
+
+impl FromStr for KnownPlainTextHint {
+    type Err = anyhow::Error;
+
+    fn from_str(input: &str) -> Result<Self> {
+        let (offset, text) = input.split_once(':').ok_or_else(|| {
+            anyhow!(
+                "`{}` is not a known plain text hint. Expected `<offset>:<text>`",
+                input
+            )
+        })?;
+
+        Ok(Self {
+            offset: offset
+                .trim()
+                .parse()
+                .context(format!("`{}` is not a valid offset", offset))?,
+            text: text.parse()?,
+        })
+    }
+}
+
+impl KnownPlainText {
+    /// Later hints override earlier ones where they overlap
+    pub fn new(hints: &[KnownPlainTextHint]) -> Self {
+        Self(
+            hints
+                .iter()
+                .flat_map(|hint| {
+                    hint.text
+                        .iter()
+                        .enumerate()
+                        .map(move |(idx, byte)| (hint.offset + idx, *byte))
+                })
+                .collect(),
+        )
+    }
+
+    pub fn byte_at(&self, offset: usize) -> Option<u8> {
+        self.0.get(&offset).cloned()
+    }
+}
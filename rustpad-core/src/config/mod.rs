@@ -0,0 +1,681 @@
+pub mod base64_padding;
+pub mod block_selection;
+pub mod body_normalization;
+pub mod cache_bust_method;
+pub mod calibration_mode;
+pub mod concurrency;
+pub mod cypher_text_split;
+pub mod encoding_option;
+pub mod escaped_bytes;
+pub mod guess_order;
+pub mod guess_strategy;
+pub mod header;
+pub mod http_version;
+pub mod keyword_occurrence;
+pub mod known_plain_text;
+pub mod normalization_rules;
+pub mod padding_error_overrides;
+pub mod padding_error_pattern;
+pub mod request_timeout;
+pub mod segmentation;
+pub mod sticky_session_source;
+pub mod thread_count;
+pub mod thread_delay;
+pub mod user_agent;
+pub mod verdict_source;
+
+use std::{
+    num::NonZeroUsize,
+    path::PathBuf,
+    sync::{atomic::AtomicBool, Arc},
+    time::Duration,
+};
+
+use getset::Getters;
+use reqwest::{Method, Proxy};
+
+use self::{
+    body_normalization::BodyNormalization, cache_bust_method::CacheBustMethod,
+    calibration_mode::CalibrationMode, concurrency::Concurrency,
+    cypher_text_split::CypherTextSplit, escaped_bytes::EscapedBytes, header::Header,
+    http_version::HttpVersion, keyword_occurrence::KeywordOccurrences,
+    normalization_rules::NormalizationRules, padding_error_overrides::PaddingErrorOverrides,
+    padding_error_pattern::PaddingErrorPattern, request_timeout::RequestTimeout,
+    thread_count::ThreadCount, thread_delay::ThreadDelay, user_agent::UserAgent,
+    verdict_source::VerdictSource,
+};
+
+use crate::oracle::{
+    certificate_pin::CertificatePin, request_budget::RequestBudget, sticky_session::StickySession,
+    traffic_shaper::TrafficShaper, web::har::Har,
+};
+
+#[derive(Debug)]
+pub enum SubConfig {
+    Web(Box<WebConfig>),
+    Script(ScriptConfig),
+    Tcp(Box<TcpConfig>),
+    Plugin(PluginConfig),
+    Lua(LuaConfig),
+    Python(PythonConfig),
+}
+
+/// Config of the web oracle. Defaults are those of the CLI, e.g. `GET` requests with the cypher text at `CTEXT`
+#[derive(Debug, Clone, Getters)]
+pub struct WebConfig {
+    #[getset(get = "pub")]
+    method: Method,
+    #[getset(get = "pub")]
+    post_data: Option<String>,
+    #[getset(get = "pub")]
+    headers: Vec<Header>,
+    #[getset(get = "pub")]
+    keyword: String,
+    #[getset(get = "pub")]
+    keyword_occurrences: KeywordOccurrences,
+    #[getset(get = "pub")]
+    cypher_text_split: Option<CypherTextSplit>,
+    #[getset(get = "pub")]
+    user_agent: UserAgent,
+    #[getset(get = "pub")]
+    proxy: Option<Proxy>,
+    #[getset(get = "pub")]
+    request_timeout: RequestTimeout,
+    #[getset(get = "pub")]
+    redirect: bool,
+    #[getset(get = "pub")]
+    insecure: bool,
+    #[getset(get = "pub")]
+    certificate_pin: Option<Arc<CertificatePin>>,
+    #[getset(get = "pub")]
+    consider_body: bool,
+    #[getset(get = "pub")]
+    body_normalizations: Vec<BodyNormalization>,
+    // applied after `body_normalizations`
+    #[getset(get = "pub")]
+    normalization_rules: NormalizationRules,
+    #[getset(get = "pub")]
+    padding_error_overrides: PaddingErrorOverrides,
+    #[getset(get = "pub")]
+    calibration_mode: CalibrationMode,
+    #[getset(get = "pub")]
+    calibration_passes: NonZeroUsize,
+    #[getset(get = "pub")]
+    thread_delay: ThreadDelay,
+    #[getset(get = "pub")]
+    concurrency: Concurrency,
+    #[getset(get = "pub")]
+    http_version: HttpVersion,
+    // each over a connection of its own. Only used with HTTP/2, as HTTP/1.1 opens a connection per request in flight anyway
+    #[getset(get = "pub")]
+    http2_connections: NonZeroUsize,
+    // shared by all oracles built from the config, so the oracle is only tried over HTTP/3 until it's found unreachable that way
+    #[getset(get = "pub")]
+    http3_unavailable: Arc<AtomicBool>,
+    #[getset(get = "pub")]
+    cache_bust: Option<CacheBustMethod>,
+    #[getset(get = "pub")]
+    sticky_session: Option<Arc<StickySession>>,
+    #[getset(get = "pub")]
+    request_budget: Arc<RequestBudget>,
+    #[getset(get = "pub")]
+    traffic_shaper: Option<Arc<TrafficShaper>>,
+    #[getset(get = "pub")]
+    warm_up: bool,
+    #[getset(get = "pub")]
+    har: Option<Arc<Har>>,
+    #[getset(get = "pub")]
+    dry_run: Option<usize>,
+    // proof of concept which sends the forged cypher text, written once encryption succeeds
+    #[getset(get = "pub")]
+    poc_file: Option<PathBuf>,
+    #[getset(get = "pub")]
+    maintenance_probe: humantime::Duration,
+    // in total, over all maintenance windows
+    #[getset(get = "pub")]
+    maintenance_timeout: humantime::Duration,
+}
+
+/// Config of the script oracle
+#[derive(Debug, Clone, Getters)]
+pub struct ScriptConfig {
+    #[getset(get = "pub")]
+    thread_delay: ThreadDelay,
+    #[getset(get = "pub")]
+    persistent: bool,
+    #[getset(get = "pub")]
+    verdict_source: VerdictSource,
+    // of the answers of a persistent script
+    #[getset(get = "pub")]
+    request_timeout: RequestTimeout,
+    #[getset(get = "pub")]
+    request_budget: Arc<RequestBudget>,
+    #[getset(get = "pub")]
+    traffic_shaper: Option<Arc<TrafficShaper>>,
+}
+
+/// Config of the plugin oracle
+#[derive(Debug, Clone, Getters)]
+pub struct PluginConfig {
+    // handed to the plugin as is when it's opened
+    #[getset(get = "pub")]
+    plugin_config: Option<String>,
+    // of requests sent by WASM plugins
+    #[getset(get = "pub")]
+    request_timeout: RequestTimeout,
+    #[getset(get = "pub")]
+    insecure: bool,
+    // which WASM plugins may send requests to. They may send none if empty
+    #[getset(get = "pub")]
+    allowed_hosts: Vec<String>,
+    #[getset(get = "pub")]
+    thread_delay: ThreadDelay,
+    #[getset(get = "pub")]
+    request_budget: Arc<RequestBudget>,
+    #[getset(get = "pub")]
+    traffic_shaper: Option<Arc<TrafficShaper>>,
+}
+
+/// Config of the Lua oracle
+#[derive(Debug, Clone, Getters)]
+pub struct LuaConfig {
+    // of requests sent by the script with `rustpad.http`
+    #[getset(get = "pub")]
+    request_timeout: RequestTimeout,
+    #[getset(get = "pub")]
+    insecure: bool,
+    #[getset(get = "pub")]
+    thread_delay: ThreadDelay,
+    #[getset(get = "pub")]
+    request_budget: Arc<RequestBudget>,
+    #[getset(get = "pub")]
+    traffic_shaper: Option<Arc<TrafficShaper>>,
+}
+
+/// Config of the Python oracle
+#[derive(Debug, Clone, Getters)]
+pub struct PythonConfig {
+    #[getset(get = "pub")]
+    thread_delay: ThreadDelay,
+    #[getset(get = "pub")]
+    request_budget: Arc<RequestBudget>,
+    #[getset(get = "pub")]
+    traffic_shaper: Option<Arc<TrafficShaper>>,
+}
+
+/// Config of the TCP oracle. The template to send and the padding error to recognise have no default
+#[derive(Debug, Clone, Getters)]
+pub struct TcpConfig {
+    #[getset(get = "pub")]
+    send_template: EscapedBytes,
+    #[getset(get = "pub")]
+    keyword: String,
+    #[getset(get = "pub")]
+    padding_error: PaddingErrorPattern,
+    #[getset(get = "pub")]
+    response_delimiter: Option<EscapedBytes>,
+    #[getset(get = "pub")]
+    tls: bool,
+    #[getset(get = "pub")]
+    insecure: bool,
+    #[getset(get = "pub")]
+    certificate_pin: Option<Arc<CertificatePin>>,
+    #[getset(get = "pub")]
+    request_timeout: RequestTimeout,
+    #[getset(get = "pub")]
+    thread_delay: ThreadDelay,
+    #[getset(get = "pub")]
+    request_budget: Arc<RequestBudget>,
+    #[getset(get = "pub")]
+    traffic_shaper: Option<Arc<TrafficShaper>>,
+}
+
+impl Default for WebConfig {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl WebConfig {
+    pub fn new() -> Self {
+        Self {
+            method: Method::GET,
+            post_data: None,
+            headers: Vec::new(),
+            keyword: "CTEXT".to_string(),
+            keyword_occurrences: KeywordOccurrences::default(),
+            cypher_text_split: None,
+            user_agent: UserAgent::default(),
+            proxy: None,
+            request_timeout: RequestTimeout::default(),
+            redirect: false,
+            insecure: false,
+            certificate_pin: None,
+            consider_body: false,
+            body_normalizations: Vec::new(),
+            normalization_rules: NormalizationRules::default(),
+            padding_error_overrides: PaddingErrorOverrides::new(None, None, None, None),
+            calibration_mode: CalibrationMode::default(),
+            calibration_passes: NonZeroUsize::MIN,
+            thread_delay: ThreadDelay::default(),
+            concurrency: Concurrency::from(&ThreadCount::default()),
+            http_version: HttpVersion::default(),
+            http2_connections: NonZeroUsize::new(4).expect("4 is non-zero"),
+            http3_unavailable: Arc::new(AtomicBool::new(false)),
+            cache_bust: None,
+            sticky_session: None,
+            request_budget: Arc::new(RequestBudget::default()),
+            traffic_shaper: None,
+            warm_up: false,
+            har: None,
+            dry_run: None,
+            poc_file: None,
+            maintenance_probe: Duration::from_secs(60).into(),
+            maintenance_timeout: Duration::from_secs(60 * 60).into(),
+        }
+    }
+
+    pub fn with_method(mut self, method: Method) -> Self {
+        self.method = method;
+        self
+    }
+
+    pub fn with_post_data(mut self, post_data: Option<String>) -> Self {
+        self.post_data = post_data;
+        self
+    }
+
+    pub fn with_headers(mut self, headers: Vec<Header>) -> Self {
+        self.headers = headers;
+        self
+    }
+
+    pub fn with_keyword(mut self, keyword: String) -> Self {
+        self.keyword = keyword;
+        self
+    }
+
+    pub fn with_keyword_occurrences(mut self, keyword_occurrences: KeywordOccurrences) -> Self {
+        self.keyword_occurrences = keyword_occurrences;
+        self
+    }
+
+    pub fn with_cypher_text_split(mut self, cypher_text_split: Option<CypherTextSplit>) -> Self {
+        self.cypher_text_split = cypher_text_split;
+        self
+    }
+
+    pub fn with_user_agent(mut self, user_agent: UserAgent) -> Self {
+        self.user_agent = user_agent;
+        self
+    }
+
+    pub fn with_proxy(mut self, proxy: Option<Proxy>) -> Self {
+        self.proxy = proxy;
+        self
+    }
+
+    pub fn with_request_timeout(mut self, request_timeout: RequestTimeout) -> Self {
+        self.request_timeout = request_timeout;
+        self
+    }
+
+    pub fn with_redirect(mut self, redirect: bool) -> Self {
+        self.redirect = redirect;
+        self
+    }
+
+    pub fn with_insecure(mut self, insecure: bool) -> Self {
+        self.insecure = insecure;
+        self
+    }
+
+    pub fn with_certificate_pin(mut self, certificate_pin: Option<CertificatePin>) -> Self {
+        self.certificate_pin = certificate_pin.map(Arc::new);
+        self
+    }
+
+    pub fn with_consider_body(mut self, consider_body: bool) -> Self {
+        self.consider_body = consider_body;
+        self
+    }
+
+    pub fn with_body_normalizations(mut self, body_normalizations: Vec<BodyNormalization>) -> Self {
+        self.body_normalizations = body_normalizations;
+        self
+    }
+
+    pub fn with_normalization_rules(mut self, normalization_rules: NormalizationRules) -> Self {
+        self.normalization_rules = normalization_rules;
+        self
+    }
+
+    pub fn with_padding_error_overrides(
+        mut self,
+        padding_error_overrides: PaddingErrorOverrides,
+    ) -> Self {
+        self.padding_error_overrides = padding_error_overrides;
+        self
+    }
+
+    pub fn with_calibration_mode(mut self, calibration_mode: CalibrationMode) -> Self {
+        self.calibration_mode = calibration_mode;
+        self
+    }
+
+    pub fn with_calibration_passes(mut self, calibration_passes: NonZeroUsize) -> Self {
+        self.calibration_passes = calibration_passes;
+        self
+    }
+
+    pub fn with_thread_delay(mut self, thread_delay: ThreadDelay) -> Self {
+        self.thread_delay = thread_delay;
+        self
+    }
+
+    pub fn with_concurrency(mut self, concurrency: Concurrency) -> Self {
+        self.concurrency = concurrency;
+        self
+    }
+
+    pub fn with_http_version(mut self, http_version: HttpVersion) -> Self {
+        self.http_version = http_version;
+        self
+    }
+
+    pub fn with_http2_connections(mut self, http2_connections: NonZeroUsize) -> Self {
+        self.http2_connections = http2_connections;
+        self
+    }
+
+    pub fn with_cache_bust(mut self, cache_bust: Option<CacheBustMethod>) -> Self {
+        self.cache_bust = cache_bust;
+        self
+    }
+
+    pub fn with_sticky_session(mut self, sticky_session: Option<StickySession>) -> Self {
+        self.sticky_session = sticky_session.map(Arc::new);
+        self
+    }
+
+    pub fn with_request_budget(mut self, request_budget: RequestBudget) -> Self {
+        self.request_budget = Arc::new(request_budget);
+        self
+    }
+
+    pub fn with_traffic_shaper(mut self, traffic_shaper: Option<TrafficShaper>) -> Self {
+        self.traffic_shaper = traffic_shaper.map(Arc::new);
+        self
+    }
+
+    pub fn with_warm_up(mut self, warm_up: bool) -> Self {
+        self.warm_up = warm_up;
+        self
+    }
+
+    pub fn with_har(mut self, har: Option<Har>) -> Self {
+        self.har = har.map(Arc::new);
+        self
+    }
+
+    pub fn with_dry_run(mut self, dry_run: Option<usize>) -> Self {
+        self.dry_run = dry_run;
+        self
+    }
+
+    pub fn with_poc_file(mut self, poc_file: Option<PathBuf>) -> Self {
+        self.poc_file = poc_file;
+        self
+    }
+
+    pub fn with_maintenance_probe(mut self, maintenance_probe: humantime::Duration) -> Self {
+        self.maintenance_probe = maintenance_probe;
+        self
+    }
+
+    pub fn with_maintenance_timeout(mut self, maintenance_timeout: humantime::Duration) -> Self {
+        self.maintenance_timeout = maintenance_timeout;
+        self
+    }
+}
+
+impl Default for ScriptConfig {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ScriptConfig {
+    pub fn new() -> Self {
+        Self {
+            thread_delay: ThreadDelay::default(),
+            persistent: false,
+            verdict_source: VerdictSource::ExitCode,
+            request_timeout: RequestTimeout::default(),
+            request_budget: Arc::new(RequestBudget::default()),
+            traffic_shaper: None,
+        }
+    }
+
+    pub fn with_thread_delay(mut self, thread_delay: ThreadDelay) -> Self {
+        self.thread_delay = thread_delay;
+        self
+    }
+
+    pub fn with_persistent(mut self, persistent: bool) -> Self {
+        self.persistent = persistent;
+        self
+    }
+
+    pub fn with_verdict_source(mut self, verdict_source: VerdictSource) -> Self {
+        self.verdict_source = verdict_source;
+        self
+    }
+
+    pub fn with_request_timeout(mut self, request_timeout: RequestTimeout) -> Self {
+        self.request_timeout = request_timeout;
+        self
+    }
+
+    pub fn with_request_budget(mut self, request_budget: RequestBudget) -> Self {
+        self.request_budget = Arc::new(request_budget);
+        self
+    }
+
+    pub fn with_traffic_shaper(mut self, traffic_shaper: Option<TrafficShaper>) -> Self {
+        self.traffic_shaper = traffic_shaper.map(Arc::new);
+        self
+    }
+}
+
+impl Default for PluginConfig {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PluginConfig {
+    pub fn new() -> Self {
+        Self {
+            plugin_config: None,
+            request_timeout: RequestTimeout::default(),
+            insecure: false,
+            allowed_hosts: Vec::new(),
+            thread_delay: ThreadDelay::default(),
+            request_budget: Arc::new(RequestBudget::default()),
+            traffic_shaper: None,
+        }
+    }
+
+    pub fn with_plugin_config(mut self, plugin_config: Option<String>) -> Self {
+        self.plugin_config = plugin_config;
+        self
+    }
+
+    pub fn with_request_timeout(mut self, request_timeout: RequestTimeout) -> Self {
+        self.request_timeout = request_timeout;
+        self
+    }
+
+    pub fn with_insecure(mut self, insecure: bool) -> Self {
+        self.insecure = insecure;
+        self
+    }
+
+    pub fn with_allowed_hosts(mut self, allowed_hosts: Vec<String>) -> Self {
+        self.allowed_hosts = allowed_hosts;
+        self
+    }
+
+    pub fn with_thread_delay(mut self, thread_delay: ThreadDelay) -> Self {
+        self.thread_delay = thread_delay;
+        self
+    }
+
+    pub fn with_request_budget(mut self, request_budget: RequestBudget) -> Self {
+        self.request_budget = Arc::new(request_budget);
+        self
+    }
+
+    pub fn with_traffic_shaper(mut self, traffic_shaper: Option<TrafficShaper>) -> Self {
+        self.traffic_shaper = traffic_shaper.map(Arc::new);
+        self
+    }
+}
+
+impl Default for LuaConfig {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LuaConfig {
+    pub fn new() -> Self {
+        Self {
+            request_timeout: RequestTimeout::default(),
+            insecure: false,
+            thread_delay: ThreadDelay::default(),
+            request_budget: Arc::new(RequestBudget::default()),
+            traffic_shaper: None,
+        }
+    }
+
+    pub fn with_request_timeout(mut self, request_timeout: RequestTimeout) -> Self {
+        self.request_timeout = request_timeout;
+        self
+    }
+
+    pub fn with_insecure(mut self, insecure: bool) -> Self {
+        self.insecure = insecure;
+        self
+    }
+
+    pub fn with_thread_delay(mut self, thread_delay: ThreadDelay) -> Self {
+        self.thread_delay = thread_delay;
+        self
+    }
+
+    pub fn with_request_budget(mut self, request_budget: RequestBudget) -> Self {
+        self.request_budget = Arc::new(request_budget);
+        self
+    }
+
+    pub fn with_traffic_shaper(mut self, traffic_shaper: Option<TrafficShaper>) -> Self {
+        self.traffic_shaper = traffic_shaper.map(Arc::new);
+        self
+    }
+}
+
+impl Default for PythonConfig {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PythonConfig {
+    pub fn new() -> Self {
+        Self {
+            thread_delay: ThreadDelay::default(),
+            request_budget: Arc::new(RequestBudget::default()),
+            traffic_shaper: None,
+        }
+    }
+
+    pub fn with_thread_delay(mut self, thread_delay: ThreadDelay) -> Self {
+        self.thread_delay = thread_delay;
+        self
+    }
+
+    pub fn with_request_budget(mut self, request_budget: RequestBudget) -> Self {
+        self.request_budget = Arc::new(request_budget);
+        self
+    }
+
+    pub fn with_traffic_shaper(mut self, traffic_shaper: Option<TrafficShaper>) -> Self {
+        self.traffic_shaper = traffic_shaper.map(Arc::new);
+        self
+    }
+}
+
+impl TcpConfig {
+    pub fn new(send_template: EscapedBytes, padding_error: PaddingErrorPattern) -> Self {
+        Self {
+            send_template,
+            keyword: "CTEXT".to_string(),
+            padding_error,
+            response_delimiter: None,
+            tls: false,
+            insecure: false,
+            certificate_pin: None,
+            request_timeout: RequestTimeout::default(),
+            thread_delay: ThreadDelay::default(),
+            request_budget: Arc::new(RequestBudget::default()),
+            traffic_shaper: None,
+        }
+    }
+
+    pub fn with_keyword(mut self, keyword: String) -> Self {
+        self.keyword = keyword;
+        self
+    }
+
+    pub fn with_response_delimiter(mut self, response_delimiter: Option<EscapedBytes>) -> Self {
+        self.response_delimiter = response_delimiter;
+        self
+    }
+
+    pub fn with_tls(mut self, tls: bool) -> Self {
+        self.tls = tls;
+        self
+    }
+
+    pub fn with_insecure(mut self, insecure: bool) -> Self {
+        self.insecure = insecure;
+        self
+    }
+
+    pub fn with_certificate_pin(mut self, certificate_pin: Option<CertificatePin>) -> Self {
+        self.certificate_pin = certificate_pin.map(Arc::new);
+        self
+    }
+
+    pub fn with_request_timeout(mut self, request_timeout: RequestTimeout) -> Self {
+        self.request_timeout = request_timeout;
+        self
+    }
+
+    pub fn with_thread_delay(mut self, thread_delay: ThreadDelay) -> Self {
+        self.thread_delay = thread_delay;
+        self
+    }
+
+    pub fn with_request_budget(mut self, request_budget: RequestBudget) -> Self {
+        self.request_budget = Arc::new(request_budget);
+        self
+    }
+
+    pub fn with_traffic_shaper(mut self, traffic_shaper: Option<TrafficShaper>) -> Self {
+        self.traffic_shaper = traffic_shaper.map(Arc::new);
+        self
+    }
+}
@@ -0,0 +1,152 @@
+use std::{fs, path::Path, str::FromStr};
+
+use anyhow::{anyhow, Context, Result};
+use regex::Regex;
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+
+use super::body_normalization::BodyNormalization;
+
+/// Rule which removes dynamic content from a response before it's classified. See `NormalizationRules`
+#[derive(Debug, Clone)]
+pub enum NormalizationRule {
+    // remove the header, so it isn't compared
+    StripHeader(HeaderName),
+    // compare the header's value in lowercase
+    LowercaseHeader(HeaderName),
+    // compare the header as well, besides the status, `Location` header and body
+    CompareHeader(HeaderName),
+    // replace matches of the regex. The replacement may refer to capture groups, e.g. `$1`
+    ReplaceBody(Regex, String),
+    // a step of `--normalize-body`
+    NormalizeBody(BodyNormalization),
+}
+
+/// Rules of a normalization rules file, applied in order to every response before it's classified.
+/// Each line holds a rule and its argument, e.g. `strip-header = Location` or `replace-body = csrf=\w+ => csrf=`. Empty lines and lines starting with `#` are skipped.
+#[derive(Debug, Clone, Default)]
+pub struct NormalizationRules(Vec<NormalizationRule>);
+
+impl NormalizationRules {
+    pub fn load(path: &Path) -> Result<Self> {
+        fs::read_to_string(path)
+            .context(format!(
+                "Normalization rules file `{}` failed to open",
+                path.display()
+            ))?
+            .parse()
+            .context(format!(
+                "Normalization rules file `{}` is invalid",
+                path.display()
+            ))
+    }
+
+    /// Whether the body is needed to apply the rules
+    pub fn normalizes_body(&self) -> bool {
+        self.0.iter().any(|rule| {
+            matches!(
+                rule,
+                NormalizationRule::ReplaceBody(..) | NormalizationRule::NormalizeBody(_)
+            )
+        })
+    }
+
+    /// Headers to compare besides `Location`, in the order they were given
+    pub fn compared_headers(&self) -> impl Iterator<Item = &HeaderName> {
+        self.0.iter().filter_map(|rule| match rule {
+            NormalizationRule::CompareHeader(name) => Some(name),
+            _ => None,
+        })
+    }
+
+    pub fn apply_to_headers(&self, headers: &HeaderMap) -> HeaderMap {
+        let mut headers = headers.clone();
+        for rule in &self.0 {
+            match rule {
+                NormalizationRule::StripHeader(name) => {
+                    headers.remove(name);
+                }
+                NormalizationRule::LowercaseHeader(name) => {
+                    // values which can't be lowercased aren't text to begin with, and are left as is
+                    let lowercased: Vec<HeaderValue> = headers
+                        .get_all(name)
+                        .iter()
+                        .map(|value| {
+                            HeaderValue::from_bytes(&value.as_bytes().to_ascii_lowercase())
+                                .unwrap_or_else(|_| value.clone())
+                        })
+                        .collect();
+                    headers.remove(name);
+                    for value in lowercased {
+                        headers.append(name.clone(), value);
+                    }
+                }
+                NormalizationRule::CompareHeader(_)
+                | NormalizationRule::ReplaceBody(..)
+                | NormalizationRule::NormalizeBody(_) => {}
+            }
+        }
+        headers
+    }
+
+    pub fn apply_to_body(&self, body: String) -> String {
+        self.0.iter().fold(body, |body, rule| match rule {
+            NormalizationRule::ReplaceBody(regex, replacement) => {
+                regex.replace_all(&body, replacement.as_str()).into_owned()
+            }
+            NormalizationRule::NormalizeBody(normalization) => normalization.apply(&body),
+            NormalizationRule::StripHeader(_)
+            | NormalizationRule::LowercaseHeader(_)
+            | NormalizationRule::CompareHeader(_) => body,
+        })
+    }
+}
+
+impl FromStr for NormalizationRules {
+    type Err = anyhow::Error;
+
+    fn from_str(content: &str) -> Result<Self> {
+        content
+            .lines()
+            .enumerate()
+            .map(|(line_idx, line)| (line_idx, line.trim()))
+            .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'))
+            .map(|(line_idx, line)| {
+                parse_rule(line).context(format!("Line {}: `{}`", line_idx + 1, line))
+            })
+            .collect::<Result<_>>()
+            .map(Self)
+    }
+}
+
+fn parse_rule(line: &str) -> Result<NormalizationRule> {
+    let (kind, argument) = line
+        .split_once('=')
+        .map(|(kind, argument)| (kind.trim(), argument.trim()))
+        .ok_or_else(|| anyhow!("Expected `<rule> = <argument>`"))?;
+    let header_name = || {
+        HeaderName::from_str(argument).context(format!("`{}` is not a valid header name", argument))
+    };
+
+    match kind.to_lowercase().replace('_', "-").as_str() {
+        "strip-header" => Ok(NormalizationRule::StripHeader(header_name()?)),
+        "lowercase-header" => Ok(NormalizationRule::LowercaseHeader(header_name()?)),
+        "compare-header" => Ok(NormalizationRule::CompareHeader(header_name()?)),
+        "replace-body" => {
+            // the regex may contain `=>` itself, the replacement rarely does
+            let (pattern, replacement) = argument
+                .rsplit_once("=>")
+                .ok_or_else(|| anyhow!("Expected `replace-body = <regex> => <replacement>`"))?;
+            let pattern = pattern.trim();
+            Ok(NormalizationRule::ReplaceBody(
+                Regex::new(pattern)
+                    .context(format!("`{}` is not a valid regular expression", pattern))?,
+                replacement.trim().to_string(),
+            ))
+        }
+        "normalize-body" => Ok(NormalizationRule::NormalizeBody(argument.parse()?)),
+        _ => Err(anyhow!(
+            "`{}` is not a normalization rule. Expected one of: [strip-header, lowercase-header, compare-header, replace-body, normalize-body]",
+            kind
+        )),
+    }
+}
@@ -0,0 +1,41 @@
+use getset::Getters;
+use reqwest::{header::HeaderValue, StatusCode};
+
+use super::padding_error_pattern::PaddingErrorPattern;
+
+/// Parts of the web oracle's response to incorrect padding, as given by the user. If any is given, calibration is skipped, and only the given parts are compared.
+#[derive(Debug, Clone, Getters)]
+pub struct PaddingErrorOverrides {
+    #[getset(get = "pub")]
+    status: Option<StatusCode>,
+    #[getset(get = "pub")]
+    location: Option<HeaderValue>,
+    // size of the body, in bytes
+    #[getset(get = "pub")]
+    size: Option<u64>,
+    #[getset(get = "pub")]
+    body_pattern: Option<PaddingErrorPattern>,
+}
+
+impl PaddingErrorOverrides {
+    pub fn new(
+        status: Option<StatusCode>,
+        location: Option<HeaderValue>,
+        size: Option<u64>,
+        body_pattern: Option<PaddingErrorPattern>,
+    ) -> Self {
+        Self {
+            status,
+            location,
+            size,
+            body_pattern,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.status.is_none()
+            && self.location.is_none()
+            && self.size.is_none()
+            && self.body_pattern.is_none()
+    }
+}
@@ -0,0 +1,47 @@
+use std::{
+    hash::{Hash, Hasher},
+    ops::Deref,
+    str::FromStr,
+};
+
+use anyhow::{Context, Result};
+use regex::bytes::{Regex, RegexBuilder};
+
+/// Pattern matching a response which indicates incorrect padding. Responses are raw bytes, so the pattern is matched byte-wise. Use `\xHH` to match arbitrary bytes.
+/// Patterns are equal if their source is, which allows storing them in the cache.
+#[derive(Debug, Clone)]
+pub struct PaddingErrorPattern(Regex);
+
+impl FromStr for PaddingErrorPattern {
+    type Err = anyhow::Error;
+
+    fn from_str(pattern: &str) -> Result<Self> {
+        RegexBuilder::new(pattern)
+            .unicode(false)
+            .build()
+            .map(Self)
+            .context(format!("`{}` is not a valid regular expression", pattern))
+    }
+}
+
+impl Deref for PaddingErrorPattern {
+    type Target = Regex;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl PartialEq for PaddingErrorPattern {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.as_str() == other.0.as_str()
+    }
+}
+
+impl Eq for PaddingErrorPattern {}
+
+impl Hash for PaddingErrorPattern {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.as_str().hash(state);
+    }
+}
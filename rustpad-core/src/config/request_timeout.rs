@@ -3,7 +3,7 @@ use std::{fmt::Display, ops::Deref, str::FromStr, time::Duration};
 use anyhow::{Context, Result};
 
 #[derive(Debug, Clone)]
-pub(crate) struct RequestTimeout(Duration);
+pub struct RequestTimeout(Duration);
 
 impl Default for RequestTimeout {
     fn default() -> Self {
@@ -0,0 +1,60 @@
+use std::{num::NonZeroUsize, ops::Range};
+
+use anyhow::{anyhow, Result};
+
+use super::escaped_bytes::EscapedBytes;
+
+/// Some tokens concatenate multiple independent cypher texts, e.g. `IV1||CT1||IV2||CT2`. Each segment is attacked separately.
+/// Segments are found in the decoded token, either by a delimiter between them or by their fixed length.
+#[derive(Debug, Clone)]
+pub enum Segmentation {
+    Delimiter(EscapedBytes),
+    Length(NonZeroUsize),
+}
+
+impl Segmentation {
+    pub fn new(
+        delimiter: Option<&EscapedBytes>,
+        length: Option<NonZeroUsize>,
+    ) -> Result<Option<Self>> {
+        match (delimiter, length) {
+            (Some(delimiter), _) if delimiter.is_empty() => Err(anyhow!(
+                "The delimiter between segments can't be empty. See `--split-pattern` for extra info"
+            )),
+            (Some(delimiter), _) => Ok(Some(Self::Delimiter(delimiter.clone()))),
+            (None, Some(length)) => Ok(Some(Self::Length(length))),
+            (None, None) => Ok(None),
+        }
+    }
+
+    /// Byte ranges of the segments in `data`. Delimiters are excluded, and empty segments skipped, e.g. due to a trailing delimiter
+    pub fn split(&self, data: &[u8]) -> Vec<Range<usize>> {
+        let segments = match self {
+            Self::Delimiter(delimiter) => {
+                let mut segments = Vec::new();
+                let mut start = 0;
+                let mut idx = 0;
+                while idx + delimiter.len() <= data.len() {
+                    if data[idx..].starts_with(delimiter) {
+                        segments.push(start..idx);
+                        idx += delimiter.len();
+                        start = idx;
+                    } else {
+                        idx += 1;
+                    }
+                }
+                segments.push(start..data.len());
+                segments
+            }
+            Self::Length(length) => (0..data.len())
+                .step_by(length.get())
+                .map(|start| start..(start + length.get()).min(data.len()))
+                .collect(),
+        };
+
+        segments
+            .into_iter()
+            .filter(|segment| !segment.is_empty())
+            .collect()
+    }
+}
@@ -0,0 +1,53 @@
+use std::{fmt::Display, str::FromStr};
+
+use anyhow::{anyhow, Result};
+
+/// Where the load balancer keeps the backend a client is pinned to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StickySessionSource {
+    // any cookie with the name of a well-known affinity cookie
+    Auto,
+    Cookie(String),
+    // response header of which the value is sent back as a request header of the same name
+    Header(String),
+}
+
+impl Display for StickySessionSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StickySessionSource::Auto => write!(f, "auto"),
+            StickySessionSource::Cookie(name) => write!(f, "cookie:{}", name),
+            StickySessionSource::Header(name) => write!(f, "header:{}", name),
+        }
+    }
+}
+
+impl FromStr for StickySessionSource {
+    type Err = anyhow::Error;
+
+    fn from_str(input: &str) -> Result<Self> {
+        if input.eq_ignore_ascii_case("auto") {
+            return Ok(Self::Auto);
+        }
+
+        let (kind, name) = input.split_once(':').ok_or_else(|| {
+            anyhow!(
+                "`{}` is not a sticky session source. Expected one of: [auto, cookie:<name>, header:<name>]",
+                input
+            )
+        })?;
+        let name = name.trim();
+        if name.is_empty() {
+            return Err(anyhow!("Sticky session source `{}` lacks a name", input));
+        }
+
+        match kind.to_lowercase().as_str() {
+            "cookie" => Ok(Self::Cookie(name.to_string())),
+            "header" => Ok(Self::Header(name.to_string())),
+            _ => Err(anyhow!(
+                "`{}` is not a sticky session source. Expected one of: [auto, cookie:<name>, header:<name>]",
+                input
+            )),
+        }
+    }
+}
@@ -0,0 +1,47 @@
+use std::{fmt::Display, str::FromStr};
+
+use anyhow::{anyhow, Result};
+use itertools::Itertools;
+
+/// Where a script oracle communicates its verdict.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VerdictSource {
+    // `0` for correct padding, anything else otherwise
+    ExitCode,
+    // `VALID`/`INVALID`, or a JSON object, on stdout
+    Stdout,
+}
+
+impl VerdictSource {
+    fn variants() -> &'static [Self] {
+        &[Self::ExitCode, Self::Stdout]
+    }
+}
+
+impl Display for VerdictSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            VerdictSource::ExitCode => write!(f, "exit-code"),
+            VerdictSource::Stdout => write!(f, "stdout"),
+        }
+    }
+}
+
+impl FromStr for VerdictSource {
+    type Err = anyhow::Error;
+
+    fn from_str(input: &str) -> Result<Self> {
+        match input.to_lowercase().as_str() {
+            "exit-code" | "exit_code" | "exit" | "status" => Ok(Self::ExitCode),
+            "stdout" | "output" => Ok(Self::Stdout),
+            _ => Err(anyhow!(
+                "`{}` is not a source of script verdicts. Expected one of: [{}]",
+                input,
+                Self::variants()
+                    .iter()
+                    .map(|variant| variant.to_string())
+                    .join(", ")
+            )),
+        }
+    }
+}
@@ -0,0 +1,102 @@
+use std::str::FromStr;
+
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    block::Block,
+    config::{base64_padding::Base64Padding, encoding_option::EncodingOption},
+};
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Encoding {
+    Hex,
+    // whether `=` padding is used, which is mirrored in forged cypher texts as some targets reject the other style
+    Base64 { padded: bool },
+    Base64Url { padded: bool },
+}
+
+pub trait Encode<'a> {
+    type Blocks: IntoIterator<Item = &'a Block>;
+
+    fn encode(&'a self) -> String;
+    /// The bytes which `encode` encodes
+    fn raw_bytes(&'a self) -> Vec<u8>;
+
+    fn blocks(&'a self) -> Self::Blocks;
+    fn url_encoded(&self) -> &bool;
+    fn used_encoding(&self) -> &Encoding;
+}
+
+pub trait AmountBlocksTrait {
+    fn amount_blocks(&self) -> usize;
+}
+
+impl Encoding {
+    /// Encode `raw_bytes` with exactly the variant, and padding, of this encoding
+    pub fn encode(&self, raw_bytes: &[u8]) -> String {
+        match self {
+            Encoding::Hex => hex::encode(raw_bytes),
+            Encoding::Base64 { padded } => {
+                base64::encode_config(raw_bytes, base64::STANDARD.pad(*padded))
+            }
+            Encoding::Base64Url { padded } => {
+                base64::encode_config(raw_bytes, base64::URL_SAFE.pad(*padded))
+            }
+        }
+    }
+
+    /// Same encoding, but with the padding style of `encoded_data`
+    pub fn with_padding_of(self, encoded_data: &str) -> Self {
+        self.with_padding(encoded_data.ends_with('='))
+    }
+
+    /// Same encoding, but with the padding style overridden by the user, if any
+    pub fn with_padding_override(self, base64_padding: Base64Padding) -> Self {
+        match base64_padding {
+            Base64Padding::Keep => self,
+            Base64Padding::Always => self.with_padding(true),
+            Base64Padding::Never => self.with_padding(false),
+        }
+    }
+
+    fn with_padding(self, padded: bool) -> Self {
+        match self {
+            Encoding::Hex => Encoding::Hex,
+            Encoding::Base64 { .. } => Encoding::Base64 { padded },
+            Encoding::Base64Url { .. } => Encoding::Base64Url { padded },
+        }
+    }
+}
+
+impl FromStr for Encoding {
+    type Err = anyhow::Error;
+
+    fn from_str(input: &str) -> Result<Self> {
+        if input == "hex" {
+            Ok(Encoding::Hex)
+        } else if input == "base64" {
+            Ok(Encoding::Base64 { padded: true })
+        } else if input == "base64url" {
+            Ok(Encoding::Base64Url { padded: true })
+        } else {
+            Err(anyhow!("Unknown encoding: {}", input))
+        }
+    }
+}
+
+impl TryFrom<&EncodingOption> for Encoding {
+    type Error = anyhow::Error;
+
+    fn try_from(encoding: &EncodingOption) -> Result<Self> {
+        match encoding {
+            EncodingOption::Hex => Ok(Self::Hex),
+            EncodingOption::Base64 => Ok(Self::Base64 { padded: true }),
+            EncodingOption::Base64Url => Ok(Self::Base64Url { padded: true }),
+            EncodingOption::Auto => Err(anyhow!(
+                "`EncodingOption::Auto` cannot be converted into a specific `Encoding`"
+            )),
+        }
+    }
+}
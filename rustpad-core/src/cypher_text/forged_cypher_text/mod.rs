@@ -0,0 +1,300 @@
+pub mod solved;
+
+use getset::Getters;
+
+use crate::{
+    block::{
+        block_size::{BlockSize, BlockSizeTrait},
+        padding_scheme::PaddingScheme,
+    },
+    session::BlockProgress,
+};
+
+use self::solved::SolvedForgedCypherText;
+
+use super::{
+    AmountBlocksTrait, Block, CypherText, Encode, Encoding, Surroundings, NO_SURROUNDINGS,
+};
+
+pub enum ByteLockResult<'a> {
+    BytesLeft(ForgedCypherText<'a>),
+    Solved(SolvedForgedCypherText<'a>),
+}
+
+#[derive(Debug, Clone, Getters)]
+pub struct ForgedCypherText<'a> {
+    original_blocks: &'a [Block],
+    url_encoded: bool,
+    used_encoding: Encoding,
+    padding_scheme: PaddingScheme,
+    // only the forged block and the block to decrypt are sent otherwise
+    prefix_in_requests: bool,
+    surroundings: &'a Surroundings,
+
+    #[getset(get = "pub")]
+    current_byte_idx: u8,
+    // the forged block is kept as if the oracle validated PKCS7 padding, whichever scheme it actually validates. This makes solutions, and so the cache and intermediates, independent of the padding scheme
+    #[getset(get = "pub")]
+    forged_block_wip: Block,
+    // `forged_block_wip`, with the bytes solved so far adjusted to produce the padding, of the actual scheme, for the current byte. Computed once per byte, instead of for every value the current byte is tested with
+    forged_block_padded: Block,
+    forged_block_solution: Block,
+}
+
+impl<'a> ForgedCypherText<'a> {
+    pub fn from_cypher_text(
+        cypher_text: &'a CypherText,
+        block_to_decrypt_idx: usize,
+        padding_scheme: PaddingScheme,
+    ) -> Self {
+        if block_to_decrypt_idx > cypher_text.amount_blocks() - 1 {
+            panic!(
+                "Tried to create ForgedCypherText to decrypt block {}, but only {} blocks exist in the original cypher text",
+                block_to_decrypt_idx + 1,
+                cypher_text.amount_blocks()
+            );
+        }
+
+        let original_blocks = &cypher_text.blocks()[..block_to_decrypt_idx + 1];
+
+        let block_size = cypher_text.block_size();
+        let forged_cypher_text = Self {
+            original_blocks,
+            url_encoded: *cypher_text.url_encoded(),
+            used_encoding: *cypher_text.used_encoding(),
+            padding_scheme,
+            prefix_in_requests: cypher_text.prefix_in_requests(),
+            surroundings: &cypher_text.surroundings,
+            current_byte_idx: *block_size - 1,
+            forged_block_wip: Block::new(&block_size),
+            forged_block_padded: Block::new(&block_size),
+            forged_block_solution: Block::new(&block_size),
+        };
+
+        forged_cypher_text
+    }
+
+    pub fn from_slice(
+        original_blocks: &'a [Block],
+        block_size: BlockSize,
+        url_encoded: bool,
+        used_encoding: Encoding,
+        padding_scheme: PaddingScheme,
+    ) -> Self {
+        Self {
+            original_blocks,
+            url_encoded,
+            used_encoding,
+            padding_scheme,
+            prefix_in_requests: true,
+            // encryption builds cypher texts from scratch
+            surroundings: &NO_SURROUNDINGS,
+            current_byte_idx: *block_size - 1,
+            forged_block_wip: Block::new(&block_size),
+            forged_block_padded: Block::new(&block_size),
+            forged_block_solution: Block::new(&block_size),
+        }
+    }
+
+    /// Same cypher text, but without any of the forged block's bytes answered
+    pub fn to_unsolved(&self) -> Self {
+        Self {
+            prefix_in_requests: self.prefix_in_requests,
+            surroundings: self.surroundings,
+            ..Self::from_slice(
+                self.original_blocks,
+                self.block_size(),
+                self.url_encoded,
+                self.used_encoding,
+                self.padding_scheme,
+            )
+        }
+    }
+
+    /// Same cypher text, but continuing with the bytes which were solved in an interrupted attack. `progress` must not be of a solved block
+    pub fn to_resumed(&self, progress: &BlockProgress) -> Self {
+        let mut resumed = self.to_unsolved();
+        resumed.current_byte_idx = *self.block_size() - 1 - *progress.bytes_answered();
+        resumed.forged_block_wip = progress.forged_block_solution().clone();
+        resumed.forged_block_solution = progress.forged_block_solution().clone();
+        resumed.forged_block_padded = resumed.forged_block_wip.to_adjusted_for_padding(
+            *self.block_size() - resumed.current_byte_idx,
+            &self.padding_scheme,
+        );
+
+        resumed
+    }
+
+    /// The bytes solved so far, to be saved in a session
+    pub fn to_progress(&self) -> BlockProgress {
+        BlockProgress::new(self.forged_block_solution.clone(), self.bytes_answered())
+    }
+
+    pub fn set_current_byte(&mut self, value: u8) -> &mut Self {
+        let idx = self.current_byte_idx as usize;
+        let pad_size = *self.block_size() - self.current_byte_idx;
+
+        // the value is sent as is. Only its PKCS7 equivalent is kept
+        self.forged_block_padded.set_byte(idx, value);
+        self.forged_block_wip.set_byte(
+            idx,
+            value
+                ^ self
+                    .padding_scheme
+                    .padding_byte(self.forged_block_wip.len(), idx, pad_size)
+                ^ pad_size,
+        );
+
+        self
+    }
+
+    /// Value of the current byte which results in valid padding, if the plain text byte it decrypts to is `plain_text_byte`
+    pub fn value_for_plain_text(&self, plain_text_byte: u8) -> u8 {
+        let idx = self.current_byte_idx as usize;
+        let pad_size = *self.block_size() - self.current_byte_idx;
+        // -2 to get the original of the forged block
+        let intermediate_byte =
+            plain_text_byte ^ self.original_blocks[self.original_blocks.len() - 2][idx];
+
+        intermediate_byte
+            ^ self
+                .padding_scheme
+                .padding_byte(self.forged_block_wip.len(), idx, pad_size)
+    }
+
+    /// Plain text byte which the current value decrypts to, if its padding is valid
+    pub fn current_plain_text_byte(&self) -> u8 {
+        let idx = self.current_byte_idx as usize;
+        let pad_size = *self.block_size() - self.current_byte_idx;
+
+        self.current_byte_value()
+            ^ self
+                .padding_scheme
+                .padding_byte(self.forged_block_wip.len(), idx, pad_size)
+            ^ self.original_blocks[self.original_blocks.len() - 2][idx]
+    }
+
+    /// Offset of the current byte in the plain text. The IV is not part of the plain text
+    pub fn plain_text_offset(&self) -> usize {
+        (self.amount_blocks() - 2) * *self.block_size() as usize + self.current_byte_idx as usize
+    }
+
+    /// Same cypher text, but with the byte preceding the current one changed. Only for the 1st byte of a block: if its padding is still valid, it wasn't caused by the preceding bytes, e.g. `0x02 0x02` instead of `0x01`.
+    /// Later bytes don't need this, as all bytes after them are forced into the padding.
+    pub fn to_padding_confirmation(&self) -> Option<Self> {
+        if self.bytes_answered() != 0 || self.current_byte_idx == 0 {
+            return None;
+        }
+
+        let mut confirmation = self.clone();
+        confirmation.forged_block_padded[self.current_byte_idx as usize - 1] ^= 0xff;
+        Some(confirmation)
+    }
+
+    /// Same cypher text, with the forged block set to `forged_block_solution` such that the whole block decrypts to padding. The padding is only valid if that solution, e.g. a cached one, is still right
+    pub fn to_solution_check(&self, forged_block_solution: &Block) -> Self {
+        let mut solution_check = self.to_unsolved();
+        solution_check.current_byte_idx = 0;
+        solution_check.forged_block_padded =
+            forged_block_solution.to_adjusted_for_padding(*self.block_size(), &self.padding_scheme);
+
+        solution_check
+    }
+
+    /// Indicate that the current byte's value was found. Advance and save the solution.
+    pub fn lock_byte(mut self) -> ByteLockResult<'a> {
+        let idx = self.current_byte_idx;
+
+        // locking a byte means it's supposedly correct
+        self.forged_block_solution[idx as usize] = self.forged_block_wip[idx as usize];
+
+        // we just solved the last (L<-R) byte so the whole block is solved
+        if idx == 0 {
+            ByteLockResult::Solved(SolvedForgedCypherText::from(self))
+        } else {
+            self.current_byte_idx = idx - 1;
+            // PKCS5/7 padding's value is the same as its length. So the desired padding when testing for the last byte is 0x01. But when testing the 2nd last byte, the last byte must be 0x02. This means that when moving on to the next byte (right to left), all of the previous bytes' solutions must be adjusted.
+            self.forged_block_padded = self.forged_block_wip.to_adjusted_for_padding(
+                *self.block_size() - self.current_byte_idx,
+                &self.padding_scheme,
+            );
+            ByteLockResult::BytesLeft(self)
+        }
+    }
+
+    /// Value which is sent for the current byte
+    pub fn current_byte_value(&self) -> u8 {
+        self.forged_block_padded[self.current_byte_idx as usize]
+    }
+
+    pub fn bytes_answered(&self) -> u8 {
+        (*self.block_size() - 1) - self.current_byte_idx
+    }
+
+    pub fn as_cache_key(&self) -> (Block, Block) {
+        // for decryption to work, at least 2 block must exist. CypherText should have already checked this
+        (
+            self.blocks()[self.amount_blocks() - 2].clone(),
+            self.blocks()[self.amount_blocks() - 1].clone(),
+        )
+    }
+}
+
+impl<'a> Encode<'a> for ForgedCypherText<'a> {
+    type Blocks = &'a [Block];
+
+    fn encode(&'a self) -> String {
+        let encoded_data = self.used_encoding().encode(&self.raw_bytes());
+
+        if *self.url_encoded() {
+            urlencoding::encode(&encoded_data).to_string()
+        } else {
+            encoded_data
+        }
+    }
+
+    fn raw_bytes(&'a self) -> Vec<u8> {
+        // exclude forge-able block and block to decrypt
+        let prefix_blocks = if self.prefix_in_requests {
+            &self.blocks()[..self.amount_blocks() - 2]
+        } else {
+            &[]
+        };
+        let to_decrypt_block = &self.blocks()[self.amount_blocks() - 1];
+
+        let raw_bytes: Vec<u8> = prefix_blocks
+            .iter()
+            .chain([&self.forged_block_padded])
+            .chain([to_decrypt_block])
+            .flat_map(|block| &**block)
+            // blocks are scattered through memory, gotta collect them
+            .cloned()
+            .collect();
+
+        self.surroundings.wrap(raw_bytes)
+    }
+
+    fn blocks(&'a self) -> Self::Blocks {
+        self.original_blocks
+    }
+
+    fn url_encoded(&self) -> &bool {
+        &self.url_encoded
+    }
+
+    fn used_encoding(&self) -> &Encoding {
+        &self.used_encoding
+    }
+}
+
+impl<'a> BlockSizeTrait for ForgedCypherText<'a> {
+    fn block_size(&self) -> BlockSize {
+        self.blocks()[0].block_size()
+    }
+}
+
+impl<'a> AmountBlocksTrait for ForgedCypherText<'a> {
+    fn amount_blocks(&self) -> usize {
+        self.blocks().len()
+    }
+}
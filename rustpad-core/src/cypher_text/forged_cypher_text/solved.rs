@@ -1,34 +1,37 @@
 use getset::Getters;
 
 use crate::{
-    block::Block,
+    block::{padding_scheme::PaddingScheme, Block},
     cypher_text::encode::{AmountBlocksTrait, Encoding},
 };
 
 use super::ForgedCypherText;
 
 #[derive(Getters, Clone, Debug)]
-pub(crate) struct SolvedForgedCypherText<'a> {
-    #[getset(get = "pub(crate)")]
+pub struct SolvedForgedCypherText<'a> {
+    #[getset(get = "pub")]
     original_blocks: &'a [Block],
-    #[getset(get = "pub(crate)")]
+    #[getset(get = "pub")]
     url_encoded: bool,
-    #[getset(get = "pub(crate)")]
+    #[getset(get = "pub")]
     used_encoding: Encoding,
+    #[getset(get = "pub")]
+    padding_scheme: PaddingScheme,
 
-    #[getset(get = "pub(crate)")]
+    #[getset(get = "pub")]
     forged_block_solution: Block,
 }
 
 impl<'a> SolvedForgedCypherText<'a> {
-    pub(crate) fn plain_text_solution(&self) -> String {
-        let plain_text =
-            &self.forged_block_solution.to_intermediate() ^ self.original_forged_block();
+    pub fn plain_text_solution(&self) -> String {
+        self.plain_text_block().to_string()
+    }
 
-        plain_text.to_string()
+    pub fn plain_text_block(&self) -> Block {
+        &self.forged_block_solution.to_intermediate() ^ self.original_forged_block()
     }
 
-    pub(crate) fn block_to_decrypt(&self) -> &Block {
+    pub fn block_to_decrypt(&self) -> &Block {
         &self.original_blocks[self.amount_blocks() - 1]
     }
 
@@ -44,6 +47,7 @@ impl<'a> From<ForgedCypherText<'a>> for SolvedForgedCypherText<'a> {
             original_blocks: forged_cypher_text.original_blocks,
             url_encoded: forged_cypher_text.url_encoded,
             used_encoding: forged_cypher_text.used_encoding,
+            padding_scheme: forged_cypher_text.padding_scheme,
 
             forged_block_solution: forged_cypher_text.forged_block_solution,
         }
@@ -57,6 +61,7 @@ impl<'a> From<(ForgedCypherText<'a>, Block)> for SolvedForgedCypherText<'a> {
             original_blocks: forged_cypher_text.original_blocks,
             url_encoded: forged_cypher_text.url_encoded,
             used_encoding: forged_cypher_text.used_encoding,
+            padding_scheme: forged_cypher_text.padding_scheme,
 
             forged_block_solution,
         }
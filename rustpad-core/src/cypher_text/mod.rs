@@ -0,0 +1,323 @@
+pub mod encode;
+pub mod forged_cypher_text;
+
+use crate::{
+    block::{block_size::BlockSizeTrait, Block},
+    config::{
+        base64_padding::Base64Padding, encoding_option::EncodingOption, segmentation::Segmentation,
+    },
+    error::RustpadError,
+};
+use std::borrow::Cow;
+
+use anyhow::{anyhow, Context, Result};
+
+use crate::block::block_size::BlockSize;
+
+use self::encode::{AmountBlocksTrait, Encode, Encoding};
+
+#[derive(Debug, Clone)]
+pub struct CypherText {
+    blocks: Vec<Block>,
+    url_encoded: bool,
+    used_encoding: Encoding,
+    // whether questions about a block include all blocks preceding the forged one
+    prefix_in_requests: bool,
+    surroundings: Surroundings,
+}
+
+/// Bytes around a segment of a token which concatenates multiple cypher texts. They're sent along, unchanged, with every question about the segment
+#[derive(Debug, Clone, Default)]
+pub struct Surroundings {
+    before: Vec<u8>,
+    after: Vec<u8>,
+}
+
+// for cypher texts which aren't a segment of a token
+static NO_SURROUNDINGS: Surroundings = Surroundings {
+    before: Vec::new(),
+    after: Vec::new(),
+};
+
+impl Surroundings {
+    fn wrap(&self, raw_bytes: Vec<u8>) -> Vec<u8> {
+        if self.before.is_empty() && self.after.is_empty() {
+            return raw_bytes;
+        }
+
+        self.before
+            .iter()
+            .chain(&raw_bytes)
+            .chain(&self.after)
+            .cloned()
+            .collect()
+    }
+}
+
+impl CypherText {
+    /// Decodes the whole input at once, as the encoding is detected from all of it. Forged cypher texts borrow the blocks, so they're held in memory just once
+    pub fn parse(
+        input_data: &str,
+        block_size: &BlockSize,
+        no_iv: bool,
+        encoding: &EncodingOption,
+        no_url_encode: bool,
+        base64_padding: Base64Padding,
+    ) -> Result<Self> {
+        let (decoded_data, used_encoding, url_encoded) =
+            decode_input(input_data, encoding, no_url_encode, base64_padding)?;
+
+        Self::from_decoded(
+            &decoded_data,
+            block_size,
+            no_iv,
+            url_encoded,
+            used_encoding,
+            Surroundings::default(),
+        )
+    }
+
+    /// Parse a token which concatenates multiple cypher texts into a cypher text per segment. The rest of the token surrounds each
+    pub fn parse_segments(
+        input_data: &str,
+        block_size: &BlockSize,
+        no_iv: bool,
+        encoding: &EncodingOption,
+        no_url_encode: bool,
+        base64_padding: Base64Padding,
+        segmentation: &Segmentation,
+    ) -> Result<Vec<Self>> {
+        let (decoded_data, used_encoding, url_encoded) =
+            decode_input(input_data, encoding, no_url_encode, base64_padding)?;
+
+        let segments = segmentation.split(&decoded_data);
+        let amount_segments = segments.len();
+        segments
+            .into_iter()
+            .enumerate()
+            .map(|(idx, segment)| {
+                Self::from_decoded(
+                    &decoded_data[segment.clone()],
+                    block_size,
+                    no_iv,
+                    url_encoded,
+                    used_encoding,
+                    Surroundings {
+                        before: decoded_data[..segment.start].to_vec(),
+                        after: decoded_data[segment.end..].to_vec(),
+                    },
+                )
+                .context(format!(
+                    "Segment {} of {}, at bytes {}-{} of the decoded cypher text, is invalid",
+                    idx + 1,
+                    amount_segments,
+                    segment.start,
+                    segment.end
+                ))
+            })
+            .collect()
+    }
+
+    fn from_decoded(
+        decoded_data: &[u8],
+        block_size: &BlockSize,
+        no_iv: bool,
+        url_encoded: bool,
+        used_encoding: Encoding,
+        surroundings: Surroundings,
+    ) -> Result<Self> {
+        let blocks = split_into_blocks(decoded_data, *block_size)?;
+        let blocks = if no_iv {
+            [Block::new(block_size)].into_iter().chain(blocks).collect()
+        } else {
+            blocks
+        };
+
+        if blocks.len() == 1 {
+            return Err(anyhow!("Decryption impossible with only 1 block. Does this cypher text include an IV? If not, indicate that with `--no-iv`"));
+        }
+
+        Ok(Self {
+            blocks,
+            url_encoded,
+            used_encoding,
+            prefix_in_requests: true,
+            surroundings,
+        })
+    }
+
+    /// Same cypher text, but questions about its blocks only include the forged block and the block to decrypt
+    pub fn without_prefix_in_requests(mut self) -> Self {
+        self.prefix_in_requests = false;
+        self
+    }
+
+    pub fn prefix_in_requests(&self) -> bool {
+        self.prefix_in_requests
+    }
+
+    pub fn from_iter<'a>(
+        blocks: impl IntoIterator<Item = &'a Block>,
+        url_encoded: bool,
+        used_encoding: Encoding,
+    ) -> Self {
+        Self {
+            blocks: blocks.into_iter().cloned().collect(),
+            url_encoded,
+            used_encoding,
+            prefix_in_requests: true,
+            surroundings: Surroundings::default(),
+        }
+    }
+}
+
+impl<'a> Encode<'a> for CypherText {
+    type Blocks = &'a [Block];
+
+    fn encode(&'a self) -> String {
+        let encoded_data = self.used_encoding().encode(&self.raw_bytes());
+
+        if *self.url_encoded() {
+            urlencoding::encode(&encoded_data).to_string()
+        } else {
+            encoded_data
+        }
+    }
+
+    fn raw_bytes(&'a self) -> Vec<u8> {
+        let raw_bytes: Vec<u8> = self
+            .blocks()
+            .iter()
+            .flat_map(|block| &**block)
+            // blocks are scattered through memory, gotta collect them
+            .cloned()
+            .collect();
+
+        self.surroundings.wrap(raw_bytes)
+    }
+
+    fn blocks(&'a self) -> Self::Blocks {
+        &self.blocks[..]
+    }
+
+    fn url_encoded(&self) -> &bool {
+        &self.url_encoded
+    }
+
+    fn used_encoding(&self) -> &Encoding {
+        &self.used_encoding
+    }
+}
+
+impl BlockSizeTrait for CypherText {
+    fn block_size(&self) -> BlockSize {
+        self.blocks()[0].block_size()
+    }
+}
+
+impl AmountBlocksTrait for CypherText {
+    fn amount_blocks(&self) -> usize {
+        self.blocks().len()
+    }
+}
+
+/// Decoded bytes of the cypher text as given, its encoding, and whether it was URL encoded
+fn decode_input(
+    input_data: &str,
+    encoding: &EncodingOption,
+    no_url_encode: bool,
+    base64_padding: Base64Padding,
+) -> Result<(Vec<u8>, Encoding, bool)> {
+    let url_decoded = if no_url_encode {
+        Cow::Borrowed(input_data)
+    } else {
+        // detect url encoding automatically and decode if needed
+        urlencoding::decode(input_data).unwrap_or(Cow::Borrowed(input_data))
+    };
+
+    let (decoded_data, used_encoding) = decode(&url_decoded, encoding)?;
+    Ok((
+        decoded_data,
+        used_encoding.with_padding_override(base64_padding),
+        input_data != url_decoded,
+    ))
+}
+
+fn decode(input_data: &str, encoding: &EncodingOption) -> Result<(Vec<u8>, Encoding)> {
+    fn auto_decode(input_data: &str) -> Result<(Vec<u8>, Encoding)> {
+        if let Ok(decoded_data) = hex::decode(input_data) {
+            return Ok((decoded_data, Encoding::Hex));
+        }
+
+        // the base64 variants only differ in 2 characters. Guessing by trying to decode would pick standard base64 for a URL safe cypher text without those characters, so forged cypher texts could contain characters the target doesn't expect
+        let standard_alphabet = input_data.contains(['+', '/']);
+        let url_safe_alphabet = input_data.contains(['-', '_']);
+        let encoding = match (standard_alphabet, url_safe_alphabet) {
+            (true, true) => {
+                return Err(RustpadError::EncodingMismatch {
+                    reason: format!(
+                        "`{}` mixes characters of base64 (`+`, `/`) and base64url (`-`, `_`)",
+                        input_data
+                    ),
+                }
+                .into())
+            }
+            (false, true) => Encoding::Base64Url { padded: true },
+            // without any of the distinguishing characters, both variants are equal
+            _ => Encoding::Base64 { padded: true },
+        }
+        .with_padding_of(input_data);
+
+        forced_decode(input_data, encoding).map_err(|_| {
+            RustpadError::EncodingMismatch {
+                reason: format!("`{}` has an invalid or unsupported encoding", input_data),
+            }
+            .into()
+        })
+    }
+
+    fn forced_decode(input_data: &str, encoding: Encoding) -> Result<(Vec<u8>, Encoding)> {
+        let decoded_data = match encoding {
+            Encoding::Hex => {
+                hex::decode(input_data).context(format!("`{}` is not valid hex", input_data))
+            }
+            Encoding::Base64 { .. } => base64::decode_config(input_data, base64::STANDARD)
+                .context(format!("`{}` is not valid base64", input_data)),
+            Encoding::Base64Url { .. } => base64::decode_config(input_data, base64::URL_SAFE)
+                .context(format!("`{}` is not valid base64 (URL safe)", input_data)),
+        }
+        .map_err(|e| RustpadError::EncodingMismatch {
+            reason: format!("{:#}", e),
+        })
+        .context("Invalid encoding for cypher text specified")?;
+
+        Ok((decoded_data, encoding.with_padding_of(input_data)))
+    }
+
+    match encoding {
+        EncodingOption::Auto => auto_decode(input_data),
+        _ => {
+            let encoding = Encoding::try_from(encoding)?;
+            forced_decode(input_data, encoding)
+        }
+    }
+}
+
+fn split_into_blocks(decoded_data: &[u8], block_size: BlockSize) -> Result<Vec<Block>> {
+    if !decoded_data.len().is_multiple_of(*block_size as usize) {
+        return Err(RustpadError::EncodingMismatch {
+            reason: format!(
+                "Splitting cypher text into blocks of {} bytes failed. Double check the block size",
+                *block_size
+            ),
+        }
+        .into());
+    }
+
+    let blocks = decoded_data
+        .chunks_exact(*block_size as usize)
+        .map(|chunk| chunk.into())
+        .collect();
+
+    Ok(blocks)
+}
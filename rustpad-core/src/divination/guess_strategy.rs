@@ -0,0 +1,177 @@
+use std::{cmp::Reverse, collections::HashMap, sync::Mutex};
+
+use openssl::rand::rand_bytes;
+
+use crate::{block::Block, config::guess_order::GuessOrder};
+
+/// Decides the order in which the values of a byte are tried. A value decrypts to a known plain text byte, so strategies order plain text bytes.
+/// Bytes are solved from the end of a block to its start, with blocks solved in parallel. One strategy is shared by all blocks of an attack.
+pub trait GuessStrategy: Send + Sync {
+    /// Every plain text byte, in the order they're tried for the byte at `plain_text_offset`. `None` to try the values in numerical order, ignoring the plain text
+    fn plain_text_order(&self, plain_text_offset: usize) -> Option<Vec<u8>>;
+
+    /// Learn from plain text which was solved, starting at `plain_text_offset`. Plain text may be handed over more than once, e.g. per byte and then per block
+    fn learn(&self, _plain_text_offset: usize, _plain_text: &[u8]) {}
+}
+
+/// Every value in numerical order
+pub struct SequentialStrategy;
+
+impl GuessStrategy for SequentialStrategy {
+    fn plain_text_order(&self, _plain_text_offset: usize) -> Option<Vec<u8>> {
+        None
+    }
+}
+
+/// A fresh shuffle for every byte, so the requests for a byte don't follow a pattern
+pub struct RandomStrategy;
+
+impl GuessStrategy for RandomStrategy {
+    fn plain_text_order(&self, _plain_text_offset: usize) -> Option<Vec<u8>> {
+        let mut keys = [0u8; 256 * 4];
+        // a byte is still solved in numerical order, were the CSPRNG to fail
+        rand_bytes(&mut keys).ok()?;
+
+        let mut plain_text_bytes: Vec<u8> = (u8::MIN..=u8::MAX).collect();
+        plain_text_bytes.sort_by_cached_key(|&byte| {
+            let key_idx = byte as usize * 4;
+            u32::from_le_bytes(
+                keys[key_idx..key_idx + 4]
+                    .try_into()
+                    .expect("Slice of 4 bytes"),
+            )
+        });
+        Some(plain_text_bytes)
+    }
+}
+
+/// The plain text bytes of a `GuessOrder`, most expected first
+pub struct FrequencyStrategy {
+    guess_order: GuessOrder,
+}
+
+impl FrequencyStrategy {
+    pub fn new(guess_order: GuessOrder) -> Self {
+        Self { guess_order }
+    }
+}
+
+impl GuessStrategy for FrequencyStrategy {
+    fn plain_text_order(&self, _plain_text_offset: usize) -> Option<Vec<u8>> {
+        self.guess_order.plain_text_bytes()
+    }
+}
+
+/// Markov model of the plain text solved so far in this attack. As bytes are solved from the end of a block, the byte after the one being solved is usually known. The bytes which most often preceded it are tried first, then those of the `GuessOrder`.
+pub struct MarkovStrategy {
+    guess_order: GuessOrder,
+    model: Mutex<MarkovModel>,
+}
+
+#[derive(Default)]
+struct MarkovModel {
+    // plain text byte per offset
+    solved: HashMap<usize, u8>,
+    // per plain text byte, how often each byte preceded it
+    preceding_counts: HashMap<u8, [u32; 256]>,
+}
+
+impl MarkovStrategy {
+    pub fn new(guess_order: GuessOrder) -> Self {
+        Self {
+            guess_order,
+            model: Mutex::new(MarkovModel::default()),
+        }
+    }
+}
+
+impl GuessStrategy for MarkovStrategy {
+    fn plain_text_order(&self, plain_text_offset: usize) -> Option<Vec<u8>> {
+        let mut plain_text_bytes = self
+            .guess_order
+            .plain_text_bytes()
+            .unwrap_or_else(|| (u8::MIN..=u8::MAX).collect());
+
+        let model = self.model.lock().unwrap();
+        // the last byte of a block follows the 1st byte of the next block, which may not be solved yet
+        if let Some(preceding_counts) = model
+            .solved
+            .get(&(plain_text_offset + 1))
+            .and_then(|following| model.preceding_counts.get(following))
+        {
+            // stable, so bytes which were seen equally often keep the guess order
+            plain_text_bytes.sort_by_key(|&byte| Reverse(preceding_counts[byte as usize]));
+        }
+
+        Some(plain_text_bytes)
+    }
+
+    fn learn(&self, plain_text_offset: usize, plain_text: &[u8]) {
+        let mut model = self.model.lock().unwrap();
+        for (offset, &byte) in (plain_text_offset..).zip(plain_text) {
+            if model.solved.insert(offset, byte).is_some() {
+                continue;
+            }
+
+            // either neighbour may have been solved first, e.g. by another block
+            if let Some(&following) = model.solved.get(&(offset + 1)) {
+                model.count(byte, following);
+            }
+            if let Some(&preceding) = offset
+                .checked_sub(1)
+                .and_then(|offset| model.solved.get(&offset))
+            {
+                model.count(preceding, byte);
+            }
+        }
+    }
+}
+
+impl MarkovModel {
+    fn count(&mut self, preceding: u8, following: u8) {
+        self.preceding_counts.entry(following).or_insert([0; 256])[preceding as usize] += 1;
+    }
+}
+
+/// Seeds another strategy with the plain text of other cypher texts of the oracle, e.g. cached tokens of the same serializer. These often share their layout, so the bytes found at an offset before are tried first there, most often found first. The other bytes follow in the order of the seeded strategy.
+pub struct WarmStartStrategy {
+    strategy: Box<dyn GuessStrategy>,
+    // per plain text offset, how often each byte was found there
+    counts: HashMap<usize, [u32; 256]>,
+}
+
+impl WarmStartStrategy {
+    pub fn new(
+        strategy: Box<dyn GuessStrategy>,
+        plain_texts: impl IntoIterator<Item = (usize, Block)>,
+    ) -> Self {
+        let mut counts = HashMap::new();
+        for (plain_text_offset, plain_text) in plain_texts {
+            for (offset, &byte) in (plain_text_offset..).zip(plain_text.iter()) {
+                counts.entry(offset).or_insert([0; 256])[byte as usize] += 1;
+            }
+        }
+
+        Self { strategy, counts }
+    }
+}
+
+impl GuessStrategy for WarmStartStrategy {
+    fn plain_text_order(&self, plain_text_offset: usize) -> Option<Vec<u8>> {
+        let plain_text_order = self.strategy.plain_text_order(plain_text_offset);
+        let counts = match self.counts.get(&plain_text_offset) {
+            Some(counts) => counts,
+            None => return plain_text_order,
+        };
+
+        let mut plain_text_bytes =
+            plain_text_order.unwrap_or_else(|| (u8::MIN..=u8::MAX).collect());
+        // stable, so bytes which were found equally often keep the seeded strategy's order
+        plain_text_bytes.sort_by_key(|&byte| Reverse(counts[byte as usize]));
+        Some(plain_text_bytes)
+    }
+
+    fn learn(&self, plain_text_offset: usize, plain_text: &[u8]) {
+        self.strategy.learn(plain_text_offset, plain_text)
+    }
+}
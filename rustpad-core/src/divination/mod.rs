@@ -0,0 +1,596 @@
+pub mod decryptor;
+pub mod encryptor;
+pub mod guess_strategy;
+pub mod progress_observer;
+
+use std::{
+    sync::{Arc, Mutex},
+    thread,
+};
+
+use anyhow::{Context, Result};
+use log::{debug, info, warn, Level};
+use retry::delay::Fibonacci;
+
+use crate::{
+    block::block_size::BlockSizeTrait,
+    cache::Cache,
+    config::known_plain_text::KnownPlainText,
+    cypher_text::{
+        encode::AmountBlocksTrait,
+        forged_cypher_text::{solved::SolvedForgedCypherText, ByteLockResult, ForgedCypherText},
+    },
+    divination::{guess_strategy::GuessStrategy, progress_observer::ProgressObserver},
+    error::RustpadError,
+    logging::{log_coalesced, LOG_TARGET},
+    oracle::Oracle,
+    other::{RETRY_DELAY_MS, RETRY_MAX_ATTEMPTS},
+    session::{BlockProgress, Session},
+};
+
+// a byte's whole range without valid padding, this many times in a row, hints that the oracle changed its behaviour
+const RECALIBRATE_AFTER_ATTEMPTS: u64 = 2;
+// why a byte's value can't be trusted, as the oracle's answers about it weren't consistent
+const DOUBT_RETRIED: &str = "the oracle only gave valid padding when asked again";
+const DOUBT_UNANSWERED: &str =
+    "the oracle couldn't be questioned about some values, which were considered invalid";
+const DOUBT_UNCONFIRMED: &str =
+    "the oracle couldn't be questioned to confirm that the preceding bytes didn't cause valid padding";
+
+/// What the plain text of a decrypted block is expected to be. When encrypting, the blocks being decrypted are garbage, so nothing is expected.
+#[derive(Clone, Copy)]
+pub struct PlainTextExpectations<'b> {
+    pub known_plain_text: &'b KnownPlainText,
+    pub guess_strategy: &'b dyn GuessStrategy,
+}
+
+/// The oracle's answer to the questions about the candidates for a byte
+struct Answer {
+    // index of the candidate with valid padding, if any
+    solution_idx: Option<usize>,
+    // why the answer can't be trusted, if it can't
+    doubt: Option<&'static str>,
+    // indices of the candidates with invalid padding, including those considered invalid
+    invalid: Vec<usize>,
+}
+
+/// With `strict`, bytes of which the value can't be trusted fail the block, instead of being logged
+fn solve_block<'a>(
+    oracle: &impl Oracle,
+    cache: Arc<Mutex<Option<Cache>>>,
+    session: Arc<Mutex<Option<Session>>>,
+    expectations: Option<PlainTextExpectations>,
+    strict: bool,
+    cypher_text_for_block: &ForgedCypherText<'a>,
+    observer: &impl ProgressObserver,
+) -> Result<SolvedForgedCypherText<'a>> {
+    let block_to_decrypt_idx = cypher_text_for_block.amount_blocks() - 1;
+    let mut cypher_text_for_block = cypher_text_for_block.clone();
+    observer.block_started(block_to_decrypt_idx);
+
+    // check for a cache hit and short-circuit solving it
+    let cache_key = cypher_text_for_block.as_cache_key();
+    let cached_block = cache
+        .lock()
+        .unwrap()
+        .as_mut()
+        .map(|cache| {
+            cache.get_verified(&cache_key, |cached_block| {
+                question_oracle(
+                    oracle,
+                    &[cypher_text_for_block.to_solution_check(cached_block)],
+                    block_to_decrypt_idx,
+                    observer,
+                )
+                .map(|answer| answer.solution_idx.is_some())
+                .context(format!(
+                    "Block {}: verifying the cached block failed",
+                    block_to_decrypt_idx + 1
+                ))
+            })
+        })
+        .transpose()?
+        .flatten();
+    let mut block_solution = cached_block.map(|cached_block| {
+        debug!(
+            target: LOG_TARGET,
+            "Cache hit for ({}, {})",
+            cache_key.0.to_hex(),
+            cache_key.1.to_hex()
+        );
+        observer.bytes_solved(block_to_decrypt_idx, *cached_block.block_size() as usize);
+
+        SolvedForgedCypherText::from((cypher_text_for_block.clone(), cached_block))
+    });
+    if let Some(solution) = &block_solution {
+        // the session then holds every solved block, e.g. to export the attack's state
+        save_progress(
+            &session,
+            &cypher_text_for_block,
+            BlockProgress::new(
+                solution.forged_block_solution().clone(),
+                *cypher_text_for_block.block_size(),
+            ),
+        )?;
+    }
+
+    // continue where an interrupted attack left off
+    if block_solution.is_none() {
+        if let Some(progress) = session.lock().unwrap().as_ref().and_then(|session| {
+            session
+                .get(&cypher_text_for_block.as_cache_key())
+                .filter(|progress| *progress.bytes_answered() > 0)
+                .cloned()
+        }) {
+            debug!(
+                target: LOG_TARGET,
+                "Block {}: resuming with {} byte(s) solved",
+                block_to_decrypt_idx + 1,
+                progress.bytes_answered()
+            );
+            observer.bytes_solved(block_to_decrypt_idx, *progress.bytes_answered() as usize);
+
+            if progress.is_solved() {
+                block_solution = Some(SolvedForgedCypherText::from((
+                    cypher_text_for_block.clone(),
+                    progress.forged_block_solution().clone(),
+                )));
+            } else {
+                cypher_text_for_block = cypher_text_for_block.to_resumed(&progress);
+            }
+        }
+    }
+
+    // blocks which are solved already, e.g. cached ones, are still handed out when winding down
+    if block_solution.is_none() {
+        if let Some(request_budget) = oracle.request_budget() {
+            request_budget.start_block()?;
+        }
+    }
+
+    let mut attempts_to_solve_byte = 1;
+    let mut recalibrated_for_byte = false;
+    let mut hint_failed_for_byte = false;
+    let mut doubt_for_byte = None;
+    while block_solution.is_none() {
+        let plain_text_offset = cypher_text_for_block.plain_text_offset();
+        let hinted_value = expectations
+            .filter(|_| !hint_failed_for_byte)
+            .and_then(|expectations| expectations.known_plain_text.byte_at(plain_text_offset))
+            .map(|plain_text_byte| cypher_text_for_block.value_for_plain_text(plain_text_byte));
+
+        let candidates: Vec<ForgedCypherText> = match hinted_value {
+            Some(byte_value) => vec![byte_value],
+            None => candidate_values(
+                &cypher_text_for_block,
+                expectations.and_then(|expectations| {
+                    expectations
+                        .guess_strategy
+                        .plain_text_order(plain_text_offset)
+                }),
+            ),
+        }
+        .into_iter()
+        .map(|byte_value| {
+            let mut forged_cypher_text = cypher_text_for_block.clone();
+            forged_cypher_text.set_current_byte(byte_value);
+            forged_cypher_text
+        })
+        .collect();
+
+        let (current_byte_solution, doubt) =
+            question_oracle_confirmed(oracle, candidates, block_to_decrypt_idx, observer)?;
+        doubt_for_byte = doubt_for_byte.or(doubt);
+        if hinted_value.is_some() && current_byte_solution.is_none() {
+            warn!(
+                target: LOG_TARGET,
+                "Block {}, byte {}: the known plain text hint is wrong. Trying all values",
+                block_to_decrypt_idx + 1,
+                *cypher_text_for_block.block_size() - cypher_text_for_block.bytes_answered(),
+            );
+            hint_failed_for_byte = true;
+            continue;
+        }
+
+        if let (Some(forged_cypher_text), Some(doubt)) = (&current_byte_solution, doubt_for_byte) {
+            let byte = *forged_cypher_text.block_size() - forged_cypher_text.bytes_answered();
+            if strict {
+                return Err(RustpadError::Unverified {
+                    reason: format!(
+                        "Block {}, byte {}: the value can't be verified, as {}",
+                        block_to_decrypt_idx + 1,
+                        byte,
+                        doubt
+                    ),
+                }
+                .into());
+            }
+            log_coalesced(
+                Level::Warn,
+                "unverified byte",
+                format!(
+                    "Block {}, byte {}: solved, but unverified, as {}",
+                    block_to_decrypt_idx + 1,
+                    byte,
+                    doubt
+                ),
+            );
+        }
+
+        let current_byte_solution = current_byte_solution
+            .map(|forged_cypher_text| {
+                debug!(
+                    target: LOG_TARGET,
+                    "Block {}, byte {}: solved!",
+                    block_to_decrypt_idx + 1,
+                    *forged_cypher_text.block_size() - forged_cypher_text.bytes_answered(),
+                );
+                if let Some(expectations) = expectations {
+                    expectations.guess_strategy.learn(
+                        forged_cypher_text.plain_text_offset(),
+                        &[forged_cypher_text.current_plain_text_byte()],
+                    );
+                }
+
+                forged_cypher_text.lock_byte()
+            })
+            .ok_or_else(|| {
+                anyhow::Error::from(RustpadError::BlockUnsolvable {
+                    block: block_to_decrypt_idx + 1,
+                    byte: *cypher_text_for_block.block_size()
+                        - cypher_text_for_block.bytes_answered(),
+                })
+            });
+
+        match current_byte_solution {
+            Ok(current_byte_solution) => {
+                attempts_to_solve_byte = 1;
+                recalibrated_for_byte = false;
+                hint_failed_for_byte = false;
+                doubt_for_byte = None;
+                observer.bytes_solved(block_to_decrypt_idx, 1);
+
+                match current_byte_solution {
+                    ByteLockResult::BytesLeft(current_byte_solution) => {
+                        save_progress(
+                            &session,
+                            &current_byte_solution,
+                            current_byte_solution.to_progress(),
+                        )?;
+                        cypher_text_for_block = current_byte_solution;
+                    }
+
+                    // solving the current byte happens to have solved the whole block!
+                    ByteLockResult::Solved(solution) => {
+                        // solved block, save to cache
+                        let _ = cache
+                            .lock()
+                            .unwrap()
+                            .as_mut()
+                            .map(|cache| {
+                                cache.insert(
+                                    cypher_text_for_block.as_cache_key(),
+                                    solution.forged_block_solution().clone(),
+                                    // blocks forged while encrypting have no place in a plain text
+                                    expectations.map(|_| {
+                                        (block_to_decrypt_idx - 1)
+                                            * *cypher_text_for_block.block_size() as usize
+                                    }),
+                                )
+                            })
+                            .transpose()?;
+                        save_progress(
+                            &session,
+                            &cypher_text_for_block,
+                            BlockProgress::new(
+                                solution.forged_block_solution().clone(),
+                                *cypher_text_for_block.block_size(),
+                            ),
+                        )?;
+
+                        block_solution = Some(solution);
+                    }
+                }
+            }
+            // validation for byte failed, attempt retry
+            Err(e) => {
+                if attempts_to_solve_byte == RECALIBRATE_AFTER_ATTEMPTS && !recalibrated_for_byte {
+                    recalibrated_for_byte = true;
+                    warn!(
+                        target: LOG_TARGET,
+                        "Block {}, byte {}: no valid padding found in {} attempts. Recalibrating the oracle",
+                        block_to_decrypt_idx + 1,
+                        *cypher_text_for_block.block_size() - cypher_text_for_block.bytes_answered(),
+                        attempts_to_solve_byte
+                    );
+
+                    let recalibrated = oracle
+                        .recalibrate(&cypher_text_for_block.to_unsolved())
+                        .context(format!(
+                            "Block {}: recalibration of the oracle failed",
+                            block_to_decrypt_idx + 1
+                        ))?;
+                    if recalibrated {
+                        info!(
+                            target: LOG_TARGET,
+                            "Block {}: recalibrated the oracle, resuming",
+                            block_to_decrypt_idx + 1
+                        );
+                        attempts_to_solve_byte = 1;
+                        // the answers before recalibrating were about another behaviour of the oracle
+                        doubt_for_byte = None;
+                        continue;
+                    }
+                }
+
+                if attempts_to_solve_byte > RETRY_MAX_ATTEMPTS {
+                    return Err(e);
+                }
+
+                log_coalesced(
+                    Level::Warn,
+                    "retry decryption",
+                    format!(
+                        "Block {}, byte {}: retrying decryption ({}/{})",
+                        block_to_decrypt_idx + 1,
+                        *cypher_text_for_block.block_size()
+                            - cypher_text_for_block.bytes_answered(),
+                        attempts_to_solve_byte,
+                        RETRY_MAX_ATTEMPTS
+                    ),
+                );
+                observer.retry(
+                    block_to_decrypt_idx,
+                    *cypher_text_for_block.current_byte_idx(),
+                    attempts_to_solve_byte,
+                );
+                attempts_to_solve_byte += 1;
+                // a value found by asking again contradicts the answers so far
+                doubt_for_byte = doubt_for_byte.or(Some(DOUBT_RETRIED));
+            }
+        }
+    }
+
+    let block_solution =
+        block_solution.expect("`while` loop finished so this must contain a value");
+    // also the bytes which were cached or resumed
+    if let Some(expectations) = expectations {
+        let plain_text_block = block_solution.plain_text_block();
+        expectations.guess_strategy.learn(
+            (block_to_decrypt_idx - 1) * plain_text_block.len(),
+            &plain_text_block,
+        );
+    }
+    observer.block_finished(block_to_decrypt_idx, block_solution.forged_block_solution());
+    Ok(block_solution)
+}
+
+/// Every value of the current byte, in the order they're tried. `plain_text_order` is that of a `GuessStrategy`
+pub fn candidate_values(
+    cypher_text_for_block: &ForgedCypherText,
+    plain_text_order: Option<Vec<u8>>,
+) -> Vec<u8> {
+    match plain_text_order {
+        Some(plain_text_bytes) => plain_text_bytes
+            .into_iter()
+            .map(|plain_text_byte| cypher_text_for_block.value_for_plain_text(plain_text_byte))
+            .collect(),
+        None => (u8::MIN..=u8::MAX).collect(),
+    }
+}
+
+fn save_progress(
+    session: &Arc<Mutex<Option<Session>>>,
+    cypher_text_for_block: &ForgedCypherText,
+    progress: BlockProgress,
+) -> Result<()> {
+    session
+        .lock()
+        .unwrap()
+        .as_mut()
+        .map(|session| session.insert(cypher_text_for_block.as_cache_key(), progress))
+        .transpose()
+        .map(|_| ())
+}
+
+/// Like `question_oracle`, but returns the candidate, and why it can't be trusted, if it can't. Valid padding for the 1st byte of a block is confirmed, as the preceding bytes may have caused it. If they did, the search continues with the candidates which weren't answered yet. If the confirmation can't be asked, the value is kept, but doubted.
+fn question_oracle_confirmed<'a>(
+    oracle: &impl Oracle,
+    mut candidates: Vec<ForgedCypherText<'a>>,
+    block_to_decrypt_idx: usize,
+    observer: &impl ProgressObserver,
+) -> Result<(Option<ForgedCypherText<'a>>, Option<&'static str>)> {
+    let mut doubt = None;
+    while !candidates.is_empty() {
+        let answer = question_oracle(oracle, &candidates, block_to_decrypt_idx, observer)?;
+        doubt = doubt.or(answer.doubt);
+        let solution_idx = match answer.solution_idx {
+            Some(solution_idx) => solution_idx,
+            None => return Ok((None, doubt)),
+        };
+        // candidates which were already answered aren't asked again. Keep the guess order of the remaining ones
+        let mut solution = None;
+        candidates = candidates
+            .into_iter()
+            .enumerate()
+            .filter_map(|(idx, candidate)| {
+                if idx == solution_idx {
+                    solution = Some(candidate);
+                    None
+                } else if answer.invalid.contains(&idx) {
+                    None
+                } else {
+                    Some(candidate)
+                }
+            })
+            .collect();
+        let solution = solution.expect("The solution is one of the candidates");
+
+        let confirmation = match solution.to_padding_confirmation() {
+            Some(confirmation) => confirmation,
+            None => return Ok((Some(solution), doubt)),
+        };
+        let answer = match question_oracle(oracle, &[confirmation], block_to_decrypt_idx, observer)
+        {
+            Ok(answer) => answer,
+            Err(e) if RustpadError::is_fatal(&e) => return Err(e),
+            // confirmation was already retried. Rather keep the likely value than fail the block
+            Err(e) => {
+                log_coalesced(
+                        Level::Warn,
+                        "unconfirmed padding",
+                        format!(
+                            "Block {}, byte {}: valid padding couldn't be confirmed, keeping the value: {:#}",
+                            block_to_decrypt_idx + 1,
+                            *solution.block_size() - solution.bytes_answered(),
+                            e
+                        ),
+                    );
+                return Ok((Some(solution), doubt.or(Some(DOUBT_UNCONFIRMED))));
+            }
+        };
+        doubt = doubt.or(answer.doubt);
+        if answer.solution_idx.is_some() {
+            return Ok((Some(solution), doubt));
+        }
+
+        log_coalesced(
+            Level::Warn,
+            "false positive padding",
+            format!(
+                "Block {}, byte {}: valid padding was caused by the preceding bytes. Continuing the search",
+                block_to_decrypt_idx + 1,
+                *solution.block_size() - solution.bytes_answered(),
+            ),
+        );
+    }
+
+    Ok((None, doubt))
+}
+
+/// Question the oracle about all candidates for the current byte. Returns the index of the candidate with valid padding, if any.
+/// Candidates for which the oracle couldn't be questioned are retried. If they keep failing, they're considered to have invalid padding. Either makes the answer doubtful.
+/// Fails only if the oracle aborted the attack, if its TLS certificate changed, or if it couldn't be questioned about any candidate.
+fn question_oracle(
+    oracle: &impl Oracle,
+    candidates: &[ForgedCypherText],
+    block_to_decrypt_idx: usize,
+    observer: &impl ProgressObserver,
+) -> Result<Answer> {
+    let block_size = *candidates[0].block_size();
+    let bytes_answered = candidates[0].bytes_answered();
+
+    let mut retry_delays = Fibonacci::from_millis(RETRY_DELAY_MS);
+    let mut to_ask: Vec<usize> = (0..candidates.len()).collect();
+    let mut answered_any = false;
+    let mut invalid = Vec::new();
+    let mut last_error = None;
+    for attempt in 1..=RETRY_MAX_ATTEMPTS + 1 {
+        let batch: Vec<ForgedCypherText> = to_ask
+            .iter()
+            .map(|&candidate_idx| candidates[candidate_idx].clone())
+            .collect();
+        let verdicts = oracle.ask_validation_batch(&batch, &|batch_idx, latency| {
+            observer.request_issued(block_to_decrypt_idx, &batch[batch_idx], latency);
+        });
+
+        let mut failed = Vec::new();
+        for (candidate_idx, verdict) in to_ask.into_iter().zip(verdicts) {
+            match verdict {
+                Some(Ok(true)) => {
+                    return Ok(Answer {
+                        solution_idx: Some(candidate_idx),
+                        doubt: (attempt > 1).then_some(DOUBT_RETRIED),
+                        invalid,
+                    })
+                }
+                Some(Ok(false)) => {
+                    answered_any = true;
+                    invalid.push(candidate_idx);
+                }
+                None => {}
+                Some(Err(e)) if RustpadError::is_fatal(&e) => return Err(e),
+                Some(Err(e)) => {
+                    debug!(
+                        target: LOG_TARGET,
+                        "Block {}, byte {}, value {:#04x}: {:?}",
+                        block_to_decrypt_idx + 1,
+                        block_size - bytes_answered,
+                        candidates[candidate_idx].current_byte_value(),
+                        e
+                    );
+                    failed.push(candidate_idx);
+                    last_error = Some(e);
+                }
+            }
+        }
+
+        if failed.is_empty() {
+            break;
+        }
+        if attempt > RETRY_MAX_ATTEMPTS {
+            if !answered_any {
+                return Err(RustpadError::OracleUnreachable {
+                    reason: format!(
+                        "block {}, byte {}: {:#}",
+                        block_to_decrypt_idx + 1,
+                        block_size - bytes_answered,
+                        last_error.expect("Questions failed, so an error was seen")
+                    ),
+                }
+                .into());
+            }
+
+            // per value, so a single misbehaving guess stands out among the summaries
+            for &candidate_idx in &failed {
+                log_coalesced(
+                    Level::Warn,
+                    "validation failed",
+                    format!(
+                        "Block {}, byte {}, value {:#04x}: retried {}x, considered invalid",
+                        block_to_decrypt_idx + 1,
+                        block_size - bytes_answered,
+                        candidates[candidate_idx].current_byte_value(),
+                        RETRY_MAX_ATTEMPTS
+                    ),
+                );
+            }
+            invalid.extend(failed);
+            return Ok(Answer {
+                solution_idx: None,
+                doubt: Some(DOUBT_UNANSWERED),
+                invalid,
+            });
+        }
+
+        log_coalesced(
+            Level::Warn,
+            "retry validation",
+            format!(
+                "Block {}, byte {}: retrying validation of {} value(s) ({}/{})",
+                block_to_decrypt_idx + 1,
+                block_size - bytes_answered,
+                failed.len(),
+                attempt,
+                RETRY_MAX_ATTEMPTS
+            ),
+        );
+        observer.retry(
+            block_to_decrypt_idx,
+            block_size - 1 - bytes_answered,
+            attempt,
+        );
+        thread::sleep(
+            retry_delays
+                .next()
+                .expect("Fibonacci delays are an infinite iterator"),
+        );
+        to_ask = failed;
+    }
+
+    Ok(Answer {
+        solution_idx: None,
+        doubt: None,
+        invalid,
+    })
+}
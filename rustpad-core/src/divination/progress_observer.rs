@@ -0,0 +1,31 @@
+use std::time::Duration;
+
+use crate::{block::Block, cypher_text::forged_cypher_text::ForgedCypherText};
+
+/// Structured progress of an attack, independent of how it's shown. Blocks are identified by their index in the cypher text being decrypted or forged, the IV being 0.
+/// Every method does nothing by default, so observers only implement what they're interested in. Methods are called concurrently from the thread pool.
+pub trait ProgressObserver: Sync {
+    /// Solving the block started, or continued from a cache or session
+    fn block_started(&self, _block_idx: usize) {}
+
+    /// The oracle answered a question about `forged_cypher_text`, i.e. a guess of the value of its current byte
+    fn request_issued(
+        &self,
+        _block_idx: usize,
+        _forged_cypher_text: &ForgedCypherText,
+        _latency: Duration,
+    ) {
+    }
+
+    /// Questioning the oracle is retried, as it failed or didn't give valid padding for any value
+    fn retry(&self, _block_idx: usize, _byte_idx: u8, _attempt: u64) {}
+
+    /// `amount` more bytes of the block are solved. Cached blocks and resumed sessions solve multiple bytes at once
+    fn bytes_solved(&self, _block_idx: usize, _amount: usize) {}
+
+    /// The block is solved. `forged_block_solution` is kept as if the oracle validated PKCS7 padding, so its intermediate follows from it
+    fn block_finished(&self, _block_idx: usize, _forged_block_solution: &Block) {}
+
+    /// Solving the block failed, e.g. as the oracle became unreachable. Other blocks may still be solved
+    fn block_failed(&self, _block_idx: usize, _error: &anyhow::Error) {}
+}
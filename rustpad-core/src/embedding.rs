@@ -0,0 +1,166 @@
+use std::sync::{Arc, Mutex};
+
+use anyhow::{anyhow, Context, Result};
+
+use crate::{
+    block::{
+        block_size::{BlockSize, BlockSizeTrait},
+        padding_scheme::PaddingScheme,
+        Block,
+    },
+    config::{
+        guess_order::GuessOrder, guess_strategy::GuessStrategyOption,
+        known_plain_text::KnownPlainText,
+    },
+    cypher_text::{
+        encode::{Encode, Encoding},
+        CypherText,
+    },
+    divination::{
+        decryptor::Decryptor, encryptor::Encryptor, progress_observer::ProgressObserver,
+        PlainTextExpectations,
+    },
+    error::RustpadError,
+    oracle::Oracle,
+    plain_text::PlainText,
+};
+
+/// Decrypt `cypher_text`, which starts with its IV, as raw bytes. The plain text is returned with its padding.
+/// The oracle is questioned from `threads` threads at once, or from as many as there are CPUs if 0. It's given hex encoded cypher texts.
+/// For bindings to other languages, of which the oracle is a callback. No cache or session is used.
+pub fn decrypt(
+    cypher_text: &[u8],
+    block_size: u8,
+    threads: usize,
+    oracle: &impl Oracle,
+    observer: impl ProgressObserver,
+) -> Result<Vec<u8>> {
+    let blocks = to_blocks(cypher_text, block_size)?;
+    let cypher_text = CypherText::from_iter(&blocks, false, Encoding::Hex);
+    let decryptor =
+        Decryptor::new_decryption_only(observer, &cypher_text, PaddingScheme::default(), None);
+
+    let known_plain_text = KnownPlainText::new(&[]);
+    let guess_strategy = GuessStrategyOption::default().to_strategy(&GuessOrder::default());
+    let solutions = thread_pool(threads)?.install(|| {
+        decryptor.decrypt_blocks(
+            oracle,
+            Arc::new(Mutex::new(None)),
+            Arc::new(Mutex::new(None)),
+            PlainTextExpectations {
+                known_plain_text: &known_plain_text,
+                guess_strategy: guess_strategy.as_ref(),
+            },
+            false,
+        )
+    })?;
+
+    Ok(solutions
+        .iter()
+        .flat_map(|solution| solution.plain_text_block().to_vec())
+        .collect())
+}
+
+/// Forge a cypher text, starting with its IV, which decrypts to `plain_text`, padded with PKCS#7. The forged cypher text ends with the last block of `cypher_text`, any cypher text of the oracle, or with a block of zeroes if there is none.
+/// Questioning the oracle works as with `decrypt`, though the blocks of the forged cypher text can only be solved one after the other.
+pub fn encrypt(
+    plain_text: &[u8],
+    cypher_text: Option<&[u8]>,
+    block_size: u8,
+    threads: usize,
+    oracle: &impl Oracle,
+    observer: impl ProgressObserver + Clone,
+) -> Result<Vec<u8>> {
+    let blocks = match cypher_text {
+        Some(cypher_text) => to_blocks(cypher_text, block_size)?,
+        None => {
+            let block_size = to_block_size(block_size)?;
+            vec![Block::new(&block_size), Block::new(&block_size)]
+        }
+    };
+    let cypher_text = CypherText::from_iter(&blocks, false, Encoding::Hex);
+    let plain_text = PlainText::from_bytes(
+        plain_text,
+        &cypher_text.block_size(),
+        &PaddingScheme::default(),
+    );
+    let decryptor =
+        Decryptor::new_encryption(observer.clone(), &cypher_text, PaddingScheme::default());
+
+    let known_plain_text = KnownPlainText::new(&[]);
+    let guess_strategy = GuessStrategyOption::default().to_strategy(&GuessOrder::default());
+    let thread_pool = thread_pool(threads)?;
+    let last_block = thread_pool
+        .install(|| {
+            decryptor.decrypt_blocks(
+                oracle,
+                Arc::new(Mutex::new(None)),
+                Arc::new(Mutex::new(None)),
+                PlainTextExpectations {
+                    known_plain_text: &known_plain_text,
+                    guess_strategy: guess_strategy.as_ref(),
+                },
+                false,
+            )
+        })?
+        .into_iter()
+        .max_by_key(|solution| solution.original_blocks().len())
+        .expect("Can't encrypt without having decrypted a block");
+
+    let encryptor = Encryptor::new(observer, last_block);
+    let forged_cypher_text = thread_pool.install(|| {
+        encryptor.encrypt_plain_text(
+            &plain_text,
+            oracle,
+            Arc::new(Mutex::new(None)),
+            Arc::new(Mutex::new(None)),
+            false,
+        )
+    })?;
+
+    Ok(forged_cypher_text
+        .blocks()
+        .iter()
+        .flat_map(|block| block.to_vec())
+        .collect())
+}
+
+fn to_block_size(block_size: u8) -> Result<BlockSize> {
+    if block_size == 0 {
+        return Err(anyhow!("The block size can't be 0"));
+    }
+    Ok(BlockSize::from(block_size))
+}
+
+fn to_blocks(cypher_text: &[u8], block_size: u8) -> Result<Vec<Block>> {
+    let block_size = to_block_size(block_size)?;
+    if cypher_text.is_empty() || !cypher_text.len().is_multiple_of(*block_size as usize) {
+        return Err(RustpadError::EncodingMismatch {
+            reason: format!(
+                "{} bytes don't split into blocks of {} bytes",
+                cypher_text.len(),
+                *block_size
+            ),
+        }
+        .into());
+    }
+    let blocks: Vec<Block> = cypher_text
+        .chunks(*block_size as usize)
+        .map(Block::from)
+        .collect();
+    if blocks.len() < 2 {
+        return Err(anyhow!(
+            "Decryption impossible with only 1 block. The cypher text must start with its IV"
+        ));
+    }
+
+    Ok(blocks)
+}
+
+// a pool per attack, so the threads of the embedding program's own `rayon` pool aren't taken
+fn thread_pool(threads: usize) -> Result<rayon::ThreadPool> {
+    rayon::ThreadPoolBuilder::new()
+        .num_threads(threads)
+        .build()
+        .context("Thread pool initialisation failed")
+}
@@ -0,0 +1,130 @@
+use std::{error::Error, fmt::Display};
+
+// any failure which isn't a `RustpadError`
+pub const GENERIC_EXIT_CODE: i32 = 3;
+
+/// Classes of failure which users may want to react to, e.g. in scripts by checking the exit code. Other failures are plain `anyhow` errors.
+#[derive(Debug)]
+pub enum RustpadError {
+    // the cypher text can't be decoded into blocks
+    EncodingMismatch { reason: String },
+    // the web oracle's response to incorrect padding couldn't be determined
+    CalibrationFailed { reason: String },
+    // none of the questions for a byte got an answer, e.g. as the target is down
+    OracleUnreachable { reason: String },
+    // the oracle asked to stop the attack
+    OracleAborted { reason: String },
+    // the oracle answered, but none of the values for a byte gave valid padding
+    BlockUnsolvable { block: usize, byte: u8 },
+    // the result of the attack differs from decrypting with the known key
+    VerificationFailed { reason: String },
+    // the oracle presented another TLS certificate than the pinned one
+    CertificateChanged { location: String },
+    // `--max-requests` or `--max-duration` was reached
+    BudgetExhausted { reason: String },
+    // with `--strict`, a byte was solved, but the oracle's answers about it weren't consistent
+    Unverified { reason: String },
+    // the oracle was under maintenance for longer than `--maintenance-timeout`
+    MaintenanceTimeout { waited: String },
+}
+
+impl RustpadError {
+    /// The `RustpadError` which caused `error`, if any
+    pub fn find(error: &anyhow::Error) -> Option<&Self> {
+        error.chain().find_map(|cause| cause.downcast_ref::<Self>())
+    }
+
+    /// Whether `error` stops the attack right away, instead of being retried
+    pub fn is_fatal(error: &anyhow::Error) -> bool {
+        matches!(
+            Self::find(error),
+            Some(
+                Self::OracleAborted { .. }
+                    | Self::CertificateChanged { .. }
+                    | Self::BudgetExhausted { .. }
+                    | Self::MaintenanceTimeout { .. }
+            )
+        )
+    }
+
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            RustpadError::EncodingMismatch { .. } => 10,
+            RustpadError::CalibrationFailed { .. } => 11,
+            RustpadError::OracleUnreachable { .. } => 12,
+            RustpadError::OracleAborted { .. } => 13,
+            RustpadError::BlockUnsolvable { .. } => 14,
+            RustpadError::VerificationFailed { .. } => 15,
+            RustpadError::CertificateChanged { .. } => 16,
+            RustpadError::BudgetExhausted { .. } => 17,
+            RustpadError::Unverified { .. } => 18,
+            RustpadError::MaintenanceTimeout { .. } => 19,
+        }
+    }
+
+    /// Name of the class of failure, which stays the same between versions
+    pub fn kind(&self) -> &'static str {
+        match self {
+            RustpadError::EncodingMismatch { .. } => "encoding_mismatch",
+            RustpadError::CalibrationFailed { .. } => "calibration_failed",
+            RustpadError::OracleUnreachable { .. } => "oracle_unreachable",
+            RustpadError::OracleAborted { .. } => "oracle_aborted",
+            RustpadError::BlockUnsolvable { .. } => "block_unsolvable",
+            RustpadError::VerificationFailed { .. } => "verification_failed",
+            RustpadError::CertificateChanged { .. } => "certificate_changed",
+            RustpadError::BudgetExhausted { .. } => "budget_exhausted",
+            RustpadError::Unverified { .. } => "unverified",
+            RustpadError::MaintenanceTimeout { .. } => "maintenance_timeout",
+        }
+    }
+}
+
+impl Display for RustpadError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RustpadError::EncodingMismatch { reason } => write!(f, "{}", reason),
+            RustpadError::CalibrationFailed { reason } => {
+                write!(f, "Calibration of the web oracle failed. {}", reason)
+            }
+            RustpadError::OracleUnreachable { reason } => {
+                write!(f, "Oracle could not be questioned: {}", reason)
+            }
+            RustpadError::OracleAborted { reason } => {
+                write!(f, "Oracle aborted the attack: {}", reason)
+            }
+            RustpadError::BlockUnsolvable { block, byte } => {
+                write!(f, "Block {}, byte {}: decryption failed", block, byte)
+            }
+            RustpadError::VerificationFailed { reason } => {
+                write!(f, "Verification with the key failed: {}", reason)
+            }
+            RustpadError::CertificateChanged { location } => write!(
+                f,
+                "The TLS certificate of `{}` changed since it was pinned. The connection may be intercepted, or another backend may be answering. See `--allow-cert-change` to continue regardless",
+                location
+            ),
+            RustpadError::BudgetExhausted { reason } => {
+                write!(f, "The request budget is exhausted: {}", reason)
+            }
+            RustpadError::Unverified { reason } => write!(
+                f,
+                "{}. Refusing the result, as `--strict` is given",
+                reason
+            ),
+            RustpadError::MaintenanceTimeout { waited } => write!(
+                f,
+                "The oracle was under maintenance for longer than {} in total. See `--maintenance-timeout`",
+                waited
+            ),
+        }
+    }
+}
+
+impl Error for RustpadError {}
+
+/// Exit code for the failure `error`
+pub fn exit_code(error: &anyhow::Error) -> i32 {
+    RustpadError::find(error)
+        .map(RustpadError::exit_code)
+        .unwrap_or(GENERIC_EXIT_CODE)
+}
@@ -0,0 +1,27 @@
+//! The attack engine of rustpad: padding oracle attacks against CBC mode, to decrypt cypher texts and encrypt arbitrary plain text, without knowing the key.
+//!
+//! The engine questions an [`Oracle`](oracle::Oracle), which tells whether a cypher text has valid padding. Oracles for web servers, scripts, TCP services, shared library plugins, Lua scripts and, with the `python` feature, Python scripts are included. Others implement the trait.
+//! - [`Decryptor`](divination::decryptor::Decryptor) decrypts the blocks of a [`CypherText`](cypher_text::CypherText).
+//! - [`Encryptor`](divination::encryptor::Encryptor) forges a cypher text for a given plain text.
+//! - Progress is reported to a [`ProgressObserver`](divination::progress_observer::ProgressObserver), e.g. to show it in a UI.
+//!
+//! Web oracles are calibrated first, with a [`Calibrator`](calibrator::Calibrator), to learn how they respond to incorrect padding. Solved blocks may be kept in a [`Cache`](cache::Cache), and progress in a [`Session`](session::Session) to resume from.
+//!
+//! Bindings to other languages, of which the oracle is a callback, attack raw bytes with [`embedding`].
+//!
+//! Logs are written with the `log` crate, with target [`LOG_TARGET`](logging::LOG_TARGET).
+
+pub mod block;
+pub mod cache;
+pub mod calibrator;
+pub mod config;
+pub mod cypher_text;
+pub mod divination;
+pub mod embedding;
+pub mod error;
+pub mod lint;
+pub mod logging;
+pub mod oracle;
+pub mod other;
+pub mod plain_text;
+pub mod session;
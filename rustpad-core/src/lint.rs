@@ -0,0 +1,20 @@
+use anyhow::Result;
+use getset::Getters;
+
+/// Outcome of a single check of `rustpad lint`
+#[derive(Getters)]
+pub struct LintCheck {
+    #[getset(get = "pub")]
+    description: &'static str,
+    #[getset(get = "pub")]
+    outcome: Result<()>,
+}
+
+impl LintCheck {
+    pub fn new(description: &'static str, outcome: Result<()>) -> Self {
+        Self {
+            description,
+            outcome,
+        }
+    }
+}
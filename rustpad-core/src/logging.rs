@@ -0,0 +1,76 @@
+use std::{
+    collections::HashMap,
+    sync::{Mutex, OnceLock},
+    time::{Duration, Instant},
+};
+
+use log::{log, Level};
+
+/// Target of all of rustpad's log records, to tell them apart from those of dependencies
+pub const LOG_TARGET: &str = "rustpad";
+
+// messages of the same kind logged per window, before the rest is only counted
+const COALESCE_BURST: usize = 3;
+const COALESCE_WINDOW: Duration = Duration::from_secs(10);
+
+static COALESCED: OnceLock<Mutex<HashMap<&'static str, Coalesced>>> = OnceLock::new();
+
+struct Coalesced {
+    level: Level,
+    window_start: Instant,
+    logged: usize,
+    suppressed: usize,
+    last_message: String,
+}
+
+/// Log a message which may repeat thousands of times, e.g. a retry per guess. Only the first few of a `kind` are logged per window. The rest is summarised once the window ends, with the latest message
+pub fn log_coalesced(level: Level, kind: &'static str, message: String) {
+    let mut coalesced = COALESCED
+        .get_or_init(|| Mutex::new(HashMap::new()))
+        .lock()
+        .unwrap();
+    summarise_expired(&mut coalesced, false);
+
+    let entry = coalesced.entry(kind).or_insert_with(|| Coalesced {
+        level,
+        window_start: Instant::now(),
+        logged: 0,
+        suppressed: 0,
+        last_message: String::new(),
+    });
+    if entry.logged < COALESCE_BURST {
+        entry.logged += 1;
+        log!(target: LOG_TARGET, level, "{}", message);
+    } else {
+        entry.suppressed += 1;
+        entry.last_message = message;
+    }
+}
+
+/// Summarise the messages suppressed in windows which ended. With `all`, also those of ongoing windows, e.g. before exiting
+pub fn summarise_coalesced(all: bool) {
+    if let Some(coalesced) = COALESCED.get() {
+        summarise_expired(&mut coalesced.lock().unwrap(), all);
+    }
+}
+
+fn summarise_expired(coalesced: &mut HashMap<&'static str, Coalesced>, all: bool) {
+    coalesced.retain(|_, entry| {
+        if !all && entry.window_start.elapsed() < COALESCE_WINDOW {
+            return true;
+        }
+
+        if entry.suppressed > 0 {
+            log!(
+                target: LOG_TARGET,
+                entry.level,
+                "{} \u{2014} {} similar message(s) suppressed in the last {}s",
+                entry.last_message,
+                entry.suppressed,
+                entry.window_start.elapsed().as_secs().max(1)
+            );
+        }
+        // a new window starts with the next message
+        false
+    });
+}
@@ -0,0 +1,81 @@
+use std::{
+    io::{Read, Write},
+    sync::Mutex,
+};
+
+use anyhow::{anyhow, Context, Result};
+use log::{info, warn};
+use native_tls::TlsStream;
+
+use crate::{error::RustpadError, logging::LOG_TARGET};
+
+/// Trust-on-first-use pinning of the oracle's TLS certificate. The certificate presented on first contact is pinned, after which any other certificate means the connection was intercepted, or the traffic moved to another backend. Either invalidates the calibration, and possibly the attack.
+/// Shared by all oracles built from the same config, e.g. the calibration oracle and the oracle recalibrating mid-attack. The whole certificate is kept, so certificates are compared exactly.
+#[derive(Debug)]
+pub struct CertificatePin {
+    // DER encoding of the pinned certificate
+    certificate: Mutex<Option<Vec<u8>>>,
+    // pin the new certificate instead of stopping the attack
+    allow_change: bool,
+}
+
+impl CertificatePin {
+    pub fn new(allow_change: bool) -> Self {
+        Self {
+            certificate: Mutex::new(None),
+            allow_change,
+        }
+    }
+
+    /// Compare the certificate presented by `location` with the pinned one. The first certificate seen is pinned.
+    pub fn check(&self, location: &str, certificate: &[u8]) -> Result<()> {
+        let mut pinned = self.certificate.lock().unwrap();
+
+        match pinned.as_deref() {
+            None => {
+                info!(
+                    target: LOG_TARGET,
+                    "Pinned the TLS certificate of `{}`", location
+                );
+            }
+            Some(pinned) if pinned == certificate => return Ok(()),
+            Some(_) if self.allow_change => {
+                warn!(
+                    target: LOG_TARGET,
+                    "The TLS certificate of `{}` changed. Pinning the new certificate", location
+                );
+            }
+            Some(_) => {
+                return Err(RustpadError::CertificateChanged {
+                    location: location.to_string(),
+                }
+                .into())
+            }
+        }
+
+        *pinned = Some(certificate.to_vec());
+        Ok(())
+    }
+
+    /// Check the certificate of the peer on the other end of `stream`
+    pub fn check_stream<S: Read + Write>(
+        &self,
+        location: &str,
+        stream: &TlsStream<S>,
+    ) -> Result<()> {
+        let certificate = stream
+            .peer_certificate()
+            .context(format!(
+                "Retrieving the TLS certificate of `{}` failed",
+                location
+            ))?
+            .ok_or_else(|| anyhow!("`{}` did not present a TLS certificate", location))?
+            .to_der()
+            .context(format!(
+                "Encoding the TLS certificate of `{}` failed",
+                location
+            ))?;
+
+        self.check(location, &certificate)
+    }
+}
@@ -0,0 +1,69 @@
+use openssl::hash::{hash, MessageDigest};
+
+use crate::cypher_text::encode::Encode;
+
+/// Placeholder whose value is computed from the forged cypher text, for protocols with a length or checksum field next to the cypher text. Written as `{<keyword>_<suffix>}`, e.g. `{CTEXT_LEN}`.
+/// Values are computed over the raw bytes of the cypher text, before encoding.
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum ComputedPlaceholder {
+    // decimal
+    Length,
+    // lowercase hex
+    Md5,
+    // lowercase hex, 8 digits
+    Crc32,
+}
+
+impl ComputedPlaceholder {
+    const ALL: [Self; 3] = [Self::Length, Self::Md5, Self::Crc32];
+
+    fn suffix(&self) -> &'static str {
+        match self {
+            Self::Length => "LEN",
+            Self::Md5 => "MD5",
+            Self::Crc32 => "CRC32",
+        }
+    }
+
+    /// Forms of the placeholder in a request. The URL parser percent-encodes the braces in a path
+    fn forms(&self, keyword: &str) -> [String; 2] {
+        [
+            format!("{{{}_{}}}", keyword, self.suffix()),
+            format!("%7B{}_{}%7D", keyword, self.suffix()),
+        ]
+    }
+
+    fn value(&self, raw_cypher_text: &[u8]) -> String {
+        match self {
+            Self::Length => raw_cypher_text.len().to_string(),
+            Self::Md5 => hex::encode(
+                hash(MessageDigest::md5(), raw_cypher_text).expect("MD5 is always available"),
+            ),
+            Self::Crc32 => format!("{:08x}", crc32fast::hash(raw_cypher_text)),
+        }
+    }
+}
+
+/// Pairs of (placeholder, value) of every computed placeholder. These contain the keyword, so they're replaced before it
+pub(crate) fn substitutions<'a>(
+    keyword: &str,
+    cypher_text: &'a impl Encode<'a>,
+) -> Vec<(String, String)> {
+    let raw_cypher_text = cypher_text.raw_bytes();
+
+    ComputedPlaceholder::ALL
+        .iter()
+        .flat_map(|placeholder| {
+            let value = placeholder.value(&raw_cypher_text);
+            placeholder.forms(keyword).map(|form| (form, value.clone()))
+        })
+        .collect()
+}
+
+/// `text` without any computed placeholders, to count the occurrences of the keyword itself
+pub(crate) fn strip(text: &str, keyword: &str) -> String {
+    ComputedPlaceholder::ALL
+        .iter()
+        .flat_map(|placeholder| placeholder.forms(keyword))
+        .fold(text.to_string(), |text, form| text.replace(&form, ""))
+}
@@ -0,0 +1,126 @@
+use std::sync::Arc;
+
+use anyhow::{anyhow, Context, Result};
+use reqwest::{header::HeaderMap, redirect, Client, Method, Url};
+use tokio::runtime::{self, Runtime};
+
+use crate::config::request_timeout::RequestTimeout;
+
+// as reqwest's default policy
+const MAX_REDIRECTS: usize = 10;
+
+/// Sends requests on behalf of oracles which run user code, i.e. Lua scripts and WASM plugins, as they can't send requests themselves
+#[derive(Clone)]
+pub(crate) struct EmbeddedHttp {
+    client: Client,
+    runtime: Arc<Runtime>,
+    // hosts which requests may be sent to, redirects included. `None` allows any
+    allowed_hosts: Option<Arc<[String]>>,
+}
+
+pub(crate) struct EmbeddedResponse {
+    pub(crate) status: u16,
+    pub(crate) headers: HeaderMap,
+    pub(crate) body: Vec<u8>,
+}
+
+impl EmbeddedHttp {
+    pub(crate) fn new(
+        request_timeout: &RequestTimeout,
+        insecure: bool,
+        allowed_hosts: Option<&[String]>,
+    ) -> Result<Self> {
+        let allowed_hosts: Option<Arc<[String]>> = allowed_hosts.map(Arc::from);
+        let redirect_policy = match &allowed_hosts {
+            Some(allowed_hosts) => {
+                let allowed_hosts = allowed_hosts.clone();
+                redirect::Policy::custom(move |attempt| {
+                    if attempt.previous().len() >= MAX_REDIRECTS {
+                        attempt.error("too many redirects")
+                    } else if is_allowed(&allowed_hosts, attempt.url()) {
+                        attempt.follow()
+                    } else {
+                        let error = format!(
+                            "redirect to `{}`, which isn't an allowed host",
+                            attempt.url()
+                        );
+                        attempt.error(error)
+                    }
+                })
+            }
+            None => redirect::Policy::limited(MAX_REDIRECTS),
+        };
+        let client = Client::builder()
+            .danger_accept_invalid_certs(insecure)
+            .timeout(**request_timeout)
+            .redirect(redirect_policy)
+            .build()
+            .context("HTTP client setup failed")?;
+        let runtime = runtime::Builder::new_multi_thread()
+            .enable_all()
+            .thread_name("Embedded HTTP")
+            .build()
+            .context("Async runtime setup failed")?;
+
+        Ok(Self {
+            client,
+            runtime: Arc::new(runtime),
+            allowed_hosts,
+        })
+    }
+
+    /// Send the request, and wait for the whole response
+    pub(crate) fn send(
+        &self,
+        method: &str,
+        url: &str,
+        headers: Vec<(String, String)>,
+        body: Option<Vec<u8>>,
+    ) -> Result<EmbeddedResponse> {
+        let method = Method::from_bytes(method.as_bytes())
+            .context(format!("`{}` is not a valid HTTP method", method))?;
+        let parsed_url = Url::parse(url).context(format!("`{}` is not a valid URL", url))?;
+        if let Some(allowed_hosts) = &self.allowed_hosts {
+            if !is_allowed(allowed_hosts, &parsed_url) {
+                return Err(anyhow!(
+                    "Request to `{}` refused, as its host isn't allowed",
+                    url
+                ));
+            }
+        }
+        let mut request_builder = self.client.request(method, parsed_url);
+        for (name, value) in headers {
+            request_builder = request_builder.header(name, value);
+        }
+        if let Some(body) = body {
+            request_builder = request_builder.body(body);
+        }
+
+        self.runtime.block_on(async {
+            let response = request_builder
+                .send()
+                .await
+                .context(format!("Request to `{}` failed", url))?;
+            let status = response.status().as_u16();
+            let headers = response.headers().clone();
+            let body = response
+                .bytes()
+                .await
+                .context(format!("Reading the response of `{}` failed", url))?;
+
+            Ok(EmbeddedResponse {
+                status,
+                headers,
+                body: body.to_vec(),
+            })
+        })
+    }
+}
+
+fn is_allowed(allowed_hosts: &[String], url: &Url) -> bool {
+    url.host_str().is_some_and(|host| {
+        allowed_hosts
+            .iter()
+            .any(|allowed_host| allowed_host.eq_ignore_ascii_case(host))
+    })
+}
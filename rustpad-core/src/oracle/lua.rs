@@ -0,0 +1,331 @@
+use std::{
+    path::{Path, PathBuf},
+    sync::Mutex,
+    thread,
+};
+
+use anyhow::Result;
+
+use crate::{
+    config::{thread_delay::ThreadDelay, LuaConfig, SubConfig},
+    cypher_text::encode::Encode,
+    lint::LintCheck,
+};
+
+use self::interpreter::{Script, State};
+
+use super::{oracle_location::OracleLocation, request_budget::RequestBudget, Oracle};
+
+/// Oracle defined by an `ask_validation(cypher_text)` function in a Lua script. The function is handed the encoded cypher text, and returns `true` if its padding is valid. Raising an error fails the question, which is retried.
+/// Unlike a script oracle, the script is loaded once per thread, rather than run per question. Globals thus persist between questions, e.g. to keep a session.
+/// The script is given a `rustpad` table with helpers, as Lua itself can't send requests. See `interpreter::api`
+/// Only available with the `lua` feature, as it embeds a Lua interpreter
+pub struct LuaOracle {
+    path: PathBuf,
+    script: Script,
+    config: LuaConfig,
+    // a Lua state can only be used by one thread at a time. Each question takes an idle one, or loads the script into a new one
+    idle_states: Mutex<Vec<State>>,
+}
+
+impl Oracle for LuaOracle {
+    fn visit(oracle_location: &OracleLocation, oracle_config: &SubConfig) -> Result<Self> {
+        let path = match oracle_location {
+            OracleLocation::Lua(path) => path,
+            OracleLocation::Web(_)
+            | OracleLocation::Script(_)
+            | OracleLocation::Tcp(_)
+            | OracleLocation::Plugin(_)
+            | OracleLocation::Python(_) => {
+                panic!(
+                    "Tried to visit the Lua oracle using a URL, script, socket address, plugin or Python script!"
+                )
+            }
+        };
+
+        let oracle_config = match oracle_config {
+            SubConfig::Lua(config) => config,
+            SubConfig::Web(_)
+            | SubConfig::Script(_)
+            | SubConfig::Tcp(_)
+            | SubConfig::Plugin(_)
+            | SubConfig::Python(_) => {
+                panic!("Tried to visit the Lua oracle using web, script, TCP, plugin or Python configs!")
+            }
+        };
+
+        let oracle = Self {
+            path: path.clone(),
+            script: Script::read(path, oracle_config)?,
+            config: oracle_config.clone(),
+            idle_states: Mutex::new(Vec::new()),
+        };
+        // errors in the script are reported now, rather than as failed questions
+        let state = oracle.script.load(path)?;
+        oracle.idle_states.lock().unwrap().push(state);
+
+        Ok(oracle)
+    }
+
+    fn ask_validation<'a>(&self, cypher_text: &'a impl Encode<'a>) -> Result<bool> {
+        self.config.request_budget().spend()?;
+        if let Some(traffic_shaper) = self.config.traffic_shaper() {
+            thread::sleep(traffic_shaper.reserve());
+        }
+
+        let idle_state = self.idle_states.lock().unwrap().pop();
+        let state = match idle_state {
+            Some(state) => state,
+            None => self.script.load(&self.path)?,
+        };
+        let verdict = interpreter::ask(&self.path, &state, &cypher_text.encode());
+        self.idle_states.lock().unwrap().push(state);
+
+        verdict
+    }
+
+    fn location(&self) -> OracleLocation {
+        OracleLocation::Lua(self.path.clone())
+    }
+    fn thread_delay(&self) -> &ThreadDelay {
+        self.config.thread_delay()
+    }
+    fn request_budget(&self) -> Option<&RequestBudget> {
+        Some(self.config.request_budget())
+    }
+}
+
+/// Checks of `rustpad lint`. The script is run, so it may contact the oracle if it does so outside of `ask_validation`
+pub fn lint(path: &Path, config: &LuaConfig) -> Vec<LintCheck> {
+    vec![LintCheck::new(
+        "Lua script defines `ask_validation`",
+        Script::read(path, config)
+            .and_then(|script| script.load(path))
+            .map(|_| ()),
+    )]
+}
+
+/// Whether `path` has the extension of a Lua script
+pub fn is_script(path: &Path) -> bool {
+    path.extension()
+        .and_then(|extension| extension.to_str())
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("lua"))
+}
+
+#[cfg(feature = "lua")]
+mod interpreter {
+    use std::{fs, path::Path};
+
+    use anyhow::{anyhow, Context, Result};
+    use log::info;
+    use mlua::{Function, Lua, Table, Value};
+
+    use crate::{
+        config::LuaConfig,
+        error::RustpadError,
+        logging::LOG_TARGET,
+        oracle::embedded_http::{EmbeddedHttp, EmbeddedResponse},
+    };
+
+    pub(super) type State = Lua;
+
+    /// Source of the script, and the client of its `rustpad.http`
+    pub(super) struct Script {
+        source: String,
+        http: EmbeddedHttp,
+    }
+
+    // reason given to `rustpad.abort`, kept in the state's app data
+    struct Abort(String);
+
+    impl Script {
+        pub(super) fn read(path: &Path, config: &LuaConfig) -> Result<Self> {
+            Ok(Self {
+                source: fs::read_to_string(path).context(format!(
+                    "Reading the Lua script `{}` failed",
+                    path.display()
+                ))?,
+                http: EmbeddedHttp::new(config.request_timeout(), *config.insecure(), None)?,
+            })
+        }
+
+        /// New Lua state, in which the script was run
+        pub(super) fn load(&self, path: &Path) -> Result<State> {
+            let state = Lua::new();
+            api(&state, &self.http)
+                .and_then(|api| state.globals().set("rustpad", api))
+                .and_then(|_| {
+                    state
+                        .load(&self.source)
+                        .set_name(path.display().to_string())
+                        .exec()
+                })
+                .context(format!(
+                    "Running the Lua script `{}` failed",
+                    path.display()
+                ))?;
+
+            if !matches!(
+                state.globals().get::<_, Value>("ask_validation"),
+                Ok(Value::Function(_))
+            ) {
+                return Err(anyhow!(
+                    "The Lua script `{}` doesn't define the function `ask_validation(cypher_text)`",
+                    path.display()
+                ));
+            }
+
+            Ok(state)
+        }
+    }
+
+    pub(super) fn ask(path: &Path, state: &State, cypher_text: &str) -> Result<bool> {
+        let ask_validation: Function = state.globals().get("ask_validation").context(format!(
+            "The Lua script `{}` doesn't define `ask_validation`",
+            path.display()
+        ))?;
+
+        match ask_validation.call::<_, Value>(cypher_text) {
+            Ok(Value::Boolean(verdict)) => Ok(verdict),
+            Ok(value) => Err(anyhow!(
+                "`ask_validation` of the Lua script `{}` returned a value of type {}, rather than a boolean",
+                path.display(),
+                value.type_name()
+            )),
+            Err(e) => match state.remove_app_data::<Abort>() {
+                Some(Abort(reason)) => Err(RustpadError::OracleAborted {
+                    reason: format!("the Lua script `{}` aborted: {}", path.display(), reason),
+                }
+                .into()),
+                None => Err(anyhow!(e).context(format!(
+                    "`ask_validation` of the Lua script `{}` failed",
+                    path.display()
+                ))),
+            },
+        }
+    }
+
+    /// The `rustpad` table of helpers:
+    /// - `http{ url = ..., method = "GET", headers = { ... }, body = "..." }` sends a request, and returns `{ status = ..., headers = { ... }, body = "..." }`. Header names are lowercase
+    /// - `base64_encode`, `base64_decode`, `hex_encode`, `hex_decode`, `url_encode` and `url_decode` convert strings
+    /// - `log(message)` logs a message
+    /// - `abort(reason)` stops the attack
+    fn api<'lua>(state: &'lua Lua, http: &EmbeddedHttp) -> mlua::Result<Table<'lua>> {
+        let api = state.create_table()?;
+
+        let http = http.clone();
+        api.set(
+            "http",
+            state.create_function(move |state, request: Table| {
+                let url: String = request.get("url")?;
+                let method: Option<String> = request.get("method")?;
+                let headers = match request.get::<_, Option<Table>>("headers")? {
+                    Some(headers) => headers
+                        .pairs::<String, String>()
+                        .collect::<mlua::Result<_>>()?,
+                    None => Vec::new(),
+                };
+                let body = request
+                    .get::<_, Option<mlua::String>>("body")?
+                    .map(|body| body.as_bytes().to_vec());
+
+                let EmbeddedResponse {
+                    status,
+                    headers,
+                    body,
+                } = http
+                    .send(method.as_deref().unwrap_or("GET"), &url, headers, body)
+                    .map_err(mlua::Error::external)?;
+
+                let response = state.create_table()?;
+                response.set("status", status)?;
+                let response_headers = state.create_table()?;
+                for (name, value) in headers.iter() {
+                    response_headers.set(name.as_str(), state.create_string(value.as_bytes())?)?;
+                }
+                response.set("headers", response_headers)?;
+                response.set("body", state.create_string(&body)?)?;
+                Ok(response)
+            })?,
+        )?;
+
+        api.set(
+            "base64_encode",
+            state.create_function(|_, data: mlua::String| Ok(base64::encode(data.as_bytes())))?,
+        )?;
+        api.set(
+            "base64_decode",
+            state.create_function(|state, data: String| {
+                state.create_string(base64::decode(data.trim()).map_err(mlua::Error::external)?)
+            })?,
+        )?;
+        api.set(
+            "hex_encode",
+            state.create_function(|_, data: mlua::String| Ok(hex::encode(data.as_bytes())))?,
+        )?;
+        api.set(
+            "hex_decode",
+            state.create_function(|state, data: String| {
+                state.create_string(hex::decode(data.trim()).map_err(mlua::Error::external)?)
+            })?,
+        )?;
+        api.set(
+            "url_encode",
+            state.create_function(|_, data: String| Ok(urlencoding::encode(&data).into_owned()))?,
+        )?;
+        api.set(
+            "url_decode",
+            state.create_function(|_, data: String| {
+                urlencoding::decode(&data)
+                    .map(|data| data.into_owned())
+                    .map_err(mlua::Error::external)
+            })?,
+        )?;
+        api.set(
+            "log",
+            state.create_function(|_, message: String| {
+                info!(target: LOG_TARGET, "{}", message);
+                Ok(())
+            })?,
+        )?;
+        api.set(
+            "abort",
+            state.create_function(|state, reason: Option<String>| {
+                let reason = reason.unwrap_or_else(|| "no reason given".to_string());
+                state.set_app_data(Abort(reason.clone()));
+                Err::<(), _>(mlua::Error::runtime(format!("aborted: {}", reason)))
+            })?,
+        )?;
+
+        Ok(api)
+    }
+}
+
+#[cfg(not(feature = "lua"))]
+mod interpreter {
+    use std::path::Path;
+
+    use anyhow::{anyhow, Result};
+
+    use crate::config::LuaConfig;
+
+    // no oracle can be visited without an interpreter
+    pub(super) enum Script {}
+    pub(super) enum State {}
+
+    impl Script {
+        pub(super) fn read(_path: &Path, _config: &LuaConfig) -> Result<Self> {
+            Err(anyhow!(
+                "This build of rustpad has no Lua support. Rebuild it with `--features lua`"
+            ))
+        }
+
+        pub(super) fn load(&self, _path: &Path) -> Result<State> {
+            match *self {}
+        }
+    }
+
+    pub(super) fn ask(_path: &Path, state: &State, _cypher_text: &str) -> Result<bool> {
+        match *state {}
+    }
+}
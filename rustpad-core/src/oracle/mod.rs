@@ -0,0 +1,94 @@
+pub mod certificate_pin;
+mod computed_placeholder;
+#[cfg(any(feature = "lua", feature = "wasm"))]
+mod embedded_http;
+pub mod lua;
+pub mod oracle_location;
+pub mod plugin;
+pub mod python;
+pub mod request_budget;
+pub mod script;
+pub mod sticky_session;
+pub mod tcp;
+pub mod traffic_shaper;
+pub mod web;
+
+use std::{
+    sync::atomic::{AtomicBool, Ordering},
+    thread,
+    time::{Duration, Instant},
+};
+
+use anyhow::Result;
+use rayon::iter::{ParallelBridge, ParallelIterator};
+
+use crate::{
+    config::{thread_delay::ThreadDelay, SubConfig},
+    cypher_text::{encode::Encode, forged_cypher_text::ForgedCypherText},
+    error::RustpadError,
+};
+
+use self::{oracle_location::OracleLocation, request_budget::RequestBudget};
+
+pub trait Oracle: Sync {
+    /// Constructor
+    fn visit(oracle_location: &OracleLocation, oracle_config: &SubConfig) -> Result<Self>
+    where
+        Self: Sized;
+
+    /// Ask endpoint to verify cypher text. Return true if padding is valid.
+    fn ask_validation<'a>(&self, cypher_text: &'a impl Encode<'a>) -> Result<bool>;
+
+    /// Ask endpoint to verify multiple cypher texts. Questioning stops as soon as a cypher text with valid padding is found.
+    /// Returns, per cypher text, `None` if it wasn't asked, or the outcome of `ask_validation`. `on_answer` is called with the index of each answered cypher text, and how long the answer took.
+    /// By default, the cypher texts are spread over the thread pool. Oracles which can multiplex requests should override this.
+    fn ask_validation_batch(
+        &self,
+        cypher_texts: &[ForgedCypherText],
+        on_answer: &(dyn Fn(usize, Duration) + Sync),
+    ) -> Vec<Option<Result<bool>>> {
+        let stop_questioning = AtomicBool::new(false);
+
+        // cypher texts are handed out in order, so the likeliest candidates, which come first, are questioned first
+        let answers: Vec<(usize, Result<bool>)> = cypher_texts
+            .iter()
+            .enumerate()
+            .par_bridge()
+            .filter_map(|(idx, cypher_text)| {
+                if stop_questioning.load(Ordering::Relaxed) {
+                    return None;
+                }
+
+                thread::sleep(**self.thread_delay());
+                let start = Instant::now();
+                let verdict = self.ask_validation(cypher_text);
+                on_answer(idx, start.elapsed());
+
+                let fatal = matches!(&verdict, Err(e) if RustpadError::is_fatal(e));
+                if matches!(verdict, Ok(true)) || fatal {
+                    stop_questioning.store(true, Ordering::Relaxed);
+                }
+                Some((idx, verdict))
+            })
+            .collect();
+
+        let mut verdicts: Vec<Option<Result<bool>>> = cypher_texts.iter().map(|_| None).collect();
+        for (idx, verdict) in answers {
+            verdicts[idx] = Some(verdict);
+        }
+        verdicts
+    }
+
+    /// Determine anew how the oracle responds to incorrect padding, e.g. after the target was redeployed. `calibration_cypher_text` is questioned with every value of its current byte.
+    /// Returns whether the oracle was recalibrated. By default, there's nothing to calibrate.
+    fn recalibrate(&self, _calibration_cypher_text: &ForgedCypherText) -> Result<bool> {
+        Ok(false)
+    }
+
+    fn location(&self) -> OracleLocation;
+    fn thread_delay(&self) -> &ThreadDelay;
+    /// Limits on questioning the oracle, which are checked before a block is started. By default, there are none
+    fn request_budget(&self) -> Option<&RequestBudget> {
+        None
+    }
+}
@@ -0,0 +1,119 @@
+use anyhow::{anyhow, Context, Result};
+use is_executable::IsExecutable;
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+use std::{path::PathBuf, str::FromStr};
+
+use super::{lua, plugin, python};
+
+#[derive(Debug, Clone)]
+pub enum OracleLocation {
+    Web(Url),
+    Script(PathBuf),
+    // `<host>:<port>`
+    Tcp(String),
+    // shared library or WASM module, see `plugin::PluginOracle`
+    Plugin(PathBuf),
+    // see `lua::LuaOracle`
+    Lua(PathBuf),
+    // see `python::PythonOracle`
+    Python(PathBuf),
+}
+
+#[derive(Serialize, Deserialize, Hash, PartialEq, Eq, Clone)]
+pub enum SerializableOracleLocation {
+    Web(String),
+    Script(PathBuf),
+    Tcp(String),
+    Plugin(PathBuf),
+    Lua(PathBuf),
+    Python(PathBuf),
+}
+
+impl OracleLocation {
+    /// Kind of oracle, as logged
+    pub fn kind(&self) -> &'static str {
+        match self {
+            OracleLocation::Web(_) => "web",
+            OracleLocation::Script(_) => "script",
+            OracleLocation::Tcp(_) => "TCP",
+            OracleLocation::Plugin(_) => "plugin",
+            OracleLocation::Lua(_) => "Lua",
+            OracleLocation::Python(_) => "Python",
+        }
+    }
+}
+
+impl FromStr for OracleLocation {
+    type Err = anyhow::Error;
+
+    fn from_str(oracle_location: &str) -> Result<Self> {
+        if is_socket_address(oracle_location) {
+            return Ok(Self::Tcp(oracle_location.to_string()));
+        }
+
+        Url::parse(oracle_location).map(Self::Web).or_else(|_| {
+            let path = PathBuf::from(oracle_location);
+            if path.is_file() && plugin::is_plugin(&path) {
+                Ok(Self::Plugin(path))
+            } else if let Some(path) = plugin::find(oracle_location) {
+                Ok(Self::Plugin(path))
+            } else if !path.is_file() {
+                Err(anyhow!(
+                    "`{}` does not point to a file. Double check the path",
+                    oracle_location
+                ))
+            } else if lua::is_script(&path) && !path.is_executable() {
+                // executable ones may also be run as scripts, e.g. with a `#!/usr/bin/env lua` shebang. See `--oracle` of the `lua` sub-command
+                Ok(Self::Lua(path))
+            } else if python::is_script(&path) && !path.is_executable() {
+                // as for Lua, see `--oracle` of the `python` sub-command
+                Ok(Self::Python(path))
+            } else if !path.is_executable() {
+                Err(anyhow!(
+                    "`{}` is not executable. Double check its permissions",
+                    oracle_location
+                ))
+            } else {
+                Ok(Self::Script(path))
+            }
+        })
+    }
+}
+
+impl From<OracleLocation> for SerializableOracleLocation {
+    fn from(oracle_location: OracleLocation) -> Self {
+        match oracle_location {
+            OracleLocation::Web(url) => Self::Web(String::from(url.as_str())),
+            OracleLocation::Script(path) => Self::Script(path),
+            OracleLocation::Tcp(address) => Self::Tcp(address),
+            OracleLocation::Plugin(path) => Self::Plugin(path),
+            OracleLocation::Lua(path) => Self::Lua(path),
+            OracleLocation::Python(path) => Self::Python(path),
+        }
+    }
+}
+
+impl From<SerializableOracleLocation> for OracleLocation {
+    fn from(oracle_location: SerializableOracleLocation) -> Self {
+        match oracle_location {
+            SerializableOracleLocation::Web(url) => Self::Web(url.parse().context("URL stored in cache is invalid").expect("Data stored in the cache was verified when it was created. As such, the only possible reason for this must be a corrupted cache file.")),
+            SerializableOracleLocation::Script(path) => Self::Script(path),
+            SerializableOracleLocation::Tcp(address) => Self::Tcp(address),
+            SerializableOracleLocation::Plugin(path) => Self::Plugin(path),
+            SerializableOracleLocation::Lua(path) => Self::Lua(path),
+            SerializableOracleLocation::Python(path) => Self::Python(path),
+        }
+    }
+}
+
+// `<host>:<port>`, without a scheme. E.g. `localhost:8080` would otherwise be parsed as a URL with scheme `localhost`
+fn is_socket_address(oracle_location: &str) -> bool {
+    !oracle_location.contains("://")
+        && oracle_location
+            .rsplit_once(':')
+            .map(|(host, port)| {
+                !host.is_empty() && !host.contains('/') && port.parse::<u16>().is_ok()
+            })
+            .unwrap_or(false)
+}
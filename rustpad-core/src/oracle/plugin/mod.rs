@@ -0,0 +1,255 @@
+pub mod wasm;
+
+use std::{
+    env::consts::{DLL_PREFIX, DLL_SUFFIX},
+    ffi::{c_char, c_void, CString},
+    path::{Path, PathBuf},
+    thread,
+};
+
+use anyhow::{anyhow, Context, Result};
+use libloading::Library;
+
+use crate::{
+    config::{thread_delay::ThreadDelay, PluginConfig, SubConfig},
+    cypher_text::encode::Encode,
+    error::RustpadError,
+    lint::LintCheck,
+};
+
+use self::wasm::WasmPlugin;
+
+use super::{oracle_location::OracleLocation, request_budget::RequestBudget, Oracle};
+
+/// Version of the plugin ABI, which a plugin must return from `rustpad_plugin_abi_version`. Bumped on any change to the exported functions
+pub const PLUGIN_ABI_VERSION: u32 = 1;
+/// The cypher text has valid padding
+pub const PLUGIN_PADDING_VALID: i32 = 1;
+/// The cypher text has invalid padding
+pub const PLUGIN_PADDING_INVALID: i32 = 0;
+/// Questioning the oracle failed. The question is retried
+pub const PLUGIN_ORACLE_FAILED: i32 = -1;
+/// Stop the attack
+pub const PLUGIN_ORACLE_ABORT: i32 = -2;
+
+type AbiVersionFn = unsafe extern "C" fn() -> u32;
+type OpenFn = unsafe extern "C" fn(config: *const c_char) -> *mut c_void;
+type AskFn =
+    unsafe extern "C" fn(state: *mut c_void, cypher_text: *const u8, cypher_text_len: usize) -> i32;
+type CloseFn = unsafe extern "C" fn(state: *mut c_void);
+
+// the plugin vouches that its state may be used from multiple threads at once, see `rustpad_plugin.h`
+struct PluginState(*mut c_void);
+unsafe impl Send for PluginState {}
+unsafe impl Sync for PluginState {}
+
+/// Oracle implemented by a plugin, for transports which rustpad doesn't speak itself. Either a shared library, see `rustpad-ffi/include/rustpad_plugin.h` for the functions it exports, or a WASM module, see `wasm::WasmPlugin`
+pub struct PluginOracle {
+    path: PathBuf,
+    config: PluginConfig,
+    plugin: Plugin,
+}
+
+enum Plugin {
+    Native(NativePlugin),
+    Wasm(WasmPlugin),
+}
+
+/// Shared library loaded into the process. Its `unsafe` calls rely on the contract in `rustpad_plugin.h`: the functions have the declared signatures and don't unwind, `ask` is thread-safe, and the state stays valid until `close`, which is called once, on drop
+struct NativePlugin {
+    ask: AskFn,
+    close: CloseFn,
+    state: PluginState,
+    // must outlive the function pointers above, so it's dropped last
+    _library: Library,
+}
+
+impl Oracle for PluginOracle {
+    fn visit(oracle_location: &OracleLocation, oracle_config: &SubConfig) -> Result<Self> {
+        let path = match oracle_location {
+            OracleLocation::Plugin(path) => path,
+            OracleLocation::Web(_)
+            | OracleLocation::Script(_)
+            | OracleLocation::Tcp(_)
+            | OracleLocation::Lua(_)
+            | OracleLocation::Python(_) => {
+                panic!("Tried to visit the plugin oracle using a URL, script, socket address, Lua or Python script!")
+            }
+        };
+
+        let oracle_config = match oracle_config {
+            SubConfig::Plugin(config) => config,
+            SubConfig::Web(_)
+            | SubConfig::Script(_)
+            | SubConfig::Tcp(_)
+            | SubConfig::Lua(_)
+            | SubConfig::Python(_) => {
+                panic!("Tried to visit the plugin oracle using web, script, TCP, Lua or Python configs!")
+            }
+        };
+
+        let plugin = if wasm::is_module(path) {
+            Plugin::Wasm(WasmPlugin::load(path, oracle_config)?)
+        } else {
+            Plugin::Native(NativePlugin::open(path, oracle_config)?)
+        };
+
+        Ok(Self {
+            path: path.clone(),
+            config: oracle_config.clone(),
+            plugin,
+        })
+    }
+
+    fn ask_validation<'a>(&self, cypher_text: &'a impl Encode<'a>) -> Result<bool> {
+        self.config.request_budget().spend()?;
+        if let Some(traffic_shaper) = self.config.traffic_shaper() {
+            thread::sleep(traffic_shaper.reserve());
+        }
+        let cypher_text = cypher_text.encode();
+
+        let answer = match &self.plugin {
+            Plugin::Native(plugin) => plugin.ask(cypher_text.as_bytes()),
+            Plugin::Wasm(plugin) => plugin.ask(cypher_text.as_bytes())?,
+        };
+        match answer {
+            PLUGIN_PADDING_VALID => Ok(true),
+            PLUGIN_PADDING_INVALID => Ok(false),
+            PLUGIN_ORACLE_ABORT => Err(RustpadError::OracleAborted {
+                reason: format!("the plugin `{}` aborted", self.path.display()),
+            }
+            .into()),
+            code => Err(anyhow!(
+                "The plugin `{}` failed with code {}",
+                self.path.display(),
+                code
+            )),
+        }
+    }
+
+    fn location(&self) -> OracleLocation {
+        OracleLocation::Plugin(self.path.clone())
+    }
+    fn thread_delay(&self) -> &ThreadDelay {
+        self.config.thread_delay()
+    }
+    fn request_budget(&self) -> Option<&RequestBudget> {
+        Some(self.config.request_budget())
+    }
+}
+
+impl NativePlugin {
+    fn open(path: &Path, config: &PluginConfig) -> Result<Self> {
+        let (library, open, ask, close) = load(path)?;
+
+        let plugin_config = CString::new(config.plugin_config().as_deref().unwrap_or(""))
+            .context("The plugin's config can't contain NUL bytes")?;
+        let state = unsafe { open(plugin_config.as_ptr()) };
+        if state.is_null() {
+            return Err(anyhow!(
+                "The plugin `{}` failed to open. Double check `--plugin-config`",
+                path.display()
+            ));
+        }
+
+        Ok(Self {
+            ask,
+            close,
+            state: PluginState(state),
+            _library: library,
+        })
+    }
+
+    fn ask(&self, cypher_text: &[u8]) -> i32 {
+        unsafe { (self.ask)(self.state.0, cypher_text.as_ptr(), cypher_text.len()) }
+    }
+}
+
+impl Drop for NativePlugin {
+    fn drop(&mut self) {
+        unsafe { (self.close)(self.state.0) };
+    }
+}
+
+/// Checks of `rustpad lint`. Loading a shared library runs its initialisers, but it isn't opened, so it doesn't contact the oracle
+pub fn lint(path: &Path) -> Vec<LintCheck> {
+    if wasm::is_module(path) {
+        return vec![LintCheck::new(
+            "WASM plugin exports the functions of the plugin ABI",
+            wasm::check_exports(path),
+        )];
+    }
+
+    vec![LintCheck::new(
+        "Plugin exports the functions of the plugin ABI",
+        load(path).map(|_| ()),
+    )]
+}
+
+/// Directory in which plugins are looked up by name: `<config dir>/rustpad/plugins`
+pub fn plugins_dir() -> Option<PathBuf> {
+    dirs::config_dir().map(|dir| dir.join("rustpad").join("plugins"))
+}
+
+/// Path of the plugin called `name` in the plugins directory, e.g. `libmqtt.so` or `mqtt.wasm` for `mqtt` on Linux
+pub fn find(name: &str) -> Option<PathBuf> {
+    if name.is_empty() || name.contains(['/', '\\']) {
+        return None;
+    }
+
+    let dir = plugins_dir()?;
+    [
+        dir.join(format!("{}{}{}", DLL_PREFIX, name, DLL_SUFFIX)),
+        dir.join(format!("{}.wasm", name)),
+    ]
+    .into_iter()
+    .find(|path| path.is_file())
+}
+
+/// Whether `path` has the extension of a shared library on this platform, or of a WASM module
+pub fn is_plugin(path: &Path) -> bool {
+    wasm::is_module(path)
+        || path
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .is_some_and(|extension| Some(extension) == DLL_SUFFIX.strip_prefix('.'))
+}
+
+/// Load the plugin, and check that it implements this version of the plugin ABI
+fn load(path: &Path) -> Result<(Library, OpenFn, AskFn, CloseFn)> {
+    // loading a library runs its initialisers. The user chose to run the plugin, as with a script
+    let library = unsafe { Library::new(path) }
+        .context(format!("Loading the plugin `{}` failed", path.display()))?;
+    let (abi_version, open, ask, close) = unsafe {
+        (
+            *symbol::<AbiVersionFn>(&library, "rustpad_plugin_abi_version", path)?,
+            *symbol::<OpenFn>(&library, "rustpad_plugin_open", path)?,
+            *symbol::<AskFn>(&library, "rustpad_plugin_ask", path)?,
+            *symbol::<CloseFn>(&library, "rustpad_plugin_close", path)?,
+        )
+    };
+
+    let abi_version = unsafe { abi_version() };
+    if abi_version != PLUGIN_ABI_VERSION {
+        return Err(anyhow!(
+            "The plugin `{}` implements plugin ABI version {}, but version {} is expected. Rebuild it against this version of rustpad",
+            path.display(),
+            abi_version,
+            PLUGIN_ABI_VERSION
+        ));
+    }
+
+    Ok((library, open, ask, close))
+}
+
+unsafe fn symbol<'lib, T>(
+    library: &'lib Library,
+    name: &str,
+    path: &Path,
+) -> Result<libloading::Symbol<'lib, T>> {
+    library.get(name.as_bytes()).context(format!(
+        "The plugin `{}` doesn't export `{}`",
+        path.display(),
+        name
+    ))
+}
@@ -0,0 +1,443 @@
+use std::path::Path;
+
+pub(super) use self::runtime::{check_exports, WasmPlugin};
+
+/// Whether `path` has the extension of a WASM module, in its binary or text format
+pub fn is_module(path: &Path) -> bool {
+    path.extension()
+        .and_then(|extension| extension.to_str())
+        .is_some_and(|extension| {
+            extension.eq_ignore_ascii_case("wasm") || extension.eq_ignore_ascii_case("wat")
+        })
+}
+
+#[cfg(feature = "wasm")]
+mod runtime {
+    use std::{
+        path::{Path, PathBuf},
+        sync::Mutex,
+        thread,
+        time::Duration,
+    };
+
+    use anyhow::{anyhow, Context, Result};
+    use log::{info, Level};
+    use wasmtime::{
+        Caller, Config, Engine, Extern, ExternType, FuncType, InstancePre, Linker, Memory, Module,
+        Store, StoreLimits, StoreLimitsBuilder, TypedFunc,
+    };
+
+    use crate::{
+        config::PluginConfig,
+        logging::{log_coalesced, LOG_TARGET},
+        oracle::embedded_http::{EmbeddedHttp, EmbeddedResponse},
+    };
+
+    // a module asking for more is likely broken, rather than busy
+    const MAX_MEMORY: usize = 256 * 1024 * 1024;
+    // module of the host functions a plugin may import
+    const HOST_MODULE: &str = "rustpad";
+    // how often the engine's epoch advances, which is when running calls check their deadline
+    const EPOCH_TICK: Duration = Duration::from_millis(100);
+    // of a single call into the module, in epoch ticks. A call running longer is likely stuck in a loop. Requests sent with `http` are bounded by `--timeout` instead, as the deadline is only checked while the module runs
+    const CALL_DEADLINE_TICKS: u64 = 300;
+
+    /// Plugin compiled to WebAssembly, e.g. `--oracle mqtt.wasm`. Unlike a shared library, it's sandboxed: it can't touch the machine it runs on, and only sends requests through the host functions below, to the hosts of `--allow-host`. Calls into it are interrupted once they run for 30 seconds. As it's portable too, it's safe to share between teams.
+    /// The module exports:
+    /// - `memory`
+    /// - `alloc(len: i32) -> i32`, returning where to write `len` bytes for the module. It's called before every `validate`, so the module may hand out the same buffer every time
+    /// - `validate(ptr: i32, len: i32) -> i32`, which answers whether the encoded cypher text at `ptr` has valid padding, as `rustpad_plugin_ask` does
+    /// - optionally `init(ptr: i32, len: i32) -> i32`, which is handed `--plugin-config`, and returns 0 on success
+    ///
+    /// It may import these functions from the `rustpad` module. Strings are given as pointer and length:
+    /// - `http(method, url, headers, body) -> i32` sends a request, and returns the response's status, or -1 on failure, e.g. if the host isn't allowed. `headers` are `<name>: <value>` lines
+    /// - `response_body(ptr, cap) -> i32` copies up to `cap` bytes of the last response's body to `ptr`, and returns the body's full length
+    /// - `response_header(name, ptr, cap) -> i32` does the same for a header of the last response, or returns -1 if it's missing
+    /// - `log(message)` logs a message
+    pub(in crate::oracle::plugin) struct WasmPlugin {
+        path: PathBuf,
+        instance_pre: InstancePre<Host>,
+        http: EmbeddedHttp,
+        plugin_config: Option<String>,
+        // a store can only be used by one thread at a time. Each question takes an idle instance, or instantiates the module anew
+        idle_instances: Mutex<Vec<Instance>>,
+    }
+
+    /// State of an instance which its host functions access
+    struct Host {
+        http: EmbeddedHttp,
+        // read with `response_body` and `response_header`
+        last_response: Option<EmbeddedResponse>,
+        limits: StoreLimits,
+    }
+
+    struct Instance {
+        store: Store<Host>,
+        memory: Memory,
+        alloc: TypedFunc<i32, i32>,
+        validate: TypedFunc<(i32, i32), i32>,
+    }
+
+    impl WasmPlugin {
+        pub(in crate::oracle::plugin) fn load(path: &Path, config: &PluginConfig) -> Result<Self> {
+            let engine = engine()?;
+            let module = compile(&engine, path)?;
+            let instance_pre = linker(&engine)?.instantiate_pre(&module).context(format!(
+                "The WASM plugin `{}` imports functions which rustpad doesn't provide",
+                path.display()
+            ))?;
+
+            let plugin = Self {
+                path: path.to_path_buf(),
+                instance_pre,
+                http: EmbeddedHttp::new(
+                    config.request_timeout(),
+                    *config.insecure(),
+                    Some(config.allowed_hosts()),
+                )?,
+                plugin_config: config.plugin_config().clone(),
+                idle_instances: Mutex::new(Vec::new()),
+            };
+            // errors in the module are reported now, rather than as failed questions
+            let instance = plugin.instantiate()?;
+            plugin.idle_instances.lock().unwrap().push(instance);
+
+            Ok(plugin)
+        }
+
+        /// One of the answers of `rustpad_plugin_ask`
+        pub(in crate::oracle::plugin) fn ask(&self, cypher_text: &[u8]) -> Result<i32> {
+            let idle_instance = self.idle_instances.lock().unwrap().pop();
+            let mut instance = match idle_instance {
+                Some(instance) => instance,
+                None => self.instantiate()?,
+            };
+
+            // an instance which trapped is in an unknown state. Dropping it makes the next question instantiate a fresh one
+            instance.store.set_epoch_deadline(CALL_DEADLINE_TICKS);
+            let (ptr, len) = instance.write(cypher_text)?;
+            let answer = instance
+                .validate
+                .call(&mut instance.store, (ptr, len))
+                .context(format!(
+                    "`validate` of the WASM plugin `{}` trapped",
+                    self.path.display()
+                ))?;
+            self.idle_instances.lock().unwrap().push(instance);
+
+            Ok(answer)
+        }
+
+        fn instantiate(&self) -> Result<Instance> {
+            let mut store = Store::new(
+                self.instance_pre.module().engine(),
+                Host {
+                    http: self.http.clone(),
+                    last_response: None,
+                    limits: StoreLimitsBuilder::new().memory_size(MAX_MEMORY).build(),
+                },
+            );
+            store.limiter(|host| &mut host.limits);
+            // covers the module's start function and `init`
+            store.set_epoch_deadline(CALL_DEADLINE_TICKS);
+
+            let instance = self.instance_pre.instantiate(&mut store).context(format!(
+                "Instantiating the WASM plugin `{}` failed",
+                self.path.display()
+            ))?;
+            let export_error = |name: &str| {
+                format!(
+                    "The WASM plugin `{}` doesn't export `{}`, or with another type. See `--oracle` of the `plugin` sub-command",
+                    self.path.display(),
+                    name
+                )
+            };
+            let init = instance
+                .get_typed_func::<(i32, i32), i32>(&mut store, "init")
+                .ok();
+            let mut plugin_instance = Instance {
+                memory: instance
+                    .get_memory(&mut store, "memory")
+                    .ok_or_else(|| anyhow!(export_error("memory")))?,
+                alloc: instance
+                    .get_typed_func(&mut store, "alloc")
+                    .context(export_error("alloc"))?,
+                validate: instance
+                    .get_typed_func(&mut store, "validate")
+                    .context(export_error("validate"))?,
+                store,
+            };
+
+            if let Some(init) = init {
+                let config = plugin_instance
+                    .write(self.plugin_config.as_deref().unwrap_or("").as_bytes())?;
+                let outcome = init
+                    .call(&mut plugin_instance.store, config)
+                    .context(format!(
+                        "`init` of the WASM plugin `{}` trapped",
+                        self.path.display()
+                    ))?;
+                if outcome != 0 {
+                    return Err(anyhow!(
+                        "The WASM plugin `{}` failed to initialise with code {}. Double check `--plugin-config`",
+                        self.path.display(),
+                        outcome
+                    ));
+                }
+            }
+
+            Ok(plugin_instance)
+        }
+    }
+
+    impl Instance {
+        /// Copy `data` into the instance's memory, returning its pointer and length
+        fn write(&mut self, data: &[u8]) -> Result<(i32, i32)> {
+            let len = i32::try_from(data.len()).context("Data is too large for a WASM plugin")?;
+            let ptr = self
+                .alloc
+                .call(&mut self.store, len)
+                .context("`alloc` of the WASM plugin trapped")?;
+            self.memory
+                .write(&mut self.store, ptr as u32 as usize, data)
+                .context("`alloc` of the WASM plugin returned memory out of bounds")?;
+
+            Ok((ptr, len))
+        }
+    }
+
+    /// Check of `rustpad lint`. The module is compiled, but not instantiated, so it doesn't contact the oracle
+    pub(in crate::oracle::plugin) fn check_exports(path: &Path) -> Result<()> {
+        let engine = engine()?;
+        let module = compile(&engine, path)?;
+        linker(&engine)?.instantiate_pre(&module).context(format!(
+            "The WASM plugin `{}` imports functions which rustpad doesn't provide",
+            path.display()
+        ))?;
+
+        let is_func = |name: &str, params: usize| match module.get_export(name) {
+            Some(ExternType::Func(func_type)) => takes_i32s(&func_type, params),
+            _ => false,
+        };
+        let missing: Vec<&str> = [
+            (
+                "memory",
+                matches!(module.get_export("memory"), Some(ExternType::Memory(_))),
+            ),
+            ("alloc(len: i32) -> i32", is_func("alloc", 1)),
+            (
+                "validate(ptr: i32, len: i32) -> i32",
+                is_func("validate", 2),
+            ),
+        ]
+        .into_iter()
+        .filter(|(_, exported)| !exported)
+        .map(|(export, _)| export)
+        .collect();
+        if !missing.is_empty() {
+            return Err(anyhow!(
+                "The WASM plugin `{}` doesn't export: {}",
+                path.display(),
+                missing.join(", ")
+            ));
+        }
+
+        Ok(())
+    }
+
+    // `(i32, ...) -> i32`
+    fn takes_i32s(func_type: &FuncType, params: usize) -> bool {
+        func_type.params().len() == params
+            && func_type.params().all(|param| param.is_i32())
+            && func_type.results().len() == 1
+            && func_type.results().all(|result| result.is_i32())
+    }
+
+    /// Engine of which calls into modules are interrupted once past their deadline. The engine's epoch is advanced by a thread, which stops once the engine is dropped
+    fn engine() -> Result<Engine> {
+        let mut config = Config::new();
+        config.epoch_interruption(true);
+        let engine = Engine::new(&config).context("WASM engine setup failed")?;
+
+        let weak_engine = engine.weak();
+        thread::Builder::new()
+            .name("WASM epoch".to_string())
+            .spawn(move || {
+                while let Some(engine) = weak_engine.upgrade() {
+                    engine.increment_epoch();
+                    drop(engine);
+                    thread::sleep(EPOCH_TICK);
+                }
+            })
+            .context("WASM epoch thread failed to start")?;
+
+        Ok(engine)
+    }
+
+    fn compile(engine: &Engine, path: &Path) -> Result<Module> {
+        Module::from_file(engine, path).context(format!(
+            "Compiling the WASM plugin `{}` failed",
+            path.display()
+        ))
+    }
+
+    /// The host functions, see `WasmPlugin`
+    fn linker(engine: &Engine) -> Result<Linker<Host>> {
+        let mut linker = Linker::new(engine);
+
+        linker.func_wrap(
+            HOST_MODULE,
+            "http",
+            |mut caller: Caller<'_, Host>,
+             method_ptr: i32,
+             method_len: i32,
+             url_ptr: i32,
+             url_len: i32,
+             headers_ptr: i32,
+             headers_len: i32,
+             body_ptr: i32,
+             body_len: i32|
+             -> Result<i32> {
+                let method = read_string(&mut caller, method_ptr, method_len)?;
+                let url = read_string(&mut caller, url_ptr, url_len)?;
+                let headers = read_string(&mut caller, headers_ptr, headers_len)?
+                    .lines()
+                    .filter_map(|line| line.split_once(':'))
+                    .map(|(name, value)| (name.trim().to_string(), value.trim().to_string()))
+                    .collect();
+                let body = read(&mut caller, body_ptr, body_len)?;
+                let body = (!body.is_empty()).then_some(body);
+
+                let host = caller.data_mut();
+                host.last_response = None;
+                match host.http.send(&method, &url, headers, body) {
+                    Ok(response) => {
+                        let status = response.status;
+                        host.last_response = Some(response);
+                        Ok(i32::from(status))
+                    }
+                    Err(e) => {
+                        log_coalesced(Level::Warn, "WASM plugin request", format!("{:#}", e));
+                        Ok(-1)
+                    }
+                }
+            },
+        )?;
+
+        linker.func_wrap(
+            HOST_MODULE,
+            "response_body",
+            |mut caller: Caller<'_, Host>, ptr: i32, cap: i32| -> Result<i32> {
+                let body = caller
+                    .data()
+                    .last_response
+                    .as_ref()
+                    .map(|response| response.body.clone())
+                    .unwrap_or_default();
+                copy_out(&mut caller, &body, ptr, cap)
+            },
+        )?;
+
+        linker.func_wrap(
+            HOST_MODULE,
+            "response_header",
+            |mut caller: Caller<'_, Host>,
+             name_ptr: i32,
+             name_len: i32,
+             ptr: i32,
+             cap: i32|
+             -> Result<i32> {
+                let name = read_string(&mut caller, name_ptr, name_len)?;
+                let value = caller.data().last_response.as_ref().and_then(|response| {
+                    response
+                        .headers
+                        .get(name.as_str())
+                        .map(|value| value.as_bytes().to_vec())
+                });
+                match value {
+                    Some(value) => copy_out(&mut caller, &value, ptr, cap),
+                    None => Ok(-1),
+                }
+            },
+        )?;
+
+        linker.func_wrap(
+            HOST_MODULE,
+            "log",
+            |mut caller: Caller<'_, Host>, ptr: i32, len: i32| -> Result<()> {
+                let message = read_string(&mut caller, ptr, len)?;
+                info!(target: LOG_TARGET, "{}", message);
+                Ok(())
+            },
+        )?;
+
+        Ok(linker)
+    }
+
+    fn memory(caller: &mut Caller<'_, Host>) -> Result<Memory> {
+        caller
+            .get_export("memory")
+            .and_then(Extern::into_memory)
+            .ok_or_else(|| anyhow!("The WASM plugin doesn't export `memory`"))
+    }
+
+    fn read(caller: &mut Caller<'_, Host>, ptr: i32, len: i32) -> Result<Vec<u8>> {
+        let memory = memory(caller)?;
+        let start = ptr as u32 as usize;
+        let len = usize::try_from(len).context("Negative length")?;
+        // checked before allocating, so a bogus length can't make the host allocate more than the module's memory
+        let data = start
+            .checked_add(len)
+            .and_then(|end| memory.data(&caller).get(start..end))
+            .context("The WASM plugin passed memory out of bounds")?;
+        Ok(data.to_vec())
+    }
+
+    fn read_string(caller: &mut Caller<'_, Host>, ptr: i32, len: i32) -> Result<String> {
+        String::from_utf8(read(caller, ptr, len)?).context("The WASM plugin passed invalid UTF-8")
+    }
+
+    // as much of `data` as fits in `cap` bytes. Returns the full length, so the plugin can retry with a larger buffer
+    fn copy_out(caller: &mut Caller<'_, Host>, data: &[u8], ptr: i32, cap: i32) -> Result<i32> {
+        let amount = data
+            .len()
+            .min(usize::try_from(cap).context("Negative capacity")?);
+        memory(caller)?
+            .write(caller, ptr as u32 as usize, &data[..amount])
+            .context("The WASM plugin passed memory out of bounds")?;
+        i32::try_from(data.len()).context("Data is too large for a WASM plugin")
+    }
+}
+
+#[cfg(not(feature = "wasm"))]
+mod runtime {
+    use std::path::Path;
+
+    use anyhow::{anyhow, Result};
+
+    use crate::config::PluginConfig;
+
+    // no plugin can be loaded without a runtime
+    pub(in crate::oracle::plugin) enum WasmPlugin {}
+
+    impl WasmPlugin {
+        pub(in crate::oracle::plugin) fn load(
+            _path: &Path,
+            _config: &PluginConfig,
+        ) -> Result<Self> {
+            Err(unsupported())
+        }
+
+        pub(in crate::oracle::plugin) fn ask(&self, _cypher_text: &[u8]) -> Result<i32> {
+            match *self {}
+        }
+    }
+
+    pub(in crate::oracle::plugin) fn check_exports(_path: &Path) -> Result<()> {
+        Err(unsupported())
+    }
+
+    fn unsupported() -> anyhow::Error {
+        anyhow!("This build of rustpad has no WASM support. Rebuild it with `--features wasm`")
+    }
+}
@@ -0,0 +1,214 @@
+use std::{
+    path::{Path, PathBuf},
+    thread,
+};
+
+use anyhow::Result;
+
+use crate::{
+    config::{thread_delay::ThreadDelay, PythonConfig, SubConfig},
+    cypher_text::encode::Encode,
+    lint::LintCheck,
+};
+
+use self::interpreter::Validate;
+
+use super::{oracle_location::OracleLocation, request_budget::RequestBudget, Oracle};
+
+/// Oracle defined by a `validate(cypher_text)` function in a Python script, run by an embedded interpreter. The function is handed the encoded cypher text as `str`, and returns `True` if its padding is valid. Raising `rustpad.Abort` stops the attack, other exceptions fail the question, which is retried.
+/// Unlike a script oracle, the script is imported once, rather than run per question. Its module state thus persists between questions, e.g. to keep a session or socket.
+/// Only available with the `python` feature, as the interpreter is linked from the system's `libpython`
+pub struct PythonOracle {
+    path: PathBuf,
+    config: PythonConfig,
+    validate: Validate,
+}
+
+impl Oracle for PythonOracle {
+    fn visit(oracle_location: &OracleLocation, oracle_config: &SubConfig) -> Result<Self> {
+        let path = match oracle_location {
+            OracleLocation::Python(path) => path,
+            OracleLocation::Web(_)
+            | OracleLocation::Script(_)
+            | OracleLocation::Tcp(_)
+            | OracleLocation::Plugin(_)
+            | OracleLocation::Lua(_) => {
+                panic!("Tried to visit the Python oracle using a URL, script, socket address, plugin or Lua script!")
+            }
+        };
+
+        let oracle_config = match oracle_config {
+            SubConfig::Python(config) => config,
+            SubConfig::Web(_)
+            | SubConfig::Script(_)
+            | SubConfig::Tcp(_)
+            | SubConfig::Plugin(_)
+            | SubConfig::Lua(_) => {
+                panic!("Tried to visit the Python oracle using web, script, TCP, plugin or Lua configs!")
+            }
+        };
+
+        Ok(Self {
+            path: path.clone(),
+            config: oracle_config.clone(),
+            // errors in the script are reported now, rather than as failed questions
+            validate: interpreter::load(path)?,
+        })
+    }
+
+    fn ask_validation<'a>(&self, cypher_text: &'a impl Encode<'a>) -> Result<bool> {
+        self.config.request_budget().spend()?;
+        if let Some(traffic_shaper) = self.config.traffic_shaper() {
+            thread::sleep(traffic_shaper.reserve());
+        }
+
+        interpreter::ask(&self.path, &self.validate, &cypher_text.encode())
+    }
+
+    fn location(&self) -> OracleLocation {
+        OracleLocation::Python(self.path.clone())
+    }
+    fn thread_delay(&self) -> &ThreadDelay {
+        self.config.thread_delay()
+    }
+    fn request_budget(&self) -> Option<&RequestBudget> {
+        Some(self.config.request_budget())
+    }
+}
+
+/// Checks of `rustpad lint`. The script is imported, so it may contact the oracle if it does so outside of `validate`
+pub fn lint(path: &Path) -> Vec<LintCheck> {
+    vec![LintCheck::new(
+        "Python script defines `validate`",
+        interpreter::load(path).map(|_| ()),
+    )]
+}
+
+/// Whether `path` has the extension of a Python script
+pub fn is_script(path: &Path) -> bool {
+    path.extension()
+        .and_then(|extension| extension.to_str())
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("py"))
+}
+
+#[cfg(feature = "python")]
+mod interpreter {
+    use std::{ffi::CString, fs, path::Path};
+
+    use anyhow::{anyhow, Context, Result};
+    use log::info;
+    use pyo3::{create_exception, exceptions::PyException, prelude::*};
+
+    use crate::{error::RustpadError, logging::LOG_TARGET};
+
+    create_exception!(
+        rustpad,
+        Abort,
+        PyException,
+        "Raised by `validate` to stop the attack."
+    );
+
+    pub(super) type Validate = Py<PyAny>;
+
+    /// Import the script, and look up its `validate` function. The script can `import rustpad`, for `rustpad.Abort` and `rustpad.log(message)`
+    pub(super) fn load(path: &Path) -> Result<Validate> {
+        let source = fs::read_to_string(path).context(format!(
+            "Reading the Python script `{}` failed",
+            path.display()
+        ))?;
+        let source = CString::new(source).context("The Python script can't contain NUL bytes")?;
+        let file_name = CString::new(path.display().to_string())
+            .context("The Python script's path can't contain NUL bytes")?;
+        let module_name = CString::new(
+            path.file_stem()
+                .map(|stem| stem.to_string_lossy().into_owned())
+                .unwrap_or_default(),
+        )
+        .context("The Python script's name can't contain NUL bytes")?;
+
+        pyo3::prepare_freethreaded_python();
+        Python::with_gil(|py| {
+            let sys = py.import("sys")?;
+            sys.getattr("modules")?.set_item("rustpad", api(py)?)?;
+            // so the script can import modules next to it
+            if let Some(directory) = path.parent() {
+                sys.getattr("path")?
+                    .call_method1("insert", (0, directory.display().to_string()))?;
+            }
+
+            PyModule::from_code(py, &source, &file_name, &module_name)?
+                .getattr("validate")
+                .map(Bound::unbind)
+        })
+        .map_err(|e: PyErr| anyhow!("{}", e))
+        .context(format!(
+            "Importing the Python script `{}` failed. It must define the function `validate(cypher_text)`",
+            path.display()
+        ))
+    }
+
+    pub(super) fn ask(path: &Path, validate: &Validate, cypher_text: &str) -> Result<bool> {
+        Python::with_gil(|py| {
+            match validate.call1(py, (cypher_text,)) {
+            Ok(verdict) => verdict.extract::<bool>(py).map_err(|_| {
+                anyhow!(
+                    "`validate` of the Python script `{}` returned a value of type {}, rather than a bool",
+                    path.display(),
+                    verdict
+                        .bind(py)
+                        .get_type()
+                        .name()
+                        .map(|name| name.to_string())
+                        .unwrap_or_default()
+                )
+            }),
+            Err(e) if e.is_instance_of::<Abort>(py) => Err(RustpadError::OracleAborted {
+                reason: format!(
+                    "the Python script `{}` aborted: {}",
+                    path.display(),
+                    e.value(py)
+                ),
+            }
+            .into()),
+            Err(e) => Err(anyhow!("{}", e).context(format!(
+                "`validate` of the Python script `{}` failed",
+                path.display()
+            ))),
+        }
+        })
+    }
+
+    #[pyfunction]
+    #[pyo3(name = "log")]
+    fn log_message(message: &str) {
+        info!(target: LOG_TARGET, "{}", message);
+    }
+
+    /// The `rustpad` module
+    fn api(py: Python<'_>) -> PyResult<Bound<'_, PyModule>> {
+        let api = PyModule::new(py, "rustpad")?;
+        api.add("Abort", py.get_type::<Abort>())?;
+        api.add_function(wrap_pyfunction!(log_message, &api)?)?;
+        Ok(api)
+    }
+}
+
+#[cfg(not(feature = "python"))]
+mod interpreter {
+    use std::path::Path;
+
+    use anyhow::{anyhow, Result};
+
+    // no oracle can be visited without an interpreter
+    pub(super) enum Validate {}
+
+    pub(super) fn load(_path: &Path) -> Result<Validate> {
+        Err(anyhow!(
+            "This build of rustpad has no Python support. Rebuild it with `--features python`"
+        ))
+    }
+
+    pub(super) fn ask(_path: &Path, validate: &Validate, _cypher_text: &str) -> Result<bool> {
+        match *validate {}
+    }
+}
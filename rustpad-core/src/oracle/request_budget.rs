@@ -0,0 +1,128 @@
+use std::{
+    sync::atomic::{AtomicBool, AtomicU64, Ordering},
+    time::{Duration, Instant},
+};
+
+use anyhow::Result;
+use humantime::format_duration;
+use log::info;
+
+use crate::{error::RustpadError, logging::LOG_TARGET};
+
+/// Limits on how much the oracle is questioned, for engagements with strict rules. Calibration counts too.
+/// Shared by all oracles built from the same config, so the limits hold for the whole attack.
+#[derive(Debug)]
+pub struct RequestBudget {
+    max_requests: Option<u64>,
+    max_duration: Option<Duration>,
+    start: Instant,
+    requests: AtomicU64,
+    // set from outside the attack, e.g. by `serve`, to stop it at the next request
+    cancelled: AtomicBool,
+    wind_down_after: Option<Duration>,
+    // set from outside the attack, e.g. by the TUI, to stop it once the blocks being solved are finished
+    winding_down: AtomicBool,
+}
+
+// unlimited
+impl Default for RequestBudget {
+    fn default() -> Self {
+        Self::new(None, None, None)
+    }
+}
+
+impl RequestBudget {
+    pub fn new(
+        max_requests: Option<u64>,
+        max_duration: Option<Duration>,
+        wind_down_after: Option<Duration>,
+    ) -> Self {
+        Self {
+            max_requests,
+            max_duration,
+            start: Instant::now(),
+            requests: AtomicU64::new(0),
+            cancelled: AtomicBool::new(false),
+            wind_down_after,
+            winding_down: AtomicBool::new(false),
+        }
+    }
+
+    /// Account for a request which is about to be sent. Fails if the budget is exhausted, in which case the request mustn't be sent
+    pub fn spend(&self) -> Result<()> {
+        if self.cancelled.load(Ordering::Relaxed) {
+            return Err(RustpadError::BudgetExhausted {
+                reason: "the attack was cancelled".to_string(),
+            }
+            .into());
+        }
+        if let Some(max_duration) = self.max_duration {
+            if self.start.elapsed() >= max_duration {
+                return Err(RustpadError::BudgetExhausted {
+                    reason: format!(
+                        "the time limit of {} was reached",
+                        format_duration(max_duration)
+                    ),
+                }
+                .into());
+            }
+        }
+
+        // counted once allowed, and the check and count are a single step so concurrent requests can't overshoot. A failed request still counts, as it may have reached the target
+        let max_requests = self.max_requests.unwrap_or(u64::MAX);
+        self.requests
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |requests| {
+                (requests < max_requests).then(|| requests + 1)
+            })
+            .map_err(|_| RustpadError::BudgetExhausted {
+                reason: format!("all {} requests were sent", max_requests),
+            })?;
+
+        Ok(())
+    }
+
+    /// Refuse all further requests, which stops the attack as an exhausted budget would
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+
+    /// Start solving no new blocks, while the blocks being solved are finished, and cached as usual. A cleaner partial result than cancelling
+    pub fn wind_down(&self) {
+        if !self.winding_down.swap(true, Ordering::Relaxed) {
+            info!(
+                target: LOG_TARGET,
+                "Winding down: the blocks being solved are finished, no new blocks are started"
+            );
+        }
+    }
+
+    pub fn is_winding_down(&self) -> bool {
+        self.winding_down.load(Ordering::Relaxed)
+    }
+
+    /// Account for a block which is about to be solved. Fails once the attack winds down, in which case the block mustn't be started
+    pub fn start_block(&self) -> Result<()> {
+        if let Some(wind_down_after) = self.wind_down_after {
+            if self.start.elapsed() >= wind_down_after {
+                self.wind_down();
+            }
+        }
+
+        if self.is_winding_down() {
+            return Err(RustpadError::BudgetExhausted {
+                reason: "the attack wound down".to_string(),
+            }
+            .into());
+        }
+        Ok(())
+    }
+
+    /// Amount of requests allowed to be sent
+    pub fn requests(&self) -> u64 {
+        self.requests.load(Ordering::Relaxed)
+    }
+}
@@ -0,0 +1,281 @@
+mod verdict;
+
+use std::{
+    io::{self, BufRead, BufReader, Write},
+    path::{Path, PathBuf},
+    process::{Child, ChildStdin, Command, Stdio},
+    sync::{
+        mpsc::{self, Receiver, RecvTimeoutError},
+        Mutex,
+    },
+    thread,
+    time::Duration,
+};
+
+use anyhow::{anyhow, Context, Result};
+use log::warn;
+
+use crate::{
+    config::{thread_delay::ThreadDelay, verdict_source::VerdictSource, ScriptConfig, SubConfig},
+    cypher_text::encode::Encode,
+    error::RustpadError,
+    logging::LOG_TARGET,
+    other::RETRY_MAX_ATTEMPTS,
+};
+
+use self::verdict::{parse_verdict, ScriptVerdict};
+use super::{oracle_location::OracleLocation, request_budget::RequestBudget, Oracle};
+
+pub struct ScriptOracle {
+    path: PathBuf,
+    config: ScriptConfig,
+    // idle processes of a persistent script. Every thread takes one while questioning, so the pool grows up to the thread count
+    idle_scripts: Mutex<Vec<PersistentScript>>,
+}
+
+impl Oracle for ScriptOracle {
+    fn visit(oracle_location: &OracleLocation, oracle_config: &SubConfig) -> Result<Self> {
+        let path = match oracle_location {
+            OracleLocation::Script(path) => path,
+            OracleLocation::Web(_)
+            | OracleLocation::Tcp(_)
+            | OracleLocation::Plugin(_)
+            | OracleLocation::Lua(_)
+            | OracleLocation::Python(_) => {
+                panic!("Tried to visit the script oracle using a URL, socket address, plugin, Lua or Python script!")
+            }
+        };
+
+        let oracle_config = match oracle_config {
+            SubConfig::Script(config) => config,
+            SubConfig::Web(_)
+            | SubConfig::Tcp(_)
+            | SubConfig::Plugin(_)
+            | SubConfig::Lua(_)
+            | SubConfig::Python(_) => {
+                panic!("Tried to visit the script oracle using web, TCP, plugin, Lua or Python configs!")
+            }
+        };
+
+        let oracle = Self {
+            path: path.to_path_buf(),
+            config: oracle_config.clone(),
+            idle_scripts: Mutex::new(Vec::new()),
+        };
+        Ok(oracle)
+    }
+
+    /// Scripts can ask to be questioned again after a backoff, or to abort the attack
+    fn ask_validation<'a>(&self, cypher_text: &'a impl Encode<'a>) -> Result<bool> {
+        for attempt in 1..=RETRY_MAX_ATTEMPTS + 1 {
+            match self.ask_script(cypher_text)? {
+                ScriptVerdict::Valid => return Ok(true),
+                ScriptVerdict::Invalid => return Ok(false),
+                ScriptVerdict::Abort { reason } => {
+                    return Err(RustpadError::OracleAborted {
+                        reason: reason.unwrap_or_else(|| "no reason given".to_string()),
+                    }
+                    .into())
+                }
+                ScriptVerdict::Retry { reason, backoff } => {
+                    if attempt > RETRY_MAX_ATTEMPTS {
+                        break;
+                    }
+
+                    warn!(
+                        target: LOG_TARGET,
+                        "Script asked to retry ({}), backing off for {}",
+                        reason.as_deref().unwrap_or("no reason given"),
+                        humantime::format_duration(backoff)
+                    );
+                    thread::sleep(backoff);
+                }
+            }
+        }
+
+        Err(anyhow!(
+            "Script kept asking to retry: {}",
+            self.path.display()
+        ))
+    }
+
+    fn location(&self) -> OracleLocation {
+        OracleLocation::Script(self.path.clone())
+    }
+    fn thread_delay(&self) -> &ThreadDelay {
+        self.config.thread_delay()
+    }
+    fn request_budget(&self) -> Option<&RequestBudget> {
+        Some(self.config.request_budget())
+    }
+}
+
+impl ScriptOracle {
+    fn ask_script<'a>(&self, cypher_text: &'a impl Encode<'a>) -> Result<ScriptVerdict> {
+        self.config.request_budget().spend()?;
+        if let Some(traffic_shaper) = self.config.traffic_shaper() {
+            thread::sleep(traffic_shaper.reserve());
+        }
+        if *self.config.persistent() {
+            return self.ask_persistent_validation(cypher_text);
+        }
+
+        let mut command = Command::new("/bin/sh");
+        command.stderr(Stdio::null()).arg("-c").arg(format!(
+            "{} {}",
+            self.path.as_path().to_str().ok_or_else(|| anyhow!(
+                "Path `{}` invalid. Double check the path",
+                self.path.display()
+            ))?,
+            cypher_text.encode()
+        ));
+
+        match self.config.verdict_source() {
+            VerdictSource::ExitCode => {
+                let status = command
+                    .stdout(Stdio::null())
+                    .status()
+                    .context(format!("Script execution failed: {}", self.path.display()))?;
+
+                Ok(if status.success() {
+                    ScriptVerdict::Valid
+                } else {
+                    ScriptVerdict::Invalid
+                })
+            }
+            VerdictSource::Stdout => {
+                let output = command
+                    .output()
+                    .context(format!("Script execution failed: {}", self.path.display()))?;
+
+                parse_verdict(&String::from_utf8_lossy(&output.stdout))
+                    .context(format!("Script gave no verdict: {}", self.path.display()))
+            }
+        }
+    }
+
+    fn ask_persistent_validation<'a>(
+        &self,
+        cypher_text: &'a impl Encode<'a>,
+    ) -> Result<ScriptVerdict> {
+        let idle_script = self.idle_scripts.lock().unwrap().pop();
+        let mut script = match idle_script {
+            Some(script) => script,
+            None => PersistentScript::launch(&self.path)?,
+        };
+
+        // a script which failed to answer is in an unknown state. Dropping it makes the next question launch a fresh one
+        let verdict = script
+            .ask_validation(
+                &cypher_text.encode(),
+                *self.config.verdict_source(),
+                **self.config.request_timeout(),
+            )
+            .context(format!(
+                "Persistent script failed to answer: {}",
+                self.path.display()
+            ))?;
+        self.idle_scripts.lock().unwrap().push(script);
+
+        Ok(verdict)
+    }
+}
+
+/// Script which is launched once, and then questioned over and over. An encoded cypher text is written to its stdin, followed by a newline. The script responds with a line containing its verdict on stdout: `0` for correct padding, any other exit code otherwise. This mirrors the exit codes of a regular script.
+/// If verdicts are read from stdout, the line must hold a verdict as a regular script would print it, with JSON on a single line.
+struct PersistentScript {
+    process: Child,
+    stdin: ChildStdin,
+    // lines of stdout, read by a separate thread so answers can time out
+    stdout_lines: Receiver<io::Result<String>>,
+}
+
+impl PersistentScript {
+    fn launch(path: &Path) -> Result<Self> {
+        // `exec`, so killing the process kills the script rather than the shell
+        let mut process = Command::new("/bin/sh")
+            .arg("-c")
+            .arg(format!(
+                "exec {}",
+                path.to_str().ok_or_else(|| anyhow!(
+                    "Path `{}` invalid. Double check the path",
+                    path.display()
+                ))?
+            ))
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .context(format!("Script execution failed: {}", path.display()))?;
+
+        let stdin = process
+            .stdin
+            .take()
+            .expect("Script's stdin was configured to be piped");
+        let stdout = BufReader::new(
+            process
+                .stdout
+                .take()
+                .expect("Script's stdout was configured to be piped"),
+        );
+        let (line_sender, stdout_lines) = mpsc::channel();
+        // ends once the script's stdout closes, i.e. when it exits or is killed
+        thread::spawn(move || {
+            for line in stdout.lines() {
+                if line_sender.send(line).is_err() {
+                    break;
+                }
+            }
+        });
+
+        Ok(Self {
+            process,
+            stdin,
+            stdout_lines,
+        })
+    }
+
+    fn ask_validation(
+        &mut self,
+        encoded_cypher_text: &str,
+        verdict_source: VerdictSource,
+        timeout: Duration,
+    ) -> Result<ScriptVerdict> {
+        writeln!(self.stdin, "{}", encoded_cypher_text)
+            .and_then(|_| self.stdin.flush())
+            .context("Writing cypher text to script failed")?;
+
+        let verdict = match self.stdout_lines.recv_timeout(timeout) {
+            Ok(verdict) => verdict.context("Reading verdict from script failed")?,
+            Err(RecvTimeoutError::Timeout) => {
+                return Err(anyhow!(
+                    "Script gave no verdict within {}",
+                    humantime::format_duration(timeout)
+                ))
+            }
+            Err(RecvTimeoutError::Disconnected) => {
+                return Err(anyhow!("Script exited before giving a verdict"))
+            }
+        };
+
+        match verdict_source {
+            VerdictSource::ExitCode => match verdict.trim().parse::<i32>() {
+                Ok(0) => Ok(ScriptVerdict::Valid),
+                Ok(_) => Ok(ScriptVerdict::Invalid),
+                Err(_) => Err(anyhow!(
+                    "`{}` is not an exit code. Expected `0` for correct padding, or another exit code otherwise",
+                    verdict.trim()
+                )),
+            },
+            VerdictSource::Stdout => parse_verdict(&verdict),
+        }
+    }
+}
+
+impl Drop for PersistentScript {
+    fn drop(&mut self) {
+        // the script might not stop by itself once its stdin closes. Errors mean it's already gone
+        let _ = self.process.kill();
+        let _ = self.process.wait();
+    }
+}
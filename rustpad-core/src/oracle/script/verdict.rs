@@ -0,0 +1,106 @@
+use std::time::Duration;
+
+use anyhow::{anyhow, Context, Result};
+use log::debug;
+use serde_json::{Map, Value};
+
+use crate::{logging::LOG_TARGET, other::RETRY_DELAY_MS};
+
+/// What a script answered when questioned.
+#[derive(Debug)]
+pub enum ScriptVerdict {
+    Valid,
+    Invalid,
+    // the script couldn't decide, e.g. as it was rate-limited. Ask again after the backoff
+    Retry {
+        reason: Option<String>,
+        backoff: Duration,
+    },
+    // the attack can't continue, e.g. as the session expired
+    Abort {
+        reason: Option<String>,
+    },
+}
+
+/// Parse the verdict a script printed on stdout. Either a line containing `VALID`/`INVALID`/`RETRY`/`ABORT`, or a JSON object such as `{"verdict": "retry", "reason": "rate-limited", "backoff_ms": 3000}`.
+/// Anything else the script printed is auxiliary data, which is logged.
+pub fn parse_verdict(output: &str) -> Result<ScriptVerdict> {
+    let output = output.trim();
+
+    if output.starts_with('{') {
+        parse_json_verdict(output)
+    } else {
+        parse_plain_verdict(output)
+    }
+}
+
+fn parse_plain_verdict(output: &str) -> Result<ScriptVerdict> {
+    let mut lines = output
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty());
+
+    let verdict = lines
+        .next()
+        .ok_or_else(|| anyhow!("Script did not print a verdict"))?;
+    for line in lines {
+        debug!(target: LOG_TARGET, "Script reported: {}", line);
+    }
+
+    verdict_from_str(verdict, &Map::new())
+}
+
+fn parse_json_verdict(output: &str) -> Result<ScriptVerdict> {
+    let object = match serde_json::from_str(output)
+        .context(format!("Script printed invalid JSON: {}", output))?
+    {
+        Value::Object(object) => object,
+        _ => unreachable!("Output starting with `{{` can only be parsed as a JSON object"),
+    };
+
+    for (key, value) in object.iter().filter(|(key, _)| *key != "verdict") {
+        debug!(target: LOG_TARGET, "Script reported {}: {}", key, value);
+    }
+
+    match object.get("verdict") {
+        Some(Value::String(verdict)) => verdict_from_str(verdict, &object),
+        Some(Value::Bool(true)) => Ok(ScriptVerdict::Valid),
+        Some(Value::Bool(false)) => Ok(ScriptVerdict::Invalid),
+        Some(verdict) => Err(anyhow!("`{}` is not a verdict", verdict)),
+        None => Err(anyhow!("Script printed JSON without a `verdict` field")),
+    }
+}
+
+/// `details` holds the optional `reason` and `backoff_ms` of a retry or abort
+fn verdict_from_str(verdict: &str, details: &Map<String, Value>) -> Result<ScriptVerdict> {
+    let reason = details.get("reason").map(|reason| match reason {
+        Value::String(reason) => reason.clone(),
+        reason => reason.to_string(),
+    });
+
+    match verdict.to_lowercase().as_str() {
+        "valid" => Ok(ScriptVerdict::Valid),
+        "invalid" => Ok(ScriptVerdict::Invalid),
+        "retry" => {
+            let backoff_ms = match details.get("backoff_ms") {
+                Some(backoff_ms) => backoff_ms.as_u64().ok_or_else(|| {
+                    anyhow!(
+                        "`{}` is not a backoff. Expected a positive amount of milliseconds",
+                        backoff_ms
+                    )
+                })?,
+                None => RETRY_DELAY_MS,
+            };
+
+            Ok(ScriptVerdict::Retry {
+                reason,
+                backoff: Duration::from_millis(backoff_ms),
+            })
+        }
+        "abort" => Ok(ScriptVerdict::Abort { reason }),
+        _ => Err(anyhow!(
+            "`{}` is not a verdict. Expected one of: [VALID, INVALID, RETRY, ABORT]",
+            verdict
+        )),
+    }
+}
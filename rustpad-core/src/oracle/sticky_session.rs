@@ -0,0 +1,135 @@
+use std::sync::RwLock;
+
+use itertools::Itertools;
+use log::info;
+use reqwest::header::{self, HeaderMap, HeaderName, HeaderValue};
+
+use crate::{config::sticky_session_source::StickySessionSource, logging::LOG_TARGET};
+
+/// Prefixes of cookies which well-known load balancers use to pin a client to a backend, e.g. AWS ALB, F5 BIG-IP, HAProxy, Azure, GCP and nginx ingress.
+const AFFINITY_COOKIE_PREFIXES: &[&str] = &[
+    "awsalb",
+    "awselb",
+    "bigipserver",
+    "serverid",
+    "routeid",
+    "route",
+    "arraffinity",
+    "gclb",
+    "ingresscookie",
+    "nsc_",
+];
+
+/// Keeps all requests on the same backend of a load balancer, by sending back the affinity it set in the first response. Backends can answer differently, e.g. due to different versions, which invalidates the calibration.
+/// Shared by all oracles built from the same config, so calibration and the attack land on the same backend. Requests sent before the first response arrives can't be pinned.
+#[derive(Debug)]
+pub struct StickySession {
+    source: StickySessionSource,
+    affinity: RwLock<Option<Affinity>>,
+}
+
+#[derive(Debug)]
+enum Affinity {
+    // `name=value` pairs
+    Cookies(Vec<String>),
+    Header(HeaderName, HeaderValue),
+}
+
+impl StickySession {
+    pub fn new(source: StickySessionSource) -> Self {
+        Self {
+            source,
+            affinity: RwLock::new(None),
+        }
+    }
+
+    /// Add the captured affinity to a request. Cookies are added to those given by the user, but a header given by the user is respected.
+    pub fn apply(&self, headers: &mut HeaderMap) {
+        match &*self.affinity.read().unwrap() {
+            None => {}
+            Some(Affinity::Cookies(cookies)) => {
+                let cookies = cookies.join("; ");
+                let cookie_header = match headers.get(header::COOKIE) {
+                    Some(user_cookies) => HeaderValue::from_str(&format!(
+                        "{}; {}",
+                        String::from_utf8_lossy(user_cookies.as_bytes()),
+                        cookies
+                    )),
+                    None => HeaderValue::from_str(&cookies),
+                };
+                if let Ok(cookie_header) = cookie_header {
+                    let _ = headers.insert(header::COOKIE, cookie_header);
+                }
+            }
+            Some(Affinity::Header(name, value)) => {
+                headers.entry(name.clone()).or_insert_with(|| value.clone());
+            }
+        }
+    }
+
+    /// Capture the affinity from a response, unless it was captured already
+    pub fn capture(&self, response_headers: &HeaderMap) {
+        if self.affinity.read().unwrap().is_some() {
+            return;
+        }
+
+        let affinity = match &self.source {
+            StickySessionSource::Auto => self.capture_cookies(response_headers, |name| {
+                let name = name.to_lowercase();
+                AFFINITY_COOKIE_PREFIXES
+                    .iter()
+                    .any(|prefix| name.starts_with(prefix))
+            }),
+            StickySessionSource::Cookie(wanted) => {
+                self.capture_cookies(response_headers, |name| name == wanted)
+            }
+            StickySessionSource::Header(wanted) => HeaderName::from_bytes(wanted.as_bytes())
+                .ok()
+                .and_then(|name| {
+                    response_headers
+                        .get(&name)
+                        .map(|value| Affinity::Header(name.clone(), value.clone()))
+                }),
+        };
+
+        let mut captured = self.affinity.write().unwrap();
+        // another response may have been captured in the meantime. The first one is kept
+        if let (None, Some(affinity)) = (captured.as_ref(), affinity) {
+            info!(
+                target: LOG_TARGET,
+                "Sticking to the load balancer's backend with {}",
+                match &affinity {
+                    Affinity::Cookies(cookies) => format!(
+                        "cookie(s): {}",
+                        cookies
+                            .iter()
+                            .map(|cookie| cookie.split('=').next().unwrap_or_default())
+                            .join(", ")
+                    ),
+                    Affinity::Header(name, _) => format!("header: {}", name),
+                }
+            );
+            *captured = Some(affinity);
+        }
+    }
+
+    fn capture_cookies(
+        &self,
+        response_headers: &HeaderMap,
+        is_affinity_cookie: impl Fn(&str) -> bool,
+    ) -> Option<Affinity> {
+        let cookies: Vec<String> = response_headers
+            .get_all(header::SET_COOKIE)
+            .iter()
+            .filter_map(|set_cookie| set_cookie.to_str().ok())
+            // attributes such as `Path` follow the first `;`
+            .filter_map(|set_cookie| set_cookie.split(';').next())
+            .filter_map(|cookie| {
+                let (name, value) = cookie.split_once('=')?;
+                is_affinity_cookie(name.trim()).then(|| format!("{}={}", name.trim(), value.trim()))
+            })
+            .collect();
+
+        (!cookies.is_empty()).then(|| Affinity::Cookies(cookies))
+    }
+}
@@ -0,0 +1,247 @@
+use std::{
+    io::{ErrorKind, Read, Write},
+    net::{TcpStream, ToSocketAddrs},
+    thread,
+};
+
+use anyhow::{anyhow, Context, Result};
+use native_tls::TlsConnector;
+
+use crate::{
+    config::{thread_delay::ThreadDelay, SubConfig, TcpConfig},
+    cypher_text::encode::Encode,
+    lint::LintCheck,
+};
+
+use super::{
+    computed_placeholder, oracle_location::OracleLocation, request_budget::RequestBudget, Oracle,
+};
+
+pub struct TcpOracle {
+    address: String,
+    config: TcpConfig,
+    tls_connector: Option<TlsConnector>,
+}
+
+impl Oracle for TcpOracle {
+    fn visit(oracle_location: &OracleLocation, oracle_config: &SubConfig) -> Result<Self> {
+        let address = match oracle_location {
+            OracleLocation::Tcp(address) => address,
+            OracleLocation::Web(_)
+            | OracleLocation::Script(_)
+            | OracleLocation::Plugin(_)
+            | OracleLocation::Lua(_)
+            | OracleLocation::Python(_) => {
+                panic!("Tried to visit the TCP oracle using a URL or file path!")
+            }
+        };
+
+        let oracle_config = match oracle_config {
+            SubConfig::Tcp(config) => config,
+            SubConfig::Web(_)
+            | SubConfig::Script(_)
+            | SubConfig::Plugin(_)
+            | SubConfig::Lua(_)
+            | SubConfig::Python(_) => {
+                panic!("Tried to visit the TCP oracle using web, script, plugin, Lua or Python configs!")
+            }
+        };
+
+        check_keyword_present(oracle_config)?;
+
+        let tls_connector = if *oracle_config.tls() {
+            Some(
+                TlsConnector::builder()
+                    .danger_accept_invalid_certs(*oracle_config.insecure())
+                    .danger_accept_invalid_hostnames(*oracle_config.insecure())
+                    .build()
+                    .context("TLS setup failed")?,
+            )
+        } else {
+            None
+        };
+
+        Ok(Self {
+            address: address.clone(),
+            config: oracle_config.as_ref().clone(),
+            tls_connector,
+        })
+    }
+
+    fn ask_validation<'a>(&self, cypher_text: &'a impl Encode<'a>) -> Result<bool> {
+        self.config.request_budget().spend()?;
+        if let Some(traffic_shaper) = self.config.traffic_shaper() {
+            thread::sleep(traffic_shaper.reserve());
+        }
+        // computed placeholders contain the keyword, so they go first
+        let payload = computed_placeholder::substitutions(self.config.keyword(), cypher_text)
+            .into_iter()
+            .fold(
+                self.config.send_template().to_vec(),
+                |payload, (placeholder, value)| {
+                    replace_keyword(&payload, placeholder.as_bytes(), value.as_bytes())
+                },
+            );
+        let payload = replace_keyword(
+            &payload,
+            self.config.keyword().as_bytes(),
+            cypher_text.encode().as_bytes(),
+        );
+
+        let stream = self.connect()?;
+        let response = match &self.tls_connector {
+            Some(tls_connector) => {
+                let stream = tls_connector
+                    .connect(self.host(), stream)
+                    .context(format!("TLS handshake with `{}` failed", self.address))?;
+                if let Some(certificate_pin) = self.config.certificate_pin() {
+                    certificate_pin.check_stream(&self.address, &stream)?;
+                }
+                self.exchange(stream, &payload)?
+            }
+            None => self.exchange(stream, &payload)?,
+        };
+
+        Ok(!self.config.padding_error().is_match(&response))
+    }
+
+    fn location(&self) -> OracleLocation {
+        OracleLocation::Tcp(self.address.clone())
+    }
+    fn thread_delay(&self) -> &ThreadDelay {
+        self.config.thread_delay()
+    }
+    fn request_budget(&self) -> Option<&RequestBudget> {
+        Some(self.config.request_budget())
+    }
+}
+
+impl TcpOracle {
+    fn connect(&self) -> Result<TcpStream> {
+        let socket_addresses = self
+            .address
+            .to_socket_addrs()
+            .context(format!("Resolving `{}` failed", self.address))?;
+
+        let mut last_error = None;
+        for socket_address in socket_addresses {
+            match TcpStream::connect_timeout(&socket_address, **self.config.request_timeout()) {
+                Ok(stream) => {
+                    stream.set_read_timeout(Some(**self.config.request_timeout()))?;
+                    stream.set_write_timeout(Some(**self.config.request_timeout()))?;
+                    return Ok(stream);
+                }
+                Err(e) => last_error = Some(e),
+            }
+        }
+
+        match last_error {
+            Some(e) => Err(e).context(format!("Connecting to `{}` failed", self.address)),
+            None => Err(anyhow!("`{}` did not resolve to any address", self.address)),
+        }
+    }
+
+    /// Send the payload and read the response. The response is complete once the connection is closed, the padding error pattern matches, or the response delimiter is received.
+    fn exchange(&self, mut stream: impl Read + Write, payload: &[u8]) -> Result<Vec<u8>> {
+        stream
+            .write_all(payload)
+            .and_then(|_| stream.flush())
+            .context(format!("Sending data to `{}` failed", self.address))?;
+
+        let mut response = Vec::new();
+        let mut buffer = [0; 4096];
+        loop {
+            match stream.read(&mut buffer) {
+                Ok(0) => break,
+                Ok(amount_read) => {
+                    response.extend_from_slice(&buffer[..amount_read]);
+
+                    let delimiter_received = self
+                        .config
+                        .response_delimiter()
+                        .as_ref()
+                        .map(|delimiter| {
+                            response
+                                .windows(delimiter.len())
+                                .any(|window| window == &delimiter[..])
+                        })
+                        .unwrap_or(false);
+                    if delimiter_received || self.config.padding_error().is_match(&response) {
+                        break;
+                    }
+                }
+                // the target may keep the connection open. Having received something, this is as complete as the response gets
+                Err(e)
+                    if matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut)
+                        && !response.is_empty() =>
+                {
+                    break
+                }
+                Err(e) => {
+                    return Err(e)
+                        .context(format!("Receiving response from `{}` failed", self.address))
+                }
+            }
+        }
+
+        Ok(response)
+    }
+
+    /// Host part of the address, used for TLS' server name indication
+    fn host(&self) -> &str {
+        self.address
+            .rsplit_once(':')
+            .map(|(host, _)| host)
+            .unwrap_or(&self.address)
+            .trim_start_matches('[')
+            .trim_end_matches(']')
+    }
+}
+
+/// Checks of the configuration which don't require contacting the oracle. See `rustpad lint`
+pub fn lint(address: &str, config: &TcpConfig) -> Vec<LintCheck> {
+    vec![
+        LintCheck::new(
+            "Keyword is present in the data to send",
+            check_keyword_present(config),
+        ),
+        LintCheck::new(
+            "Oracle address resolves",
+            address
+                .to_socket_addrs()
+                .context(format!("Resolving `{}` failed", address))
+                .map(|_| ()),
+        ),
+    ]
+}
+
+fn check_keyword_present(config: &TcpConfig) -> Result<()> {
+    if !config
+        .send_template()
+        .windows(config.keyword().len())
+        .any(|window| window == config.keyword().as_bytes())
+    {
+        return Err(anyhow!(
+            "Keyword not found in the data to send. Double check whether you indicated the cypher text's location. See `--send` and `--keyword` for extra info"
+        ));
+    }
+
+    Ok(())
+}
+
+fn replace_keyword(template: &[u8], keyword: &[u8], value: &[u8]) -> Vec<u8> {
+    let mut result = Vec::with_capacity(template.len() + value.len());
+    let mut remainder = template;
+
+    while let Some(idx) = remainder
+        .windows(keyword.len())
+        .position(|window| window == keyword)
+    {
+        result.extend_from_slice(&remainder[..idx]);
+        result.extend_from_slice(value);
+        remainder = &remainder[idx + keyword.len()..];
+    }
+    result.extend_from_slice(remainder);
+
+    result
+}
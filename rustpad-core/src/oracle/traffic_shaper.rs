@@ -0,0 +1,56 @@
+use std::{
+    num::NonZeroU64,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+/// Sends requests in bursts, pausing after each, for `--burst` and `--pause`. Matches rate limiters which reset a window, and beats spreading requests evenly under the same limit.
+/// Shared by all oracles built from the same config, so bursts hold for the whole attack.
+#[derive(Debug)]
+pub struct TrafficShaper {
+    burst: NonZeroU64,
+    pause: Duration,
+    state: Mutex<State>,
+}
+
+#[derive(Debug)]
+struct State {
+    // requests of the current burst handed a slot
+    in_burst: u64,
+    // when the current burst may start sending
+    burst_start: Instant,
+}
+
+impl TrafficShaper {
+    pub fn new(burst: NonZeroU64, pause: Duration) -> Self {
+        Self {
+            burst,
+            pause,
+            state: Mutex::new(State {
+                in_burst: 0,
+                burst_start: Instant::now(),
+            }),
+        }
+    }
+
+    /// Claim a slot for a request which is about to be sent. Returns how long to wait before sending it, i.e. until its burst starts
+    pub fn reserve(&self) -> Duration {
+        let mut state = self.state.lock().unwrap();
+        let now = Instant::now();
+        // a burst which didn't fill up within the length of a pause counts as paused, as a rate limiter's window would have reset
+        if state.in_burst > 0 && now >= state.burst_start + self.pause {
+            state.in_burst = 0;
+            state.burst_start = now;
+        }
+        let send_at = state.burst_start.max(now);
+
+        state.in_burst += 1;
+        if state.in_burst >= self.burst.get() {
+            // the pause starts once the burst's last request is sent
+            state.in_burst = 0;
+            state.burst_start = send_at + self.pause;
+        }
+
+        send_at - now
+    }
+}
@@ -0,0 +1,52 @@
+use std::{
+    collections::hash_map::RandomState,
+    hash::{BuildHasher, Hasher},
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+use reqwest::{
+    header::{self, HeaderMap, HeaderValue},
+    Url,
+};
+
+use crate::config::cache_bust_method::CacheBustMethod;
+
+/// Name of the query parameter which makes every request unique.
+const CACHE_BUST_PARAMETER: &str = "rustpad_cache_bust";
+
+/// Makes every request unique, such that caches in front of the oracle can't answer in its stead.
+pub struct CacheBuster {
+    method: CacheBustMethod,
+    // randomly keyed, so tokens are unpredictable and differ between runs
+    random_state: RandomState,
+    counter: AtomicU64,
+}
+
+impl CacheBuster {
+    pub fn new(method: CacheBustMethod) -> Self {
+        Self {
+            method,
+            random_state: RandomState::new(),
+            counter: AtomicU64::new(0),
+        }
+    }
+
+    /// Add a random query parameter, and/or ask caches not to serve a stored response. Caching headers set by the user are respected.
+    pub fn apply(&self, url: &mut Url, headers: &mut HeaderMap) {
+        if self.method.uses_parameter() {
+            let mut hasher = self.random_state.build_hasher();
+            hasher.write_u64(self.counter.fetch_add(1, Ordering::Relaxed));
+            url.query_pairs_mut()
+                .append_pair(CACHE_BUST_PARAMETER, &format!("{:016x}", hasher.finish()));
+        }
+
+        if self.method.uses_header() {
+            headers
+                .entry(header::CACHE_CONTROL)
+                .or_insert_with(|| HeaderValue::from_static("no-cache"));
+            headers
+                .entry(header::PRAGMA)
+                .or_insert_with(|| HeaderValue::from_static("no-cache"));
+        }
+    }
+}
@@ -0,0 +1,72 @@
+use std::sync::Arc;
+
+use anyhow::Result;
+
+use crate::{
+    calibrator::{cache_hints::CacheHints, calibration_response::CalibrationResponse},
+    config::{SubConfig, WebConfig},
+    cypher_text::{encode::Encode, CypherText},
+    oracle::oracle_location::OracleLocation,
+};
+
+use super::{maintenance::Maintenance, Questioner};
+
+/// Unlike with `ScriptOracle`, we don't know which response from the web oracle corresponds with "valid", and which corresponds to "incorrect padding". For `WebOracle` to magically work, we need to determine the "incorrect padding" response. This struct manages the requests used for the calibration.
+/// `ask_validation` needs to return the web request's `CalibrationResponse`. Meaning, `Oracle` can't be implemented. Also, implementing it would be confusing as `CalibrateWebOracle`'s purpose is different from normal oracles.
+pub struct CalibrationWebOracle {
+    questioner: Questioner,
+}
+
+impl CalibrationWebOracle {
+    pub fn visit(oracle_location: &OracleLocation, oracle_config: &SubConfig) -> Result<Self> {
+        let oracle = Self {
+            questioner: Questioner::new(oracle_location, oracle_config)?,
+        };
+        // calibration is the first contact with the oracle, and is redone when recalibrating. Either is a good moment to (re)check the pin
+        oracle.questioner.check_certificate()?;
+        Ok(oracle)
+    }
+
+    /// Besides the response itself, returns hints on whether it was served from cache.
+    pub fn ask_validation<'a>(
+        &self,
+        cypher_text: &'a impl Encode<'a>,
+    ) -> Result<(CalibrationResponse, CacheHints)> {
+        self.questioner.block_on(async {
+            let response = self.questioner.send(cypher_text).await?;
+            let cache_hints = CacheHints::from(response.headers());
+            let response = CalibrationResponse::from_response(response, self.config()).await?;
+            Ok((response, cache_hints))
+        })
+    }
+
+    /// Open the pooled connections before calibrating, if `--warm-up` is given. The first calibration requests would be slowed down by connection setup otherwise
+    pub fn warm_up(&self, cypher_text: &CypherText) -> Result<()> {
+        if !*self.config().warm_up() {
+            return Ok(());
+        }
+        self.questioner.warm_up(cypher_text)
+    }
+
+    /// Wait out maintenance windows, checking for them with the original cypher text. See `Maintenance`
+    pub fn watch_maintenance<'a>(&mut self, original_cypher_text: &'a impl Encode<'a>) {
+        self.questioner.watch_maintenance(original_cypher_text);
+    }
+
+    // when recalibrating, maintenance is waited out together with the attack's oracle
+    pub(super) fn share_maintenance(&mut self, maintenance: Option<Arc<Maintenance>>) {
+        self.questioner.maintenance = maintenance;
+    }
+
+    pub fn enable_cache_busting(&mut self) {
+        self.questioner.enable_cache_busting();
+    }
+
+    pub fn cache_busting(&self) -> bool {
+        self.questioner.cache_busting()
+    }
+
+    pub fn config(&self) -> &WebConfig {
+        self.questioner.config()
+    }
+}
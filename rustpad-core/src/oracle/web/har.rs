@@ -0,0 +1,388 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fs::File,
+    future::Future,
+    io::BufWriter,
+    path::PathBuf,
+    sync::Mutex,
+    time::{Duration, Instant, SystemTime},
+};
+
+use anyhow::{Context, Result};
+use openssl::sha::sha256;
+use reqwest::{
+    header::{HeaderMap, HeaderValue, CONTENT_TYPE, LOCATION, USER_AGENT},
+    Request, Response, Version,
+};
+use serde::Serialize;
+
+use crate::config::user_agent::UserAgent;
+
+/// Records every request to the web oracle, and its response, in the HTTP Archive format. Browsers and Burp can import the file, e.g. to replay requests or as evidence for a report.
+/// Shared by all oracles built from the same config, so calibration and the attack end up in the same file.
+/// Response bodies are stored once per content hash, as an oracle gives the same few answers to many thousands of requests. The file only holds a body's text at its first occurrence, later ones refer to it by hash.
+#[derive(Debug)]
+pub struct Har {
+    path: PathBuf,
+    entries: Mutex<Vec<Entry>>,
+    // SHA-256 of the body, in hex, to its text and encoding
+    bodies: Mutex<HashMap<String, StoredBody>>,
+}
+
+#[derive(Debug, Clone)]
+struct StoredBody {
+    text: String,
+    encoding: Option<&'static str>,
+}
+
+#[derive(Serialize)]
+struct Document<'a> {
+    log: Log<'a>,
+}
+
+#[derive(Serialize)]
+struct Log<'a> {
+    version: &'static str,
+    creator: Creator,
+    entries: &'a [Entry],
+}
+
+#[derive(Serialize)]
+struct Creator {
+    name: &'static str,
+    version: &'static str,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Entry {
+    started_date_time: String,
+    // total, in ms
+    time: f64,
+    request: HarRequest,
+    response: HarResponse,
+    // required, but rustpad never answers from a browser cache
+    cache: Empty,
+    timings: Timings,
+    // custom field, for requests which got no response
+    #[serde(rename = "_error", skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct HarRequest {
+    method: String,
+    url: String,
+    http_version: String,
+    cookies: Vec<Empty>,
+    headers: Vec<NameValue>,
+    query_string: Vec<NameValue>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    post_data: Option<PostData>,
+    // -1: unknown
+    headers_size: i64,
+    body_size: i64,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct HarResponse {
+    // 0 if no response arrived, as browsers do
+    status: u16,
+    status_text: String,
+    http_version: String,
+    cookies: Vec<Empty>,
+    headers: Vec<NameValue>,
+    content: Content,
+    #[serde(rename = "redirectURL")]
+    redirect_url: String,
+    headers_size: i64,
+    body_size: i64,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct PostData {
+    mime_type: String,
+    text: String,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Content {
+    size: i64,
+    mime_type: String,
+    // filled in from the body store on export
+    #[serde(skip_serializing_if = "Option::is_none")]
+    text: Option<String>,
+    // `base64` for bodies which aren't UTF-8
+    #[serde(skip_serializing_if = "Option::is_none")]
+    encoding: Option<&'static str>,
+    // custom field, the key of the body in the store. Identical bodies share it
+    #[serde(rename = "_sha256", skip_serializing_if = "Option::is_none")]
+    sha256: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct NameValue {
+    name: String,
+    value: String,
+}
+
+// in ms
+#[derive(Debug, Serialize)]
+struct Timings {
+    send: f64,
+    wait: f64,
+    receive: f64,
+}
+
+#[derive(Debug, Serialize)]
+struct Empty {}
+
+impl Har {
+    pub fn new(path: PathBuf) -> Self {
+        Self {
+            path,
+            entries: Mutex::new(Vec::new()),
+            bodies: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Send the request with `send`, and record it with its response. The body is read to be recorded, so the returned response is rebuilt from it
+    pub async fn exchange<F>(
+        &self,
+        request: Request,
+        user_agent: &UserAgent,
+        send: impl FnOnce(Request) -> F,
+    ) -> Result<Response>
+    where
+        F: Future<Output = Result<Response>>,
+    {
+        let mut har_request = HarRequest::new(&request, user_agent);
+        let started = SystemTime::now();
+        let start = Instant::now();
+
+        let response = match send(request).await {
+            Ok(response) => response,
+            Err(e) => {
+                self.record(
+                    har_request,
+                    HarResponse::none(),
+                    started,
+                    start.elapsed(),
+                    Duration::ZERO,
+                    Some(format!("{:#}", e)),
+                );
+                return Err(e);
+            }
+        };
+        let wait = start.elapsed();
+
+        let status = response.status();
+        let version = response.version();
+        let headers = response.headers().clone();
+        har_request.http_version = format!("{:?}", version);
+        let body = match response.bytes().await {
+            Ok(body) => body,
+            Err(e) => {
+                self.record(
+                    har_request,
+                    HarResponse::none(),
+                    started,
+                    wait,
+                    start.elapsed() - wait,
+                    Some(format!("{:#}", e)),
+                );
+                return Err(e).context("Reading the response failed");
+            }
+        };
+        let receive = start.elapsed() - wait;
+
+        let har_response = HarResponse {
+            status: status.as_u16(),
+            status_text: status.canonical_reason().unwrap_or_default().to_string(),
+            http_version: format!("{:?}", version),
+            cookies: Vec::new(),
+            headers: name_values(&headers),
+            content: self.store_body(&body, headers.get(CONTENT_TYPE)),
+            redirect_url: headers
+                .get(LOCATION)
+                .map(header_value_text)
+                .unwrap_or_default(),
+            headers_size: -1,
+            body_size: body.len() as i64,
+        };
+        self.record(har_request, har_response, started, wait, receive, None);
+
+        let mut rebuilt = http::Response::new(body);
+        *rebuilt.status_mut() = status;
+        *rebuilt.version_mut() = version;
+        *rebuilt.headers_mut() = headers;
+        Ok(Response::from(rebuilt))
+    }
+
+    fn record(
+        &self,
+        request: HarRequest,
+        response: HarResponse,
+        started: SystemTime,
+        wait: Duration,
+        receive: Duration,
+        error: Option<String>,
+    ) {
+        let entry = Entry {
+            started_date_time: humantime::format_rfc3339_millis(started).to_string(),
+            time: as_ms(wait + receive),
+            request,
+            response,
+            cache: Empty {},
+            // the request is handed to the web client as a whole, so sending it can't be timed separately
+            timings: Timings {
+                send: 0.0,
+                wait: as_ms(wait),
+                receive: as_ms(receive),
+            },
+            error,
+        };
+        self.entries.lock().unwrap().push(entry);
+    }
+
+    /// Keep the body in the store, unless an identical one is already there. The returned content refers to it
+    fn store_body(&self, body: &[u8], content_type: Option<&HeaderValue>) -> Content {
+        let sha256 = hex::encode(sha256(body));
+        self.bodies
+            .lock()
+            .unwrap()
+            .entry(sha256.clone())
+            .or_insert_with(|| match std::str::from_utf8(body) {
+                Ok(text) => StoredBody {
+                    text: text.to_string(),
+                    encoding: None,
+                },
+                Err(_) => StoredBody {
+                    text: base64::encode(body),
+                    encoding: Some("base64"),
+                },
+            });
+
+        Content {
+            size: body.len() as i64,
+            mime_type: content_type.map(header_value_text).unwrap_or_default(),
+            text: None,
+            encoding: None,
+            sha256: Some(sha256),
+        }
+    }
+
+    /// Write all recorded requests to the file, replacing it
+    pub fn export(&self) -> Result<()> {
+        let mut entries = self.entries.lock().unwrap();
+        let bodies = self.bodies.lock().unwrap();
+        let mut exported = HashSet::new();
+        for content in entries.iter_mut().map(|entry| &mut entry.response.content) {
+            let stored = content
+                .sha256
+                .as_ref()
+                .filter(|sha256| exported.insert(*sha256))
+                .and_then(|sha256| bodies.get(sha256));
+            content.text = stored.map(|stored| stored.text.clone());
+            content.encoding = stored.and_then(|stored| stored.encoding);
+        }
+
+        let document = Document {
+            log: Log {
+                version: "1.2",
+                creator: Creator {
+                    name: env!("CARGO_PKG_NAME"),
+                    version: env!("CARGO_PKG_VERSION"),
+                },
+                entries: &entries,
+            },
+        };
+
+        let file = File::create(&self.path)
+            .context(format!("HAR file `{}` failed to open", self.path.display()))?;
+        serde_json::to_writer(BufWriter::new(file), &document)
+            .context(format!("Writing HAR file `{}` failed", self.path.display()))
+    }
+}
+
+impl HarRequest {
+    fn new(request: &Request, user_agent: &UserAgent) -> Self {
+        let mut headers = request.headers().clone();
+        // set by the web client, unless given as a header
+        if !headers.contains_key(USER_AGENT) {
+            if let Ok(user_agent) = HeaderValue::from_str(user_agent) {
+                headers.insert(USER_AGENT, user_agent);
+            }
+        }
+        let body = request.body().and_then(|body| body.as_bytes());
+
+        Self {
+            method: request.method().to_string(),
+            url: request.url().to_string(),
+            http_version: format!("{:?}", request.version()),
+            cookies: Vec::new(),
+            query_string: request
+                .url()
+                .query_pairs()
+                .map(|(name, value)| NameValue {
+                    name: name.to_string(),
+                    value: value.to_string(),
+                })
+                .collect(),
+            post_data: body.map(|body| PostData {
+                mime_type: headers
+                    .get(CONTENT_TYPE)
+                    .map(header_value_text)
+                    .unwrap_or_default(),
+                text: String::from_utf8_lossy(body).to_string(),
+            }),
+            headers: name_values(&headers),
+            headers_size: -1,
+            body_size: body.map_or(0, |body| body.len() as i64),
+        }
+    }
+}
+
+impl HarResponse {
+    fn none() -> Self {
+        Self {
+            status: 0,
+            status_text: String::new(),
+            http_version: format!("{:?}", Version::HTTP_11),
+            cookies: Vec::new(),
+            headers: Vec::new(),
+            content: Content {
+                size: 0,
+                mime_type: String::new(),
+                text: None,
+                encoding: None,
+                sha256: None,
+            },
+            redirect_url: String::new(),
+            headers_size: -1,
+            body_size: -1,
+        }
+    }
+}
+
+fn name_values(headers: &HeaderMap) -> Vec<NameValue> {
+    headers
+        .iter()
+        .map(|(name, value)| NameValue {
+            name: name.to_string(),
+            value: header_value_text(value),
+        })
+        .collect()
+}
+
+fn header_value_text(value: &HeaderValue) -> String {
+    String::from_utf8_lossy(value.as_bytes()).to_string()
+}
+
+fn as_ms(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+}
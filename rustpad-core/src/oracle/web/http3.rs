@@ -0,0 +1,343 @@
+pub(super) use self::quic::Http3Client;
+
+#[cfg(feature = "http3")]
+mod quic {
+    use std::{
+        net::SocketAddr,
+        sync::{
+            atomic::{AtomicBool, Ordering},
+            Arc,
+        },
+        time::Duration,
+    };
+
+    use anyhow::{anyhow, Context, Result};
+    use bytes::{Buf, Bytes, BytesMut};
+    use h3::client::SendRequest;
+    use h3_quinn::OpenStreams;
+    use log::Level;
+    use quinn::{crypto::rustls::QuicClientConfig, ClientConfig, Endpoint};
+    use reqwest::{header::USER_AGENT, Request, Response, Url, Version};
+    use rustls::{
+        client::danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier},
+        crypto::{ring, verify_tls12_signature, verify_tls13_signature, CryptoProvider},
+        pki_types::{CertificateDer, ServerName, UnixTime},
+        DigitallySignedStruct, RootCertStore, SignatureScheme,
+    };
+    use tokio::sync::Mutex;
+
+    use crate::{
+        config::WebConfig, error::RustpadError, logging::log_coalesced,
+        oracle::certificate_pin::CertificatePin,
+    };
+
+    const ALPN_H3: &[u8] = b"h3";
+
+    /// Sends the web oracle's requests over HTTP/3, for oracles which are only reachable, or behave differently, over QUIC. Requests are multiplexed over a single QUIC connection, which is reopened if it's closed.
+    /// If the oracle can't be reached over HTTP/3, e.g. as UDP is blocked, the client gives up on it for good, and the requests are sent over HTTP/2 or HTTP/1.1 instead.
+    /// Only available with the `http3` feature. `h3` speaks `http` 1.x, so requests and responses are converted from and to those of `reqwest`
+    pub(in crate::oracle::web) struct Http3Client {
+        host: String,
+        port: u16,
+        // `scheme://host:port`, for messages and the certificate pin
+        location: String,
+        tls: Arc<QuicClientConfig>,
+        timeout: Duration,
+        user_agent: String,
+        certificate_pin: Option<Arc<CertificatePin>>,
+        // set once connecting failed, after which the requests are sent over HTTP/2 or HTTP/1.1. Shared by all clients built from the same config
+        unavailable: Arc<AtomicBool>,
+        // held while connecting, so requests in flight share the connection
+        connection: Mutex<Option<Connection>>,
+    }
+
+    struct Connection {
+        quic: quinn::Connection,
+        send_request: SendRequest<OpenStreams, Bytes>,
+        // the connection's UDP socket
+        _endpoint: Endpoint,
+    }
+
+    impl Http3Client {
+        /// Set up TLS for the oracle at `url`. The connection is only opened by the first request
+        pub(in crate::oracle::web) fn new(url: &Url, config: &WebConfig) -> Result<Self> {
+            let provider = Arc::new(ring::default_provider());
+            let mut tls = rustls::ClientConfig::builder_with_provider(provider.clone())
+                .with_protocol_versions(&[&rustls::version::TLS13])
+                .context("TLS setup for HTTP/3 failed")?
+                .with_root_certificates(native_root_certificates())
+                .with_no_client_auth();
+            tls.alpn_protocols = vec![ALPN_H3.to_vec()];
+            if *config.insecure() {
+                tls.dangerous()
+                    .set_certificate_verifier(Arc::new(AcceptAnyCertificate(provider)));
+            }
+
+            Ok(Self {
+                host: url
+                    .host_str()
+                    .expect("Only HTTPS oracles, which have a host, are questioned over HTTP/3")
+                    .trim_start_matches('[')
+                    .trim_end_matches(']')
+                    .to_string(),
+                port: url.port_or_known_default().unwrap_or(443),
+                location: url.origin().ascii_serialization(),
+                tls: Arc::new(
+                    QuicClientConfig::try_from(tls).context("TLS setup for HTTP/3 failed")?,
+                ),
+                timeout: **config.request_timeout(),
+                user_agent: config.user_agent().to_string(),
+                certificate_pin: config.certificate_pin().clone(),
+                unavailable: config.http3_unavailable().clone(),
+                connection: Mutex::new(None),
+            })
+        }
+
+        /// Send `request` over HTTP/3. `None` if the oracle can't be reached over HTTP/3, in which case the request is to be sent over HTTP/2 or HTTP/1.1
+        pub(in crate::oracle::web) async fn execute(
+            &self,
+            request: &Request,
+        ) -> Result<Option<Response>> {
+            let mut send_request = match self.send_request().await {
+                Ok(Some(send_request)) => send_request,
+                Ok(None) => return Ok(None),
+                Err(e) if RustpadError::is_fatal(&e) => return Err(e),
+                Err(e) => {
+                    self.unavailable.store(true, Ordering::Relaxed);
+                    log_coalesced(
+                        Level::Warn,
+                        "HTTP/3 unavailable",
+                        format!(
+                            "Connecting to `{}` over HTTP/3 failed, falling back to HTTP/2 or HTTP/1.1: {:#}",
+                            self.location, e
+                        ),
+                    );
+                    return Ok(None);
+                }
+            };
+
+            tokio::time::timeout(
+                self.timeout,
+                exchange(&mut send_request, request, &self.user_agent),
+            )
+            .await
+            .map_err(|_| anyhow!("The HTTP/3 request to `{}` timed out", self.location))?
+            .map(Some)
+        }
+
+        /// Sender over the open connection. A connection is opened if there's none, or if it was closed, e.g. for being idle. `None` if connecting failed before
+        async fn send_request(&self) -> Result<Option<SendRequest<OpenStreams, Bytes>>> {
+            let mut connection = self.connection.lock().await;
+            // also by another client, while this one waited for the lock
+            if self.unavailable.load(Ordering::Relaxed) {
+                return Ok(None);
+            }
+            if let Some(connection) = connection
+                .as_ref()
+                .filter(|connection| connection.quic.close_reason().is_none())
+            {
+                return Ok(Some(connection.send_request.clone()));
+            }
+
+            let opened = tokio::time::timeout(self.timeout, self.connect())
+                .await
+                .map_err(|_| anyhow!("The QUIC handshake timed out"))??;
+            let send_request = opened.send_request.clone();
+            *connection = Some(opened);
+            Ok(Some(send_request))
+        }
+
+        async fn connect(&self) -> Result<Connection> {
+            let address = tokio::net::lookup_host((self.host.as_str(), self.port))
+                .await
+                .context(format!("Resolving `{}` failed", self.location))?
+                .next()
+                .ok_or_else(|| anyhow!("`{}` did not resolve to any address", self.location))?;
+            let local_address: SocketAddr = if address.is_ipv6() {
+                "[::]:0"
+            } else {
+                "0.0.0.0:0"
+            }
+            .parse()
+            .expect("Unspecified address is valid");
+
+            let mut endpoint =
+                Endpoint::client(local_address).context("Opening a UDP socket failed")?;
+            endpoint.set_default_client_config(ClientConfig::new(self.tls.clone()));
+            let quic = endpoint
+                .connect(address, &self.host)
+                .context("Connecting over QUIC failed")?
+                .await
+                .context("The QUIC handshake failed")?;
+
+            // the connection can't be inspected otherwise, so it's checked as it's opened
+            if let Some(certificate_pin) = &self.certificate_pin {
+                let certificate = quic
+                    .peer_identity()
+                    .and_then(|identity| identity.downcast::<Vec<CertificateDer>>().ok())
+                    .and_then(|certificates| {
+                        certificates.first().map(|certificate| certificate.to_vec())
+                    })
+                    .ok_or_else(|| {
+                        anyhow!("`{}` did not present a TLS certificate", self.location)
+                    })?;
+                certificate_pin.check(&self.location, &certificate)?;
+            }
+
+            let (mut driver, send_request) =
+                h3::client::new(h3_quinn::Connection::new(quic.clone()))
+                    .await
+                    .context("Setting up HTTP/3 failed")?;
+            // handles the connection's control stream, until it's closed
+            tokio::spawn(async move { driver.wait_idle().await });
+
+            Ok(Connection {
+                quic,
+                send_request,
+                _endpoint: endpoint,
+            })
+        }
+    }
+
+    async fn exchange(
+        send_request: &mut SendRequest<OpenStreams, Bytes>,
+        request: &Request,
+        user_agent: &str,
+    ) -> Result<Response> {
+        let mut h3_request = http1::Request::builder()
+            .method(request.method().as_str())
+            .uri(request.url().as_str());
+        for (name, value) in request.headers() {
+            h3_request = h3_request.header(name.as_str(), value.as_bytes());
+        }
+        // `reqwest` adds it when sending, rather than when building
+        if !request.headers().contains_key(USER_AGENT) {
+            h3_request = h3_request.header(USER_AGENT.as_str(), user_agent);
+        }
+        let h3_request = h3_request
+            .body(())
+            .context("Building the HTTP/3 request failed")?;
+
+        let mut stream = send_request
+            .send_request(h3_request)
+            .await
+            .context("Sending the HTTP/3 request failed")?;
+        if let Some(body) = request.body().and_then(|body| body.as_bytes()) {
+            stream
+                .send_data(Bytes::copy_from_slice(body))
+                .await
+                .context("Sending the HTTP/3 request's body failed")?;
+        }
+        stream
+            .finish()
+            .await
+            .context("Sending the HTTP/3 request failed")?;
+
+        let h3_response = stream
+            .recv_response()
+            .await
+            .context("Receiving the HTTP/3 response failed")?;
+        let mut body = BytesMut::new();
+        while let Some(mut chunk) = stream
+            .recv_data()
+            .await
+            .context("Receiving the HTTP/3 response's body failed")?
+        {
+            body.extend_from_slice(&chunk.copy_to_bytes(chunk.remaining()));
+        }
+
+        let mut response = http::Response::builder()
+            .status(h3_response.status().as_u16())
+            .version(Version::HTTP_3);
+        for (name, value) in h3_response.headers() {
+            response = response.header(name.as_str(), value.as_bytes());
+        }
+        Ok(Response::from(
+            response
+                .body(body.freeze())
+                .context("Converting the HTTP/3 response failed")?,
+        ))
+    }
+
+    /// The system's root certificates, which `native-tls` trusts for HTTP/2 and HTTP/1.1
+    fn native_root_certificates() -> RootCertStore {
+        let mut roots = RootCertStore::empty();
+        roots.add_parsable_certificates(rustls_native_certs::load_native_certs().certs);
+        roots
+    }
+
+    /// `--insecure`: any certificate is accepted, but the handshake's signatures must still match it
+    #[derive(Debug)]
+    struct AcceptAnyCertificate(Arc<CryptoProvider>);
+
+    impl ServerCertVerifier for AcceptAnyCertificate {
+        fn verify_server_cert(
+            &self,
+            _end_entity: &CertificateDer<'_>,
+            _intermediates: &[CertificateDer<'_>],
+            _server_name: &ServerName<'_>,
+            _ocsp_response: &[u8],
+            _now: UnixTime,
+        ) -> std::result::Result<ServerCertVerified, rustls::Error> {
+            Ok(ServerCertVerified::assertion())
+        }
+
+        fn verify_tls12_signature(
+            &self,
+            message: &[u8],
+            certificate: &CertificateDer<'_>,
+            signature: &DigitallySignedStruct,
+        ) -> std::result::Result<HandshakeSignatureValid, rustls::Error> {
+            verify_tls12_signature(
+                message,
+                certificate,
+                signature,
+                &self.0.signature_verification_algorithms,
+            )
+        }
+
+        fn verify_tls13_signature(
+            &self,
+            message: &[u8],
+            certificate: &CertificateDer<'_>,
+            signature: &DigitallySignedStruct,
+        ) -> std::result::Result<HandshakeSignatureValid, rustls::Error> {
+            verify_tls13_signature(
+                message,
+                certificate,
+                signature,
+                &self.0.signature_verification_algorithms,
+            )
+        }
+
+        fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+            self.0.signature_verification_algorithms.supported_schemes()
+        }
+    }
+}
+
+#[cfg(not(feature = "http3"))]
+mod quic {
+    use anyhow::{anyhow, Result};
+    use reqwest::{Request, Response, Url};
+
+    use crate::config::WebConfig;
+
+    // no request can be sent without QUIC
+    pub(in crate::oracle::web) enum Http3Client {}
+
+    impl Http3Client {
+        pub(in crate::oracle::web) fn new(_url: &Url, _config: &WebConfig) -> Result<Self> {
+            Err(anyhow!(
+                "This build of rustpad has no HTTP/3 support. Rebuild it with `--features http3`"
+            ))
+        }
+
+        pub(in crate::oracle::web) async fn execute(
+            &self,
+            _request: &Request,
+        ) -> Result<Option<Response>> {
+            match *self {}
+        }
+    }
+}
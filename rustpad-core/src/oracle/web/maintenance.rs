@@ -0,0 +1,148 @@
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Mutex,
+    },
+    time::{Duration, Instant},
+};
+
+use anyhow::Result;
+use humantime::format_duration;
+use log::{info, warn};
+use reqwest::{header::RETRY_AFTER, Response, StatusCode};
+use tokio::sync::{Mutex as AsyncMutex, MutexGuard as AsyncMutexGuard};
+
+use crate::{error::RustpadError, logging::LOG_TARGET};
+
+use super::Substitutions;
+
+// first probe after a `503`, so short hiccups barely slow the attack down
+const MIN_PROBE_DELAY: Duration = Duration::from_secs(1);
+
+/// Waits out maintenance windows of the web oracle, during which it answers `503 Service Unavailable`. Such answers aren't verdicts, so they'd look like total failure otherwise.
+/// A `503` only counts as maintenance if the original cypher text gets one too, as its padding is valid. A single request probes the oracle with it, at growing intervals up to `--maintenance-probe`, while the others wait. Once the oracle answers normally, all of them resume.
+#[derive(Debug)]
+pub struct Maintenance {
+    // the original cypher text
+    probe: Substitutions,
+    max_probe_delay: Duration,
+    max_wait: Duration,
+    // set once calibration finds `503` is how the oracle answers padding errors
+    ignored: AtomicBool,
+    // held by the request probing the oracle
+    prober: AsyncMutex<()>,
+    state: Mutex<State>,
+}
+
+#[derive(Debug, Default)]
+struct State {
+    // since when the oracle is unavailable
+    since: Option<Instant>,
+    recovered_at: Option<Instant>,
+    // when the last probe was sent, which the oracle answered normally
+    available_at: Option<Instant>,
+    // spent in maintenance windows which are over
+    waited: Duration,
+}
+
+impl Maintenance {
+    pub(super) fn new(probe: Substitutions, max_probe_delay: Duration, max_wait: Duration) -> Self {
+        Self {
+            probe,
+            max_probe_delay,
+            max_wait,
+            ignored: AtomicBool::new(false),
+            prober: AsyncMutex::new(()),
+            state: Mutex::new(State::default()),
+        }
+    }
+
+    pub(super) fn probe(&self) -> &Substitutions {
+        &self.probe
+    }
+
+    /// Whether the response may be due to maintenance, and is to be checked by probing
+    pub fn signalled_by(&self, response: &Response) -> bool {
+        !self.ignored.load(Ordering::Relaxed)
+            && response.status() == StatusCode::SERVICE_UNAVAILABLE
+    }
+
+    /// `503` is how the oracle answers padding errors, so it's a verdict from now on
+    pub fn ignore(&self) {
+        self.ignored.store(true, Ordering::Relaxed);
+    }
+
+    /// Become the request which probes the oracle. Waits while another request is probing
+    pub async fn start_probing(&self) -> AsyncMutexGuard<'_, ()> {
+        self.prober.lock().await
+    }
+
+    /// Whether the oracle recovered after the given time, i.e. while waiting for the request probing it
+    pub fn recovered_since(&self, instant: Instant) -> bool {
+        self.state
+            .lock()
+            .unwrap()
+            .recovered_at
+            .is_some_and(|recovered_at| recovered_at > instant)
+    }
+
+    /// Whether a probe sent after the given time was answered normally. A `503` to a request sent before it was then a verdict
+    pub fn available_since(&self, instant: Instant) -> bool {
+        self.state
+            .lock()
+            .unwrap()
+            .available_at
+            .is_some_and(|available_at| available_at >= instant)
+    }
+
+    /// How long to wait before probing again, after the oracle answered `503` to the probe. Honours `Retry-After`, up to the maximum delay. Fails once the maintenance windows lasted too long in total
+    pub fn probe_delay(&self, response: &Response) -> Result<Duration> {
+        let mut state = self.state.lock().unwrap();
+        let since = *state.since.get_or_insert_with(|| {
+            warn!(
+                target: LOG_TARGET,
+                "The oracle answered `503 Service Unavailable`, even to the original cypher text, as during maintenance. Pausing the attack, and probing every {} at most until it recovers",
+                format_duration(self.max_probe_delay)
+            );
+            Instant::now()
+        });
+
+        let remaining = self.max_wait.saturating_sub(state.waited + since.elapsed());
+        if remaining.is_zero() {
+            return Err(RustpadError::MaintenanceTimeout {
+                waited: format_duration(self.max_wait).to_string(),
+            }
+            .into());
+        }
+
+        // back off as the outage lasts, so short hiccups are over fast while long maintenance isn't hammered
+        let delay = response
+            .headers()
+            .get(RETRY_AFTER)
+            .and_then(|retry_after| retry_after.to_str().ok())
+            .and_then(|retry_after| retry_after.trim().parse().ok())
+            .map(Duration::from_secs)
+            .unwrap_or_else(|| since.elapsed().max(MIN_PROBE_DELAY));
+        // the last probe is made as the wait runs out
+        Ok(delay.min(self.max_probe_delay).min(remaining))
+    }
+
+    /// The oracle answered the probe sent at the given time normally. Returns whether it was unavailable until then
+    pub fn available(&self, probed_at: Instant) -> bool {
+        let mut state = self.state.lock().unwrap();
+        state.available_at = Some(probed_at);
+        let since = match state.since.take() {
+            Some(since) => since,
+            None => return false,
+        };
+
+        state.recovered_at = Some(Instant::now());
+        state.waited += since.elapsed();
+        info!(
+            target: LOG_TARGET,
+            "The oracle recovered after {} of unavailability. Resuming the attack",
+            format_duration(Duration::from_secs(since.elapsed().as_secs()))
+        );
+        true
+    }
+}
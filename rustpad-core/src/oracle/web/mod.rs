@@ -0,0 +1,1074 @@
+mod cache_buster;
+pub mod calibrate_web;
+pub mod har;
+mod http3;
+mod maintenance;
+pub mod proxy_chain;
+
+use std::{
+    collections::HashMap,
+    future::Future,
+    net::TcpStream,
+    str::FromStr,
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc, Mutex, RwLock,
+    },
+    time::{Duration, Instant},
+};
+
+use anyhow::{anyhow, Context, Result};
+use futures::{future, stream, StreamExt};
+use getset::Getters;
+use log::{info, warn};
+use native_tls::TlsConnector;
+use reqwest::{
+    header::{HeaderMap, HeaderName, HeaderValue, USER_AGENT},
+    redirect::Policy,
+    Client, ClientBuilder, Request, Response, StatusCode, Url,
+};
+use tokio::{
+    runtime::{self, Runtime},
+    sync::Semaphore,
+};
+
+use crate::{
+    calibrator::{calibration_response::CalibrationResponse, Calibrator},
+    config::{
+        cache_bust_method::CacheBustMethod, http_version::HttpVersion,
+        keyword_occurrence::KeywordOccurrence, thread_delay::ThreadDelay, SubConfig, WebConfig,
+    },
+    cypher_text::{encode::Encode, forged_cypher_text::ForgedCypherText, CypherText},
+    lint::LintCheck,
+    logging::LOG_TARGET,
+};
+
+use self::{
+    cache_buster::CacheBuster, calibrate_web::CalibrationWebOracle, http3::Http3Client,
+    maintenance::Maintenance,
+};
+
+use super::{
+    computed_placeholder, oracle_location::OracleLocation, request_budget::RequestBudget, Oracle,
+};
+
+// a separate connection is opened for each check of the certificate pin, so it's not checked with every batch
+const CERTIFICATE_CHECK_INTERVAL: Duration = Duration::from_secs(60);
+
+pub struct WebOracle {
+    questioner: Questioner,
+    // when the certificate pin was last checked during the attack
+    certificate_checked_at: Mutex<Option<Instant>>,
+    // replaced when recalibrating mid-attack
+    padding_error_response: RwLock<Option<CalibrationResponse>>,
+    // held while recalibrating, so threads which run into the same change of behaviour don't all recalibrate
+    recalibrating: Mutex<()>,
+}
+
+impl WebOracle {
+    async fn ask_validation_async<'a>(&self, cypher_text: &'a impl Encode<'a>) -> Result<bool> {
+        let response = self.questioner.send(cypher_text).await?;
+        let response =
+            CalibrationResponse::from_response(response, self.questioner.config()).await?;
+
+        let padding_error_response = self.padding_error_response.read().unwrap();
+        let padding_error_response = padding_error_response.as_ref().expect("Web oracle not calibrated. We don't know how an (in)correct padding response looks like");
+
+        Ok(!padding_error_response.matches(&response))
+    }
+
+    pub fn set_padding_error_response(
+        &mut self,
+        padding_error_response: Option<CalibrationResponse>,
+    ) {
+        if let Some(padding_error_response) = &padding_error_response {
+            self.questioner
+                .ignore_maintenance_for(padding_error_response);
+        }
+        *self.padding_error_response.get_mut().unwrap() = padding_error_response;
+    }
+
+    /// Wait out maintenance windows, checking for them with the original cypher text. See `Maintenance`
+    pub fn watch_maintenance<'a>(&mut self, original_cypher_text: &'a impl Encode<'a>) {
+        self.questioner.watch_maintenance(original_cypher_text);
+    }
+
+    pub fn enable_cache_busting(&mut self) {
+        self.questioner.enable_cache_busting();
+    }
+
+    /// Check the certificate pin, unless it was checked less than `CERTIFICATE_CHECK_INTERVAL` ago. Calibrating and recalibrating check it regardless
+    fn check_certificate_periodically(&self) -> Result<()> {
+        {
+            let mut checked_at = self.certificate_checked_at.lock().unwrap();
+            if checked_at
+                .is_some_and(|checked_at| checked_at.elapsed() < CERTIFICATE_CHECK_INTERVAL)
+            {
+                return Ok(());
+            }
+            *checked_at = Some(Instant::now());
+        }
+        self.questioner.check_certificate()
+    }
+
+    /// Open the pooled connections before the attack, if `--warm-up` is given
+    pub fn warm_up(&self, cypher_text: &CypherText) -> Result<()> {
+        if !*self.questioner.config().warm_up() {
+            return Ok(());
+        }
+        self.questioner.warm_up(cypher_text)
+    }
+}
+
+impl Oracle for WebOracle {
+    fn visit(oracle_location: &OracleLocation, oracle_config: &SubConfig) -> Result<Self> {
+        let oracle = Self {
+            questioner: Questioner::new(oracle_location, oracle_config)?,
+            certificate_checked_at: Mutex::new(None),
+            padding_error_response: RwLock::new(None),
+            recalibrating: Mutex::new(()),
+        };
+        Ok(oracle)
+    }
+
+    fn ask_validation<'a>(&self, cypher_text: &'a impl Encode<'a>) -> Result<bool> {
+        self.questioner
+            .block_on(self.ask_validation_async(cypher_text))
+    }
+
+    /// All cypher texts are sent asynchronously, limited only by the configured concurrency. In-flight requests are dropped as soon as valid padding is found.
+    fn ask_validation_batch(
+        &self,
+        cypher_texts: &[ForgedCypherText],
+        on_answer: &(dyn Fn(usize, Duration) + Sync),
+    ) -> Vec<Option<Result<bool>>> {
+        // a changed certificate fails the first cypher text, which stops the attack. Otherwise, all cypher texts are retried
+        if let Err(e) = self.check_certificate_periodically() {
+            let reason = format!("{:#}", e);
+            let mut verdicts: Vec<Option<Result<bool>>> = cypher_texts
+                .iter()
+                .map(|_| Some(Err(anyhow!("{}", reason))))
+                .collect();
+            verdicts[0] = Some(Err(e));
+            return verdicts;
+        }
+
+        self.questioner.block_on(async {
+            let mut verdicts: Vec<Option<Result<bool>>> =
+                cypher_texts.iter().map(|_| None).collect();
+
+            let mut answers = stream::iter(cypher_texts.iter().enumerate())
+                .map(|(idx, cypher_text)| async move {
+                    let start = Instant::now();
+                    let verdict = self.ask_validation_async(cypher_text).await;
+                    (idx, verdict, start.elapsed())
+                })
+                .buffer_unordered(**self.questioner.config().concurrency());
+
+            while let Some((idx, verdict, latency)) = answers.next().await {
+                on_answer(idx, latency);
+
+                let valid_padding = matches!(verdict, Ok(true));
+                verdicts[idx] = Some(verdict);
+                if valid_padding {
+                    break;
+                }
+            }
+
+            verdicts
+        })
+    }
+
+    /// Responses given by the user are never replaced
+    fn recalibrate(&self, calibration_cypher_text: &ForgedCypherText) -> Result<bool> {
+        if !self
+            .questioner
+            .config()
+            .padding_error_overrides()
+            .is_empty()
+        {
+            return Ok(false);
+        }
+
+        let _recalibrating = match self.recalibrating.try_lock() {
+            Ok(guard) => guard,
+            // another thread is recalibrating already. Its result applies to us as well
+            Err(_) => {
+                let _recalibrated = self.recalibrating.lock().unwrap();
+                return Ok(true);
+            }
+        };
+
+        let mut calibration_oracle = CalibrationWebOracle::visit(
+            &self.location(),
+            &SubConfig::Web(Box::new(self.questioner.config().clone())),
+        )?;
+        if self.questioner.cache_busting() {
+            calibration_oracle.enable_cache_busting();
+        }
+        calibration_oracle.share_maintenance(self.questioner.maintenance.clone());
+        let padding_error_response = Calibrator::new(calibration_cypher_text.clone())
+            .determine_padding_error_response(&mut calibration_oracle)?;
+
+        self.questioner
+            .ignore_maintenance_for(&padding_error_response);
+        *self.padding_error_response.write().unwrap() = Some(padding_error_response);
+        Ok(true)
+    }
+
+    fn location(&self) -> OracleLocation {
+        OracleLocation::Web(self.questioner.url().clone())
+    }
+    fn thread_delay(&self) -> &ThreadDelay {
+        self.questioner.config().thread_delay()
+    }
+    fn request_budget(&self) -> Option<&RequestBudget> {
+        Some(self.questioner.config().request_budget())
+    }
+}
+
+/// Sends forged cypher texts to the web oracle. Requests are made asynchronously, but the total amount in flight is limited to the configured concurrency.
+#[derive(Getters)]
+struct Questioner {
+    #[getset(get)]
+    url: Url,
+    #[getset(get)]
+    config: WebConfig,
+    // a single one, unless HTTP/2 or HTTP/3 requests are spread over multiple connections. See `web_client`
+    web_clients: Vec<WebClient>,
+    next_client: AtomicUsize,
+    keyword_locations: Vec<KeywordLocation>,
+    runtime: Runtime,
+    requests_in_flight: Semaphore,
+    cache_buster: Option<CacheBuster>,
+    // `None` until watched, and if `503` is how the oracle answers padding errors. Shared with the oracle used for recalibration
+    maintenance: Option<Arc<Maintenance>>,
+}
+
+struct WebClient {
+    client: Client,
+    // with `--http-version 3`. `client` takes over if the oracle can't be reached over HTTP/3
+    http3: Option<Http3Client>,
+    // whether requests are multiplexed over an open connection. Always for HTTP/1.1, which opens a connection per request in flight
+    connected: AtomicBool,
+    // held by the request which opens the HTTP/2 connection. The version is only known once negotiated, so otherwise every request in flight would open a connection of its own
+    connecting: tokio::sync::Mutex<()>,
+}
+
+impl WebClient {
+    fn new(client: Client, http3: Option<Http3Client>, multiplexes: bool) -> Self {
+        Self {
+            client,
+            http3,
+            connected: AtomicBool::new(!multiplexes),
+            connecting: tokio::sync::Mutex::new(()),
+        }
+    }
+
+    /// Wait for the connection to open. Returns a guard if this request is to open it instead
+    async fn connect(&self) -> Option<tokio::sync::MutexGuard<'_, ()>> {
+        if self.connected.load(Ordering::Relaxed) {
+            return None;
+        }
+        let connecting = self.connecting.lock().await;
+        (!self.connected.load(Ordering::Relaxed)).then_some(connecting)
+    }
+
+    /// Send the request over HTTP/3, if the oracle can be reached over it
+    async fn execute(&self, request: Request) -> Result<Response> {
+        if let Some(http3) = &self.http3 {
+            if let Some(response) = http3.execute(&request).await? {
+                return Ok(response);
+            }
+        }
+
+        self.client
+            .execute(request)
+            .await
+            .context("Sending request failed")
+    }
+}
+
+impl Questioner {
+    fn new(oracle_location: &OracleLocation, oracle_config: &SubConfig) -> Result<Self> {
+        let (url, web_clients, keyword_locations, web_config) =
+            build_web_oracle(oracle_location, oracle_config)?;
+
+        let runtime = runtime::Builder::new_multi_thread()
+            .enable_all()
+            .thread_name("Web oracle")
+            .build()
+            .context("Async runtime setup failed")?;
+
+        Ok(Self {
+            url,
+            config: web_config.clone(),
+            web_clients,
+            next_client: AtomicUsize::new(0),
+            keyword_locations,
+            runtime,
+            requests_in_flight: Semaphore::new(**web_config.concurrency()),
+            cache_buster: web_config.cache_bust().map(CacheBuster::new),
+            maintenance: None,
+        })
+    }
+
+    fn watch_maintenance<'a>(&mut self, original_cypher_text: &'a impl Encode<'a>) {
+        if *self.config.padding_error_overrides().status() == Some(StatusCode::SERVICE_UNAVAILABLE)
+        {
+            return;
+        }
+        self.maintenance = Some(Arc::new(Maintenance::new(
+            keyword_substitutions(&self.config, original_cypher_text),
+            **self.config.maintenance_probe(),
+            **self.config.maintenance_timeout(),
+        )));
+    }
+
+    /// Once calibration finds `503` is how the oracle answers padding errors, it's no longer taken for maintenance
+    fn ignore_maintenance_for(&self, padding_error_response: &CalibrationResponse) {
+        let maintenance = match &self.maintenance {
+            Some(maintenance) => maintenance,
+            None => return,
+        };
+        let unavailable =
+            |response: &CalibrationResponse| *response.status() == StatusCode::SERVICE_UNAVAILABLE;
+        if unavailable(padding_error_response)
+            || padding_error_response
+                .other_signatures()
+                .iter()
+                .any(unavailable)
+        {
+            maintenance.ignore();
+        }
+    }
+
+    fn enable_cache_busting(&mut self) {
+        self.cache_buster
+            .get_or_insert_with(|| CacheBuster::new(CacheBustMethod::All));
+    }
+
+    fn cache_busting(&self) -> bool {
+        self.cache_buster.is_some()
+    }
+
+    /// Send the cypher text to the oracle. Maintenance windows are waited out, so a `503 Service Unavailable` is only returned if the oracle was available when it was given
+    async fn send<'a>(&self, cypher_text: &'a impl Encode<'a>) -> Result<Response> {
+        loop {
+            let sent_at = Instant::now();
+            let response = self.send_once(cypher_text).await?;
+            let maintenance = match &self.maintenance {
+                Some(maintenance) if maintenance.signalled_by(&response) => maintenance,
+                _ => return Ok(response),
+            };
+
+            let _probing = maintenance.start_probing().await;
+            if maintenance.recovered_since(sent_at) {
+                continue;
+            }
+            if maintenance.available_since(sent_at) || !self.wait_out(maintenance).await? {
+                return Ok(response);
+            }
+        }
+    }
+
+    /// Probe the oracle with the original cypher text until it answers normally. Returns whether it was under maintenance
+    async fn wait_out(&self, maintenance: &Maintenance) -> Result<bool> {
+        loop {
+            let probed_at = Instant::now();
+            let response = self.send_substituted(maintenance.probe()).await?;
+            if !maintenance.signalled_by(&response) {
+                return Ok(maintenance.available(probed_at));
+            }
+            tokio::time::sleep(maintenance.probe_delay(&response)?).await;
+        }
+    }
+
+    async fn send_once<'a>(&self, cypher_text: &'a impl Encode<'a>) -> Result<Response> {
+        self.send_substituted(&keyword_substitutions(&self.config, cypher_text))
+            .await
+    }
+
+    async fn send_substituted(&self, substitutions: &Substitutions) -> Result<Response> {
+        self.config.request_budget().spend()?;
+        let (url, data, mut headers) = replace_keyword_occurrences(
+            &self.url,
+            &self.config,
+            self.keyword_locations.iter(),
+            substitutions,
+            self.cache_buster.as_ref(),
+        )
+        .context("Replacing all occurrences of keyword failed")?;
+        if let Some(sticky_session) = self.config.sticky_session() {
+            sticky_session.apply(&mut headers);
+        }
+
+        let web_client = self.web_client();
+        let request = web_client
+            .client
+            .request(self.config.method().clone(), url)
+            .headers(headers);
+        let request = match data {
+            Some(data) => request.body(data),
+            None => request,
+        };
+        let request = request.build().context("Building request failed")?;
+
+        let _permit = self
+            .requests_in_flight
+            .acquire()
+            .await
+            .context("Request limiter closed")?;
+        tokio::time::sleep(**self.config.thread_delay()).await;
+        if let Some(traffic_shaper) = self.config.traffic_shaper() {
+            tokio::time::sleep(traffic_shaper.reserve()).await;
+        }
+
+        let _connecting = web_client.connect().await;
+        let response = match self.config.har() {
+            Some(har) => {
+                har.exchange(request, self.config.user_agent(), |request| {
+                    web_client.execute(request)
+                })
+                .await?
+            }
+            None => web_client.execute(request).await?,
+        };
+        web_client.connected.store(true, Ordering::Relaxed);
+        if let Some(sticky_session) = self.config.sticky_session() {
+            sticky_session.capture(response.headers());
+        }
+        Ok(response)
+    }
+
+    /// Client for the next request. Each multiplexes its HTTP/2 or HTTP/3 requests over a connection of its own, so they're taken in turn
+    fn web_client(&self) -> &WebClient {
+        let idx = self.next_client.fetch_add(1, Ordering::Relaxed);
+        &self.web_clients[idx % self.web_clients.len()]
+    }
+
+    /// Open as many pooled connections as the concurrency allows, by sending the original cypher text over each. Bodies are read, so connections go back to the pool
+    fn warm_up(&self, cypher_text: &CypherText) -> Result<()> {
+        let connections = **self.config.concurrency();
+        let start = Instant::now();
+
+        let results = self.block_on(future::join_all((0..connections).map(|_| async {
+            let response = self.send(cypher_text).await?;
+            response
+                .bytes()
+                .await
+                .context("Reading the warm-up response failed")?;
+            Ok::<_, anyhow::Error>(())
+        })));
+
+        let mut errors: Vec<_> = results.into_iter().filter_map(Result::err).collect();
+        let failed = errors.len();
+        if failed == connections {
+            return Err(errors
+                .swap_remove(0)
+                .context("Warming up connections failed"));
+        }
+        if failed > 0 {
+            warn!(
+                target: LOG_TARGET,
+                "{} of {} warm-up request(s) failed",
+                failed,
+                connections
+            );
+        }
+
+        info!(
+            target: LOG_TARGET,
+            "Warmed up {} connection(s) in {:.2?}",
+            connections - failed,
+            start.elapsed()
+        );
+        Ok(())
+    }
+
+    /// Check the oracle's TLS certificate against the pin, if any. The web client's connections can't be inspected, so a separate connection is made. QUIC connections are checked as they're opened instead
+    fn check_certificate(&self) -> Result<()> {
+        let certificate_pin = match self.config.certificate_pin() {
+            Some(certificate_pin) => certificate_pin,
+            None => return Ok(()),
+        };
+        let over_quic = self
+            .web_clients
+            .iter()
+            .all(|web_client| web_client.http3.is_some())
+            && !self.config.http3_unavailable().load(Ordering::Relaxed);
+        if over_quic {
+            return Ok(());
+        }
+        let location = self.url.origin().ascii_serialization();
+
+        let mut last_error = None;
+        let mut stream = None;
+        for socket_address in self
+            .url
+            .socket_addrs(|| None)
+            .context(format!("Resolving `{}` failed", location))?
+        {
+            match TcpStream::connect_timeout(&socket_address, **self.config.request_timeout()) {
+                Ok(connected) => {
+                    stream = Some(connected);
+                    break;
+                }
+                Err(e) => last_error = Some(e),
+            }
+        }
+        let stream = match (stream, last_error) {
+            (Some(stream), _) => stream,
+            (None, Some(e)) => {
+                return Err(e).context(format!("Connecting to `{}` failed", location))
+            }
+            (None, None) => return Err(anyhow!("`{}` did not resolve to any address", location)),
+        };
+        stream.set_read_timeout(Some(**self.config.request_timeout()))?;
+        stream.set_write_timeout(Some(**self.config.request_timeout()))?;
+
+        let stream = TlsConnector::builder()
+            .danger_accept_invalid_certs(*self.config.insecure())
+            .danger_accept_invalid_hostnames(*self.config.insecure())
+            .build()
+            .context("TLS setup failed")?
+            .connect(
+                self.url
+                    .host_str()
+                    .expect("Only HTTPS oracles, which have a host, can be pinned")
+                    .trim_start_matches('[')
+                    .trim_end_matches(']'),
+                stream,
+            )
+            .context(format!("TLS handshake with `{}` failed", location))?;
+
+        certificate_pin.check_stream(&location, &stream)
+    }
+
+    fn block_on<F: Future>(&self, future: F) -> F::Output {
+        self.runtime.block_on(future)
+    }
+}
+
+#[derive(Debug)]
+enum KeywordLocation {
+    Url,
+    PostData,
+    Headers(HashMap<usize, HeaderWithKeyword>),
+}
+
+#[derive(Debug)]
+struct HeaderWithKeyword {
+    keyword_in_name: bool,
+    keyword_in_value: bool,
+}
+
+/// Pairs of (placeholder, value) with which the placeholders of a request are replaced
+#[derive(Debug)]
+struct Substitutions {
+    // see `ComputedPlaceholder`. Always replaced, as they don't count as occurrences of the keyword
+    computed: Vec<(String, String)>,
+    // the keyword, or the placeholders of its chunks. Replaced as selected by `--keyword-occurrence`
+    keyword: Vec<(String, String)>,
+}
+
+fn keyword_substitutions<'a>(
+    config: &WebConfig,
+    cypher_text: &'a impl Encode<'a>,
+) -> Substitutions {
+    let encoded_cypher_text = cypher_text.encode();
+
+    let keyword = match config.cypher_text_split() {
+        Some(split) => split
+            .split(&encoded_cypher_text, *cypher_text.url_encoded())
+            .into_iter()
+            .enumerate()
+            .map(|(idx, chunk)| (format!("{}{}", config.keyword(), idx), chunk))
+            // `CTEXT10` contains `CTEXT1`. Replace the highest indices first
+            .rev()
+            .collect(),
+        None => vec![(config.keyword().clone(), encoded_cypher_text)],
+    };
+
+    Substitutions {
+        computed: computed_placeholder::substitutions(config.keyword(), cypher_text),
+        keyword,
+    }
+}
+
+fn substitute(text: &str, substitutions: &Substitutions, occurrence: &KeywordOccurrence) -> String {
+    // computed placeholders contain the keyword, so they go first
+    let text = substitutions
+        .computed
+        .iter()
+        .fold(text.to_string(), |text, (placeholder, value)| {
+            text.replace(placeholder, value)
+        });
+    substitutions
+        .keyword
+        .iter()
+        .fold(text, |text, (placeholder, value)| {
+            occurrence.replace(&text, placeholder, value)
+        })
+}
+
+fn replace_keyword_occurrences<'a>(
+    url: &Url,
+    config: &WebConfig,
+    keyword_locations: impl Iterator<Item = &'a KeywordLocation>,
+    substitutions: &Substitutions,
+    cache_buster: Option<&CacheBuster>,
+) -> Result<(Url, Option<String>, HeaderMap)> {
+    let mut url = url.clone();
+    let mut data = config.post_data().clone();
+    let mut headers = None;
+
+    for location in keyword_locations {
+        match location {
+            KeywordLocation::Url => {
+                url = Url::parse(&substitute(url.as_str(), substitutions, config.keyword_occurrences().url())).expect("Target URL, which parsed correctly initially, doesn't parse any more after replacing the keyword");
+            }
+            KeywordLocation::PostData => {
+                data = Some(substitute(
+                    data.as_deref().expect(
+                        "The keyword was found in the POST data, yet no POST data exists...",
+                    ),
+                    substitutions,
+                    config.keyword_occurrences().post_data(),
+                ));
+            }
+            KeywordLocation::Headers(headers_with_keyword) => {
+                headers = Some(
+                    replace_keyword_in_headers(config, headers_with_keyword, substitutions)
+                        .context("Parsing headers failed")?,
+                );
+            }
+        }
+    }
+
+    // maybe there are no headers to replace, in which case the `HeaderMap` hasn't been constructed. Do it now
+    if headers.is_none() {
+        headers = Some(
+            replace_keyword_in_headers(config, &HashMap::new(), substitutions)
+                .context("Parsing headers failed")?,
+        );
+    }
+
+    let mut headers = headers.expect(
+        "HeaderMap should have been constructed even if no replacement in the headers is required",
+    );
+
+    // done after replacing the keyword, so the user's request can't accidentally mangle the cache busting
+    if let Some(cache_buster) = cache_buster {
+        cache_buster.apply(&mut url, &mut headers);
+    }
+
+    Ok((url, data, headers))
+}
+
+fn replace_keyword_in_headers(
+    config: &WebConfig,
+    headers_with_keyword: &HashMap<usize, HeaderWithKeyword>,
+    substitutions: &Substitutions,
+) -> Result<HeaderMap> {
+    config
+        .headers()
+        .iter()
+        .enumerate()
+        .map(|(idx, header)| {
+            // check if this header contains the keyword
+            let (header_name, header_value) = match headers_with_keyword.get(&idx) {
+                // do `HeaderName/HeaderValue::from_str` right away so we can prevent some `clone`s
+                Some(replace_location) => {
+                    // replace if needed
+                    let resulting_name = if replace_location.keyword_in_name {
+                        HeaderName::from_str(&substitute(
+                            header.name(),
+                            substitutions,
+                            config.keyword_occurrences().headers(),
+                        ))
+                    } else {
+                        HeaderName::from_str(header.name())
+                    };
+
+                    let resulting_value = if replace_location.keyword_in_value {
+                        HeaderValue::from_str(&substitute(
+                            header.value(),
+                            substitutions,
+                            config.keyword_occurrences().headers(),
+                        ))
+                    } else {
+                        HeaderValue::from_str(header.value())
+                    };
+
+                    (resulting_name, resulting_value)
+                }
+                None => (
+                    HeaderName::from_str(header.name()),
+                    HeaderValue::from_str(header.value()),
+                ),
+            };
+
+            Ok((
+                header_name.context(format!("Header name invalid: {}", header.name()))?,
+                header_value.context(format!("Header value invalid: {}", header.value()))?,
+            ))
+        })
+        .collect::<Result<_>>()
+}
+
+/// Try to indicate where the keyword is as precisely as possible. This is to prevent unneeded `.replace`s on every value, every time a request is made
+fn keyword_location(url: &Url, config: &WebConfig) -> Vec<KeywordLocation> {
+    let mut keyword_locations = Vec::with_capacity(3);
+
+    if url.to_string().contains(config.keyword()) {
+        keyword_locations.push(KeywordLocation::Url);
+    }
+
+    if config
+        .post_data()
+        .as_deref()
+        .unwrap_or_default()
+        .contains(config.keyword())
+    {
+        keyword_locations.push(KeywordLocation::PostData);
+    }
+
+    let headers_with_keyword = config
+        .headers()
+        .iter()
+        .enumerate()
+        .filter_map(|(idx, header)| {
+            let keyword_in_name = header.name().contains(config.keyword());
+            let keyword_in_value = header.value().contains(config.keyword());
+
+            if keyword_in_name || keyword_in_value {
+                Some((
+                    idx,
+                    HeaderWithKeyword {
+                        keyword_in_name,
+                        keyword_in_value,
+                    },
+                ))
+            } else {
+                None
+            }
+        })
+        .collect::<HashMap<_, _>>();
+    if !headers_with_keyword.is_empty() {
+        keyword_locations.push(KeywordLocation::Headers(headers_with_keyword));
+    }
+
+    keyword_locations
+}
+
+/// Checks of the configuration which don't require contacting the oracle. See `rustpad lint`
+pub fn lint(url: &Url, config: &WebConfig, cypher_text: &CypherText) -> Vec<LintCheck> {
+    let keyword_locations = keyword_location(url, config);
+
+    vec![
+        LintCheck::new(
+            "Oracle URL uses HTTP(S)",
+            match url.scheme() {
+                "http" | "https" => Ok(()),
+                scheme => Err(anyhow!("Unsupported scheme `{}`", scheme)),
+            },
+        ),
+        LintCheck::new(
+            "Keyword is present in the request",
+            check_keyword_present(&keyword_locations),
+        ),
+        LintCheck::new(
+            "Placeholders of all cypher text chunks are present",
+            check_split_placeholders(url, config),
+        ),
+        LintCheck::new(
+            "Selected occurrences of the keyword exist",
+            check_keyword_occurrences(url, config),
+        ),
+        LintCheck::new(
+            "Request is valid once the cypher text is filled in",
+            replace_keyword_occurrences(
+                url,
+                config,
+                keyword_locations.iter(),
+                &keyword_substitutions(config, cypher_text),
+                None,
+            )
+            .map(|_| ()),
+        ),
+    ]
+}
+
+/// The requests which would be sent for `cypher_texts`, as text: request line, headers, and POST data. See `--dry-run`
+pub fn dry_run(
+    url: &Url,
+    config: &WebConfig,
+    cypher_texts: &[ForgedCypherText],
+) -> Result<Vec<String>> {
+    let keyword_locations = checked_keyword_location(url, config)?;
+    let cache_buster = config.cache_bust().map(CacheBuster::new);
+
+    cypher_texts
+        .iter()
+        .map(|cypher_text| {
+            let (url, headers, data) = request_parts(
+                url,
+                config,
+                &keyword_locations,
+                cypher_text,
+                cache_buster.as_ref(),
+            )?;
+
+            let mut request = format!("{} {}\n", config.method(), url);
+            for (name, value) in &headers {
+                request.push_str(&format!(
+                    "{}: {}\n",
+                    name,
+                    String::from_utf8_lossy(value.as_bytes())
+                ));
+            }
+            if let Some(data) = data {
+                request.push_str(&format!("\n{}\n", data));
+            }
+            Ok(request)
+        })
+        .collect()
+}
+
+/// URL, headers and POST data of the request which sends `cypher_text`, e.g. to reproduce the attack's result. The user agent is among the headers
+pub fn request_for<'a>(
+    url: &Url,
+    config: &WebConfig,
+    cypher_text: &'a impl Encode<'a>,
+) -> Result<(Url, HeaderMap, Option<String>)> {
+    let keyword_locations = checked_keyword_location(url, config)?;
+    request_parts(url, config, &keyword_locations, cypher_text, None)
+}
+
+fn checked_keyword_location(url: &Url, config: &WebConfig) -> Result<Vec<KeywordLocation>> {
+    let keyword_locations = keyword_location(url, config);
+    check_keyword_present(&keyword_locations)?;
+    check_split_placeholders(url, config)?;
+    check_keyword_occurrences(url, config)?;
+    Ok(keyword_locations)
+}
+
+fn request_parts<'a>(
+    url: &Url,
+    config: &WebConfig,
+    keyword_locations: &[KeywordLocation],
+    cypher_text: &'a impl Encode<'a>,
+    cache_buster: Option<&CacheBuster>,
+) -> Result<(Url, HeaderMap, Option<String>)> {
+    let (url, data, mut headers) = replace_keyword_occurrences(
+        url,
+        config,
+        keyword_locations.iter(),
+        &keyword_substitutions(config, cypher_text),
+        cache_buster,
+    )
+    .context("Replacing all occurrences of keyword failed")?;
+    // set by the web client, unless given as a header
+    if !headers.contains_key(USER_AGENT) {
+        headers.insert(
+            USER_AGENT,
+            HeaderValue::from_str(config.user_agent())
+                .context("User agent is not a valid header value")?,
+        );
+    }
+
+    Ok((url, headers, data))
+}
+
+fn check_keyword_present(keyword_locations: &[KeywordLocation]) -> Result<()> {
+    if keyword_locations.is_empty() {
+        return Err(anyhow!(
+            "Keyword not found in URL, headers, or POST data. Double check whether you indicated the cypher text's location. See `--keyword` for extra info"
+        ));
+    }
+
+    Ok(())
+}
+
+fn check_split_placeholders(url: &Url, config: &WebConfig) -> Result<()> {
+    if let Some(split) = config.cypher_text_split() {
+        let request_text = [url.to_string()]
+            .into_iter()
+            .chain(config.post_data().clone())
+            .chain(
+                config
+                    .headers()
+                    .iter()
+                    .map(|header| format!("{}:{}", header.name(), header.value())),
+            )
+            .collect::<String>();
+        if let Some(missing_placeholder) = (0..*split.amount_chunks())
+            .map(|idx| format!("{}{}", config.keyword(), idx))
+            .find(|placeholder| !request_text.contains(placeholder))
+        {
+            return Err(anyhow!(
+                "The cypher text is split into {} chunks, but `{}` was not found in the URL, headers, or POST data. See `--split` for extra info",
+                split.amount_chunks(),
+                missing_placeholder
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+/// Selecting the n-th occurrence of the keyword is pointless if there are less than n occurrences. Every placeholder is checked in every text in which it appears.
+fn check_keyword_occurrences(url: &Url, config: &WebConfig) -> Result<()> {
+    let placeholders = match config.cypher_text_split() {
+        Some(split) => (0..*split.amount_chunks())
+            .map(|idx| format!("{}{}", config.keyword(), idx))
+            .collect(),
+        None => vec![config.keyword().clone()],
+    };
+
+    let url = url.to_string();
+    let texts_per_part = [
+        (
+            "URL",
+            config.keyword_occurrences().url(),
+            vec![url.as_str()],
+        ),
+        (
+            "POST data",
+            config.keyword_occurrences().post_data(),
+            config.post_data().as_deref().into_iter().collect(),
+        ),
+        (
+            "headers",
+            config.keyword_occurrences().headers(),
+            config
+                .headers()
+                .iter()
+                .flat_map(|header| [header.name().as_str(), header.value().as_str()])
+                .collect(),
+        ),
+    ];
+
+    for (part, occurrence, texts) in texts_per_part {
+        for text in texts {
+            let keyword_text = computed_placeholder::strip(text, config.keyword());
+            for placeholder in &placeholders {
+                let amount_occurrences = keyword_text.matches(placeholder.as_str()).count();
+                if amount_occurrences > 0 && amount_occurrences < occurrence.required_occurrences()
+                {
+                    return Err(anyhow!(
+                        "Occurrence {} of `{}` was selected to be replaced in the {}, but `{}` only contains {} occurrence(s). See `--keyword-occurrence` for extra info",
+                        occurrence.required_occurrences(),
+                        placeholder,
+                        part,
+                        text,
+                        amount_occurrences
+                    ));
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn build_web_oracle<'a>(
+    oracle_location: &OracleLocation,
+    oracle_config: &'a SubConfig,
+) -> Result<(Url, Vec<WebClient>, Vec<KeywordLocation>, &'a WebConfig)> {
+    let url = match oracle_location {
+        OracleLocation::Web(url) => url,
+        OracleLocation::Script(_)
+        | OracleLocation::Tcp(_)
+        | OracleLocation::Plugin(_)
+        | OracleLocation::Lua(_)
+        | OracleLocation::Python(_) => {
+            panic!("Tried to visit the web oracle using a file path or socket address!");
+        }
+    };
+
+    let oracle_config = match oracle_config {
+        SubConfig::Web(config) => config,
+        SubConfig::Script(_)
+        | SubConfig::Tcp(_)
+        | SubConfig::Plugin(_)
+        | SubConfig::Lua(_)
+        | SubConfig::Python(_) => {
+            panic!(
+                "Tried to visit the web oracle using script, TCP, plugin, Lua or Python configs!"
+            );
+        }
+    };
+
+    let keyword_locations = keyword_location(url, oracle_config);
+    check_keyword_present(&keyword_locations)?;
+    check_split_placeholders(url, oracle_config)?;
+    check_keyword_occurrences(url, oracle_config)?;
+
+    let build_client = || {
+        let mut client_builder = ClientBuilder::new()
+            .timeout(**oracle_config.request_timeout())
+            .danger_accept_invalid_certs(*oracle_config.insecure())
+            .user_agent(&**oracle_config.user_agent());
+        if !oracle_config.redirect() {
+            client_builder = client_builder.redirect(Policy::none());
+        }
+        if let Some(proxy) = oracle_config.proxy() {
+            client_builder = client_builder.proxy(proxy.clone());
+        }
+        client_builder = match oracle_config.http_version() {
+            HttpVersion::Http1 => client_builder.http1_only(),
+            // offered next to HTTP/1.1 during the TLS handshake
+            // the fallback of HTTP/3
+            HttpVersion::Http2 | HttpVersion::Http3 => client_builder,
+            HttpVersion::Http2PriorKnowledge => client_builder.http2_prior_knowledge(),
+        };
+
+        client_builder.build().context("Web client setup failed")
+    };
+    // QUIC can't be tunnelled through the proxies, and needs TLS
+    let http3 = match oracle_config.http_version() {
+        HttpVersion::Http3 if oracle_config.proxy().is_some() => {
+            warn!(
+                target: LOG_TARGET,
+                "HTTP/3 can't be sent through a proxy. Falling back to HTTP/2 or HTTP/1.1"
+            );
+            false
+        }
+        HttpVersion::Http3 if url.scheme() != "https" => {
+            warn!(
+                target: LOG_TARGET,
+                "HTTP/3 needs an `https` URL. Falling back to HTTP/1.1"
+            );
+            false
+        }
+        HttpVersion::Http3 => true,
+        _ => false,
+    };
+    // a client keeps a single HTTP/2 or QUIC connection per host
+    let multiplexes = oracle_config.http_version().multiplexes();
+    let amount_clients = if multiplexes {
+        oracle_config.http2_connections().get()
+    } else {
+        1
+    };
+    let web_clients = (0..amount_clients)
+        .map(|_| {
+            Ok(WebClient::new(
+                build_client()?,
+                http3
+                    .then(|| Http3Client::new(url, oracle_config))
+                    .transpose()?,
+                multiplexes,
+            ))
+        })
+        .collect::<Result<_>>()?;
+
+    Ok((
+        url.to_owned(),
+        web_clients,
+        keyword_locations,
+        oracle_config,
+    ))
+}
@@ -0,0 +1,397 @@
+use std::{
+    io::{self, Read, Write},
+    net::{IpAddr, Shutdown, SocketAddr, TcpListener, TcpStream, ToSocketAddrs},
+    thread,
+    time::Duration,
+};
+
+use anyhow::{anyhow, Context, Result};
+use log::Level;
+use openssl::rand::rand_bytes;
+use reqwest::Url;
+
+use crate::logging::log_coalesced;
+
+const SOCKS_VERSION: u8 = 5;
+const SOCKS_NO_AUTHENTICATION: u8 = 0;
+const SOCKS_USERNAME_PASSWORD: u8 = 2;
+const SOCKS_NO_ACCEPTABLE_METHODS: u8 = 0xff;
+// of the username/password subnegotiation, see RFC 1929
+const SOCKS_AUTH_VERSION: u8 = 1;
+const SOCKS_AUTH_SUCCEEDED: u8 = 0;
+const SOCKS_AUTH_FAILED: u8 = 1;
+const SOCKS_CONNECT: u8 = 1;
+const SOCKS_IPV4: u8 = 1;
+const SOCKS_DOMAIN: u8 = 3;
+const SOCKS_IPV6: u8 = 4;
+const SOCKS_SUCCEEDED: u8 = 0;
+const SOCKS_GENERAL_FAILURE: u8 = 1;
+
+/// Local SOCKS5 proxy which tunnels every connection through a chain of proxies, in order, as proxychains does. For `--proxy` given multiple times, as `reqwest` only speaks to a single proxy.
+/// Each proxy of the chain is an HTTP proxy, which is asked to `CONNECT`, or a SOCKS5 one. Credentials are taken from the proxy's URL
+/// The local proxy requires a username and password, generated per run, so other local users can't tunnel through the chain
+#[derive(Debug, Clone)]
+pub struct ProxyChain {
+    proxies: Vec<Url>,
+    timeout: Duration,
+    username: String,
+    password: String,
+}
+
+impl ProxyChain {
+    /// `timeout` limits connecting to each proxy, and setting up the tunnel through it
+    pub fn new(proxies: Vec<Url>, timeout: Duration) -> Result<Self> {
+        for proxy in &proxies {
+            match proxy.scheme() {
+                "http" | "socks5" | "socks5h" => {}
+                "https" => {
+                    return Err(anyhow!(
+                        "`{}`: HTTPS proxies can't be chained, only HTTP and SOCKS5 ones",
+                        redacted(proxy)
+                    ))
+                }
+                scheme => {
+                    return Err(anyhow!(
+                        "`{}`: `{}` proxies can't be chained, only HTTP and SOCKS5 ones",
+                        redacted(proxy),
+                        scheme
+                    ))
+                }
+            }
+            if proxy.host_str().is_none() {
+                return Err(anyhow!("`{}`: the proxy has no host", redacted(proxy)));
+            }
+        }
+
+        Ok(Self {
+            proxies,
+            timeout,
+            username: random_credential()?,
+            password: random_credential()?,
+        })
+    }
+
+    /// Listen on a random local port in the background, for as long as the process runs. Returns the URL of the local proxy, with its credentials, which resolves host names through the chain
+    pub fn listen(self) -> Result<Url> {
+        let listener =
+            TcpListener::bind("127.0.0.1:0").context("Listening for the proxy chain failed")?;
+        let address = listener.local_addr()?;
+        let url = Url::parse(&format!(
+            "socks5h://{}:{}@{}",
+            self.username, self.password, address
+        ))?;
+
+        thread::Builder::new()
+            .name("proxy chain".to_string())
+            .spawn(move || {
+                for client in listener.incoming().flatten() {
+                    let chain = self.clone();
+                    thread::spawn(move || {
+                        if let Err(e) = chain.serve(client) {
+                            log_coalesced(
+                                Level::Warn,
+                                "proxy chain",
+                                format!("Tunnelling through the proxy chain failed: {:#}", e),
+                            );
+                        }
+                    });
+                }
+            })
+            .context("Starting the proxy chain failed")?;
+
+        Ok(url)
+    }
+
+    /// Take the target of a SOCKS5 client, and relay its traffic through the chain
+    fn serve(&self, mut client: TcpStream) -> Result<()> {
+        client.set_read_timeout(Some(self.timeout))?;
+
+        let mut greeting = [0; 2];
+        client.read_exact(&mut greeting)?;
+        let mut methods = vec![0; greeting[1] as usize];
+        client.read_exact(&mut methods)?;
+        if !methods.contains(&SOCKS_USERNAME_PASSWORD) {
+            client.write_all(&[SOCKS_VERSION, SOCKS_NO_ACCEPTABLE_METHODS])?;
+            return Err(anyhow!("The client didn't offer to authenticate"));
+        }
+        client.write_all(&[SOCKS_VERSION, SOCKS_USERNAME_PASSWORD])?;
+        self.authenticate(&mut client)?;
+
+        let mut request = [0; 4];
+        client.read_exact(&mut request)?;
+        if request[1] != SOCKS_CONNECT {
+            return Err(anyhow!("Only connecting is supported"));
+        }
+        let host = read_socks_address(&mut client, request[3])?;
+        let mut port = [0; 2];
+        client.read_exact(&mut port)?;
+        let port = u16::from_be_bytes(port);
+
+        let upstream = match self.connect(&host, port) {
+            Ok(upstream) => upstream,
+            Err(e) => {
+                let _ = client.write_all(&socks_reply(SOCKS_GENERAL_FAILURE));
+                return Err(e);
+            }
+        };
+        client.write_all(&socks_reply(SOCKS_SUCCEEDED))?;
+
+        // the client's own timeouts apply once the tunnel is set up
+        client.set_read_timeout(None)?;
+        upstream.set_read_timeout(None)?;
+        upstream.set_write_timeout(None)?;
+        relay(client, upstream)
+    }
+
+    /// Check the client's username and password against those of this run
+    fn authenticate(&self, client: &mut TcpStream) -> Result<()> {
+        let mut version = [0; 1];
+        client.read_exact(&mut version)?;
+        let username = read_socks_credential(client)?;
+        let password = read_socks_credential(client)?;
+
+        if username != self.username.as_bytes() || password != self.password.as_bytes() {
+            client.write_all(&[SOCKS_AUTH_VERSION, SOCKS_AUTH_FAILED])?;
+            return Err(anyhow!("The client gave wrong credentials"));
+        }
+        client.write_all(&[SOCKS_AUTH_VERSION, SOCKS_AUTH_SUCCEEDED])?;
+        Ok(())
+    }
+
+    /// Connection to `host:port`, tunnelled through every proxy of the chain
+    fn connect(&self, host: &str, port: u16) -> Result<TcpStream> {
+        let first_proxy = redacted(&self.proxies[0]);
+        let (first_host, first_port) = hop(&self.proxies[0]);
+        let socket_address = (first_host.as_str(), first_port)
+            .to_socket_addrs()
+            .context(format!("Resolving `{}` failed", first_proxy))?
+            .next()
+            .ok_or_else(|| anyhow!("`{}` did not resolve to any address", first_proxy))?;
+        let mut stream = TcpStream::connect_timeout(&socket_address, self.timeout)
+            .context(format!("Connecting to `{}` failed", first_proxy))?;
+        stream.set_read_timeout(Some(self.timeout))?;
+        stream.set_write_timeout(Some(self.timeout))?;
+
+        // each proxy is asked to connect to the next one, and the last one to the target
+        let next_hops = self.proxies[1..]
+            .iter()
+            .map(hop)
+            .chain([(host.to_string(), port)]);
+        for (proxy, (next_host, next_port)) in self.proxies.iter().zip(next_hops) {
+            match proxy.scheme() {
+                "http" => http_connect(&mut stream, proxy, &next_host, next_port),
+                _ => socks_connect(&mut stream, proxy, &next_host, next_port),
+            }
+            .context(format!(
+                "`{}` failed to connect to `{}:{}`",
+                redacted(proxy),
+                next_host,
+                next_port
+            ))?;
+        }
+
+        Ok(stream)
+    }
+}
+
+fn hop(proxy: &Url) -> (String, u16) {
+    (
+        proxy.host_str().unwrap_or_default().to_string(),
+        proxy.port_or_known_default().unwrap_or(1080),
+    )
+}
+
+/// Ask an HTTP proxy to tunnel to `host:port`
+fn http_connect(stream: &mut TcpStream, proxy: &Url, host: &str, port: u16) -> Result<()> {
+    let mut request = format!(
+        "CONNECT {host}:{port} HTTP/1.1\r\nHost: {host}:{port}\r\n",
+        host = host,
+        port = port
+    );
+    if !proxy.username().is_empty() {
+        let credentials = format!(
+            "{}:{}",
+            decoded(proxy.username()),
+            decoded(proxy.password().unwrap_or_default())
+        );
+        request.push_str(&format!(
+            "Proxy-Authorization: Basic {}\r\n",
+            base64::encode(credentials)
+        ));
+    }
+    request.push_str("\r\n");
+    stream.write_all(request.as_bytes())?;
+
+    // read byte by byte, so that nothing of the tunnelled traffic is consumed
+    let mut response = Vec::new();
+    let mut byte = [0; 1];
+    while !response.ends_with(b"\r\n\r\n") {
+        stream.read_exact(&mut byte)?;
+        response.push(byte[0]);
+    }
+    let status_line = String::from_utf8_lossy(&response);
+    let status_line = status_line.lines().next().unwrap_or_default();
+    match status_line.split_whitespace().nth(1) {
+        Some(status) if status.starts_with('2') => Ok(()),
+        _ => Err(anyhow!("The proxy answered `{}`", status_line)),
+    }
+}
+
+/// Ask a SOCKS5 proxy to tunnel to `host:port`. `socks5://` resolves the host locally, `socks5h://` leaves it to the proxy
+fn socks_connect(stream: &mut TcpStream, proxy: &Url, host: &str, port: u16) -> Result<()> {
+    let authenticate = !proxy.username().is_empty();
+    if authenticate {
+        stream.write_all(&[SOCKS_VERSION, 1, SOCKS_USERNAME_PASSWORD])?;
+    } else {
+        stream.write_all(&[SOCKS_VERSION, 1, SOCKS_NO_AUTHENTICATION])?;
+    }
+    let mut choice = [0; 2];
+    stream.read_exact(&mut choice)?;
+    match choice[1] {
+        SOCKS_NO_AUTHENTICATION => {}
+        SOCKS_USERNAME_PASSWORD if authenticate => {
+            let username = decoded(proxy.username());
+            let password = decoded(proxy.password().unwrap_or_default());
+            let mut request = vec![SOCKS_AUTH_VERSION];
+            push_socks_credential(&mut request, "username", &username)?;
+            push_socks_credential(&mut request, "password", &password)?;
+            stream.write_all(&request)?;
+
+            let mut status = [0; 2];
+            stream.read_exact(&mut status)?;
+            if status[1] != SOCKS_AUTH_SUCCEEDED {
+                return Err(anyhow!("The proxy refused the credentials"));
+            }
+        }
+        _ => return Err(anyhow!("The proxy requires unsupported authentication")),
+    }
+
+    let mut request = vec![SOCKS_VERSION, SOCKS_CONNECT, 0];
+    let resolved = if proxy.scheme() == "socks5" {
+        Some(
+            (host, port)
+                .to_socket_addrs()
+                .context(format!("Resolving `{}` failed", host))?
+                .next()
+                .ok_or_else(|| anyhow!("`{}` did not resolve to any address", host))?,
+        )
+    } else {
+        host.parse::<IpAddr>()
+            .ok()
+            .map(|ip| SocketAddr::new(ip, port))
+    };
+    match resolved.map(|socket_address| socket_address.ip()) {
+        Some(IpAddr::V4(ip)) => {
+            request.push(SOCKS_IPV4);
+            request.extend_from_slice(&ip.octets());
+        }
+        Some(IpAddr::V6(ip)) => {
+            request.push(SOCKS_IPV6);
+            request.extend_from_slice(&ip.octets());
+        }
+        None => {
+            request.push(SOCKS_DOMAIN);
+            request.push(host.len() as u8);
+            request.extend_from_slice(host.as_bytes());
+        }
+    }
+    request.extend_from_slice(&port.to_be_bytes());
+    stream.write_all(&request)?;
+
+    let mut reply = [0; 4];
+    stream.read_exact(&mut reply)?;
+    if reply[1] != SOCKS_SUCCEEDED {
+        return Err(anyhow!("The proxy refused with SOCKS reply {}", reply[1]));
+    }
+    // the address the proxy connected from is of no use
+    read_socks_address(stream, reply[3])?;
+    stream.read_exact(&mut [0; 2])?;
+    Ok(())
+}
+
+fn read_socks_address(stream: &mut TcpStream, address_type: u8) -> Result<String> {
+    Ok(match address_type {
+        SOCKS_IPV4 => {
+            let mut ip = [0; 4];
+            stream.read_exact(&mut ip)?;
+            IpAddr::from(ip).to_string()
+        }
+        SOCKS_IPV6 => {
+            let mut ip = [0; 16];
+            stream.read_exact(&mut ip)?;
+            IpAddr::from(ip).to_string()
+        }
+        SOCKS_DOMAIN => {
+            let mut length = [0; 1];
+            stream.read_exact(&mut length)?;
+            let mut domain = vec![0; length[0] as usize];
+            stream.read_exact(&mut domain)?;
+            String::from_utf8(domain).context("The domain isn't valid UTF-8")?
+        }
+        _ => return Err(anyhow!("Unknown SOCKS address type {}", address_type)),
+    })
+}
+
+// SOCKS5 prefixes them with their length in a single byte
+fn push_socks_credential(request: &mut Vec<u8>, name: &str, credential: &str) -> Result<()> {
+    let length = u8::try_from(credential.len()).map_err(|_| {
+        anyhow!(
+            "The {} is {} bytes long, while SOCKS5 allows 255 at most",
+            name,
+            credential.len()
+        )
+    })?;
+    request.push(length);
+    request.extend_from_slice(credential.as_bytes());
+    Ok(())
+}
+
+fn read_socks_credential(stream: &mut TcpStream) -> Result<Vec<u8>> {
+    let mut length = [0; 1];
+    stream.read_exact(&mut length)?;
+    let mut credential = vec![0; length[0] as usize];
+    stream.read_exact(&mut credential)?;
+    Ok(credential)
+}
+
+fn random_credential() -> Result<String> {
+    let mut bytes = [0; 16];
+    rand_bytes(&mut bytes).context("Generating credentials for the proxy chain failed")?;
+    Ok(hex::encode(bytes))
+}
+
+fn socks_reply(status: u8) -> [u8; 10] {
+    [SOCKS_VERSION, status, 0, SOCKS_IPV4, 0, 0, 0, 0, 0, 0]
+}
+
+// proxies are logged without their password
+fn redacted(proxy: &Url) -> Url {
+    let mut proxy = proxy.clone();
+    if proxy.password().is_some() {
+        let _ = proxy.set_password(Some("redacted"));
+    }
+    proxy
+}
+
+fn decoded(text: &str) -> String {
+    urlencoding::decode(text)
+        .map(|text| text.into_owned())
+        .unwrap_or_else(|_| text.to_string())
+}
+
+/// Copy traffic both ways, until either side closes the connection
+fn relay(client: TcpStream, upstream: TcpStream) -> Result<()> {
+    let (mut client_reader, mut upstream_writer) = (client.try_clone()?, upstream.try_clone()?);
+    let upload = thread::spawn(move || -> io::Result<()> {
+        io::copy(&mut client_reader, &mut upstream_writer)?;
+        upstream_writer.shutdown(Shutdown::Write)
+    });
+
+    let (mut upstream_reader, mut client_writer) = (upstream, client);
+    let download = io::copy(&mut upstream_reader, &mut client_writer);
+    let _ = client_writer.shutdown(Shutdown::Both);
+    let _ = upstream_reader.shutdown(Shutdown::Both);
+
+    let _ = upload.join();
+    download.map(|_| ()).map_err(Into::into)
+}
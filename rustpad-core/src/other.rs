@@ -0,0 +1,2 @@
+pub const RETRY_DELAY_MS: u64 = 100;
+pub const RETRY_MAX_ATTEMPTS: u64 = 3;
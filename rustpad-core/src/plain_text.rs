@@ -1,34 +1,37 @@
 use getset::Getters;
-use itertools::Itertools;
 
 use crate::{
     block::{
         block_size::{BlockSize, BlockSizeTrait},
+        padding_scheme::PaddingScheme,
         Block,
     },
     cypher_text::encode::AmountBlocksTrait,
 };
 
-/// PKCS7 padded plain text.
+/// Padded plain text.
 #[derive(Debug, Getters)]
-pub(super) struct PlainText {
-    #[getset(get = "pub(super)")]
+pub struct PlainText {
+    #[getset(get = "pub")]
     blocks: Vec<Block>,
 }
 
 impl PlainText {
-    pub(super) fn new(input_data: &str, block_size: &BlockSize) -> Self {
+    pub fn new(input_data: &str, block_size: &BlockSize, padding_scheme: &PaddingScheme) -> Self {
+        Self::from_bytes(input_data.as_bytes(), block_size, padding_scheme)
+    }
+
+    pub fn from_bytes(
+        input_data: &[u8],
+        block_size: &BlockSize,
+        padding_scheme: &PaddingScheme,
+    ) -> Self {
         let block_size = **block_size as usize;
-        let padding_size = block_size - input_data.len() % block_size;
 
-        let padded_blocks = input_data
-            .as_bytes()
-            .iter()
-            .cloned()
-            .pad_using(input_data.len() + padding_size, |_| padding_size as u8)
+        let padded_blocks = padding_scheme
+            .pad(input_data, block_size)
             .chunks(block_size)
-            .into_iter()
-            .map(|chunk| Block::from(&chunk.collect::<Vec<_>>()[..]))
+            .map(Block::from)
             .collect();
 
         Self {
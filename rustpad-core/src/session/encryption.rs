@@ -0,0 +1,100 @@
+use anyhow::{anyhow, Context, Result};
+use openssl::{
+    pkcs5::scrypt,
+    rand::rand_bytes,
+    symm::{decrypt_aead, encrypt_aead, Cipher},
+};
+
+// identifies an encrypted session file, and the version of its format
+const MAGIC: &[u8] = b"RUSTPAD-SESSION-1";
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 12;
+const TAG_LEN: usize = 16;
+const KEY_LEN: usize = 32;
+// scrypt cost parameters. Deriving the key takes a fraction of a second, which happens only once per run
+const SCRYPT_N: u64 = 1 << 15;
+const SCRYPT_R: u64 = 8;
+const SCRYPT_P: u64 = 1;
+const SCRYPT_MAX_MEM: u64 = 64 * 1024 * 1024;
+
+/// Passphrase-based encryption of session files, which hold recovered plain text. AES-256-GCM, with a key derived by scrypt.
+/// The key is derived once, as the session is saved after every solved byte. Every save uses a fresh nonce.
+pub struct SessionEncryption {
+    salt: [u8; SALT_LEN],
+    key: [u8; KEY_LEN],
+}
+
+impl SessionEncryption {
+    /// Encryption for a new session file, with a fresh salt
+    pub fn new(passphrase: &str) -> Result<Self> {
+        let mut salt = [0; SALT_LEN];
+        rand_bytes(&mut salt).context("Generating a salt failed")?;
+        Self::with_salt(passphrase, salt)
+    }
+
+    fn with_salt(passphrase: &str, salt: [u8; SALT_LEN]) -> Result<Self> {
+        let mut key = [0; KEY_LEN];
+        scrypt(
+            passphrase.as_bytes(),
+            &salt,
+            SCRYPT_N,
+            SCRYPT_R,
+            SCRYPT_P,
+            SCRYPT_MAX_MEM,
+            &mut key,
+        )
+        .context("Deriving the session key from the passphrase failed")?;
+
+        Ok(Self { salt, key })
+    }
+
+    pub fn is_encrypted(file_data: &[u8]) -> bool {
+        file_data.starts_with(MAGIC)
+    }
+
+    /// Decrypt the contents of an encrypted session file. Returns the encryption to continue saving the session with, as it uses the file's salt
+    pub fn decrypt(passphrase: &str, file_data: &[u8]) -> Result<(Self, Vec<u8>)> {
+        let header_len = MAGIC.len() + SALT_LEN + NONCE_LEN;
+        if file_data.len() < header_len + TAG_LEN {
+            return Err(anyhow!("Encrypted session file is truncated"));
+        }
+
+        let (salt, rest) = file_data[MAGIC.len()..].split_at(SALT_LEN);
+        let (nonce, rest) = rest.split_at(NONCE_LEN);
+        let (encrypted_data, tag) = rest.split_at(rest.len() - TAG_LEN);
+
+        let encryption = Self::with_salt(
+            passphrase,
+            salt.try_into().expect("Salt was split off at its length"),
+        )?;
+        let data = decrypt_aead(
+            Cipher::aes_256_gcm(),
+            &encryption.key,
+            Some(nonce),
+            MAGIC,
+            encrypted_data,
+            tag,
+        )
+        .map_err(|_| anyhow!("Decrypting the session file failed. Is the passphrase correct?"))?;
+
+        Ok((encryption, data))
+    }
+
+    pub fn encrypt(&self, data: &[u8]) -> Result<Vec<u8>> {
+        let mut nonce = [0; NONCE_LEN];
+        rand_bytes(&mut nonce).context("Generating a nonce failed")?;
+
+        let mut tag = [0; TAG_LEN];
+        let encrypted_data = encrypt_aead(
+            Cipher::aes_256_gcm(),
+            &self.key,
+            Some(&nonce),
+            MAGIC,
+            data,
+            &mut tag,
+        )
+        .context("Encrypting the session failed")?;
+
+        Ok([MAGIC, &self.salt, &nonce, &encrypted_data, &tag].concat())
+    }
+}
@@ -0,0 +1,150 @@
+use std::{
+    collections::HashMap,
+    fs::{self, File, OpenOptions},
+    io::{Read, Write},
+    path::{Path, PathBuf},
+    process,
+};
+
+mod encryption;
+
+use anyhow::{anyhow, Context, Result};
+use getset::Getters;
+use serde::{Deserialize, Serialize};
+
+use crate::block::Block;
+
+use self::encryption::SessionEncryption;
+
+/// Progress of an attack, saved after every solved byte, so an interrupted attack can be resumed. Unlike the cache, this also holds blocks which are only partially solved.
+pub struct Session {
+    // without a file, progress is only kept in memory, e.g. to export the attack's state
+    session_file_path: Option<PathBuf>,
+    // session files hold recovered plain text, so they can be encrypted with a passphrase
+    encryption: Option<SessionEncryption>,
+    data: HashMap<(Block, Block), BlockProgress>,
+}
+
+/// The bytes of a forged block which were solved so far. Solved bytes are at the end of the block.
+#[derive(Serialize, Deserialize, Clone, Debug, Getters)]
+pub struct BlockProgress {
+    #[getset(get = "pub")]
+    forged_block_solution: Block,
+    #[getset(get = "pub")]
+    bytes_answered: u8,
+}
+
+impl Session {
+    /// Open the session file at `path`. The file is created if it doesn't exist yet, in which case the attack starts from scratch.
+    /// With a passphrase, the session is saved encrypted. An unencrypted session file is encrypted on the next save.
+    pub fn load_from_file(path: &Path, passphrase: Option<&str>) -> Result<Self> {
+        let mut session_file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(path)
+            .context(format!("Session file `{}` failed to open", path.display()))?;
+
+        let mut file_data = vec![];
+        session_file
+            .read_to_end(&mut file_data)
+            .context("Session file read failure")?;
+
+        let (encryption, file_data) = match passphrase {
+            Some(passphrase) if SessionEncryption::is_encrypted(&file_data) => {
+                let (encryption, file_data) = SessionEncryption::decrypt(passphrase, &file_data)?;
+                (Some(encryption), file_data)
+            }
+            Some(passphrase) => (Some(SessionEncryption::new(passphrase)?), file_data),
+            None if SessionEncryption::is_encrypted(&file_data) => {
+                return Err(anyhow!(
+                    "Session file `{}` is encrypted. See `--session-passphrase` to decrypt it",
+                    path.display()
+                ))
+            }
+            None => (None, file_data),
+        };
+
+        let data = if file_data.is_empty() {
+            HashMap::new()
+        } else {
+            rmp_serde::from_read_ref(&file_data)
+                .context("Session file de-serialization failed: corrupted MessagePack data")?
+        };
+
+        Ok(Self {
+            session_file_path: Some(path.to_path_buf()),
+            encryption,
+            data,
+        })
+    }
+
+    pub fn in_memory() -> Self {
+        Self {
+            session_file_path: None,
+            encryption: None,
+            data: HashMap::new(),
+        }
+    }
+
+    pub fn insert(&mut self, key: (Block, Block), value: BlockProgress) -> Result<()> {
+        let _ = self.data.insert(key, value);
+        let session_file_path = match &self.session_file_path {
+            Some(session_file_path) => session_file_path,
+            None => return Ok(()),
+        };
+
+        let file_data =
+            rmp_serde::to_vec(&self.data).context("Session data serialization failed")?;
+        let file_data = match &self.encryption {
+            Some(encryption) => encryption.encrypt(&file_data)?,
+            None => file_data,
+        };
+
+        // same approach as the cache: write to a temporary file and rename it over the session, so a crash mid-write leaves the previous progress intact
+        let temp_file_path = session_file_path.with_extension(format!("{}.tmp", process::id()));
+        let written = File::create(&temp_file_path)
+            .context(format!(
+                "Temporary session file `{}` failed to open",
+                temp_file_path.display()
+            ))
+            .and_then(|mut temp_file| {
+                temp_file
+                    .write_all(&file_data)
+                    .context("Session could not be saved")?;
+                // the data must be on disk before the rename makes it the session
+                temp_file
+                    .sync_all()
+                    .context("Session could not be flushed to disk")
+            })
+            .and_then(|_| {
+                fs::rename(&temp_file_path, session_file_path).context(format!(
+                    "Session file `{}` could not be replaced",
+                    session_file_path.display()
+                ))
+            });
+        if written.is_err() {
+            let _ = fs::remove_file(&temp_file_path);
+        }
+
+        written
+    }
+
+    pub fn get(&self, key: &(Block, Block)) -> Option<&BlockProgress> {
+        self.data.get(key)
+    }
+}
+
+impl BlockProgress {
+    pub fn new(forged_block_solution: Block, bytes_answered: u8) -> Self {
+        Self {
+            forged_block_solution,
+            bytes_answered,
+        }
+    }
+
+    pub fn is_solved(&self) -> bool {
+        self.bytes_answered as usize == self.forged_block_solution.len()
+    }
+}
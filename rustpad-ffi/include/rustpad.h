@@ -0,0 +1,51 @@
+/* C bindings to the attack engine of rustpad. Link against `librustpad_ffi`. */
+#ifndef RUSTPAD_H
+#define RUSTPAD_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* Answers of the oracle callback */
+#define RUSTPAD_PADDING_VALID 1
+#define RUSTPAD_PADDING_INVALID 0
+/* questioning the oracle failed. The question is retried */
+#define RUSTPAD_ORACLE_FAILED -1
+/* stop the attack */
+#define RUSTPAD_ORACLE_ABORT -2
+
+/* Asks whether `cypher_text` has valid padding. Returns one of the answers above */
+typedef int32_t (*rustpad_oracle_callback)(void *user_data, const uint8_t *cypher_text,
+                                           size_t cypher_text_len);
+/* Told that `amount` more bytes of the block at `block_idx` are solved. The IV is block 0 */
+typedef void (*rustpad_progress_callback)(void *user_data, size_t block_idx, size_t amount);
+
+/* Decrypt `cypher_text`, which starts with its IV, by questioning `oracle`. `progress` may be NULL.
+ * The callbacks are called from `threads` threads at once, or from as many as there are CPUs if 0. `user_data` is handed to them as is.
+ * On success, returns 0 and stores the plain text, padding included, in `plain_text`. Free it with `rustpad_free`.
+ * Otherwise returns the exit code of the `rustpad` binary for the failure, see `rustpad_last_error`.
+ *
+ * The caller must guarantee that:
+ * - `cypher_text` points to `cypher_text_len` readable bytes, and `plain_text` and `plain_text_len` are writable
+ * - the callbacks are safe to call with `user_data` from `threads` threads at once, until `rustpad_decrypt` returns
+ * - the callbacks return, rather than unwinding (e.g. a C++ exception) or jumping out of them
+ * - the callbacks don't keep `cypher_text`, which is only valid during the call */
+int32_t rustpad_decrypt(const uint8_t *cypher_text, size_t cypher_text_len, uint8_t block_size,
+                        size_t threads, rustpad_oracle_callback oracle,
+                        rustpad_progress_callback progress, void *user_data, uint8_t **plain_text,
+                        size_t *plain_text_len);
+
+/* Free a plain text returned by `rustpad_decrypt`, with the length it was returned with. Each plain text is freed once. NULL is ignored */
+void rustpad_free(uint8_t *plain_text, size_t plain_text_len);
+
+/* Description of the last failure of `rustpad_decrypt` on this thread, or NULL. Valid until the next call on this thread */
+const char *rustpad_last_error(void);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif
@@ -0,0 +1,48 @@
+/* Oracle plugins of rustpad. Build a shared library which exports the functions below, and question it with `rustpad plugin --oracle <path or name>`.
+ *
+ * The library is loaded into rustpad's process, which trusts it to keep this contract:
+ * - its initialisers, which run when it's loaded, don't interfere with rustpad
+ * - the functions have exactly the signatures below, and return, rather than unwinding (e.g. a C++ exception) or jumping out of them
+ * - pointers handed to the plugin are only valid during the call they're handed to. The plugin doesn't write through them
+ * - `rustpad_plugin_ask` is safe to call with the same `state` from multiple threads at once
+ * - `state` stays valid until `rustpad_plugin_close`, which is called once, after every `rustpad_plugin_ask` returned */
+#ifndef RUSTPAD_PLUGIN_H
+#define RUSTPAD_PLUGIN_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* Version of the plugin ABI which this header describes. Return it from `rustpad_plugin_abi_version` */
+#define RUSTPAD_PLUGIN_ABI_VERSION 1
+
+/* Answers of `rustpad_plugin_ask` */
+#define RUSTPAD_PLUGIN_PADDING_VALID 1
+#define RUSTPAD_PLUGIN_PADDING_INVALID 0
+/* questioning the oracle failed. The question is retried */
+#define RUSTPAD_PLUGIN_ORACLE_FAILED -1
+/* stop the attack */
+#define RUSTPAD_PLUGIN_ORACLE_ABORT -2
+
+/* Version of the plugin ABI which the plugin implements. Plugins of another version are refused */
+uint32_t rustpad_plugin_abi_version(void);
+
+/* Set up the plugin, e.g. connect to the transport. `config` is the value of `--plugin-config`, or an empty string.
+ * Returns the plugin's state, which is handed to the other functions, or NULL on failure */
+void *rustpad_plugin_open(const char *config);
+
+/* Asks whether `cypher_text` has valid padding. It's encoded as given by `--encoding`, and not NUL-terminated.
+ * Called from as many threads at once as `--threads`. Returns one of the answers above */
+int32_t rustpad_plugin_ask(void *state, const uint8_t *cypher_text, size_t cypher_text_len);
+
+/* Tear down the plugin once the attack is over. `state` isn't used afterwards */
+void rustpad_plugin_close(void *state);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif
@@ -0,0 +1,207 @@
+//! C bindings to the attack engine of rustpad. The oracle is a callback of the embedding program, so tools written in C, C++, Go and the like drive the attack without shelling out to the binary.
+//!
+//! See `include/rustpad.h` for the C declarations.
+
+use std::{
+    cell::RefCell,
+    ffi::{c_char, c_void, CString},
+    panic::{self, AssertUnwindSafe},
+    path::PathBuf,
+    ptr, slice,
+};
+
+use anyhow::{anyhow, Context, Result};
+use rustpad_core::{
+    config::thread_delay::ThreadDelay,
+    cypher_text::encode::Encode,
+    divination::progress_observer::ProgressObserver,
+    embedding,
+    error::{self, RustpadError, GENERIC_EXIT_CODE},
+    oracle::{oracle_location::OracleLocation, Oracle},
+};
+
+/// The cypher text has valid padding
+pub const RUSTPAD_PADDING_VALID: i32 = 1;
+/// The cypher text has invalid padding
+pub const RUSTPAD_PADDING_INVALID: i32 = 0;
+/// Questioning the oracle failed. The question is retried
+pub const RUSTPAD_ORACLE_FAILED: i32 = -1;
+/// Stop the attack
+pub const RUSTPAD_ORACLE_ABORT: i32 = -2;
+
+/// Asks whether `cypher_text` has valid padding. Returns one of the `RUSTPAD_PADDING_*`/`RUSTPAD_ORACLE_*` codes
+pub type OracleCallback =
+    extern "C" fn(user_data: *mut c_void, cypher_text: *const u8, cypher_text_len: usize) -> i32;
+/// Told that `amount` more bytes of the block at `block_idx` are solved. The IV is block 0
+pub type ProgressCallback = extern "C" fn(user_data: *mut c_void, block_idx: usize, amount: usize);
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+// the caller vouches that the callbacks may be called with it from multiple threads, see `rustpad_decrypt`
+#[derive(Clone, Copy)]
+struct UserData(*mut c_void);
+unsafe impl Send for UserData {}
+unsafe impl Sync for UserData {}
+
+/// Oracle which questions the embedding program
+struct CallbackOracle {
+    callback: OracleCallback,
+    user_data: UserData,
+    thread_delay: ThreadDelay,
+}
+
+impl Oracle for CallbackOracle {
+    fn visit(
+        _oracle_location: &OracleLocation,
+        _oracle_config: &rustpad_core::config::SubConfig,
+    ) -> Result<Self> {
+        Err(anyhow!(
+            "The callback oracle is given by the embedding program, it can't be visited"
+        ))
+    }
+
+    fn ask_validation<'a>(&self, cypher_text: &'a impl Encode<'a>) -> Result<bool> {
+        // cypher texts are hex encoded, see `embedding::decrypt`
+        let raw_bytes =
+            hex::decode(cypher_text.encode()).context("Forged cypher text isn't hex encoded")?;
+
+        match (self.callback)(self.user_data.0, raw_bytes.as_ptr(), raw_bytes.len()) {
+            RUSTPAD_PADDING_VALID => Ok(true),
+            RUSTPAD_PADDING_INVALID => Ok(false),
+            RUSTPAD_ORACLE_ABORT => Err(RustpadError::OracleAborted {
+                reason: "the oracle callback aborted".to_string(),
+            }
+            .into()),
+            code => Err(anyhow!("The oracle callback failed with code {}", code)),
+        }
+    }
+
+    fn location(&self) -> OracleLocation {
+        // not a script, but the closest kind of oracle: one run by the embedding program
+        OracleLocation::Script(PathBuf::from("<callback>"))
+    }
+    fn thread_delay(&self) -> &ThreadDelay {
+        &self.thread_delay
+    }
+}
+
+struct CallbackObserver {
+    callback: Option<ProgressCallback>,
+    user_data: UserData,
+}
+
+impl ProgressObserver for CallbackObserver {
+    fn bytes_solved(&self, block_idx: usize, amount: usize) {
+        if let Some(callback) = self.callback {
+            callback(self.user_data.0, block_idx, amount);
+        }
+    }
+}
+
+/// Decrypt `cypher_text`, which starts with its IV, by questioning `oracle`. `progress` may be `NULL`.
+/// The callbacks are called from `threads` threads at once, or from as many as there are CPUs if 0. `user_data` is handed to them as is.
+/// On success, returns 0 and stores the plain text, padding included, in `plain_text`. Free it with `rustpad_free`. Otherwise returns the exit code of the `rustpad` binary for the failure, see `rustpad_last_error`.
+///
+/// # Safety
+/// `cypher_text` must point to `cypher_text_len` readable bytes, and `plain_text` and `plain_text_len` must be writable. The callbacks must be safe to call with `user_data` from `threads` threads at once, and must return rather than unwind. They may not keep the cypher text they're handed.
+#[no_mangle]
+pub unsafe extern "C" fn rustpad_decrypt(
+    cypher_text: *const u8,
+    cypher_text_len: usize,
+    block_size: u8,
+    threads: usize,
+    oracle: OracleCallback,
+    progress: Option<ProgressCallback>,
+    user_data: *mut c_void,
+    plain_text: *mut *mut u8,
+    plain_text_len: *mut usize,
+) -> i32 {
+    if cypher_text.is_null() || plain_text.is_null() || plain_text_len.is_null() {
+        set_last_error(&anyhow!("A required pointer is NULL"));
+        return GENERIC_EXIT_CODE;
+    }
+    let cypher_text = slice::from_raw_parts(cypher_text, cypher_text_len);
+    let user_data = UserData(user_data);
+
+    let decrypted = panic::catch_unwind(AssertUnwindSafe(|| {
+        decrypt(
+            cypher_text,
+            block_size,
+            threads,
+            oracle,
+            progress,
+            user_data,
+        )
+    }))
+    .unwrap_or_else(|_| Err(anyhow!("The attack panicked")));
+
+    match decrypted {
+        Ok(decrypted) => {
+            let decrypted = decrypted.into_boxed_slice();
+            *plain_text_len = decrypted.len();
+            *plain_text = Box::into_raw(decrypted) as *mut u8;
+            0
+        }
+        Err(e) => {
+            *plain_text = ptr::null_mut();
+            *plain_text_len = 0;
+            let exit_code = error::exit_code(&e);
+            set_last_error(&e);
+            exit_code
+        }
+    }
+}
+
+/// Free a plain text returned by `rustpad_decrypt`
+///
+/// # Safety
+/// `plain_text` and `plain_text_len` must be as returned by `rustpad_decrypt`, and not be freed already. `NULL` is ignored.
+#[no_mangle]
+pub unsafe extern "C" fn rustpad_free(plain_text: *mut u8, plain_text_len: usize) {
+    if !plain_text.is_null() {
+        drop(Box::from_raw(ptr::slice_from_raw_parts_mut(
+            plain_text,
+            plain_text_len,
+        )));
+    }
+}
+
+/// Description of the last failure of `rustpad_decrypt` on this thread, or `NULL`. Valid until the next call on this thread
+#[no_mangle]
+pub extern "C" fn rustpad_last_error() -> *const c_char {
+    LAST_ERROR.with(|last_error| {
+        last_error
+            .borrow()
+            .as_ref()
+            .map_or(ptr::null(), |last_error| last_error.as_ptr())
+    })
+}
+
+fn decrypt(
+    cypher_text: &[u8],
+    block_size: u8,
+    threads: usize,
+    oracle: OracleCallback,
+    progress: Option<ProgressCallback>,
+    user_data: UserData,
+) -> Result<Vec<u8>> {
+    let oracle = CallbackOracle {
+        callback: oracle,
+        user_data,
+        thread_delay: ThreadDelay::default(),
+    };
+    let observer = CallbackObserver {
+        callback: progress,
+        user_data,
+    };
+
+    embedding::decrypt(cypher_text, block_size, threads, &oracle, observer)
+}
+
+fn set_last_error(error: &anyhow::Error) {
+    let description =
+        CString::new(format!("{:#}", error).replace('\0', "")).expect("NUL bytes were removed");
+    LAST_ERROR.with(|last_error| *last_error.borrow_mut() = Some(description));
+}
@@ -0,0 +1,30 @@
+from typing import Callable, Optional
+
+class Error(Exception):
+    """The attack failed, e.g. as the oracle kept failing or no byte value gave valid padding"""
+
+class Abort(Exception):
+    """Raised by the oracle to stop the attack. Any other exception of the oracle is retried"""
+
+def decrypt(
+    cypher_text: bytes,
+    oracle: Callable[[bytes], bool],
+    block_size: int = 16,
+    threads: int = 0,
+    progress: Optional[Callable[[int, int], None]] = None,
+) -> bytes:
+    """Decrypt `cypher_text`, which starts with its IV, by calling `oracle` with forged cypher texts. Returns the plain text, padding included.
+
+    `oracle` is called from `threads` threads at once, or from as many as there are CPUs if 0. `progress`, if given, is told `(block_idx, amount)` as bytes are solved, the IV being block 0."""
+
+def encrypt(
+    plain_text: bytes,
+    oracle: Callable[[bytes], bool],
+    block_size: int = 16,
+    cypher_text: Optional[bytes] = None,
+    threads: int = 0,
+    progress: Optional[Callable[[int, int], None]] = None,
+) -> bytes:
+    """Forge a cypher text, starting with its IV, which decrypts to `plain_text`, padded with PKCS#7. It ends with the last block of `cypher_text`, any cypher text of the oracle, or with a block of zeroes if not given.
+
+    `oracle`, `threads` and `progress` are as for `decrypt`."""
@@ -0,0 +1,174 @@
+//! Python bindings to the attack engine of rustpad. The oracle is a Python callable, so scripts drive the attack, with the engine's retries and parallelism, without shelling out to the binary.
+//!
+//! See `rustpad.pyi` for the Python signatures.
+
+use std::{path::PathBuf, sync::Arc};
+
+use anyhow::{anyhow, Context, Result};
+use pyo3::{create_exception, exceptions::PyException, prelude::*, types::PyBytes};
+use rustpad_core::{
+    config::thread_delay::ThreadDelay,
+    cypher_text::encode::Encode,
+    divination::progress_observer::ProgressObserver,
+    embedding,
+    error::RustpadError,
+    oracle::{oracle_location::OracleLocation, Oracle},
+};
+
+create_exception!(
+    rustpad,
+    Error,
+    PyException,
+    "The attack failed, e.g. as the oracle kept failing or no byte value gave valid padding"
+);
+create_exception!(
+    rustpad,
+    Abort,
+    PyException,
+    "Raised by the oracle to stop the attack. Any other exception of the oracle is retried"
+);
+
+/// Oracle which calls a Python callable with the cypher text, as `bytes`. Its result's truthiness tells whether the padding is valid
+struct CallableOracle {
+    callable: PyObject,
+    thread_delay: ThreadDelay,
+}
+
+impl Oracle for CallableOracle {
+    fn visit(
+        _oracle_location: &OracleLocation,
+        _oracle_config: &rustpad_core::config::SubConfig,
+    ) -> Result<Self> {
+        Err(anyhow!(
+            "The callable oracle is given by the Python program, it can't be visited"
+        ))
+    }
+
+    fn ask_validation<'a>(&self, cypher_text: &'a impl Encode<'a>) -> Result<bool> {
+        // cypher texts are hex encoded, see `embedding::decrypt`
+        let raw_bytes =
+            hex::decode(cypher_text.encode()).context("Forged cypher text isn't hex encoded")?;
+
+        Python::with_gil(|py| {
+            self.callable
+                .call1(py, (PyBytes::new(py, &raw_bytes),))
+                .and_then(|verdict| verdict.is_truthy(py))
+                .map_err(|e| {
+                    if e.is_instance_of::<Abort>(py) {
+                        RustpadError::OracleAborted {
+                            reason: e.value(py).to_string(),
+                        }
+                        .into()
+                    } else {
+                        anyhow!("The oracle raised {}", e)
+                    }
+                })
+        })
+    }
+
+    fn location(&self) -> OracleLocation {
+        // not a script, but the closest kind of oracle: one run by the embedding program
+        OracleLocation::Script(PathBuf::from("<callable>"))
+    }
+    fn thread_delay(&self) -> &ThreadDelay {
+        &self.thread_delay
+    }
+}
+
+#[derive(Clone)]
+struct CallableObserver {
+    callable: Option<Arc<PyObject>>,
+}
+
+impl ProgressObserver for CallableObserver {
+    fn bytes_solved(&self, block_idx: usize, amount: usize) {
+        if let Some(callable) = &self.callable {
+            Python::with_gil(|py| {
+                // progress is only informative, so a failing callable doesn't stop the attack
+                if let Err(e) = callable.call1(py, (block_idx, amount)) {
+                    e.write_unraisable(py, None);
+                }
+            });
+        }
+    }
+}
+
+/// Decrypt `cypher_text`, which starts with its IV, by calling `oracle` with forged cypher texts. Returns the plain text, padding included.
+/// `oracle` is called from `threads` threads at once, or from as many as there are CPUs if 0. `progress`, if given, is told `(block_idx, amount)` as bytes are solved, the IV being block 0.
+#[pyfunction]
+#[pyo3(signature = (cypher_text, oracle, block_size = 16, threads = 0, progress = None))]
+fn decrypt(
+    py: Python<'_>,
+    cypher_text: &[u8],
+    oracle: PyObject,
+    block_size: u8,
+    threads: usize,
+    progress: Option<PyObject>,
+) -> PyResult<Py<PyBytes>> {
+    let oracle = CallableOracle {
+        callable: oracle,
+        thread_delay: ThreadDelay::default(),
+    };
+    let observer = CallableObserver {
+        callable: progress.map(Arc::new),
+    };
+
+    // the oracle's calls take the GIL themselves
+    let plain_text = py
+        .allow_threads(|| embedding::decrypt(cypher_text, block_size, threads, &oracle, observer))
+        .map_err(|e| to_py_err(&e))?;
+    Ok(PyBytes::new(py, &plain_text).unbind())
+}
+
+/// Forge a cypher text, starting with its IV, which decrypts to `plain_text`, padded with PKCS#7. It ends with the last block of `cypher_text`, any cypher text of the oracle, or with a block of zeroes if not given.
+/// `oracle`, `threads` and `progress` are as for `decrypt`.
+#[pyfunction]
+#[pyo3(signature = (plain_text, oracle, block_size = 16, cypher_text = None, threads = 0, progress = None))]
+fn encrypt(
+    py: Python<'_>,
+    plain_text: &[u8],
+    oracle: PyObject,
+    block_size: u8,
+    cypher_text: Option<&[u8]>,
+    threads: usize,
+    progress: Option<PyObject>,
+) -> PyResult<Py<PyBytes>> {
+    let oracle = CallableOracle {
+        callable: oracle,
+        thread_delay: ThreadDelay::default(),
+    };
+    let observer = CallableObserver {
+        callable: progress.map(Arc::new),
+    };
+
+    let forged_cypher_text = py
+        .allow_threads(|| {
+            embedding::encrypt(
+                plain_text,
+                cypher_text,
+                block_size,
+                threads,
+                &oracle,
+                observer,
+            )
+        })
+        .map_err(|e| to_py_err(&e))?;
+    Ok(PyBytes::new(py, &forged_cypher_text).unbind())
+}
+
+fn to_py_err(error: &anyhow::Error) -> PyErr {
+    let description = format!("{:#}", error);
+    match RustpadError::find(error) {
+        Some(RustpadError::OracleAborted { .. }) => Abort::new_err(description),
+        _ => Error::new_err(description),
+    }
+}
+
+#[pymodule]
+fn rustpad(module: &Bound<'_, PyModule>) -> PyResult<()> {
+    module.add_function(wrap_pyfunction!(decrypt, module)?)?;
+    module.add_function(wrap_pyfunction!(encrypt, module)?)?;
+    module.add("Error", module.py().get_type::<Error>())?;
+    module.add("Abort", module.py().get_type::<Abort>())?;
+    Ok(())
+}
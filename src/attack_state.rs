@@ -0,0 +1,221 @@
+use std::{fs, path::Path};
+
+use anyhow::{anyhow, Context, Result};
+use log::info;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    block::{block_size::BlockSizeTrait, padding_scheme::PaddingScheme, Block},
+    cypher_text::{encode::AmountBlocksTrait, forged_cypher_text::ForgedCypherText},
+    divination::{candidate_values, guess_strategy::GuessStrategy},
+    logging::LOG_TARGET,
+    session::{BlockProgress, Session},
+};
+
+// bumped on incompatible changes of the schema
+const STATE_VERSION: u8 = 1;
+
+/// Solving state of every block, for external solvers. Bytes are solved from the end of a block to its start, so the known bytes of a block are always at its end. Unknown bytes are `null`.
+/// On import, only `intermediate` is used. The other per-block fields are derived from it, for convenience.
+#[derive(Serialize, Deserialize, Debug)]
+pub(super) struct AttackState {
+    version: u8,
+    block_size: u8,
+    // `forged` is kept as if the oracle validated PKCS7 padding, whichever scheme it actually validates
+    padding: String,
+    blocks: Vec<BlockState>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+struct BlockState {
+    // index of the block in the cypher text, the IV being 0
+    block: usize,
+    // hex encoded, as are the blocks in the intermediates export
+    preceding_cypher_text: String,
+    cypher_text: String,
+    bytes_locked: u8,
+    // value of the preceding block with which a locked byte gave valid padding, for a padding of the byte's distance to the end of the block
+    forged: Vec<Option<u8>>,
+    intermediate: Vec<Option<u8>>,
+    plain_text: Vec<Option<u8>>,
+    // the byte being solved, `null` if the block is solved
+    current_byte: Option<u8>,
+    // values of `current_byte`, as sent to the oracle, in the order they're tried
+    remaining_candidates: Vec<u8>,
+}
+
+impl AttackState {
+    pub(super) fn new(
+        forged_cypher_texts: &[ForgedCypherText],
+        session: &Session,
+        padding_scheme: PaddingScheme,
+        guess_strategy: &dyn GuessStrategy,
+    ) -> Self {
+        let block_size = forged_cypher_texts
+            .first()
+            .map(|forged_cypher_text| *forged_cypher_text.block_size())
+            .unwrap_or_default();
+
+        Self {
+            version: STATE_VERSION,
+            block_size,
+            padding: padding_scheme.to_string(),
+            blocks: forged_cypher_texts
+                .iter()
+                .map(|forged_cypher_text| {
+                    let key = forged_cypher_text.as_cache_key();
+                    let progress = session.get(&key).cloned().unwrap_or_else(|| {
+                        BlockProgress::new(Block::new(&forged_cypher_text.block_size()), 0)
+                    });
+                    BlockState::new(forged_cypher_text, &key, &progress, guess_strategy)
+                })
+                .collect(),
+        }
+    }
+
+    pub(super) fn export(&self, path: &Path) -> Result<()> {
+        let json = serde_json::to_string_pretty(self).context("State serialization failed")?;
+        fs::write(path, json).context(format!(
+            "Writing the attack's state to `{}` failed",
+            path.display()
+        ))?;
+        info!(
+            target: LOG_TARGET,
+            "Wrote the attack's state to `{}`",
+            path.display()
+        );
+        Ok(())
+    }
+
+    pub(super) fn load(path: &Path) -> Result<Self> {
+        let json = fs::read_to_string(path)
+            .context(format!("State file `{}` failed to open", path.display()))?;
+        let state: Self = serde_json::from_str(&json).context(format!(
+            "State file `{}` is not a valid export of `--export-state`",
+            path.display()
+        ))?;
+        if state.version != STATE_VERSION {
+            return Err(anyhow!(
+                "State file `{}` is of version {}, but only version {} is supported",
+                path.display(),
+                state.version,
+                STATE_VERSION
+            ));
+        }
+
+        Ok(state)
+    }
+
+    /// Continue the attack from the intermediates of the state, e.g. after an external solver found some
+    pub(super) fn import_into(&self, session: &mut Session) -> Result<()> {
+        let mut imported = 0;
+        for block in &self.blocks {
+            let decode = |hex_data: &str| -> Result<Block> {
+                let data = hex::decode(hex_data).context(format!(
+                    "Block {} of the state holds invalid hex: `{}`",
+                    block.block, hex_data
+                ))?;
+                Ok(Block::from(&data[..]))
+            };
+            if block.intermediate.len() != self.block_size as usize {
+                return Err(anyhow!(
+                    "Block {} of the state has an intermediate of {} bytes, while its block size is {}",
+                    block.block,
+                    block.intermediate.len(),
+                    self.block_size
+                ));
+            }
+
+            // only the known bytes at the end of the block can be continued from
+            let bytes_locked = block
+                .intermediate
+                .iter()
+                .rev()
+                .take_while(|byte| byte.is_some())
+                .count();
+            if bytes_locked == 0 {
+                continue;
+            }
+
+            let mut forged_block_solution = Block::new(&self.block_size.into());
+            for idx in self.block_size as usize - bytes_locked..self.block_size as usize {
+                let intermediate_byte = block.intermediate[idx]
+                    .expect("Locked bytes were counted as the known bytes at the end");
+                forged_block_solution[idx] = intermediate_byte ^ (self.block_size - idx as u8);
+            }
+
+            session.insert(
+                (
+                    decode(&block.preceding_cypher_text)?,
+                    decode(&block.cypher_text)?,
+                ),
+                BlockProgress::new(forged_block_solution, bytes_locked as u8),
+            )?;
+            imported += 1;
+        }
+
+        info!(
+            target: LOG_TARGET,
+            "Imported the state of {} block(s)",
+            imported
+        );
+        Ok(())
+    }
+}
+
+impl BlockState {
+    fn new(
+        forged_cypher_text: &ForgedCypherText,
+        (preceding_block, block): &(Block, Block),
+        progress: &BlockProgress,
+        guess_strategy: &dyn GuessStrategy,
+    ) -> Self {
+        let block_size = *forged_cypher_text.block_size();
+        let bytes_locked = *progress.bytes_answered();
+        let is_locked = |idx: usize| idx >= (block_size - bytes_locked) as usize;
+
+        let forged: Vec<Option<u8>> = progress
+            .forged_block_solution()
+            .iter()
+            .enumerate()
+            .map(|(idx, byte)| is_locked(idx).then_some(*byte))
+            .collect();
+        let intermediate: Vec<Option<u8>> = forged
+            .iter()
+            .enumerate()
+            .map(|(idx, byte)| byte.map(|byte| byte ^ (block_size - idx as u8)))
+            .collect();
+        let plain_text = intermediate
+            .iter()
+            .zip(preceding_block.iter())
+            .map(|(intermediate_byte, preceding_byte)| {
+                intermediate_byte.map(|intermediate_byte| intermediate_byte ^ preceding_byte)
+            })
+            .collect();
+
+        let (current_byte, remaining_candidates) = if progress.is_solved() {
+            (None, vec![])
+        } else {
+            let resumed = forged_cypher_text.to_resumed(progress);
+            (
+                Some(*resumed.current_byte_idx()),
+                candidate_values(
+                    &resumed,
+                    guess_strategy.plain_text_order(resumed.plain_text_offset()),
+                ),
+            )
+        };
+
+        Self {
+            block: forged_cypher_text.amount_blocks() - 1,
+            preceding_cypher_text: preceding_block.to_hex(),
+            cypher_text: block.to_hex(),
+            bytes_locked,
+            forged,
+            intermediate,
+            plain_text,
+            current_byte,
+            remaining_candidates,
+        }
+    }
+}
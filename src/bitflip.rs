@@ -0,0 +1,91 @@
+use anyhow::{anyhow, Result};
+
+use crate::{
+    cli::BitflipCli,
+    cypher_text::{
+        encode::{AmountBlocksTrait, Encode},
+        CypherText,
+    },
+};
+
+/// Change known plain text into the desired value, by flipping the same bits in the preceding cypher text block. The modified cypher text is printed.
+pub(super) fn bitflip(bitflip_cli: BitflipCli) -> Result<()> {
+    let original = bitflip_cli.original();
+    let desired = bitflip_cli.desired();
+    if original.len() != desired.len() {
+        return Err(anyhow!(
+            "The known plain text is {} bytes long, but the desired plain text is {}. They must be equally long",
+            original.len(),
+            desired.len()
+        ));
+    }
+
+    let cypher_text = CypherText::parse(
+        bitflip_cli.cypher_text(),
+        bitflip_cli.block_size(),
+        *bitflip_cli.no_iv(),
+        bitflip_cli.encoding(),
+        *bitflip_cli.no_url_encode(),
+        *bitflip_cli.base64_padding(),
+    )?;
+    let block_size = **bitflip_cli.block_size() as usize;
+    let offset = *bitflip_cli.offset();
+
+    // the 1st block is the IV
+    let plain_text_len = (cypher_text.amount_blocks() - 1) * block_size;
+    if offset + original.len() > plain_text_len {
+        return Err(anyhow!(
+            "The known plain text ends at byte {}, beyond the plain text's {} bytes",
+            offset + original.len(),
+            plain_text_len
+        ));
+    }
+
+    let mut blocks = cypher_text.blocks().to_vec();
+    let mut flipped_blocks = Vec::new();
+    for (idx, (original_byte, desired_byte)) in original.iter().zip(desired.iter()).enumerate() {
+        let plain_text_idx = offset + idx;
+        // plain text block `n` is changed through cypher text block `n - 1`. As the IV is block 0, the plain text's block idx is the one to flip
+        let block_idx = plain_text_idx / block_size;
+        blocks[block_idx][plain_text_idx % block_size] ^= original_byte ^ desired_byte;
+
+        if original_byte != desired_byte && !flipped_blocks.contains(&block_idx) {
+            flipped_blocks.push(block_idx);
+        }
+    }
+
+    if *bitflip_cli.no_iv() && flipped_blocks.contains(&0) {
+        return Err(anyhow!(
+            "The 1st block of plain text can only be changed through the IV, but the cypher text has none"
+        ));
+    }
+    // the plain text of a flipped block, other than the IV, is garbled
+    let first_changed_block = offset / block_size + 1;
+    if let Some(garbled_block) = flipped_blocks
+        .iter()
+        .find(|block_idx| **block_idx != 0 && **block_idx >= first_changed_block)
+    {
+        return Err(anyhow!(
+            "Changing the plain text of block {} garbles that of block {}, which is part of the change. Changes spanning multiple blocks are only possible if the 1st one is changed through the IV",
+            garbled_block + 1,
+            garbled_block
+        ));
+    }
+    for garbled_block in flipped_blocks.iter().filter(|block_idx| **block_idx != 0) {
+        eprintln!(
+            "Warning: block {} of the plain text will be garbled",
+            garbled_block
+        );
+    }
+
+    // without IV, an empty IV block was prepended during parsing
+    let first_block = if *bitflip_cli.no_iv() { 1 } else { 0 };
+    let flipped_cypher_text = CypherText::from_iter(
+        blocks[first_block..].iter(),
+        *cypher_text.url_encoded(),
+        *cypher_text.used_encoding(),
+    );
+    println!("{}", flipped_cypher_text.encode());
+
+    Ok(())
+}
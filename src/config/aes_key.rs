@@ -0,0 +1,32 @@
+use std::{ops::Deref, str::FromStr};
+
+use anyhow::{anyhow, Context, Result};
+
+/// Hex encoded AES key, of 128, 192, or 256 bits.
+#[derive(Debug, Clone)]
+pub(crate) struct AesKey(Vec<u8>);
+
+impl FromStr for AesKey {
+    type Err = anyhow::Error;
+
+    fn from_str(input: &str) -> Result<Self> {
+        let key = hex::decode(input).context(format!("`{}` is not valid hex", input))?;
+
+        match key.len() {
+            16 | 24 | 32 => Ok(Self(key)),
+            length => Err(anyhow!(
+                "AES keys are 16, 24, or 32 bytes long, but `{}` is {} bytes long",
+                input,
+                length
+            )),
+        }
+    }
+}
+
+impl Deref for AesKey {
+    type Target = [u8];
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
@@ -0,0 +1,56 @@
+use std::{
+    fs,
+    io::{self, Read},
+    ops::Deref,
+    str::FromStr,
+    sync::OnceLock,
+};
+
+use anyhow::{anyhow, Context, Result};
+
+/// Cypher text as passed by the user. Long tokens are painful on the command line and end up in the shell's history, so it can also be read from a file (`@<path>`) or stdin (`-`).
+#[derive(Debug, Clone)]
+pub(crate) struct CypherTextInput(String);
+
+// `clap` parses a value both to validate it and to store it, but stdin can only be read once
+static STDIN: OnceLock<String> = OnceLock::new();
+
+impl FromStr for CypherTextInput {
+    type Err = anyhow::Error;
+
+    fn from_str(input: &str) -> Result<Self> {
+        let cypher_text = if input == "-" {
+            match STDIN.get() {
+                Some(cypher_text) => cypher_text.clone(),
+                None => {
+                    let mut cypher_text = String::new();
+                    io::stdin()
+                        .read_to_string(&mut cypher_text)
+                        .context("Reading the cypher text from stdin failed")?;
+                    STDIN.get_or_init(|| cypher_text).clone()
+                }
+            }
+        } else if let Some(path) = input.strip_prefix('@') {
+            fs::read_to_string(path)
+                .context(format!("Reading the cypher text from `{}` failed", path))?
+        } else {
+            return Ok(Self(input.to_string()));
+        };
+
+        // files usually end with a newline, which isn't part of the cypher text
+        let cypher_text = cypher_text.trim_end_matches(['\r', '\n']);
+        if cypher_text.is_empty() {
+            return Err(anyhow!("The cypher text read from `{}` is empty", input));
+        }
+
+        Ok(Self(cypher_text.to_string()))
+    }
+}
+
+impl Deref for CypherTextInput {
+    type Target = str;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
@@ -0,0 +1,52 @@
+use std::{fmt::Display, str::FromStr};
+
+use anyhow::{anyhow, Result};
+use itertools::Itertools;
+
+/// How the demo server gives away a padding error.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub(crate) enum DemoErrorStyle {
+    // `500 Internal Server Error`, other failures are a `403 Forbidden`
+    #[default]
+    Status,
+    // always `200 OK`, only the body differs
+    Body,
+    // a redirect to an error page
+    Redirect,
+}
+
+impl DemoErrorStyle {
+    fn variants() -> &'static [Self] {
+        &[Self::Status, Self::Body, Self::Redirect]
+    }
+}
+
+impl Display for DemoErrorStyle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DemoErrorStyle::Status => write!(f, "status"),
+            DemoErrorStyle::Body => write!(f, "body"),
+            DemoErrorStyle::Redirect => write!(f, "redirect"),
+        }
+    }
+}
+
+impl FromStr for DemoErrorStyle {
+    type Err = anyhow::Error;
+
+    fn from_str(input: &str) -> Result<Self> {
+        match input.to_lowercase().as_str() {
+            "status" | "status-code" | "status_code" => Ok(Self::Status),
+            "body" => Ok(Self::Body),
+            "redirect" => Ok(Self::Redirect),
+            _ => Err(anyhow!(
+                "`{}` is not an error style. Expected one of: [{}]",
+                input,
+                Self::variants()
+                    .iter()
+                    .map(|variant| variant.to_string())
+                    .join(", ")
+            )),
+        }
+    }
+}
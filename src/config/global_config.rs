@@ -1,14 +1,24 @@
-use anyhow::Result;
+use anyhow::{anyhow, Context, Result};
 use getset::Getters;
 use log::LevelFilter;
-use std::path::PathBuf;
+use reqwest::Url;
+use std::{ops::Deref, path::PathBuf};
 
 use crate::{
-    block::block_size::BlockSize, cli::GlobalOptions, cypher_text::CypherText,
-    oracle::oracle_location::OracleLocation, plain_text::PlainText,
+    block::{block_size::BlockSize, padding_scheme::PaddingScheme, Block},
+    cli::GlobalOptions,
+    cypher_text::{encode::AmountBlocksTrait, CypherText},
+    oracle::oracle_location::OracleLocation,
+    plain_text::PlainText,
+    tui::keymap::Keymap,
 };
 
-use super::thread_count::ThreadCount;
+use super::{
+    aes_key::AesKey, block_selection::BlockSelection, escaped_bytes::EscapedBytes,
+    guess_order::GuessOrder, guess_strategy::GuessStrategyOption, known_plain_text::KnownPlainText,
+    output_format::OutputFormat, plain_text_edit::PlainTextEdit, segment_output::SegmentOutput,
+    segmentation::Segmentation, syslog_destination::SyslogDestination, thread_count::ThreadCount,
+};
 
 #[derive(Debug, Getters)]
 pub(crate) struct GlobalConfig {
@@ -16,18 +26,102 @@ pub(crate) struct GlobalConfig {
     oracle_location: OracleLocation,
     #[getset(get = "pub(crate)")]
     cypher_text: CypherText,
+    // every segment of a token which concatenates multiple cypher texts. Empty if the token isn't split
+    #[getset(get = "pub(crate)")]
+    cypher_text_segments: Vec<CypherText>,
+    #[getset(get = "pub(crate)")]
+    segment_output: SegmentOutput,
+    // joins the plain texts of segments, with `SegmentOutput::Delimited`
+    #[getset(get = "pub(crate)")]
+    output_delimiter: Option<EscapedBytes>,
     #[getset(get = "pub(crate)")]
     plain_text: Option<PlainText>,
+    // `--reencrypt-with`: the plain text to encrypt is the decrypted one, edited
+    #[getset(get = "pub(crate)")]
+    plain_text_edit: Option<PlainTextEdit>,
     #[getset(get = "pub(crate)")]
     block_size: BlockSize,
     #[getset(get = "pub(crate)")]
+    no_iv: bool,
+    #[getset(get = "pub(crate)")]
+    padding_scheme: PaddingScheme,
+    #[getset(get = "pub(crate)")]
+    block_selection: Option<BlockSelection>,
+    #[getset(get = "pub(crate)")]
+    known_plain_text: KnownPlainText,
+    #[getset(get = "pub(crate)")]
+    guess_order: GuessOrder,
+    #[getset(get = "pub(crate)")]
+    guess_strategy: GuessStrategyOption,
+    #[getset(get = "pub(crate)")]
+    no_warm_start: bool,
+    #[getset(get = "pub(crate)")]
     log_level: LevelFilter,
     #[getset(get = "pub(crate)")]
     thread_count: ThreadCount,
     #[getset(get = "pub(crate)")]
     output_file: Option<PathBuf>,
     #[getset(get = "pub(crate)")]
+    #[getset(get = "pub(crate)")]
+    syslog: Option<SyslogDestination>,
+    #[getset(get = "pub(crate)")]
+    no_tui: bool,
+    #[getset(get = "pub(crate)")]
+    keymap: Keymap,
+    #[getset(get = "pub(crate)")]
+    timeline_file: Option<PathBuf>,
+    #[getset(get = "pub(crate)")]
+    events_file: Option<PathBuf>,
+    #[getset(get = "pub(crate)")]
+    record_file: Option<PathBuf>,
+    #[getset(get = "pub(crate)")]
+    cast_file: Option<PathBuf>,
+    #[getset(get = "pub(crate)")]
+    report_file: Option<PathBuf>,
+    #[getset(get = "pub(crate)")]
+    guess_stats_file: Option<PathBuf>,
+    #[getset(get = "pub(crate)")]
+    share_stats: bool,
+    #[getset(get = "pub(crate)")]
+    notify_webhook: Option<Url>,
+    #[getset(get = "pub(crate)")]
+    plain_text_file: Option<PathBuf>,
+    #[getset(get = "pub(crate)")]
+    strip_padding: bool,
+    #[getset(get = "pub(crate)")]
+    analyze: bool,
+    #[getset(get = "pub(crate)")]
+    output_format: OutputFormat,
+    #[getset(get = "pub(crate)")]
+    intermediates_file: Option<PathBuf>,
+    #[getset(get = "pub(crate)")]
+    export_state_file: Option<PathBuf>,
+    #[getset(get = "pub(crate)")]
+    import_state_file: Option<PathBuf>,
+    #[getset(get = "pub(crate)")]
+    strict: bool,
+    #[getset(get = "pub(crate)")]
     no_cache: bool,
+    #[getset(get = "pub(crate)")]
+    session_file: Option<PathBuf>,
+    #[getset(get = "pub(crate)")]
+    session_passphrase: Option<String>,
+    #[getset(get = "pub(crate)")]
+    verification_key: Option<AesKey>,
+    #[getset(get = "pub(crate)")]
+    verification_iv: Option<Block>,
+}
+
+impl GlobalConfig {
+    /// Attack another segment of the cypher text
+    pub(super) fn set_cypher_text(&mut self, cypher_text: CypherText) {
+        self.cypher_text = cypher_text;
+    }
+
+    /// Send to another location of the same oracle, e.g. the path of `--request`
+    pub(super) fn set_oracle_location(&mut self, oracle_location: OracleLocation) {
+        self.oracle_location = oracle_location;
+    }
 }
 
 impl TryFrom<&GlobalOptions> for GlobalConfig {
@@ -40,24 +134,172 @@ impl TryFrom<&GlobalOptions> for GlobalConfig {
             _ => LevelFilter::Trace,
         };
 
-        Ok(Self {
-            oracle_location: options.oracle_location().clone(),
-            cypher_text: CypherText::parse(
-                options.cypher_text(),
+        if options.verification_key().is_some() && **options.block_size() != 16 {
+            return Err(anyhow!(
+                "AES uses 16 byte blocks. Verification with `--verify-key` is impossible for other block sizes"
+            ));
+        }
+        let verification_iv = options
+            .verification_iv()
+            .as_ref()
+            .map(|iv| {
+                let iv = hex::decode(iv).context(format!("`{}` is not valid hex", iv))?;
+                if iv.len() != **options.block_size() as usize {
+                    return Err(anyhow!(
+                        "The IV must be as long as a block, {} bytes, but is {} bytes long",
+                        **options.block_size(),
+                        iv.len()
+                    ));
+                }
+                Ok(Block::from(&iv[..]))
+            })
+            .transpose()?;
+
+        let cypher_text_input = options
+            .cypher_text()
+            .iter()
+            .map(Deref::deref)
+            .collect::<String>();
+        let segmentation = Segmentation::new(
+            options.segment_delimiter().as_ref(),
+            *options.segment_length(),
+        )?;
+        if let Some(Segmentation::Length(length)) = &segmentation {
+            if length.get() % **options.block_size() as usize != 0 {
+                return Err(anyhow!(
+                    "Segments of {} bytes can't be split into blocks of {} bytes. See `--segment-length` for extra info",
+                    length,
+                    **options.block_size()
+                ));
+            }
+        }
+        let output_delimiter = match (&segmentation, options.segment_output()) {
+            (None, SegmentOutput::Separate) if options.output_delimiter().is_none() => None,
+            (None, _) => {
+                return Err(anyhow!(
+                    "Only tokens split into segments have per-segment output. See `--split-pattern` or `--segment-length` for extra info"
+                ))
+            }
+            (Some(segmentation), SegmentOutput::Delimited) => Some(
+                options
+                    .output_delimiter()
+                    .clone()
+                    .or_else(|| match segmentation {
+                        Segmentation::Delimiter(delimiter) => Some(delimiter.clone()),
+                        Segmentation::Length(_) => None,
+                    })
+                    .ok_or_else(|| anyhow!(
+                        "Segments of fixed length have no delimiter to join them with. See `--output-delimiter` for extra info"
+                    ))?,
+            ),
+            (Some(_), _) if options.output_delimiter().is_some() => {
+                return Err(anyhow!(
+                    "The output delimiter only joins a delimited result. See `--segment-output` for extra info"
+                ))
+            }
+            (Some(_), _) => None,
+        };
+        let cypher_text_segments = match &segmentation {
+            Some(segmentation) => CypherText::parse_segments(
+                &cypher_text_input,
                 options.block_size(),
                 *options.no_iv(),
                 options.encoding(),
                 *options.no_url_encode(),
+                *options.base64_padding(),
+                segmentation,
             )?,
-            plain_text: options
-                .plain_text()
-                .as_ref()
-                .map(|plain_text| PlainText::new(plain_text, options.block_size())),
+            None => vec![CypherText::parse(
+                &cypher_text_input,
+                options.block_size(),
+                *options.no_iv(),
+                options.encoding(),
+                *options.no_url_encode(),
+                *options.base64_padding(),
+            )?],
+        };
+        let cypher_text_segments: Vec<_> = cypher_text_segments
+            .into_iter()
+            .map(|cypher_text| {
+                if *options.short_requests() {
+                    cypher_text.without_prefix_in_requests()
+                } else {
+                    cypher_text
+                }
+            })
+            .collect();
+        // the 1st segment is attacked first
+        let cypher_text = cypher_text_segments
+            .first()
+            .cloned()
+            .ok_or_else(|| anyhow!("The cypher text holds no segments"))?;
+        let cypher_text_segments = if segmentation.is_some() {
+            cypher_text_segments
+        } else {
+            Vec::new()
+        };
+        if let Some(block_selection) = options.block_selection() {
+            if block_selection.last() >= cypher_text.amount_blocks() {
+                return Err(anyhow!(
+                    "Block {} was selected, but the last block of the cypher text is block {}. See `--blocks` for extra info",
+                    block_selection.last(),
+                    cypher_text.amount_blocks() - 1
+                ));
+            }
+        }
+
+        // without the TUI, there are no keys to remap
+        let keymap = if *options.no_tui() {
+            Keymap::default()
+        } else {
+            Keymap::load(options.keymap_file().as_deref())?
+        };
+
+        Ok(Self {
+            oracle_location: options.oracle_location().clone(),
+            cypher_text,
+            cypher_text_segments,
+            segment_output: *options.segment_output(),
+            output_delimiter,
+            plain_text: options.plain_text().as_ref().map(|plain_text| {
+                PlainText::new(plain_text, options.block_size(), options.padding_scheme())
+            }),
+            plain_text_edit: options.plain_text_edit().clone(),
             block_size: *options.block_size(),
+            no_iv: *options.no_iv(),
+            padding_scheme: *options.padding_scheme(),
+            block_selection: options.block_selection().clone(),
+            known_plain_text: KnownPlainText::new(options.known_plain_text_hints()),
+            guess_order: options.guess_order().clone(),
+            guess_strategy: *options.guess_strategy(),
+            no_warm_start: *options.no_warm_start(),
             log_level,
             thread_count: options.thread_count().clone(),
             output_file: options.log_file().clone(),
+            syslog: options.syslog().clone(),
+            no_tui: *options.no_tui(),
+            keymap,
+            timeline_file: options.timeline_file().clone(),
+            events_file: options.events_file().clone(),
+            record_file: options.record_file().clone(),
+            cast_file: options.cast_file().clone(),
+            report_file: options.report_file().clone(),
+            guess_stats_file: options.guess_stats_file().clone(),
+            share_stats: *options.share_stats(),
+            notify_webhook: options.notify_webhook().clone(),
+            plain_text_file: options.plain_text_file().clone(),
+            intermediates_file: options.intermediates_file().clone(),
+            export_state_file: options.export_state_file().clone(),
+            import_state_file: options.import_state_file().clone(),
+            strip_padding: *options.strip_padding(),
+            analyze: *options.analyze(),
+            output_format: *options.output_format(),
+            strict: *options.strict(),
             no_cache: *options.no_cache(),
+            session_file: options.session_file().clone(),
+            session_passphrase: options.session_passphrase().clone(),
+            verification_key: options.verification_key().clone(),
+            verification_iv,
         })
     }
 }
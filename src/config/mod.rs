@@ -1,24 +1,56 @@
-pub(super) mod encoding_option;
+pub(super) mod aes_key;
+pub(super) mod cypher_text_input;
+pub(super) mod demo_error_style;
 mod global_config;
-pub(super) mod header;
+pub(super) mod output_format;
+pub(super) mod plain_text_edit;
 pub(super) mod proxy_credentials;
-pub(super) mod request_timeout;
-pub(super) mod thread_count;
-pub(super) mod thread_delay;
-pub(super) mod user_agent;
+pub(super) mod replay_speed;
+pub(super) mod request_template;
+pub(super) mod segment_output;
+pub(super) mod syslog_destination;
 
-use std::ops::Deref;
+use std::{ops::Deref, str::FromStr, sync::Arc};
 
-use anyhow::Result;
+use anyhow::{anyhow, Context, Result};
 use getset::Getters;
-use reqwest::Proxy;
+use reqwest::{Method, Proxy, Url};
 
 use self::{
-    global_config::GlobalConfig, header::Header, request_timeout::RequestTimeout,
-    thread_delay::ThreadDelay, user_agent::UserAgent,
+    calibration_mode::CalibrationMode, concurrency::Concurrency,
+    cypher_text_split::CypherTextSplit, escaped_bytes::EscapedBytes, global_config::GlobalConfig,
+    header::Header, keyword_occurrence::KeywordOccurrences,
+    normalization_rules::NormalizationRules, padding_error_overrides::PaddingErrorOverrides,
 };
 
-use crate::cli::{Cli, ScriptCli, SubCommand, WebCli};
+use crate::{
+    cli::{
+        Cli, GlobalOptions, LuaCli, PluginCli, PythonCli, ScriptCli, SubCommand, TcpCli, WebCli,
+    },
+    curl,
+    cypher_text::CypherText,
+    oracle::{
+        certificate_pin::CertificatePin,
+        lua,
+        oracle_location::OracleLocation,
+        python,
+        request_budget::RequestBudget,
+        sticky_session::StickySession,
+        traffic_shaper::TrafficShaper,
+        web::{har::Har, proxy_chain::ProxyChain},
+    },
+    presets,
+};
+
+// the engine's options, which the CLI parses into
+pub(super) use rustpad_core::config::{
+    base64_padding, block_selection, body_normalization, cache_bust_method, calibration_mode,
+    concurrency, cypher_text_split, encoding_option, escaped_bytes, guess_order, guess_strategy,
+    header, http_version, keyword_occurrence, known_plain_text, normalization_rules,
+    padding_error_overrides, padding_error_pattern, request_timeout, segmentation,
+    sticky_session_source, thread_count, thread_delay, user_agent, verdict_source, LuaConfig,
+    PluginConfig, PythonConfig, ScriptConfig, SubConfig, TcpConfig, WebConfig,
+};
 
 /// Application configuration based on processed CLI args.
 #[derive(Debug, Getters)]
@@ -28,55 +60,77 @@ pub(super) struct Config {
     sub_config: SubConfig,
 }
 
-#[derive(Debug)]
-pub(super) enum SubConfig {
-    Web(WebConfig),
-    Script(ScriptConfig),
-}
-
-#[derive(Debug, Clone, Getters)]
-pub(super) struct WebConfig {
-    #[getset(get = "pub(super)")]
-    post_data: Option<String>,
-    #[getset(get = "pub(super)")]
-    headers: Vec<Header>,
-    #[getset(get = "pub(super)")]
-    keyword: String,
-    #[getset(get = "pub(super)")]
-    user_agent: UserAgent,
-    #[getset(get = "pub(super)")]
-    proxy: Option<Proxy>,
-    #[getset(get = "pub(super)")]
-    request_timeout: RequestTimeout,
-    #[getset(get = "pub(super)")]
-    redirect: bool,
-    #[getset(get = "pub(super)")]
-    insecure: bool,
-    #[getset(get = "pub(super)")]
-    consider_body: bool,
-    #[getset(get = "pub(super)")]
-    thread_delay: ThreadDelay,
-}
-
-#[derive(Debug, Clone, Getters)]
-pub(super) struct ScriptConfig {
-    #[getset(get = "pub(super)")]
-    thread_delay: ThreadDelay,
-}
-
 impl TryFrom<Cli> for Config {
     type Error = anyhow::Error;
 
     fn try_from(cli: Cli) -> Result<Self> {
         match cli.sub_command {
-            SubCommand::Web(web_cli) => Ok(Self {
-                global_config: GlobalConfig::try_from(web_cli.global_options())?,
-                sub_config: SubConfig::Web(WebConfig::try_from(*web_cli)?),
-            }),
+            SubCommand::Web(web_cli) => {
+                let mut global_config = GlobalConfig::try_from(web_cli.global_options())?;
+                if let OracleLocation::Web(url) = global_config.oracle_location() {
+                    let mut url = url.clone();
+                    if let Some(request_template) = web_cli.request_template() {
+                        url = request_template.url(&url)?;
+                    }
+                    if web_cli.from_curl().is_some() {
+                        let located = curl::locate_cypher_text(
+                            url.as_str(),
+                            web_cli.cypher_text(),
+                            web_cli.keyword(),
+                        );
+                        url = Url::parse(&located).context(format!(
+                            "Locating the cypher text in `{}` gave an invalid URL",
+                            url
+                        ))?;
+                    }
+                    global_config.set_oracle_location(OracleLocation::Web(url));
+                }
+
+                Ok(Self {
+                    global_config,
+                    sub_config: SubConfig::Web(Box::new(WebConfig::try_from(*web_cli)?)),
+                })
+            }
             SubCommand::Script(script_cli) => Ok(Self {
                 global_config: GlobalConfig::try_from(script_cli.global_options())?,
                 sub_config: SubConfig::Script(ScriptConfig::try_from(*script_cli)?),
             }),
+            SubCommand::Tcp(tcp_cli) => Ok(Self {
+                global_config: GlobalConfig::try_from(tcp_cli.global_options())?,
+                sub_config: SubConfig::Tcp(Box::new(TcpConfig::try_from(*tcp_cli)?)),
+            }),
+            SubCommand::Plugin(plugin_cli) => Ok(Self {
+                global_config: GlobalConfig::try_from(plugin_cli.global_options())?,
+                sub_config: SubConfig::Plugin(PluginConfig::try_from(*plugin_cli)?),
+            }),
+            SubCommand::Lua(lua_cli) => {
+                let mut global_config = GlobalConfig::try_from(lua_cli.global_options())?;
+                // executable Lua scripts are taken as script oracles, unless questioned as Lua oracles
+                if let OracleLocation::Script(path) = global_config.oracle_location() {
+                    if lua::is_script(path) {
+                        global_config.set_oracle_location(OracleLocation::Lua(path.clone()));
+                    }
+                }
+
+                Ok(Self {
+                    global_config,
+                    sub_config: SubConfig::Lua(LuaConfig::try_from(*lua_cli)?),
+                })
+            }
+            SubCommand::Python(python_cli) => {
+                let mut global_config = GlobalConfig::try_from(python_cli.global_options())?;
+                // as for Lua, executable Python scripts are taken as script oracles
+                if let OracleLocation::Script(path) = global_config.oracle_location() {
+                    if python::is_script(path) {
+                        global_config.set_oracle_location(OracleLocation::Python(path.clone()));
+                    }
+                }
+
+                Ok(Self {
+                    global_config,
+                    sub_config: SubConfig::Python(PythonConfig::try_from(*python_cli)?),
+                })
+            }
             _ => unreachable!(
                 "Attempted to convert sub-command {:?} into a config.",
                 cli.sub_command
@@ -89,29 +143,163 @@ impl TryFrom<WebCli> for WebConfig {
     type Error = anyhow::Error;
 
     fn try_from(cli: WebCli) -> Result<Self> {
-        Ok(Self {
-            post_data: cli.post_data().clone(),
-            headers: cli.header().clone(),
-            keyword: cli.keyword().clone(),
-            user_agent: cli.user_agent().clone(),
-            proxy: cli
-                .proxy_url()
+        let url = match cli.oracle_location() {
+            OracleLocation::Web(url) => url,
+            _ => {
+                return Err(anyhow!(
+                    "The web oracle must be given as a URL. See `--oracle` for extra info"
+                ))
+            }
+        };
+        if *cli.pin_cert() && url.scheme() != "https" {
+            return Err(anyhow!(
+                "Only the certificate of an HTTPS oracle can be pinned. See `--pin-cert` for extra info"
+            ));
+        }
+        let normalization_rules = match cli.normalization_rules_file() {
+            Some(path) => NormalizationRules::load(path)?,
+            None => NormalizationRules::default(),
+        };
+        // `-H` and `-d` take precedence over the captured request
+        let headers = match cli.request_template() {
+            Some(request_template) => request_template
+                .headers()
+                .iter()
+                .filter(|template_header| {
+                    !cli.header()
+                        .iter()
+                        .any(|header| header.name().eq_ignore_ascii_case(template_header.name()))
+                })
+                .chain(cli.header().iter())
+                .cloned()
+                .collect(),
+            None => cli.header().clone(),
+        };
+        let post_data = cli.post_data().clone().or_else(|| {
+            cli.request_template()
                 .as_ref()
-                .map(|url| -> Result<Proxy> {
-                    let proxy = Proxy::all(url.clone())?;
-                    if let Some(proxy_creds) = cli.proxy_credentials() {
-                        Ok(proxy.basic_auth(proxy_creds.username(), proxy_creds.password()))
-                    } else {
-                        Ok(proxy)
+                .and_then(|request_template| request_template.body().clone())
+        });
+        let method = match (cli.method(), cli.request_template()) {
+            (Some(method), _) => method.clone(),
+            (None, Some(request_template)) => request_template.method().clone(),
+            (None, None) if post_data.is_some() => Method::POST,
+            (None, None) => Method::GET,
+        };
+        // the request was copied along with the cypher text
+        let (headers, post_data) = if cli.from_curl().is_some() {
+            let locate =
+                |part: &str| curl::locate_cypher_text(part, cli.cypher_text(), cli.keyword());
+            (
+                headers
+                    .iter()
+                    .map(|header| {
+                        Header::from_str(&format!("{}:{}", header.name(), locate(header.value())))
+                    })
+                    .collect::<Result<Vec<_>>>()?,
+                post_data.as_deref().map(locate),
+            )
+        } else {
+            (headers, post_data)
+        };
+
+        if let Some(preset) = cli.preset() {
+            let keyword_of_preset_left = post_data
+                .iter()
+                .chain(headers.iter().map(|header| header.value()))
+                .any(|part| part.contains(presets::KEYWORD));
+            if preset.places_keyword()
+                && cli.keyword() != presets::KEYWORD
+                && keyword_of_preset_left
+            {
+                return Err(anyhow!(
+                    "Preset `{}` locates the cypher text with keyword `{}`. Locate it yourself when changing `--keyword`. See `presets list` for extra info",
+                    preset.name(),
+                    presets::KEYWORD
+                ));
+            }
+        }
+
+        let proxy = match &cli.proxy_urls()[..] {
+            [] => None,
+            [url] => {
+                let proxy = Proxy::all(url.clone())?;
+                Some(match cli.proxy_credentials() {
+                    Some(proxy_creds) => {
+                        proxy.basic_auth(proxy_creds.username(), proxy_creds.password())
                     }
+                    None => proxy,
                 })
-                .transpose()?,
-            request_timeout: cli.request_timeout().clone(),
-            redirect: *cli.redirect(),
-            insecure: *cli.no_cert_validation(),
-            consider_body: *cli.consider_body(),
-            thread_delay: cli.thread_delay().clone(),
-        })
+            }
+            // `reqwest` speaks to a single proxy, which tunnels through the others
+            chain => {
+                if cli.proxy_credentials().is_some() {
+                    return Err(anyhow!(
+                            "`--proxy-credentials` applies to a single proxy. Give the credentials of chained proxies in their URLs, e.g. `socks5://<user>:<pass>@<host>:<port>`"
+                        ));
+                }
+                let chain = ProxyChain::new(chain.to_vec(), **cli.request_timeout())?;
+                Some(Proxy::all(chain.listen()?)?)
+            }
+        };
+
+        Ok(Self::new()
+            .with_method(method)
+            .with_post_data(post_data)
+            .with_headers(headers)
+            .with_keyword(cli.keyword().clone())
+            .with_keyword_occurrences(KeywordOccurrences::from(&cli.keyword_occurrence()[..]))
+            .with_cypher_text_split(CypherTextSplit::new(
+                *cli.split_length(),
+                cli.cypher_text(),
+                *cli.no_url_encode(),
+            ))
+            .with_user_agent(cli.user_agent().clone())
+            .with_proxy(proxy)
+            .with_request_timeout(cli.request_timeout().clone())
+            .with_redirect(*cli.redirect())
+            .with_insecure(*cli.no_cert_validation())
+            .with_certificate_pin(
+                cli.pin_cert()
+                    .then(|| CertificatePin::new(*cli.allow_cert_change())),
+            )
+            // the body's size can't be compared without retrieving the body
+            .with_consider_body(
+                *cli.consider_body()
+                    || !cli.body_normalizations().is_empty()
+                    || normalization_rules.normalizes_body()
+                    || cli.padding_error_size().is_some()
+                    || cli.padding_error_regex().is_some()
+                    || *cli.calibration_mode() == CalibrationMode::BodyPattern,
+            )
+            .with_body_normalizations(cli.body_normalizations().clone())
+            .with_normalization_rules(normalization_rules)
+            .with_padding_error_overrides(PaddingErrorOverrides::new(
+                *cli.padding_error_status(),
+                cli.padding_error_location().clone(),
+                *cli.padding_error_size(),
+                cli.padding_error_regex().clone(),
+            ))
+            .with_calibration_mode(*cli.calibration_mode())
+            .with_calibration_passes(*cli.calibration_passes())
+            .with_thread_delay(cli.thread_delay().clone())
+            .with_concurrency(
+                cli.concurrency()
+                    .clone()
+                    .unwrap_or_else(|| Concurrency::from(cli.thread_count())),
+            )
+            .with_http_version(*cli.http_version())
+            .with_http2_connections(*cli.http2_connections())
+            .with_cache_bust(*cli.cache_bust())
+            .with_sticky_session(cli.sticky_session().clone().map(StickySession::new))
+            .with_request_budget(RequestBudget::from(&*cli))
+            .with_traffic_shaper(traffic_shaper(&cli))
+            .with_warm_up(*cli.warm_up())
+            .with_har(cli.har_file().clone().map(Har::new))
+            .with_dry_run(*cli.dry_run())
+            .with_poc_file(cli.poc_file().clone())
+            .with_maintenance_probe(*cli.maintenance_probe())
+            .with_maintenance_timeout(*cli.maintenance_timeout()))
     }
 }
 
@@ -119,9 +307,160 @@ impl TryFrom<ScriptCli> for ScriptConfig {
     type Error = anyhow::Error;
 
     fn try_from(cli: ScriptCli) -> Result<Self> {
-        Ok(Self {
-            thread_delay: cli.thread_delay().clone(),
-        })
+        if !matches!(cli.oracle_location(), OracleLocation::Script(_)) {
+            return Err(anyhow!(
+                "The script oracle must be given as a file path. See `--oracle` for extra info"
+            ));
+        }
+
+        Ok(Self::new()
+            .with_thread_delay(cli.thread_delay().clone())
+            .with_persistent(*cli.persistent())
+            .with_verdict_source(*cli.verdict_source())
+            .with_request_timeout(cli.request_timeout().clone())
+            .with_request_budget(RequestBudget::from(&*cli))
+            .with_traffic_shaper(traffic_shaper(&cli)))
+    }
+}
+
+impl TryFrom<TcpCli> for TcpConfig {
+    type Error = anyhow::Error;
+
+    fn try_from(cli: TcpCli) -> Result<Self> {
+        if !matches!(cli.oracle_location(), OracleLocation::Tcp(_)) {
+            return Err(anyhow!(
+                "The TCP oracle must be given as `<host>:<port>`. See `--oracle` for extra info"
+            ));
+        }
+        if cli.keyword().is_empty() {
+            return Err(anyhow!(
+                "The keyword can't be empty. See `--keyword` for extra info"
+            ));
+        }
+
+        let send_template = match cli.send_template() {
+            Some(send_template) => send_template.clone(),
+            // by default, the cypher text is sent as a single line
+            None => EscapedBytes::from(format!("{}\n", cli.keyword()).into_bytes()),
+        };
+
+        Ok(Self::new(send_template, cli.padding_error().clone())
+            .with_keyword(cli.keyword().clone())
+            .with_response_delimiter(cli.response_delimiter().clone())
+            .with_tls(*cli.tls())
+            .with_insecure(*cli.no_cert_validation())
+            .with_certificate_pin(
+                cli.pin_cert()
+                    .then(|| CertificatePin::new(*cli.allow_cert_change())),
+            )
+            .with_request_timeout(cli.request_timeout().clone())
+            .with_thread_delay(cli.thread_delay().clone())
+            .with_request_budget(RequestBudget::from(&*cli))
+            .with_traffic_shaper(traffic_shaper(&cli)))
+    }
+}
+
+impl TryFrom<PluginCli> for PluginConfig {
+    type Error = anyhow::Error;
+
+    fn try_from(cli: PluginCli) -> Result<Self> {
+        if !matches!(cli.oracle_location(), OracleLocation::Plugin(_)) {
+            return Err(anyhow!(
+                "The plugin oracle must be given as the path to a shared library or WASM module, or the name of one in the plugins directory. See `--oracle` for extra info"
+            ));
+        }
+
+        Ok(Self::new()
+            .with_plugin_config(cli.plugin_config().clone())
+            .with_request_timeout(cli.request_timeout().clone())
+            .with_insecure(*cli.no_cert_validation())
+            .with_allowed_hosts(cli.allowed_hosts().clone())
+            .with_thread_delay(cli.thread_delay().clone())
+            .with_request_budget(RequestBudget::from(&*cli))
+            .with_traffic_shaper(traffic_shaper(&cli)))
+    }
+}
+
+impl TryFrom<LuaCli> for LuaConfig {
+    type Error = anyhow::Error;
+
+    fn try_from(cli: LuaCli) -> Result<Self> {
+        let is_lua_script = match cli.oracle_location() {
+            OracleLocation::Lua(_) => true,
+            OracleLocation::Script(path) => lua::is_script(path),
+            _ => false,
+        };
+        if !is_lua_script {
+            return Err(anyhow!(
+                "The Lua oracle must be given as the path to a `.lua` script. See `--oracle` for extra info"
+            ));
+        }
+
+        Ok(Self::new()
+            .with_request_timeout(cli.request_timeout().clone())
+            .with_insecure(*cli.no_cert_validation())
+            .with_thread_delay(cli.thread_delay().clone())
+            .with_request_budget(RequestBudget::from(&*cli))
+            .with_traffic_shaper(traffic_shaper(&cli)))
+    }
+}
+
+impl TryFrom<PythonCli> for PythonConfig {
+    type Error = anyhow::Error;
+
+    fn try_from(cli: PythonCli) -> Result<Self> {
+        let is_python_script = match cli.oracle_location() {
+            OracleLocation::Python(_) => true,
+            OracleLocation::Script(path) => python::is_script(path),
+            _ => false,
+        };
+        if !is_python_script {
+            return Err(anyhow!(
+                "The Python oracle must be given as the path to a `.py` script. See `--oracle` for extra info"
+            ));
+        }
+
+        Ok(Self::new()
+            .with_thread_delay(cli.thread_delay().clone())
+            .with_request_budget(RequestBudget::from(&*cli))
+            .with_traffic_shaper(traffic_shaper(&cli)))
+    }
+}
+
+impl From<&GlobalOptions> for RequestBudget {
+    fn from(options: &GlobalOptions) -> Self {
+        Self::new(
+            *options.max_requests(),
+            options.max_duration().map(Into::into),
+            options.wind_down_after().map(Into::into),
+        )
+    }
+}
+
+fn traffic_shaper(options: &GlobalOptions) -> Option<TrafficShaper> {
+    options
+        .burst()
+        .zip(*options.pause())
+        .map(|(burst, pause)| TrafficShaper::new(burst, pause.into()))
+}
+
+impl Config {
+    /// Limits of the oracle, which also count the requests sent
+    pub(super) fn request_budget(&self) -> &Arc<RequestBudget> {
+        match &self.sub_config {
+            SubConfig::Web(web_config) => web_config.request_budget(),
+            SubConfig::Script(script_config) => script_config.request_budget(),
+            SubConfig::Tcp(tcp_config) => tcp_config.request_budget(),
+            SubConfig::Plugin(plugin_config) => plugin_config.request_budget(),
+            SubConfig::Lua(lua_config) => lua_config.request_budget(),
+            SubConfig::Python(python_config) => python_config.request_budget(),
+        }
+    }
+
+    /// Same config, attacking the given segment of the cypher text instead
+    pub(super) fn with_cypher_text(mut self, cypher_text: CypherText) -> Self {
+        self.global_config.set_cypher_text(cypher_text);
+        self
     }
 }
 
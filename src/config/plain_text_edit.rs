@@ -0,0 +1,338 @@
+use std::{
+    fmt::{self, Display},
+    str::FromStr,
+};
+
+use anyhow::{anyhow, Context, Result};
+use indexmap::IndexMap;
+use regex::bytes::{Regex, RegexBuilder};
+use serde::{Deserialize, Serialize};
+use serde_json::Number;
+
+/// Edit of the decrypted plain text, which is then encrypted, for `--reencrypt-with`
+#[derive(Debug, Clone)]
+pub(crate) enum PlainTextEdit {
+    // `s/regex/replacement/flags`, as in sed
+    Substitution {
+        regex: Regex,
+        // in the syntax of `regex`, e.g. `${1}` rather than sed's `\1`
+        replacement: Vec<u8>,
+        global: bool,
+    },
+    // RFC 6902, for plain texts which are JSON
+    JsonPatch(Vec<PatchOperation>),
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "op", rename_all = "lowercase")]
+pub(crate) enum PatchOperation {
+    Add { path: String, value: Json },
+    Remove { path: String },
+    Replace { path: String, value: Json },
+    Move { from: String, path: String },
+    Copy { from: String, path: String },
+    Test { path: String, value: Json },
+}
+
+/// JSON value of which objects keep the order of their keys, so a patched plain text only differs where it was patched. `serde_json::Value` sorts them
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub(crate) enum Json {
+    Null,
+    Bool(bool),
+    Number(Number),
+    String(String),
+    Array(Vec<Json>),
+    Object(IndexMap<String, Json>),
+}
+
+impl Json {
+    /// Value at the JSON pointer, as `serde_json::Value::pointer`
+    fn pointer(&self, pointer: &str) -> Option<&Json> {
+        pointer_tokens(pointer)?.try_fold(self, |target, token| match target {
+            Self::Object(object) => object.get(&token),
+            Self::Array(array) => array_token_idx(&token).and_then(|idx| array.get(idx)),
+            _ => None,
+        })
+    }
+
+    fn pointer_mut(&mut self, pointer: &str) -> Option<&mut Json> {
+        pointer_tokens(pointer)?.try_fold(self, |target, token| match target {
+            Self::Object(object) => object.get_mut(&token),
+            Self::Array(array) => array_token_idx(&token).and_then(|idx| array.get_mut(idx)),
+            _ => None,
+        })
+    }
+}
+
+impl Display for Json {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&serde_json::to_string(self).map_err(|_| fmt::Error)?)
+    }
+}
+
+/// Unescaped keys of a JSON pointer. `None` if it isn't one
+fn pointer_tokens(pointer: &str) -> Option<impl Iterator<Item = String> + '_> {
+    let tokens = if pointer.is_empty() {
+        None
+    } else {
+        Some(pointer.strip_prefix('/')?.split('/'))
+    };
+    Some(
+        tokens
+            .into_iter()
+            .flatten()
+            .map(|token| token.replace("~1", "/").replace("~0", "~")),
+    )
+}
+
+// as RFC 6901, which forbids leading zeros
+fn array_token_idx(token: &str) -> Option<usize> {
+    if token.starts_with('+') || (token.starts_with('0') && token.len() > 1) {
+        return None;
+    }
+    token.parse().ok()
+}
+
+impl PlainTextEdit {
+    /// Edit `plain_text`, which is without padding. Fails if the edit doesn't apply, as the plain text would be encrypted unchanged, or not as intended
+    pub(crate) fn apply(&self, plain_text: &[u8]) -> Result<Vec<u8>> {
+        match self {
+            Self::Substitution {
+                regex,
+                replacement,
+                global,
+            } => {
+                if !regex.is_match(plain_text) {
+                    return Err(anyhow!("`{}` doesn't match the plain text", regex));
+                }
+                let limit = if *global { 0 } else { 1 };
+                Ok(regex
+                    .replacen(plain_text, limit, replacement.as_slice())
+                    .into_owned())
+            }
+            Self::JsonPatch(operations) => {
+                let mut document: Json = serde_json::from_slice(plain_text)
+                    .context("The plain text is not JSON, so it can't be patched")?;
+                for operation in operations {
+                    operation.apply(&mut document)?;
+                }
+                serde_json::to_vec(&document).context("Serializing the patched plain text failed")
+            }
+        }
+    }
+}
+
+impl PatchOperation {
+    fn apply(&self, document: &mut Json) -> Result<()> {
+        match self {
+            Self::Add { path, value } => add(document, path, value.clone()),
+            Self::Remove { path } => remove(document, path).map(|_| ()),
+            // in place, so the keys of objects keep their order
+            Self::Replace { path, value } => {
+                *document
+                    .pointer_mut(path)
+                    .ok_or_else(|| anyhow!("JSON Patch: `{}` doesn't exist", path))? =
+                    value.clone();
+                Ok(())
+            }
+            Self::Move { from, path } => {
+                let value = remove(document, from)?;
+                add(document, path, value)
+            }
+            Self::Copy { from, path } => {
+                let value = document
+                    .pointer(from)
+                    .cloned()
+                    .ok_or_else(|| anyhow!("JSON Patch: `{}` doesn't exist", from))?;
+                add(document, path, value)
+            }
+            Self::Test { path, value } => match document.pointer(path) {
+                Some(found) if found == value => Ok(()),
+                Some(found) => Err(anyhow!(
+                    "JSON Patch: `{}` is {}, not {}",
+                    path,
+                    found,
+                    value
+                )),
+                None => Err(anyhow!("JSON Patch: `{}` doesn't exist", path)),
+            },
+        }
+    }
+}
+
+fn add(document: &mut Json, path: &str, value: Json) -> Result<()> {
+    let (parent, key) = match split_pointer(path)? {
+        Some(split) => split,
+        None => {
+            *document = value;
+            return Ok(());
+        }
+    };
+
+    match document.pointer_mut(parent) {
+        Some(Json::Object(object)) => {
+            let _ = object.insert(key, value);
+        }
+        Some(Json::Array(array)) => {
+            let idx = match key.as_str() {
+                "-" => array.len(),
+                idx => array_idx(path, idx, array.len() + 1)?,
+            };
+            array.insert(idx, value);
+        }
+        Some(_) => {
+            return Err(anyhow!(
+                "JSON Patch: `{}` is neither an object nor an array",
+                parent
+            ))
+        }
+        None => return Err(anyhow!("JSON Patch: `{}` doesn't exist", parent)),
+    }
+    Ok(())
+}
+
+fn remove(document: &mut Json, path: &str) -> Result<Json> {
+    let (parent, key) = split_pointer(path)?
+        .ok_or_else(|| anyhow!("JSON Patch: the whole plain text can't be removed"))?;
+
+    match document.pointer_mut(parent) {
+        // `swap_remove` would move the last key into its place
+        Some(Json::Object(object)) => object
+            .shift_remove(&key)
+            .ok_or_else(|| anyhow!("JSON Patch: `{}` doesn't exist", path)),
+        Some(Json::Array(array)) => {
+            let idx = array_idx(path, &key, array.len())?;
+            Ok(array.remove(idx))
+        }
+        _ => Err(anyhow!("JSON Patch: `{}` doesn't exist", path)),
+    }
+}
+
+/// JSON pointer to the parent of `path`, and the unescaped key in it. `None` for the pointer to the whole document
+fn split_pointer(path: &str) -> Result<Option<(&str, String)>> {
+    if path.is_empty() {
+        return Ok(None);
+    }
+
+    let (parent, key) = path
+        .rsplit_once('/')
+        .filter(|_| path.starts_with('/'))
+        .ok_or_else(|| anyhow!("JSON Patch: `{}` is not a JSON pointer, e.g. `/role`", path))?;
+    Ok(Some((parent, key.replace("~1", "/").replace("~0", "~"))))
+}
+
+fn array_idx(path: &str, idx: &str, bound: usize) -> Result<usize> {
+    idx.parse()
+        .ok()
+        .filter(|idx| *idx < bound)
+        .ok_or_else(|| anyhow!("JSON Patch: `{}` is not an index of the array", path))
+}
+
+impl FromStr for PlainTextEdit {
+    type Err = anyhow::Error;
+
+    fn from_str(input: &str) -> Result<Self> {
+        let trimmed = input.trim_start();
+        if trimmed.starts_with('[') {
+            return serde_json::from_str(trimmed)
+                .map(Self::JsonPatch)
+                .map_err(|e| anyhow!("`{}` is not a valid JSON Patch: {}", input, e));
+        }
+        // a single operation, without the array around it
+        if trimmed.starts_with('{') {
+            return serde_json::from_str(trimmed)
+                .map(|operation| Self::JsonPatch(vec![operation]))
+                .map_err(|e| anyhow!("`{}` is not a valid JSON Patch operation: {}", input, e));
+        }
+
+        parse_substitution(input).ok_or_else(|| {
+            anyhow!(
+                "`{}` is neither a substitution, e.g. `s/\"role\":\"user\"/\"role\":\"admin\"/`, nor a JSON Patch, e.g. `[{{\"op\":\"replace\",\"path\":\"/role\",\"value\":\"admin\"}}]`",
+                input
+            )
+        })?
+    }
+}
+
+/// `s/regex/replacement/flags`, with any delimiter instead of `/`. `None` if `input` isn't shaped like one
+fn parse_substitution(input: &str) -> Option<Result<PlainTextEdit>> {
+    let mut chars = input.strip_prefix('s')?.chars();
+    let delimiter = chars
+        .next()
+        .filter(|delimiter| !delimiter.is_alphanumeric() && !delimiter.is_whitespace())
+        .filter(|delimiter| *delimiter != '\\')?;
+
+    // split at the delimiters which aren't escaped. An escaped delimiter stands for itself
+    let mut parts = vec![String::new()];
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => match chars.next() {
+                Some(escaped) if escaped == delimiter => parts.last_mut()?.push(escaped),
+                Some(escaped) => {
+                    parts.last_mut()?.push('\\');
+                    parts.last_mut()?.push(escaped);
+                }
+                None => parts.last_mut()?.push('\\'),
+            },
+            c if c == delimiter => parts.push(String::new()),
+            c => parts.last_mut()?.push(c),
+        }
+    }
+    let (pattern, replacement, flags) = match &parts[..] {
+        [pattern, replacement, flags] => (pattern, replacement, flags),
+        _ => return None,
+    };
+
+    Some(substitution(pattern, replacement, flags))
+}
+
+fn substitution(pattern: &str, replacement: &str, flags: &str) -> Result<PlainTextEdit> {
+    let mut global = false;
+    let mut case_insensitive = false;
+    for flag in flags.chars() {
+        match flag {
+            'g' => global = true,
+            'i' | 'I' => case_insensitive = true,
+            flag => {
+                return Err(anyhow!(
+                    "`{}` is not a flag of substitutions. Expected any of: [g, i]",
+                    flag
+                ))
+            }
+        }
+    }
+
+    let regex = RegexBuilder::new(pattern)
+        .case_insensitive(case_insensitive)
+        .build()
+        .map_err(|e| anyhow!("`{}` is not a valid regex: {}", pattern, e))?;
+
+    Ok(PlainTextEdit::Substitution {
+        regex,
+        replacement: sed_replacement(replacement).into_bytes(),
+        global,
+    })
+}
+
+/// Translate sed's `&` and `\1` into `regex`'s `${0}` and `${1}`
+fn sed_replacement(replacement: &str) -> String {
+    let mut translated = String::new();
+    let mut chars = replacement.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => match chars.next() {
+                Some(group @ '0'..='9') => translated.push_str(&format!("${{{}}}", group)),
+                Some('n') => translated.push('\n'),
+                Some('t') => translated.push('\t'),
+                Some('$') => translated.push_str("$$"),
+                Some(escaped) => translated.push(escaped),
+                None => translated.push('\\'),
+            },
+            '&' => translated.push_str("${0}"),
+            '$' => translated.push_str("$$"),
+            c => translated.push(c),
+        }
+    }
+    translated
+}
@@ -0,0 +1,44 @@
+use std::{fmt::Display, ops::Deref, str::FromStr};
+
+use anyhow::{anyhow, Context, Result};
+
+/// Factor by which a replay is faster than the recorded attack, e.g. `10x`
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct ReplaySpeed(f64);
+
+impl FromStr for ReplaySpeed {
+    type Err = anyhow::Error;
+
+    fn from_str(input: &str) -> Result<Self> {
+        let factor = input
+            .strip_suffix(['x', 'X'])
+            .unwrap_or(input)
+            .parse::<f64>()
+            .context(format!(
+                "`{}`. Expected a positive factor, e.g. `10x` or `0.5x`",
+                input
+            ))?;
+        if factor.is_finite() && factor > 0.0 {
+            Ok(Self(factor))
+        } else {
+            Err(anyhow!(
+                "`{}`. Expected a positive factor, e.g. `10x` or `0.5x`",
+                input
+            ))
+        }
+    }
+}
+
+impl Deref for ReplaySpeed {
+    type Target = f64;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl Display for ReplaySpeed {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}x", self.0)
+    }
+}
@@ -0,0 +1,106 @@
+use std::{fs, str::FromStr};
+
+use anyhow::{anyhow, Context, Result};
+use getset::Getters;
+use reqwest::{Method, Url};
+
+use crate::config::header::Header;
+
+// set by the web client, as they depend on the oracle's URL or the body, which changes with the cypher text
+const COMPUTED_HEADERS: &[&str] = &["host", "content-length", "transfer-encoding", "connection"];
+
+/// Captured HTTP request, as saved by e.g. Burp or ZAP, for `--request`. The keyword may be placed anywhere in it
+#[derive(Debug, Clone, Getters)]
+pub(crate) struct RequestTemplate {
+    #[getset(get = "pub(crate)")]
+    method: Method,
+    // path and query, e.g. `/login?next=CTEXT`
+    target: String,
+    #[getset(get = "pub(crate)")]
+    headers: Vec<Header>,
+    #[getset(get = "pub(crate)")]
+    body: Option<String>,
+}
+
+impl RequestTemplate {
+    /// The URL to send the request to: the scheme, host and port of the oracle, with the request's path and query
+    pub(crate) fn url(&self, oracle: &Url) -> Result<Url> {
+        oracle.join(&self.target).context(format!(
+            "The request's target `{}` doesn't combine with `{}` into a URL",
+            self.target, oracle
+        ))
+    }
+}
+
+impl FromStr for RequestTemplate {
+    type Err = anyhow::Error;
+
+    fn from_str(path: &str) -> Result<Self> {
+        let request = fs::read_to_string(path)
+            .context(format!("Reading the request from `{}` failed", path))?;
+        // saved with CRLF or LF line endings
+        let (head, body) = request
+            .split_once("\r\n\r\n")
+            .or_else(|| request.split_once("\n\n"))
+            .unwrap_or((&request, ""));
+        let mut lines = head
+            .lines()
+            .map(|line| line.trim_end_matches('\r'))
+            .skip_while(|line| line.trim().is_empty());
+
+        let request_line = lines
+            .next()
+            .ok_or_else(|| anyhow!("The request in `{}` is empty", path))?;
+        let mut parts = request_line.split_whitespace();
+        let (method, target) = match (parts.next(), parts.next()) {
+            (Some(method), Some(target)) => (method, target),
+            _ => {
+                return Err(anyhow!(
+                    "`{}` doesn't start with a request line, e.g. `POST /login HTTP/1.1`",
+                    path
+                ))
+            }
+        };
+        let method =
+            Method::from_str(method).context(format!("`{}` is not an HTTP method", method))?;
+        // absolute-form, as sent to proxies
+        let target = match Url::parse(target) {
+            Ok(url) => match url.query() {
+                Some(query) => format!("{}?{}", url.path(), query),
+                None => url.path().to_string(),
+            },
+            Err(_) if target.starts_with('/') => target.to_string(),
+            Err(_) => {
+                return Err(anyhow!(
+                    "The request's target `{}` is neither a path nor a URL",
+                    target
+                ))
+            }
+        };
+
+        let headers = lines
+            // pseudo-headers of HTTP/2 requests
+            .filter(|line| !line.starts_with(':'))
+            .map(Header::from_str)
+            .filter(|header| {
+                header.as_ref().map_or(true, |header| {
+                    !COMPUTED_HEADERS.contains(&header.name().to_lowercase().as_str())
+                })
+            })
+            .collect::<Result<Vec<_>>>()
+            .context(format!(
+                "The headers of the request in `{}` are invalid",
+                path
+            ))?;
+
+        // files usually end with a newline, which isn't part of the body
+        let body = body.trim_end_matches(['\r', '\n']);
+
+        Ok(Self {
+            method,
+            target,
+            headers,
+            body: (!body.is_empty()).then(|| body.to_string()),
+        })
+    }
+}
@@ -0,0 +1,52 @@
+use std::{fmt::Display, str::FromStr};
+
+use anyhow::{anyhow, Result};
+use itertools::Itertools;
+
+/// How the plain texts of a token's segments are reported.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub(crate) enum SegmentOutput {
+    // a labelled result per segment
+    #[default]
+    Separate,
+    // a line per segment, without padding
+    Lines,
+    // a single result, segments without padding joined by a delimiter
+    Delimited,
+}
+
+impl SegmentOutput {
+    fn variants() -> &'static [Self] {
+        &[Self::Separate, Self::Lines, Self::Delimited]
+    }
+}
+
+impl Display for SegmentOutput {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SegmentOutput::Separate => write!(f, "separate"),
+            SegmentOutput::Lines => write!(f, "lines"),
+            SegmentOutput::Delimited => write!(f, "delimited"),
+        }
+    }
+}
+
+impl FromStr for SegmentOutput {
+    type Err = anyhow::Error;
+
+    fn from_str(input: &str) -> Result<Self> {
+        match input.to_lowercase().as_str() {
+            "separate" | "labelled" | "labeled" => Ok(Self::Separate),
+            "lines" | "line" => Ok(Self::Lines),
+            "delimited" | "joined" | "join" => Ok(Self::Delimited),
+            _ => Err(anyhow!(
+                "`{}` is not a segment output style. Expected one of: [{}]",
+                input,
+                Self::variants()
+                    .iter()
+                    .map(|variant| variant.to_string())
+                    .join(", ")
+            )),
+        }
+    }
+}
@@ -0,0 +1,53 @@
+use std::{fmt::Display, path::PathBuf, str::FromStr};
+
+use anyhow::{anyhow, Result};
+
+const LOCAL_SYSLOG_SOCKET: &str = "/dev/log";
+
+/// Where log lines are sent to, besides the TUI and log file, to feed central logging.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum SyslogDestination {
+    // Unix datagram socket of the local syslog daemon
+    Local(PathBuf),
+    // `<host>:<port>` of a remote syslog server
+    Udp(String),
+    // systemd's journal, with native fields
+    Journald,
+}
+
+impl Display for SyslogDestination {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SyslogDestination::Local(socket) => write!(f, "{}", socket.display()),
+            SyslogDestination::Udp(address) => write!(f, "udp://{}", address),
+            SyslogDestination::Journald => write!(f, "journald"),
+        }
+    }
+}
+
+impl FromStr for SyslogDestination {
+    type Err = anyhow::Error;
+
+    fn from_str(input: &str) -> Result<Self> {
+        match input.to_lowercase().as_str() {
+            "local" => return Ok(Self::Local(PathBuf::from(LOCAL_SYSLOG_SOCKET))),
+            "journald" | "journal" => return Ok(Self::Journald),
+            _ => {}
+        }
+
+        if input.starts_with('/') {
+            return Ok(Self::Local(PathBuf::from(input)));
+        }
+
+        let address = input.strip_prefix("udp://").unwrap_or(input);
+        match address.rsplit_once(':') {
+            Some((host, port)) if !host.is_empty() && port.parse::<u16>().is_ok() => {
+                Ok(Self::Udp(address.to_string()))
+            }
+            _ => Err(anyhow!(
+                "`{}` is not a syslog destination. Expected one of: [local, journald, <socket path>, udp://<host>:<port>]",
+                input
+            )),
+        }
+    }
+}
@@ -0,0 +1,345 @@
+use std::{ffi::OsString, fs};
+
+use anyhow::{anyhow, Context, Result};
+
+use crate::{config::cypher_text_input::CypherTextInput, presets};
+
+// short options of `curl` which take a value, and the long option each stands for
+const SHORT_OPTIONS_WITH_VALUE: &[(char, &str)] = &[
+    ('X', "--request"),
+    ('H', "--header"),
+    ('b', "--cookie"),
+    ('d', "--data"),
+    ('u', "--user"),
+    ('e', "--referer"),
+    ('A', "--user-agent"),
+    ('x', "--proxy"),
+    ('U', "--proxy-user"),
+    ('F', "--form"),
+    ('T', "--upload-file"),
+    ('o', "--output"),
+    ('w', "--write-out"),
+    ('m', "--max-time"),
+];
+const SHORT_FLAGS: &[(char, &str)] = &[
+    ('G', "--get"),
+    ('I', "--head"),
+    ('k', "--insecure"),
+    ('L', "--location"),
+    ('s', "--silent"),
+    ('S', "--show-error"),
+    ('v', "--verbose"),
+    ('i', "--include"),
+    ('g', "--globoff"),
+    ('f', "--fail"),
+    ('N', "--no-buffer"),
+    ('#', "--progress-bar"),
+];
+// options which only change how `curl` itself behaves, e.g. its output. They don't affect the request
+const IGNORED_FLAGS: &[&str] = &[
+    "--compressed",
+    "--silent",
+    "--show-error",
+    "--verbose",
+    "--include",
+    "--globoff",
+    "--fail",
+    "--no-buffer",
+    "--progress-bar",
+    "--no-progress-meter",
+    "--path-as-is",
+    "--http1.0",
+    "--http1.1",
+];
+const IGNORED_OPTIONS_WITH_VALUE: &[&str] = &[
+    "--output",
+    "--write-out",
+    "--max-time",
+    "--connect-timeout",
+    "--retry",
+];
+
+/// Request described by a `curl` command line
+#[derive(Default)]
+struct CurlRequest {
+    url: Option<String>,
+    method: Option<String>,
+    headers: Vec<String>,
+    cookies: Vec<String>,
+    data: Vec<String>,
+    // `--get`: the data is sent in the URL's query instead
+    data_in_query: bool,
+    user_agent: Option<String>,
+    proxy: Option<String>,
+    proxy_credentials: Option<String>,
+    insecure: bool,
+    redirect: bool,
+    // `--http2`, `--http2-prior-knowledge` or `--http3`, as a value of `--http-version`
+    http_version: Option<&'static str>,
+}
+
+/// Insert the options of `web` which `--from-curl`'s command line stands for, e.g. of a browser's "copy as cURL", right after the sub-command. Options given on the command line override them.
+/// `--from-curl` itself is kept, so that the cypher text in it is replaced by the keyword once parsed. See `locate_cypher_text`
+pub(super) fn expand(args: impl IntoIterator<Item = impl Into<OsString>>) -> Result<Vec<OsString>> {
+    let mut args: Vec<OsString> = args.into_iter().map(Into::into).collect();
+
+    let own_args = presets::own_args_end(&args);
+    let command = match presets::option_value(&args[..own_args], "--from-curl") {
+        Some(command) => command.to_string(),
+        None => return Ok(args),
+    };
+
+    // other sub-commands reject `--from-curl` itself, rather than the options it stands for
+    if let Some(web_options_start) = presets::web_options_start(&args[..own_args]) {
+        let options = parse(&command).context("`--from-curl` is invalid")?;
+        args.splice(
+            web_options_start..web_options_start,
+            options.into_iter().map(OsString::from),
+        );
+    }
+
+    Ok(args)
+}
+
+/// Replace the cypher text, as copied along with the request, by the keyword. URL encoded occurrences are replaced too
+pub(super) fn locate_cypher_text(
+    part: &str,
+    cypher_text: &[CypherTextInput],
+    keyword: &str,
+) -> String {
+    cypher_text.iter().fold(part.to_string(), |part, chunk| {
+        part.replace(&*urlencoding::encode(chunk), keyword)
+            .replace(&**chunk, keyword)
+    })
+}
+
+/// Parse a `curl` command line into the options of `web`
+fn parse(command: &str) -> Result<Vec<String>> {
+    let words = shell_words::split(command).context("Splitting the command line failed")?;
+    let mut words = words.into_iter();
+    match words.next() {
+        Some(program) if program.trim_end_matches(".exe").ends_with("curl") => {}
+        _ => return Err(anyhow!("Expected a command line starting with `curl`")),
+    }
+
+    let mut request = CurlRequest::default();
+    while let Some(word) = words.next() {
+        for (option, attached_value) in split_option(&word)? {
+            let mut value = || {
+                attached_value
+                    .clone()
+                    .or_else(|| words.next())
+                    .ok_or_else(|| anyhow!("`{}` misses its value", option))
+            };
+
+            match option.as_str() {
+                "" => request.set_url(word.clone())?,
+                "--url" => request.set_url(value()?)?,
+                "--request" => request.method = Some(value()?),
+                "--head" => request.method = Some("HEAD".to_string()),
+                "--header" => request.headers.push(value()?),
+                "--cookie" => {
+                    let cookie = value()?;
+                    if !cookie.contains('=') {
+                        return Err(anyhow!(
+                            "`--cookie {}` reads cookies from a file. Pass them as `<name>=<value>` instead",
+                            cookie
+                        ));
+                    }
+                    request.cookies.push(cookie);
+                }
+                "--data" | "--data-ascii" => {
+                    let data = value()?;
+                    // as `curl` does, newlines of files are stripped
+                    let data = match data.strip_prefix('@') {
+                        Some(path) => read(path)?.replace(['\r', '\n'], ""),
+                        None => data,
+                    };
+                    request.data.push(data);
+                }
+                "--data-binary" => {
+                    let data = value()?;
+                    let data = match data.strip_prefix('@') {
+                        Some(path) => read(path)?,
+                        None => data,
+                    };
+                    request.data.push(data);
+                }
+                "--data-raw" => request.data.push(value()?),
+                "--data-urlencode" => request.data.push(url_encode_data(&value()?)?),
+                "--get" => request.data_in_query = true,
+                "--user" => {
+                    let credentials = value()?;
+                    if !credentials.contains(':') {
+                        return Err(anyhow!(
+                            "`--user {}` prompts for the password. Pass the credentials as `<user>:<password>` instead",
+                            credentials
+                        ));
+                    }
+                    request.headers.push(format!(
+                        "Authorization: Basic {}",
+                        base64::encode(credentials)
+                    ));
+                }
+                "--referer" => {
+                    let referer = value()?;
+                    let referer = referer.trim_end_matches(";auto");
+                    if !referer.is_empty() {
+                        request.headers.push(format!("Referer: {}", referer));
+                    }
+                }
+                "--user-agent" => request.user_agent = Some(value()?),
+                "--proxy" => {
+                    let proxy = value()?;
+                    // `curl` defaults to an HTTP proxy
+                    request.proxy = Some(if proxy.contains("://") {
+                        proxy
+                    } else {
+                        format!("http://{}", proxy)
+                    });
+                }
+                "--proxy-user" => request.proxy_credentials = Some(value()?),
+                "--insecure" => request.insecure = true,
+                "--location" => request.redirect = true,
+                "--http2" => request.http_version = Some("2"),
+                "--http2-prior-knowledge" => request.http_version = Some("2-prior-knowledge"),
+                "--http3" | "--http3-only" => request.http_version = Some("3"),
+                "--form" | "--form-string" | "--upload-file" => {
+                    return Err(anyhow!(
+                        "`{}` isn't supported. Place the cypher text with `--data` or `--header` instead",
+                        option
+                    ))
+                }
+                _ if IGNORED_FLAGS.contains(&option.as_str()) => {}
+                _ if IGNORED_OPTIONS_WITH_VALUE.contains(&option.as_str()) => {
+                    value()?;
+                }
+                _ => return Err(anyhow!("The `curl` option `{}` isn't supported", option)),
+            }
+        }
+    }
+
+    request.into_options()
+}
+
+/// Split a word of the command line into its options and their values, if attached. Short flags may be combined, e.g. `-sSL`, and short options may have their value attached, e.g. `-XPOST`.
+/// A URL gives an empty option
+fn split_option(word: &str) -> Result<Vec<(String, Option<String>)>> {
+    if word.starts_with("--") {
+        return Ok(vec![(word.to_string(), None)]);
+    }
+    let shorts = match word.strip_prefix('-') {
+        Some(shorts) if !shorts.is_empty() => shorts,
+        _ => return Ok(vec![(String::new(), None)]),
+    };
+
+    let mut options = Vec::new();
+    for (idx, short) in shorts.char_indices() {
+        if let Some((_, option)) = SHORT_OPTIONS_WITH_VALUE.iter().find(|(c, _)| *c == short) {
+            let value = &shorts[idx + short.len_utf8()..];
+            options.push((
+                option.to_string(),
+                (!value.is_empty()).then(|| value.to_string()),
+            ));
+            // the rest of the word is the value
+            break;
+        }
+        match SHORT_FLAGS.iter().find(|(c, _)| *c == short) {
+            Some((_, option)) => options.push((option.to_string(), None)),
+            None => return Err(anyhow!("The `curl` option `-{}` isn't supported", short)),
+        }
+    }
+    Ok(options)
+}
+
+/// Data of `--data-urlencode`, as `<content>`, `=<content>`, `<name>=<content>`, `@<file>` or `<name>@<file>`
+fn url_encode_data(data: &str) -> Result<String> {
+    let (name, content) = if let Some((name, content)) = data.split_once('=') {
+        (name, content.to_string())
+    } else if let Some((name, path)) = data.split_once('@') {
+        (name, read(path)?)
+    } else {
+        ("", data.to_string())
+    };
+
+    let content = urlencoding::encode(&content);
+    Ok(if name.is_empty() {
+        content.into_owned()
+    } else {
+        format!("{}={}", name, content)
+    })
+}
+
+fn read(path: &str) -> Result<String> {
+    fs::read_to_string(path).context(format!("Reading the data from `{}` failed", path))
+}
+
+impl CurlRequest {
+    fn set_url(&mut self, url: String) -> Result<()> {
+        if self.url.is_some() {
+            return Err(anyhow!("Only a single URL can be questioned"));
+        }
+        self.url = Some(url);
+        Ok(())
+    }
+
+    /// Options of `web`, with their values attached, as values may start with a `-`
+    fn into_options(self) -> Result<Vec<String>> {
+        let mut url = self
+            .url
+            .ok_or_else(|| anyhow!("The command line doesn't contain a URL"))?;
+        // `curl` defaults to HTTP
+        if !url.contains("://") {
+            url = format!("http://{}", url);
+        }
+
+        let mut options = Vec::new();
+        if !self.data.is_empty() {
+            let data = self.data.join("&");
+            if self.data_in_query {
+                let separator = if url.contains('?') { '&' } else { '?' };
+                url = format!("{}{}{}", url, separator, data);
+            } else {
+                options.push(format!("--data={}", data));
+            }
+        }
+        options.push(format!("--oracle={}", url));
+        if let Some(method) = self.method {
+            options.push(format!("--method={}", method));
+        }
+
+        for header in self.headers {
+            match header.split_once(':') {
+                // removes a header which `curl` would add itself
+                Some((_, value)) if value.trim().is_empty() => {}
+                Some(_) => options.push(format!("--header={}", header)),
+                // `<name>;` sends the header without value
+                None => options.push(format!("--header={}:", header.trim_end_matches(';'))),
+            }
+        }
+        if !self.cookies.is_empty() {
+            options.push(format!("--header=Cookie: {}", self.cookies.join("; ")));
+        }
+
+        if let Some(user_agent) = self.user_agent {
+            options.push(format!("--user-agent={}", user_agent));
+        }
+        if let Some(proxy) = self.proxy {
+            options.push(format!("--proxy={}", proxy));
+        }
+        if let Some(proxy_credentials) = self.proxy_credentials {
+            options.push(format!("--proxy-credentials={}", proxy_credentials));
+        }
+        if self.insecure {
+            options.push("--insecure".to_string());
+        }
+        if self.redirect {
+            options.push("--redirect".to_string());
+        }
+        if let Some(http_version) = self.http_version {
+            options.push(format!("--http-version={}", http_version));
+        }
+
+        Ok(options)
+    }
+}
@@ -0,0 +1,280 @@
+use std::{
+    collections::HashMap,
+    io::{BufRead, BufReader, Read, Write},
+    net::{TcpListener, TcpStream},
+    sync::Arc,
+    thread,
+};
+
+use anyhow::{anyhow, Context, Result};
+use openssl::{
+    rand::rand_bytes,
+    symm::{Cipher, Crypter, Mode},
+};
+
+use crate::{
+    block::padding_scheme::PaddingScheme,
+    cli::DemoServerCli,
+    config::{demo_error_style::DemoErrorStyle, encoding_option::EncodingOption},
+    cypher_text::encode::Encoding,
+};
+
+// query parameter which holds the token
+const TOKEN_PARAMETER: &str = "token";
+
+/// Deliberately vulnerable CBC endpoint. Hands out encrypted tokens on `/`, and checks tokens given as `?token=` on any path, leaking whether their padding is valid.
+struct DemoServer {
+    cipher: Cipher,
+    key: Vec<u8>,
+    block_size: usize,
+    encoding: Encoding,
+    error_style: DemoErrorStyle,
+    padding_scheme: PaddingScheme,
+    secret: String,
+}
+
+/// What the server thinks of a token. Only `PaddingError` may be told apart by the attack
+enum Verdict {
+    Welcome,
+    PaddingError,
+    InvalidToken,
+}
+
+/// Run the demo server until the process is stopped. Every connection is handled on its own thread.
+pub(super) fn demo_server(demo_server_cli: DemoServerCli) -> Result<()> {
+    let (cipher, cipher_name) = match **demo_server_cli.block_size() {
+        16 => (Cipher::aes_128_cbc(), "AES-128"),
+        8 => (Cipher::des_ede3_cbc(), "Triple DES"),
+        block_size => {
+            return Err(anyhow!(
+                "The demo server supports block sizes 8 (Triple DES) and 16 (AES), not {}",
+                block_size
+            ))
+        }
+    };
+    if let EncodingOption::Auto = demo_server_cli.encoding() {
+        return Err(anyhow!(
+            "The demo server needs a specific encoding: hex, base64, or base64url"
+        ));
+    }
+
+    let mut key = vec![0; cipher.key_len()];
+    rand_bytes(&mut key).context("Generating a key failed")?;
+    let server = Arc::new(DemoServer {
+        cipher,
+        key,
+        block_size: **demo_server_cli.block_size() as usize,
+        encoding: Encoding::try_from(demo_server_cli.encoding())?,
+        error_style: *demo_server_cli.error_style(),
+        padding_scheme: *demo_server_cli.padding_scheme(),
+        secret: demo_server_cli.secret().clone(),
+    });
+
+    let listener = TcpListener::bind(demo_server_cli.listen()).context(format!(
+        "Listening on `{}` failed",
+        demo_server_cli.listen()
+    ))?;
+    let token = server.issue_token()?;
+    println!(
+        "Vulnerable demo server listening on http://{}/ ({}, {} padding, {} error style)",
+        demo_server_cli.listen(),
+        cipher_name,
+        demo_server_cli.padding_scheme(),
+        demo_server_cli.error_style()
+    );
+    println!("Key:   {}", hex::encode(&server.key));
+    println!("Token: {}", token);
+    println!(
+        "Try:   rustpad web --oracle 'http://{}/?{}=CTEXT' --decrypt '{}' --block-size {} --padding {}",
+        demo_server_cli.listen(),
+        TOKEN_PARAMETER,
+        token,
+        server.block_size,
+        demo_server_cli.padding_scheme()
+    );
+
+    for stream in listener.incoming() {
+        let stream = match stream {
+            Ok(stream) => stream,
+            Err(e) => {
+                eprintln!("Accepting a connection failed: {}", e);
+                continue;
+            }
+        };
+        let server = server.clone();
+        thread::spawn(move || {
+            if let Err(e) = server.handle_connection(stream) {
+                eprintln!("{:#}", e);
+            }
+        });
+    }
+
+    Ok(())
+}
+
+impl DemoServer {
+    /// The secret, encrypted with a fresh IV, which is prepended
+    fn issue_token(&self) -> Result<String> {
+        let mut iv = vec![0; self.block_size];
+        rand_bytes(&mut iv).context("Generating an IV failed")?;
+
+        let plain_text = self
+            .padding_scheme
+            .pad(self.secret.as_bytes(), self.block_size);
+        let cypher_text = self.crypt(Mode::Encrypt, &iv, &plain_text)?;
+
+        Ok(self
+            .encoding
+            .encode(&iv.into_iter().chain(cypher_text).collect::<Vec<_>>()))
+    }
+
+    fn judge(&self, token: &str) -> Verdict {
+        let data = match self.decode(token) {
+            Some(data)
+                if data.len() >= 2 * self.block_size && data.len() % self.block_size == 0 =>
+            {
+                data
+            }
+            _ => return Verdict::InvalidToken,
+        };
+        let (iv, cypher_text) = data.split_at(self.block_size);
+        let plain_text = match self.crypt(Mode::Decrypt, iv, cypher_text) {
+            Ok(plain_text) => plain_text,
+            Err(_) => return Verdict::InvalidToken,
+        };
+
+        match self.padding_scheme.unpad(&plain_text, self.block_size) {
+            None => Verdict::PaddingError,
+            Some(plain_text) if plain_text == self.secret.as_bytes() => Verdict::Welcome,
+            Some(_) => Verdict::InvalidToken,
+        }
+    }
+
+    fn decode(&self, token: &str) -> Option<Vec<u8>> {
+        match self.encoding {
+            Encoding::Hex => hex::decode(token).ok(),
+            Encoding::Base64 { .. } => base64::decode_config(token, base64::STANDARD).ok(),
+            Encoding::Base64Url { .. } => base64::decode_config(token, base64::URL_SAFE).ok(),
+        }
+    }
+
+    /// Raw CBC, as padding is handled by `padding_scheme`
+    fn crypt(&self, mode: Mode, iv: &[u8], data: &[u8]) -> Result<Vec<u8>> {
+        let mut crypter = Crypter::new(self.cipher, mode, &self.key, Some(iv))?;
+        crypter.pad(false);
+
+        let mut output = vec![0; data.len() + self.block_size];
+        let mut length = crypter.update(data, &mut output)?;
+        length += crypter.finalize(&mut output[length..])?;
+        output.truncate(length);
+        Ok(output)
+    }
+
+    /// Answer requests until the client closes the connection, as clients keep connections alive
+    fn handle_connection(&self, stream: TcpStream) -> Result<()> {
+        let mut reader = BufReader::new(stream.try_clone()?);
+        let mut stream = stream;
+
+        loop {
+            let mut request_line = String::new();
+            if reader.read_line(&mut request_line)? == 0 {
+                return Ok(());
+            }
+
+            let mut headers = HashMap::new();
+            loop {
+                let mut header = String::new();
+                if reader.read_line(&mut header)? == 0 {
+                    return Ok(());
+                }
+                let header = header.trim_end();
+                if header.is_empty() {
+                    break;
+                }
+                if let Some((name, value)) = header.split_once(':') {
+                    headers.insert(name.trim().to_lowercase(), value.trim().to_string());
+                }
+            }
+            // the body is ignored, but must be consumed to get to the next request
+            if let Some(length) = headers
+                .get("content-length")
+                .and_then(|length| length.parse::<u64>().ok())
+            {
+                std::io::copy(&mut (&mut reader).take(length), &mut std::io::sink())?;
+            }
+
+            let target = request_line
+                .split_whitespace()
+                .nth(1)
+                .ok_or_else(|| anyhow!("Malformed request: `{}`", request_line.trim_end()))?;
+            stream.write_all(&self.respond(target)?)?;
+
+            if headers
+                .get("connection")
+                .map(|connection| connection.eq_ignore_ascii_case("close"))
+                .unwrap_or(false)
+            {
+                return Ok(());
+            }
+        }
+    }
+
+    fn respond(&self, target: &str) -> Result<Vec<u8>> {
+        let (path, query) = target.split_once('?').unwrap_or((target, ""));
+        let token = query
+            .split('&')
+            .filter_map(|parameter| parameter.split_once('='))
+            .find(|(name, _)| *name == TOKEN_PARAMETER)
+            .map(|(_, value)| urlencoding::decode(value).map(|value| value.into_owned()))
+            .transpose()
+            .unwrap_or(None);
+
+        let (status, location, body) = match (token, path) {
+            (None, "/") => (
+                "200 OK",
+                None,
+                format!(
+                    "Your token: {}\nCheck it at /?{}=<token>\n",
+                    self.issue_token()?,
+                    TOKEN_PARAMETER
+                ),
+            ),
+            (None, "/error") => ("200 OK", None, "Something went wrong\n".to_string()),
+            (None, _) => ("404 Not Found", None, "Not found\n".to_string()),
+            (Some(token), _) => match (self.judge(&token), self.error_style) {
+                (Verdict::Welcome, _) => ("200 OK", None, "Welcome back\n".to_string()),
+                (Verdict::InvalidToken, DemoErrorStyle::Body) => {
+                    ("200 OK", None, "Invalid token\n".to_string())
+                }
+                (Verdict::InvalidToken, _) => {
+                    ("403 Forbidden", None, "Invalid token\n".to_string())
+                }
+                (Verdict::PaddingError, DemoErrorStyle::Status) => (
+                    "500 Internal Server Error",
+                    None,
+                    "Internal server error\n".to_string(),
+                ),
+                (Verdict::PaddingError, DemoErrorStyle::Body) => (
+                    "200 OK",
+                    None,
+                    "Padding is invalid and cannot be removed\n".to_string(),
+                ),
+                (Verdict::PaddingError, DemoErrorStyle::Redirect) => {
+                    ("302 Found", Some("/error"), String::new())
+                }
+            },
+        };
+
+        let mut response = format!(
+            "HTTP/1.1 {}\r\nContent-Type: text/plain\r\nContent-Length: {}\r\n",
+            status,
+            body.len()
+        );
+        if let Some(location) = location {
+            response.push_str(&format!("Location: {}\r\n", location));
+        }
+        response.push_str("\r\n");
+        response.push_str(&body);
+        Ok(response.into_bytes())
+    }
+}
@@ -0,0 +1,179 @@
+use std::{
+    collections::HashMap,
+    fs::{File, OpenOptions},
+    io::Write,
+    path::Path,
+    sync::Mutex,
+    time::Instant,
+};
+
+use anyhow::{Context, Result};
+use log::error;
+use serde::Serialize;
+
+use crate::{
+    block::Block,
+    logging::LOG_TARGET,
+    timeline::Phase,
+    tui::ui_event::{UiControlEvent, UiDecryptionEvent, UiEncryptionEvent, UiEvent},
+};
+
+/// Appends the attack's progress to a file as JSON lines, as it happens. Lets wrapper tools and CI pipelines monitor long-running attacks without scraping the TUI.
+pub(super) struct EventStream {
+    start: Instant,
+    state: Mutex<State>,
+}
+
+struct State {
+    file: File,
+    // (bytes_finished, bytes_to_finish) per phase
+    progress: HashMap<Phase, (usize, usize)>,
+    // the UI is initialised again after calibration, for the same cypher text
+    decrypting: Vec<Block>,
+    // writing failed before. Reported once, instead of for every event
+    broken: bool,
+}
+
+#[derive(Serialize)]
+struct StreamedEvent {
+    elapsed_ms: u128,
+    #[serde(flatten)]
+    kind: StreamedEventKind,
+}
+
+#[derive(Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub(super) enum StreamedEventKind {
+    Started {
+        phase: Phase,
+        blocks: usize,
+    },
+    BlockSolved {
+        phase: Phase,
+        block: usize,
+        // hex. The forged block which made the padding valid
+        forged_block: String,
+        intermediate: String,
+    },
+    Progress {
+        phase: Phase,
+        bytes_finished: usize,
+        bytes_to_finish: usize,
+    },
+    Error {
+        message: String,
+        // class of the failure, if it's a `RustpadError`
+        #[serde(skip_serializing_if = "Option::is_none")]
+        kind: Option<&'static str>,
+    },
+    Finished {
+        exit_code: i32,
+    },
+}
+
+impl EventStream {
+    /// Events are appended, so a file of a previous run is kept. Any path works, e.g. `/dev/fd/3` to stream to an inherited file descriptor
+    pub(super) fn open(path: &Path) -> Result<Self> {
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .context(format!("Events file `{}` failed to open", path.display()))?;
+
+        Ok(Self {
+            start: Instant::now(),
+            state: Mutex::new(State {
+                file,
+                progress: HashMap::new(),
+                decrypting: Vec::new(),
+                broken: false,
+            }),
+        })
+    }
+
+    /// Stream the events sent to the UI which mark progress. Guesses about single bytes are too frequent to be of use
+    pub(super) fn record_ui_event(&self, event: &UiEvent) {
+        let kind = match event {
+            UiEvent::Decryption(UiDecryptionEvent::InitDecryption(cypher_text_blocks)) => {
+                let mut state = self.state.lock().unwrap();
+                if state.decrypting == *cypher_text_blocks {
+                    return;
+                }
+                state.decrypting = cypher_text_blocks.clone();
+                StreamedEventKind::Started {
+                    phase: Phase::Decryption,
+                    // the IV isn't decrypted
+                    blocks: cypher_text_blocks.len() - 1,
+                }
+            }
+            UiEvent::Encryption(UiEncryptionEvent::InitEncryption(plain_text_blocks, _)) => {
+                StreamedEventKind::Started {
+                    phase: Phase::Encryption,
+                    blocks: plain_text_blocks.len(),
+                }
+            }
+            UiEvent::Decryption(UiDecryptionEvent::BlockSolved(forged_block, block)) => {
+                StreamedEventKind::BlockSolved {
+                    phase: Phase::Decryption,
+                    block: *block,
+                    forged_block: forged_block.to_hex(),
+                    intermediate: forged_block.to_intermediate().to_hex(),
+                }
+            }
+            UiEvent::Encryption(UiEncryptionEvent::BlockSolved(forged_block, block)) => {
+                StreamedEventKind::BlockSolved {
+                    phase: Phase::Encryption,
+                    block: *block,
+                    forged_block: forged_block.to_hex(),
+                    intermediate: forged_block.to_intermediate().to_hex(),
+                }
+            }
+            UiEvent::Control(UiControlEvent::IndicateWork(decryption_bytes, encryption_bytes)) => {
+                // a new segment of the cypher text starts from scratch
+                self.state.lock().unwrap().progress = HashMap::from([
+                    (Phase::Decryption, (0, *decryption_bytes)),
+                    (Phase::Encryption, (0, *encryption_bytes)),
+                ]);
+                return;
+            }
+            UiEvent::Control(UiControlEvent::ProgressUpdate(phase, newly_solved_bytes)) => {
+                let mut state = self.state.lock().unwrap();
+                let (bytes_finished, bytes_to_finish) = state.progress.entry(*phase).or_default();
+                *bytes_finished += newly_solved_bytes;
+                StreamedEventKind::Progress {
+                    phase: *phase,
+                    bytes_finished: *bytes_finished,
+                    bytes_to_finish: *bytes_to_finish,
+                }
+            }
+            _ => return,
+        };
+
+        self.record(kind);
+    }
+
+    pub(super) fn record(&self, kind: StreamedEventKind) {
+        let event = StreamedEvent {
+            elapsed_ms: self.start.elapsed().as_millis(),
+            kind,
+        };
+
+        let mut state = self.state.lock().unwrap();
+        if state.broken {
+            return;
+        }
+        let written = serde_json::to_string(&event)
+            .context("Serialising event failed")
+            .and_then(|line| {
+                // a single write per line, so readers never see half an event
+                state
+                    .file
+                    .write_all(format!("{}\n", line).as_bytes())
+                    .context("Writing to the events file failed")
+            });
+        if let Err(e) = written {
+            error!(target: LOG_TARGET, "{:?}", e);
+            state.broken = true;
+        }
+    }
+}
@@ -0,0 +1,178 @@
+use std::io::{self, BufRead};
+
+use anyhow::{Context, Result};
+
+use crate::{
+    block::{block_size::BlockSize, padding_scheme::PaddingScheme, Block},
+    cli::ExplainCli,
+    config::base64_padding::Base64Padding,
+    cypher_text::{
+        encode::{AmountBlocksTrait, Encode},
+        CypherText,
+    },
+    key_verification::KeyVerifier,
+};
+
+// the mock oracle decrypts with AES
+const AES_BLOCK_SIZE: u8 = 16;
+
+/// Walk through the attack against a local mock oracle, which knows the key, and annotate how each byte is found. For teaching how the attack works.
+/// The solving logic is written out plainly here, instead of reusing the multi-threaded attack, so the walkthrough reads like the maths.
+pub(super) fn explain(explain_cli: ExplainCli) -> Result<()> {
+    let block_size = BlockSize::from(AES_BLOCK_SIZE);
+    let cypher_text = CypherText::parse(
+        explain_cli.cypher_text(),
+        &block_size,
+        *explain_cli.no_iv(),
+        explain_cli.encoding(),
+        *explain_cli.no_url_encode(),
+        Base64Padding::Keep,
+    )?;
+    let oracle = MockOracle {
+        key_verifier: KeyVerifier::new(explain_cli.key().clone(), None),
+    };
+    let step_by_step = *explain_cli.step_by_step();
+    let interactive = step_by_step && atty::is(atty::Stream::Stdin);
+
+    println!(
+        "The mock oracle decrypts a cypher text with the key, and only says whether the padding of the result is valid (PKCS#7).
+CBC decryption: plain text = decrypt(block) ^ previous block. We call decrypt(block) the intermediate.
+By sending a forged \"previous block\" together with the block, and watching the oracle's verdict, the intermediate is found byte by byte, right to left.
+"
+    );
+
+    let mut plain_text = Vec::new();
+    let mut total_questions = 0;
+    for block_idx in 1..cypher_text.amount_blocks() {
+        let previous_block = &cypher_text.blocks()[block_idx - 1];
+        let block = &cypher_text.blocks()[block_idx];
+        println!(
+            "=== Block {}/{}: {} ===",
+            block_idx,
+            cypher_text.amount_blocks() - 1,
+            block.to_hex()
+        );
+
+        let mut intermediate = Block::new(&block_size);
+        for byte_idx in (0..AES_BLOCK_SIZE as usize).rev() {
+            let pad_size = AES_BLOCK_SIZE - byte_idx as u8;
+            // the bytes found so far are forced to the padding value, so only the current byte decides whether the padding is valid
+            let mut forged_block = Block::new(&block_size);
+            for idx in byte_idx + 1..AES_BLOCK_SIZE as usize {
+                forged_block[idx] = intermediate[idx] ^ pad_size;
+            }
+
+            let mut questions = 0;
+            let mut found = None;
+            for value in u8::MIN..=u8::MAX {
+                forged_block[byte_idx] = value;
+                questions += 1;
+                if !oracle.padding_valid(&forged_block, block) {
+                    continue;
+                }
+
+                // for the last byte, `0x02 0x02` etc. is also valid padding. Changing the byte before it tells these apart from `0x01`
+                if byte_idx == AES_BLOCK_SIZE as usize - 1 {
+                    let mut confirmation = forged_block.clone();
+                    confirmation[byte_idx - 1] ^= 0xff;
+                    questions += 1;
+                    if !oracle.padding_valid(&confirmation, block) {
+                        if step_by_step {
+                            println!(
+                                "  value {:#04x} gave valid padding thanks to the byte before it. Continuing the search",
+                                value
+                            );
+                        }
+                        continue;
+                    }
+                }
+
+                found = Some(value);
+                break;
+            }
+            total_questions += questions;
+
+            let value = found.context(format!(
+                "Block {}, byte {}: the mock oracle accepted no value. Is the key correct?",
+                block_idx,
+                byte_idx + 1
+            ))?;
+            intermediate[byte_idx] = value ^ pad_size;
+            let plain_text_byte = intermediate[byte_idx] ^ previous_block[byte_idx];
+
+            if step_by_step {
+                println!(
+                    "Byte {:>2}: valid padding after {} question(s)
+  forged block     {}
+  padding target   {:#04x} ({} byte(s) of padding)
+  intermediate     {:#04x} ^ {:#04x} = {:#04x}  (forged byte ^ padding)
+  plain text       {:#04x} ^ {:#04x} = {:#04x}  (intermediate ^ original previous block) {}",
+                    byte_idx + 1,
+                    questions,
+                    forged_block.to_hex(),
+                    pad_size,
+                    pad_size,
+                    value,
+                    pad_size,
+                    intermediate[byte_idx],
+                    intermediate[byte_idx],
+                    previous_block[byte_idx],
+                    plain_text_byte,
+                    printable(plain_text_byte)
+                );
+                if interactive {
+                    println!("  (press enter to continue)");
+                    io::stdin()
+                        .lock()
+                        .lines()
+                        .next()
+                        .transpose()
+                        .context("Reading from stdin failed")?;
+                }
+            }
+        }
+
+        let plain_text_block = &intermediate ^ previous_block;
+        println!(
+            "Intermediate: {}\nPlain text:   {} `{}`\n",
+            intermediate.to_hex(),
+            plain_text_block.to_hex(),
+            plain_text_block.to_ascii()
+        );
+        plain_text.push(plain_text_block);
+    }
+
+    println!(
+        "Decrypted {} block(s) with {} question(s) to the oracle, without knowing the key:\n{}",
+        plain_text.len(),
+        total_questions,
+        plain_text
+            .iter()
+            .map(|block| block.to_ascii())
+            .collect::<String>()
+    );
+    Ok(())
+}
+
+/// Oracle which knows the key, instead of a real target
+struct MockOracle {
+    key_verifier: KeyVerifier,
+}
+
+impl MockOracle {
+    fn padding_valid(&self, forged_block: &Block, block: &Block) -> bool {
+        let plain_text = &self.key_verifier.decrypt_block(block) ^ forged_block;
+        PaddingScheme::Pkcs7
+            .unpad(&plain_text, AES_BLOCK_SIZE as usize)
+            .is_some()
+    }
+}
+
+fn printable(byte_value: u8) -> String {
+    let c = byte_value as char;
+    if c.is_ascii() && !c.is_ascii_control() {
+        format!("`{}`", c)
+    } else {
+        String::new()
+    }
+}
@@ -0,0 +1,160 @@
+use std::{
+    collections::HashMap,
+    fmt::Write as _,
+    fs,
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+use anyhow::{Context, Result};
+
+use crate::{
+    block::Block,
+    timeline::Phase,
+    tui::ui_event::{UiControlEvent, UiDecryptionEvent, UiEncryptionEvent, UiEvent},
+};
+
+/// Guesses the oracle was asked for each byte, and the byte values which won, for `--guess-stats`. For research into guess orders, see `--guess-order`.
+/// Bytes solved without questioning the oracle, e.g. cached or hinted ones, aren't counted.
+pub(super) struct GuessStats {
+    path: PathBuf,
+    state: Mutex<State>,
+}
+
+#[derive(Default)]
+struct State {
+    // blocks of the cypher text being decrypted, to derive the plain text of solved blocks
+    cypher_text_blocks: Vec<Block>,
+    // (block, byte) -> guesses, of the blocks being solved. Phases don't overlap, so blocks needn't be told apart by phase
+    guesses: HashMap<(usize, u8), usize>,
+    solved_bytes: Vec<SolvedByte>,
+}
+
+struct SolvedByte {
+    phase: Phase,
+    block: usize,
+    byte: usize,
+    guesses: usize,
+    forged: u8,
+    intermediate: u8,
+    // only known when decrypting
+    plain_text: Option<u8>,
+}
+
+impl GuessStats {
+    pub(super) fn new(path: &Path) -> Self {
+        Self {
+            path: path.to_path_buf(),
+            state: Mutex::new(State::default()),
+        }
+    }
+
+    pub(super) fn record_ui_event(&self, event: &UiEvent) {
+        let mut state = self.state.lock().unwrap();
+        match event {
+            UiEvent::Decryption(UiDecryptionEvent::InitDecryption(cypher_text_blocks)) => {
+                // also sent for the next segment of the cypher text
+                state.cypher_text_blocks = cypher_text_blocks.clone();
+                state.guesses.clear();
+            }
+            UiEvent::Encryption(UiEncryptionEvent::InitEncryption(..)) => {
+                state.guesses.clear();
+            }
+            UiEvent::Control(UiControlEvent::GuessLatency(block, byte, _, _)) => {
+                *state.guesses.entry((*block, *byte)).or_default() += 1;
+            }
+            UiEvent::Decryption(UiDecryptionEvent::BlockSolved(forged_block, block)) => {
+                let plain_text = state
+                    .cypher_text_blocks
+                    .get(block - 1)
+                    .map(|previous_block| &forged_block.to_intermediate() ^ previous_block);
+                state.record_block(Phase::Decryption, *block, forged_block, plain_text);
+            }
+            UiEvent::Encryption(UiEncryptionEvent::BlockSolved(forged_block, block)) => {
+                state.record_block(Phase::Encryption, *block, forged_block, None);
+            }
+            _ => {}
+        }
+    }
+
+    /// Write the guesses per byte as CSV, replacing the file. The distribution of the plain text's byte values is written next to it, as `<name>.histogram.csv`
+    pub(super) fn export(&self) -> Result<()> {
+        let state = self.state.lock().unwrap();
+
+        // writing to a `String` can't fail
+        let mut bytes = String::from("phase,block,byte,guesses,forged,intermediate,plain_text\n");
+        for solved_byte in &state.solved_bytes {
+            let _ = writeln!(
+                bytes,
+                "{},{},{},{},{},{},{}",
+                match solved_byte.phase {
+                    Phase::Decryption => "decryption",
+                    Phase::Encryption => "encryption",
+                },
+                solved_byte.block,
+                solved_byte.byte,
+                solved_byte.guesses,
+                solved_byte.forged,
+                solved_byte.intermediate,
+                solved_byte
+                    .plain_text
+                    .map(|plain_text| plain_text.to_string())
+                    .unwrap_or_default()
+            );
+        }
+        fs::write(&self.path, bytes).context(format!(
+            "Writing guess statistics `{}` failed",
+            self.path.display()
+        ))?;
+
+        // (occurrences, guesses) per plain text byte value
+        let mut histogram = vec![(0, 0); u8::MAX as usize + 1];
+        for solved_byte in &state.solved_bytes {
+            if let Some(plain_text) = solved_byte.plain_text {
+                histogram[plain_text as usize].0 += 1;
+                histogram[plain_text as usize].1 += solved_byte.guesses;
+            }
+        }
+        let mut histogram_csv = String::from("value,occurrences,mean_guesses\n");
+        for (value, (occurrences, guesses)) in histogram.into_iter().enumerate() {
+            let mean_guesses = if occurrences == 0 {
+                String::new()
+            } else {
+                format!("{:.1}", guesses as f64 / occurrences as f64)
+            };
+            let _ = writeln!(histogram_csv, "{},{},{}", value, occurrences, mean_guesses);
+        }
+        let histogram_path = self.path.with_extension("histogram.csv");
+        fs::write(&histogram_path, histogram_csv).context(format!(
+            "Writing guess histogram `{}` failed",
+            histogram_path.display()
+        ))
+    }
+}
+
+impl State {
+    fn record_block(
+        &mut self,
+        phase: Phase,
+        block: usize,
+        forged_block: &Block,
+        plain_text: Option<Block>,
+    ) {
+        let intermediate = forged_block.to_intermediate();
+        for byte in 0..forged_block.len() {
+            let guesses = match self.guesses.remove(&(block, byte as u8)) {
+                Some(guesses) => guesses,
+                None => continue,
+            };
+            self.solved_bytes.push(SolvedByte {
+                phase,
+                block,
+                byte,
+                guesses,
+                forged: forged_block[byte],
+                intermediate: intermediate[byte],
+                plain_text: plain_text.as_ref().map(|plain_text| plain_text[byte]),
+            });
+        }
+    }
+}
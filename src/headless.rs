@@ -0,0 +1,151 @@
+use std::{
+    process,
+    sync::{
+        atomic::{AtomicI32, AtomicUsize, Ordering},
+        Mutex,
+    },
+};
+
+use crate::{
+    timeline::Phase,
+    tui::ui_event::{UiControlEvent, UiEvent},
+};
+
+/// Stand-in for the TUI with `--no-tui`. Progress is printed as plain lines on stderr, and the result once finished, as the TUI does after exiting.
+pub(super) struct Headless {
+    print_after_exit: Mutex<Vec<String>>,
+    // replaces `print_after_exit`, and always goes to stdout
+    document_after_exit: Mutex<Option<String>>,
+    exit_code: AtomicI32,
+
+    decryption_progress: PhaseProgress,
+    encryption_progress: PhaseProgress,
+}
+
+struct PhaseProgress {
+    bytes_to_finish: AtomicUsize,
+    bytes_finished: AtomicUsize,
+    // only a change of the whole percentage is printed
+    printed_percentage: AtomicUsize,
+}
+
+impl Headless {
+    pub(super) fn new() -> Self {
+        Self {
+            print_after_exit: Mutex::new(vec![]),
+            document_after_exit: Mutex::new(None),
+            exit_code: AtomicI32::new(0),
+
+            decryption_progress: PhaseProgress::new(),
+            encryption_progress: PhaseProgress::new(),
+        }
+    }
+
+    pub(super) fn handle_application_event(&self, event: UiEvent) {
+        let event = match event {
+            UiEvent::Control(event) => event,
+            // blocks are logged as they're solved, and printed in full once finished
+            UiEvent::Decryption(_) | UiEvent::Encryption(_) => return,
+        };
+
+        match event {
+            UiControlEvent::IndicateWork(decryption_bytes, encryption_bytes) => {
+                // a new segment of the cypher text starts from scratch
+                self.decryption_progress.reset(decryption_bytes);
+                self.encryption_progress.reset(encryption_bytes);
+            }
+            UiControlEvent::ProgressUpdate(phase, newly_solved_bytes) => {
+                let progress = match phase {
+                    Phase::Decryption => &self.decryption_progress,
+                    Phase::Encryption => &self.encryption_progress,
+                };
+                // phases are only named when encrypting, which decrypts first
+                let label = if self
+                    .encryption_progress
+                    .bytes_to_finish
+                    .load(Ordering::Relaxed)
+                    > 0
+                {
+                    match phase {
+                        Phase::Decryption => "Decryption progress",
+                        Phase::Encryption => "Encryption progress",
+                    }
+                } else {
+                    "Progress"
+                };
+                progress.advance(label, newly_solved_bytes);
+            }
+            UiControlEvent::GuessLatency(..)
+            | UiControlEvent::BlockFailed(..)
+            | UiControlEvent::Calibrated(_) => {}
+            UiControlEvent::PrintAfterExit(message) => {
+                self.print_after_exit.lock().unwrap().push(message);
+            }
+            UiControlEvent::PrintDocumentAfterExit(document) => {
+                *self.document_after_exit.lock().unwrap() = Some(document);
+            }
+            UiControlEvent::ExitCode(code) => {
+                self.exit_code.store(code, Ordering::Relaxed);
+            }
+            // there's no UI to keep open
+            UiControlEvent::SlowRedraw => self.exit(),
+        }
+    }
+
+    /// Print the result, as the TUI does after leaving the alternate screen, and quit
+    pub(super) fn exit(&self) {
+        if let Some(document) = self.document_after_exit.lock().unwrap().take() {
+            println!("{}", document);
+            process::exit(self.exit_code.load(Ordering::Relaxed));
+        }
+
+        let use_stderr = self.exit_code.load(Ordering::Relaxed) != 0;
+        for message in self.print_after_exit.lock().unwrap().drain(..) {
+            if use_stderr {
+                eprintln!("{}", message);
+            } else {
+                println!("{}", message);
+            }
+        }
+
+        process::exit(self.exit_code.load(Ordering::Relaxed));
+    }
+}
+
+impl PhaseProgress {
+    fn new() -> Self {
+        Self {
+            bytes_to_finish: AtomicUsize::new(0),
+            bytes_finished: AtomicUsize::new(0),
+            printed_percentage: AtomicUsize::new(0),
+        }
+    }
+
+    fn reset(&self, bytes_to_finish: usize) {
+        self.bytes_to_finish
+            .store(bytes_to_finish, Ordering::Relaxed);
+        self.bytes_finished.store(0, Ordering::Relaxed);
+        self.printed_percentage.store(0, Ordering::Relaxed);
+    }
+
+    fn advance(&self, label: &str, newly_solved_bytes: usize) {
+        let bytes_finished = self
+            .bytes_finished
+            .fetch_add(newly_solved_bytes, Ordering::Relaxed)
+            + newly_solved_bytes;
+        let bytes_to_finish = self.bytes_to_finish.load(Ordering::Relaxed).max(1);
+        let percentage = (bytes_finished * 100 / bytes_to_finish).min(100);
+
+        // concurrent updates could otherwise print the same percentage twice
+        if self
+            .printed_percentage
+            .fetch_max(percentage, Ordering::Relaxed)
+            < percentage
+        {
+            eprintln!(
+                "{}: {}% ({}/{} bytes)",
+                label, percentage, bytes_finished, bytes_to_finish
+            );
+        }
+    }
+}
@@ -0,0 +1,196 @@
+use std::{collections::HashMap, fs, path::Path};
+
+use anyhow::{anyhow, Context, Result};
+use log::info;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    block::{block_size::BlockSizeTrait, Block},
+    cli::ForgeOfflineCli,
+    cypher_text::{
+        encode::{Encode, Encoding},
+        forged_cypher_text::solved::SolvedForgedCypherText,
+        CypherText,
+    },
+    logging::LOG_TARGET,
+    plain_text::PlainText,
+};
+
+/// Intermediates of cypher text blocks, i.e. the blocks as decrypted by the cypher before being xor-ed with the preceding block. With these, cypher texts can be forged without the oracle.
+#[derive(Serialize, Deserialize, Debug)]
+pub(super) struct Intermediates {
+    block_size: u8,
+    url_encoded: bool,
+    encoding: Encoding,
+    blocks: Vec<KnownBlock>,
+}
+
+/// Hex encoded, to keep the exported file readable
+#[derive(Serialize, Deserialize, Debug)]
+struct KnownBlock {
+    cypher_text: String,
+    intermediate: String,
+}
+
+impl Intermediates {
+    /// Intermediates found by decrypting blocks with the oracle
+    pub(super) fn from_solutions(solutions: &[SolvedForgedCypherText]) -> Self {
+        let first_solution = &solutions[0];
+        Self::new(
+            first_solution.block_to_decrypt(),
+            *first_solution.url_encoded(),
+            *first_solution.used_encoding(),
+            solutions.iter().map(|solution| {
+                (
+                    solution.block_to_decrypt().clone(),
+                    solution.forged_block_solution().to_intermediate(),
+                )
+            }),
+        )
+    }
+
+    /// Intermediates of a cypher text of which the plain text is known. The 1st block is the IV, which has no intermediate
+    pub(super) fn from_encryption(cypher_text: &CypherText, plain_text: &PlainText) -> Self {
+        Self::new(
+            &cypher_text.blocks()[0],
+            *cypher_text.url_encoded(),
+            *cypher_text.used_encoding(),
+            cypher_text
+                .blocks()
+                .windows(2)
+                .zip(plain_text.blocks())
+                .map(|(pair, plain_text_block)| (pair[1].clone(), &pair[0] ^ plain_text_block)),
+        )
+    }
+
+    fn new(
+        any_block: &Block,
+        url_encoded: bool,
+        encoding: Encoding,
+        blocks: impl Iterator<Item = (Block, Block)>,
+    ) -> Self {
+        Self {
+            block_size: *any_block.block_size(),
+            url_encoded,
+            encoding,
+            blocks: blocks
+                .map(|(cypher_text, intermediate)| KnownBlock {
+                    cypher_text: cypher_text.to_hex(),
+                    intermediate: intermediate.to_hex(),
+                })
+                .collect(),
+        }
+    }
+
+    /// Both sets of intermediates, e.g. of the original cypher text and of one forged with the oracle
+    pub(super) fn chain(mut self, other: Self) -> Self {
+        self.blocks.extend(other.blocks);
+        self
+    }
+
+    pub(super) fn export(&self, path: &Path) -> Result<()> {
+        let json =
+            serde_json::to_string_pretty(self).context("Intermediates serialization failed")?;
+        fs::write(path, json).context(format!(
+            "Writing the intermediates to `{}` failed",
+            path.display()
+        ))?;
+        info!(
+            target: LOG_TARGET,
+            "Wrote the intermediates to `{}`",
+            path.display()
+        );
+        Ok(())
+    }
+
+    fn load(path: &Path) -> Result<Self> {
+        let json = fs::read_to_string(path).context(format!(
+            "Intermediates file `{}` failed to open",
+            path.display()
+        ))?;
+        serde_json::from_str(&json).context(format!(
+            "Intermediates file `{}` is not a valid export of `--output-intermediates`",
+            path.display()
+        ))
+    }
+
+    /// (cypher text block, intermediate) pairs, in the order they were exported
+    fn known_blocks(&self) -> Result<Vec<(Block, Block)>> {
+        let decode = |hex_data: &str| -> Result<Block> {
+            let data = hex::decode(hex_data).context(format!(
+                "Intermediates file holds invalid hex: `{}`",
+                hex_data
+            ))?;
+            if data.len() != self.block_size as usize {
+                return Err(anyhow!(
+                    "Intermediates file holds a block of {} bytes, while its block size is {}",
+                    data.len(),
+                    self.block_size
+                ));
+            }
+            Ok(Block::from(&data[..]))
+        };
+
+        self.blocks
+            .iter()
+            .map(|block| Ok((decode(&block.cypher_text)?, decode(&block.intermediate)?)))
+            .collect()
+    }
+
+    /// Chain known blocks into a cypher text of `plain_text`. A block's plain text is its intermediate xor-ed with the preceding cypher text block, so every block but the 1st must be preceded by a known block. The 1st block is preceded by the IV, which can be anything
+    fn forge(&self, plain_text: &PlainText) -> Result<CypherText> {
+        let known_blocks = self.known_blocks()?;
+        let intermediates: HashMap<&Block, &Block> = known_blocks
+            .iter()
+            .map(|(cypher_text, intermediate)| (cypher_text, intermediate))
+            .collect();
+
+        let forged_blocks = known_blocks
+            .iter()
+            .find_map(|(last_block, _)| {
+                let mut blocks_backwards = vec![last_block.clone()];
+                for (idx, plain_text_block) in plain_text.blocks().iter().enumerate().rev() {
+                    let current_block = blocks_backwards
+                        .last()
+                        .expect("Chain starts with 1 block, yet no block was found in the list");
+                    let preceding_block = intermediates[current_block] ^ plain_text_block;
+                    // the IV has no intermediate to continue with
+                    if idx != 0 && !intermediates.contains_key(&preceding_block) {
+                        return None;
+                    }
+                    blocks_backwards.push(preceding_block);
+                }
+                Some(blocks_backwards)
+            })
+            .ok_or_else(|| {
+                anyhow!(
+                    "The plain text can't be forged from the {} known block(s). Offline, only the 1st block of plain text can be chosen freely. The other blocks must be plain text of which the intermediates are known, e.g. that of the original cypher text",
+                    known_blocks.len()
+                )
+            })?;
+
+        Ok(CypherText::from_iter(
+            forged_blocks.iter().rev(),
+            self.url_encoded,
+            self.encoding,
+        ))
+    }
+}
+
+/// Forge a cypher text of the given plain text from exported intermediates, without questioning the oracle. The forged cypher text is printed.
+pub(super) fn forge_offline(forge_offline_cli: ForgeOfflineCli) -> Result<()> {
+    let intermediates = Intermediates::load(forge_offline_cli.intermediates_file())?;
+    if intermediates.blocks.is_empty() {
+        return Err(anyhow!("Intermediates file holds no blocks"));
+    }
+
+    let plain_text = PlainText::new(
+        forge_offline_cli.plain_text(),
+        &intermediates.block_size.into(),
+        forge_offline_cli.padding_scheme(),
+    );
+    let forged_cypher_text = intermediates.forge(&plain_text)?;
+    println!("{}", forged_cypher_text.encode());
+
+    Ok(())
+}
@@ -0,0 +1,307 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+
+use crate::{
+    block::Block,
+    config::Config,
+    cypher_text::encode::{Encode, Encoding},
+    error::RustpadError,
+    oracle::request_budget::RequestBudget,
+    timeline::Phase,
+    tui::ui_event::{UiControlEvent, UiDecryptionEvent, UiEncryptionEvent, UiEvent},
+};
+
+/// Result of the attack as a single JSON document, for `--format json`. Built from the events sent to the UI, as the TUI's tables are.
+pub(super) struct JsonResult {
+    start: Instant,
+    encoding: Encoding,
+    url_encoded: bool,
+    block_size: u8,
+    request_budget: Arc<RequestBudget>,
+    state: Mutex<State>,
+}
+
+#[derive(Default)]
+struct State {
+    // a cypher text per attacked segment. Only 1 if the token isn't split
+    decryptions: Vec<Decryption>,
+    encryption: Option<Encryption>,
+    // the 1st guess about a block marks its start
+    block_starts: HashMap<(Phase, usize), Instant>,
+    // human readable result lines, as they would've been printed
+    output: Vec<String>,
+    exit_code: i32,
+    // the failure, and its class if it's a `RustpadError`
+    error: Option<(String, Option<&'static str>)>,
+}
+
+struct Decryption {
+    cypher_text_blocks: Vec<Block>,
+    solved_blocks: Vec<SolvedBlock>,
+}
+
+struct Encryption {
+    // without the IV
+    plain_text_blocks: Vec<Block>,
+    solved_blocks: Vec<SolvedBlock>,
+}
+
+struct SolvedBlock {
+    block: usize,
+    intermediate: Block,
+    // the decrypted plain text, or the forged cypher text when encrypting
+    result: Block,
+    duration: Option<Duration>,
+}
+
+#[derive(Serialize)]
+pub(super) struct Document {
+    pub(super) status: &'static str,
+    pub(super) exit_code: i32,
+    pub(super) encoding: Encoding,
+    pub(super) url_encoded: bool,
+    pub(super) block_size: u8,
+    pub(super) requests: u64,
+    pub(super) duration_ms: u128,
+    pub(super) decryption: Vec<DecryptionDocument>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(super) encryption: Option<EncryptionDocument>,
+    pub(super) output: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(super) error: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(super) error_kind: Option<&'static str>,
+}
+
+#[derive(Serialize)]
+pub(super) struct DecryptionDocument {
+    pub(super) cypher_text: String,
+    // hex, for binary data
+    pub(super) plain_text_hex: String,
+    // lossy UTF-8
+    pub(super) plain_text: String,
+    pub(super) blocks: Vec<DecryptedBlockDocument>,
+}
+
+#[derive(Serialize)]
+pub(super) struct DecryptedBlockDocument {
+    pub(super) block: usize,
+    pub(super) cypher_text: String,
+    pub(super) intermediate: String,
+    pub(super) plain_text_hex: String,
+    pub(super) plain_text: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(super) duration_ms: Option<u128>,
+}
+
+#[derive(Serialize)]
+pub(super) struct EncryptionDocument {
+    pub(super) plain_text_hex: String,
+    pub(super) blocks: Vec<EncryptedBlockDocument>,
+}
+
+#[derive(Serialize)]
+pub(super) struct EncryptedBlockDocument {
+    pub(super) block: usize,
+    pub(super) plain_text_hex: String,
+    pub(super) intermediate: String,
+    pub(super) cypher_text: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(super) duration_ms: Option<u128>,
+}
+
+impl JsonResult {
+    pub(super) fn new(config: &Config) -> Self {
+        Self {
+            start: Instant::now(),
+            encoding: *config.cypher_text().used_encoding(),
+            url_encoded: *config.cypher_text().url_encoded(),
+            block_size: **config.block_size(),
+            request_budget: config.request_budget().clone(),
+            state: Mutex::new(State::default()),
+        }
+    }
+
+    /// Track solved blocks, result lines and the exit code from the events sent to the UI
+    pub(super) fn record_ui_event(&self, event: &UiEvent) {
+        let mut state = self.state.lock().unwrap();
+        match event {
+            UiEvent::Decryption(UiDecryptionEvent::InitDecryption(cypher_text_blocks)) => {
+                // sent again after calibration, for the same cypher text
+                let is_new = state
+                    .decryptions
+                    .last()
+                    .is_none_or(|decryption| decryption.cypher_text_blocks != *cypher_text_blocks);
+                if is_new {
+                    state.decryptions.push(Decryption {
+                        cypher_text_blocks: cypher_text_blocks.clone(),
+                        solved_blocks: Vec::new(),
+                    });
+                    state
+                        .block_starts
+                        .retain(|(phase, _), _| *phase != Phase::Decryption);
+                }
+            }
+            UiEvent::Decryption(UiDecryptionEvent::BlockWip(_, block_idx)) => {
+                state
+                    .block_starts
+                    .entry((Phase::Decryption, *block_idx))
+                    .or_insert_with(Instant::now);
+            }
+            UiEvent::Decryption(UiDecryptionEvent::BlockSolved(forged_block, block_idx)) => {
+                let duration = state.block_duration(Phase::Decryption, *block_idx);
+                if let Some(decryption) = state.decryptions.last_mut() {
+                    let intermediate = forged_block.to_intermediate();
+                    let result = &intermediate ^ &decryption.cypher_text_blocks[block_idx - 1];
+                    decryption.solved_blocks.push(SolvedBlock {
+                        block: *block_idx,
+                        intermediate,
+                        result,
+                        duration,
+                    });
+                }
+            }
+            UiEvent::Encryption(UiEncryptionEvent::InitEncryption(plain_text_blocks, _)) => {
+                state.encryption = Some(Encryption {
+                    plain_text_blocks: plain_text_blocks.clone(),
+                    solved_blocks: Vec::new(),
+                });
+            }
+            UiEvent::Encryption(UiEncryptionEvent::BlockWip(_, block_idx)) => {
+                state
+                    .block_starts
+                    .entry((Phase::Encryption, *block_idx))
+                    .or_insert_with(Instant::now);
+            }
+            UiEvent::Encryption(UiEncryptionEvent::BlockSolved(forged_block, block_idx)) => {
+                let duration = state.block_duration(Phase::Encryption, *block_idx);
+                if let Some(encryption) = &mut state.encryption {
+                    let intermediate = forged_block.to_intermediate();
+                    // the plain text blocks exclude the IV
+                    let result = &intermediate ^ &encryption.plain_text_blocks[block_idx - 1];
+                    encryption.solved_blocks.push(SolvedBlock {
+                        block: *block_idx,
+                        intermediate,
+                        result,
+                        duration,
+                    });
+                }
+            }
+            UiEvent::Control(UiControlEvent::PrintAfterExit(message)) => {
+                state.output.push(message.clone());
+            }
+            UiEvent::Control(UiControlEvent::ExitCode(exit_code)) => {
+                state.exit_code = *exit_code;
+            }
+            _ => {}
+        }
+    }
+
+    /// Track why the attack failed, so scripts can tell failures apart without parsing the output
+    pub(super) fn record_error(&self, error: &anyhow::Error) {
+        self.state.lock().unwrap().error = Some((
+            format!("{:#}", error),
+            RustpadError::find(error).map(RustpadError::kind),
+        ));
+    }
+
+    /// The document, as JSON on a single line
+    pub(super) fn render(&self) -> Result<String> {
+        serde_json::to_string(&self.document()).context("Serialising the JSON result failed")
+    }
+
+    /// The result as of now
+    pub(super) fn document(&self) -> Document {
+        let state = self.state.lock().unwrap();
+
+        Document {
+            status: if state.exit_code == 0 {
+                "success"
+            } else {
+                "failure"
+            },
+            exit_code: state.exit_code,
+            encoding: self.encoding,
+            url_encoded: self.url_encoded,
+            block_size: self.block_size,
+            requests: self.request_budget.requests(),
+            duration_ms: self.start.elapsed().as_millis(),
+            decryption: state
+                .decryptions
+                .iter()
+                .map(Decryption::to_document)
+                .collect(),
+            encryption: state.encryption.as_ref().map(Encryption::to_document),
+            output: state.output.clone(),
+            error: state.error.as_ref().map(|(message, _)| message.clone()),
+            error_kind: state.error.as_ref().and_then(|(_, kind)| *kind),
+        }
+    }
+}
+
+impl State {
+    fn block_duration(&self, phase: Phase, block_idx: usize) -> Option<Duration> {
+        self.block_starts
+            .get(&(phase, block_idx))
+            .map(Instant::elapsed)
+    }
+}
+
+impl Decryption {
+    fn to_document(&self) -> DecryptionDocument {
+        let mut solved_blocks: Vec<_> = self.solved_blocks.iter().collect();
+        solved_blocks.sort_by_key(|solved_block| solved_block.block);
+        let plain_text: Vec<u8> = solved_blocks
+            .iter()
+            .flat_map(|solved_block| solved_block.result.to_vec())
+            .collect();
+
+        DecryptionDocument {
+            cypher_text: hex_of(&self.cypher_text_blocks),
+            plain_text_hex: hex::encode(&plain_text),
+            plain_text: String::from_utf8_lossy(&plain_text).to_string(),
+            blocks: solved_blocks
+                .into_iter()
+                .map(|solved_block| DecryptedBlockDocument {
+                    block: solved_block.block,
+                    cypher_text: self.cypher_text_blocks[solved_block.block].to_hex(),
+                    intermediate: solved_block.intermediate.to_hex(),
+                    plain_text_hex: solved_block.result.to_hex(),
+                    plain_text: String::from_utf8_lossy(&solved_block.result).to_string(),
+                    duration_ms: solved_block.duration.map(|duration| duration.as_millis()),
+                })
+                .collect(),
+        }
+    }
+}
+
+impl Encryption {
+    fn to_document(&self) -> EncryptionDocument {
+        let mut solved_blocks: Vec<_> = self.solved_blocks.iter().collect();
+        solved_blocks.sort_by_key(|solved_block| solved_block.block);
+
+        EncryptionDocument {
+            plain_text_hex: hex_of(&self.plain_text_blocks),
+            blocks: solved_blocks
+                .into_iter()
+                .map(|solved_block| EncryptedBlockDocument {
+                    block: solved_block.block,
+                    plain_text_hex: self.plain_text_blocks[solved_block.block - 1].to_hex(),
+                    intermediate: solved_block.intermediate.to_hex(),
+                    cypher_text: solved_block.result.to_hex(),
+                    duration_ms: solved_block.duration.map(|duration| duration.as_millis()),
+                })
+                .collect(),
+        }
+    }
+}
+
+fn hex_of(blocks: &[Block]) -> String {
+    blocks.iter().map(Block::to_hex).collect()
+}
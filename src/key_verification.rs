@@ -0,0 +1,126 @@
+use aes::{
+    cipher::{generic_array::GenericArray, BlockDecrypt, KeyInit},
+    Aes128, Aes192, Aes256,
+};
+use anyhow::Result;
+use itertools::Itertools;
+use log::{info, warn};
+
+use crate::{block::Block, config::aes_key::AesKey, error::RustpadError, logging::LOG_TARGET};
+
+/// Ground truth for the attack's output, by decrypting locally with the known AES key. For CTF practice, or for validating changes to the solving logic.
+pub(super) struct KeyVerifier {
+    key: AesKey,
+    // replaces the cypher text's IV. The IV of a cypher text without IV is all zeroes otherwise
+    iv: Option<Block>,
+}
+
+impl KeyVerifier {
+    pub(super) fn new(key: AesKey, iv: Option<Block>) -> Self {
+        Self { key, iv }
+    }
+
+    /// Compare the plain text found by the attack against the plain text of `cypher_text`. `cypher_text` includes the IV.
+    pub(super) fn verify_decryption(
+        &self,
+        cypher_text: &[Block],
+        attack_plain_text: &[Block],
+    ) -> Result<String> {
+        let mut cypher_text = cypher_text.to_vec();
+        if let Some(iv) = &self.iv {
+            cypher_text[0] = iv.clone();
+        }
+
+        compare(&self.decrypt(&cypher_text), attack_plain_text)
+    }
+
+    /// Check whether the cypher text forged by the attack decrypts to the (padded) plain text. `forged_cypher_text` includes the IV.
+    pub(super) fn verify_encryption(
+        &self,
+        forged_cypher_text: &[Block],
+        plain_text: &[Block],
+    ) -> Result<String> {
+        compare(plain_text, &self.decrypt(forged_cypher_text))
+    }
+
+    fn decrypt(&self, cypher_text: &[Block]) -> Vec<Block> {
+        cypher_text
+            .iter()
+            .tuple_windows()
+            .map(|(previous_block, block)| &self.decrypt_block(block) ^ previous_block)
+            .collect()
+    }
+
+    pub(super) fn decrypt_block(&self, block: &Block) -> Block {
+        let mut data = GenericArray::clone_from_slice(block);
+        let invalid_length = "AES key length was verified during parsing";
+
+        match self.key.len() {
+            16 => Aes128::new_from_slice(&self.key)
+                .expect(invalid_length)
+                .decrypt_block(&mut data),
+            24 => Aes192::new_from_slice(&self.key)
+                .expect(invalid_length)
+                .decrypt_block(&mut data),
+            _ => Aes256::new_from_slice(&self.key)
+                .expect(invalid_length)
+                .decrypt_block(&mut data),
+        }
+
+        Block::from(&data[..])
+    }
+}
+
+/// Summarise the differences between the expected and actual plain text. Every differing byte is logged
+fn compare(expected: &[Block], actual: &[Block]) -> Result<String> {
+    if expected.len() != actual.len() {
+        return Err(RustpadError::VerificationFailed {
+            reason: format!(
+                "expected {} block(s), but the attack gave {}",
+                expected.len(),
+                actual.len()
+            ),
+        }
+        .into());
+    }
+
+    let mut amount_bytes = 0;
+    let mut amount_diffs = 0;
+    for (block_idx, (expected_block, actual_block)) in expected.iter().zip(actual).enumerate() {
+        for (byte_idx, (expected_byte, actual_byte)) in
+            expected_block.iter().zip(actual_block.iter()).enumerate()
+        {
+            amount_bytes += 1;
+            if expected_byte != actual_byte {
+                amount_diffs += 1;
+                warn!(
+                    target: LOG_TARGET,
+                    "Block {}, byte {}: expected {:#04x}, but the attack gave {:#04x}",
+                    block_idx + 1,
+                    byte_idx + 1,
+                    expected_byte,
+                    actual_byte
+                );
+            }
+        }
+    }
+
+    if amount_diffs > 0 {
+        return Err(RustpadError::VerificationFailed {
+            reason: format!(
+                "{} of {} bytes differ. Expected: {}",
+                amount_diffs,
+                amount_bytes,
+                expected.iter().map(|block| block.to_hex()).join("")
+            ),
+        }
+        .into());
+    }
+
+    let summary = format!(
+        "Verified with the key: all {} bytes are correct",
+        amount_bytes
+    );
+    info!(target: LOG_TARGET, "{}", summary);
+    Ok(summary)
+}
@@ -0,0 +1,183 @@
+use std::{net::TcpStream, ops::Deref, time::Duration};
+
+use anyhow::{anyhow, Context, Result};
+use reqwest::Url;
+use rustpad_core::lint::LintCheck;
+
+use crate::{
+    cli::{Cli, LintCli, LintSubCommand},
+    config::{Config, SubConfig},
+    cypher_text::{
+        encode::{AmountBlocksTrait, Encode},
+        CypherText,
+    },
+    oracle::{lua, oracle_location::OracleLocation, plugin, python, tcp, web},
+};
+
+/// Validate the setup of an oracle sub-command, without contacting the oracle. Every check is reported, after which an error is returned if any failed.
+pub(super) fn lint(lint_cli: LintCli) -> Result<()> {
+    let (cypher_text_input, proxy) = match &lint_cli.sub_command {
+        LintSubCommand::Web(web_cli) => (
+            web_cli
+                .cypher_text()
+                .iter()
+                .map(Deref::deref)
+                .collect::<String>(),
+            // the other proxies of a chain are only reachable through the first
+            web_cli
+                .proxy_urls()
+                .first()
+                .cloned()
+                .map(|proxy_url| (proxy_url, **web_cli.request_timeout())),
+        ),
+        LintSubCommand::Script(script_cli) => (
+            script_cli
+                .cypher_text()
+                .iter()
+                .map(Deref::deref)
+                .collect::<String>(),
+            None,
+        ),
+        LintSubCommand::Tcp(tcp_cli) => (
+            tcp_cli
+                .cypher_text()
+                .iter()
+                .map(Deref::deref)
+                .collect::<String>(),
+            None,
+        ),
+        LintSubCommand::Plugin(plugin_cli) => (
+            plugin_cli
+                .cypher_text()
+                .iter()
+                .map(Deref::deref)
+                .collect::<String>(),
+            None,
+        ),
+        LintSubCommand::Lua(lua_cli) => (
+            lua_cli
+                .cypher_text()
+                .iter()
+                .map(Deref::deref)
+                .collect::<String>(),
+            None,
+        ),
+        LintSubCommand::Python(python_cli) => (
+            python_cli
+                .cypher_text()
+                .iter()
+                .map(Deref::deref)
+                .collect::<String>(),
+            None,
+        ),
+    };
+
+    let mut checks = Vec::new();
+    match Config::try_from(Cli {
+        sub_command: lint_cli.sub_command.into(),
+    }) {
+        Ok(config) => {
+            checks.push(LintCheck::new("Configuration is valid", Ok(())));
+            checks.push(LintCheck::new(
+                "Cypher text encodes back to its original form",
+                check_encoding_round_trip(
+                    config.cypher_text(),
+                    &cypher_text_input,
+                    *config.no_iv(),
+                ),
+            ));
+
+            match (config.oracle_location(), config.sub_config()) {
+                (OracleLocation::Web(url), SubConfig::Web(web_config)) => {
+                    checks.extend(web::lint(url, web_config, config.cypher_text()))
+                }
+                (OracleLocation::Tcp(address), SubConfig::Tcp(tcp_config)) => {
+                    checks.extend(tcp::lint(address, tcp_config))
+                }
+                (OracleLocation::Plugin(path), SubConfig::Plugin(_)) => {
+                    checks.extend(plugin::lint(path))
+                }
+                (OracleLocation::Lua(path), SubConfig::Lua(lua_config)) => {
+                    checks.extend(lua::lint(path, lua_config))
+                }
+                (OracleLocation::Python(path), SubConfig::Python(_)) => {
+                    checks.extend(python::lint(path))
+                }
+                _ => {}
+            }
+
+            if let Some((proxy_url, timeout)) = proxy {
+                checks.push(LintCheck::new(
+                    "Proxy server is reachable",
+                    check_proxy_reachable(&proxy_url, timeout),
+                ));
+            }
+        }
+        // other checks rely on the configuration
+        Err(e) => checks.push(LintCheck::new("Configuration is valid", Err(e))),
+    }
+
+    let amount_failed = checks
+        .iter()
+        .filter(|check| check.outcome().is_err())
+        .count();
+    for check in &checks {
+        match check.outcome() {
+            Ok(()) => println!("[ok]   {}", check.description()),
+            Err(e) => println!("[fail] {}: {:#}", check.description(), e),
+        }
+    }
+
+    if amount_failed > 0 {
+        Err(anyhow!(
+            "{} of {} checks failed",
+            amount_failed,
+            checks.len()
+        ))
+    } else {
+        println!("All {} checks passed", checks.len());
+        Ok(())
+    }
+}
+
+/// Forged cypher texts are encoded the same way as the original. If that doesn't reproduce the original, the oracle might not understand the forgeries.
+fn check_encoding_round_trip(cypher_text: &CypherText, original: &str, no_iv: bool) -> Result<()> {
+    // without IV, an empty IV block was prepended during parsing
+    let encoded = if no_iv {
+        CypherText::from_iter(
+            cypher_text.blocks()[1..cypher_text.amount_blocks()].iter(),
+            *cypher_text.url_encoded(),
+            *cypher_text.used_encoding(),
+        )
+        .encode()
+    } else {
+        cypher_text.encode()
+    };
+
+    if encoded == original {
+        Ok(())
+    } else {
+        Err(anyhow!(
+            "Encoded as `{}` instead of `{}`. Double check `--encoding`, `--no-url-encode` and `--b64-padding`",
+            encoded,
+            original
+        ))
+    }
+}
+
+/// Connecting to the proxy doesn't contact the oracle
+fn check_proxy_reachable(proxy_url: &Url, timeout: Duration) -> Result<()> {
+    let socket_addresses = proxy_url
+        .socket_addrs(|| match proxy_url.scheme() {
+            "https" => Some(443),
+            "socks5" | "socks5h" => Some(1080),
+            _ => Some(80),
+        })
+        .context(format!("Resolving `{}` failed", proxy_url))?;
+
+    socket_addresses
+        .iter()
+        .find_map(|socket_address| TcpStream::connect_timeout(socket_address, timeout).ok())
+        .map(|_| ())
+        .ok_or_else(|| anyhow!("Connecting to `{}` failed", proxy_url))
+}
@@ -1,14 +1,38 @@
-use std::path::Path;
+use std::{
+    fs::{File, OpenOptions},
+    io::Write,
+    path::Path,
+    sync::{Arc, Mutex},
+    time::SystemTime,
+};
 
 use anyhow::{anyhow, Context, Result};
-use log::LevelFilter;
+use log::{Level, LevelFilter, Log, Metadata, Record};
+use slog::{o, BorrowedKV, Drain, RecordLocation, RecordStatic};
 
-pub(super) const LOG_TARGET: &str = "rustpad";
+use crate::{recording::Recording, syslog::Syslog};
 
-pub(super) fn init_logging(log_level: LevelFilter, output_file: Option<&Path>) -> Result<()> {
-    tui_logger::init_logger(log_level)
-        .map_err(|e| anyhow!("{}", e))
-        .context("Logger setup failed")?;
+pub(super) use rustpad_core::logging::{summarise_coalesced, LOG_TARGET};
+
+pub(super) fn init_logging(
+    log_level: LevelFilter,
+    output_file: Option<&Path>,
+    syslog: Option<Syslog>,
+    recording: Option<Arc<Recording>>,
+) -> Result<()> {
+    if syslog.is_none() && recording.is_none() {
+        tui_logger::init_logger(log_level)
+    } else {
+        log::set_max_level(log_level);
+        // the logger lives as long as the application
+        log::set_logger(Box::leak(Box::new(TuiForwardingLogger {
+            log_level,
+            syslog,
+            recording,
+        })))
+    }
+    .map_err(|e| anyhow!("{}", e))
+    .context("Logger setup failed")?;
     tui_logger::set_default_level(LevelFilter::Trace);
     if let Some(output_file) = output_file {
         tui_logger::set_log_file(&output_file.to_string_lossy()).context(format!(
@@ -19,3 +43,132 @@ pub(super) fn init_logging(log_level: LevelFilter, output_file: Option<&Path>) -
 
     Ok(())
 }
+
+/// Logs as plain lines on stderr, for `--no-tui`. The TUI's logger only keeps them for its log view
+pub(super) fn init_plain_logging(
+    log_level: LevelFilter,
+    output_file: Option<&Path>,
+    syslog: Option<Syslog>,
+    recording: Option<Arc<Recording>>,
+) -> Result<()> {
+    let output_file = output_file
+        .map(|output_file| {
+            OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(output_file)
+                .context(format!(
+                    "Log file `{}` failed to open",
+                    output_file.display()
+                ))
+        })
+        .transpose()?;
+
+    // the logger lives as long as the application
+    log::set_logger(Box::leak(Box::new(PlainLogger {
+        log_level,
+        output_file: output_file.map(Mutex::new),
+        syslog,
+        recording,
+    })))
+    .map_err(|e| anyhow!("{}", e))
+    .context("Logger setup failed")?;
+    log::set_max_level(log_level);
+
+    Ok(())
+}
+
+struct PlainLogger {
+    log_level: LevelFilter,
+    output_file: Option<Mutex<File>>,
+    syslog: Option<Syslog>,
+    recording: Option<Arc<Recording>>,
+}
+
+/// Logs to the TUI's logger as well as to syslog and the recording. The TUI's logger only takes records from `log` if it's the only logger, so they're handed to it as `slog` records
+struct TuiForwardingLogger {
+    log_level: LevelFilter,
+    syslog: Option<Syslog>,
+    recording: Option<Arc<Recording>>,
+}
+
+impl Log for PlainLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= self.log_level
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+
+        let line = format!(
+            "{} {:<5} {}",
+            humantime::format_rfc3339_seconds(SystemTime::now()),
+            record.level(),
+            record.args()
+        );
+        eprintln!("{}", line);
+        if let Some(output_file) = &self.output_file {
+            // a failing log file can't be logged about
+            let _ = writeln!(output_file.lock().unwrap(), "{}", line);
+        }
+        if let Some(syslog) = &self.syslog {
+            syslog.send(record);
+        }
+        if let Some(recording) = &self.recording {
+            recording.record_log(record);
+        }
+    }
+
+    fn flush(&self) {
+        if let Some(output_file) = &self.output_file {
+            let _ = output_file.lock().unwrap().flush();
+        }
+    }
+}
+
+impl Log for TuiForwardingLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= self.log_level
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+
+        // the location isn't shown by the TUI
+        static LOCATION: RecordLocation = RecordLocation {
+            file: "",
+            line: 0,
+            column: 0,
+            function: "",
+            module: LOG_TARGET,
+        };
+        let record_static = RecordStatic {
+            location: &LOCATION,
+            tag: record.target(),
+            level: match record.level() {
+                Level::Error => slog::Level::Error,
+                Level::Warn => slog::Level::Warning,
+                Level::Info => slog::Level::Info,
+                Level::Debug => slog::Level::Debug,
+                Level::Trace => slog::Level::Trace,
+            },
+        };
+        let _ = tui_logger::slog_drain().log(
+            &slog::Record::new(&record_static, record.args(), BorrowedKV(&())),
+            &o!().into(),
+        );
+
+        if let Some(syslog) = &self.syslog {
+            syslog.send(record);
+        }
+        if let Some(recording) = &self.recording {
+            recording.record_log(record);
+        }
+    }
+
+    fn flush(&self) {}
+}
@@ -1,60 +1,197 @@
-mod block;
-mod cache;
-mod calibrator;
+mod attack_state;
+mod bitflip;
+mod bleichenbacher;
 mod cli;
 mod config;
-mod cypher_text;
-mod divination;
+mod curl;
+mod demo_server;
+mod event_stream;
+mod explain;
+mod guess_stats;
+mod headless;
+mod intermediates;
+mod json_result;
+mod key_verification;
+mod lint;
 mod logging;
-mod oracle;
+mod mass;
 mod other;
-mod plain_text;
+mod padbuster;
+mod plain_text_analysis;
+mod poc;
+mod presets;
+mod recording;
+mod report;
+mod run_stats;
+mod serve;
+mod syslog;
+mod timeline;
 mod tui;
+mod webhook;
+
+// the attack engine. Imported here, so its modules are used as if they were the binary's own
+use rustpad_core::{
+    block, cache, calibrator, cypher_text, divination, error, oracle, plain_text, session,
+};
 
 use std::{
+    env, fs,
+    path::Path,
+    process,
     sync::{Arc, Mutex},
     time::{Duration, Instant},
 };
 
-use anyhow::{Context, Result};
+use anyhow::{anyhow, Context, Result};
 use async_std::task;
 use clap::StructOpt;
 use crossbeam::thread;
 use humantime::format_duration;
-use log::{error, info};
+use log::{error, info, warn};
 
 use crate::{
+    attack_state::AttackState,
     block::block_size::BlockSizeTrait,
+    block::Block,
     cache::{cache_config::CacheConfig, Cache},
     calibrator::calibration_response::CalibrationResponse,
     cli::Cli,
-    config::Config,
-    cypher_text::encode::{AmountBlocksTrait, Encode},
-    divination::{decryptor::Decryptor, encryptor::Encryptor},
-    logging::{init_logging, LOG_TARGET},
+    config::{
+        output_format::OutputFormat, plain_text_edit::PlainTextEdit, segment_output::SegmentOutput,
+        Config, SubConfig, WebConfig,
+    },
+    cypher_text::{
+        encode::{AmountBlocksTrait, Encode},
+        forged_cypher_text::solved::SolvedForgedCypherText,
+        CypherText,
+    },
+    divination::{
+        decryptor::Decryptor,
+        encryptor::{forge_by_iv_tampering, iv_tampering_possible, Encryptor},
+        guess_strategy::WarmStartStrategy,
+        PlainTextExpectations,
+    },
+    error::RustpadError,
+    event_stream::{EventStream, StreamedEventKind},
+    guess_stats::GuessStats,
+    headless::Headless,
+    intermediates::Intermediates,
+    json_result::JsonResult,
+    key_verification::KeyVerifier,
+    logging::{init_logging, init_plain_logging, summarise_coalesced, LOG_TARGET},
     oracle::{
+        lua::LuaOracle,
         oracle_location::OracleLocation,
+        plugin::PluginOracle,
+        python::PythonOracle,
         script::ScriptOracle,
+        tcp::TcpOracle,
         web::{calibrate_web::CalibrationWebOracle, WebOracle},
         Oracle,
     },
     other::{config_thread_pool, generate_shell_autocomplete},
+    plain_text::PlainText,
+    plain_text_analysis::PlainTextAnalysis,
+    recording::Recording,
+    report::Report,
+    run_stats::RunStats,
+    session::Session,
+    syslog::Syslog,
+    timeline::{Phase, Timeline, TimelineEventKind},
     tui::{
         ui_event::{UiControlEvent, UiDecryptionEvent, UiEncryptionEvent, UiEvent},
+        ui_observer::UiObserver,
         Tui,
     },
+    webhook::Webhook,
 };
 
-fn main() -> Result<()> {
-    let cli = Cli::parse();
+// from this size on, sending every preceding block with each question gets costly
+const LARGE_CYPHER_TEXT_BLOCKS: usize = 64;
+
+fn main() {
+    if let Err(e) = run() {
+        // same output as returning the error from `main`, but with the exit code of the failure's class
+        eprintln!("Error: {:?}", e);
+        process::exit(
+            RustpadError::find(&e)
+                .map(RustpadError::exit_code)
+                .unwrap_or(1),
+        );
+    }
+}
+
+fn run() -> Result<()> {
+    let cli = Cli::parse_from(presets::expand(curl::expand(env::args_os())?)?);
+    // continues as the `web` sub-command which it stands for
+    let cli = if let cli::SubCommand::PadBuster(padbuster_cli) = &cli.sub_command {
+        padbuster::web_cli(padbuster_cli)?
+    } else {
+        cli
+    };
+    if let cli::SubCommand::Presets(presets_cli) = cli.sub_command {
+        return presets::presets(*presets_cli);
+    }
     if let cli::SubCommand::Setup(setup_cli) = cli.sub_command {
         generate_shell_autocomplete(setup_cli.shell());
         return Ok(());
     }
+    if let cli::SubCommand::Lint(lint_cli) = cli.sub_command {
+        return lint::lint(*lint_cli);
+    }
+    if let cli::SubCommand::Bitflip(bitflip_cli) = cli.sub_command {
+        return bitflip::bitflip(*bitflip_cli);
+    }
+    if let cli::SubCommand::Explain(explain_cli) = cli.sub_command {
+        return explain::explain(*explain_cli);
+    }
+    if let cli::SubCommand::ForgeOffline(forge_offline_cli) = cli.sub_command {
+        return intermediates::forge_offline(*forge_offline_cli);
+    }
+    if let cli::SubCommand::DemoServer(demo_server_cli) = cli.sub_command {
+        return demo_server::demo_server(*demo_server_cli);
+    }
+    if let cli::SubCommand::Mass(mass_cli) = cli.sub_command {
+        return mass::mass(*mass_cli);
+    }
+    if let cli::SubCommand::Bleichenbacher(bleichenbacher_cli) = cli.sub_command {
+        return bleichenbacher::bleichenbacher(*bleichenbacher_cli);
+    }
+    if let cli::SubCommand::Serve(serve_cli) = cli.sub_command {
+        return serve::serve(*serve_cli);
+    }
+    if let cli::SubCommand::ReplaySession(replay_session_cli) = cli.sub_command {
+        return recording::replay_session(*replay_session_cli);
+    }
     let config = Config::try_from(cli)?;
 
     config_thread_pool(config.thread_count())?;
-    init_logging(*config.log_level(), config.output_file().as_deref())?;
+    let syslog = config
+        .syslog()
+        .as_ref()
+        .map(|destination| Syslog::connect(destination, Some(config.oracle_location())))
+        .transpose()?;
+    // created before the logger, as log lines are recorded too
+    let recording = config
+        .record_file()
+        .as_deref()
+        .map(|path| Recording::create(path, &config).map(Arc::new))
+        .transpose()?;
+    if *config.no_tui() {
+        init_plain_logging(
+            *config.log_level(),
+            config.output_file().as_deref(),
+            syslog,
+            recording.clone(),
+        )?;
+    } else {
+        init_logging(
+            *config.log_level(),
+            config.output_file().as_deref(),
+            syslog,
+            recording.clone(),
+        )?;
+    }
     // couldn't log cypher text info during parsing as logger wasn't initiated yet
     info!(target: LOG_TARGET, "Using encoding:");
     info!(
@@ -67,45 +204,233 @@ fn main() -> Result<()> {
         "- URL encoded: {}",
         config.cypher_text().url_encoded()
     );
+    if config.cypher_text().prefix_in_requests()
+        && config.cypher_text().amount_blocks() > LARGE_CYPHER_TEXT_BLOCKS
+    {
+        info!(
+            target: LOG_TARGET,
+            "The cypher text has {} blocks. Questions about later blocks include all blocks preceding them. Consider `--short-requests` if the oracle allows it",
+            config.cypher_text().amount_blocks()
+        );
+    }
+    if let Some(block_selection) = config.block_selection() {
+        info!(
+            target: LOG_TARGET,
+            "Decrypting only block(s): {}", block_selection
+        );
+    }
+
+    if let SubConfig::Web(web_config) = config.sub_config() {
+        if let Some(amount_requests) = web_config.dry_run() {
+            return dry_run(&config, web_config, *amount_requests);
+        }
+    }
 
-    let tui = Tui::new(config.block_size()).context("TUI creation failed")?;
+    // without TUI, the terminal is left alone entirely
+    let tui = (!*config.no_tui())
+        .then(|| {
+            Tui::new(
+                config.block_size(),
+                config.keymap().clone(),
+                Some(config.request_budget().clone()),
+                config.cast_file().as_deref(),
+            )
+        })
+        .transpose()
+        .context("TUI creation failed")?;
+    let headless = config.no_tui().then(Headless::new);
+    let timeline_file = config.timeline_file().clone();
+    let har = match config.sub_config() {
+        SubConfig::Web(web_config) => web_config.har().clone(),
+        SubConfig::Script(_)
+        | SubConfig::Tcp(_)
+        | SubConfig::Plugin(_)
+        | SubConfig::Lua(_)
+        | SubConfig::Python(_) => None,
+    };
+    let timeline = timeline_file.as_ref().map(|_| Timeline::new());
+    let json_result =
+        (*config.output_format() == OutputFormat::Json).then(|| JsonResult::new(&config));
+    let event_stream = config
+        .events_file()
+        .as_deref()
+        .map(EventStream::open)
+        .transpose()?;
+    let report = config
+        .report_file()
+        .as_deref()
+        .map(|path| Report::new(&config, path));
+    let guess_stats = config.guess_stats_file().as_deref().map(GuessStats::new);
+    let run_stats = config.share_stats().then(|| RunStats::new(&config));
+    let webhook = config
+        .notify_webhook()
+        .as_ref()
+        .map(|url| Webhook::new(&config, url))
+        .transpose()?;
 
-    let update_ui_callback = |event| tui.handle_application_event(event);
+    let update_ui_callback = |event| {
+        // events arrive steadily during the attack, so suppressed messages are summarised periodically
+        summarise_coalesced(false);
+        if let Some(timeline) = &timeline {
+            timeline.record_ui_event(&event);
+        }
+        if let Some(json_result) = &json_result {
+            json_result.record_ui_event(&event);
+        }
+        if let Some(event_stream) = &event_stream {
+            event_stream.record_ui_event(&event);
+        }
+        if let Some(recording) = &recording {
+            recording.record_ui_event(&event);
+        }
+        if let Some(report) = &report {
+            report.record_ui_event(&event);
+        }
+        if let Some(guess_stats) = &guess_stats {
+            guess_stats.record_ui_event(&event);
+        }
+        if let Some(run_stats) = &run_stats {
+            run_stats.record_ui_event(&event);
+        }
+        if let Some(webhook) = &webhook {
+            webhook.record_ui_event(&event);
+        }
+        if let Some(tui) = &tui {
+            tui.handle_application_event(event)
+        } else if let Some(headless) = &headless {
+            headless.handle_application_event(event)
+        }
+    };
     thread::scope(|scope| {
-        if let Err(e) = scope.builder().name("TUI".to_string()).spawn(|_| {
-            if let Err(e) = task::block_on(tui.main_loop()) {
+        if let Some(tui) = &tui {
+            if let Err(e) = scope.builder().name("TUI".to_string()).spawn(|_| {
+                if let Err(e) = task::block_on(tui.main_loop()) {
+                    error!(target: LOG_TARGET, "{:?}", e);
+                    // logic thread can stop the draw main loop, but there is no such thing the other way around
+                    update_ui_callback(UiEvent::Control(UiControlEvent::PrintAfterExit(format!(
+                        "Error: {:?}",
+                        e
+                    ))));
+                    update_ui_callback(UiEvent::Control(UiControlEvent::ExitCode(1)));
+                    tui.exit()
+                }
+            }) {
                 error!(target: LOG_TARGET, "{:?}", e);
-                // logic thread can stop the draw main loop, but there is no such thing the other way around
                 update_ui_callback(UiEvent::Control(UiControlEvent::PrintAfterExit(format!(
                     "Error: {:?}",
                     e
                 ))));
-                update_ui_callback(UiEvent::Control(UiControlEvent::ExitCode(1)));
+                update_ui_callback(UiEvent::Control(UiControlEvent::ExitCode(2)));
                 tui.exit()
             }
-        }) {
-            error!(target: LOG_TARGET, "{:?}", e);
-            update_ui_callback(UiEvent::Control(UiControlEvent::PrintAfterExit(format!(
-                "Error: {:?}",
-                e
-            ))));
-            update_ui_callback(UiEvent::Control(UiControlEvent::ExitCode(2)));
-            tui.exit()
         }
 
         if let Err(e) = scope
             .builder()
             .name("Padding oracle attack".to_string())
             .spawn(|_| {
-                if let Err(e) = logic_preparation(config, update_ui_callback) {
-                    error!(target: LOG_TARGET, "{:?}", e);
-                    update_ui_callback(UiEvent::Control(UiControlEvent::PrintAfterExit(format!(
-                        "Error: {:?}",
-                        e
-                    ))));
-                    update_ui_callback(UiEvent::Control(UiControlEvent::ExitCode(3)));
-                    (update_ui_callback)(UiEvent::Control(UiControlEvent::SlowRedraw));
+                let result = logic_preparation(config, update_ui_callback, timeline.as_ref());
+                // whatever was suppressed is logged before the outcome
+                summarise_coalesced(true);
+                let exit_code = match result {
+                    Ok(()) => 0,
+                    Err(e) => {
+                        error!(target: LOG_TARGET, "{:?}", e);
+                        if let Some(timeline) = &timeline {
+                            timeline.record(TimelineEventKind::Error {
+                                message: format!("{:?}", e),
+                                kind: RustpadError::find(&e).map(RustpadError::kind),
+                            });
+                        }
+                        if let Some(event_stream) = &event_stream {
+                            event_stream.record(StreamedEventKind::Error {
+                                message: format!("{:#}", e),
+                                kind: RustpadError::find(&e).map(RustpadError::kind),
+                            });
+                        }
+                        if let Some(json_result) = &json_result {
+                            json_result.record_error(&e);
+                        }
+                        update_ui_callback(UiEvent::Control(UiControlEvent::PrintAfterExit(
+                            format!("Error: {:?}", e),
+                        )));
+                        let exit_code = error::exit_code(&e);
+                        update_ui_callback(UiEvent::Control(UiControlEvent::ExitCode(exit_code)));
+                        exit_code
+                    }
+                };
+                if let Some(event_stream) = &event_stream {
+                    event_stream.record(StreamedEventKind::Finished { exit_code });
+                }
+                if let Some(run_stats) = &run_stats {
+                    // secondary to the attack, so no reason to fail it
+                    match run_stats.append(exit_code) {
+                        Ok(stats_file_path) => info!(
+                            target: LOG_TARGET,
+                            "Appended anonymous run statistics to `{}`",
+                            stats_file_path.display()
+                        ),
+                        Err(e) => warn!(target: LOG_TARGET, "{:?}", e),
+                    }
+                }
+
+                // export before the UI gets the chance to quit the application
+                if let (Some(timeline), Some(timeline_file)) = (&timeline, &timeline_file) {
+                    if let Err(e) = timeline.export(timeline_file) {
+                        error!(target: LOG_TARGET, "{:?}", e);
+                        update_ui_callback(UiEvent::Control(UiControlEvent::PrintAfterExit(
+                            format!("Error: {:?}", e),
+                        )));
+                    }
+                }
+
+                if let Some(har) = &har {
+                    if let Err(e) = har.export() {
+                        error!(target: LOG_TARGET, "{:?}", e);
+                        update_ui_callback(UiEvent::Control(UiControlEvent::PrintAfterExit(
+                            format!("Error: {:?}", e),
+                        )));
+                    }
+                }
+
+                if let Some(report) = &report {
+                    if let Err(e) = report.export() {
+                        error!(target: LOG_TARGET, "{:?}", e);
+                        update_ui_callback(UiEvent::Control(UiControlEvent::PrintAfterExit(
+                            format!("Error: {:?}", e),
+                        )));
+                    }
                 }
+
+                if let Some(guess_stats) = &guess_stats {
+                    if let Err(e) = guess_stats.export() {
+                        error!(target: LOG_TARGET, "{:?}", e);
+                        update_ui_callback(UiEvent::Control(UiControlEvent::PrintAfterExit(
+                            format!("Error: {:?}", e),
+                        )));
+                    }
+                }
+
+                if let Some(webhook) = &webhook {
+                    webhook.finished();
+                }
+
+                if let Some(json_result) = &json_result {
+                    match json_result.render() {
+                        Ok(document) => update_ui_callback(UiEvent::Control(
+                            UiControlEvent::PrintDocumentAfterExit(document),
+                        )),
+                        Err(e) => {
+                            error!(target: LOG_TARGET, "{:?}", e);
+                            update_ui_callback(UiEvent::Control(UiControlEvent::PrintAfterExit(
+                                format!("Error: {:?}", e),
+                            )));
+                        }
+                    }
+                }
+
+                // keep window open for user to read results
+                (update_ui_callback)(UiEvent::Control(UiControlEvent::SlowRedraw));
             })
         {
             error!(target: LOG_TARGET, "{:?}", e);
@@ -122,74 +447,278 @@ fn main() -> Result<()> {
     Ok(())
 }
 
-fn logic_preparation<U>(config: Config, mut update_ui_callback: U) -> Result<()>
+fn logic_preparation<U>(
+    config: Config,
+    update_ui_callback: U,
+    timeline: Option<&Timeline>,
+) -> Result<()>
 where
     U: FnMut(UiEvent) + Sync + Send + Clone,
 {
-    let encryption_mode = config.plain_text().is_some();
-    let decryptor = if encryption_mode {
-        Decryptor::new_encryption(update_ui_callback.clone(), config.cypher_text())
-    } else {
-        Decryptor::new_decryption_only(update_ui_callback.clone(), config.cypher_text())
+    if config.cypher_text_segments().is_empty() {
+        return attack(&config, update_ui_callback, timeline).map(|_| ());
+    }
+
+    // segments are independent cypher texts, so each is attacked from scratch, calibration included
+    let segments = config.cypher_text_segments().clone();
+    let amount_segments = segments.len();
+    let mut config = config;
+    let mut plain_texts = Vec::with_capacity(amount_segments);
+    for (idx, segment) in segments.into_iter().enumerate() {
+        info!(
+            target: LOG_TARGET,
+            "Attacking segment {} of {}",
+            idx + 1,
+            amount_segments
+        );
+        if *config.segment_output() == SegmentOutput::Separate {
+            (update_ui_callback.clone())(UiEvent::Control(UiControlEvent::PrintAfterExit(
+                format!("Segment {} of {}:", idx + 1, amount_segments),
+            )));
+        }
+
+        config = config.with_cypher_text(segment);
+        let plain_text = attack(&config, update_ui_callback.clone(), timeline).context(format!(
+            "Attacking segment {} of {} failed",
+            idx + 1,
+            amount_segments
+        ))?;
+        if let Some(plain_text) = plain_text {
+            plain_texts.push(
+                config
+                    .padding_scheme()
+                    .unpad(&plain_text, **config.block_size() as usize)
+                    .unwrap_or_else(|| {
+                        warn!(
+                            target: LOG_TARGET,
+                            "The padding of segment {} is invalid. Reporting it as is",
+                            idx + 1
+                        );
+                        &plain_text
+                    })
+                    .to_vec(),
+            );
+        }
+    }
+
+    report_segments(&config, &plain_texts, update_ui_callback);
+    Ok(())
+}
+
+/// Print the plain texts of all segments, for `--segment-output`. Separate results were already printed per segment
+fn report_segments<U>(config: &Config, plain_texts: &[Vec<u8>], mut update_ui_callback: U)
+where
+    U: FnMut(UiEvent) + Sync + Send + Clone,
+{
+    // rendered as the plain text of a single cypher text would be
+    let render = |bytes: &[u8]| {
+        bytes
+            .iter()
+            .map(|byte_value| *byte_value as char)
+            .collect::<String>()
     };
 
-    match config.oracle_location() {
+    match config.segment_output() {
+        SegmentOutput::Separate => {}
+        SegmentOutput::Lines => {
+            for plain_text in plain_texts {
+                (update_ui_callback)(UiEvent::Control(UiControlEvent::PrintAfterExit(render(
+                    plain_text,
+                ))));
+            }
+        }
+        SegmentOutput::Delimited => {
+            let delimiter = config
+                .output_delimiter()
+                .as_ref()
+                .expect("Delimited segment output has a delimiter");
+            let assembled = render(&plain_texts.join(&delimiter[..]));
+            info!(target: LOG_TARGET, "Reassembled segments: {}", assembled);
+            (update_ui_callback)(UiEvent::Control(UiControlEvent::PrintAfterExit(assembled)));
+        }
+    }
+}
+
+/// Returns the decrypted plain text, with padding, unless encrypting
+fn attack<U>(
+    config: &Config,
+    update_ui_callback: U,
+    timeline: Option<&Timeline>,
+) -> Result<Option<Vec<u8>>>
+where
+    U: FnMut(UiEvent) + Sync + Send + Clone,
+{
+    info!(
+        target: LOG_TARGET,
+        "Using {} oracle",
+        config.oracle_location().kind()
+    );
+    let location = config.oracle_location();
+    let sub_config = config.sub_config();
+    let plain_text = match location {
         OracleLocation::Web(_) => {
-            info!(target: LOG_TARGET, "Using web oracle");
-            let mut oracle = WebOracle::visit(config.oracle_location(), config.sub_config())?;
-            let padding_error_response =
-                calibrate_web(&decryptor, update_ui_callback.clone(), &config)?;
-            oracle.set_padding_error_response(Some(padding_error_response.clone()));
-            let cache = if *config.no_cache() {
-                None
-            } else {
-                Some(Cache::load_from_file(CacheConfig::new(
-                    oracle.location(),
-                    Some(padding_error_response),
-                ))?)
-            };
-
-            logic_main(
-                &decryptor,
+            let (oracle, padding_error_response) =
+                open_web_oracle(update_ui_callback.clone(), config, timeline)?;
+            run_with(
                 &oracle,
-                Arc::new(Mutex::new(cache)),
-                encryption_mode,
-                update_ui_callback.clone(),
-                &config,
-            )?;
+                Some(padding_error_response),
+                update_ui_callback,
+                config,
+            )?
         }
-        OracleLocation::Script(_) => {
-            info!(target: LOG_TARGET, "Using script oracle");
-            let oracle = ScriptOracle::visit(config.oracle_location(), config.sub_config())?;
-            let cache = if *config.no_cache() {
-                None
-            } else {
-                Some(Cache::load_from_file(CacheConfig::new(
-                    oracle.location(),
-                    None,
-                ))?)
-            };
-
-            logic_main(
-                &decryptor,
-                &oracle,
-                Arc::new(Mutex::new(cache)),
-                encryption_mode,
-                update_ui_callback.clone(),
-                &config,
-            )?;
+        OracleLocation::Script(_) => run_with(
+            &ScriptOracle::visit(location, sub_config)?,
+            None,
+            update_ui_callback,
+            config,
+        )?,
+        OracleLocation::Tcp(_) => run_with(
+            &TcpOracle::visit(location, sub_config)?,
+            None,
+            update_ui_callback,
+            config,
+        )?,
+        OracleLocation::Plugin(_) => run_with(
+            &PluginOracle::visit(location, sub_config)?,
+            None,
+            update_ui_callback,
+            config,
+        )?,
+        OracleLocation::Lua(_) => run_with(
+            &LuaOracle::visit(location, sub_config)?,
+            None,
+            update_ui_callback,
+            config,
+        )?,
+        OracleLocation::Python(_) => run_with(
+            &PythonOracle::visit(location, sub_config)?,
+            None,
+            update_ui_callback,
+            config,
+        )?,
+    };
+
+    Ok(plain_text)
+}
+
+/// Visit and calibrate the web oracle. Returns it along with the padding error response, under which its cache is kept
+fn open_web_oracle<U>(
+    update_ui_callback: U,
+    config: &Config,
+    timeline: Option<&Timeline>,
+) -> Result<(WebOracle, CalibrationResponse)>
+where
+    U: FnMut(UiEvent) + Sync + Send + Clone,
+{
+    let mut oracle = WebOracle::visit(config.oracle_location(), config.sub_config())?;
+    oracle.watch_maintenance(config.cypher_text());
+    if let Some(timeline) = timeline {
+        timeline.record(TimelineEventKind::CalibrationStarted);
+    }
+    let padding_error_response = calibrate_web(update_ui_callback.clone(), config, &mut oracle)?;
+    (update_ui_callback.clone())(UiEvent::Control(UiControlEvent::Calibrated(
+        padding_error_response.clone(),
+    )));
+    if let Some(timeline) = timeline {
+        timeline.record(TimelineEventKind::CalibrationFinished);
+    }
+    oracle.set_padding_error_response(Some(padding_error_response.clone()));
+    oracle.warm_up(config.cypher_text())?;
+
+    Ok((oracle, padding_error_response))
+}
+
+/// Attack with the oracle, whichever kind it is, once its cache is loaded. Only the web oracle has a padding error response to key the cache by
+fn run_with<O: Oracle, U>(
+    oracle: &O,
+    padding_error_response: Option<CalibrationResponse>,
+    update_ui_callback: U,
+    config: &Config,
+) -> Result<Option<Vec<u8>>>
+where
+    U: FnMut(UiEvent) + Sync + Send + Clone,
+{
+    let cache = if *config.no_cache() {
+        None
+    } else {
+        Some(Cache::load_from_file(CacheConfig::new(
+            oracle.location(),
+            padding_error_response,
+        ))?)
+    };
+
+    let encryption_mode = config.plain_text().is_some() || config.plain_text_edit().is_some();
+    logic_main(
+        oracle,
+        Arc::new(Mutex::new(cache)),
+        encryption_mode,
+        update_ui_callback,
+        config,
+    )
+}
+
+/// Decrypts the blocks needed for the mode of attack. Encryption needs only the last block, unless tampering with the IV suffices
+fn decryptor_for<'a, U>(
+    config: &'a Config,
+    cache: Option<&Cache>,
+    update_ui_callback: U,
+) -> Decryptor<'a, UiObserver<U>>
+where
+    U: FnMut(UiEvent) + Sync + Send + Clone,
+{
+    if config.plain_text().is_some() && !iv_tampering_possible_for(config, cache) {
+        Decryptor::new_encryption(
+            UiObserver::decryption(update_ui_callback),
+            config.cypher_text(),
+            *config.padding_scheme(),
+        )
+    } else {
+        Decryptor::new_decryption_only(
+            UiObserver::decryption(update_ui_callback),
+            config.cypher_text(),
+            *config.padding_scheme(),
+            config.block_selection().as_ref(),
+        )
+    }
+}
+
+/// Print the first requests of calibration, for `--dry-run`. Nothing is sent
+fn dry_run(config: &Config, web_config: &WebConfig, amount_requests: usize) -> Result<()> {
+    let url = match config.oracle_location() {
+        OracleLocation::Web(url) => url,
+        OracleLocation::Script(_)
+        | OracleLocation::Tcp(_)
+        | OracleLocation::Plugin(_)
+        | OracleLocation::Lua(_)
+        | OracleLocation::Python(_) => {
+            panic!("Dry runs are only supported by the web oracle")
         }
     };
 
-    // keep window open for user to read results
-    (update_ui_callback)(UiEvent::Control(UiControlEvent::SlowRedraw));
+    // calibration tries every value of the last byte of the first block to solve
+    let decryptor = decryptor_for(config, None, |_| {});
+    let calibration_probes: Vec<_> = (u8::MIN..=u8::MAX)
+        .take(amount_requests)
+        .map(|byte_value| {
+            let mut forged_cypher_text = decryptor.forged_cypher_texts()[0].clone();
+            forged_cypher_text.set_current_byte(byte_value);
+            forged_cypher_text
+        })
+        .collect();
+
+    let requests = oracle::web::dry_run(url, web_config, &calibration_probes)?;
+    for (idx, request) in requests.iter().enumerate() {
+        println!("# Request {} of {}", idx + 1, requests.len());
+        println!("{}", request);
+    }
+    println!("Dry run: no requests were sent");
     Ok(())
 }
 
 fn calibrate_web<U>(
-    decryptor: &Decryptor<U>,
     mut update_ui_callback: U,
     config: &Config,
+    oracle: &mut WebOracle,
 ) -> Result<CalibrationResponse>
 where
     U: FnMut(UiEvent) + Sync + Send + Clone,
@@ -199,72 +728,223 @@ where
         config.cypher_text().blocks().to_vec(),
     )));
 
+    if let SubConfig::Web(web_config) = config.sub_config() {
+        if let Some(padding_error_response) =
+            CalibrationResponse::from_overrides(web_config.padding_error_overrides())
+        {
+            info!(
+                target: LOG_TARGET,
+                "Skipping calibration, using the given padding error response"
+            );
+            return Ok(padding_error_response);
+        }
+    }
+
     info!(target: LOG_TARGET, "Calibrating web oracle...");
+    // the cache isn't loaded yet, as it's kept per calibration. Either way, the same blocks are calibrated with
+    let decryptor = decryptor_for(config, None, update_ui_callback);
     let web_calibrator = decryptor.web_calibrator();
-    let calibration_oracle =
+    let mut calibration_oracle =
         CalibrationWebOracle::visit(config.oracle_location(), config.sub_config())?;
-    web_calibrator.determine_padding_error_response(calibration_oracle)
+    calibration_oracle.watch_maintenance(config.cypher_text());
+    calibration_oracle.warm_up(config.cypher_text())?;
+    let padding_error_response =
+        web_calibrator.determine_padding_error_response(&mut calibration_oracle)?;
+
+    // the oracle's verdicts are only meaningful under the same conditions as calibration
+    if calibration_oracle.cache_busting() {
+        oracle.enable_cache_busting();
+    }
+    Ok(padding_error_response)
 }
 
 fn logic_main<U>(
-    decryptor: &Decryptor<U>,
     oracle: &impl Oracle,
     cache: Arc<Mutex<Option<Cache>>>,
     encryption_mode: bool,
-    mut update_ui_callback: U,
+    update_ui_callback: U,
     config: &Config,
-) -> Result<()>
+) -> Result<Option<Vec<u8>>>
 where
     U: FnMut(UiEvent) + Sync + Send + Clone,
 {
+    let decryptor = decryptor_for(
+        config,
+        cache.lock().unwrap().as_ref(),
+        update_ui_callback.clone(),
+    );
     (update_ui_callback.clone())(UiEvent::Decryption(UiDecryptionEvent::InitDecryption(
         config.cypher_text().blocks().to_vec(),
     )));
+    // the IV and blocks which weren't selected aren't decrypted. Encryption decrypts just what it forges from
+    let decryption_bytes =
+        decryptor.forged_cypher_texts().len() * *config.cypher_text().block_size() as usize;
+    let encryption_bytes = match (config.plain_text(), config.plain_text_edit()) {
+        (Some(plain_text), _) => plain_text.amount_blocks() * *plain_text.block_size() as usize,
+        // the edited plain text is only known once decrypted. Assume it's as long as the original
+        (None, Some(_)) => decryption_bytes,
+        (None, None) => 0,
+    };
     (update_ui_callback.clone())(UiEvent::Control(UiControlEvent::IndicateWork(
-        if encryption_mode {
-            let plain_text = config
-                .plain_text()
-                .as_ref()
-                .expect("Should have a plain text in encryption mode");
-            // + 1 for decrypting a block of cypher text
-            (plain_text.amount_blocks() + 1) * *plain_text.block_size() as usize
-        } else {
-            let cypher_text = config.cypher_text();
-            // -1 as IV doesn't have to be decrypted
-            (cypher_text.amount_blocks() - 1) * *cypher_text.block_size() as usize
-        },
+        decryption_bytes,
+        encryption_bytes,
     )));
 
+    let key_verifier = config
+        .verification_key()
+        .clone()
+        .map(|key| KeyVerifier::new(key, config.verification_iv().clone()));
+
+    let mut session = config
+        .session_file()
+        .as_deref()
+        .map(|path| Session::load_from_file(path, config.session_passphrase().as_deref()))
+        .transpose()?;
+    // the state is built from the session's progress, which then needn't be saved to a file
+    if session.is_none()
+        && (config.export_state_file().is_some() || config.import_state_file().is_some())
+    {
+        session = Some(Session::in_memory());
+    }
+    if let (Some(session), Some(import_state_file)) = (&mut session, config.import_state_file()) {
+        AttackState::load(import_state_file)?.import_into(session)?;
+    }
+    let session = Arc::new(Mutex::new(session));
+
+    // learns from this cypher text's plain text only, though it may be seeded with the cached plain text of others
+    let mut guess_strategy = config.guess_strategy().to_strategy(config.guess_order());
+    let cached_plain_texts = cache
+        .lock()
+        .unwrap()
+        .as_ref()
+        .filter(|_| config.guess_strategy().warm_starts() && !*config.no_warm_start())
+        .map(|cache| cache.plain_texts())
+        .unwrap_or_default();
+    if !cached_plain_texts.is_empty() {
+        info!(
+            target: LOG_TARGET,
+            "Seeding guesses with the plain text of {} cached block(s) of this oracle",
+            cached_plain_texts.len()
+        );
+        guess_strategy = Box::new(WarmStartStrategy::new(guess_strategy, cached_plain_texts));
+    }
     let now = Instant::now();
-    let decryption_results = decryptor.decrypt_blocks(oracle, cache.clone())?;
+    let decryption_results = decryptor.decrypt_blocks(
+        oracle,
+        cache.clone(),
+        session.clone(),
+        PlainTextExpectations {
+            known_plain_text: config.known_plain_text(),
+            guess_strategy: guess_strategy.as_ref(),
+        },
+        *config.strict(),
+    );
+    if let (Some(session), Some(export_state_file)) =
+        (session.lock().unwrap().as_ref(), config.export_state_file())
+    {
+        AttackState::new(
+            decryptor.forged_cypher_texts(),
+            session,
+            *config.padding_scheme(),
+            guess_strategy.as_ref(),
+        )
+        .export(export_state_file)?;
+    }
+    let decryption_results = decryption_results?;
 
     if encryption_mode {
+        let edited_plain_text;
+        let plain_text = match config.plain_text_edit() {
+            Some(plain_text_edit) => {
+                edited_plain_text = edit_plain_text(plain_text_edit, &decryption_results, config)?;
+                &edited_plain_text
+            }
+            None => config
+                .plain_text()
+                .as_ref()
+                .expect("Should have a plain text in encryption mode"),
+        };
+
+        if iv_tampering_possible(config.cypher_text(), plain_text, *config.no_iv()) {
+            if let Some(forged_cypher_text) =
+                forge_by_iv_tampering(config.cypher_text(), &decryption_results, plain_text)
+            {
+                info!(
+                    target: LOG_TARGET,
+                    "Plain text only differs from the original in the 1st block. Tampered with the IV instead of questioning the oracle"
+                );
+                (update_ui_callback.clone())(UiEvent::Control(UiControlEvent::ProgressUpdate(
+                    Phase::Encryption,
+                    plain_text.amount_blocks() * *plain_text.block_size() as usize,
+                )));
+
+                if let Some(intermediates_file) = config.intermediates_file() {
+                    // tampering with the IV doesn't change the intermediates
+                    Intermediates::from_solutions(&decryption_results)
+                        .export(intermediates_file)?;
+                }
+
+                let encrypted_plain_text = forged_cypher_text.encode();
+                let verification = key_verifier
+                    .map(|key_verifier| {
+                        key_verifier
+                            .verify_encryption(forged_cypher_text.blocks(), plain_text.blocks())
+                    })
+                    .transpose();
+                info!(
+                    target: LOG_TARGET,
+                    "The oracle talked some gibberish. It took {}",
+                    format_duration(Duration::new(now.elapsed().as_secs(), 0))
+                );
+                info!(
+                    target: LOG_TARGET,
+                    "Their divination is: {}", encrypted_plain_text
+                );
+                (update_ui_callback.clone())(UiEvent::Control(UiControlEvent::PrintAfterExit(
+                    encrypted_plain_text,
+                )));
+                write_poc(config, &forged_cypher_text, update_ui_callback.clone());
+                report_verification(verification?, update_ui_callback);
+                return Ok(None);
+            }
+
+            info!(
+                target: LOG_TARGET,
+                "Plain text differs from the original beyond the 1st block. Falling back to regular encryption"
+            );
+        }
+
+        let original_intermediates = Intermediates::from_solutions(&decryption_results);
         let last_block = decryption_results
             .into_iter()
             .max_by_key(|cypher_text| cypher_text.original_blocks().len())
             .expect("Can't encrypt without having decrypted a block");
         (update_ui_callback.clone())(UiEvent::Encryption(UiEncryptionEvent::InitEncryption(
-            config
-                .plain_text()
-                .as_ref()
-                .expect("Should have a plain text in encryption mode")
-                .blocks()
-                .to_vec(),
+            plain_text.blocks().to_vec(),
             last_block.block_to_decrypt().clone(),
         )));
 
-        let encryptor = Encryptor::new(update_ui_callback.clone(), last_block);
+        let encryptor = Encryptor::new(
+            UiObserver::encryption(update_ui_callback.clone()),
+            last_block,
+        );
 
-        let encrypted_plain_text = encryptor
-            .encrypt_plain_text(
-                config
-                    .plain_text()
-                    .as_ref()
-                    .expect("Should have a plain text in encryption mode"),
-                oracle,
-                cache,
-            )?
-            .encode();
+        let forged_cypher_text =
+            encryptor.encrypt_plain_text(plain_text, oracle, cache, session, *config.strict())?;
+        if let Some(intermediates_file) = config.intermediates_file() {
+            original_intermediates
+                .chain(Intermediates::from_encryption(
+                    &forged_cypher_text,
+                    plain_text,
+                ))
+                .export(intermediates_file)?;
+        }
+        let encrypted_plain_text = forged_cypher_text.encode();
+        let verification = key_verifier
+            .map(|key_verifier| {
+                key_verifier.verify_encryption(forged_cypher_text.blocks(), plain_text.blocks())
+            })
+            .transpose();
 
         info!(
             target: LOG_TARGET,
@@ -275,9 +955,12 @@ where
             target: LOG_TARGET,
             "Their divination is: {}", encrypted_plain_text
         );
-        (update_ui_callback)(UiEvent::Control(UiControlEvent::PrintAfterExit(
+        (update_ui_callback.clone())(UiEvent::Control(UiControlEvent::PrintAfterExit(
             encrypted_plain_text,
         )));
+        write_poc(config, &forged_cypher_text, update_ui_callback.clone());
+        report_verification(verification?, update_ui_callback);
+        Ok(None)
     } else {
         info!(
             target: LOG_TARGET,
@@ -289,15 +972,193 @@ where
             .iter()
             .map(|forged_cypher_text| forged_cypher_text.plain_text_solution())
             .collect();
+        let verification = key_verifier
+            .map(|key_verifier| {
+                let attack_plain_text: Vec<_> = decryption_results
+                    .iter()
+                    .map(|forged_cypher_text| forged_cypher_text.plain_text_block())
+                    .collect();
+                key_verifier.verify_decryption(config.cypher_text().blocks(), &attack_plain_text)
+            })
+            .transpose();
 
         info!(
             target: LOG_TARGET,
             "Their divination is: {}", plain_text_solution
         );
-        (update_ui_callback)(UiEvent::Control(UiControlEvent::PrintAfterExit(
-            plain_text_solution,
-        )));
+        let plain_text: Vec<u8> = decryption_results
+            .iter()
+            .flat_map(|forged_cypher_text| forged_cypher_text.plain_text_block().to_vec())
+            .collect();
+        if let Some(plain_text_file) = config.plain_text_file() {
+            write_plain_text(plain_text_file, &plain_text, config)?;
+        }
+        if let Some(intermediates_file) = config.intermediates_file() {
+            Intermediates::from_solutions(&decryption_results).export(intermediates_file)?;
+        }
+        // segments are otherwise reported together, once all are decrypted
+        if config.cypher_text_segments().is_empty()
+            || *config.segment_output() == SegmentOutput::Separate
+        {
+            (update_ui_callback.clone())(UiEvent::Control(UiControlEvent::PrintAfterExit(
+                plain_text_solution,
+            )));
+        }
+        report_verification(verification?, update_ui_callback.clone());
+        report_analysis(config, &plain_text, update_ui_callback);
+        Ok(Some(plain_text))
+    }
+}
+
+/// Write the proof of concept of `--poc`. The forged cypher text is printed either way, so failing to write it doesn't fail the attack
+fn write_poc<U>(config: &Config, forged_cypher_text: &CypherText, mut update_ui_callback: U)
+where
+    U: FnMut(UiEvent) + Sync + Send + Clone,
+{
+    if let Err(e) = poc::write(config, forged_cypher_text) {
+        error!(target: LOG_TARGET, "{:?}", e);
+        update_ui_callback(UiEvent::Control(UiControlEvent::PrintAfterExit(format!(
+            "Error: {:?}",
+            e
+        ))));
+    }
+}
+
+/// Print the outcome of `--verify-key`, after the result itself
+fn report_verification<U>(verification: Option<String>, mut update_ui_callback: U)
+where
+    U: FnMut(UiEvent) + Sync + Send + Clone,
+{
+    if let Some(summary) = verification {
+        (update_ui_callback)(UiEvent::Control(UiControlEvent::PrintAfterExit(summary)));
+    }
+}
+
+/// Print follow-up suggestions for the decrypted plain text, or its whole analysis for `--analyze`
+fn report_analysis<U>(config: &Config, plain_text: &[u8], mut update_ui_callback: U)
+where
+    U: FnMut(UiEvent) + Sync + Send + Clone,
+{
+    let block_size = **config.block_size() as usize;
+    let plain_text = config
+        .padding_scheme()
+        .unpad(plain_text, block_size)
+        .unwrap_or(plain_text);
+
+    for line in PlainTextAnalysis::new(plain_text, Some(block_size)).lines(*config.analyze()) {
+        info!(target: LOG_TARGET, "{}", line);
+        (update_ui_callback)(UiEvent::Control(UiControlEvent::PrintAfterExit(line)));
+    }
+}
+
+/// The plain text to encrypt for `--reencrypt-with`: the decrypted one without its padding, edited
+fn edit_plain_text(
+    plain_text_edit: &PlainTextEdit,
+    decryption_results: &[SolvedForgedCypherText],
+    config: &Config,
+) -> Result<PlainText> {
+    let render = |bytes: &[u8]| {
+        bytes
+            .iter()
+            .map(|byte_value| *byte_value as char)
+            .collect::<String>()
+    };
+
+    let plain_text: Vec<u8> = decryption_results
+        .iter()
+        .flat_map(|forged_cypher_text| forged_cypher_text.plain_text_block().to_vec())
+        .collect();
+    let plain_text = config
+        .padding_scheme()
+        .unpad(&plain_text, **config.block_size() as usize)
+        .ok_or_else(|| {
+            anyhow!("The decrypted plain text's padding is invalid, so it can't be edited")
+        })?;
+    info!(
+        target: LOG_TARGET,
+        "Decrypted plain text: {}",
+        render(plain_text)
+    );
+
+    let edited_plain_text = plain_text_edit.apply(plain_text)?;
+    info!(
+        target: LOG_TARGET,
+        "Re-encrypting the edited plain text: {}",
+        render(&edited_plain_text)
+    );
+    Ok(PlainText::from_bytes(
+        &edited_plain_text,
+        config.block_size(),
+        config.padding_scheme(),
+    ))
+}
+
+/// Write the raw bytes of the decrypted plain text, for `--output-plaintext`
+fn write_plain_text(path: &Path, plain_text: &[u8], config: &Config) -> Result<()> {
+    let plain_text = if *config.strip_padding() {
+        config
+            .padding_scheme()
+            .unpad(plain_text, **config.block_size() as usize)
+            .unwrap_or_else(|| {
+                warn!(
+                    target: LOG_TARGET,
+                    "The plain text's padding is invalid. Writing it as is"
+                );
+                plain_text
+            })
+    } else {
+        plain_text
     };
 
+    fs::write(path, plain_text).context(format!(
+        "Writing the plain text to `{}` failed",
+        path.display()
+    ))?;
+    info!(
+        target: LOG_TARGET,
+        "Wrote the plain text to `{}`",
+        path.display()
+    );
     Ok(())
 }
+
+/// Decrypting every block only to tamper with the IV wastes requests if the plain text differs beyond the 1st block. So the original's plain text beyond it must be known already, and match
+fn iv_tampering_possible_for(config: &Config, cache: Option<&Cache>) -> bool {
+    let plain_text = match config.plain_text() {
+        Some(plain_text) => plain_text,
+        None => return false,
+    };
+    if !iv_tampering_possible(config.cypher_text(), plain_text, *config.no_iv()) {
+        return false;
+    }
+
+    known_original_plain_text(config, cache)
+        .map(|original| original[..] == plain_text.blocks()[1..])
+        .unwrap_or(false)
+}
+
+/// Plain text of the original's blocks beyond the 1st, if known without questioning the oracle. Each is taken from the cache, or from known plain text hints covering it entirely
+fn known_original_plain_text(config: &Config, cache: Option<&Cache>) -> Option<Vec<Block>> {
+    let cypher_blocks = config.cypher_text().blocks();
+    let block_size = *config.cypher_text().block_size() as usize;
+
+    // the IV and the 1st block are skipped
+    (2..cypher_blocks.len())
+        .map(|idx| {
+            let preceding_block = &cypher_blocks[idx - 1];
+            let cached = cache.and_then(|cache| {
+                cache
+                    .get(&(preceding_block.clone(), cypher_blocks[idx].clone()))
+                    .map(|solution| &solution.to_intermediate() ^ preceding_block)
+            });
+            cached.or_else(|| {
+                // -1 as the IV doesn't hold any plain text
+                let offset = (idx - 1) * block_size;
+                (offset..offset + block_size)
+                    .map(|offset| config.known_plain_text().byte_at(offset))
+                    .collect::<Option<Vec<u8>>>()
+                    .map(|bytes| Block::from(&bytes[..]))
+            })
+        })
+        .collect()
+}
@@ -0,0 +1,231 @@
+use std::{
+    fs,
+    path::Path,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use anyhow::{anyhow, Context, Result};
+use clap::StructOpt;
+use humantime::format_duration;
+use log::info;
+
+use crate::{
+    cli::{Cli, MassCli, SubCommand},
+    config::{Config, SubConfig},
+    curl,
+    error::RustpadError,
+    logging::{init_logging, LOG_TARGET},
+    logic_preparation,
+    other::config_thread_pool,
+    presets,
+    syslog::Syslog,
+    tui::ui_event::{UiControlEvent, UiEvent},
+};
+
+/// Oracle and cypher text of a line of the targets file
+struct Target {
+    url: String,
+    cypher_text: String,
+}
+
+struct TargetOutcome {
+    url: String,
+    // what would've been printed after the TUI exits, i.e. the plain or cypher text
+    result: Result<String>,
+    elapsed: Duration,
+}
+
+/// Attack every target of `--targets` in turn, without TUI. Each target gets its own config, so it's calibrated separately and has its own request budget. A summary table is printed at the end.
+pub(super) fn mass(mass_cli: MassCli) -> Result<()> {
+    let targets = load_targets(mass_cli.targets_file())?;
+    // a mistake in the shared options is reported before any target is attacked
+    let configs = targets
+        .iter()
+        .map(|target| target_config(target, mass_cli.web_options()))
+        .collect::<Result<Vec<_>>>()?;
+
+    let first_config = configs
+        .first()
+        .expect("Targets file holds at least 1 target");
+    config_thread_pool(first_config.thread_count())?;
+    // the targets are logged about in turn, so lines aren't tagged with an oracle
+    let syslog = first_config
+        .syslog()
+        .as_ref()
+        .map(|destination| Syslog::connect(destination, None))
+        .transpose()?;
+    init_logging(
+        *first_config.log_level(),
+        first_config.output_file().as_deref(),
+        syslog,
+        None,
+    )?;
+    if first_config.timeline_file().is_some() {
+        eprintln!("Ignoring `--timeline` in mass mode");
+    }
+    if first_config.report_file().is_some() {
+        eprintln!("Ignoring `--report` in mass mode");
+    }
+    if first_config.guess_stats_file().is_some() {
+        eprintln!("Ignoring `--guess-stats` in mass mode");
+    }
+    if first_config.record_file().is_some() {
+        eprintln!("Ignoring `--record` in mass mode");
+    }
+    if first_config.cast_file().is_some() {
+        eprintln!("Ignoring `--record-cast` in mass mode");
+    }
+    if first_config.notify_webhook().is_some() {
+        eprintln!("Ignoring `--notify-webhook` in mass mode");
+    }
+    // every target would record into the same file
+    if let SubConfig::Web(web_config) = first_config.sub_config() {
+        if web_config.har().is_some() {
+            return Err(anyhow!("`--har` is unsupported in mass mode"));
+        }
+    }
+
+    let amount_targets = targets.len();
+    let mut outcomes = Vec::with_capacity(amount_targets);
+    for (idx, (target, config)) in targets.into_iter().zip(configs).enumerate() {
+        eprintln!("[{}/{}] Attacking {}", idx + 1, amount_targets, target.url);
+        info!(
+            target: LOG_TARGET,
+            "Attacking target {} of {}: {}",
+            idx + 1,
+            amount_targets,
+            target.url
+        );
+
+        let output = Mutex::new(Vec::new());
+        let update_ui_callback = |event| {
+            if let UiEvent::Control(UiControlEvent::PrintAfterExit(message)) = event {
+                output.lock().unwrap().push(message);
+            }
+        };
+
+        let start = Instant::now();
+        let result = logic_preparation(config, update_ui_callback, None)
+            .map(|_| output.into_inner().unwrap().join("\n"));
+        let elapsed = start.elapsed();
+        match &result {
+            Ok(_) => eprintln!("[{}/{}] Done", idx + 1, amount_targets),
+            Err(e) => eprintln!("[{}/{}] Failed: {:#}", idx + 1, amount_targets, e),
+        }
+
+        outcomes.push(TargetOutcome {
+            url: target.url,
+            result,
+            elapsed,
+        });
+    }
+
+    print_summary(&outcomes);
+
+    if outcomes.iter().all(|outcome| outcome.result.is_err()) {
+        return Err(anyhow!(
+            "The attack failed against all {} target(s)",
+            amount_targets
+        ));
+    }
+    Ok(())
+}
+
+fn load_targets(path: &Path) -> Result<Vec<Target>> {
+    let content = fs::read_to_string(path)
+        .context(format!("Targets file `{}` failed to open", path.display()))?;
+
+    let targets = content
+        .lines()
+        .enumerate()
+        .map(|(line_idx, line)| (line_idx, line.trim()))
+        .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'))
+        .map(|(line_idx, line)| {
+            let mut fields = line.split_whitespace();
+            match (fields.next(), fields.next(), fields.next()) {
+                (Some(url), Some(cypher_text), None) => Ok(Target {
+                    url: url.to_string(),
+                    cypher_text: cypher_text.to_string(),
+                }),
+                _ => Err(anyhow!(
+                    "Line {} of the targets file is not of the form `<url> <cypher text>`",
+                    line_idx + 1
+                )),
+            }
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    if targets.is_empty() {
+        return Err(anyhow!(
+            "Targets file `{}` holds no targets",
+            path.display()
+        ));
+    }
+    Ok(targets)
+}
+
+/// Config of the `web` sub-command for the target, as if it were given on the command line
+fn target_config(target: &Target, web_options: &[String]) -> Result<Config> {
+    let args = [env!("CARGO_PKG_NAME").to_string(), "web".to_string()]
+        .into_iter()
+        .chain(web_options.iter().cloned())
+        .chain([
+            format!("--oracle={}", target.url),
+            format!("--decrypt={}", target.cypher_text),
+        ]);
+    let cli = Cli::try_parse_from(presets::expand(curl::expand(args)?)?)
+        .context(format!("Invalid options for target `{}`", target.url))?;
+
+    // chunks of `--decrypt` would be glued to the target's cypher text
+    if let SubCommand::Web(web_cli) = &cli.sub_command {
+        if web_cli.cypher_text().len() > 1 {
+            return Err(anyhow!(
+                "The cypher text is taken from the targets file. Don't pass `--decrypt` as a shared option"
+            ));
+        }
+    }
+
+    Config::try_from(cli).context(format!("Invalid options for target `{}`", target.url))
+}
+
+fn print_summary(outcomes: &[TargetOutcome]) {
+    let url_width = outcomes
+        .iter()
+        .map(|outcome| outcome.url.len())
+        .max()
+        .unwrap_or_default()
+        .max("Target".len());
+
+    println!();
+    println!(
+        "{:<4} {:<url_width$} {:<20} {:<10} Result",
+        "#", "Target", "Status", "Time"
+    );
+    for (idx, outcome) in outcomes.iter().enumerate() {
+        let (status, result) = match &outcome.result {
+            Ok(output) => ("solved", output.replace('\n', " ")),
+            Err(e) => (
+                RustpadError::find(e)
+                    .map(RustpadError::kind)
+                    .unwrap_or("failed"),
+                format!("{:#}", e),
+            ),
+        };
+        println!(
+            "{:<4} {:<url_width$} {:<20} {:<10} {}",
+            idx + 1,
+            outcome.url,
+            status,
+            format_duration(Duration::new(outcome.elapsed.as_secs(), 0)).to_string(),
+            result
+        );
+    }
+
+    let solved = outcomes
+        .iter()
+        .filter(|outcome| outcome.result.is_ok())
+        .count();
+    println!();
+    println!("Solved {} of {} target(s)", solved, outcomes.len());
+}
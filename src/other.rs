@@ -6,8 +6,7 @@ use clap_complete::{generate, Shell};
 
 use crate::{cli::Cli, config::thread_count::ThreadCount};
 
-pub(super) const RETRY_DELAY_MS: u64 = 100;
-pub(super) const RETRY_MAX_ATTEMPTS: u64 = 3;
+pub(super) use rustpad_core::other::{RETRY_DELAY_MS, RETRY_MAX_ATTEMPTS};
 
 pub(super) fn config_thread_pool(thread_count: &ThreadCount) -> Result<()> {
     rayon::ThreadPoolBuilder::new()
@@ -0,0 +1,189 @@
+use anyhow::{anyhow, Context, Result};
+use clap::Parser;
+
+use crate::{
+    cli::{Cli, PadBusterCli},
+    curl,
+    presets::{self, KEYWORD},
+};
+
+// options of PadBuster which take a value
+const OPTIONS_WITH_VALUE: &[&str] = &[
+    "auth",
+    "ciphertext",
+    "cookies",
+    "encoding",
+    "error",
+    "headers",
+    "intermediate",
+    "plaintext",
+    "post",
+    "prefix",
+    "proxy",
+    "proxyauth",
+    "resume",
+];
+
+/// Command line of the `web` sub-command which PadBuster's command line stands for, parsed
+pub(super) fn web_cli(padbuster_cli: &PadBusterCli) -> Result<Cli> {
+    let mut args = vec![
+        env!("CARGO_PKG_NAME").to_string(),
+        "web".to_string(),
+        format!("--block-size={}", **padbuster_cli.block_size()),
+    ];
+
+    let sample = padbuster_cli.sample();
+    // PadBuster URL decodes the sample, as it's usually copied from a URL
+    let decoded_sample = urlencoding::decode(sample)
+        .map(|sample| sample.into_owned())
+        .unwrap_or_else(|_| sample.clone());
+    args.push(format!("--decrypt={}", decoded_sample));
+    // as PadBuster does, the sample is replaced as given, and also as encoded differently in another part of the request
+    let locate = |part: &str| {
+        [
+            urlencoding::encode(&decoded_sample).into_owned(),
+            sample.clone(),
+            decoded_sample.clone(),
+        ]
+        .iter()
+        .fold(part.to_string(), |part, form| part.replace(form, KEYWORD))
+    };
+
+    let url = locate(padbuster_cli.url());
+    let mut located = url.contains(KEYWORD);
+    args.push(format!("--oracle={}", url));
+
+    let mut encoding = "base64";
+    let mut verbosity = 0;
+    let mut options = padbuster_cli.options().iter();
+    while let Some(option) = options.next() {
+        if option == "--" {
+            break;
+        }
+
+        // as Perl's `Getopt::Long` parses them: `-<name>`, `--<name>`, with the value attached by `=` or as the next argument
+        let (name, attached_value) = match option.strip_prefix('-') {
+            Some(option) => {
+                let option = option.strip_prefix('-').unwrap_or(option);
+                match option.split_once('=') {
+                    Some((name, value)) => (name.to_lowercase(), Some(value.to_string())),
+                    None => (option.to_lowercase(), None),
+                }
+            }
+            None => {
+                return Err(anyhow!(
+                    "Unexpected argument `{}`. Only the URL, sample and block size are given without option",
+                    option
+                ))
+            }
+        };
+        let value = if OPTIONS_WITH_VALUE.contains(&name.as_str()) {
+            attached_value
+                .or_else(|| options.next().cloned())
+                .ok_or_else(|| anyhow!("`-{}` misses its value", name))?
+        } else {
+            String::new()
+        };
+
+        match name.as_str() {
+            "encoding" => {
+                encoding = match value.as_str() {
+                    "0" => "base64",
+                    // hex is sent in lowercase, which most oracles accept in either case
+                    "1" | "2" => "hex",
+                    "4" => "base64url",
+                    "3" => {
+                        return Err(anyhow!(
+                            "`-encoding 3`, .NET's `UrlTokenEncode`, isn't supported"
+                        ))
+                    }
+                    _ => {
+                        return Err(anyhow!(
+                            "`-encoding {}` is not an encoding of PadBuster. Expected one of: [0, 1, 2, 3, 4]",
+                            value
+                        ))
+                    }
+                }
+            }
+            "post" => {
+                let data = locate(&value);
+                located |= data.contains(KEYWORD);
+                args.push(format!("--data={}", data));
+            }
+            "cookies" => {
+                let cookies = locate(&value);
+                located |= cookies.contains(KEYWORD);
+                args.push(format!("--header=Cookie: {}", cookies));
+            }
+            "headers" => {
+                // `<name>::<value>`, separated by `;`
+                for header in value.split(';').filter(|header| !header.is_empty()) {
+                    let (name, value) = header.split_once("::").ok_or_else(|| {
+                        anyhow!(
+                            "`-headers` expects `<name>::<value>` pairs, separated by `;`. Got `{}`",
+                            header
+                        )
+                    })?;
+                    args.push(format!("--header={}: {}", name, value));
+                }
+            }
+            "auth" => {
+                if !value.contains(':') {
+                    return Err(anyhow!(
+                        "`-auth {}`. Expected format `<username>:<password>`",
+                        value
+                    ));
+                }
+                args.push(format!(
+                    "--header=Authorization: Basic {}",
+                    base64::encode(&value)
+                ));
+            }
+            "error" => args.push(format!("--padding-error-regex={}", regex::escape(&value))),
+            "plaintext" => args.push(format!("--encrypt={}", value)),
+            "proxy" => args.push(if value.contains("://") {
+                format!("--proxy={}", value)
+            } else {
+                format!("--proxy=http://{}", value)
+            }),
+            "proxyauth" => args.push(format!("--proxy-credentials={}", value)),
+            "noencode" => args.push("--no-url-encode".to_string()),
+            "noiv" => args.push("--no-iv".to_string()),
+            "usebody" => args.push("--consider-body".to_string()),
+            "verbose" => verbosity = verbosity.max(1),
+            "veryverbose" => verbosity = verbosity.max(2),
+            // the oracle is calibrated automatically, and everything is logged with `--output`
+            "interactive" | "log" => {}
+            "resume" => {
+                return Err(anyhow!(
+                    "`-resume` isn't supported. Decrypt only some blocks with `-- --blocks` instead"
+                ))
+            }
+            "ciphertext" | "intermediate" => {
+                return Err(anyhow!(
+                    "`-{}` isn't supported. Forge cypher texts from known intermediates with the `forge-offline` sub-command instead",
+                    name
+                ))
+            }
+            "bruteforce" | "prefix" => {
+                return Err(anyhow!("`-{}` isn't supported", name))
+            }
+            _ => return Err(anyhow!("`-{}` is not an option of PadBuster", name)),
+        }
+    }
+
+    if !located {
+        return Err(anyhow!(
+            "The sample isn't found in the URL, `-post` data or `-cookies`"
+        ));
+    }
+    args.push(format!("--encoding={}", encoding));
+    if verbosity > 0 {
+        args.push(format!("-{}", "v".repeat(verbosity)));
+    }
+    // options of `web` given after `--` override those of PadBuster
+    args.extend(options.cloned());
+
+    Cli::try_parse_from(presets::expand(curl::expand(args)?)?)
+        .context("The PadBuster arguments don't map onto the options of `web`")
+}
@@ -0,0 +1,255 @@
+mod suggestions;
+
+use std::fmt::Display;
+
+use itertools::Itertools;
+use regex::bytes::Regex;
+
+/// Triage of a recovered plain text: how random it looks, which serialization format it's in, and which secrets it holds.
+pub(super) struct PlainTextAnalysis {
+    // Shannon entropy, in bits per byte. Up to 8, for random data
+    entropy: f64,
+    printable_ratio: f64,
+    format: Option<Format>,
+    findings: Vec<Finding>,
+    // follow-up steps, given the structure of the plain text
+    suggestions: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Format {
+    Json,
+    PhpSerialize,
+    JavaSerialized,
+    Protobuf,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum FindingKind {
+    Jwt,
+    Guid,
+    Email,
+}
+
+struct Finding {
+    kind: FindingKind,
+    offset: usize,
+    value: String,
+}
+
+// stream header of `java.io.ObjectOutputStream`: magic `0xACED`, version 5
+const JAVA_SERIALIZATION_HEADER: [u8; 4] = [0xac, 0xed, 0x00, 0x05];
+// above this, the plain text is likely compressed or encrypted data itself
+const HIGH_ENTROPY: f64 = 7.0;
+
+impl PlainTextAnalysis {
+    /// Analyse the plain text, without padding. `block_size` is `None` for RSA
+    pub(super) fn new(plain_text: &[u8], block_size: Option<usize>) -> Self {
+        let mut analysis = Self {
+            entropy: entropy(plain_text),
+            printable_ratio: printable_ratio(plain_text),
+            format: Format::detect(plain_text),
+            findings: find_secrets(plain_text),
+            suggestions: Vec::new(),
+        };
+        analysis.suggestions = suggestions::suggest(&analysis, plain_text, block_size);
+        analysis
+    }
+
+    /// Lines printed after the result: the whole report for `--analyze`, otherwise just the follow-up suggestions
+    pub(super) fn lines(&self, full_report: bool) -> Vec<String> {
+        if full_report {
+            return self.report();
+        }
+
+        self.suggestions
+            .iter()
+            .map(|suggestion| format!("Suggestion: {}", suggestion))
+            .collect()
+    }
+
+    fn report(&self) -> Vec<String> {
+        let mut lines = vec![format!(
+            "Plain text analysis: entropy {:.2} bits/byte, {:.0}% printable",
+            self.entropy,
+            self.printable_ratio * 100.0
+        )];
+        match self.format {
+            Some(format) => lines.push(format!("- Format: {}", format)),
+            None if self.entropy > HIGH_ENTROPY => lines.push(
+                "- Format: unknown. The entropy is high, so it's likely compressed or encrypted"
+                    .to_string(),
+            ),
+            None => lines.push("- Format: unknown".to_string()),
+        }
+        lines.extend(self.findings.iter().map(|finding| {
+            format!(
+                "- {} at byte {}: {}",
+                finding.kind, finding.offset, finding.value
+            )
+        }));
+        lines.extend(
+            self.suggestions
+                .iter()
+                .map(|suggestion| format!("- Suggestion: {}", suggestion)),
+        );
+
+        lines
+    }
+}
+
+impl Format {
+    fn detect(plain_text: &[u8]) -> Option<Self> {
+        if plain_text.starts_with(&JAVA_SERIALIZATION_HEADER) {
+            Some(Self::JavaSerialized)
+        } else if is_json(plain_text) {
+            Some(Self::Json)
+        } else if is_php_serialized(plain_text) {
+            Some(Self::PhpSerialize)
+        } else if is_protobuf(plain_text) {
+            Some(Self::Protobuf)
+        } else {
+            None
+        }
+    }
+}
+
+impl Display for Format {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Format::Json => write!(f, "JSON"),
+            Format::PhpSerialize => write!(f, "PHP serialize()"),
+            Format::JavaSerialized => write!(f, "Java serialized object"),
+            Format::Protobuf => write!(f, "protobuf (heuristic)"),
+        }
+    }
+}
+
+impl Display for FindingKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FindingKind::Jwt => write!(f, "JWT"),
+            FindingKind::Guid => write!(f, "GUID"),
+            FindingKind::Email => write!(f, "Email address"),
+        }
+    }
+}
+
+fn entropy(data: &[u8]) -> f64 {
+    if data.is_empty() {
+        return 0.0;
+    }
+
+    let counts = data.iter().counts();
+    counts
+        .values()
+        .map(|count| {
+            let probability = *count as f64 / data.len() as f64;
+            -probability * probability.log2()
+        })
+        .sum()
+}
+
+fn printable_ratio(data: &[u8]) -> f64 {
+    if data.is_empty() {
+        return 0.0;
+    }
+
+    let printable = data
+        .iter()
+        .filter(|byte_value| byte_value.is_ascii_graphic() || byte_value.is_ascii_whitespace())
+        .count();
+    printable as f64 / data.len() as f64
+}
+
+/// Only objects and arrays. Any number or quoted string is valid JSON as well, but says nothing about the format
+fn is_json(data: &[u8]) -> bool {
+    let trimmed = data.trim_ascii();
+    (trimmed.starts_with(b"{") || trimmed.starts_with(b"["))
+        && serde_json::from_slice::<serde_json::Value>(trimmed).is_ok()
+}
+
+fn is_php_serialized(data: &[u8]) -> bool {
+    let php_value =
+        Regex::new(r#"^(?:a:\d+:\{|O:\d+:"|C:\d+:"|s:\d+:"|i:-?\d+;|d:-?[\d.E+-]+;|b:[01];|N;)"#)
+            .expect("PHP serialize regex is valid");
+    php_value.is_match(data)
+}
+
+/// Whether the data parses as a sequence of protobuf fields, with valid wire types, and lengths which fit exactly
+fn is_protobuf(data: &[u8]) -> bool {
+    fn read_varint(data: &[u8], idx: &mut usize) -> Option<u64> {
+        let mut value = 0u64;
+        for shift in (0..64).step_by(7) {
+            let byte_value = *data.get(*idx)?;
+            *idx += 1;
+            value |= u64::from(byte_value & 0x7f) << shift;
+            if byte_value & 0x80 == 0 {
+                return Some(value);
+            }
+        }
+        None
+    }
+
+    let mut idx = 0;
+    let mut amount_fields = 0;
+    while idx < data.len() {
+        let key = match read_varint(data, &mut idx) {
+            Some(key) => key,
+            None => return false,
+        };
+        if key >> 3 == 0 {
+            return false;
+        }
+        let field_end = match key & 0x07 {
+            0 => read_varint(data, &mut idx).map(|_| idx),
+            1 => Some(idx + 8),
+            2 => read_varint(data, &mut idx)
+                .and_then(|length| idx.checked_add(usize::try_from(length).ok()?)),
+            5 => Some(idx + 4),
+            // groups are deprecated, and other wire types don't exist
+            _ => None,
+        };
+        match field_end {
+            Some(field_end) if field_end <= data.len() => idx = field_end,
+            _ => return false,
+        }
+        amount_fields += 1;
+    }
+
+    // a few random bytes happen to parse as a field too easily
+    amount_fields >= 2
+}
+
+fn find_secrets(data: &[u8]) -> Vec<Finding> {
+    let patterns = [
+        (
+            FindingKind::Jwt,
+            r"eyJ[A-Za-z0-9_-]+\.eyJ[A-Za-z0-9_-]+\.[A-Za-z0-9_-]*",
+        ),
+        (
+            FindingKind::Guid,
+            r"\b[0-9A-Fa-f]{8}-[0-9A-Fa-f]{4}-[0-9A-Fa-f]{4}-[0-9A-Fa-f]{4}-[0-9A-Fa-f]{12}\b",
+        ),
+        (
+            FindingKind::Email,
+            r"[A-Za-z0-9._%+-]+@[A-Za-z0-9.-]+\.[A-Za-z]{2,}",
+        ),
+    ];
+
+    patterns
+        .iter()
+        .flat_map(|(kind, pattern)| {
+            Regex::new(pattern)
+                .expect("Secret regexes are valid")
+                .find_iter(data)
+                .map(|found| Finding {
+                    kind: *kind,
+                    offset: found.start(),
+                    value: String::from_utf8_lossy(found.as_bytes()).to_string(),
+                })
+                .collect::<Vec<_>>()
+        })
+        .sorted_by_key(|finding| finding.offset)
+        .collect()
+}
@@ -0,0 +1,138 @@
+use regex::bytes::Regex;
+
+use super::{FindingKind, Format, PlainTextAnalysis, HIGH_ENTROPY};
+
+// magic bytes of gzip, and of zlib at the default compression level
+const GZIP_HEADER: [u8; 2] = [0x1f, 0x8b];
+const ZLIB_HEADER: [u8; 2] = [0x78, 0x9c];
+
+/// A field of the plain text which is worth tampering with, e.g. `"role":"user"` or `uid=42`
+struct InterestingField {
+    name: String,
+    // end of the field's value
+    end: usize,
+    kind: FieldKind,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum FieldKind {
+    // who the token belongs to
+    Identity,
+    // what the owner may do
+    Privilege,
+    // how long the token is valid
+    Expiry,
+}
+
+/// Concrete next steps, given the structure of the plain text. `block_size` is `None` for RSA, where anyone can encrypt with the public key
+pub(super) fn suggest(
+    analysis: &PlainTextAnalysis,
+    plain_text: &[u8],
+    block_size: Option<usize>,
+) -> Vec<String> {
+    let re_encrypt = match block_size {
+        Some(_) => "re-encrypt with `--reencrypt-with` or `--encrypt`",
+        None => "re-encrypt with the public key",
+    };
+    let mut suggestions = Vec::new();
+
+    match analysis.format {
+        Some(Format::PhpSerialize) => suggestions.push(format!(
+            "The plain text is a PHP serialized object. Consider object injection: craft an object of a class with a useful `__wakeup()` or `__destruct()`, and {}",
+            re_encrypt
+        )),
+        Some(Format::JavaSerialized) => suggestions.push(format!(
+            "The plain text is a Java serialized object. Consider insecure deserialization: generate a gadget chain (e.g. with ysoserial) and {}",
+            re_encrypt
+        )),
+        Some(Format::Protobuf) => suggestions.push(
+            "The plain text looks like protobuf. Decode it without a schema with `protoc --decode_raw` to find its fields".to_string(),
+        ),
+        Some(Format::Json) | None => {}
+    }
+    if analysis.format.is_none() {
+        if plain_text.starts_with(&GZIP_HEADER) || plain_text.starts_with(&ZLIB_HEADER) {
+            suggestions.push(
+                "The plain text is compressed. Decompress the file of `--output-plaintext`, and analyse the result".to_string(),
+            );
+        } else if analysis.entropy > HIGH_ENTROPY {
+            suggestions.push(
+                "The plain text looks random. It's likely encrypted again, or a key or nonce. Check whether it's reused across tokens".to_string(),
+            );
+        }
+    }
+
+    for field in find_interesting_fields(plain_text) {
+        let suggestion = match field.kind {
+            FieldKind::Identity => format!(
+                "Contains the field `{}`. Swap in the id of another user, e.g. an admin, and {}",
+                field.name, re_encrypt
+            ),
+            FieldKind::Privilege => format!(
+                "Contains the field `{}`. Raise it to a privileged value, and {}",
+                field.name, re_encrypt
+            ),
+            FieldKind::Expiry => format!(
+                "Contains the field `{}`. Extend it to keep the token valid, and {}",
+                field.name, re_encrypt
+            ),
+        };
+        suggestions.push(match block_size {
+            // the IV is xor-ed with the 1st block only, so changing it needs no oracle questions
+            Some(block_size) if field.end <= block_size => format!(
+                "{}. As it's in the 1st block, flipping bits of the IV suffices (`bitflip`)",
+                suggestion
+            ),
+            _ => suggestion,
+        });
+    }
+
+    for finding in &analysis.findings {
+        match finding.kind {
+            FindingKind::Jwt => suggestions.push(format!(
+                "Holds a JWT at byte {}. Check its algorithm: `none` or a weak HMAC secret lets you forge one",
+                finding.offset
+            )),
+            FindingKind::Guid => suggestions.push(format!(
+                "Holds a GUID at byte {}. If it identifies an object, try another one's (IDOR)",
+                finding.offset
+            )),
+            FindingKind::Email => {}
+        }
+    }
+
+    suggestions
+}
+
+fn find_interesting_fields(plain_text: &[u8]) -> Vec<InterestingField> {
+    // a key, optionally quoted, followed by `=` or `:` and its value. As in JSON, query strings and cookies
+    let field = Regex::new(r#"(?i)["']?\b([a-z_]*?(?:user_?id|uid|user_?name|user|login|email|role|roles|admin|is_?admin|group|groups|priv|privileges?|perm|permissions?|level|access|scope|exp|expires?|expiry|expiration|valid_?until|ttl))\b["']?\s*[:=]\s*["']?[^"'&;,}\s]*"#)
+        .expect("Field regex is valid");
+
+    let mut fields: Vec<InterestingField> = Vec::new();
+    for captures in field.captures_iter(plain_text) {
+        let end = captures
+            .get(0)
+            .expect("Capture group 0 is the whole match")
+            .end();
+        let name = String::from_utf8_lossy(captures[1].as_ref()).to_lowercase();
+        let kind = if name.contains("exp") || name.contains("valid") || name.contains("ttl") {
+            FieldKind::Expiry
+        } else if name.contains("user")
+            || name.contains("uid")
+            || name.contains("login")
+            || name.contains("email")
+        {
+            FieldKind::Identity
+        } else {
+            FieldKind::Privilege
+        };
+
+        // a field repeated, e.g. in a list, needs a single suggestion
+        if fields.iter().all(|known| known.name != name) {
+            fields.push(InterestingField { name, end, kind });
+        }
+    }
+
+    fields
+}
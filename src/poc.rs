@@ -0,0 +1,158 @@
+use std::{fmt::Write as _, fs};
+
+use anyhow::{Context, Result};
+use reqwest::{header::HeaderMap, Method, Url};
+
+use crate::{
+    config::{Config, SubConfig, WebConfig},
+    cypher_text::CypherText,
+    oracle::{oracle_location::OracleLocation, web},
+};
+
+/// Write the proof of concept of `--poc`, which sends the forged cypher text to the oracle. A no-op without `--poc`
+pub(super) fn write(config: &Config, forged_cypher_text: &CypherText) -> Result<()> {
+    let (url, web_config, path) = match (config.oracle_location(), config.sub_config()) {
+        (OracleLocation::Web(url), SubConfig::Web(web_config)) => match web_config.poc_file() {
+            Some(path) => (url, web_config, path),
+            None => return Ok(()),
+        },
+        _ => return Ok(()),
+    };
+
+    let (url, headers, data) = web::request_for(url, web_config, forged_cypher_text)
+        .context("Building the proof of concept's request failed")?;
+    let is_python = path
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("py"));
+    let poc = if is_python {
+        python(
+            web_config,
+            web_config.method(),
+            &url,
+            &headers,
+            data.as_deref(),
+        )
+    } else {
+        shell(
+            web_config,
+            web_config.method(),
+            &url,
+            &headers,
+            data.as_deref(),
+        )
+    };
+
+    fs::write(path, poc).context(format!(
+        "Writing the proof of concept `{}` failed",
+        path.display()
+    ))
+}
+
+fn header_lines(headers: &HeaderMap) -> impl Iterator<Item = (&str, String)> {
+    headers.iter().map(|(name, value)| {
+        (
+            name.as_str(),
+            String::from_utf8_lossy(value.as_bytes()).into_owned(),
+        )
+    })
+}
+
+fn description() -> String {
+    format!(
+        "Proof of concept generated by {} {}. Sends the forged cypher text, which the oracle decrypts to the chosen plain text",
+        env!("CARGO_PKG_NAME"),
+        env!("CARGO_PKG_VERSION")
+    )
+}
+
+fn shell(
+    config: &WebConfig,
+    method: &Method,
+    url: &Url,
+    headers: &HeaderMap,
+    data: Option<&str>,
+) -> String {
+    // wrapped in single quotes, in which nothing but the quote itself needs escaping
+    let quote = |text: &str| format!("'{}'", text.replace('\'', r"'\''"));
+
+    let mut options = vec![
+        "--silent".to_string(),
+        "--include".to_string(),
+        format!("--request {}", quote(method.as_str())),
+    ];
+    options.extend(
+        header_lines(headers)
+            .map(|(name, value)| format!("--header {}", quote(&format!("{}: {}", name, value)))),
+    );
+    if let Some(data) = data {
+        options.push(format!("--data-raw {}", quote(data)));
+    }
+    if *config.insecure() {
+        options.push("--insecure".to_string());
+    }
+    if *config.redirect() {
+        options.push("--location".to_string());
+    }
+
+    format!(
+        "#!/bin/sh\n# {}\ncurl {} \\\n  {}\n",
+        description(),
+        quote(url.as_str()),
+        options.join(" \\\n  ")
+    )
+}
+
+fn python(
+    config: &WebConfig,
+    method: &Method,
+    url: &Url,
+    headers: &HeaderMap,
+    data: Option<&str>,
+) -> String {
+    // JSON strings are valid Python string literals
+    let literal = |text: &str| serde_json::Value::from(text).to_string();
+
+    let mut poc = format!(
+        "#!/usr/bin/env python3\n# {}\nimport ssl\nimport urllib.error\nimport urllib.request\n\n",
+        description()
+    );
+    // writing to a `String` can't fail
+    if !*config.redirect() {
+        let _ = writeln!(
+            poc,
+            "class NoRedirect(urllib.request.HTTPRedirectHandler):\n    def redirect_request(self, *args):\n        return None\n\n"
+        );
+    }
+    let _ = writeln!(poc, "request = urllib.request.Request(");
+    let _ = writeln!(poc, "    {},", literal(url.as_str()));
+    let _ = writeln!(poc, "    method={},", literal(method.as_str()));
+    let _ = writeln!(poc, "    headers={{");
+    for (name, value) in header_lines(headers) {
+        let _ = writeln!(poc, "        {}: {},", literal(name), literal(&value));
+    }
+    let _ = writeln!(poc, "    }},");
+    if let Some(data) = data {
+        let _ = writeln!(poc, "    data={}.encode(),", literal(data));
+    }
+    let _ = writeln!(poc, ")");
+
+    let mut handlers = Vec::new();
+    if *config.insecure() {
+        handlers.push("urllib.request.HTTPSHandler(context=ssl._create_unverified_context())");
+    }
+    if !*config.redirect() {
+        handlers.push("NoRedirect");
+    }
+    let _ = writeln!(
+        poc,
+        "opener = urllib.request.build_opener({})\n",
+        handlers.join(", ")
+    );
+    let _ = writeln!(
+        poc,
+        "try:\n    response = opener.open(request)\nexcept urllib.error.HTTPError as error:\n    # the oracle's answer, even if it's an error\n    response = error\nprint(response.getcode(), response.reason)\nprint(response.headers)\nprint(response.read().decode(errors=\"replace\"))"
+    );
+
+    poc
+}
@@ -0,0 +1,157 @@
+use std::ffi::OsString;
+
+use anyhow::{anyhow, Result};
+
+use crate::cli::{PresetsCli, PresetsSubCommand};
+
+/// Known setup of a commonly exploited framework, so its options of `web` needn't be looked up for every engagement
+#[derive(Debug)]
+pub(super) struct Preset {
+    name: &'static str,
+    description: &'static str,
+    // options of `web` which `--preset` stands for. Options given on the command line take precedence
+    options: &'static [&'static str],
+    // whether `options` place the cypher text, using the default keyword
+    places_keyword: bool,
+}
+
+/// Keyword with which presets locate the cypher text, the default of `--keyword`
+pub(super) const KEYWORD: &str = "CTEXT";
+
+const PRESETS: &[Preset] = &[
+    Preset {
+        name: "jsf-viewstate",
+        description: "JavaServer Faces view state, kept client-side and encrypted with DES, as Apache MyFaces does by default. Question the page which posted the view state",
+        options: &[
+            "--block-size=8",
+            "--encoding=base64",
+            "--data=javax.faces.ViewState=CTEXT",
+            "--padding-error-regex=ViewExpiredException|BadPaddingException",
+        ],
+        places_keyword: true,
+    },
+    Preset {
+        name: "laravel-cookie",
+        description: "Laravel's `laravel_session` cookie, encrypted with AES. Only exploitable on versions or setups which don't check the cookie's MAC",
+        options: &[
+            "--block-size=16",
+            "--encoding=base64",
+            "--header=Cookie: laravel_session=CTEXT",
+            "--padding-error-regex=(?i)could not decrypt the data",
+        ],
+        places_keyword: true,
+    },
+    Preset {
+        name: "telerik",
+        description: "`dp` parameter of Telerik UI for ASP.NET AJAX's dialog handler (CVE-2017-9248), encrypted with AES. Question e.g. `https://<host>/Telerik.Web.UI.DialogHandler.aspx?dp=CTEXT`",
+        options: &[
+            "--block-size=16",
+            "--encoding=base64",
+            "--padding-error-regex=Padding is invalid and cannot be removed",
+        ],
+        places_keyword: false,
+    },
+];
+
+impl Preset {
+    pub(super) fn name(&self) -> &'static str {
+        self.name
+    }
+
+    pub(super) fn places_keyword(&self) -> bool {
+        self.places_keyword
+    }
+}
+
+/// Parses the value of `--preset`
+pub(super) fn find(name: &str) -> Result<&'static Preset> {
+    PRESETS
+        .iter()
+        .find(|preset| preset.name.eq_ignore_ascii_case(name))
+        .ok_or_else(|| {
+            anyhow!(
+                "`{}` is not a known preset. Expected one of: [{}]. See `presets list`",
+                name,
+                PRESETS
+                    .iter()
+                    .map(|preset| preset.name)
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )
+        })
+}
+
+/// Insert the options a `--preset` stands for right after the sub-command, so that options given on the command line override them.
+/// Arguments after `--` are left alone, as they belong to another command line, e.g. of `mass`.
+pub(super) fn expand(args: impl IntoIterator<Item = impl Into<OsString>>) -> Result<Vec<OsString>> {
+    let mut args: Vec<OsString> = args.into_iter().map(Into::into).collect();
+
+    let own_args = own_args_end(&args);
+    let preset = option_value(&args[..own_args], "--preset")
+        // before `clap` parses `--preset`, as it reports the options missing without the preset first
+        .map(find)
+        .transpose()?;
+
+    if let Some(preset) = preset {
+        // other sub-commands reject `--preset` itself, rather than the options it stands for
+        if let Some(web_options_start) = web_options_start(&args[..own_args]) {
+            args.splice(
+                web_options_start..web_options_start,
+                preset.options.iter().map(OsString::from),
+            );
+        }
+    }
+
+    Ok(args)
+}
+
+/// End of the program's own arguments. Those after `--` belong to another command line, e.g. of `mass`
+pub(super) fn own_args_end(args: &[OsString]) -> usize {
+    args.iter()
+        .position(|arg| arg == "--")
+        .unwrap_or(args.len())
+}
+
+/// Value of the given long option, as `<option> <value>` or `<option>=<value>`
+pub(super) fn option_value<'a>(args: &'a [OsString], option: &str) -> Option<&'a str> {
+    args.iter().enumerate().find_map(|(idx, arg)| {
+        let arg = arg.to_str()?;
+        match arg.strip_prefix(option) {
+            Some("") => args.get(idx + 1)?.to_str(),
+            Some(value) => value.strip_prefix('='),
+            None => None,
+        }
+    })
+}
+
+/// Position right after the `web` sub-command, e.g. of `lint web`, where options which stand for others are inserted. `None` for other sub-commands
+pub(super) fn web_options_start(args: &[OsString]) -> Option<usize> {
+    // the program's name, followed by the sub-command(s)
+    let sub_command_end = args
+        .iter()
+        .skip(1)
+        .position(|arg| {
+            arg.to_str()
+                .is_none_or(|arg| arg.starts_with('-') && !matches!(arg, "-W" | "--web" | "--lint"))
+        })
+        .map_or(args.len(), |idx| idx + 1);
+    args[..sub_command_end]
+        .last()
+        .is_some_and(|arg| matches!(arg.to_str(), Some("w" | "we" | "web" | "-W" | "--web")))
+        .then_some(sub_command_end)
+}
+
+pub(super) fn presets(presets_cli: PresetsCli) -> Result<()> {
+    match presets_cli.sub_command {
+        PresetsSubCommand::List => {
+            for preset in PRESETS {
+                println!("{}", preset.name);
+                println!("  {}", preset.description);
+                for option in preset.options {
+                    println!("    {}", option);
+                }
+            }
+            Ok(())
+        }
+    }
+}
@@ -0,0 +1,534 @@
+use std::{
+    collections::HashMap,
+    fs::{self, File},
+    io::Write,
+    path::Path,
+    str::FromStr,
+    sync::Mutex,
+    time::{Duration, Instant, SystemTime},
+};
+
+use anyhow::{anyhow, Context, Result};
+use async_std::task;
+use crossbeam::thread;
+use log::{error, info, log, Level, LevelFilter, Record};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    block::{block_size::BlockSize, Block},
+    cli::ReplaySessionCli,
+    config::Config,
+    logging::{init_logging, LOG_TARGET},
+    oracle::oracle_location::OracleLocation,
+    timeline::Phase,
+    tui::{
+        keymap::Keymap,
+        ui_event::{UiControlEvent, UiDecryptionEvent, UiEncryptionEvent, UiEvent},
+        Tui,
+    },
+};
+
+// bumped when recordings of older versions can't be replayed anymore
+const FORMAT_VERSION: u32 = 2;
+// the TUI doesn't draw faster than this, so more frequent WIP updates of a block can't be seen
+const WIP_INTERVAL: Duration = Duration::from_millis(20);
+
+/// Records everything the TUI shows as JSON lines, for `--record`, so `replay-session` can play the attack back without the oracle.
+/// The 1st line describes the recording, the others are events.
+pub(super) struct Recording {
+    start: Instant,
+    state: Mutex<State>,
+}
+
+struct State {
+    file: File,
+    // when the latest WIP update of a block was recorded
+    latest_wips: HashMap<(Phase, usize), Instant>,
+    // writing failed before. Reported once, instead of for every event
+    broken: bool,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Header {
+    version: u32,
+    block_size: u8,
+    oracle: String,
+    // RFC 3339
+    recorded: String,
+}
+
+#[derive(Serialize, Deserialize)]
+struct RecordedEvent {
+    elapsed_ms: u64,
+    #[serde(flatten)]
+    kind: RecordedEventKind,
+}
+
+// blocks are hex encoded
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+enum RecordedEventKind {
+    Log {
+        level: String,
+        target: String,
+        message: String,
+    },
+    InitDecryption {
+        cypher_text_blocks: Vec<String>,
+    },
+    InitEncryption {
+        plain_text_blocks: Vec<String>,
+        init_cypher_text: String,
+    },
+    BlockWip {
+        phase: Phase,
+        block: usize,
+        forged_block: String,
+    },
+    BlockSolved {
+        phase: Phase,
+        block: usize,
+        forged_block: String,
+    },
+    IndicateWork {
+        decryption_bytes: usize,
+        encryption_bytes: usize,
+    },
+    Progress {
+        phase: Phase,
+        newly_solved_bytes: usize,
+    },
+    GuessLatency {
+        block: usize,
+        byte_idx: u8,
+        byte_value: u8,
+        latency_us: u64,
+    },
+    BlockFailed {
+        block: usize,
+        error: String,
+    },
+    PrintAfterExit {
+        message: String,
+    },
+    PrintDocumentAfterExit {
+        document: String,
+    },
+    ExitCode {
+        exit_code: i32,
+    },
+}
+
+impl Recording {
+    /// A recording of a previous attack at `path` is overwritten
+    pub(super) fn create(path: &Path, config: &Config) -> Result<Self> {
+        let mut file =
+            File::create(path).context(format!("Recording `{}` failed to open", path.display()))?;
+        let header = Header {
+            version: FORMAT_VERSION,
+            block_size: **config.block_size(),
+            oracle: match config.oracle_location() {
+                OracleLocation::Web(url) => url.to_string(),
+                OracleLocation::Script(path) => path.display().to_string(),
+                OracleLocation::Tcp(address) => address.clone(),
+                OracleLocation::Plugin(path) => path.display().to_string(),
+                OracleLocation::Lua(path) => path.display().to_string(),
+                OracleLocation::Python(path) => path.display().to_string(),
+            },
+            recorded: humantime::format_rfc3339_seconds(SystemTime::now()).to_string(),
+        };
+        writeln!(
+            file,
+            "{}",
+            serde_json::to_string(&header).context("Serialising recording header failed")?
+        )
+        .context(format!("Writing to recording `{}` failed", path.display()))?;
+
+        Ok(Self {
+            start: Instant::now(),
+            state: Mutex::new(State {
+                file,
+                latest_wips: HashMap::new(),
+                broken: false,
+            }),
+        })
+    }
+
+    pub(super) fn record_ui_event(&self, event: &UiEvent) {
+        let kind = match event {
+            UiEvent::Decryption(UiDecryptionEvent::InitDecryption(cypher_text_blocks)) => {
+                RecordedEventKind::InitDecryption {
+                    cypher_text_blocks: cypher_text_blocks.iter().map(Block::to_hex).collect(),
+                }
+            }
+            UiEvent::Encryption(UiEncryptionEvent::InitEncryption(
+                plain_text_blocks,
+                init_cypher_text,
+            )) => RecordedEventKind::InitEncryption {
+                plain_text_blocks: plain_text_blocks.iter().map(Block::to_hex).collect(),
+                init_cypher_text: init_cypher_text.to_hex(),
+            },
+            UiEvent::Decryption(UiDecryptionEvent::BlockWip(forged_block, block)) => {
+                if !self.is_wip_due(Phase::Decryption, *block) {
+                    return;
+                }
+                RecordedEventKind::BlockWip {
+                    phase: Phase::Decryption,
+                    block: *block,
+                    forged_block: forged_block.to_hex(),
+                }
+            }
+            UiEvent::Encryption(UiEncryptionEvent::BlockWip(forged_block, block)) => {
+                if !self.is_wip_due(Phase::Encryption, *block) {
+                    return;
+                }
+                RecordedEventKind::BlockWip {
+                    phase: Phase::Encryption,
+                    block: *block,
+                    forged_block: forged_block.to_hex(),
+                }
+            }
+            UiEvent::Decryption(UiDecryptionEvent::BlockSolved(forged_block, block)) => {
+                RecordedEventKind::BlockSolved {
+                    phase: Phase::Decryption,
+                    block: *block,
+                    forged_block: forged_block.to_hex(),
+                }
+            }
+            UiEvent::Encryption(UiEncryptionEvent::BlockSolved(forged_block, block)) => {
+                RecordedEventKind::BlockSolved {
+                    phase: Phase::Encryption,
+                    block: *block,
+                    forged_block: forged_block.to_hex(),
+                }
+            }
+            UiEvent::Control(UiControlEvent::IndicateWork(decryption_bytes, encryption_bytes)) => {
+                RecordedEventKind::IndicateWork {
+                    decryption_bytes: *decryption_bytes,
+                    encryption_bytes: *encryption_bytes,
+                }
+            }
+            UiEvent::Control(UiControlEvent::ProgressUpdate(phase, newly_solved_bytes)) => {
+                RecordedEventKind::Progress {
+                    phase: *phase,
+                    newly_solved_bytes: *newly_solved_bytes,
+                }
+            }
+            UiEvent::Control(UiControlEvent::GuessLatency(
+                block,
+                byte_idx,
+                byte_value,
+                latency,
+            )) => RecordedEventKind::GuessLatency {
+                block: *block,
+                byte_idx: *byte_idx,
+                byte_value: *byte_value,
+                latency_us: latency.as_micros() as u64,
+            },
+            UiEvent::Control(UiControlEvent::BlockFailed(block, error)) => {
+                RecordedEventKind::BlockFailed {
+                    block: *block,
+                    error: error.clone(),
+                }
+            }
+            UiEvent::Control(UiControlEvent::PrintAfterExit(message)) => {
+                RecordedEventKind::PrintAfterExit {
+                    message: message.clone(),
+                }
+            }
+            UiEvent::Control(UiControlEvent::PrintDocumentAfterExit(document)) => {
+                RecordedEventKind::PrintDocumentAfterExit {
+                    document: document.clone(),
+                }
+            }
+            UiEvent::Control(UiControlEvent::ExitCode(exit_code)) => RecordedEventKind::ExitCode {
+                exit_code: *exit_code,
+            },
+            // not shown by the TUI, or sent by the replay itself
+            UiEvent::Control(UiControlEvent::Calibrated(_) | UiControlEvent::SlowRedraw) => return,
+        };
+
+        self.record(kind);
+    }
+
+    /// Log lines are shown by the TUI as well
+    pub(super) fn record_log(&self, record: &Record) {
+        self.record(RecordedEventKind::Log {
+            level: record.level().to_string(),
+            target: record.target().to_string(),
+            message: record.args().to_string(),
+        });
+    }
+
+    fn is_wip_due(&self, phase: Phase, block: usize) -> bool {
+        let now = Instant::now();
+        let mut state = self.state.lock().unwrap();
+        match state.latest_wips.get(&(phase, block)) {
+            Some(latest_wip) if now.duration_since(*latest_wip) < WIP_INTERVAL => false,
+            _ => {
+                state.latest_wips.insert((phase, block), now);
+                true
+            }
+        }
+    }
+
+    fn record(&self, kind: RecordedEventKind) {
+        let event = RecordedEvent {
+            elapsed_ms: self.start.elapsed().as_millis() as u64,
+            kind,
+        };
+
+        let mut state = self.state.lock().unwrap();
+        if state.broken {
+            return;
+        }
+        let written = serde_json::to_string(&event)
+            .context("Serialising recorded event failed")
+            .and_then(|line| {
+                // a single write per line, so an interrupted attack leaves a replayable recording
+                state
+                    .file
+                    .write_all(format!("{}\n", line).as_bytes())
+                    .context("Writing to the recording failed")
+            });
+        if let Err(e) = written {
+            // marked broken first, as logging the error would be recorded too
+            state.broken = true;
+            drop(state);
+            error!(target: LOG_TARGET, "{:?}", e);
+        }
+    }
+}
+
+/// Play a recording back in the TUI, at the recorded pace divided by `--speed`
+pub(super) fn replay_session(replay_session_cli: ReplaySessionCli) -> Result<()> {
+    let path = replay_session_cli.record_file();
+    let content = fs::read_to_string(path)
+        .context(format!("Recording `{}` failed to open", path.display()))?;
+    let mut lines = content.lines();
+    let header: Header = lines
+        .next()
+        .ok_or_else(|| anyhow!("Recording `{}` is empty", path.display()))
+        .and_then(|line| {
+            serde_json::from_str(line).context(format!(
+                "`{}` is not a recording of `--record`",
+                path.display()
+            ))
+        })?;
+    if header.version != FORMAT_VERSION {
+        return Err(anyhow!(
+            "Recording `{}` is of format version {}, but only version {} can be replayed",
+            path.display(),
+            header.version,
+            FORMAT_VERSION
+        ));
+    }
+    if header.block_size == 0 {
+        return Err(anyhow!(
+            "Recording `{}` has a block size of 0",
+            path.display()
+        ));
+    }
+    // a corrupt recording is reported before the TUI takes over the terminal
+    let events = lines
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(line_idx, line)| {
+            serde_json::from_str::<RecordedEvent>(line)
+                .context("Malformed event")
+                .and_then(|event| event.into_replayable(header.block_size))
+                .context(format!(
+                    "Line {} of recording `{}` is invalid",
+                    line_idx + 2,
+                    path.display()
+                ))
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let block_size = BlockSize::from(header.block_size);
+    init_logging(LevelFilter::Trace, None, None, None)?;
+    let tui = Tui::new(
+        &block_size,
+        Keymap::load(None)?,
+        None,
+        replay_session_cli.cast_file().as_deref(),
+    )
+    .context("TUI creation failed")?;
+    info!(
+        target: LOG_TARGET,
+        "Replaying the attack on {}, recorded {}, at {}",
+        header.oracle,
+        header.recorded,
+        replay_session_cli.speed()
+    );
+
+    let speed = **replay_session_cli.speed();
+    thread::scope(|scope| {
+        if let Err(e) = scope.builder().name("TUI".to_string()).spawn(|_| {
+            if let Err(e) = task::block_on(tui.main_loop()) {
+                error!(target: LOG_TARGET, "{:?}", e);
+                tui.exit()
+            }
+        }) {
+            error!(target: LOG_TARGET, "{:?}", e);
+            tui.exit()
+        }
+
+        let start = Instant::now();
+        for (elapsed, event) in events {
+            let due = elapsed.div_f64(speed);
+            if let Some(wait) = due.checked_sub(start.elapsed()) {
+                std::thread::sleep(wait);
+            }
+            match event {
+                ReplayedEvent::Log {
+                    level,
+                    target,
+                    message,
+                } => log!(target: &target, level, "{}", message),
+                ReplayedEvent::Ui(event) => tui.handle_application_event(event),
+            }
+        }
+
+        // keep window open for user to read results
+        tui.handle_application_event(UiEvent::Control(UiControlEvent::SlowRedraw));
+    })
+    .unwrap();
+
+    Ok(())
+}
+
+enum ReplayedEvent {
+    Log {
+        level: Level,
+        target: String,
+        message: String,
+    },
+    Ui(UiEvent),
+}
+
+impl RecordedEvent {
+    /// Blocks must be of the recording's `block_size`, as the TUI relies on it
+    fn into_replayable(self, block_size: u8) -> Result<(Duration, ReplayedEvent)> {
+        let ui_event = match self.kind {
+            RecordedEventKind::Log {
+                level,
+                target,
+                message,
+            } => {
+                return Ok((
+                    Duration::from_millis(self.elapsed_ms),
+                    ReplayedEvent::Log {
+                        level: Level::from_str(&level)
+                            .map_err(|_| anyhow!("`{}` is not a log level", level))?,
+                        target,
+                        message,
+                    },
+                ))
+            }
+            RecordedEventKind::InitDecryption { cypher_text_blocks } => UiEvent::Decryption(
+                UiDecryptionEvent::InitDecryption(to_blocks(&cypher_text_blocks, block_size)?),
+            ),
+            RecordedEventKind::InitEncryption {
+                plain_text_blocks,
+                init_cypher_text,
+            } => UiEvent::Encryption(UiEncryptionEvent::InitEncryption(
+                to_blocks(&plain_text_blocks, block_size)?,
+                to_block(&init_cypher_text, block_size)?,
+            )),
+            RecordedEventKind::BlockWip {
+                phase: Phase::Decryption,
+                block,
+                forged_block,
+            } => UiEvent::Decryption(UiDecryptionEvent::BlockWip(
+                to_block(&forged_block, block_size)?,
+                block,
+            )),
+            RecordedEventKind::BlockWip {
+                phase: Phase::Encryption,
+                block,
+                forged_block,
+            } => UiEvent::Encryption(UiEncryptionEvent::BlockWip(
+                to_block(&forged_block, block_size)?,
+                block,
+            )),
+            RecordedEventKind::BlockSolved {
+                phase: Phase::Decryption,
+                block,
+                forged_block,
+            } => UiEvent::Decryption(UiDecryptionEvent::BlockSolved(
+                to_block(&forged_block, block_size)?,
+                block,
+            )),
+            RecordedEventKind::BlockSolved {
+                phase: Phase::Encryption,
+                block,
+                forged_block,
+            } => UiEvent::Encryption(UiEncryptionEvent::BlockSolved(
+                to_block(&forged_block, block_size)?,
+                block,
+            )),
+            RecordedEventKind::IndicateWork {
+                decryption_bytes,
+                encryption_bytes,
+            } => UiEvent::Control(UiControlEvent::IndicateWork(
+                decryption_bytes,
+                encryption_bytes,
+            )),
+            RecordedEventKind::Progress {
+                phase,
+                newly_solved_bytes,
+            } => UiEvent::Control(UiControlEvent::ProgressUpdate(phase, newly_solved_bytes)),
+            RecordedEventKind::GuessLatency {
+                block,
+                byte_idx,
+                byte_value,
+                latency_us,
+            } => UiEvent::Control(UiControlEvent::GuessLatency(
+                block,
+                byte_idx,
+                byte_value,
+                Duration::from_micros(latency_us),
+            )),
+            RecordedEventKind::BlockFailed { block, error } => {
+                UiEvent::Control(UiControlEvent::BlockFailed(block, error))
+            }
+            RecordedEventKind::PrintAfterExit { message } => {
+                UiEvent::Control(UiControlEvent::PrintAfterExit(message))
+            }
+            RecordedEventKind::PrintDocumentAfterExit { document } => {
+                UiEvent::Control(UiControlEvent::PrintDocumentAfterExit(document))
+            }
+            RecordedEventKind::ExitCode { exit_code } => {
+                UiEvent::Control(UiControlEvent::ExitCode(exit_code))
+            }
+        };
+
+        Ok((
+            Duration::from_millis(self.elapsed_ms),
+            ReplayedEvent::Ui(ui_event),
+        ))
+    }
+}
+
+fn to_block(hex_block: &str, block_size: u8) -> Result<Block> {
+    let bytes = hex::decode(hex_block).context(format!("`{}` is not a hex block", hex_block))?;
+    if bytes.len() != block_size as usize {
+        return Err(anyhow!(
+            "`{}` is not a block of {} bytes",
+            hex_block,
+            block_size
+        ));
+    }
+    Ok(Block::from(&bytes[..]))
+}
+
+fn to_blocks(hex_blocks: &[String], block_size: u8) -> Result<Vec<Block>> {
+    if hex_blocks.is_empty() {
+        return Err(anyhow!("No blocks were recorded"));
+    }
+    hex_blocks
+        .iter()
+        .map(|block| to_block(block, block_size))
+        .collect()
+}
@@ -0,0 +1,531 @@
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+    sync::Mutex,
+    time::{Duration, SystemTime},
+};
+
+use anyhow::{Context, Result};
+use humantime::format_duration;
+
+use crate::{
+    calibrator::calibration_response::CalibrationResponse,
+    config::{Config, SubConfig},
+    cypher_text::encode::Encode,
+    json_result::{Document, JsonResult},
+    oracle::oracle_location::OracleLocation,
+    tui::ui_event::{UiControlEvent, UiEvent},
+};
+
+// options whose value is redacted from the reproduction command, with all their aliases
+const SECRET_OPTIONS: &[&str] = &[
+    "--session-passphrase",
+    "--session_passphrase",
+    "--passphrase",
+    "--proxy-credentials",
+    "--proxy_credentials",
+    "--proxy-creds",
+    "--proxy_creds",
+];
+const REDACTED: &str = "<redacted>";
+
+/// Shareable report of the attack, for `--report`, e.g. to paste into a pentest deliverable. The results are built from the events sent to the UI, as the JSON result is.
+pub(super) struct Report {
+    path: PathBuf,
+    format: ReportFormat,
+    // (parameter, value)
+    parameters: Vec<(String, String)>,
+    oracle_kind: &'static str,
+    command_line: String,
+    result: JsonResult,
+    calibration: Mutex<Option<CalibrationResponse>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ReportFormat {
+    Markdown,
+    Html,
+}
+
+/// Building blocks of the report, rendered as either format
+enum Element {
+    Heading(String),
+    Paragraph(String),
+    List(Vec<String>),
+    Table(Vec<&'static str>, Vec<Vec<String>>),
+    Code(String),
+}
+
+impl Report {
+    pub(super) fn new(config: &Config, path: &Path) -> Self {
+        let format = match path
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .map(str::to_lowercase)
+            .as_deref()
+        {
+            Some("html") | Some("htm") => ReportFormat::Html,
+            _ => ReportFormat::Markdown,
+        };
+
+        Self {
+            path: path.to_path_buf(),
+            format,
+            parameters: parameters(config),
+            oracle_kind: match config.oracle_location() {
+                OracleLocation::Web(_) => "web",
+                OracleLocation::Script(_) => "script",
+                OracleLocation::Tcp(_) => "TCP",
+                OracleLocation::Plugin(_) => "plugin",
+                OracleLocation::Lua(_) => "Lua",
+                OracleLocation::Python(_) => "Python",
+            },
+            // the binary's path is specific to this machine
+            command_line: command_line(
+                [env!("CARGO_PKG_NAME").to_string()]
+                    .into_iter()
+                    .chain(env::args().skip(1)),
+            ),
+            result: JsonResult::new(config),
+            calibration: Mutex::new(None),
+        }
+    }
+
+    pub(super) fn record_ui_event(&self, event: &UiEvent) {
+        if let UiEvent::Control(UiControlEvent::Calibrated(padding_error_response)) = event {
+            *self.calibration.lock().unwrap() = Some(padding_error_response.clone());
+        }
+        self.result.record_ui_event(event);
+    }
+
+    /// Write the report, replacing the file
+    pub(super) fn export(&self) -> Result<()> {
+        let elements = self.elements(&self.result.document());
+        let rendered = match self.format {
+            ReportFormat::Markdown => render_markdown(&elements),
+            ReportFormat::Html => render_html(&elements),
+        };
+
+        fs::write(&self.path, rendered)
+            .context(format!("Writing report `{}` failed", self.path.display()))
+    }
+
+    fn elements(&self, document: &Document) -> Vec<Element> {
+        let mut elements = vec![
+            Element::Heading("Padding oracle attack report".to_string()),
+            Element::Paragraph(format!(
+                "Generated by {} {} at {}. The attack {} with exit code {}.",
+                env!("CARGO_PKG_NAME"),
+                env!("CARGO_PKG_VERSION"),
+                humantime::format_rfc3339_seconds(SystemTime::now()),
+                if document.exit_code == 0 {
+                    "succeeded"
+                } else {
+                    "failed"
+                },
+                document.exit_code
+            )),
+            Element::Heading("Target".to_string()),
+            Element::Table(
+                vec!["Parameter", "Value"],
+                self.parameters
+                    .iter()
+                    .map(|(parameter, value)| vec![parameter.clone(), value.clone()])
+                    .collect(),
+            ),
+        ];
+
+        elements.push(Element::Heading("Calibration".to_string()));
+        match &*self.calibration.lock().unwrap() {
+            Some(padding_error_response) => {
+                elements.push(Element::Paragraph(
+                    "Responses to an incorrect padding are recognised by:".to_string(),
+                ));
+                elements.push(Element::List(padding_error_response.describe()));
+            }
+            None if self.oracle_kind == "web" => elements.push(Element::Paragraph(
+                "The web oracle wasn't calibrated, e.g. as the results came from the cache."
+                    .to_string(),
+            )),
+            None => elements.push(Element::Paragraph(format!(
+                "Not needed: the {} oracle gives its verdict on the padding directly.",
+                self.oracle_kind
+            ))),
+        }
+
+        let amount_decryptions = document.decryption.len();
+        for (idx, decryption) in document.decryption.iter().enumerate() {
+            elements.push(Element::Heading(if amount_decryptions > 1 {
+                format!("Decryption of segment {}", idx + 1)
+            } else {
+                "Decryption".to_string()
+            }));
+            elements.push(Element::Paragraph("Cypher text, in hex:".to_string()));
+            elements.push(Element::Code(decryption.cypher_text.clone()));
+            elements.push(Element::Table(
+                vec![
+                    "Block",
+                    "Cypher text",
+                    "Intermediate",
+                    "Plain text (hex)",
+                    "Plain text",
+                    "Time",
+                ],
+                decryption
+                    .blocks
+                    .iter()
+                    .map(|block| {
+                        vec![
+                            block.block.to_string(),
+                            block.cypher_text.clone(),
+                            block.intermediate.clone(),
+                            block.plain_text_hex.clone(),
+                            printable(&block.plain_text),
+                            block_time(block.duration_ms),
+                        ]
+                    })
+                    .collect(),
+            ));
+            elements.push(Element::Paragraph("Plain text:".to_string()));
+            elements.push(Element::Code(printable(&decryption.plain_text)));
+        }
+
+        if let Some(encryption) = &document.encryption {
+            elements.push(Element::Heading("Encryption".to_string()));
+            elements.push(Element::Paragraph(
+                "Plain text, padded, in hex:".to_string(),
+            ));
+            elements.push(Element::Code(encryption.plain_text_hex.clone()));
+            elements.push(Element::Table(
+                vec![
+                    "Block",
+                    "Plain text (hex)",
+                    "Intermediate",
+                    "Cypher text",
+                    "Time",
+                ],
+                encryption
+                    .blocks
+                    .iter()
+                    .map(|block| {
+                        vec![
+                            block.block.to_string(),
+                            block.plain_text_hex.clone(),
+                            block.intermediate.clone(),
+                            block.cypher_text.clone(),
+                            block_time(block.duration_ms),
+                        ]
+                    })
+                    .collect(),
+            ));
+        }
+
+        if !document.output.is_empty() {
+            elements.push(Element::Heading("Result".to_string()));
+            elements.push(Element::Code(document.output.join("\n")));
+        }
+
+        elements.push(Element::Heading("Requests".to_string()));
+        elements.push(Element::Table(
+            vec!["Statistic", "Value"],
+            statistics(document),
+        ));
+
+        elements.push(Element::Heading("Reproduction".to_string()));
+        let mut steps = vec![
+            format!(
+                "Install {} {}.",
+                env!("CARGO_PKG_NAME"),
+                env!("CARGO_PKG_VERSION")
+            ),
+            "Run the command below.".to_string(),
+            "Compare the printed result with the one of this report.".to_string(),
+        ];
+        if self.oracle_kind == "web" {
+            steps.push("To confirm the oracle manually, send the original cypher text, then the same one with the last byte of the 2nd to last block changed. In 255 of 256 cases, the latter gets the padding error response of the calibration.".to_string());
+        }
+        elements.push(Element::List(steps));
+        elements.push(Element::Code(self.command_line.clone()));
+
+        elements
+    }
+}
+
+fn parameters(config: &Config) -> Vec<(String, String)> {
+    let mut parameters = vec![
+        (
+            "Oracle".to_string(),
+            match config.oracle_location() {
+                OracleLocation::Web(url) => format!("web, {}", url),
+                OracleLocation::Script(path) => format!("script, {}", path.display()),
+                OracleLocation::Tcp(address) => format!("TCP, {}", address),
+                OracleLocation::Plugin(path) => format!("plugin, {}", path.display()),
+                OracleLocation::Lua(path) => format!("Lua, {}", path.display()),
+                OracleLocation::Python(path) => format!("Python, {}", path.display()),
+            },
+        ),
+        (
+            "Mode".to_string(),
+            if config.plain_text().is_some() || config.plain_text_edit().is_some() {
+                "encryption"
+            } else {
+                "decryption"
+            }
+            .to_string(),
+        ),
+        ("Block size".to_string(), config.block_size().to_string()),
+        (
+            "Encoding".to_string(),
+            format!(
+                "{:?}{}",
+                config.cypher_text().used_encoding(),
+                if *config.cypher_text().url_encoded() {
+                    ", URL encoded"
+                } else {
+                    ""
+                }
+            ),
+        ),
+        (
+            "Padding".to_string(),
+            format!("{:?}", config.padding_scheme()),
+        ),
+        (
+            "IV".to_string(),
+            if *config.no_iv() {
+                "none, the 1st block can't be decrypted"
+            } else {
+                "prepended to the cypher text"
+            }
+            .to_string(),
+        ),
+        ("Threads".to_string(), config.thread_count().to_string()),
+    ];
+
+    if let SubConfig::Web(web_config) = config.sub_config() {
+        parameters.extend([
+            ("Method".to_string(), web_config.method().to_string()),
+            ("Keyword".to_string(), web_config.keyword().clone()),
+            (
+                "Concurrency".to_string(),
+                web_config.concurrency().to_string(),
+            ),
+            (
+                "HTTP version".to_string(),
+                web_config.http_version().to_string(),
+            ),
+            (
+                "Calibration mode".to_string(),
+                web_config.calibration_mode().to_string(),
+            ),
+        ]);
+    }
+
+    parameters
+}
+
+fn statistics(document: &Document) -> Vec<Vec<String>> {
+    let duration = Duration::from_millis(document.duration_ms as u64);
+    let solved_blocks = document
+        .decryption
+        .iter()
+        .map(|decryption| decryption.blocks.len())
+        .sum::<usize>()
+        + document
+            .encryption
+            .as_ref()
+            .map_or(0, |encryption| encryption.blocks.len());
+
+    let mut statistics = vec![
+        vec!["Requests".to_string(), document.requests.to_string()],
+        vec![
+            "Duration".to_string(),
+            // ms are noise in a report
+            format_duration(Duration::new(duration.as_secs(), 0)).to_string(),
+        ],
+        vec!["Solved blocks".to_string(), solved_blocks.to_string()],
+    ];
+    if duration.as_secs_f64() > 0.0 {
+        statistics.push(vec![
+            "Requests per second".to_string(),
+            format!("{:.1}", document.requests as f64 / duration.as_secs_f64()),
+        ]);
+    }
+    if solved_blocks > 0 {
+        statistics.push(vec![
+            "Requests per block".to_string(),
+            format!("{:.0}", document.requests as f64 / solved_blocks as f64),
+        ]);
+    }
+
+    statistics
+}
+
+/// The command line this run was started with, ready to paste into a shell. Secrets are redacted
+fn command_line(args: impl Iterator<Item = String>) -> String {
+    let mut redact_next = false;
+    args.map(|arg| {
+        if redact_next {
+            redact_next = false;
+            return REDACTED.to_string();
+        }
+        if let Some((option, _)) = arg.split_once('=') {
+            if SECRET_OPTIONS.contains(&option) {
+                return format!("{}={}", option, REDACTED);
+            }
+        }
+        redact_next = SECRET_OPTIONS.contains(&arg.as_str());
+        shell_quote(&arg)
+    })
+    .collect::<Vec<_>>()
+    .join(" ")
+}
+
+fn shell_quote(arg: &str) -> String {
+    let is_safe = !arg.is_empty()
+        && arg
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "-_./:=,@%+".contains(c));
+    if is_safe {
+        arg.to_string()
+    } else {
+        format!("'{}'", arg.replace('\'', "'\\''"))
+    }
+}
+
+/// Control characters would break the layout of tables and code blocks
+fn printable(text: &str) -> String {
+    text.chars()
+        .map(|c| if c.is_control() { '.' } else { c })
+        .collect()
+}
+
+fn block_time(duration_ms: Option<u128>) -> String {
+    duration_ms
+        .map(|duration_ms| format!("{:.1}s", duration_ms as f64 / 1000.0))
+        .unwrap_or_else(|| "cached".to_string())
+}
+
+fn render_markdown(elements: &[Element]) -> String {
+    let mut markdown = String::new();
+    let mut is_title = true;
+    for element in elements {
+        match element {
+            Element::Heading(heading) => {
+                // the 1st heading titles the report
+                let level = if is_title { "#" } else { "##" };
+                is_title = false;
+                markdown.push_str(&format!("{} {}\n\n", level, heading));
+            }
+            Element::Paragraph(text) => markdown.push_str(&format!("{}\n\n", text)),
+            Element::List(items) => {
+                for item in items {
+                    markdown.push_str(&format!("- {}\n", item));
+                }
+                markdown.push('\n');
+            }
+            Element::Table(header, rows) => {
+                let escape = |cell: &str| cell.replace('|', "\\|");
+                markdown.push_str(&format!("| {} |\n", header.join(" | ")));
+                markdown.push_str(&format!("|{}\n", " --- |".repeat(header.len())));
+                for row in rows {
+                    markdown.push_str(&format!(
+                        "| {} |\n",
+                        row.iter()
+                            .map(|cell| escape(cell))
+                            .collect::<Vec<_>>()
+                            .join(" | ")
+                    ));
+                }
+                markdown.push('\n');
+            }
+            Element::Code(code) => {
+                // a fence longer than any run of backticks in the code
+                let fence = "`".repeat(
+                    code.split(|c| c != '`')
+                        .map(str::len)
+                        .max()
+                        .unwrap_or_default()
+                        .max(2)
+                        + 1,
+                );
+                markdown.push_str(&format!("{}\n{}\n{}\n\n", fence, code, fence));
+            }
+        }
+    }
+
+    markdown
+}
+
+fn render_html(elements: &[Element]) -> String {
+    let mut body = String::new();
+    let mut title = None;
+    for element in elements {
+        match element {
+            Element::Heading(heading) => {
+                if title.is_none() {
+                    title = Some(escape_html(heading));
+                    body.push_str(&format!("<h1>{}</h1>\n", escape_html(heading)));
+                } else {
+                    body.push_str(&format!("<h2>{}</h2>\n", escape_html(heading)));
+                }
+            }
+            Element::Paragraph(text) => {
+                body.push_str(&format!("<p>{}</p>\n", escape_html(text)));
+            }
+            Element::List(items) => {
+                body.push_str("<ul>\n");
+                for item in items {
+                    body.push_str(&format!("<li>{}</li>\n", escape_html(item)));
+                }
+                body.push_str("</ul>\n");
+            }
+            Element::Table(header, rows) => {
+                body.push_str("<table>\n<tr>");
+                for cell in header {
+                    body.push_str(&format!("<th>{}</th>", escape_html(cell)));
+                }
+                body.push_str("</tr>\n");
+                for row in rows {
+                    body.push_str("<tr>");
+                    for cell in row {
+                        body.push_str(&format!("<td>{}</td>", escape_html(cell)));
+                    }
+                    body.push_str("</tr>\n");
+                }
+                body.push_str("</table>\n");
+            }
+            Element::Code(code) => {
+                body.push_str(&format!("<pre><code>{}</code></pre>\n", escape_html(code)));
+            }
+        }
+    }
+
+    format!(
+        "<!DOCTYPE html>
+<html>
+<head>
+<meta charset=\"utf-8\">
+<title>{}</title>
+<style>
+body {{ font-family: sans-serif; max-width: 80em; margin: 2em auto; }}
+table {{ border-collapse: collapse; }}
+th, td {{ border: 1px solid #ccc; padding: 0.3em 0.6em; font-family: monospace; text-align: left; }}
+pre {{ background: #f4f4f4; padding: 0.6em; white-space: pre-wrap; word-break: break-all; }}
+</style>
+</head>
+<body>
+{}</body>
+</html>
+",
+        title.unwrap_or_default(),
+        body
+    )
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
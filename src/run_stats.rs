@@ -0,0 +1,200 @@
+use std::{
+    fs::{create_dir_all, OpenOptions},
+    io::Write,
+    path::PathBuf,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant, SystemTime},
+};
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+
+use crate::{
+    block::Block,
+    config::{Config, SubConfig},
+    oracle::{oracle_location::OracleLocation, request_budget::RequestBudget},
+    tui::ui_event::{UiControlEvent, UiDecryptionEvent, UiEncryptionEvent, UiEvent},
+};
+
+const STATS_FILE_NAME: &str = "stats.jsonl";
+// upper bounds of the latency tiers, in ms. Slower answers fall in the last tier
+const LATENCY_TIERS_MS: [u64; 4] = [10, 50, 200, 1000];
+
+/// Anonymous performance data of the run, for `--share-stats`. Appended to a local file, which the user may contribute to tune the default pacing. Nothing is sent anywhere.
+/// Only the kind of oracle is kept, never its location, the cypher text or the plain text.
+pub(super) struct RunStats {
+    start: Instant,
+    oracle_kind: &'static str,
+    block_size: u8,
+    threads: usize,
+    // web oracles only
+    concurrency: Option<usize>,
+    thread_delay_ms: u128,
+    request_budget: Arc<RequestBudget>,
+    state: Mutex<State>,
+}
+
+#[derive(Default)]
+struct State {
+    blocks_to_solve: usize,
+    blocks_solved: usize,
+    // amount of guesses per latency tier
+    latency_tiers: [u64; LATENCY_TIERS_MS.len() + 1],
+    // the UI is initialised again after calibration, for the same cypher text
+    decrypting: Vec<Block>,
+}
+
+#[derive(Serialize)]
+struct StatsLine {
+    version: &'static str,
+    // coarse on purpose, to not pin down when an engagement took place
+    month: String,
+    oracle: &'static str,
+    block_size: u8,
+    threads: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    concurrency: Option<usize>,
+    thread_delay_ms: u128,
+    requests: u64,
+    duration_s: u64,
+    blocks_to_solve: usize,
+    blocks_solved: usize,
+    success: bool,
+    exit_code: i32,
+    // guesses per latency tier, e.g. `["<10ms", 1200]`
+    latency_tiers: Vec<(String, u64)>,
+}
+
+impl RunStats {
+    pub(super) fn new(config: &Config) -> Self {
+        let (concurrency, thread_delay) = match config.sub_config() {
+            SubConfig::Web(web_config) => {
+                (Some(**web_config.concurrency()), web_config.thread_delay())
+            }
+            SubConfig::Script(script_config) => (None, script_config.thread_delay()),
+            SubConfig::Tcp(tcp_config) => (None, tcp_config.thread_delay()),
+            SubConfig::Plugin(plugin_config) => (None, plugin_config.thread_delay()),
+            SubConfig::Lua(lua_config) => (None, lua_config.thread_delay()),
+            SubConfig::Python(python_config) => (None, python_config.thread_delay()),
+        };
+
+        Self {
+            start: Instant::now(),
+            oracle_kind: match config.oracle_location() {
+                OracleLocation::Web(_) => "web",
+                OracleLocation::Script(_) => "script",
+                OracleLocation::Tcp(_) => "tcp",
+                OracleLocation::Plugin(_) => "plugin",
+                OracleLocation::Lua(_) => "lua",
+                OracleLocation::Python(_) => "python",
+            },
+            block_size: **config.block_size(),
+            threads: **config.thread_count(),
+            concurrency,
+            thread_delay_ms: thread_delay.as_millis(),
+            request_budget: config.request_budget().clone(),
+            state: Mutex::new(State::default()),
+        }
+    }
+
+    pub(super) fn record_ui_event(&self, event: &UiEvent) {
+        let mut state = self.state.lock().unwrap();
+        match event {
+            // segments add up
+            UiEvent::Decryption(UiDecryptionEvent::InitDecryption(cypher_text_blocks))
+                if state.decrypting != *cypher_text_blocks =>
+            {
+                state.decrypting = cypher_text_blocks.clone();
+                // the IV isn't decrypted
+                state.blocks_to_solve += cypher_text_blocks.len() - 1;
+            }
+            UiEvent::Encryption(UiEncryptionEvent::InitEncryption(plain_text_blocks, _)) => {
+                state.blocks_to_solve = plain_text_blocks.len();
+            }
+            UiEvent::Decryption(UiDecryptionEvent::BlockSolved(..))
+            | UiEvent::Encryption(UiEncryptionEvent::BlockSolved(..)) => {
+                state.blocks_solved += 1;
+            }
+            UiEvent::Control(UiControlEvent::GuessLatency(_, _, _, latency)) => {
+                let tier = latency_tier(*latency);
+                state.latency_tiers[tier] += 1;
+            }
+            _ => {}
+        }
+    }
+
+    /// Append the run's statistics to the local stats file. Returns its path
+    pub(super) fn append(&self, exit_code: i32) -> Result<PathBuf> {
+        let state = self.state.lock().unwrap();
+        let line = StatsLine {
+            version: env!("CARGO_PKG_VERSION"),
+            month: humantime::format_rfc3339_seconds(SystemTime::now()).to_string()[..7]
+                .to_string(),
+            oracle: self.oracle_kind,
+            block_size: self.block_size,
+            threads: self.threads,
+            concurrency: self.concurrency,
+            thread_delay_ms: self.thread_delay_ms,
+            requests: self.request_budget.requests(),
+            duration_s: self.start.elapsed().as_secs(),
+            blocks_to_solve: state.blocks_to_solve,
+            blocks_solved: state.blocks_solved,
+            success: exit_code == 0,
+            exit_code,
+            latency_tiers: tier_names().into_iter().zip(state.latency_tiers).collect(),
+        };
+
+        let stats_file_path = stats_file_path()?;
+        let mut stats_file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&stats_file_path)
+            .context(format!(
+                "Stats file `{}` failed to open",
+                stats_file_path.display()
+            ))?;
+        let line = serde_json::to_string(&line).context("Serialising run statistics failed")?;
+        stats_file
+            .write_all(format!("{}\n", line).as_bytes())
+            .context(format!(
+                "Writing to stats file `{}` failed",
+                stats_file_path.display()
+            ))?;
+
+        Ok(stats_file_path)
+    }
+}
+
+fn latency_tier(latency: Duration) -> usize {
+    LATENCY_TIERS_MS
+        .iter()
+        .position(|upper_bound| latency < Duration::from_millis(*upper_bound))
+        .unwrap_or(LATENCY_TIERS_MS.len())
+}
+
+fn tier_names() -> Vec<String> {
+    let mut names = Vec::with_capacity(LATENCY_TIERS_MS.len() + 1);
+    let mut lower_bound = None;
+    for upper_bound in LATENCY_TIERS_MS {
+        names.push(match lower_bound {
+            None => format!("<{}ms", upper_bound),
+            Some(lower_bound) => format!("{}-{}ms", lower_bound, upper_bound),
+        });
+        lower_bound = Some(upper_bound);
+    }
+    names.push(format!(
+        ">={}ms",
+        LATENCY_TIERS_MS[LATENCY_TIERS_MS.len() - 1]
+    ));
+
+    names
+}
+
+fn stats_file_path() -> Result<PathBuf> {
+    let stats_file_dir = dirs::data_local_dir()
+        .map(|dir| dir.join(env!("CARGO_PKG_NAME")))
+        .unwrap_or_else(|| PathBuf::from("."));
+    create_dir_all(&stats_file_dir).context("Stats directory creation failed")?;
+
+    Ok(stats_file_dir.join(STATS_FILE_NAME))
+}
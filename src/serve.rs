@@ -0,0 +1,665 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    io::{BufRead, BufReader, Read, Write},
+    net::{TcpListener, TcpStream},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+    thread,
+    time::{Duration, Instant, SystemTime},
+};
+
+use anyhow::{anyhow, Context, Result};
+use clap::{App, ArgMatches, FromArgMatches, IntoApp};
+use log::{info, LevelFilter};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::{
+    cli::{Cli, ServeCli, SubCommand},
+    config::Config,
+    error::{self, RustpadError},
+    json_result::JsonResult,
+    logging::{init_plain_logging, summarise_coalesced, LOG_TARGET},
+    logic_preparation,
+    oracle::{oracle_location::OracleLocation, request_budget::RequestBudget},
+    presets,
+    tui::ui_event::{UiControlEvent, UiEvent},
+};
+
+// a job is a command line, so anything bigger is a mistake
+const MAX_BODY_SIZE: u64 = 1024 * 1024;
+// of the request line and headers together
+const MAX_HEAD_SIZE: u64 = 16 * 1024;
+// clients which stall mid-request don't hold on to their thread
+const READ_TIMEOUT: Duration = Duration::from_secs(30);
+// options jobs may be given, by their `clap` id. Any other reads or writes files on the server, or sends its results elsewhere, so clients can't make it do so. `--from-curl` is refused too, as `curl` reads `--data @<file>`
+const JOB_OPTIONS: &[&str] = &[
+    // all sub-commands
+    "oracle-location",
+    "block-size",
+    "cypher-text",
+    "plain-text",
+    "plain-text-edit",
+    "no-iv",
+    "padding-scheme",
+    "block-selection",
+    "known-plain-text-hints",
+    "guess-order",
+    "guess-strategy",
+    "no-warm-start",
+    "strict",
+    "verbosity",
+    "thread-count",
+    "thread-delay",
+    "burst",
+    "pause",
+    "max-requests",
+    "max-duration",
+    "wind-down-after",
+    "no-tui",
+    "analyze",
+    "output-format",
+    "encoding",
+    "no-url-encode",
+    "short-requests",
+    "segment-delimiter",
+    "segment-length",
+    "segment-output",
+    "output-delimiter",
+    "base64-padding",
+    "no-cache",
+    "verification-key",
+    "verification-iv",
+    // `web`
+    "preset",
+    "post-data",
+    "header",
+    "method",
+    "redirect",
+    "no-cert-validation",
+    "pin-cert",
+    "allow-cert-change",
+    "keyword",
+    "keyword-occurrence",
+    "split-length",
+    "consider-body",
+    "body-normalizations",
+    "padding-error-status",
+    "padding-error-location",
+    "padding-error-size",
+    "padding-error-regex",
+    "calibration-mode",
+    "calibration-passes",
+    "user-agent",
+    "proxy-urls",
+    "proxy-credentials",
+    "request-timeout",
+    "concurrency",
+    "http-version",
+    "http2-connections",
+    "cache-bust",
+    "sticky-session",
+    "warm-up",
+    "maintenance-probe",
+    "maintenance-timeout",
+    // `tcp`
+    "send-template",
+    "padding-error",
+    "response-delimiter",
+    "tls",
+    // `script`, `plugin`, `lua` and `python`
+    "persistent",
+    "verdict-source",
+    "plugin-config",
+    "allowed-hosts",
+];
+// options of which values name a file with `@<file>`, or stdin with `-`
+const FILE_VALUED_OPTIONS: &[&str] = &["cypher-text", "guess-order"];
+
+/// Daemon which runs attack jobs on behalf of clients of its HTTP API
+struct Server {
+    token: String,
+    allow_scripts: bool,
+    max_jobs: usize,
+    next_id: AtomicU64,
+    // kept until forgotten by the client, so results can be fetched at any time
+    jobs: Mutex<BTreeMap<u64, Arc<Job>>>,
+}
+
+/// An attack, as submitted by a client
+struct Job {
+    id: u64,
+    oracle: String,
+    submitted: SystemTime,
+    start: Instant,
+    // cancelling the budget stops the attack at its next request
+    request_budget: Arc<RequestBudget>,
+    json_result: JsonResult,
+    progress: Mutex<Progress>,
+    // `None` while running
+    outcome: Mutex<Option<Outcome>>,
+}
+
+#[derive(Default)]
+struct Progress {
+    bytes_solved: usize,
+    bytes_total: usize,
+}
+
+struct Outcome {
+    status: JobStatus,
+    exit_code: i32,
+    error: Option<String>,
+    error_kind: Option<&'static str>,
+    duration: Duration,
+}
+
+#[derive(Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+enum JobStatus {
+    Running,
+    Succeeded,
+    Failed,
+    Cancelled,
+}
+
+#[derive(Deserialize)]
+struct JobRequest {
+    // the sub-command and its options, as on the command line
+    args: Vec<String>,
+}
+
+#[derive(Serialize)]
+struct JobDocument {
+    id: u64,
+    status: JobStatus,
+    oracle: String,
+    submitted: String,
+    duration_ms: u128,
+    bytes_solved: usize,
+    bytes_total: usize,
+    requests: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    exit_code: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error_kind: Option<&'static str>,
+}
+
+/// Answer API requests until the process is stopped. Every connection is handled on its own thread, and every job runs on its own.
+pub(super) fn serve(serve_cli: ServeCli) -> Result<()> {
+    // even on a loopback address, as web pages could otherwise submit jobs through the browser, e.g. after rebinding their DNS name to it
+    let token = match serve_cli.token() {
+        Some(token) => token.clone(),
+        None => random_token()?,
+    };
+    // jobs share the process' logger, so their options can't pick the log level or file
+    init_plain_logging(LevelFilter::Info, None, None, None)?;
+
+    let server = Arc::new(Server {
+        token: token.clone(),
+        allow_scripts: *serve_cli.allow_scripts(),
+        max_jobs: serve_cli.max_jobs().get(),
+        next_id: AtomicU64::new(1),
+        jobs: Mutex::new(BTreeMap::new()),
+    });
+
+    let listener = TcpListener::bind(serve_cli.listen())
+        .context(format!("Listening on `{}` failed", serve_cli.listen()))?;
+    println!("Serving the API on http://{}/", serve_cli.listen());
+    if serve_cli.token().is_none() {
+        println!("Token: {}", token);
+    }
+    println!(
+        "Try:   curl http://{}/jobs -H 'Authorization: Bearer <token>' -d '{{\"args\": [\"web\", \"--oracle\", \"http://target/?token=CTEXT\", \"--decrypt\", \"<cypher text>\", \"-B\", \"16\"]}}'",
+        serve_cli.listen()
+    );
+
+    for stream in listener.incoming() {
+        let stream = match stream {
+            Ok(stream) => stream,
+            Err(e) => {
+                eprintln!("Accepting a connection failed: {}", e);
+                continue;
+            }
+        };
+        let server = server.clone();
+        thread::spawn(move || {
+            if let Err(e) = server.handle_connection(stream) {
+                eprintln!("{:#}", e);
+            }
+        });
+    }
+
+    Ok(())
+}
+
+impl Server {
+    /// Answer a single request. The connection is closed afterwards, as polling clients needn't keep it alive
+    fn handle_connection(self: &Arc<Self>, stream: TcpStream) -> Result<()> {
+        stream.set_read_timeout(Some(READ_TIMEOUT))?;
+        let mut reader = BufReader::new(stream.try_clone()?);
+        let mut stream = stream;
+
+        // the request line and headers can't exceed `MAX_HEAD_SIZE` together
+        let mut head = (&mut reader).take(MAX_HEAD_SIZE);
+        let mut request_line = String::new();
+        if head.read_line(&mut request_line)? == 0 {
+            return Ok(());
+        }
+        let mut headers = HashMap::new();
+        loop {
+            let mut header = String::new();
+            if head.read_line(&mut header)? == 0 {
+                if head.limit() == 0 {
+                    return write_response(
+                        &mut stream,
+                        "431 Request Header Fields Too Large",
+                        &error_document("The request line and headers are at most 16 KiB"),
+                    );
+                }
+                return Ok(());
+            }
+            let header = header.trim_end();
+            if header.is_empty() {
+                break;
+            }
+            if let Some((name, value)) = header.split_once(':') {
+                headers.insert(name.trim().to_lowercase(), value.trim().to_string());
+            }
+        }
+
+        let body_size = headers
+            .get("content-length")
+            .and_then(|length| length.parse::<u64>().ok())
+            .unwrap_or(0);
+        let (status, body) = if body_size > MAX_BODY_SIZE {
+            (
+                "413 Payload Too Large",
+                error_document("A job is at most 1 MiB"),
+            )
+        } else {
+            let mut body = Vec::new();
+            (&mut reader).take(body_size).read_to_end(&mut body)?;
+
+            let mut parts = request_line.split_whitespace();
+            match (parts.next(), parts.next()) {
+                (Some(method), Some(target)) if self.is_authorised(&headers) => {
+                    self.respond(method, target, &body)
+                }
+                (Some(_), Some(_)) => (
+                    "401 Unauthorized",
+                    error_document("Authenticate with `Authorization: Bearer <token>`"),
+                ),
+                _ => ("400 Bad Request", error_document("Malformed request line")),
+            }
+        };
+
+        write_response(&mut stream, status, &body)
+    }
+
+    fn is_authorised(&self, headers: &HashMap<String, String>) -> bool {
+        let token = &self.token;
+        headers
+            .get("authorization")
+            .and_then(|authorization| authorization.strip_prefix("Bearer "))
+            // constant time, so the token can't be guessed byte by byte from response times
+            .is_some_and(|given| {
+                given.len() == token.len()
+                    && openssl::memcmp::eq(given.as_bytes(), token.as_bytes())
+            })
+    }
+
+    fn respond(
+        self: &Arc<Self>,
+        method: &str,
+        target: &str,
+        body: &[u8],
+    ) -> (&'static str, String) {
+        let path = target.split_once('?').map_or(target, |(path, _)| path);
+        let segments: Vec<&str> = path
+            .trim_matches('/')
+            .split('/')
+            .filter(|segment| !segment.is_empty())
+            .collect();
+
+        let job = |id: &str| {
+            id.parse::<u64>()
+                .ok()
+                .and_then(|id| self.jobs.lock().unwrap().get(&id).cloned())
+        };
+        match (method, &segments[..]) {
+            ("POST", ["jobs"]) => match self.submit(body) {
+                Ok(job) => ("201 Created", to_json(job.document())),
+                Err((status, e)) => (status, error_document(&format!("{:#}", e))),
+            },
+            ("GET", ["jobs"]) => (
+                "200 OK",
+                to_json(
+                    self.jobs
+                        .lock()
+                        .unwrap()
+                        .values()
+                        .map(|job| job.document())
+                        .collect::<Vec<_>>(),
+                ),
+            ),
+            ("GET", ["jobs", id]) => match job(id) {
+                Some(job) => ("200 OK", to_json(job.document())),
+                None => not_found(id),
+            },
+            ("GET", ["jobs", id, "result"]) => match job(id) {
+                Some(job) if job.status() == JobStatus::Running => (
+                    "409 Conflict",
+                    error_document("The job is still running. Poll its status until it finishes"),
+                ),
+                Some(job) => ("200 OK", to_json(job.json_result.document())),
+                None => not_found(id),
+            },
+            ("DELETE", ["jobs", id]) => match job(id) {
+                Some(job) if job.status() == JobStatus::Running => {
+                    info!(target: LOG_TARGET, "Job {}: cancelling", job.id);
+                    job.request_budget.cancel();
+                    ("202 Accepted", to_json(job.document()))
+                }
+                Some(job) => {
+                    self.jobs.lock().unwrap().remove(&job.id);
+                    ("200 OK", to_json(job.document()))
+                }
+                None => not_found(id),
+            },
+            (_, ["jobs"]) | (_, ["jobs", _]) | (_, ["jobs", _, "result"]) => (
+                "405 Method Not Allowed",
+                error_document(&format!("`{}` isn't supported on `{}`", method, path)),
+            ),
+            _ => ("404 Not Found", error_document("Unknown endpoint")),
+        }
+    }
+
+    /// Start a job in the background
+    fn submit(self: &Arc<Self>, body: &[u8]) -> Result<Arc<Job>, (&'static str, anyhow::Error)> {
+        let request: JobRequest = serde_json::from_slice(body)
+            .context("Expected a job of the form `{\"args\": [\"<sub-command>\", ...]}`")
+            .map_err(|e| ("400 Bad Request", e))?;
+        let config = self
+            .job_config(&request.args)
+            .map_err(|e| ("400 Bad Request", e))?;
+
+        let job = {
+            let mut jobs = self.jobs.lock().unwrap();
+            let running = jobs
+                .values()
+                .filter(|job| job.status() == JobStatus::Running)
+                .count();
+            if running >= self.max_jobs {
+                return Err((
+                    "429 Too Many Requests",
+                    anyhow!(
+                        "{} jobs are running already. Retry once one finishes, see `--max-jobs`",
+                        running
+                    ),
+                ));
+            }
+
+            let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+            let job = Arc::new(Job::new(id, &config));
+            jobs.insert(id, job.clone());
+            job
+        };
+
+        info!(
+            target: LOG_TARGET,
+            "Job {}: attacking {}", job.id, job.oracle
+        );
+        let job_thread = job.clone();
+        thread::Builder::new()
+            .name(format!("Job {}", job.id))
+            .spawn(move || job_thread.run(config))
+            .context("Starting the job failed")
+            .map_err(|e| {
+                self.jobs.lock().unwrap().remove(&job.id);
+                ("500 Internal Server Error", e)
+            })?;
+
+        Ok(job)
+    }
+
+    /// Config of the job, as if its arguments were given on the command line
+    fn job_config(&self, args: &[String]) -> Result<Config> {
+        let args = [env!("CARGO_PKG_NAME").to_string()]
+            .into_iter()
+            .chain(args.iter().cloned());
+        let app = Cli::into_app();
+        let matches = app
+            .clone()
+            .try_get_matches_from(presets::expand(args)?)
+            .context("Invalid job")?;
+        let cli = Cli::from_arg_matches(&matches).context("Invalid job")?;
+        match cli.sub_command {
+            SubCommand::Web(_) | SubCommand::Tcp(_) => {}
+            SubCommand::Script(_)
+            | SubCommand::Plugin(_)
+            | SubCommand::Lua(_)
+            | SubCommand::Python(_)
+                if self.allow_scripts => {}
+            SubCommand::Script(_)
+            | SubCommand::Plugin(_)
+            | SubCommand::Lua(_)
+            | SubCommand::Python(_) => {
+                return Err(anyhow!(
+                    "Jobs with a `script`, `plugin`, `lua` or `python` oracle are refused, as they run code on the server. See `serve --allow-scripts`"
+                ))
+            }
+            _ => {
+                return Err(anyhow!(
+                    "A job is a `web`, `script`, `tcp`, `plugin`, `lua` or `python` sub-command and its options"
+                ))
+            }
+        }
+
+        check_job_options(&app, &matches)?;
+
+        Config::try_from(cli).context("Invalid job")
+    }
+}
+
+/// Refuse options which aren't in `JOB_OPTIONS`, and values which name a file
+fn check_job_options(app: &App, matches: &ArgMatches) -> Result<()> {
+    let (sub_command, matches) = matches.subcommand().expect("A sub-command is required");
+    let sub_command = app
+        .find_subcommand(sub_command)
+        .expect("The matched sub-command exists");
+
+    for arg in sub_command.get_arguments() {
+        let id = arg.get_name();
+        // default values and environment variables aren't the client's
+        if matches.occurrences_of(id) == 0 {
+            continue;
+        }
+        let option = arg
+            .get_long()
+            .map_or_else(|| id.to_string(), |long| format!("--{}", long));
+
+        if !JOB_OPTIONS.contains(&id) {
+            return Err(anyhow!(
+                "`{}` is unsupported in jobs, which can't use the server's files",
+                option
+            ));
+        }
+        if FILE_VALUED_OPTIONS.contains(&id)
+            && matches
+                .values_of(id)
+                .into_iter()
+                .flatten()
+                .any(|value| value == "-" || value.starts_with('@'))
+        {
+            return Err(anyhow!(
+                "`{}` can't be read from a file or stdin in jobs. Pass the value itself",
+                option
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Token for clients to authenticate with, if none was given
+fn random_token() -> Result<String> {
+    let mut token = [0u8; 16];
+    openssl::rand::rand_bytes(&mut token).context("Generating a token failed")?;
+    Ok(hex::encode(token))
+}
+
+impl Job {
+    fn new(id: u64, config: &Config) -> Self {
+        Self {
+            id,
+            oracle: match config.oracle_location() {
+                OracleLocation::Web(url) => url.to_string(),
+                OracleLocation::Script(path) => path.display().to_string(),
+                OracleLocation::Tcp(address) => address.clone(),
+                OracleLocation::Plugin(path) => path.display().to_string(),
+                OracleLocation::Lua(path) => path.display().to_string(),
+                OracleLocation::Python(path) => path.display().to_string(),
+            },
+            submitted: SystemTime::now(),
+            start: Instant::now(),
+            request_budget: config.request_budget().clone(),
+            json_result: JsonResult::new(config),
+            progress: Mutex::new(Progress::default()),
+            outcome: Mutex::new(None),
+        }
+    }
+
+    fn run(&self, config: Config) {
+        let update_ui_callback = |event: UiEvent| {
+            summarise_coalesced(false);
+            self.json_result.record_ui_event(&event);
+            match event {
+                UiEvent::Control(UiControlEvent::IndicateWork(
+                    decryption_bytes,
+                    encryption_bytes,
+                )) => {
+                    // a new segment of the cypher text starts from scratch
+                    *self.progress.lock().unwrap() = Progress {
+                        bytes_solved: 0,
+                        bytes_total: decryption_bytes + encryption_bytes,
+                    };
+                }
+                UiEvent::Control(UiControlEvent::ProgressUpdate(_, bytes_solved)) => {
+                    self.progress.lock().unwrap().bytes_solved += bytes_solved;
+                }
+                _ => {}
+            }
+        };
+
+        // jobs run side by side, so each gets a pool of its own `--threads`
+        let result = rayon::ThreadPoolBuilder::new()
+            .num_threads(**config.thread_count())
+            .build()
+            .context("Thread pool initialisation failed")
+            .and_then(|thread_pool| {
+                thread_pool.install(|| logic_preparation(config, update_ui_callback, None))
+            });
+        summarise_coalesced(true);
+
+        let outcome = match result {
+            Ok(()) => {
+                info!(target: LOG_TARGET, "Job {}: succeeded", self.id);
+                Outcome {
+                    status: JobStatus::Succeeded,
+                    exit_code: 0,
+                    error: None,
+                    error_kind: None,
+                    duration: self.start.elapsed(),
+                }
+            }
+            Err(e) => {
+                info!(target: LOG_TARGET, "Job {}: failed: {:#}", self.id, e);
+                let exit_code = error::exit_code(&e);
+                self.json_result.record_ui_event(&UiEvent::Control(
+                    UiControlEvent::PrintAfterExit(format!("Error: {:#}", e)),
+                ));
+                self.json_result
+                    .record_ui_event(&UiEvent::Control(UiControlEvent::ExitCode(exit_code)));
+                self.json_result.record_error(&e);
+                Outcome {
+                    status: if self.request_budget.is_cancelled() {
+                        JobStatus::Cancelled
+                    } else {
+                        JobStatus::Failed
+                    },
+                    exit_code,
+                    error: Some(format!("{:#}", e)),
+                    error_kind: RustpadError::find(&e).map(RustpadError::kind),
+                    duration: self.start.elapsed(),
+                }
+            }
+        };
+        *self.outcome.lock().unwrap() = Some(outcome);
+    }
+
+    fn status(&self) -> JobStatus {
+        self.outcome
+            .lock()
+            .unwrap()
+            .as_ref()
+            .map_or(JobStatus::Running, |outcome| outcome.status)
+    }
+
+    fn document(&self) -> JobDocument {
+        let progress = self.progress.lock().unwrap();
+        let outcome = self.outcome.lock().unwrap();
+        JobDocument {
+            id: self.id,
+            status: outcome
+                .as_ref()
+                .map_or(JobStatus::Running, |outcome| outcome.status),
+            oracle: self.oracle.clone(),
+            submitted: humantime::format_rfc3339_seconds(self.submitted).to_string(),
+            duration_ms: outcome
+                .as_ref()
+                .map_or_else(|| self.start.elapsed(), |outcome| outcome.duration)
+                .as_millis(),
+            bytes_solved: progress.bytes_solved,
+            bytes_total: progress.bytes_total,
+            requests: self.request_budget.requests(),
+            exit_code: outcome.as_ref().map(|outcome| outcome.exit_code),
+            error: outcome.as_ref().and_then(|outcome| outcome.error.clone()),
+            error_kind: outcome.as_ref().and_then(|outcome| outcome.error_kind),
+        }
+    }
+}
+
+fn write_response(stream: &mut TcpStream, status: &str, body: &str) -> Result<()> {
+    let mut response = format!(
+        "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n",
+        status,
+        body.len() + 1
+    );
+    if status == "401 Unauthorized" {
+        response.push_str("WWW-Authenticate: Bearer\r\n");
+    }
+    response.push_str("\r\n");
+    response.push_str(body);
+    response.push('\n');
+    stream.write_all(response.as_bytes())?;
+    Ok(())
+}
+
+fn to_json(document: impl Serialize) -> String {
+    serde_json::to_string_pretty(&document).expect("Documents serialise to JSON")
+}
+
+fn error_document(message: &str) -> String {
+    to_json(json!({ "error": message }))
+}
+
+fn not_found(id: &str) -> (&'static str, String) {
+    (
+        "404 Not Found",
+        error_document(&format!("No job with id `{}`", id)),
+    )
+}
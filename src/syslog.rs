@@ -0,0 +1,183 @@
+use std::{
+    fs, io::Write, net::UdpSocket, os::unix::net::UnixDatagram, process, sync::OnceLock,
+    time::SystemTime,
+};
+
+use anyhow::{Context, Result};
+use log::{Level, Record};
+use regex::Regex;
+
+use crate::{
+    config::syslog_destination::SyslogDestination, oracle::oracle_location::OracleLocation,
+};
+
+const JOURNALD_SOCKET: &str = "/run/systemd/journal/socket";
+// facility `user`, as rustpad isn't a system service
+const FACILITY: u8 = 1;
+// private enterprise number reserved for documentation (RFC 5612), identifies rustpad's structured data
+const SD_ID: &str = "rustpad@32473";
+
+static BLOCK_NUMBER: OnceLock<Regex> = OnceLock::new();
+
+/// Sends log lines to syslog or the journal, for `--syslog`, so runs on jump boxes feed central logging.
+/// Lines carry structured fields: the oracle, and the block the line is about, if any. Remote syslog gets them as structured data, the journal as fields. The local syslog socket only takes plain lines.
+pub(super) struct Syslog {
+    transport: Transport,
+    hostname: String,
+    pid: u32,
+    // unknown in mass mode, as each target has its own
+    oracle: Option<String>,
+}
+
+enum Transport {
+    Local(UnixDatagram),
+    Udp(UdpSocket),
+    Journald(UnixDatagram),
+}
+
+impl Syslog {
+    pub(super) fn connect(
+        destination: &SyslogDestination,
+        oracle_location: Option<&OracleLocation>,
+    ) -> Result<Self> {
+        let connect_unix = |path| -> Result<UnixDatagram> {
+            let socket = UnixDatagram::unbound().context("Syslog socket creation failed")?;
+            socket
+                .connect(path)
+                .context(format!("Connecting to syslog `{}` failed", destination))?;
+            Ok(socket)
+        };
+
+        let transport = match destination {
+            SyslogDestination::Local(path) => Transport::Local(connect_unix(path.as_path())?),
+            SyslogDestination::Udp(address) => {
+                let socket =
+                    UdpSocket::bind("0.0.0.0:0").context("Syslog socket creation failed")?;
+                socket
+                    .connect(address)
+                    .context(format!("Connecting to syslog `{}` failed", destination))?;
+                Transport::Udp(socket)
+            }
+            SyslogDestination::Journald => {
+                Transport::Journald(connect_unix(JOURNALD_SOCKET.as_ref())?)
+            }
+        };
+
+        Ok(Self {
+            transport,
+            hostname: fs::read_to_string("/proc/sys/kernel/hostname")
+                .map(|hostname| hostname.trim().to_string())
+                .ok()
+                .filter(|hostname| !hostname.is_empty())
+                .unwrap_or_else(|| "-".to_string()),
+            pid: process::id(),
+            oracle: oracle_location.map(|oracle_location| match oracle_location {
+                OracleLocation::Web(url) => url.to_string(),
+                OracleLocation::Script(path) => path.display().to_string(),
+                OracleLocation::Tcp(address) => address.clone(),
+                OracleLocation::Plugin(path) => path.display().to_string(),
+                OracleLocation::Lua(path) => path.display().to_string(),
+                OracleLocation::Python(path) => path.display().to_string(),
+            }),
+        })
+    }
+
+    /// Failing to send is ignored, as it can't be logged about
+    pub(super) fn send(&self, record: &Record) {
+        let message = record.args().to_string();
+        let block = block_number(&message);
+        let severity = severity(record.level());
+
+        let _ = match &self.transport {
+            Transport::Local(socket) => socket.send(
+                format!(
+                    "<{}>{} {}[{}]: {}",
+                    FACILITY * 8 + severity,
+                    humantime::format_rfc3339_seconds(SystemTime::now()),
+                    env!("CARGO_PKG_NAME"),
+                    self.pid,
+                    message
+                )
+                .as_bytes(),
+            ),
+            // RFC 5424
+            Transport::Udp(socket) => {
+                let mut structured_data = format!("[{} level=\"{}\"", SD_ID, record.level());
+                if let Some(oracle) = &self.oracle {
+                    structured_data
+                        .push_str(&format!(" oracle=\"{}\"", escape_param_value(oracle)));
+                }
+                if let Some(block) = block {
+                    structured_data.push_str(&format!(" block=\"{}\"", block));
+                }
+                structured_data.push(']');
+
+                socket.send(
+                    format!(
+                        "<{}>1 {} {} {} {} - {} {}",
+                        FACILITY * 8 + severity,
+                        humantime::format_rfc3339_millis(SystemTime::now()),
+                        self.hostname,
+                        env!("CARGO_PKG_NAME"),
+                        self.pid,
+                        structured_data,
+                        message
+                    )
+                    .as_bytes(),
+                )
+            }
+            // native protocol, see `systemd.journal-fields(7)`
+            Transport::Journald(socket) => {
+                let mut fields = Vec::new();
+                journald_field(&mut fields, "MESSAGE", &message);
+                journald_field(&mut fields, "PRIORITY", &severity.to_string());
+                journald_field(&mut fields, "SYSLOG_FACILITY", &FACILITY.to_string());
+                journald_field(&mut fields, "SYSLOG_IDENTIFIER", env!("CARGO_PKG_NAME"));
+                journald_field(&mut fields, "SYSLOG_PID", &self.pid.to_string());
+                if let Some(oracle) = &self.oracle {
+                    journald_field(&mut fields, "RUSTPAD_ORACLE", oracle);
+                }
+                if let Some(block) = block {
+                    journald_field(&mut fields, "RUSTPAD_BLOCK", &block.to_string());
+                }
+                socket.send(&fields)
+            }
+        };
+    }
+}
+
+fn severity(level: Level) -> u8 {
+    match level {
+        Level::Error => 3,
+        Level::Warn => 4,
+        Level::Info => 6,
+        Level::Debug | Level::Trace => 7,
+    }
+}
+
+/// The block a log line is about, as numbered in the line, e.g. `Block 3, byte 16: ...`
+fn block_number(message: &str) -> Option<usize> {
+    BLOCK_NUMBER
+        .get_or_init(|| Regex::new(r"(?i)\bblock (\d+)").expect("Block number regex is valid"))
+        .captures(message)
+        .and_then(|captures| captures[1].parse().ok())
+}
+
+fn escape_param_value(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace(']', "\\]")
+}
+
+fn journald_field(fields: &mut Vec<u8>, name: &str, value: &str) {
+    if value.contains('\n') {
+        // multi-line values are length-prefixed
+        let _ = writeln!(fields, "{}", name);
+        fields.extend_from_slice(&(value.len() as u64).to_le_bytes());
+        fields.extend_from_slice(value.as_bytes());
+        fields.push(b'\n');
+    } else {
+        let _ = writeln!(fields, "{}={}", name, value);
+    }
+}
@@ -0,0 +1,131 @@
+mod svg;
+
+use std::{
+    collections::HashSet,
+    fs,
+    path::Path,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::tui::ui_event::{UiDecryptionEvent, UiEncryptionEvent, UiEvent};
+
+/// Records when the phases of the attack happened, for write-ups and performance analysis. Exported as JSON and as an SVG Gantt chart.
+pub(super) struct Timeline {
+    start: Instant,
+    events: Mutex<Vec<TimelineEvent>>,
+    // WIP updates are sent for every guess, but only the 1st one marks the start of a block
+    started_blocks: Mutex<HashSet<(Phase, usize)>>,
+}
+
+#[derive(Serialize, Clone)]
+struct TimelineEvent {
+    elapsed_ms: u128,
+    #[serde(flatten)]
+    kind: TimelineEventKind,
+}
+
+#[derive(Serialize, Clone)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub(super) enum TimelineEventKind {
+    CalibrationStarted,
+    CalibrationFinished,
+    BlockStarted {
+        phase: Phase,
+        block: usize,
+    },
+    BlockFinished {
+        phase: Phase,
+        block: usize,
+    },
+    Error {
+        message: String,
+        // class of the failure, if it's a `RustpadError`
+        #[serde(skip_serializing_if = "Option::is_none")]
+        kind: Option<&'static str>,
+    },
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Hash, PartialEq, Eq, PartialOrd, Ord, Debug)]
+#[serde(rename_all = "snake_case")]
+pub(super) enum Phase {
+    Decryption,
+    Encryption,
+}
+
+#[derive(Serialize)]
+struct TimelineExport<'a> {
+    total_ms: u128,
+    events: &'a [TimelineEvent],
+}
+
+impl Timeline {
+    pub(super) fn new() -> Self {
+        Self {
+            start: Instant::now(),
+            events: Mutex::new(Vec::new()),
+            started_blocks: Mutex::new(HashSet::new()),
+        }
+    }
+
+    pub(super) fn record(&self, kind: TimelineEventKind) {
+        self.events.lock().unwrap().push(TimelineEvent {
+            elapsed_ms: self.start.elapsed().as_millis(),
+            kind,
+        });
+    }
+
+    /// Derive the start and end of blocks from the events sent to the UI
+    pub(super) fn record_ui_event(&self, event: &UiEvent) {
+        let (phase, block, solved) = match event {
+            UiEvent::Decryption(UiDecryptionEvent::BlockWip(_, block)) => {
+                (Phase::Decryption, *block, false)
+            }
+            UiEvent::Decryption(UiDecryptionEvent::BlockSolved(_, block)) => {
+                (Phase::Decryption, *block, true)
+            }
+            UiEvent::Encryption(UiEncryptionEvent::BlockWip(_, block)) => {
+                (Phase::Encryption, *block, false)
+            }
+            UiEvent::Encryption(UiEncryptionEvent::BlockSolved(_, block)) => {
+                (Phase::Encryption, *block, true)
+            }
+            _ => return,
+        };
+
+        if solved {
+            self.record(TimelineEventKind::BlockFinished { phase, block });
+        } else if self.started_blocks.lock().unwrap().insert((phase, block)) {
+            self.record(TimelineEventKind::BlockStarted { phase, block });
+        }
+    }
+
+    /// Write the timeline as JSON to `path`, and as an SVG Gantt chart next to it
+    pub(super) fn export(&self, path: &Path) -> Result<()> {
+        let events = self.events.lock().unwrap().clone();
+        let total = self.start.elapsed();
+
+        let json = serde_json::to_string_pretty(&TimelineExport {
+            total_ms: total.as_millis(),
+            events: &events,
+        })
+        .context("Serialising timeline failed")?;
+        fs::write(path, json)
+            .context(format!("Writing timeline to `{}` failed", path.display()))?;
+
+        let svg_path = path.with_extension("svg");
+        fs::write(&svg_path, svg::render(&events, total)).context(format!(
+            "Writing timeline chart to `{}` failed",
+            svg_path.display()
+        ))
+    }
+}
+
+impl TimelineEvent {
+    fn elapsed(&self) -> Duration {
+        Duration::from_millis(self.elapsed_ms as u64)
+    }
+}
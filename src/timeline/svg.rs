@@ -0,0 +1,112 @@
+use std::{collections::BTreeMap, fmt::Write, time::Duration};
+
+use super::{Phase, TimelineEvent, TimelineEventKind};
+
+const LABEL_WIDTH: u32 = 160;
+const CHART_WIDTH: u32 = 800;
+const ROW_HEIGHT: u32 = 24;
+const BAR_HEIGHT: u32 = 16;
+const AXIS_HEIGHT: u32 = 24;
+
+#[derive(PartialEq, Eq, PartialOrd, Ord)]
+enum Row {
+    Calibration,
+    Block(Phase, usize),
+}
+
+/// Gantt chart with a row for calibration and for every block. Errors are marked with a red line over the whole chart.
+pub(super) fn render(events: &[TimelineEvent], total: Duration) -> String {
+    // (start, end). Blocks which are solved without questioning the oracle only have an end
+    let mut bars: BTreeMap<Row, (Option<Duration>, Option<Duration>)> = BTreeMap::new();
+    let mut errors = Vec::new();
+
+    for event in events {
+        match &event.kind {
+            TimelineEventKind::CalibrationStarted => {
+                bars.entry(Row::Calibration).or_default().0 = Some(event.elapsed())
+            }
+            TimelineEventKind::CalibrationFinished => {
+                bars.entry(Row::Calibration).or_default().1 = Some(event.elapsed())
+            }
+            TimelineEventKind::BlockStarted { phase, block } => {
+                bars.entry(Row::Block(*phase, *block)).or_default().0 = Some(event.elapsed())
+            }
+            TimelineEventKind::BlockFinished { phase, block } => {
+                bars.entry(Row::Block(*phase, *block)).or_default().1 = Some(event.elapsed())
+            }
+            TimelineEventKind::Error { message, .. } => errors.push((event.elapsed(), message)),
+        }
+    }
+
+    let total_ms = total.as_millis().max(1) as f64;
+    let x_of =
+        |at: Duration| LABEL_WIDTH as f64 + at.as_millis() as f64 / total_ms * CHART_WIDTH as f64;
+    let height = AXIS_HEIGHT + ROW_HEIGHT * bars.len() as u32;
+
+    let mut svg = String::new();
+    // writing to a `String` can't fail
+    let _ = writeln!(
+        svg,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{}" height="{}" font-family="monospace" font-size="12">"#,
+        LABEL_WIDTH + CHART_WIDTH,
+        height
+    );
+    let _ = writeln!(
+        svg,
+        r#"<text x="{}" y="16">0s</text><text x="{}" y="16" text-anchor="end">{:.1}s</text>"#,
+        LABEL_WIDTH,
+        LABEL_WIDTH + CHART_WIDTH,
+        total.as_secs_f64()
+    );
+
+    for (idx, (row, (start, end))) in bars.iter().enumerate() {
+        let y = AXIS_HEIGHT + ROW_HEIGHT * idx as u32;
+        let (label, colour) = match row {
+            Row::Calibration => ("Calibration".to_string(), "#888888"),
+            Row::Block(Phase::Decryption, block) => (format!("Decrypt block {}", block), "#3a7bd5"),
+            Row::Block(Phase::Encryption, block) => (format!("Encrypt block {}", block), "#d5803a"),
+        };
+        // unfinished bars run until the end
+        let end = end.unwrap_or(total);
+        let start = start.unwrap_or(end);
+
+        let _ = writeln!(
+            svg,
+            r#"<text x="4" y="{}">{}</text>"#,
+            y + BAR_HEIGHT - 3,
+            label
+        );
+        let _ = writeln!(
+            svg,
+            r#"<rect x="{:.1}" y="{}" width="{:.1}" height="{}" fill="{}"><title>{}: {:.3}s - {:.3}s</title></rect>"#,
+            x_of(start),
+            y,
+            (x_of(end) - x_of(start)).max(1.0),
+            BAR_HEIGHT,
+            colour,
+            label,
+            start.as_secs_f64(),
+            end.as_secs_f64()
+        );
+    }
+
+    for (at, message) in errors {
+        let _ = writeln!(
+            svg,
+            r#"<line x1="{x:.1}" y1="{}" x2="{x:.1}" y2="{}" stroke="red" stroke-width="2"><title>{}</title></line>"#,
+            AXIS_HEIGHT,
+            height,
+            escape(message),
+            x = x_of(at)
+        );
+    }
+
+    svg.push_str("</svg>\n");
+    svg
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
@@ -0,0 +1,128 @@
+use std::{
+    fs::File,
+    io::{self, Write},
+    path::Path,
+    sync::{Arc, Mutex},
+    time::{Instant, SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::{Context, Result};
+use log::error;
+use serde::Serialize;
+use serde_json::json;
+
+use crate::logging::LOG_TARGET;
+
+/// Records the TUI's frames as an asciinema cast (v2), for `--record-cast`. Plays with `asciinema play`, and converts to a GIF with e.g. `agg`.
+/// Every frame is an output event, written as soon as it's drawn, so a cast of an interrupted attack plays too.
+pub(super) struct Cast {
+    start: Instant,
+    state: Mutex<State>,
+}
+
+struct State {
+    file: File,
+    // writing failed before. Reported once, instead of for every frame
+    broken: bool,
+}
+
+#[derive(Serialize)]
+struct Header {
+    version: u8,
+    width: u16,
+    height: u16,
+    timestamp: u64,
+    title: &'static str,
+}
+
+impl Cast {
+    /// A cast at `path` is overwritten. The terminal's size is that of the first frame, as resizes are recorded
+    pub(super) fn create(path: &Path, (width, height): (u16, u16)) -> Result<Self> {
+        let mut file =
+            File::create(path).context(format!("Cast `{}` failed to open", path.display()))?;
+        let header = Header {
+            version: 2,
+            width,
+            height,
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|since_epoch| since_epoch.as_secs())
+                .unwrap_or_default(),
+            title: env!("CARGO_PKG_NAME"),
+        };
+        writeln!(
+            file,
+            "{}",
+            serde_json::to_string(&header).context("Serialising cast header failed")?
+        )
+        .context(format!("Writing to cast `{}` failed", path.display()))?;
+
+        Ok(Self {
+            start: Instant::now(),
+            state: Mutex::new(State {
+                file,
+                broken: false,
+            }),
+        })
+    }
+
+    pub(super) fn resize(&self, cols: u16, rows: u16) {
+        self.record("r", &format!("{}x{}", cols, rows));
+    }
+
+    fn record(&self, code: &str, data: &str) {
+        let event = json!([self.start.elapsed().as_secs_f64(), code, data]);
+
+        let mut state = self.state.lock().unwrap();
+        if state.broken {
+            return;
+        }
+        // a single write per event, so players never see half an event
+        if let Err(e) = state
+            .file
+            .write_all(format!("{}\n", event).as_bytes())
+            .context("Writing to the cast failed")
+        {
+            error!(target: LOG_TARGET, "{:?}", e);
+            state.broken = true;
+        }
+    }
+}
+
+/// The TUI's output: the terminal, and the cast if any. Frames are recorded when they're flushed to the terminal
+pub(super) struct TuiOutput {
+    stdout: io::Stdout,
+    cast: Option<Arc<Cast>>,
+    // output of the frame being drawn
+    frame: Vec<u8>,
+}
+
+impl TuiOutput {
+    pub(super) fn new(cast: Option<Arc<Cast>>) -> Self {
+        Self {
+            stdout: io::stdout(),
+            cast,
+            frame: Vec::new(),
+        }
+    }
+}
+
+impl Write for TuiOutput {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let written = self.stdout.write(buf)?;
+        if self.cast.is_some() {
+            self.frame.extend_from_slice(&buf[..written]);
+        }
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        if let Some(cast) = &self.cast {
+            if !self.frame.is_empty() {
+                cast.record("o", &String::from_utf8_lossy(&self.frame));
+                self.frame.clear();
+            }
+        }
+        self.stdout.flush()
+    }
+}
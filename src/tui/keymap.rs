@@ -0,0 +1,402 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{anyhow, Context, Result};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+const KEYMAP_FILE_NAME: &str = "keymap.conf";
+
+/// What pressing a key does. Keys typed into the jump prompt aren't bindings, they're the block number
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum Action {
+    Quit,
+    WindDown,
+    ToggleHelp,
+    CloseHelp,
+    SelectPreviousBlock,
+    SelectNextBlock,
+    SelectFirstBlock,
+    SelectLastBlock,
+    PageBlocksBack,
+    PageBlocksForward,
+    JumpToBlock,
+    ScrollLogsUp,
+    ScrollLogsDown,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) struct Key {
+    code: KeyCode,
+    modifiers: KeyModifiers,
+}
+
+#[derive(Debug, Clone)]
+pub(super) struct KeyBinding {
+    pub(super) action: Action,
+    pub(super) keys: Vec<Key>,
+    pub(super) description: &'static str,
+}
+
+/// Every key binding of the TUI. Key presses are resolved, and the help overlay and key hints are generated, from the keymap only. New bindings are declared in `Keymap::default`
+#[derive(Debug, Clone)]
+pub(crate) struct Keymap {
+    bindings: Vec<KeyBinding>,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        let binding = |action, keys: &[Key], description| KeyBinding {
+            action,
+            keys: keys.to_vec(),
+            description,
+        };
+
+        Self {
+            bindings: vec![
+                binding(
+                    Action::SelectPreviousBlock,
+                    &[Key::plain(KeyCode::Up)],
+                    "Select the previous block",
+                ),
+                binding(
+                    Action::SelectNextBlock,
+                    &[Key::plain(KeyCode::Down)],
+                    "Select the next block",
+                ),
+                binding(
+                    Action::SelectFirstBlock,
+                    &[Key::plain(KeyCode::Char('g')), Key::plain(KeyCode::Home)],
+                    "Select the first block",
+                ),
+                binding(
+                    Action::SelectLastBlock,
+                    &[Key::plain(KeyCode::Char('G')), Key::plain(KeyCode::End)],
+                    "Select the last block",
+                ),
+                binding(
+                    Action::PageBlocksBack,
+                    &[Key::ctrl('b')],
+                    "Scroll the blocks a page up",
+                ),
+                binding(
+                    Action::PageBlocksForward,
+                    &[Key::ctrl('f')],
+                    "Scroll the blocks a page down",
+                ),
+                binding(
+                    Action::JumpToBlock,
+                    &[Key::plain(KeyCode::Char(':'))],
+                    "Jump to a block by its number",
+                ),
+                binding(
+                    Action::ScrollLogsUp,
+                    &[Key::plain(KeyCode::PageUp)],
+                    "Scroll the log up",
+                ),
+                binding(
+                    Action::ScrollLogsDown,
+                    &[Key::plain(KeyCode::PageDown)],
+                    "Scroll the log down",
+                ),
+                binding(
+                    Action::ToggleHelp,
+                    &[Key::plain(KeyCode::Char('?'))],
+                    "Show or hide this help",
+                ),
+                binding(
+                    Action::CloseHelp,
+                    &[Key::plain(KeyCode::Esc)],
+                    "Hide this help",
+                ),
+                binding(
+                    Action::WindDown,
+                    &[Key::plain(KeyCode::Char('w'))],
+                    "Wind down: finish the blocks being solved, start no new ones",
+                ),
+                binding(Action::Quit, &[Key::ctrl('c')], "Quit"),
+            ],
+        }
+    }
+}
+
+impl Keymap {
+    /// The default keymap, with the bindings of the keymap file applied. Without a given file, the one in the user's config directory is used, if any.
+    /// Each line of the file binds an action to keys, replacing its default keys, e.g. `select-next-block = j, Down`. An action without keys is unbound.
+    pub(crate) fn load(keymap_file: Option<&Path>) -> Result<Self> {
+        let mut keymap = Self::default();
+        let keymap_file = match keymap_file {
+            Some(keymap_file) => keymap_file.to_path_buf(),
+            None => match default_keymap_file() {
+                Some(keymap_file) if keymap_file.exists() => keymap_file,
+                _ => return Ok(keymap),
+            },
+        };
+
+        let content = fs::read_to_string(&keymap_file).context(format!(
+            "Keymap file `{}` failed to open",
+            keymap_file.display()
+        ))?;
+        keymap.apply(&content).context(format!(
+            "Keymap file `{}` is invalid",
+            keymap_file.display()
+        ))?;
+
+        Ok(keymap)
+    }
+
+    fn apply(&mut self, content: &str) -> Result<()> {
+        for (line_idx, line) in content.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let (action_name, key_names) = line.split_once('=').ok_or_else(|| {
+                anyhow!(
+                    "Line {}: expected `<action> = <key>[, <key>...]`",
+                    line_idx + 1
+                )
+            })?;
+            let action = Action::from_name(action_name.trim()).ok_or_else(|| {
+                anyhow!(
+                    "Line {}: unknown action `{}`. Actions are: {}",
+                    line_idx + 1,
+                    action_name.trim(),
+                    Action::ALL
+                        .iter()
+                        .map(|action| action.name())
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                )
+            })?;
+            let keys = key_names
+                .split(',')
+                .map(str::trim)
+                .filter(|key_name| !key_name.is_empty())
+                .map(|key_name| {
+                    Key::parse(key_name)
+                        .ok_or_else(|| anyhow!("Line {}: unknown key `{}`", line_idx + 1, key_name))
+                })
+                .collect::<Result<Vec<_>>>()?;
+
+            if let Some(binding) = self
+                .bindings
+                .iter_mut()
+                .find(|binding| binding.action == action)
+            {
+                binding.keys = keys;
+            }
+        }
+
+        self.validate()
+    }
+
+    fn validate(&self) -> Result<()> {
+        // raw mode disables the terminal's own CTRL+C, so this would be the only way out
+        if self.keys_of(Action::Quit).is_empty() {
+            return Err(anyhow!("`{}` needs at least 1 key", Action::Quit.name()));
+        }
+
+        for (idx, binding) in self.bindings.iter().enumerate() {
+            for key in &binding.keys {
+                if let Some(other_binding) = self.bindings[idx + 1..]
+                    .iter()
+                    .find(|other_binding| other_binding.keys.contains(key))
+                {
+                    return Err(anyhow!(
+                        "`{}` is bound to both `{}` and `{}`",
+                        key.name(),
+                        binding.action.name(),
+                        other_binding.action.name()
+                    ));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    pub(super) fn bindings(&self) -> &[KeyBinding] {
+        &self.bindings
+    }
+
+    pub(super) fn action_for(&self, pressed_key: &KeyEvent) -> Option<Action> {
+        self.bindings
+            .iter()
+            .find(|binding| binding.keys.iter().any(|key| key.matches(pressed_key)))
+            .map(|binding| binding.action)
+    }
+
+    fn keys_of(&self, action: Action) -> &[Key] {
+        self.bindings
+            .iter()
+            .find(|binding| binding.action == action)
+            .map(|binding| binding.keys.as_slice())
+            .unwrap_or_default()
+    }
+
+    /// The 1st key of the action, for hints in panel titles. Empty if the action is unbound
+    pub(super) fn hint(&self, action: Action) -> String {
+        self.keys_of(action)
+            .first()
+            .map(Key::name)
+            .unwrap_or_default()
+    }
+}
+
+impl Action {
+    const ALL: [Action; 13] = [
+        Action::Quit,
+        Action::WindDown,
+        Action::ToggleHelp,
+        Action::CloseHelp,
+        Action::SelectPreviousBlock,
+        Action::SelectNextBlock,
+        Action::SelectFirstBlock,
+        Action::SelectLastBlock,
+        Action::PageBlocksBack,
+        Action::PageBlocksForward,
+        Action::JumpToBlock,
+        Action::ScrollLogsUp,
+        Action::ScrollLogsDown,
+    ];
+
+    /// As used in the keymap file
+    fn name(&self) -> &'static str {
+        match self {
+            Action::Quit => "quit",
+            Action::WindDown => "wind-down",
+            Action::ToggleHelp => "toggle-help",
+            Action::CloseHelp => "close-help",
+            Action::SelectPreviousBlock => "select-previous-block",
+            Action::SelectNextBlock => "select-next-block",
+            Action::SelectFirstBlock => "select-first-block",
+            Action::SelectLastBlock => "select-last-block",
+            Action::PageBlocksBack => "page-blocks-back",
+            Action::PageBlocksForward => "page-blocks-forward",
+            Action::JumpToBlock => "jump-to-block",
+            Action::ScrollLogsUp => "scroll-logs-up",
+            Action::ScrollLogsDown => "scroll-logs-down",
+        }
+    }
+
+    fn from_name(name: &str) -> Option<Self> {
+        Action::ALL
+            .into_iter()
+            .find(|action| action.name().eq_ignore_ascii_case(&name.replace('_', "-")))
+    }
+}
+
+impl Key {
+    const fn plain(code: KeyCode) -> Self {
+        Self {
+            code,
+            modifiers: KeyModifiers::NONE,
+        }
+    }
+
+    const fn ctrl(character: char) -> Self {
+        Self {
+            code: KeyCode::Char(character),
+            modifiers: KeyModifiers::CONTROL,
+        }
+    }
+
+    /// A key as written in the keymap file, e.g. `j`, `PgUp` or `Ctrl+d`
+    fn parse(name: &str) -> Option<Self> {
+        let mut modifiers = KeyModifiers::NONE;
+        let mut rest = name;
+        loop {
+            // a lone `+` is the key itself
+            let (modifier, remainder) = match rest.split_once('+') {
+                Some((modifier, remainder)) if !remainder.is_empty() => (modifier, remainder),
+                _ => break,
+            };
+            match modifier.to_lowercase().as_str() {
+                "ctrl" | "control" => modifiers |= KeyModifiers::CONTROL,
+                "alt" => modifiers |= KeyModifiers::ALT,
+                _ => return None,
+            }
+            rest = remainder;
+        }
+
+        let mut characters = rest.chars();
+        let code = match (characters.next(), characters.next()) {
+            (Some(character), None) => KeyCode::Char(character),
+            _ => match rest.to_lowercase().as_str() {
+                "up" => KeyCode::Up,
+                "down" => KeyCode::Down,
+                "left" => KeyCode::Left,
+                "right" => KeyCode::Right,
+                "pageup" | "pgup" => KeyCode::PageUp,
+                "pagedown" | "pgdown" | "pgdwn" => KeyCode::PageDown,
+                "home" => KeyCode::Home,
+                "end" => KeyCode::End,
+                "esc" | "escape" => KeyCode::Esc,
+                "enter" | "return" => KeyCode::Enter,
+                "tab" => KeyCode::Tab,
+                "backspace" => KeyCode::Backspace,
+                "delete" | "del" => KeyCode::Delete,
+                "insert" | "ins" => KeyCode::Insert,
+                "space" => KeyCode::Char(' '),
+                function_key => KeyCode::F(
+                    function_key
+                        .strip_prefix('f')
+                        .and_then(|number| number.parse().ok())?,
+                ),
+            },
+        };
+
+        Some(Self { code, modifiers })
+    }
+
+    fn matches(&self, pressed_key: &KeyEvent) -> bool {
+        // shift is part of the character itself, e.g. `G` or `?`
+        let relevant_modifiers = KeyModifiers::CONTROL | KeyModifiers::ALT;
+        self.code == pressed_key.code
+            && self.modifiers & relevant_modifiers == pressed_key.modifiers & relevant_modifiers
+    }
+
+    /// How the key is shown to users, e.g. `Ctrl+c`
+    pub(super) fn name(&self) -> String {
+        let code = match self.code {
+            KeyCode::Char(' ') => "Space".to_string(),
+            KeyCode::Char(character) => character.to_string(),
+            KeyCode::Up => "🠕".to_string(),
+            KeyCode::Down => "🠗".to_string(),
+            KeyCode::Left => "🠔".to_string(),
+            KeyCode::Right => "🠖".to_string(),
+            KeyCode::PageUp => "PgUp".to_string(),
+            KeyCode::PageDown => "PgDwn".to_string(),
+            KeyCode::F(number) => format!("F{}", number),
+            code => format!("{:?}", code),
+        };
+
+        let mut name = String::new();
+        if self.modifiers.contains(KeyModifiers::CONTROL) {
+            name.push_str("Ctrl+");
+        }
+        if self.modifiers.contains(KeyModifiers::ALT) {
+            name.push_str("Alt+");
+        }
+        name.push_str(&code);
+        name
+    }
+}
+
+impl KeyBinding {
+    /// All keys of the binding, e.g. `g/Home`
+    pub(super) fn key_names(&self) -> String {
+        self.keys
+            .iter()
+            .map(Key::name)
+            .collect::<Vec<_>>()
+            .join("/")
+    }
+}
+
+fn default_keymap_file() -> Option<PathBuf> {
+    dirs::config_dir().map(|dir| dir.join(env!("CARGO_PKG_NAME")).join(KEYMAP_FILE_NAME))
+}
@@ -1,6 +1,10 @@
+use std::cmp::{max, min};
+
 use getset::Getters;
 use tui::layout::{Constraint, Direction, Layout, Rect};
 
+const HELP_WIDTH: u16 = 56;
+
 #[derive(Getters)]
 pub(super) struct TuiLayout {
     // logic panel
@@ -19,11 +23,21 @@ pub(super) struct TuiLayout {
     #[get = "pub(super)"]
     progress_bar_area: Rect,
     #[get = "pub(super)"]
+    latency_area: Rect,
+    #[get = "pub(super)"]
     logs_area: Rect,
+
+    // overlaid on top of everything else
+    #[get = "pub(super)"]
+    help_area: Rect,
 }
 
 impl TuiLayout {
-    pub(super) fn calculate(full_frame_size: Rect, min_width_for_horizontal_layout: u16) -> Self {
+    pub(super) fn calculate(
+        full_frame_size: Rect,
+        min_width_for_horizontal_layout: u16,
+        amount_key_bindings: usize,
+    ) -> Self {
         let main_vertical_layout = Layout::default()
             .direction(Direction::Vertical)
             .margin(1)
@@ -53,7 +67,14 @@ impl TuiLayout {
         let status_panel = Layout::default()
             .direction(Direction::Vertical)
             .margin(1)
-            .constraints([Constraint::Ratio(1, 6), Constraint::Ratio(5, 6)].as_ref())
+            .constraints(
+                [
+                    Constraint::Ratio(1, 6),
+                    Constraint::Ratio(2, 6),
+                    Constraint::Ratio(3, 6),
+                ]
+                .as_ref(),
+            )
             .split(main_vertical_layout[1]);
 
         Self {
@@ -63,7 +84,39 @@ impl TuiLayout {
             plain_text_area: logic_panel[3],
             status_panel_area: main_vertical_layout[1],
             progress_bar_area: status_panel[0],
-            logs_area: status_panel[1],
+            latency_area: status_panel[1],
+            logs_area: status_panel[2],
+            help_area: help_area(full_frame_size, amount_key_bindings),
         }
     }
 }
+
+impl TuiLayout {
+    /// The progress bar, split in a segment per phase. Each segment is as wide as its share of the work
+    pub(super) fn progress_bar_segments(&self, work_per_phase: &[usize]) -> Vec<Rect> {
+        let total_work = max(work_per_phase.iter().sum::<usize>(), 1) as u32;
+        Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints(
+                work_per_phase
+                    .iter()
+                    .map(|work| Constraint::Ratio(*work as u32, total_work))
+                    .collect::<Vec<_>>(),
+            )
+            .split(self.progress_bar_area)
+    }
+}
+
+/// Centered, and as large as the cheat sheet if the frame allows
+fn help_area(full_frame_size: Rect, amount_key_bindings: usize) -> Rect {
+    // a line per binding, the jump prompt's keys, and the borders
+    let height = min(amount_key_bindings as u16 + 4, full_frame_size.height);
+    let width = min(HELP_WIDTH, full_frame_size.width);
+
+    Rect::new(
+        full_frame_size.x + (full_frame_size.width - width) / 2,
+        full_frame_size.y + (full_frame_size.height - height) / 2,
+        width,
+        height,
+    )
+}
@@ -1,33 +1,43 @@
+mod cast;
+pub(super) mod keymap;
 mod layout;
 pub(super) mod ui_event;
+pub(super) mod ui_observer;
 mod widgets;
 
 use std::{
     cmp::{max, min},
+    collections::HashMap,
     io::{self},
+    path::Path,
     process,
     sync::{
         atomic::{AtomicBool, AtomicI32, AtomicU16, AtomicUsize, Ordering},
-        Mutex,
+        Arc, Mutex,
     },
     thread::sleep,
-    time::Duration,
+    time::{Duration, Instant},
 };
 
 use anyhow::{Context, Result};
 use atty::Stream;
 use crossterm::{
     cursor::Show,
-    event::{Event, EventStream, KeyCode, KeyModifiers},
+    event::{Event, EventStream, KeyCode},
     execute,
     terminal::{
-        disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen, SetSize,
+        self, disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen,
+        SetSize,
     },
 };
 use futures::FutureExt;
 use futures_timer::Delay;
 use log::error;
-use tui::{backend::CrosstermBackend, widgets::TableState, Terminal};
+use tui::{
+    backend::CrosstermBackend,
+    widgets::{Clear, TableState},
+    Terminal,
+};
 use tui_logger::{TuiWidgetEvent, TuiWidgetState};
 
 use crate::{
@@ -36,9 +46,13 @@ use crate::{
         Block,
     },
     logging::LOG_TARGET,
+    oracle::request_budget::RequestBudget,
+    timeline::Phase,
 };
 
 use self::{
+    cast::{Cast, TuiOutput},
+    keymap::{Action, Keymap},
     layout::TuiLayout,
     ui_event::{UiControlEvent, UiDecryptionEvent, UiEncryptionEvent, UiEvent},
     widgets::Widgets,
@@ -49,13 +63,19 @@ const INPUT_POLL_MS: u64 = 50;
 
 pub(super) struct Tui {
     // the usage of a mutex here could be prevented by separating `Terminal` from `Tui`, it's only needed in the draw thread. However, the overhead of handling the mutex should be so small (especially given that only the draw thread accesses it) should be so small that it's unneeded.
-    terminal: Mutex<Terminal<CrosstermBackend<io::Stdout>>>,
+    terminal: Mutex<Terminal<CrosstermBackend<TuiOutput>>>,
+    // for `--record-cast`. Told about resizes, as frames are recorded by the terminal's output
+    cast: Option<Arc<Cast>>,
     min_width_for_horizontal_layout: u16,
     cols: AtomicU16,
     rows: AtomicU16,
     // because we enter a "different terminal" during the application's runtime, nothing is left when the user exits the program. This stores a list of messages to print after leaving the "different terminal", but before quitting the application
     print_after_exit: Mutex<Vec<String>>,
+    // replaces `print_after_exit`, and always goes to stdout
+    document_after_exit: Mutex<Option<String>>,
     exit_code: AtomicI32,
+    // of the attack, to wind it down. There's none when replaying
+    request_budget: Option<Arc<RequestBudget>>,
 
     ui_state: UiState,
     app_state: AppState,
@@ -67,27 +87,64 @@ struct UiState {
     redraw: AtomicBool,
 
     log_view_state: Mutex<TuiWidgetState>,
+    // the selected block is an index into all blocks, not just the visible ones
     blocks_view_state: Mutex<TableState>,
+    // only the blocks which fit the tables are rendered. These start at the offset
+    blocks_view_offset: AtomicUsize,
+    // amount of blocks which fit the tables, as of the last frame
+    blocks_view_page_size: AtomicUsize,
+    // block number typed after `:`, while jumping to a block
+    jump_prompt: Mutex<Option<String>>,
+    // the cheat sheet of key bindings is overlaid
+    show_help: AtomicBool,
+    keymap: Keymap,
 }
 
 struct AppState {
-    // for progress calculation
-    bytes_to_finish: AtomicUsize,
-    bytes_finished: AtomicUsize,
+    // encryption starts once the blocks to forge from are decrypted, so each phase has its own progress and ETA
+    decryption_progress: PhaseProgress,
+    encryption_progress: PhaseProgress,
 
     cypher_text_blocks: Mutex<Vec<Block>>,
     forged_blocks: Mutex<Vec<Block>>,
     intermediate_blocks: Mutex<Vec<Block>>,
     plain_text_blocks: Mutex<Vec<Block>>,
+
+    // per block, how long the oracle took to answer each guess for the byte currently being solved
+    guess_latencies: Mutex<HashMap<usize, GuessLatencies>>,
+    // block which was guessed for most recently. Its latencies are shown if no block is selected
+    latest_guessed_block: AtomicUsize,
+}
+
+struct PhaseProgress {
+    bytes_to_finish: AtomicUsize,
+    bytes_finished: AtomicUsize,
+    // when work on the phase started, to estimate its remaining time
+    started: Mutex<Option<Instant>>,
+}
+
+struct GuessLatencies {
+    byte_idx: u8,
+    // indexed by the guessed byte value
+    latencies: Vec<Option<Duration>>,
 }
 
 impl Tui {
-    pub(super) fn new(block_size: &BlockSize) -> Result<Self> {
+    pub(super) fn new(
+        block_size: &BlockSize,
+        keymap: Keymap,
+        request_budget: Option<Arc<RequestBudget>>,
+        cast_file: Option<&Path>,
+    ) -> Result<Self> {
+        let cast = cast_file
+            .map(|path| Cast::create(path, terminal::size()?).map(Arc::new))
+            .transpose()?;
+
         enable_raw_mode()?;
         let mut stdout = io::stdout();
         execute!(stdout, EnterAlternateScreen)?;
 
-        let backend = CrosstermBackend::new(stdout);
+        let backend = CrosstermBackend::new(TuiOutput::new(cast.clone()));
         let mut terminal = Terminal::new(backend)?;
         terminal.clear().context("Clearing terminal failed")?;
         let cols = AtomicU16::new(terminal.size()?.width);
@@ -95,12 +152,15 @@ impl Tui {
 
         let tui = Self {
             terminal: Mutex::new(terminal),
+            cast,
             // enough space to display 2 tables of hex encoded blocks + padding
             min_width_for_horizontal_layout: (**block_size as usize * 12) as u16,
             cols,
             rows,
             print_after_exit: Mutex::new(vec![]),
+            document_after_exit: Mutex::new(None),
             exit_code: AtomicI32::new(0),
+            request_budget,
 
             ui_state: UiState {
                 running: AtomicBool::new(true),
@@ -109,16 +169,24 @@ impl Tui {
 
                 log_view_state: Mutex::new(TuiWidgetState::new()),
                 blocks_view_state: Mutex::new(TableState::default()),
+                blocks_view_offset: AtomicUsize::new(0),
+                blocks_view_page_size: AtomicUsize::new(1),
+                jump_prompt: Mutex::new(None),
+                show_help: AtomicBool::new(false),
+                keymap,
             },
 
             app_state: AppState {
-                bytes_to_finish: AtomicUsize::new(1),
-                bytes_finished: AtomicUsize::new(0),
+                decryption_progress: PhaseProgress::new(),
+                encryption_progress: PhaseProgress::new(),
 
                 cypher_text_blocks: Mutex::new(vec![]),
                 forged_blocks: Mutex::new(vec![]),
                 intermediate_blocks: Mutex::new(vec![]),
                 plain_text_blocks: Mutex::new(vec![]),
+
+                guess_latencies: Mutex::new(HashMap::new()),
+                latest_guessed_block: AtomicUsize::new(0),
             },
         };
 
@@ -142,6 +210,11 @@ impl Tui {
             Show
         );
 
+        if let Some(document) = self.document_after_exit.lock().unwrap().take() {
+            println!("{}", document);
+            process::exit(self.exit_code.load(Ordering::Relaxed));
+        }
+
         // we could separate `self.print_after_exit` into a stdout and a stderr version, but (for now) it's unneeded for our use case
         let use_stderr = self.exit_code.load(Ordering::Relaxed) != 0;
         for message in self.print_after_exit.lock().unwrap().drain(..) {
@@ -324,23 +397,54 @@ impl Tui {
 
     fn handle_control_event(&self, event: UiControlEvent) {
         match event {
-            UiControlEvent::IndicateWork(bytes_to_finish) => {
-                self.app_state
-                    .bytes_to_finish
-                    .store(bytes_to_finish, Ordering::Relaxed);
+            UiControlEvent::IndicateWork(decryption_bytes, encryption_bytes) => {
+                self.app_state.decryption_progress.reset(decryption_bytes);
+                self.app_state.encryption_progress.reset(encryption_bytes);
+                // decryption starts right away, encryption once decryption is done
+                self.app_state.decryption_progress.start();
             }
             // due to concurrency, we can't just send which blocks was finished. So this acts as a "ping" to indicate that a byte was locked
-            UiControlEvent::ProgressUpdate(newly_solved_bytes) => {
+            UiControlEvent::ProgressUpdate(phase, newly_solved_bytes) => {
+                let progress = match phase {
+                    Phase::Decryption => &self.app_state.decryption_progress,
+                    Phase::Encryption => &self.app_state.encryption_progress,
+                };
+                progress.start();
+                progress.advance(newly_solved_bytes);
+
+                if phase == Phase::Decryption && self.app_state.decryption_progress.is_done() {
+                    self.app_state.encryption_progress.start();
+                }
+            }
+            UiControlEvent::GuessLatency(cypher_text_block_idx, byte_idx, byte_value, latency) => {
+                // `try_lock` as updating isn't critical. This is mainly for visuals
+                if let Ok(mut guess_latencies) = self.app_state.guess_latencies.try_lock() {
+                    let block_latencies = guess_latencies
+                        .entry(cypher_text_block_idx)
+                        .or_insert_with(|| GuessLatencies::new(byte_idx));
+                    // moved on to the next byte, so the previous byte's guesses are outdated
+                    if block_latencies.byte_idx != byte_idx {
+                        *block_latencies = GuessLatencies::new(byte_idx);
+                    }
+                    block_latencies.latencies[byte_value as usize] = Some(latency);
+                }
                 self.app_state
-                    .bytes_finished
-                    .fetch_add(newly_solved_bytes, Ordering::Relaxed);
+                    .latest_guessed_block
+                    .store(cypher_text_block_idx, Ordering::Relaxed);
             }
             UiControlEvent::PrintAfterExit(message) => {
                 self.print_after_exit.lock().unwrap().push(message);
             }
+            UiControlEvent::PrintDocumentAfterExit(document) => {
+                *self.document_after_exit.lock().unwrap() = Some(document);
+            }
             UiControlEvent::ExitCode(code) => {
                 self.exit_code.store(code, Ordering::Relaxed);
             }
+            // already logged by the calibrator
+            UiControlEvent::Calibrated(_) => {}
+            // the error is logged once the attack ends
+            UiControlEvent::BlockFailed(..) => {}
             UiControlEvent::SlowRedraw => {
                 // keeping the UI running/application open without a TTY is useless. The user can't read anything anyway
                 if !atty::is(Stream::Stdout) {
@@ -361,13 +465,16 @@ impl Tui {
         // only draw UI if in a TTY. This allows users to redirect output to a file
         if atty::is(Stream::Stdout) {
             self.terminal.lock().unwrap().draw(|frame| {
-                let layout =
-                    TuiLayout::calculate(frame.size(), self.min_width_for_horizontal_layout);
-                let widgets = Widgets::build(&self.app_state, &self.ui_state);
+                let layout = TuiLayout::calculate(
+                    frame.size(),
+                    self.min_width_for_horizontal_layout,
+                    self.ui_state.keymap.bindings().len(),
+                );
+                let widgets = Widgets::build(&self.app_state, &self.ui_state, &layout);
 
                 frame.render_widget(widgets.outer_border, frame.size());
 
-                let mut blocks_view_state = self.ui_state.blocks_view_state.lock().unwrap().clone();
+                let mut blocks_view_state = widgets.blocks_view_state.clone();
                 frame.render_stateful_widget(
                     widgets.original_cypher_text_view,
                     *layout.original_cypher_text_area(),
@@ -390,9 +497,23 @@ impl Tui {
                 );
 
                 frame.render_widget(widgets.status_panel_border, *layout.status_panel_area());
-                frame.render_widget(widgets.progress_bar, *layout.progress_bar_area());
+                let work_per_phase: Vec<_> =
+                    widgets.progress_bar.iter().map(|(_, work)| *work).collect();
+                for ((segment, _), area) in widgets
+                    .progress_bar
+                    .into_iter()
+                    .zip(layout.progress_bar_segments(&work_per_phase))
+                {
+                    frame.render_widget(segment, area);
+                }
+                frame.render_widget(widgets.latency_view, *layout.latency_area());
                 // no `render_stateful_widget` as `TuiLoggerWidget` doesn't implement `StatefulWidget`, but handles it custom
                 frame.render_widget(widgets.logs_view, *layout.logs_area());
+
+                if self.ui_state.show_help.load(Ordering::Relaxed) {
+                    frame.render_widget(Clear, *layout.help_area());
+                    frame.render_widget(widgets.help_view, *layout.help_area());
+                }
             })?;
         }
 
@@ -401,29 +522,68 @@ impl Tui {
 
     fn handle_user_event(&self, event: Event) {
         match event {
-            Event::Key(pressed_key) => {
-                match pressed_key.code {
-                    KeyCode::Char(char_key) => {
+            Event::Key(pressed_key) if self.ui_state.jump_prompt.lock().unwrap().is_some() => {
+                // the prompt isn't locked while selecting, as drawing locks the blocks first
+                let jump_to = {
+                    let mut jump_prompt = self.ui_state.jump_prompt.lock().unwrap();
+                    match pressed_key.code {
+                        KeyCode::Char(digit) if digit.is_ascii_digit() => {
+                            if let Some(block_number) = jump_prompt.as_mut() {
+                                block_number.push(digit);
+                            }
+                            None
+                        }
+                        KeyCode::Backspace => {
+                            if let Some(block_number) = jump_prompt.as_mut() {
+                                block_number.pop();
+                            }
+                            None
+                        }
+                        KeyCode::Enter => jump_prompt
+                            .take()
+                            .and_then(|block_number| block_number.parse().ok()),
+                        KeyCode::Esc => jump_prompt.take().and(None),
                         // re-implement CTRL+C which was disabled by raw-mode
-                        if char_key == 'c' && pressed_key.modifiers == KeyModifiers::CONTROL {
+                        _ if self.ui_state.keymap.action_for(&pressed_key)
+                            == Some(Action::Quit) =>
+                        {
+                            drop(jump_prompt);
                             self.exit();
+                            None
                         }
+                        _ => None,
+                    }
+                };
+                if let Some(block_idx) = jump_to {
+                    self.select_block(block_idx);
+                }
+                self.ui_state.redraw.store(true, Ordering::Relaxed);
+            }
+            Event::Key(pressed_key) => {
+                match self.ui_state.keymap.action_for(&pressed_key) {
+                    // re-implement CTRL+C which was disabled by raw-mode
+                    Some(Action::Quit) => self.exit(),
+                    Some(Action::ToggleHelp) => {
+                        self.ui_state.show_help.fetch_xor(true, Ordering::Relaxed);
+                    }
+                    Some(Action::CloseHelp) => {
+                        self.ui_state.show_help.store(false, Ordering::Relaxed);
                     }
-                    KeyCode::PageUp => {
+                    Some(Action::ScrollLogsUp) => {
                         self.ui_state
                             .log_view_state
                             .lock()
                             .unwrap()
                             .transition(&TuiWidgetEvent::PrevPageKey);
                     }
-                    KeyCode::PageDown => {
+                    Some(Action::ScrollLogsDown) => {
                         self.ui_state
                             .log_view_state
                             .lock()
                             .unwrap()
                             .transition(&TuiWidgetEvent::NextPageKey);
                     }
-                    KeyCode::Up => {
+                    Some(Action::SelectPreviousBlock) => {
                         let mut state = self.ui_state.blocks_view_state.lock().unwrap();
                         let new_selection = state
                             .selected()
@@ -432,7 +592,7 @@ impl Tui {
                             .unwrap_or_default();
                         state.select(Some(new_selection));
                     }
-                    KeyCode::Down => {
+                    Some(Action::SelectNextBlock) => {
                         let mut state = self.ui_state.blocks_view_state.lock().unwrap();
                         let new_selection = state
                             .selected()
@@ -445,10 +605,26 @@ impl Tui {
                             .unwrap_or(1);
                         state.select(Some(new_selection));
                     }
-                    _ => {}
+                    Some(Action::SelectFirstBlock) => self.select_block(0),
+                    Some(Action::SelectLastBlock) => self.select_block(usize::MAX),
+                    Some(Action::PageBlocksBack) => self.scroll_blocks_page(false),
+                    Some(Action::PageBlocksForward) => self.scroll_blocks_page(true),
+                    Some(Action::JumpToBlock) => {
+                        *self.ui_state.jump_prompt.lock().unwrap() = Some(String::new());
+                    }
+                    Some(Action::WindDown) => {
+                        if let Some(request_budget) = &self.request_budget {
+                            request_budget.wind_down();
+                        }
+                    }
+                    None => {}
                 };
+                self.ui_state.redraw.store(true, Ordering::Relaxed);
             }
             Event::Resize(cols, rows) => {
+                if let Some(cast) = &self.cast {
+                    cast.resize(cols, rows);
+                }
                 self.cols.store(cols, Ordering::Relaxed);
                 self.rows.store(rows, Ordering::Relaxed);
                 self.ui_state.redraw.store(true, Ordering::Relaxed);
@@ -456,4 +632,106 @@ impl Tui {
             Event::Mouse(_) => {}
         };
     }
+
+    /// Select the block, or the last one if it doesn't exist
+    fn select_block(&self, block_idx: usize) {
+        let amount_blocks = self.app_state.cypher_text_blocks.lock().unwrap().len();
+        if amount_blocks == 0 {
+            return;
+        }
+
+        self.ui_state
+            .blocks_view_state
+            .lock()
+            .unwrap()
+            .select(Some(min(block_idx, amount_blocks - 1)));
+    }
+
+    /// Move the selection by as many blocks as fit the tables
+    fn scroll_blocks_page(&self, forward: bool) {
+        let page_size = self.ui_state.blocks_view_page_size.load(Ordering::Relaxed);
+        let selected = self
+            .ui_state
+            .blocks_view_state
+            .lock()
+            .unwrap()
+            .selected()
+            .unwrap_or_default();
+
+        self.select_block(if forward {
+            selected.saturating_add(page_size)
+        } else {
+            selected.saturating_sub(page_size)
+        });
+    }
+}
+
+impl PhaseProgress {
+    fn new() -> Self {
+        Self {
+            bytes_to_finish: AtomicUsize::new(0),
+            bytes_finished: AtomicUsize::new(0),
+            started: Mutex::new(None),
+        }
+    }
+
+    fn reset(&self, bytes_to_finish: usize) {
+        self.bytes_to_finish
+            .store(bytes_to_finish, Ordering::Relaxed);
+        self.bytes_finished.store(0, Ordering::Relaxed);
+        *self.started.lock().unwrap() = None;
+    }
+
+    /// Start timing the phase, unless it already started
+    fn start(&self) {
+        self.started
+            .lock()
+            .unwrap()
+            .get_or_insert_with(Instant::now);
+    }
+
+    fn advance(&self, newly_solved_bytes: usize) {
+        self.bytes_finished
+            .fetch_add(newly_solved_bytes, Ordering::Relaxed);
+    }
+
+    fn bytes_to_finish(&self) -> usize {
+        self.bytes_to_finish.load(Ordering::Relaxed)
+    }
+
+    fn is_done(&self) -> bool {
+        self.bytes_finished.load(Ordering::Relaxed) >= self.bytes_to_finish()
+    }
+
+    fn percentage(&self) -> u8 {
+        min(
+            self.bytes_finished.load(Ordering::Relaxed) * 100 / self.bytes_to_finish().max(1),
+            100,
+        ) as u8
+    }
+
+    /// Remaining time of the phase, at the pace of the bytes solved so far
+    fn eta(&self) -> Option<Duration> {
+        let bytes_finished = self.bytes_finished.load(Ordering::Relaxed);
+        let bytes_to_finish = self.bytes_to_finish();
+        let started = (*self.started.lock().unwrap())?;
+        if bytes_finished == 0 || bytes_finished >= bytes_to_finish {
+            return None;
+        }
+
+        Some(
+            started
+                .elapsed()
+                .mul_f64((bytes_to_finish - bytes_finished) as f64 / bytes_finished as f64),
+        )
+    }
+}
+
+impl GuessLatencies {
+    fn new(byte_idx: u8) -> Self {
+        Self {
+            byte_idx,
+            latencies: vec![None; u8::MAX as usize + 1],
+        }
+    }
 }
@@ -1,4 +1,6 @@
-use crate::block::Block;
+use std::time::Duration;
+
+use crate::{block::Block, calibrator::calibration_response::CalibrationResponse, timeline::Phase};
 
 #[derive(Debug)]
 pub(crate) enum UiEvent {
@@ -31,10 +33,20 @@ pub(crate) enum UiEncryptionEvent {
 
 #[derive(Debug)]
 pub(crate) enum UiControlEvent {
-    IndicateWork(usize),
-    ProgressUpdate(usize), // inform UI that x bytes are solved
+    // (decryption_bytes, encryption_bytes). Progress starts from scratch, e.g. for the next segment of the cypher text
+    IndicateWork(usize, usize),
+    // (phase, solved_bytes). Inform UI that x bytes are solved
+    ProgressUpdate(Phase, usize),
+    // (cypher_text_block_idx, byte_idx, byte_value, latency). How long the oracle took to answer a guess
+    GuessLatency(usize, u8, u8, Duration),
     PrintAfterExit(String),
+    // printed on stdout, even if the application fails. Replaces the messages of `PrintAfterExit`, e.g. for a machine-readable result
+    PrintDocumentAfterExit(String),
     ExitCode(i32),
+    // (cypher_text_block_idx, error). Solving the block failed
+    BlockFailed(usize, String),
+    // the web oracle's padding error response, once calibrated
+    Calibrated(CalibrationResponse),
     /// The application is done. Basically indicates that the program should stop running, without actually quitting. This keeps the UI open for users to read the output, while also decreasing the amount of draw calls.
     SlowRedraw,
 }
@@ -0,0 +1,89 @@
+use std::time::Duration;
+
+use crate::{
+    block::Block, cypher_text::forged_cypher_text::ForgedCypherText,
+    divination::progress_observer::ProgressObserver, timeline::Phase,
+};
+
+use super::ui_event::{UiControlEvent, UiDecryptionEvent, UiEncryptionEvent, UiEvent};
+
+/// Shows the progress of an attack in the UI. The UI draws decryption and encryption in separate panels, so the observer is made for either.
+pub(crate) struct UiObserver<U> {
+    update_ui_callback: U,
+    encryption: bool,
+}
+
+impl<U> UiObserver<U>
+where
+    U: FnMut(UiEvent) + Sync + Send + Clone,
+{
+    pub(crate) fn decryption(update_ui_callback: U) -> Self {
+        Self {
+            update_ui_callback,
+            encryption: false,
+        }
+    }
+
+    pub(crate) fn encryption(update_ui_callback: U) -> Self {
+        Self {
+            update_ui_callback,
+            encryption: true,
+        }
+    }
+
+    fn send(&self, event: UiEvent) {
+        (self.update_ui_callback.clone())(event)
+    }
+}
+
+impl<U> ProgressObserver for UiObserver<U>
+where
+    U: FnMut(UiEvent) + Sync + Send + Clone,
+{
+    fn request_issued(
+        &self,
+        block_idx: usize,
+        forged_cypher_text: &ForgedCypherText,
+        latency: Duration,
+    ) {
+        let forged_block = forged_cypher_text.forged_block_wip().clone();
+        self.send(if self.encryption {
+            UiEvent::Encryption(UiEncryptionEvent::BlockWip(forged_block, block_idx))
+        } else {
+            UiEvent::Decryption(UiDecryptionEvent::BlockWip(forged_block, block_idx))
+        });
+        self.send(UiEvent::Control(UiControlEvent::GuessLatency(
+            block_idx,
+            *forged_cypher_text.current_byte_idx(),
+            forged_cypher_text.current_byte_value(),
+            latency,
+        )));
+    }
+
+    fn bytes_solved(&self, _block_idx: usize, amount: usize) {
+        let phase = if self.encryption {
+            Phase::Encryption
+        } else {
+            Phase::Decryption
+        };
+        self.send(UiEvent::Control(UiControlEvent::ProgressUpdate(
+            phase, amount,
+        )));
+    }
+
+    fn block_finished(&self, block_idx: usize, forged_block_solution: &Block) {
+        let forged_block = forged_block_solution.clone();
+        self.send(if self.encryption {
+            UiEvent::Encryption(UiEncryptionEvent::BlockSolved(forged_block, block_idx))
+        } else {
+            UiEvent::Decryption(UiDecryptionEvent::BlockSolved(forged_block, block_idx))
+        });
+    }
+
+    fn block_failed(&self, block_idx: usize, error: &anyhow::Error) {
+        self.send(UiEvent::Control(UiControlEvent::BlockFailed(
+            block_idx,
+            format!("{:#}", error),
+        )));
+    }
+}
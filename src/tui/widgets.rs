@@ -1,15 +1,28 @@
-use std::{cmp::min, sync::atomic::Ordering};
+use std::{
+    cmp::{max, min},
+    ops::Range,
+    sync::atomic::Ordering,
+    time::Duration,
+};
 
 use getset::Getters;
+use humantime::format_duration;
+use itertools::Itertools;
 use tui::{
-    layout::Constraint,
+    layout::{Constraint, Rect},
     style::{Color, Modifier, Style},
-    text::Span,
-    widgets::{Block, Borders, Gauge, Row, Table},
+    text::{Span, Spans},
+    widgets::{Block, Borders, Gauge, Paragraph, Row, Table, TableState},
 };
 use tui_logger::TuiLoggerWidget;
 
-use super::{AppState, UiState};
+use super::{
+    keymap::{Action, KeyBinding, Keymap},
+    layout::TuiLayout,
+    AppState, GuessLatencies, UiState,
+};
+
+const BAR_LEVELS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
 
 #[derive(Getters)]
 pub(super) struct Widgets {
@@ -20,86 +33,279 @@ pub(super) struct Widgets {
     pub(super) forged_block_view: Table<'static>,
     pub(super) intermediate_block_view: Table<'static>,
     pub(super) plain_text_view: Table<'static>,
+    // selection within the visible blocks
+    pub(super) blocks_view_state: TableState,
 
     // status panel
     pub(super) status_panel_border: Block<'static>,
-    pub(super) progress_bar: Gauge<'static>,
+    // a segment per phase with work, along with the phase's amount of work
+    pub(super) progress_bar: Vec<(Gauge<'static>, usize)>,
+    pub(super) latency_view: Paragraph<'static>,
     pub(super) logs_view: TuiLoggerWidget<'static>,
+
+    pub(super) help_view: Paragraph<'static>,
 }
 
 impl Widgets {
-    pub(super) fn build(app_state: &AppState, ui_state: &UiState) -> Widgets {
+    pub(super) fn build(app_state: &AppState, ui_state: &UiState, layout: &TuiLayout) -> Widgets {
         let title_style = Style::default().fg(Color::Cyan);
+        let hex_row = |block: &crate::block::Block, area_width: u16| {
+            let bytes_per_line = hex_bytes_per_line(area_width);
+            Row::new([wrap(&block.to_hex(), bytes_per_line * 2)])
+                .height(row_height(block.len(), bytes_per_line))
+        };
+
+        // not locked while the selection is, as selecting a block locks them the other way around
+        let (amount_blocks, block_len) = {
+            let cypher_text_blocks = app_state.cypher_text_blocks.lock().unwrap();
+            (
+                cypher_text_blocks.len(),
+                cypher_text_blocks
+                    .first()
+                    .map(|block| block.len())
+                    .unwrap_or(1),
+            )
+        };
+        let blocks_window = blocks_window(ui_state, layout, amount_blocks, block_len);
+        let mut blocks_view_state = TableState::default();
+        blocks_view_state.select(
+            ui_state
+                .blocks_view_state
+                .lock()
+                .unwrap()
+                .selected()
+                .map(|block_idx| block_idx - blocks_window.start),
+        );
 
         Widgets {
-            outer_border: build_outer_border(title_style),
+            outer_border: build_outer_border(title_style, &ui_state.keymap),
 
             original_cypher_text_view: build_original_cypher_text_view(
                 title_style,
-                app_state
-                    .cypher_text_blocks
-                    .lock()
-                    .unwrap()
-                    .iter()
-                    .map(|block| Row::new([block.to_hex()]))
-                    .collect(),
+                visible(
+                    &app_state.cypher_text_blocks.lock().unwrap(),
+                    &blocks_window,
+                )
+                .iter()
+                .map(|block| hex_row(block, layout.original_cypher_text_area().width))
+                .collect(),
+                &blocks_window,
+                amount_blocks,
+                ui_state.jump_prompt.lock().unwrap().as_deref(),
+                &ui_state.keymap,
             ),
             forged_block_view: build_forged_block_view(
                 title_style,
-                app_state
-                    .forged_blocks
-                    .lock()
-                    .unwrap()
+                visible(&app_state.forged_blocks.lock().unwrap(), &blocks_window)
                     .iter()
-                    .map(|block| Row::new([block.to_hex()]))
+                    .map(|block| hex_row(block, layout.forged_block_area().width))
                     .collect(),
             ),
             intermediate_block_view: build_intermediate_view(
                 title_style,
-                app_state
-                    .intermediate_blocks
-                    .lock()
-                    .unwrap()
-                    .iter()
-                    .map(|block| Row::new([block.to_hex()]))
-                    .collect(),
+                visible(
+                    &app_state.intermediate_blocks.lock().unwrap(),
+                    &blocks_window,
+                )
+                .iter()
+                .map(|block| hex_row(block, layout.intermediate_block_area().width))
+                .collect(),
             ),
             plain_text_view: build_plain_text_view(
                 title_style,
-                app_state
-                    .plain_text_blocks
-                    .lock()
-                    .unwrap()
+                visible(&app_state.plain_text_blocks.lock().unwrap(), &blocks_window)
                     .iter()
-                    .map(|block| Row::new([block.to_hex(), block.to_ascii()]))
+                    .map(|block| {
+                        let bytes_per_line =
+                            plain_text_bytes_per_line(layout.plain_text_area().width);
+                        Row::new([
+                            wrap(&block.to_hex(), bytes_per_line * 2),
+                            wrap(&block.to_ascii(), bytes_per_line),
+                        ])
+                        .height(row_height(block.len(), bytes_per_line))
+                    })
                     .collect(),
             ),
+            blocks_view_state,
 
             status_panel_border: build_status_panel_border(title_style),
-            progress_bar: build_progress_bar(min(
-                ((app_state.bytes_finished.load(Ordering::Relaxed) as f32
-                    / app_state.bytes_to_finish.load(Ordering::Relaxed) as f32)
-                    * 100.0) as u8,
-                100,
-            )),
+            progress_bar: {
+                let phases = [
+                    ("Decryption", &app_state.decryption_progress),
+                    ("Encryption", &app_state.encryption_progress),
+                ];
+                let with_work = phases
+                    .iter()
+                    .filter(|(_, progress)| progress.bytes_to_finish() > 0)
+                    .count();
+                phases
+                    .iter()
+                    .filter(|(_, progress)| progress.bytes_to_finish() > 0)
+                    // named only to tell the segments apart
+                    .map(|(name, progress)| {
+                        (
+                            build_progress_bar(
+                                (with_work > 1).then_some(*name),
+                                progress.percentage(),
+                                progress.eta(),
+                            ),
+                            progress.bytes_to_finish(),
+                        )
+                    })
+                    .collect()
+            },
+            latency_view: {
+                // the selected block, or else whichever block was guessed for last
+                let block_idx = ui_state
+                    .blocks_view_state
+                    .lock()
+                    .unwrap()
+                    .selected()
+                    .unwrap_or_else(|| app_state.latest_guessed_block.load(Ordering::Relaxed));
+                let guess_latencies = app_state.guess_latencies.lock().unwrap();
+                build_latency_view(
+                    title_style,
+                    block_idx,
+                    guess_latencies.get(&block_idx),
+                    *layout.latency_area(),
+                )
+            },
             logs_view: {
-                let mut log_view = build_log_view(title_style);
+                let mut log_view = build_log_view(title_style, &ui_state.keymap);
                 log_view.state(&ui_state.log_view_state.lock().unwrap());
                 log_view
             },
+
+            help_view: build_help_view(title_style, &ui_state.keymap),
+        }
+    }
+}
+
+/// Blocks which don't fit the width of their panel are wrapped over multiple lines, instead of being cut off
+fn wrap(text: &str, line_width: usize) -> String {
+    text.chars()
+        .chunks(line_width)
+        .into_iter()
+        .map(|line| line.collect::<String>())
+        .join("\n")
+}
+
+fn row_height(block_len: usize, bytes_per_line: usize) -> u16 {
+    block_len.div_ceil(bytes_per_line) as u16
+}
+
+fn hex_bytes_per_line(area_width: u16) -> usize {
+    // border takes up a column on each side. Hex encoding takes 2 columns per byte
+    max(1, area_width.saturating_sub(2) as usize / 2)
+}
+
+fn plain_text_bytes_per_line(area_width: u16) -> usize {
+    // columns take up 2/3 and 1/3 of the space left by the border and column spacing
+    let inner_width = area_width.saturating_sub(3) as usize;
+    max(1, min(inner_width * 2 / 3 / 2, inner_width / 3))
+}
+
+/// The blocks of the window. Lists of blocks are replaced one by one when (en/de)cryption starts, so they may briefly differ in length
+fn visible<'a>(
+    blocks: &'a [crate::block::Block],
+    blocks_window: &Range<usize>,
+) -> &'a [crate::block::Block] {
+    blocks.get(blocks_window.clone()).unwrap_or(&[])
+}
+
+/// Blocks which fit the tables, keeping the selected block in view. Only these are rendered, so large cypher texts don't slow down every frame
+fn blocks_window(
+    ui_state: &UiState,
+    layout: &TuiLayout,
+    amount_blocks: usize,
+    block_len: usize,
+) -> Range<usize> {
+    let fitting_blocks = |area: &Rect, bytes_per_line: usize| {
+        // border takes up a line on the top and bottom
+        area.height.saturating_sub(2) as usize / row_height(block_len, bytes_per_line) as usize
+    };
+    let page_size = [
+        fitting_blocks(
+            layout.original_cypher_text_area(),
+            hex_bytes_per_line(layout.original_cypher_text_area().width),
+        ),
+        fitting_blocks(
+            layout.forged_block_area(),
+            hex_bytes_per_line(layout.forged_block_area().width),
+        ),
+        fitting_blocks(
+            layout.intermediate_block_area(),
+            hex_bytes_per_line(layout.intermediate_block_area().width),
+        ),
+        fitting_blocks(
+            layout.plain_text_area(),
+            plain_text_bytes_per_line(layout.plain_text_area().width),
+        ),
+    ]
+    .into_iter()
+    .min()
+    .unwrap_or_default()
+    .max(1);
+    ui_state
+        .blocks_view_page_size
+        .store(page_size, Ordering::Relaxed);
+
+    let mut offset = ui_state.blocks_view_offset.load(Ordering::Relaxed);
+    if let Some(selected) = ui_state.blocks_view_state.lock().unwrap().selected() {
+        if selected < offset {
+            offset = selected;
+        } else if selected >= offset + page_size {
+            offset = selected + 1 - page_size;
         }
     }
+    // don't leave empty space at the bottom after the terminal grew
+    offset = min(offset, amount_blocks.saturating_sub(page_size));
+    ui_state.blocks_view_offset.store(offset, Ordering::Relaxed);
+
+    offset..min(offset + page_size, amount_blocks)
 }
 
-fn build_outer_border(title_style: Style) -> Block<'static> {
+fn build_outer_border(title_style: Style, keymap: &Keymap) -> Block<'static> {
     Block::default()
-        .title(Span::styled("rustpad", title_style))
+        .title(vec![
+            Span::styled("rustpad ", title_style),
+            Span::styled(
+                format!("[{} help]", keymap.hint(Action::ToggleHelp)),
+                Style::default().add_modifier(Modifier::DIM),
+            ),
+        ])
         .borders(Borders::NONE)
 }
 
-fn build_original_cypher_text_view(title_style: Style, rows: Vec<Row>) -> Table {
+fn build_original_cypher_text_view<'a>(
+    title_style: Style,
+    rows: Vec<Row<'a>>,
+    blocks_window: &Range<usize>,
+    amount_blocks: usize,
+    jump_prompt: Option<&str>,
+    keymap: &Keymap,
+) -> Table<'a> {
     let title = Span::styled("Cypher text ", title_style);
-    let key_indicator = Span::styled("[🠕/🠗]", Style::default().add_modifier(Modifier::DIM));
+    let key_indicator = match jump_prompt {
+        Some(block_number) => Span::styled(
+            format!("[jump to block: {}_]", block_number),
+            Style::default().fg(Color::Yellow),
+        ),
+        None => Span::styled(
+            format!(
+                "[{}/{} {}/{} {}] {}-{}/{}",
+                keymap.hint(Action::SelectPreviousBlock),
+                keymap.hint(Action::SelectNextBlock),
+                keymap.hint(Action::SelectFirstBlock),
+                keymap.hint(Action::SelectLastBlock),
+                keymap.hint(Action::JumpToBlock),
+                blocks_window.start,
+                blocks_window.end.saturating_sub(1),
+                amount_blocks.saturating_sub(1)
+            ),
+            Style::default().add_modifier(Modifier::DIM),
+        ),
+    };
 
     Table::new(rows)
         .block(
@@ -147,20 +353,134 @@ fn build_status_panel_border(title_style: Style) -> Block<'static> {
         .borders(Borders::ALL)
 }
 
-fn build_progress_bar(progress: u8) -> Gauge<'static> {
+fn build_progress_bar(phase: Option<&str>, progress: u8, eta: Option<Duration>) -> Gauge<'static> {
+    let mut label = format!("{}%", progress);
+    if let Some(phase) = phase {
+        label = format!("{} {}", phase, label);
+    }
+    if let Some(eta) = eta {
+        label = format!(
+            "{}, ETA {}",
+            label,
+            format_duration(Duration::from_secs(eta.as_secs()))
+        );
+    }
+
     Gauge::default()
         .gauge_style(Style::default().fg(Color::LightCyan))
         .percent(progress as u16)
-        .label(Span::styled(
-            format!("{}%", progress),
-            Style::default().fg(Color::DarkGray),
-        ))
+        .label(Span::styled(label, Style::default().fg(Color::DarkGray)))
         .use_unicode(true)
 }
 
-fn build_log_view(title_style: Style) -> TuiLoggerWidget<'static> {
+/// Latency of each guess for the byte currently being solved, as a bar per guessed value. Guesses which the oracle answered notably slower or faster might indicate a timing side channel
+fn build_latency_view(
+    title_style: Style,
+    block_idx: usize,
+    guess_latencies: Option<&GuessLatencies>,
+    area: Rect,
+) -> Paragraph<'static> {
+    let title = Span::styled("Guess latency ", title_style);
+    let block = Block::default().borders(Borders::NONE);
+
+    let guess_latencies = match guess_latencies {
+        Some(guess_latencies) => guess_latencies,
+        None => {
+            return Paragraph::new(Spans::from(vec![
+                title,
+                Span::styled(
+                    format!("(block {}: no guesses yet)", block_idx + 1),
+                    Style::default().add_modifier(Modifier::DIM),
+                ),
+            ]))
+            .block(block)
+        }
+    };
+
+    let latencies: Vec<(usize, Duration)> = guess_latencies
+        .latencies
+        .iter()
+        .enumerate()
+        .filter_map(|(value, latency)| latency.map(|latency| (value, latency)))
+        .collect();
+    let sorted: Vec<Duration> = latencies
+        .iter()
+        .map(|(_, latency)| *latency)
+        .sorted()
+        .collect();
+    let (slowest_value, slowest) = latencies
+        .iter()
+        .max_by_key(|(_, latency)| *latency)
+        .cloned()
+        .unwrap_or_default();
+    let fastest = sorted.first().cloned().unwrap_or_default();
+    let median = sorted.get(sorted.len() / 2).cloned().unwrap_or_default();
+
+    let summary = Span::styled(
+        format!(
+            "(block {}, byte {}: {} guesses, min {:.1?}, median {:.1?}, max {:.1?} for 0x{:02x})",
+            block_idx + 1,
+            guess_latencies.byte_idx + 1,
+            latencies.len(),
+            fastest,
+            median,
+            slowest,
+            slowest_value
+        ),
+        Style::default().add_modifier(Modifier::DIM),
+    );
+
+    // 1 line is taken by the title
+    let height = max(1, area.height.saturating_sub(1) as usize);
+    let width = max(1, area.width as usize);
+    // the guesses don't fit the width of most terminals, so neighbouring values share a bar, showing their slowest guess
+    let guesses_per_bar = max(1, (guess_latencies.latencies.len()).div_ceil(width));
+    let span = max(1, (slowest - fastest).as_micros());
+    let bar_levels: Vec<usize> = guess_latencies
+        .latencies
+        .chunks(guesses_per_bar)
+        .map(|chunk| match chunk.iter().flatten().max() {
+            // always show answered guesses, even the fastest one
+            Some(latency) => {
+                1 + ((*latency - fastest).as_micros() * (height * 8 - 1) as u128 / span) as usize
+            }
+            None => 0,
+        })
+        .collect();
+
+    let lines: Vec<Spans> = (0..height)
+        .rev()
+        .map(|line| {
+            let bars: String = bar_levels
+                .iter()
+                .map(|level| match level.saturating_sub(line * 8) {
+                    0 => ' ',
+                    level => BAR_LEVELS[min(level, 8) - 1],
+                })
+                .collect();
+            Spans::from(Span::styled(bars, Style::default().fg(Color::LightCyan)))
+        })
+        .collect();
+
+    Paragraph::new(
+        [Spans::from(vec![title, summary])]
+            .into_iter()
+            .chain(lines)
+            .collect::<Vec<_>>(),
+    )
+    .block(block)
+}
+
+fn build_log_view(title_style: Style, keymap: &Keymap) -> TuiLoggerWidget<'static> {
     let title = Span::styled("Log ", title_style);
-    let key_indicator = Span::styled("[PgUp/PgDwn]", Style::default().add_modifier(Modifier::DIM));
+    let key_indicator = Span::styled(
+        format!(
+            "[{}/{}]",
+            keymap.hint(Action::ScrollLogsUp),
+            keymap.hint(Action::ScrollLogsDown)
+        ),
+        Style::default().add_modifier(Modifier::DIM),
+    );
 
     TuiLoggerWidget::default()
         .block(
@@ -174,3 +494,45 @@ fn build_log_view(title_style: Style) -> TuiLoggerWidget<'static> {
         .style_debug(Style::default().fg(Color::LightGreen))
         .style_trace(Style::default().fg(Color::White))
 }
+
+/// Cheat sheet of all key bindings, generated from the keymap
+fn build_help_view(title_style: Style, keymap: &Keymap) -> Paragraph<'static> {
+    let key_names: Vec<String> = keymap
+        .bindings()
+        .iter()
+        .map(KeyBinding::key_names)
+        .collect();
+    let key_width = key_names
+        .iter()
+        .map(|name| name.chars().count())
+        .max()
+        .unwrap_or(0);
+
+    let lines = keymap
+        .bindings()
+        .iter()
+        .zip(key_names)
+        .map(|(binding, key_name)| {
+            Spans::from(vec![
+                Span::styled(
+                    format!(" {:<width$}  ", key_name, width = key_width),
+                    Style::default().fg(Color::Yellow),
+                ),
+                Span::raw(binding.description),
+            ])
+        })
+        .chain([
+            Spans::from(""),
+            Spans::from(Span::styled(
+                " Jump prompt: 0-9, Backspace, Enter, Esc",
+                Style::default().add_modifier(Modifier::DIM),
+            )),
+        ])
+        .collect::<Vec<_>>();
+
+    Paragraph::new(lines).block(
+        Block::default()
+            .title(Span::styled("Keys", title_style))
+            .borders(Borders::ALL),
+    )
+}
@@ -0,0 +1,169 @@
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use humantime::format_duration;
+use log::warn;
+use reqwest::{header::CONTENT_TYPE, Client, Url};
+use serde::Serialize;
+use tokio::runtime::{self, Runtime};
+
+use crate::{
+    config::Config,
+    json_result::JsonResult,
+    logging::LOG_TARGET,
+    tui::ui_event::{UiControlEvent, UiEvent},
+};
+
+// a slow webhook shouldn't keep the attack, or the application, from finishing
+const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(10);
+// Discord rejects longer messages
+const MAX_TEXT_LENGTH: usize = 2000;
+
+/// POSTs a summary of the attack to a webhook, for `--notify-webhook`. The result is built from the events sent to the UI, as the JSON result is.
+pub(super) struct Webhook {
+    url: Url,
+    web_client: Client,
+    runtime: Runtime,
+    result: JsonResult,
+}
+
+#[derive(Serialize)]
+struct Notification {
+    // human readable. Slack and Mattermost show `text`, Discord shows `content`
+    text: String,
+    content: String,
+    #[serde(flatten)]
+    kind: NotificationKind,
+}
+
+#[derive(Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+enum NotificationKind {
+    BlockFailed {
+        block: usize,
+        error: String,
+    },
+    Finished {
+        success: bool,
+        exit_code: i32,
+        duration_s: u64,
+        requests: u64,
+        // per decrypted segment, lossy UTF-8
+        plain_text: Vec<String>,
+        // the result lines, as printed
+        output: Vec<String>,
+    },
+}
+
+impl Webhook {
+    pub(super) fn new(config: &Config, url: &Url) -> Result<Self> {
+        let web_client = Client::builder()
+            .timeout(WEBHOOK_TIMEOUT)
+            .build()
+            .context("Webhook client setup failed")?;
+        let runtime = runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .context("Async runtime setup failed")?;
+
+        Ok(Self {
+            url: url.clone(),
+            web_client,
+            runtime,
+            result: JsonResult::new(config),
+        })
+    }
+
+    /// Notify about failed blocks right away, as the rest of the attack may take hours
+    pub(super) fn record_ui_event(&self, event: &UiEvent) {
+        if let UiEvent::Control(UiControlEvent::BlockFailed(block_idx, error)) = event {
+            self.notify(NotificationKind::BlockFailed {
+                block: *block_idx,
+                error: error.clone(),
+            });
+        }
+        self.result.record_ui_event(event);
+    }
+
+    /// Notify about the outcome of the attack
+    pub(super) fn finished(&self) {
+        let document = self.result.document();
+        self.notify(NotificationKind::Finished {
+            success: document.exit_code == 0,
+            exit_code: document.exit_code,
+            duration_s: Duration::from_millis(document.duration_ms as u64).as_secs(),
+            requests: document.requests,
+            plain_text: document
+                .decryption
+                .into_iter()
+                .map(|decryption| decryption.plain_text)
+                .collect(),
+            output: document.output,
+        });
+    }
+
+    /// Failing to notify is only logged, as it's secondary to the attack
+    fn notify(&self, kind: NotificationKind) {
+        let text = kind.text();
+        let notification = Notification {
+            content: text.clone(),
+            text,
+            kind,
+        };
+
+        let sent = serde_json::to_string(&notification)
+            .context("Serialising the notification failed")
+            .and_then(|body| {
+                self.runtime.block_on(async {
+                    self.web_client
+                        .post(self.url.clone())
+                        .header(CONTENT_TYPE, "application/json")
+                        .body(body)
+                        .send()
+                        .await
+                        .and_then(|response| response.error_for_status())
+                        .context("Notifying the webhook failed")
+                })
+            });
+        if let Err(e) = sent {
+            warn!(target: LOG_TARGET, "{:?}", e);
+        }
+    }
+}
+
+impl NotificationKind {
+    fn text(&self) -> String {
+        let text = match self {
+            NotificationKind::BlockFailed { block, error } => format!(
+                "{}: block {} failed: {}",
+                env!("CARGO_PKG_NAME"),
+                block,
+                error
+            ),
+            NotificationKind::Finished {
+                success,
+                exit_code,
+                duration_s,
+                requests,
+                output,
+                ..
+            } => {
+                let mut lines = vec![format!(
+                    "{}: attack {} after {} with {} requests (exit code {})",
+                    env!("CARGO_PKG_NAME"),
+                    if *success { "succeeded" } else { "failed" },
+                    format_duration(Duration::from_secs(*duration_s)),
+                    requests,
+                    exit_code
+                )];
+                lines.extend(output.iter().cloned());
+                lines.join("\n")
+            }
+        };
+
+        match text.char_indices().nth(MAX_TEXT_LENGTH - 1) {
+            Some((cut_at, _)) => format!("{}\u{2026}", &text[..cut_at]),
+            None => text,
+        }
+    }
+}